@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE events DROP COLUMN updated_at;
+ALTER TABLE events DROP COLUMN created_at;
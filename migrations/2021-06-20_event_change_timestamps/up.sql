@@ -0,0 +1,6 @@
+ALTER TABLE events ADD COLUMN created_at INTEGER;
+ALTER TABLE events ADD COLUMN updated_at INTEGER;
+-- The creation time of existing events is unknown: They
+-- have been created before they start at the latest.
+UPDATE events SET created_at = MIN(start, CAST(strftime('%s', 'now') AS INTEGER))
+WHERE created_at IS NULL;
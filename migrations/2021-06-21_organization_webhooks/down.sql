@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE organization_webhook_delivery;
+DROP TABLE organization_webhook;
@@ -0,0 +1,37 @@
+CREATE TABLE organization_webhook (
+    rowid       INTEGER PRIMARY KEY NOT NULL,
+    --
+    org_rowid   INTEGER NOT NULL,
+    id          TEXT NOT NULL,
+    --
+    url         TEXT NOT NULL,
+    secret      TEXT NOT NULL,
+    event_types TEXT NOT NULL, -- comma-separated list
+    created_at  INTEGER NOT NULL,
+    --
+    UNIQUE (id),
+    FOREIGN KEY (org_rowid) REFERENCES organization(rowid)
+);
+
+-- Outbox with the delivery history of all webhooks
+CREATE TABLE organization_webhook_delivery (
+    rowid         INTEGER PRIMARY KEY NOT NULL,
+    --
+    webhook_rowid INTEGER NOT NULL,
+    id            TEXT NOT NULL,
+    --
+    event_type    TEXT NOT NULL,
+    payload       TEXT NOT NULL,
+    created_at    INTEGER NOT NULL,
+    attempts      INTEGER NOT NULL,
+    next_attempt_at      INTEGER, -- NULL if either delivered or failed permanently
+    last_attempt_at      INTEGER,
+    last_response_status INTEGER,
+    last_error           TEXT,
+    delivered_at         INTEGER,
+    --
+    UNIQUE (id),
+    FOREIGN KEY (webhook_rowid) REFERENCES organization_webhook(rowid)
+);
+
+CREATE INDEX organization_webhook_delivery_idx_next_attempt_at ON organization_webhook_delivery(next_attempt_at);
@@ -0,0 +1,19 @@
+-- Persistent log of all committed changes for resuming
+-- the change stream. AUTOINCREMENT prevents that sequence
+-- numbers are reused after outdated entries have been
+-- deleted.
+CREATE TABLE change_log (
+    rowid       INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    --
+    created_at  INTEGER NOT NULL,
+    entity_kind TEXT NOT NULL,
+    entity_id   TEXT NOT NULL,
+    place_id    TEXT, -- only for ratings
+    change_type TEXT NOT NULL,
+    title       TEXT NOT NULL,
+    lat         REAL,
+    lng         REAL,
+    tags        TEXT NOT NULL -- comma-separated list
+);
+
+CREATE INDEX change_log_idx_created_at ON change_log(created_at);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE user_refresh_token;
+DROP TABLE revoked_access_token;
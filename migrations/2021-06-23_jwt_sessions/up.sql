@@ -0,0 +1,22 @@
+-- Access tokens that have been revoked before their expiration,
+-- e.g. on logout. Entries can be deleted after they expired.
+CREATE TABLE revoked_access_token (
+    jti        TEXT PRIMARY KEY NOT NULL,
+    expires_at INTEGER NOT NULL
+);
+
+CREATE TABLE user_refresh_token (
+    rowid      INTEGER PRIMARY KEY NOT NULL,
+    user_rowid INTEGER NOT NULL,
+    --
+    session_id TEXT NOT NULL,
+    token_hash TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL,
+    used_at    INTEGER,
+    --
+    UNIQUE (token_hash),
+    FOREIGN KEY (user_rowid) REFERENCES users(id)
+);
+
+CREATE INDEX user_refresh_token_idx_session_id ON user_refresh_token(session_id);
@@ -0,0 +1,19 @@
+-- Additional named API tokens of an organization with
+-- limited permissions. The primary token in the table
+-- organization still grants all permissions.
+CREATE TABLE organization_api_token (
+    rowid        INTEGER PRIMARY KEY NOT NULL,
+    org_rowid    INTEGER NOT NULL,
+    --
+    id           TEXT NOT NULL,
+    name         TEXT NOT NULL,
+    token_hash   TEXT NOT NULL,
+    scopes       TEXT NOT NULL, -- comma-separated list
+    created_at   INTEGER NOT NULL,
+    expires_at   INTEGER,
+    last_used_at INTEGER,
+    --
+    UNIQUE (id),
+    UNIQUE (token_hash),
+    FOREIGN KEY (org_rowid) REFERENCES organization(rowid)
+);
@@ -0,0 +1,11 @@
+-- Users who are allowed to act on behalf of an
+-- organization according to their role.
+CREATE TABLE organization_member (
+    org_rowid  INTEGER NOT NULL,
+    user_rowid INTEGER NOT NULL,
+    role       TEXT NOT NULL,
+    --
+    PRIMARY KEY (org_rowid, user_rowid),
+    FOREIGN KEY (org_rowid) REFERENCES organization(rowid),
+    FOREIGN KEY (user_rowid) REFERENCES users(id)
+);
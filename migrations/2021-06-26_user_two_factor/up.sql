@@ -0,0 +1,21 @@
+CREATE TABLE user_totp (
+    user_rowid     INTEGER PRIMARY KEY NOT NULL,
+    --
+    secret         TEXT NOT NULL,
+    created_at     INTEGER NOT NULL,
+    confirmed_at   INTEGER,
+    last_used_step INTEGER,
+    --
+    FOREIGN KEY (user_rowid) REFERENCES users(id)
+);
+
+CREATE TABLE user_recovery_code (
+    rowid      INTEGER PRIMARY KEY NOT NULL,
+    user_rowid INTEGER NOT NULL,
+    --
+    code_hash  TEXT NOT NULL,
+    used_at    INTEGER,
+    --
+    UNIQUE (user_rowid, code_hash),
+    FOREIGN KEY (user_rowid) REFERENCES users(id)
+);
@@ -0,0 +1,12 @@
+CREATE TABLE failed_login (
+    -- 'account' or 'client'
+    kind           TEXT NOT NULL,
+    -- email address or IP address
+    subject        TEXT NOT NULL,
+    --
+    count          INTEGER NOT NULL,
+    last_failed_at INTEGER NOT NULL,
+    locked_until   INTEGER,
+    --
+    PRIMARY KEY (kind, subject)
+);
@@ -0,0 +1,16 @@
+ALTER TABLE users ADD COLUMN disabled BOOLEAN NOT NULL DEFAULT 0;
+
+-- Users are referenced by their email address instead of
+-- their rowid to keep the entries after a user has been
+-- deleted.
+CREATE TABLE admin_audit_log (
+    rowid        INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    --
+    created_at   INTEGER NOT NULL,
+    actor_email  TEXT NOT NULL,
+    action       TEXT NOT NULL,
+    target_email TEXT NOT NULL,
+    details      TEXT
+);
+
+CREATE INDEX admin_audit_log_idx_target_email ON admin_audit_log(target_email);
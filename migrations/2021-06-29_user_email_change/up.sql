@@ -0,0 +1,10 @@
+-- At most one pending change of the email address per user
+CREATE TABLE user_email_change (
+    user_rowid INTEGER PRIMARY KEY NOT NULL,
+    --
+    new_email  TEXT NOT NULL,
+    nonce      TEXT NOT NULL,
+    expires_at INTEGER NOT NULL,
+    --
+    FOREIGN KEY (user_rowid) REFERENCES users(id)
+);
@@ -0,0 +1,19 @@
+-- This file should undo anything in `up.sql`
+CREATE TABLE bbox_subscriptions_old (
+    id              INTEGER PRIMARY KEY NOT NULL,
+    uid             TEXT NOT NULL,
+    user_id         INTEGER NOT NULL,
+    south_west_lat  FLOAT NOT NULL,
+    south_west_lng  FLOAT NOT NULL,
+    north_east_lat  FLOAT NOT NULL,
+    north_east_lng  FLOAT NOT NULL,
+    UNIQUE(uid),
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+INSERT INTO bbox_subscriptions_old
+SELECT id, uid, user_id, south_west_lat, south_west_lng, north_east_lat, north_east_lng
+FROM bbox_subscriptions;
+
+DROP TABLE bbox_subscriptions;
+ALTER TABLE bbox_subscriptions_old RENAME TO bbox_subscriptions;
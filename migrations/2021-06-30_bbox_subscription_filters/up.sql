@@ -0,0 +1,8 @@
+ALTER TABLE bbox_subscriptions ADD COLUMN name TEXT NOT NULL DEFAULT '';
+-- Empty lists and missing values don't restrict the changes
+ALTER TABLE bbox_subscriptions ADD COLUMN filter_tags TEXT NOT NULL DEFAULT ''; -- comma-separated list
+ALTER TABLE bbox_subscriptions ADD COLUMN filter_categories TEXT NOT NULL DEFAULT ''; -- comma-separated list
+ALTER TABLE bbox_subscriptions ADD COLUMN filter_entity_kind TEXT;
+ALTER TABLE bbox_subscriptions ADD COLUMN filter_change_types TEXT NOT NULL DEFAULT ''; -- comma-separated list
+-- Existing subscriptions keep receiving only the changes they received before
+UPDATE bbox_subscriptions SET filter_change_types='created,updated';
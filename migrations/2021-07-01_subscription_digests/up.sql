@@ -0,0 +1,17 @@
+ALTER TABLE bbox_subscriptions ADD COLUMN delivery_mode TEXT NOT NULL DEFAULT 'immediate';
+
+-- Changes that are delivered with the next digest
+CREATE TABLE subscription_notification_queue (
+    rowid         INTEGER PRIMARY KEY NOT NULL,
+    user_rowid    INTEGER NOT NULL,
+    delivery_mode TEXT NOT NULL,
+    created_at    INTEGER NOT NULL,
+    entity_kind   TEXT NOT NULL,
+    change_type   TEXT NOT NULL,
+    entity_id     TEXT NOT NULL,
+    title         TEXT NOT NULL,
+    --
+    FOREIGN KEY (user_rowid) REFERENCES users(id)
+);
+
+CREATE INDEX subscription_notification_queue_idx_user_rowid ON subscription_notification_queue(user_rowid);
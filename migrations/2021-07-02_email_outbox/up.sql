@@ -0,0 +1,19 @@
+-- Outbox with the delivery history of all emails
+CREATE TABLE email_outbox (
+    rowid           INTEGER PRIMARY KEY NOT NULL,
+    id              TEXT NOT NULL,
+    --
+    recipient       TEXT NOT NULL,
+    subject         TEXT NOT NULL,
+    body            TEXT NOT NULL,
+    created_at      INTEGER NOT NULL,
+    attempts        INTEGER NOT NULL,
+    next_attempt_at INTEGER, -- NULL if either sent or failed permanently
+    last_attempt_at INTEGER,
+    last_error      TEXT,
+    sent_at         INTEGER,
+    --
+    UNIQUE (id)
+);
+
+CREATE INDEX email_outbox_idx_next_attempt_at ON email_outbox(next_attempt_at);
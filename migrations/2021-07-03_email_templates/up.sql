@@ -0,0 +1,4 @@
+-- ISO 639-1 code, NULL for the default language
+ALTER TABLE users ADD COLUMN language TEXT;
+
+ALTER TABLE email_outbox ADD COLUMN html_body TEXT;
@@ -0,0 +1,49 @@
+-- This file should undo anything in `up.sql`
+CREATE TABLE subscription_notification_queue_old (
+    rowid         INTEGER PRIMARY KEY NOT NULL,
+    user_rowid    INTEGER NOT NULL,
+    delivery_mode TEXT NOT NULL,
+    created_at    INTEGER NOT NULL,
+    entity_kind   TEXT NOT NULL,
+    change_type   TEXT NOT NULL,
+    entity_id     TEXT NOT NULL,
+    title         TEXT NOT NULL,
+    --
+    FOREIGN KEY (user_rowid) REFERENCES users(id)
+);
+
+INSERT INTO subscription_notification_queue_old
+SELECT rowid, user_rowid, delivery_mode, created_at, entity_kind, change_type, entity_id, title
+FROM subscription_notification_queue;
+
+DROP TABLE subscription_notification_queue;
+ALTER TABLE subscription_notification_queue_old RENAME TO subscription_notification_queue;
+
+CREATE INDEX subscription_notification_queue_idx_user_rowid ON subscription_notification_queue(user_rowid);
+
+CREATE TABLE email_outbox_old (
+    rowid           INTEGER PRIMARY KEY NOT NULL,
+    id              TEXT NOT NULL,
+    --
+    recipient       TEXT NOT NULL,
+    subject         TEXT NOT NULL,
+    body            TEXT NOT NULL,
+    created_at      INTEGER NOT NULL,
+    attempts        INTEGER NOT NULL,
+    next_attempt_at INTEGER, -- NULL if either sent or failed permanently
+    last_attempt_at INTEGER,
+    last_error      TEXT,
+    sent_at         INTEGER,
+    html_body       TEXT,
+    --
+    UNIQUE (id)
+);
+
+INSERT INTO email_outbox_old
+SELECT rowid, id, recipient, subject, body, created_at, attempts, next_attempt_at, last_attempt_at, last_error, sent_at, html_body
+FROM email_outbox;
+
+DROP TABLE email_outbox;
+ALTER TABLE email_outbox_old RENAME TO email_outbox;
+
+CREATE INDEX email_outbox_idx_next_attempt_at ON email_outbox(next_attempt_at);
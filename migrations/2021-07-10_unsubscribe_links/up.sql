@@ -0,0 +1,5 @@
+-- The uid of the matching subscription, NULL for notifications
+-- that have been queued before
+ALTER TABLE subscription_notification_queue ADD COLUMN subscription_uid TEXT;
+
+ALTER TABLE email_outbox ADD COLUMN unsubscribe_url TEXT;
@@ -0,0 +1,9 @@
+CREATE TABLE user_author_notifications (
+    user_rowid INTEGER PRIMARY KEY NOT NULL,
+    --
+    reviews    BOOLEAN NOT NULL,
+    ratings    BOOLEAN NOT NULL,
+    clearances BOOLEAN NOT NULL,
+    --
+    FOREIGN KEY (user_rowid) REFERENCES users(id)
+);
@@ -0,0 +1,15 @@
+CREATE TABLE organization_clearance_notification (
+    org_rowid      INTEGER PRIMARY KEY NOT NULL,
+    --
+    -- creation time of the latest pending clearance that
+    -- has been announced to the contacts of the organization
+    notified_until INTEGER NOT NULL,
+    --
+    FOREIGN KEY (org_rowid) REFERENCES organization(rowid)
+);
+
+-- Only pending clearances that are created after the deployment
+-- are announced to the contacts of existing organizations
+INSERT INTO organization_clearance_notification (org_rowid, notified_until)
+SELECT rowid, CAST(strftime('%s', 'now') AS INTEGER) * 1000
+FROM organization;
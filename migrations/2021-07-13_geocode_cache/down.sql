@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX geocode_cache_idx_created_at;
+DROP TABLE geocode_cache;
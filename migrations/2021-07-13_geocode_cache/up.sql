@@ -0,0 +1,11 @@
+CREATE TABLE geocode_cache (
+    -- normalized address
+    address    TEXT PRIMARY KEY NOT NULL,
+    --
+    -- NULL if the address could not be resolved
+    lat        REAL,
+    lng        REAL,
+    created_at INTEGER NOT NULL
+);
+
+CREATE INDEX geocode_cache_idx_created_at ON geocode_cache(created_at);
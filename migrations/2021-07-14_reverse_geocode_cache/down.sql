@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX reverse_geocode_cache_idx_created_at;
+DROP TABLE reverse_geocode_cache;
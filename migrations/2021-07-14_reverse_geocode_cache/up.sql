@@ -0,0 +1,13 @@
+CREATE TABLE reverse_geocode_cache (
+    -- normalized location
+    pos        TEXT PRIMARY KEY NOT NULL,
+    --
+    street     TEXT,
+    zip        TEXT,
+    city       TEXT,
+    country    TEXT,
+    state      TEXT,
+    created_at INTEGER NOT NULL
+);
+
+CREATE INDEX reverse_geocode_cache_idx_created_at ON reverse_geocode_cache(created_at);
@@ -0,0 +1,18 @@
+-- This file should undo anything in `up.sql`
+-- The plaintext API tokens cannot be restored
+CREATE TABLE organization_old (
+    rowid     INTEGER PRIMARY KEY NOT NULL,
+    --
+    id        TEXT NOT NULL,
+    name      TEXT NOT NULL,
+    api_token TEXT NOT NULL,
+    --
+    UNIQUE (id)
+);
+
+INSERT INTO organization_old
+SELECT rowid, id, name, ''
+FROM organization;
+
+DROP TABLE organization;
+ALTER TABLE organization_old RENAME TO organization;
@@ -0,0 +1,25 @@
+-- Replace the plaintext primary API tokens of organizations
+-- by hashed API tokens with all permissions. The function
+-- sha256() is registered on each database connection.
+INSERT OR IGNORE INTO organization_api_token (org_rowid, id, name, token_hash, scopes, created_at)
+SELECT rowid, LOWER(HEX(RANDOMBLOB(16))), 'Primary', sha256(api_token),
+    'events:write,events:delete,clearance:read,clearance:write,places:write',
+    CAST(strftime('%s', 'now') AS INTEGER) * 1000
+FROM organization
+WHERE api_token <> '';
+
+CREATE TABLE organization_new (
+    rowid     INTEGER PRIMARY KEY NOT NULL,
+    --
+    id        TEXT NOT NULL,
+    name      TEXT NOT NULL,
+    --
+    UNIQUE (id)
+);
+
+INSERT INTO organization_new
+SELECT rowid, id, name
+FROM organization;
+
+DROP TABLE organization;
+ALTER TABLE organization_new RENAME TO organization;
@@ -0,0 +1,15 @@
+-- Accounts of users at OpenID Connect providers that
+-- are identified by their subject instead of their
+-- email address.
+CREATE TABLE user_oidc_account (
+    rowid       INTEGER PRIMARY KEY NOT NULL,
+    user_rowid  INTEGER NOT NULL,
+    --
+    provider    TEXT NOT NULL,
+    subject     TEXT NOT NULL,
+    provisioned BOOLEAN NOT NULL,
+    created_at  INTEGER NOT NULL,
+    --
+    UNIQUE (provider, subject),
+    FOREIGN KEY (user_rowid) REFERENCES users(id)
+);
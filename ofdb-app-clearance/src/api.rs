@@ -1,8 +1,106 @@
-use ofdb_boundary::PendingClearanceForPlace;
+use ofdb_boundary::{
+    ClearanceForPlace, Credentials, JwtToken, OrganizationMembership, PendingClearanceForPlace,
+    ResultCount,
+};
 use ofdb_entities::place::{PlaceHistory, PlaceRevision};
+use seed::prelude::*;
 
 pub const API_ROOT: &str = "/api";
 
+/// Members select the organization on whose behalf they act
+/// with this header.
+const HEADER_ORGANIZATION: &str = "X-Organization";
+
+/// The session of a logged-in member of an organization.
+#[derive(Clone, Debug)]
+pub struct Session {
+    pub token: String,
+    pub org_id: String,
+}
+
+impl Session {
+    fn request(&self, url: String) -> Request {
+        Request::new(url)
+            .header(Header::bearer(&self.token))
+            .header(Header::custom(HEADER_ORGANIZATION, &self.org_id))
+    }
+}
+
+pub async fn post_login(credentials: &Credentials) -> fetch::Result<Option<JwtToken>> {
+    Request::new(format!("{}/login", API_ROOT))
+        .method(Method::Post)
+        .json(credentials)?
+        .fetch()
+        .await?
+        .check_status()?
+        .json()
+        .await
+}
+
+pub async fn post_logout(token: &str) -> fetch::Result<()> {
+    Request::new(format!("{}/logout", API_ROOT))
+        .method(Method::Post)
+        .header(Header::bearer(token))
+        .json(&())?
+        .fetch()
+        .await?
+        .check_status()?;
+    Ok(())
+}
+
+pub async fn get_current_user_organizations(
+    token: &str,
+) -> fetch::Result<Vec<OrganizationMembership>> {
+    Request::new(format!("{}/users/current/organizations", API_ROOT))
+        .header(Header::bearer(token))
+        .header(Header::custom("Accept", "application/json"))
+        .fetch()
+        .await?
+        .check_status()?
+        .json()
+        .await
+}
+
+pub async fn get_places_clearance(
+    session: &Session,
+) -> fetch::Result<Vec<PendingClearanceForPlace>> {
+    session
+        .request(format!("{}/places/clearance", API_ROOT))
+        .fetch()
+        .await?
+        .check_status()?
+        .json()
+        .await
+}
+
+pub async fn get_place_history(
+    session: &Session,
+    id: &str,
+) -> fetch::Result<ofdb_boundary::PlaceHistory> {
+    session
+        .request(format!("{}/places/{}/history", API_ROOT, id))
+        .fetch()
+        .await?
+        .check_status()?
+        .json()
+        .await
+}
+
+pub async fn post_places_clearance(
+    session: &Session,
+    clearances: Vec<ClearanceForPlace>,
+) -> fetch::Result<ResultCount> {
+    session
+        .request(format!("{}/places/clearance", API_ROOT))
+        .method(Method::Post)
+        .json(&clearances)?
+        .fetch()
+        .await?
+        .check_status()?
+        .json()
+        .await
+}
+
 #[derive(Clone, Debug)]
 pub struct PlaceClearance {
     pub pending: PendingClearanceForPlace,
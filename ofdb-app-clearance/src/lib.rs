@@ -6,11 +6,13 @@ mod page;
 
 use page::Page;
 
-const TOKEN_KEY: &str = "org-token";
+const TOKEN_KEY: &str = "session-token";
+const ORG_KEY: &str = "org-id";
 const TITLE: &str = "Clearance Center";
 const PAGE_URL: &str = "clearance";
 const HASH_PATH_LOGIN: &str = "login";
 const HASH_PATH_INVALID: &str = "invalid";
+const HASH_PATH_ORGANIZATIONS: &str = "organizations";
 
 #[derive(Debug)]
 struct Mdl {
@@ -22,6 +24,7 @@ pub enum Msg {
     UrlChanged(subs::UrlChanged),
     PageIndex(page::index::Msg),
     PageLogin(page::login::Msg),
+    PageOrganizations(page::organizations::Msg),
 }
 
 fn update(msg: Msg, mdl: &mut Mdl, orders: &mut impl Orders<Msg>) {
@@ -39,6 +42,15 @@ fn update(msg: Msg, mdl: &mut Mdl, orders: &mut impl Orders<Msg>) {
                 page::login::update(msg, login_mdl, &mut orders.proxy(Msg::PageLogin));
             }
         }
+        Msg::PageOrganizations(msg) => {
+            if let Page::Organizations(orgs_mdl) = &mut mdl.page {
+                page::organizations::update(
+                    msg,
+                    orgs_mdl,
+                    &mut orders.proxy(Msg::PageOrganizations),
+                );
+            }
+        }
     }
 }
 
@@ -53,10 +65,24 @@ fn view(mdl: &Mdl) -> Node<Msg> {
     match &mdl.page {
         Page::Home(mdl) => page::index::view(&mdl).map_msg(Msg::PageIndex),
         Page::Login(mdl) => page::login::view(&mdl).map_msg(Msg::PageLogin),
+        Page::Organizations(mdl) => page::organizations::view(&mdl).map_msg(Msg::PageOrganizations),
         Page::NotFound => div!["Not Found!"],
     }
 }
 
+/// Discards the current session and returns to the login page.
+fn logout_and_reload() {
+    for key in &[TOKEN_KEY, ORG_KEY] {
+        if let Err(err) = SessionStorage::remove(key) {
+            error!(err);
+        }
+    }
+    Url::new()
+        .set_path(&[PAGE_URL])
+        .set_hash_path(&[HASH_PATH_LOGIN])
+        .go_and_load();
+}
+
 #[wasm_bindgen(start)]
 pub fn start() {
     App::start("app", init, update, view);
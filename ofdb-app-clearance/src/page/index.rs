@@ -2,13 +2,12 @@ use crate::{api, components::navbar};
 use difference::{Changeset, Difference};
 use ofdb_boundary::{ClearanceForPlace, PendingClearanceForPlace, ResultCount};
 use ofdb_entities::{place::PlaceHistory, place::PlaceRevision};
-use ofdb_seed::Api;
 use seed::{prelude::*, *};
 use std::collections::{HashMap, HashSet};
 
 #[derive(Debug)]
 pub struct Mdl {
-    token: String,
+    session: api::Session,
     place_clearances: HashMap<String, api::PlaceClearance>,
     expanded: HashSet<String>,
     selected: HashSet<String>,
@@ -40,15 +39,20 @@ pub enum ClearanceError {
 }
 
 pub fn init(orders: &mut impl Orders<Msg>) -> Option<Mdl> {
-    SessionStorage::get(crate::TOKEN_KEY)
+    let token = SessionStorage::get(crate::TOKEN_KEY)
         .map_err(|err| {
             log!("No token found", err);
         })
+        .ok()?;
+    SessionStorage::get(crate::ORG_KEY)
+        .map_err(|err| {
+            log!("No organization selected", err);
+        })
         .ok()
-        .map(|token| {
+        .map(|org_id| {
             orders.send_msg(Msg::GetPendingClearances);
             Mdl {
-                token,
+                session: api::Session { token, org_id },
                 place_clearances: HashMap::new(),
                 expanded: HashSet::new(),
                 selected: HashSet::new(),
@@ -63,7 +67,7 @@ pub fn init(orders: &mut impl Orders<Msg>) -> Option<Mdl> {
 pub fn update(msg: Msg, mdl: &mut Mdl, orders: &mut impl Orders<Msg>) {
     match msg {
         Msg::GetPendingClearances => {
-            orders.perform_cmd(get_pending_clearances(mdl.token.clone()));
+            orders.perform_cmd(get_pending_clearances(mdl.session.clone()));
         }
         Msg::GotPendingClearances(pending) => {
             for p in pending {
@@ -79,7 +83,7 @@ pub fn update(msg: Msg, mdl: &mut Mdl, orders: &mut impl Orders<Msg>) {
                         },
                     );
                 }
-                orders.perform_cmd(get_place_history(mdl.token.clone(), id));
+                orders.perform_cmd(get_place_history(mdl.session.clone(), id));
             }
         }
         Msg::GotPlaceHistory(ph) => {
@@ -129,8 +133,7 @@ pub fn update(msg: Msg, mdl: &mut Mdl, orders: &mut impl Orders<Msg>) {
                 cleared_revision: Some(rev_nr),
             };
             let clearances = vec![c];
-            let token = mdl.token.to_owned();
-            orders.perform_cmd(places_clearance(token, clearances));
+            orders.perform_cmd(places_clearance(mdl.session.clone(), clearances));
         }
         Msg::AcceptAllSelected => {
             let clearances = mdl
@@ -149,8 +152,7 @@ pub fn update(msg: Msg, mdl: &mut Mdl, orders: &mut impl Orders<Msg>) {
                     cleared_revision,
                 })
                 .collect();
-            let token = mdl.token.to_owned();
-            orders.perform_cmd(places_clearance(token, clearances));
+            orders.perform_cmd(places_clearance(mdl.session.clone(), clearances));
         }
         Msg::ClearanceResult(Ok(ids)) => {
             for id in ids {
@@ -158,7 +160,7 @@ pub fn update(msg: Msg, mdl: &mut Mdl, orders: &mut impl Orders<Msg>) {
                 mdl.selected.remove(&id);
                 mdl.expanded.remove(&id);
             }
-            orders.perform_cmd(get_pending_clearances(mdl.token.clone()));
+            orders.perform_cmd(get_pending_clearances(mdl.session.clone()));
         }
         Msg::ClearanceResult(Err(err)) => {
             // TODO: handle error, e.g. show error message to the user
@@ -167,10 +169,7 @@ pub fn update(msg: Msg, mdl: &mut Mdl, orders: &mut impl Orders<Msg>) {
         Msg::ConsoleLog(str) => log!(str),
         Msg::Navbar(msg) => match msg {
             navbar::Msg::Logout => {
-                if let Err(err) = SessionStorage::remove(crate::TOKEN_KEY) {
-                    error!(err);
-                }
-                Url::reload();
+                orders.perform_cmd(logout(mdl.session.token.clone()));
             }
             _ => {
                 navbar::update(msg, &mut mdl.navbar, &mut orders.proxy(Msg::Navbar));
@@ -538,18 +537,14 @@ fn diffy_last<Ms>(cs: &Changeset) -> Node<Ms> {
     span![csm]
 }
 
-async fn get_pending_clearances(api_token: String) -> Option<Msg> {
-    let api = Api::new(api::API_ROOT.into());
-    match api.get_places_clearance_with_api_token(&api_token).await {
+async fn get_pending_clearances(session: api::Session) -> Option<Msg> {
+    match api::get_places_clearance(&session).await {
         Ok(pending) => Some(Msg::GotPendingClearances(pending)),
         Err(err) => {
             error!(err);
             if let FetchError::StatusError(Status { code, .. }) = err {
                 if code == 401 {
-                    let url = Url::new()
-                        .set_path(&[crate::PAGE_URL])
-                        .set_hash_path(&[crate::HASH_PATH_LOGIN, crate::HASH_PATH_INVALID]);
-                    url.go_and_load();
+                    crate::logout_and_reload();
                 }
             }
             None
@@ -557,9 +552,8 @@ async fn get_pending_clearances(api_token: String) -> Option<Msg> {
     }
 }
 
-async fn get_place_history(api_token: String, id: String) -> Option<Msg> {
-    let api = Api::new(api::API_ROOT.into());
-    match api.get_place_history_with_api_token(&api_token, &id).await {
+async fn get_place_history(session: api::Session, id: String) -> Option<Msg> {
+    match api::get_place_history(&session, &id).await {
         Ok(ph) => {
             let ph = PlaceHistory::from(ph);
             Some(Msg::GotPlaceHistory(ph))
@@ -571,18 +565,14 @@ async fn get_place_history(api_token: String, id: String) -> Option<Msg> {
     }
 }
 
-async fn places_clearance(token: String, clearances: Vec<ClearanceForPlace>) -> Msg {
-    let api = Api::new(api::API_ROOT.into());
+async fn places_clearance(session: api::Session, clearances: Vec<ClearanceForPlace>) -> Msg {
     let cnt = clearances.len();
     let ids = clearances
         .iter()
         .map(|c| &c.place_id)
         .map(|id| id.to_string())
         .collect();
-    match api
-        .post_places_clearance_with_api_token(&token, clearances)
-        .await
-    {
+    match api::post_places_clearance(&session, clearances).await {
         Ok(ResultCount { count }) => {
             if count as usize == cnt {
                 Msg::ClearanceResult(Ok(ids))
@@ -596,3 +586,11 @@ async fn places_clearance(token: String, clearances: Vec<ClearanceForPlace>) ->
         }
     }
 }
+
+async fn logout(token: String) -> Option<Msg> {
+    if let Err(err) = api::post_logout(&token).await {
+        error!(err);
+    }
+    crate::logout_and_reload();
+    None
+}
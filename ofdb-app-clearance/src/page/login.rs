@@ -1,9 +1,12 @@
-use crate::components::navbar;
+use crate::{api, components::navbar};
+use ofdb_boundary::{Credentials, JwtToken};
 use seed::{prelude::*, *};
 
 #[derive(Debug)]
 pub struct Mdl {
-    token: String,
+    email: String,
+    password: String,
+    totp_code: String,
     invalid: bool,
     show_password: bool,
     navbar: navbar::Mdl,
@@ -13,14 +16,19 @@ pub struct Mdl {
 pub enum Msg {
     TogglePasswordVisible,
     Login,
-    TokenInput(String),
+    LoginResult(Option<JwtToken>),
+    EmailInput(String),
+    PasswordInput(String),
+    TotpCodeInput(String),
     Navbar(navbar::Msg),
 }
 
 pub fn init(mut url: Url) -> Mdl {
     let invalid = url.next_hash_path_part().unwrap_or("") == crate::HASH_PATH_INVALID;
     Mdl {
-        token: String::new(),
+        email: String::new(),
+        password: String::new(),
+        totp_code: String::new(),
         invalid,
         show_password: false,
         navbar: navbar::Mdl {
@@ -37,27 +45,49 @@ pub fn update(msg: Msg, mdl: &mut Mdl, orders: &mut impl Orders<Msg>) {
             orders.force_render_now();
         }
         Msg::Login => {
-            if mdl.show_password {
-                orders.send_msg(Msg::TogglePasswordVisible);
-                orders.send_msg(Msg::Login);
-            } else {
-                if let Err(err) = SessionStorage::insert(crate::TOKEN_KEY, &mdl.token) {
-                    log!(err);
-                }
-                let el = document().get_element_by_id("login-form").unwrap();
-                let form = el.dyn_ref::<web_sys::HtmlFormElement>().unwrap();
-                if let Err(err) = form.submit() {
-                    error!(err);
-                }
+            let credentials = Credentials {
+                email: mdl.email.clone(),
+                password: mdl.password.clone(),
+                totp_code: Some(mdl.totp_code.trim().to_string()).filter(|c| !c.is_empty()),
+            };
+            orders.perform_cmd(login(credentials));
+        }
+        Msg::LoginResult(Some(JwtToken { token, .. })) => {
+            if let Err(err) = SessionStorage::insert(crate::TOKEN_KEY, &token) {
+                log!(err);
+            }
+            if let Err(err) = SessionStorage::remove(crate::ORG_KEY) {
+                log!(err);
             }
+            let url = Url::new()
+                .set_path(&[crate::PAGE_URL])
+                .set_hash_path(&[crate::HASH_PATH_ORGANIZATIONS]);
+            orders.request_url(url);
+        }
+        Msg::LoginResult(None) => {
+            mdl.invalid = true;
+            mdl.password.clear();
+            mdl.totp_code.clear();
         }
-        Msg::TokenInput(token) => mdl.token = token,
+        Msg::EmailInput(email) => mdl.email = email,
+        Msg::PasswordInput(password) => mdl.password = password,
+        Msg::TotpCodeInput(code) => mdl.totp_code = code,
         Msg::Navbar(msg) => {
             navbar::update(msg, &mut mdl.navbar, &mut orders.proxy(Msg::Navbar));
         }
     }
 }
 
+async fn login(credentials: Credentials) -> Msg {
+    match api::post_login(&credentials).await {
+        Ok(token) => Msg::LoginResult(token),
+        Err(err) => {
+            error!(err);
+            Msg::LoginResult(None)
+        }
+    }
+}
+
 pub fn view(mdl: &Mdl) -> Node<Msg> {
     let pwfield_type = if mdl.show_password {
         "text"
@@ -77,7 +107,7 @@ pub fn view(mdl: &Mdl) -> Node<Msg> {
                             St::Color => "red",
                             St::PaddingBottom => px(20),
                         },
-                        "Your API token is invalid. Please try again"
+                        "Your email, password or authentication code is invalid. Please try again"
                     ]
                 } else {
                     empty!()
@@ -86,37 +116,62 @@ pub fn view(mdl: &Mdl) -> Node<Msg> {
                     style! {
                         St::PaddingBottom => px(20),
                     },
-                    "Enter your organization's API token: ",
+                    "Log in with your account to review places on behalf of your organization: ",
                 ],
                 form![
                     id!("login-form"),
-                    attrs! {
-                        At::Action => crate::PAGE_URL,
-                    },
-                    input![
-                        C!["input"],
-                        attrs! {
-                            At::Name => "username",
-                            At::Type => "text",
-                            At::Value => crate::TITLE,
-                        },
-                        style! {
-                            St::Display => "none",
-                        },
+                    ev(Ev::Submit, |ev| {
+                        ev.prevent_default();
+                        Msg::Login
+                    }),
+                    div![
+                        C!["field"],
+                        label![C!["label"], "Email"],
+                        input![
+                            C!["input"],
+                            attrs! {
+                                At::Name => "username",
+                                At::Type => "email",
+                                At::Value => mdl.email,
+                            },
+                            style! {
+                                St::Width => "50%",
+                            },
+                            input_ev(Ev::Input, Msg::EmailInput),
+                        ],
                     ],
                     div![
                         C!["field"],
-                        label![C!["label"], "Token"],
+                        label![C!["label"], "Password"],
                         input![
                             C!["input"],
                             attrs! {
                                 At::Name => "password",
                                 At::Type => pwfield_type,
+                                At::Value => mdl.password,
+                            },
+                            style! {
+                                St::Width => "50%",
+                            },
+                            input_ev(Ev::Input, Msg::PasswordInput),
+                        ],
+                    ],
+                    div![
+                        C!["field"],
+                        label![C!["label"], "Authentication code"],
+                        input![
+                            C!["input"],
+                            attrs! {
+                                At::Name => "totp_code",
+                                At::Type => "text",
+                                At::AutoComplete => "one-time-code",
+                                At::Placeholder => "Only if two-factor authentication is enabled",
+                                At::Value => mdl.totp_code,
                             },
                             style! {
                                 St::Width => "50%",
                             },
-                            input_ev(Ev::Input, Msg::TokenInput),
+                            input_ev(Ev::Input, Msg::TotpCodeInput),
                         ],
                     ],
                     div![
@@ -133,7 +188,7 @@ pub fn view(mdl: &Mdl) -> Node<Msg> {
                                     },
                                     ev(Ev::Click, |_| Msg::TogglePasswordVisible),
                                 ],
-                                " show token",
+                                " show password",
                             ],
                         ]
                     ],
@@ -143,7 +198,6 @@ pub fn view(mdl: &Mdl) -> Node<Msg> {
                             At::Type => "submit",
                             At::Value => "Login",
                         },
-                        ev(Ev::Click, |_| Msg::Login),
                     ],
                 ]
             ]
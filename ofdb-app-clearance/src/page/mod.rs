@@ -3,11 +3,13 @@ use seed::{prelude::*, *};
 
 pub mod index;
 pub mod login;
+pub mod organizations;
 
 #[derive(Debug)]
 pub enum Page {
     Home(index::Mdl),
     Login(login::Mdl),
+    Organizations(organizations::Mdl),
     NotFound,
 }
 
@@ -17,14 +19,25 @@ impl Page {
             None => match index::init(&mut orders.proxy(Msg::PageIndex)) {
                 Some(mdl) => Self::Home(mdl),
                 None => {
-                    let url = Url::new()
-                        .set_path(&[crate::PAGE_URL])
-                        .set_hash_path(&[crate::HASH_PATH_LOGIN]);
-                    orders.request_url(url);
+                    if SessionStorage::get::<_, String>(crate::TOKEN_KEY).is_ok() {
+                        // Logged in without a selected organization
+                        redirect(orders, crate::HASH_PATH_ORGANIZATIONS);
+                    } else {
+                        redirect(orders, crate::HASH_PATH_LOGIN);
+                    }
                     Self::NotFound
                 }
             },
             Some(crate::HASH_PATH_LOGIN) => Self::Login(login::init(url)),
+            Some(crate::HASH_PATH_ORGANIZATIONS) => {
+                match organizations::init(&mut orders.proxy(Msg::PageOrganizations)) {
+                    Some(mdl) => Self::Organizations(mdl),
+                    None => {
+                        redirect(orders, crate::HASH_PATH_LOGIN);
+                        Self::NotFound
+                    }
+                }
+            }
             _ => {
                 log!("not found:", url);
                 Self::NotFound
@@ -32,3 +45,10 @@ impl Page {
         }
     }
 }
+
+fn redirect(orders: &mut impl Orders<Msg>, hash_path: &str) {
+    let url = Url::new()
+        .set_path(&[crate::PAGE_URL])
+        .set_hash_path(&[hash_path]);
+    orders.request_url(url);
+}
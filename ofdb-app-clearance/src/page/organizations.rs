@@ -0,0 +1,123 @@
+use crate::{api, components::navbar};
+use ofdb_boundary::OrganizationMembership;
+use seed::{prelude::*, *};
+
+// Only these roles are allowed to review pending clearances
+const CLEARANCE_ROLES: &[&str] = &["owner", "clearance-reviewer"];
+
+#[derive(Debug)]
+pub struct Mdl {
+    token: String,
+    memberships: Option<Vec<OrganizationMembership>>,
+    navbar: navbar::Mdl,
+}
+
+#[derive(Clone)]
+pub enum Msg {
+    GotMemberships(Vec<OrganizationMembership>),
+    Select(String),
+    Navbar(navbar::Msg),
+}
+
+pub fn init(orders: &mut impl Orders<Msg>) -> Option<Mdl> {
+    SessionStorage::get(crate::TOKEN_KEY)
+        .map_err(|err| {
+            log!("No token found", err);
+        })
+        .ok()
+        .map(|token: String| {
+            orders.perform_cmd(get_memberships(token.clone()));
+            Mdl {
+                token,
+                memberships: None,
+                navbar: navbar::Mdl {
+                    login_status: navbar::LoginStatus::LoggedIn,
+                    menu_is_active: false,
+                },
+            }
+        })
+}
+
+pub fn update(msg: Msg, mdl: &mut Mdl, orders: &mut impl Orders<Msg>) {
+    match msg {
+        Msg::GotMemberships(memberships) => {
+            let memberships: Vec<_> = memberships
+                .into_iter()
+                .filter(|m| CLEARANCE_ROLES.contains(&m.role.as_str()))
+                .collect();
+            if memberships.len() == 1 {
+                // Nothing to choose from
+                orders.send_msg(Msg::Select(memberships[0].organization.id.clone()));
+            }
+            mdl.memberships = Some(memberships);
+        }
+        Msg::Select(org_id) => {
+            if let Err(err) = SessionStorage::insert(crate::ORG_KEY, &org_id) {
+                error!(err);
+            }
+            orders.request_url(Url::new().set_path(&[crate::PAGE_URL]));
+        }
+        Msg::Navbar(msg) => match msg {
+            navbar::Msg::Logout => {
+                orders.perform_cmd(logout(mdl.token.clone()));
+            }
+            _ => {
+                navbar::update(msg, &mut mdl.navbar, &mut orders.proxy(Msg::Navbar));
+            }
+        },
+    }
+}
+
+pub fn view(mdl: &Mdl) -> Node<Msg> {
+    div![
+        navbar::view(&mdl.navbar).map_msg(Msg::Navbar),
+        main![div![
+            C!["container"],
+            div![
+                C!["section"],
+                h2![C!["title"], "Organizations"],
+                match &mdl.memberships {
+                    None => p!["Loading organizations ..."],
+                    Some(memberships) if memberships.is_empty() =>
+                        p!["You are not allowed to review places on behalf of any organization."],
+                    Some(memberships) => div![
+                        C!["panel"],
+                        p![C!["panel-heading"], "Select an organization"],
+                        memberships.iter().map(|m| {
+                            let org_id = m.organization.id.clone();
+                            a![
+                                C!["panel-block"],
+                                ev(Ev::Click, |_| Msg::Select(org_id)),
+                                &m.organization.name,
+                                span![C!["tag", "is-light", "ml-2"], &m.role],
+                            ]
+                        })
+                    ],
+                }
+            ]
+        ]]
+    ]
+}
+
+async fn get_memberships(token: String) -> Option<Msg> {
+    match api::get_current_user_organizations(&token).await {
+        Ok(memberships) => Some(Msg::GotMemberships(memberships)),
+        Err(err) => {
+            error!(err);
+            if let FetchError::StatusError(Status { code, .. }) = err {
+                if code == 401 {
+                    crate::logout_and_reload();
+                }
+            }
+            None
+        }
+    }
+}
+
+async fn logout(token: String) -> Option<Msg> {
+    if let Err(err) = api::post_logout(&token).await {
+        error!(err);
+    }
+    crate::logout_and_reload();
+    None
+}
@@ -73,12 +73,32 @@ impl From<e::user::User> for User {
             email,
             email_confirmed,
             role,
+            disabled,
+            language,
             password: _password,
         } = from;
         Self {
             email,
             email_confirmed,
             role: role.into(),
+            disabled,
+            language: language.map(|l| l.as_str().to_string()),
+        }
+    }
+}
+
+impl From<e::user::AuthorNotifications> for AuthorNotifications {
+    fn from(from: e::user::AuthorNotifications) -> Self {
+        let e::user::AuthorNotifications {
+            user_email: _,
+            reviews,
+            ratings,
+            clearances,
+        } = from;
+        Self {
+            reviews,
+            ratings,
+            clearances,
         }
     }
 }
@@ -621,3 +641,260 @@ impl From<ReviewStatusLog> for e::review::ReviewStatusLog {
         }
     }
 }
+
+impl From<e::organization::ModeratedTag> for ModeratedTag {
+    fn from(from: e::organization::ModeratedTag) -> Self {
+        let e::organization::ModeratedTag {
+            label,
+            allow_add,
+            allow_remove,
+            require_clearance,
+        } = from;
+        Self {
+            label,
+            allow_add,
+            allow_remove,
+            require_clearance,
+        }
+    }
+}
+
+impl From<ModeratedTag> for e::organization::ModeratedTag {
+    fn from(from: ModeratedTag) -> Self {
+        let ModeratedTag {
+            label,
+            allow_add,
+            allow_remove,
+            require_clearance,
+        } = from;
+        Self {
+            label,
+            allow_add,
+            allow_remove,
+            require_clearance,
+        }
+    }
+}
+
+impl From<e::organization::Organization> for Organization {
+    fn from(from: e::organization::Organization) -> Self {
+        let e::organization::Organization {
+            id,
+            name,
+            moderated_tags,
+        } = from;
+        Self {
+            id: id.into(),
+            name,
+            moderated_tags: moderated_tags.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<e::organization::OrganizationMember> for OrganizationMember {
+    fn from(from: e::organization::OrganizationMember) -> Self {
+        let e::organization::OrganizationMember {
+            org_id: _,
+            email,
+            role,
+        } = from;
+        Self {
+            email,
+            role: role.as_str().to_string(),
+        }
+    }
+}
+
+impl From<e::api_token::ApiToken> for ApiToken {
+    fn from(from: e::api_token::ApiToken) -> Self {
+        // Only the hash of the secret is known
+        let e::api_token::ApiToken {
+            id,
+            org_id: _,
+            name,
+            token_hash: _,
+            scopes,
+            created_at,
+            expires_at,
+            last_used_at,
+        } = from;
+        Self {
+            id: id.into(),
+            name,
+            scopes: scopes.into_iter().map(|s| s.as_str().to_string()).collect(),
+            created_at: created_at.into_inner(),
+            expires_at: expires_at.map(e::time::TimestampMs::into_inner),
+            last_used_at: last_used_at.map(e::time::TimestampMs::into_inner),
+        }
+    }
+}
+
+impl From<e::subscription::SubscriptionFilter> for SubscriptionFilter {
+    fn from(from: e::subscription::SubscriptionFilter) -> Self {
+        let e::subscription::SubscriptionFilter {
+            tags,
+            categories,
+            entity_kind,
+            change_types,
+        } = from;
+        Self {
+            tags,
+            categories: categories.into_iter().map(Into::into).collect(),
+            entity_kind: entity_kind.map(|k| k.as_str().to_string()),
+            change_types: change_types
+                .into_iter()
+                .map(|t| t.as_str().to_string())
+                .collect(),
+        }
+    }
+}
+
+impl From<e::subscription::BboxSubscription> for BboxSubscription {
+    fn from(from: e::subscription::BboxSubscription) -> Self {
+        let e::subscription::BboxSubscription {
+            id,
+            user_email: _,
+            name,
+            bbox,
+            filter,
+            delivery_mode,
+        } = from;
+        Self {
+            id: id.into(),
+            south_west_lat: bbox.southwest().lat().to_deg(),
+            south_west_lng: bbox.southwest().lng().to_deg(),
+            north_east_lat: bbox.northeast().lat().to_deg(),
+            north_east_lng: bbox.northeast().lng().to_deg(),
+            name,
+            filter: filter.into(),
+            delivery_mode: delivery_mode.as_str().to_string(),
+        }
+    }
+}
+
+impl From<e::webhook::Webhook> for Webhook {
+    fn from(from: e::webhook::Webhook) -> Self {
+        // The shared secret is never revealed again
+        let e::webhook::Webhook {
+            id,
+            org_id: _,
+            url,
+            secret: _,
+            event_types,
+            created_at,
+        } = from;
+        Self {
+            id: id.into(),
+            url: url.to_string(),
+            event_types: event_types
+                .into_iter()
+                .map(|t| t.as_str().to_string())
+                .collect(),
+            created_at: created_at.into_inner(),
+        }
+    }
+}
+
+impl From<e::webhook::WebhookDeliveryStatus> for WebhookDeliveryStatus {
+    fn from(from: e::webhook::WebhookDeliveryStatus) -> Self {
+        use e::webhook::WebhookDeliveryStatus as E;
+        match from {
+            E::Pending => Self::Pending,
+            E::Delivered => Self::Delivered,
+            E::Failed => Self::Failed,
+        }
+    }
+}
+
+impl From<e::webhook::WebhookDelivery> for WebhookDelivery {
+    fn from(from: e::webhook::WebhookDelivery) -> Self {
+        let status = from.status().into();
+        let e::webhook::WebhookDelivery {
+            id,
+            webhook_id: _,
+            event_type,
+            payload: _,
+            created_at,
+            attempts,
+            next_attempt_at,
+            last_attempt_at,
+            last_response_status,
+            last_error,
+            delivered_at,
+        } = from;
+        Self {
+            id: id.into(),
+            event_type: event_type.as_str().to_string(),
+            status,
+            created_at: created_at.into_inner(),
+            attempts,
+            next_attempt_at: next_attempt_at.map(e::time::TimestampMs::into_inner),
+            last_attempt_at: last_attempt_at.map(e::time::TimestampMs::into_inner),
+            last_response_status,
+            last_error,
+            delivered_at: delivered_at.map(e::time::TimestampMs::into_inner),
+        }
+    }
+}
+
+impl From<e::email::OutgoingEmailStatus> for OutgoingEmailStatus {
+    fn from(from: e::email::OutgoingEmailStatus) -> Self {
+        use e::email::OutgoingEmailStatus as E;
+        match from {
+            E::Pending => Self::Pending,
+            E::Sent => Self::Sent,
+            E::Failed => Self::Failed,
+        }
+    }
+}
+
+impl From<e::email::OutgoingEmail> for OutgoingEmail {
+    fn from(from: e::email::OutgoingEmail) -> Self {
+        let status = from.status().into();
+        let e::email::OutgoingEmail {
+            id,
+            recipient,
+            subject,
+            body: _,
+            html_body: _,
+            unsubscribe_url: _,
+            created_at,
+            attempts,
+            next_attempt_at,
+            last_attempt_at,
+            last_error,
+            sent_at,
+        } = from;
+        Self {
+            id: id.into(),
+            recipient: recipient.into(),
+            subject,
+            status,
+            created_at: created_at.into_inner(),
+            attempts,
+            next_attempt_at: next_attempt_at.map(e::time::TimestampMs::into_inner),
+            last_attempt_at: last_attempt_at.map(e::time::TimestampMs::into_inner),
+            last_error,
+            sent_at: sent_at.map(e::time::TimestampMs::into_inner),
+        }
+    }
+}
+
+impl From<e::audit_log::AuditLogEntry> for AuditLogEntry {
+    fn from(from: e::audit_log::AuditLogEntry) -> Self {
+        let e::audit_log::AuditLogEntry {
+            at,
+            actor,
+            action,
+            target,
+            details,
+        } = from;
+        Self {
+            created_at: at.into_inner(),
+            actor,
+            action: action.as_str().to_string(),
+            target,
+            details,
+        }
+    }
+}
@@ -172,6 +172,11 @@ pub struct User {
     pub email: String,
     pub email_confirmed: bool,
     pub role: UserRole,
+    #[serde(default)]
+    pub disabled: bool,
+    /// The preferred language for emails (ISO 639-1)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub language: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -256,6 +261,24 @@ pub struct PlaceSearchResult {
     pub ratings: EntrySearchRatings,
 }
 
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct PlaceCluster {
+    pub lat: f64,
+    pub lng: f64,
+    pub count: u64,
+    pub categories: Vec<String>,
+
+    /// Only available if the cluster contains a single place
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    /// The count is only a lower bound if the tile
+    /// contains too many places
+    #[serde(default)]
+    pub truncated: bool,
+}
+
 #[derive(Serialize, Deserialize)]
 #[cfg_attr(
     feature = "extra-derive",
@@ -304,6 +327,38 @@ pub struct BboxSubscription {
     pub south_west_lng: f64,
     pub north_east_lat: f64,
     pub north_east_lng: f64,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub filter: SubscriptionFilter,
+    #[serde(default)]
+    pub delivery_mode: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct NewBboxSubscription {
+    #[serde(default)]
+    pub name: String,
+    pub bbox: MapBbox,
+    #[serde(default)]
+    pub filter: SubscriptionFilter,
+    /// Either "immediate" (default), "daily" or "weekly"
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub delivery_mode: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct SubscriptionFilter {
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub categories: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub entity_kind: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub change_types: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -333,11 +388,51 @@ pub struct ResetPassword {
     pub new_password: String,
 }
 
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct RequestEmailChange {
+    pub new_email: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct UserLanguage {
+    /// Resets to the default language if missing
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+/// Opt-in notifications about changes of the entries
+/// that the user has created or edited.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct AuthorNotifications {
+    /// The review status has been changed
+    #[serde(default)]
+    pub reviews: bool,
+    /// A new rating with a comment has been added
+    #[serde(default)]
+    pub ratings: bool,
+    /// A change awaits the clearance of an organization
+    #[serde(default)]
+    pub clearances: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct ConfirmEmailChange {
+    pub token: String,
+}
+
 #[derive(Serialize, Deserialize)]
 #[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
 pub struct Credentials {
     pub email: String,
     pub password: String,
+    /// A one-time password or recovery code if the second
+    /// factor is enabled for the account
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub totp_code: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -621,8 +716,216 @@ impl From<Entry> for UpdatePlace {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct TotpEnrollment {
+    /// Base32 encoded
+    pub secret: String,
+    /// The URI for adding the secret to an authenticator app,
+    /// e.g. by scanning it as a QR code
+    pub otpauth_uri: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct TwoFactorStatus {
+    pub enabled: bool,
+    pub unused_recovery_codes: usize,
+}
+
 #[derive(Serialize, Deserialize)]
 #[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
 pub struct JwtToken {
     pub token: String,
+    /// A single-use token for requesting a new access token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct JwtRefreshToken {
+    pub refresh_token: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct ModeratedTag {
+    pub label: String,
+    pub allow_add: bool,
+    pub allow_remove: bool,
+    pub require_clearance: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct NewOrganization {
+    pub name: String,
+    #[serde(default)]
+    pub moderated_tags: Vec<ModeratedTag>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct Organization {
+    pub id: String,
+    pub name: String,
+    pub moderated_tags: Vec<ModeratedTag>,
+}
+
+/// A newly created organization with its API token
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct CreatedOrganization {
+    pub api_token: String,
+    #[serde(flatten)]
+    pub organization: Organization,
+}
+
+/// A user with a role within an organization
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct OrganizationMember {
+    pub email: String,
+    pub role: String,
+}
+
+/// An organization together with the role of the current user
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct OrganizationMembership {
+    pub organization: Organization,
+    pub role: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct NewApiToken {
+    pub name: String,
+    pub scopes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub expires_at: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct ApiToken {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub created_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub expires_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub last_used_at: Option<i64>,
+}
+
+/// A newly created or rotated API token with its secret
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct IssuedApiToken {
+    pub secret: String,
+    #[serde(flatten)]
+    pub api_token: ApiToken,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct NewWebhook {
+    pub url: String,
+    pub secret: String,
+    pub event_types: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    pub event_types: Vec<String>,
+    pub created_at: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "extra-derive",
+    derive(Debug, Clone, Copy, PartialEq, Eq, Hash)
+)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub event_type: String,
+    pub status: WebhookDeliveryStatus,
+    pub created_at: i64,
+    pub attempts: u32,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub next_attempt_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub last_attempt_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub last_response_status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub last_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub delivered_at: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "extra-derive",
+    derive(Debug, Clone, Copy, PartialEq, Eq, Hash)
+)]
+#[serde(rename_all = "lowercase")]
+pub enum OutgoingEmailStatus {
+    Pending,
+    Sent,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct OutgoingEmail {
+    pub id: String,
+    pub recipient: String,
+    pub subject: String,
+    pub status: OutgoingEmailStatus,
+    pub created_at: i64,
+    pub attempts: u32,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub next_attempt_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub last_attempt_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub last_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub sent_at: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct EmailOutboxStatus {
+    pub pending: u64,
+    pub sent: u64,
+    pub failed: u64,
+    /// Messages that could not be sent after all attempts
+    pub dead_letters: Vec<OutgoingEmail>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct AuditLogEntry {
+    pub created_at: i64,
+    pub actor: String,
+    pub action: String,
+    pub target: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub details: Option<String>,
 }
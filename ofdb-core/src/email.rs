@@ -0,0 +1,116 @@
+use ofdb_entities::{email::*, time::*};
+
+/// The maximum number of attempts before a message
+/// is moved into the dead letters.
+pub const MAX_SEND_ATTEMPTS: u32 = 10;
+
+/// The delay after the first failed attempt.
+///
+/// The delay is doubled after each subsequent attempt, i.e.
+/// the last retry happens about 8.5 hours after the first attempt.
+pub const INITIAL_RETRY_DELAY_SECONDS: i64 = 60;
+
+/// The time of the next attempt after an unsuccessful attempt
+/// with exponential backoff or `None` if no retries are left.
+pub fn next_attempt_at(attempts: u32, last_attempt_at: TimestampMs) -> Option<TimestampMs> {
+    debug_assert!(attempts > 0);
+    if attempts >= MAX_SEND_ATTEMPTS {
+        return None;
+    }
+    let delay_seconds = INITIAL_RETRY_DELAY_SECONDS << (attempts - 1);
+    Some(TimestampMs::from_inner(
+        last_attempt_at.into_inner() + delay_seconds * 1000,
+    ))
+}
+
+/// Records the outcome of an attempt to send an email.
+///
+/// The `result` contains an error message if the message
+/// has not been accepted by the mail server. The content
+/// of a sent message is discarded.
+pub fn record_send_attempt(email: &mut OutgoingEmail, at: TimestampMs, result: Result<(), String>) {
+    email.attempts += 1;
+    email.last_attempt_at = Some(at);
+    match result {
+        Ok(()) => {
+            email.last_error = None;
+            email.sent_at = Some(at);
+            email.next_attempt_at = None;
+            email.body.clear();
+            email.html_body = None;
+            email.unsubscribe_url = None;
+        }
+        Err(err) => {
+            email.last_error = Some(err);
+            email.next_attempt_at = next_attempt_at(email.attempts, at);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ofdb_entities::id::Id;
+
+    fn new_email() -> OutgoingEmail {
+        OutgoingEmail {
+            id: Id::new(),
+            recipient: "test@example.com".into(),
+            subject: "subject".into(),
+            body: "body".into(),
+            html_body: None,
+            unsubscribe_url: None,
+            created_at: TimestampMs::from_inner(0),
+            attempts: 0,
+            next_attempt_at: Some(TimestampMs::from_inner(0)),
+            last_attempt_at: None,
+            last_error: None,
+            sent_at: None,
+        }
+    }
+
+    #[test]
+    fn exponential_backoff() {
+        let at = TimestampMs::from_inner(1_000_000);
+        assert_eq!(
+            Some(TimestampMs::from_inner(1_060_000)),
+            next_attempt_at(1, at)
+        );
+        assert_eq!(
+            Some(TimestampMs::from_inner(1_120_000)),
+            next_attempt_at(2, at)
+        );
+        assert!(next_attempt_at(MAX_SEND_ATTEMPTS - 1, at).is_some());
+        assert!(next_attempt_at(MAX_SEND_ATTEMPTS, at).is_none());
+    }
+
+    #[test]
+    fn retry_until_sent() {
+        let mut email = new_email();
+        let at = TimestampMs::from_inner(1_000);
+        record_send_attempt(&mut email, at, Err("connection refused".into()));
+        assert_eq!(OutgoingEmailStatus::Pending, email.status());
+        assert_eq!(Some("connection refused"), email.last_error.as_deref());
+        assert_eq!("body", email.body);
+        record_send_attempt(&mut email, at, Ok(()));
+        assert_eq!(OutgoingEmailStatus::Sent, email.status());
+        assert_eq!(2, email.attempts);
+        assert_eq!(Some(at), email.sent_at);
+        assert!(email.last_error.is_none());
+        assert!(email.body.is_empty());
+        assert!(email.html_body.is_none());
+    }
+
+    #[test]
+    fn retry_until_failed() {
+        let mut email = new_email();
+        let at = TimestampMs::from_inner(1_000);
+        for _ in 0..MAX_SEND_ATTEMPTS {
+            assert_eq!(OutgoingEmailStatus::Pending, email.status());
+            record_send_attempt(&mut email, at, Err("mailbox unavailable".into()));
+        }
+        assert_eq!(MAX_SEND_ATTEMPTS, email.attempts);
+        assert_eq!(OutgoingEmailStatus::Failed, email.status());
+        assert!(email.sent_at.is_none());
+    }
+}
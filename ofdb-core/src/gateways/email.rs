@@ -1,5 +1,13 @@
-use ofdb_entities::email::Email;
+use ofdb_entities::email::{Email, EmailContent};
 
+/// Hands over emails for delivery without waiting for the outcome.
 pub trait EmailGateway {
-    fn compose_and_send(&self, recipients: &[Email], subject: &str, body: &str);
+    fn compose_and_send(&self, recipients: &[Email], content: &EmailContent);
+}
+
+/// Delivers a single email to a mail server.
+pub trait EmailSender {
+    /// Returns an error message if the email has not
+    /// been accepted.
+    fn send(&self, recipient: &Email, content: &EmailContent) -> Result<(), String>;
 }
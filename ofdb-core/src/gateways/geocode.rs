@@ -2,4 +2,7 @@ use ofdb_entities::address::Address;
 
 pub trait GeoCodingGateway {
     fn resolve_address_lat_lng(&self, addr: &Address) -> Option<(f64, f64)>;
+
+    /// Finds the address of a location (reverse geocoding).
+    fn resolve_lat_lng_address(&self, lat: f64, lng: f64) -> Option<Address>;
 }
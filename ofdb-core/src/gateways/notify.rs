@@ -1,19 +1,61 @@
 use ofdb_entities::{
-    category::Category, event::Event, nonce::EmailNonce, place::Place, user::User,
+    category::Category,
+    clearance::PendingClearanceDigest,
+    comment::Comment,
+    event::Event,
+    nonce::EmailNonce,
+    place::Place,
+    rating::Rating,
+    review::ReviewStatus,
+    subscription::{SubscriptionDigest, SubscriptionRecipient},
+    time::TimestampMs,
+    user::User,
 };
 
 pub trait NotificationGateway {
-    fn place_added(&self, email_addresses: &[String], place: &Place, all_categories: Vec<Category>);
+    fn place_added(
+        &self,
+        recipients: &[SubscriptionRecipient],
+        place: &Place,
+        all_categories: Vec<Category>,
+    );
     fn place_updated(
         &self,
-        email_addresses: &[String],
+        recipients: &[SubscriptionRecipient],
         place: &Place,
         all_categories: Vec<Category>,
     );
-    fn event_created(&self, email_addresses: &[String], event: &Event);
-    fn event_updated(&self, email_addresses: &[String], event: &Event);
+    fn place_reviewed(
+        &self,
+        recipients: &[SubscriptionRecipient],
+        place: &Place,
+        status: ReviewStatus,
+    );
+    // Opt-in notifications for the authors of a place
+    fn own_place_reviewed(
+        &self,
+        authors: &[String],
+        place: &Place,
+        status: ReviewStatus,
+        comment: Option<&str>,
+    );
+    fn own_place_rated(
+        &self,
+        authors: &[String],
+        place: &Place,
+        rating: &Rating,
+        comment: &Comment,
+    );
+    fn own_place_clearance_pending(&self, authors: &[String], place: &Place);
+    fn event_created(&self, recipients: &[SubscriptionRecipient], event: &Event);
+    fn event_updated(&self, recipients: &[SubscriptionRecipient], event: &Event);
+    fn subscription_digest(&self, digest: &SubscriptionDigest);
+    fn pending_clearance_digest(&self, digest: &PendingClearanceDigest);
     fn user_registered_kvm(&self, user: &User);
     fn user_registered_ofdb(&self, user: &User);
     fn user_registered(&self, user: &User, url: &str);
     fn user_reset_password_requested(&self, email_nonce: &EmailNonce);
+    fn user_login_locked(&self, user: &User, locked_until: TimestampMs);
+    fn user_email_change_requested(&self, new_email_nonce: &EmailNonce);
+    fn user_email_changed(&self, old_email: &str, new_email: &str);
 }
@@ -0,0 +1,35 @@
+use ofdb_entities::url::Url;
+
+/// The claims about an authenticated user as returned by
+/// the UserInfo endpoint of an OpenID Connect provider.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OidcUserInfo {
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+    /// The values of the claim that is mapped to roles,
+    /// e.g. the groups of the user
+    pub groups: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OidcCodeExchange<'a> {
+    pub token_endpoint: &'a Url,
+    pub userinfo_endpoint: &'a Url,
+    pub client_id: &'a str,
+    pub client_secret: &'a str,
+    pub redirect_uri: &'a Url,
+    pub code: &'a str,
+    /// The secret of the login request (PKCE)
+    pub code_verifier: &'a str,
+    /// The name of the claim that contains the groups,
+    /// nested claims are separated by dots
+    pub role_claim: &'a str,
+}
+
+pub trait OidcGateway {
+    /// Exchanges an authorization code for an access token and
+    /// requests the claims about the user with it or returns an
+    /// error message if one of the requests failed.
+    fn exchange_code(&self, request: &OidcCodeExchange) -> Result<OidcUserInfo, String>;
+}
@@ -0,0 +1,20 @@
+use ofdb_entities::{url::Url, webhook::WebhookEventType};
+
+#[derive(Debug, Clone)]
+pub struct WebhookRequest<'a> {
+    pub url: &'a Url,
+    pub secret: &'a str,
+    pub delivery_id: &'a str,
+    pub event_type: WebhookEventType,
+    pub payload: &'a str,
+}
+
+pub trait WebhookGateway {
+    /// Checks that requests may be sent to the URL, i.e. that
+    /// its host only resolves to public IP addresses.
+    fn check_url(&self, url: &Url) -> Result<(), String>;
+
+    /// Sends a signed payload and returns the HTTP status code
+    /// of the response or an error message if the request failed.
+    fn post(&self, request: &WebhookRequest) -> Result<u16, String>;
+}
@@ -1,6 +1,10 @@
 pub mod bbox;
+pub mod email;
 pub mod gateways;
 pub mod rating;
+pub mod subscription;
 pub mod tag;
 pub mod text;
+pub mod tile;
 pub mod user;
+pub mod webhook;
@@ -0,0 +1,75 @@
+use hmac::{Hmac, Mac, NewMac};
+use ofdb_entities::id::Id;
+use sha2::Sha256;
+use std::fmt::Write;
+
+const SEPARATOR: char = '.';
+
+fn new_mac(secret: &str, subscription_id: &str) -> Hmac<Sha256> {
+    // HMAC accepts keys of any length
+    let mut mac = Hmac::<Sha256>::new_varkey(secret.as_bytes()).expect("HMAC key");
+    mac.update(subscription_id.as_bytes());
+    mac
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Creates a token for unsubscribing from a single subscription
+/// without logging in, e.g. via a link in a notification email.
+///
+/// The token consists of the subscription id and the hex-encoded
+/// HMAC-SHA256 of the id using the secret as key.
+pub fn unsubscribe_token(secret: &str, subscription_id: &Id) -> String {
+    let mut token = format!("{}{}", subscription_id, SEPARATOR);
+    for b in new_mac(secret, subscription_id.as_str())
+        .finalize()
+        .into_bytes()
+    {
+        let _ = write!(token, "{:02x}", b);
+    }
+    token
+}
+
+/// Extracts the subscription id from a token that
+/// has been signed with the given secret.
+pub fn verify_unsubscribe_token(secret: &str, token: &str) -> Option<Id> {
+    let mut parts = token.rsplitn(2, SEPARATOR);
+    let signature = decode_hex(parts.next()?)?;
+    let subscription_id = parts.next().filter(|id| !id.is_empty())?;
+    new_mac(secret, subscription_id)
+        .verify(&signature)
+        .ok()
+        .map(|()| subscription_id.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_signed_token() {
+        let id = Id::new();
+        let token = unsubscribe_token("secret", &id);
+        assert!(token.starts_with(&format!("{}.", id)));
+        assert_eq!(Some(id), verify_unsubscribe_token("secret", &token));
+    }
+
+    #[test]
+    fn reject_invalid_tokens() {
+        let id = Id::from("foo");
+        let token = unsubscribe_token("secret", &id);
+        assert!(verify_unsubscribe_token("other", &token).is_none());
+        assert!(verify_unsubscribe_token("secret", &token.replacen("foo", "bar", 1)).is_none());
+        assert!(verify_unsubscribe_token("secret", &token[..token.len() - 1]).is_none());
+        assert!(verify_unsubscribe_token("secret", "foo").is_none());
+        assert!(verify_unsubscribe_token("secret", "").is_none());
+    }
+}
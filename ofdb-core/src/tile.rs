@@ -0,0 +1,165 @@
+use ofdb_entities::geo::*;
+
+use std::f64::consts::PI;
+
+/// The maximum zoom level of map tiles.
+pub const MAX_ZOOM: u8 = 22;
+
+/// Web Mercator cuts off the poles at this latitude.
+const MAX_LAT_DEG: f64 = 85.051_128_779_806_59;
+
+/// A map tile in the commonly used XYZ/slippy map scheme,
+/// i.e. with the origin at the top left (north west).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileId {
+    pub z: u8,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl TileId {
+    pub fn try_new(z: u8, x: u32, y: u32) -> Option<Self> {
+        if z > MAX_ZOOM {
+            return None;
+        }
+        let n = 1u32 << z;
+        if x >= n || y >= n {
+            return None;
+        }
+        Some(Self { z, x, y })
+    }
+
+    fn tiles_per_axis(self) -> f64 {
+        f64::from(1u32 << self.z)
+    }
+
+    fn lng_deg(self, x: u32) -> f64 {
+        f64::from(x) / self.tiles_per_axis() * 360.0 - 180.0
+    }
+
+    fn lat_deg(self, y: u32) -> f64 {
+        let n = PI * (1.0 - 2.0 * f64::from(y) / self.tiles_per_axis());
+        n.sinh().atan().to_degrees()
+    }
+
+    /// The fractional tile coordinates of a point at the zoom level of this tile.
+    fn tile_coords(self, pos: MapPoint) -> (f64, f64) {
+        let (lat, lng) = pos.to_lat_lng_deg();
+        let lat = lat.max(-MAX_LAT_DEG).min(MAX_LAT_DEG).to_radians();
+        let n = self.tiles_per_axis();
+        let x = (lng + 180.0) / 360.0 * n;
+        let y = (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / PI) / 2.0 * n;
+        (x, y)
+    }
+
+    /// The eastern and southern boundaries belong to the last tile.
+    fn tile_index(self, c: f64) -> u32 {
+        (c.floor() as u32).min((1u32 << self.z) - 1)
+    }
+
+    pub fn bbox(self) -> MapBbox {
+        MapBbox::new(
+            MapPoint::from_lat_lng_deg(self.lat_deg(self.y + 1), self.lng_deg(self.x)),
+            MapPoint::from_lat_lng_deg(self.lat_deg(self.y), self.lng_deg(self.x + 1)),
+        )
+    }
+
+    /// Checks if a point is located within this tile.
+    ///
+    /// In contrast to the bounding box of a tile each point
+    /// belongs to exactly one tile on every zoom level.
+    pub fn contains_point(self, pos: MapPoint) -> bool {
+        let (x, y) = self.tile_coords(pos);
+        self.tile_index(x) == self.x && self.tile_index(y) == self.y
+    }
+
+    /// The cell of a point within a regular grid of
+    /// `grid_size` x `grid_size` cells that covers this tile.
+    pub fn grid_cell(self, pos: MapPoint, grid_size: u32) -> Option<(u32, u32)> {
+        debug_assert!(grid_size > 0);
+        if !self.contains_point(pos) {
+            return None;
+        }
+        let (x, y) = self.tile_coords(pos);
+        let cell = |c: f64, offset: u32| {
+            let cell = ((c - f64::from(offset)) * f64::from(grid_size)).floor() as u32;
+            cell.min(grid_size - 1)
+        };
+        Some((cell(x, self.x), cell(y, self.y)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_tile_coordinates() {
+        assert!(TileId::try_new(0, 0, 0).is_some());
+        assert!(TileId::try_new(0, 1, 0).is_none());
+        assert!(TileId::try_new(0, 0, 1).is_none());
+        assert!(TileId::try_new(3, 7, 7).is_some());
+        assert!(TileId::try_new(3, 8, 7).is_none());
+        assert!(TileId::try_new(MAX_ZOOM, 0, 0).is_some());
+        assert!(TileId::try_new(MAX_ZOOM + 1, 0, 0).is_none());
+    }
+
+    #[test]
+    fn bbox_of_world_tile() {
+        let bbox = TileId::try_new(0, 0, 0).unwrap().bbox();
+        assert!(bbox.is_valid());
+        assert!((bbox.southwest().lat().to_deg() + MAX_LAT_DEG).abs() < 1e-6);
+        assert!((bbox.northeast().lat().to_deg() - MAX_LAT_DEG).abs() < 1e-6);
+        assert!((bbox.southwest().lng().to_deg() + 180.0).abs() < 1e-6);
+        assert!((bbox.northeast().lng().to_deg() - 180.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn bbox_of_tile() {
+        // Stuttgart
+        let tile = TileId::try_new(10, 538, 352).unwrap();
+        let bbox = tile.bbox();
+        assert!(bbox.is_valid());
+        let pos = MapPoint::from_lat_lng_deg(48.7758, 9.1829);
+        assert!(bbox.contains_point(pos));
+        assert!(tile.contains_point(pos));
+        assert!(!TileId::try_new(10, 539, 352).unwrap().contains_point(pos));
+        assert!(!TileId::try_new(10, 538, 353).unwrap().contains_point(pos));
+    }
+
+    #[test]
+    fn each_point_belongs_to_a_single_tile() {
+        let tile = TileId::try_new(1, 0, 0).unwrap();
+        let corner = tile.bbox().southwest();
+        let neighbours = [(1, 0), (0, 1), (1, 1)];
+        assert_eq!(
+            1,
+            std::iter::once((0, 0))
+                .chain(neighbours.iter().copied())
+                .filter(|(x, y)| TileId::try_new(1, *x, *y).unwrap().contains_point(corner))
+                .count()
+        );
+    }
+
+    #[test]
+    fn grid_cells() {
+        let tile = TileId::try_new(0, 0, 0).unwrap();
+        assert_eq!(
+            Some((0, 0)),
+            tile.grid_cell(MapPoint::from_lat_lng_deg(80.0, -179.0), 8)
+        );
+        assert_eq!(
+            Some((7, 7)),
+            tile.grid_cell(MapPoint::from_lat_lng_deg(-80.0, 179.0), 8)
+        );
+        assert_eq!(
+            Some((4, 4)),
+            tile.grid_cell(MapPoint::from_lat_lng_deg(-1.0, 1.0), 8)
+        );
+        let tile = TileId::try_new(1, 1, 1).unwrap();
+        assert_eq!(
+            None,
+            tile.grid_cell(MapPoint::from_lat_lng_deg(1.0, 1.0), 8)
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use ofdb_entities::{time::*, webhook::*};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// The maximum number of delivery attempts before
+/// a delivery is considered as failed permanently.
+pub const MAX_DELIVERY_ATTEMPTS: u32 = 8;
+
+/// The delay after the first failed attempt.
+///
+/// The delay is doubled after each subsequent attempt, i.e.
+/// the last retry happens about 1 hour after the first attempt.
+pub const INITIAL_RETRY_DELAY_SECONDS: i64 = 30;
+
+/// Webhooks must not be abused for sending requests to
+/// the server itself or to other hosts in its network.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => is_public_ipv6(ip),
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    // Shared address space for carrier-grade NAT (100.64.0.0/10)
+    let is_shared = a == 100 && (b & 0b1100_0000) == 64;
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || is_shared
+        || a == 0)
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    let segments = ip.segments();
+    // IPv4-mapped addresses (::ffff:0:0/96)
+    if segments[..5] == [0; 5] && segments[5] == 0xffff {
+        let [a, b] = segments[6].to_be_bytes();
+        let [c, d] = segments[7].to_be_bytes();
+        return is_public_ipv4(Ipv4Addr::new(a, b, c, d));
+    }
+    // Unique local (fc00::/7) and link-local (fe80::/10) addresses
+    let is_unique_local = (segments[0] & 0xfe00) == 0xfc00;
+    let is_unicast_link_local = (segments[0] & 0xffc0) == 0xfe80;
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        || is_unique_local
+        || is_unicast_link_local)
+}
+
+/// Checks if an HTTP response status code acknowledges a delivery.
+pub fn is_success_status(status: u16) -> bool {
+    (200..300).contains(&status)
+}
+
+/// The time of the next attempt after an unsuccessful attempt
+/// with exponential backoff or `None` if no retries are left.
+pub fn next_attempt_at(attempts: u32, last_attempt_at: TimestampMs) -> Option<TimestampMs> {
+    debug_assert!(attempts > 0);
+    if attempts >= MAX_DELIVERY_ATTEMPTS {
+        return None;
+    }
+    let delay_seconds = INITIAL_RETRY_DELAY_SECONDS << (attempts - 1);
+    Some(TimestampMs::from_inner(
+        last_attempt_at.into_inner() + delay_seconds * 1000,
+    ))
+}
+
+/// Records the outcome of a delivery attempt.
+///
+/// The `result` either contains the HTTP status code of the
+/// response or an error message if no response has been received.
+pub fn record_delivery_attempt(
+    delivery: &mut WebhookDelivery,
+    at: TimestampMs,
+    result: Result<u16, String>,
+) {
+    delivery.attempts += 1;
+    delivery.last_attempt_at = Some(at);
+    match result {
+        Ok(status) => {
+            delivery.last_response_status = Some(status);
+            delivery.last_error = None;
+            if is_success_status(status) {
+                delivery.delivered_at = Some(at);
+                delivery.next_attempt_at = None;
+                return;
+            }
+        }
+        Err(err) => {
+            delivery.last_response_status = None;
+            delivery.last_error = Some(err);
+        }
+    }
+    delivery.next_attempt_at = next_attempt_at(delivery.attempts, at);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ofdb_entities::id::Id;
+
+    fn new_delivery() -> WebhookDelivery {
+        WebhookDelivery {
+            id: Id::new(),
+            webhook_id: Id::new(),
+            event_type: WebhookEventType::PlaceChanged,
+            payload: "{}".into(),
+            created_at: TimestampMs::from_inner(0),
+            attempts: 0,
+            next_attempt_at: Some(TimestampMs::from_inner(0)),
+            last_attempt_at: None,
+            last_response_status: None,
+            last_error: None,
+            delivered_at: None,
+        }
+    }
+
+    #[test]
+    fn exponential_backoff() {
+        let at = TimestampMs::from_inner(1_000_000);
+        assert_eq!(
+            Some(TimestampMs::from_inner(1_030_000)),
+            next_attempt_at(1, at)
+        );
+        assert_eq!(
+            Some(TimestampMs::from_inner(1_060_000)),
+            next_attempt_at(2, at)
+        );
+        assert_eq!(
+            Some(TimestampMs::from_inner(1_120_000)),
+            next_attempt_at(3, at)
+        );
+        assert!(next_attempt_at(MAX_DELIVERY_ATTEMPTS - 1, at).is_some());
+        assert!(next_attempt_at(MAX_DELIVERY_ATTEMPTS, at).is_none());
+    }
+
+    #[test]
+    fn successful_delivery() {
+        let mut d = new_delivery();
+        let at = TimestampMs::from_inner(1_000);
+        record_delivery_attempt(&mut d, at, Ok(204));
+        assert_eq!(WebhookDeliveryStatus::Delivered, d.status());
+        assert_eq!(1, d.attempts);
+        assert_eq!(Some(at), d.delivered_at);
+        assert_eq!(Some(204), d.last_response_status);
+    }
+
+    #[test]
+    fn retry_until_failed() {
+        let mut d = new_delivery();
+        let at = TimestampMs::from_inner(1_000);
+        record_delivery_attempt(&mut d, at, Ok(500));
+        assert_eq!(WebhookDeliveryStatus::Pending, d.status());
+        assert_eq!(Some(500), d.last_response_status);
+        record_delivery_attempt(&mut d, at, Err("connection refused".into()));
+        assert_eq!(WebhookDeliveryStatus::Pending, d.status());
+        assert_eq!(None, d.last_response_status);
+        assert_eq!(Some("connection refused"), d.last_error.as_deref());
+        for _ in 2..MAX_DELIVERY_ATTEMPTS {
+            assert_eq!(WebhookDeliveryStatus::Pending, d.status());
+            record_delivery_attempt(&mut d, at, Ok(404));
+        }
+        assert_eq!(MAX_DELIVERY_ATTEMPTS, d.attempts);
+        assert_eq!(WebhookDeliveryStatus::Failed, d.status());
+        assert!(d.delivered_at.is_none());
+    }
+
+    #[test]
+    fn reject_non_public_ip_addresses() {
+        let is_public = |ip: &str| is_public_ip(ip.parse().unwrap());
+        assert!(is_public("93.184.216.34"));
+        assert!(is_public("2606:2800:220:1:248:1893:25c8:1946"));
+        for ip in &[
+            "0.0.0.0",
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.178.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "255.255.255.255",
+            "::",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254",
+        ] {
+            assert!(!is_public(ip), "{}", ip);
+        }
+    }
+}
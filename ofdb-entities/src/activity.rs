@@ -19,9 +19,29 @@ impl Activity {
     }
 }
 
+/// The context of activities of the system itself, e.g.
+/// maintenance tasks, that have not been performed by any user.
+pub const SYSTEM_CONTEXT: &str = "system";
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ActivityLog {
     pub activity: Activity,
     pub context: Option<String>,
     pub comment: Option<String>,
 }
+
+impl ActivityLog {
+    /// An activity of the system that is distinguished from
+    /// anonymous activities by its context.
+    pub fn system(comment: impl Into<String>) -> Self {
+        Self {
+            activity: Activity::now(None),
+            context: Some(SYSTEM_CONTEXT.into()),
+            comment: Some(comment.into()),
+        }
+    }
+
+    pub fn is_system(&self) -> bool {
+        self.context.as_deref() == Some(SYSTEM_CONTEXT)
+    }
+}
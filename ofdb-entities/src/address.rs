@@ -1,3 +1,5 @@
+use crate::time::TimestampMs;
+
 #[rustfmt::skip]
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct Address {
@@ -17,3 +19,25 @@ impl Address {
             && self.state.is_none()
     }
 }
+
+/// The location of an address that has been resolved
+/// by a geocoder.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedGeoCode {
+    /// The normalized address
+    pub address: String,
+    /// `None` if the geocoder could not resolve the address
+    pub lat_lng: Option<(f64, f64)>,
+    pub created_at: TimestampMs,
+}
+
+/// The address of a location that has been resolved
+/// by a geocoder.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedAddress {
+    /// The normalized location
+    pub pos: String,
+    /// `None` if the geocoder could not resolve the location
+    pub address: Option<Address>,
+    pub created_at: TimestampMs,
+}
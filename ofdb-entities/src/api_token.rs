@@ -0,0 +1,111 @@
+use crate::{id::*, time::*};
+
+use std::{fmt, str::FromStr};
+use strum::{EnumIter, IntoEnumIterator};
+
+/// Permissions that can be granted to an API token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
+pub enum ApiTokenScope {
+    EventsWrite,
+    EventsDelete,
+    ClearanceRead,
+    ClearanceWrite,
+    PlacesWrite,
+}
+
+impl ApiTokenScope {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::EventsWrite => "events:write",
+            Self::EventsDelete => "events:delete",
+            Self::ClearanceRead => "clearance:read",
+            Self::ClearanceWrite => "clearance:write",
+            Self::PlacesWrite => "places:write",
+        }
+    }
+
+    pub fn all() -> Vec<Self> {
+        Self::iter().collect()
+    }
+}
+
+impl fmt::Display for ApiTokenScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApiTokenScopeParseError;
+
+impl FromStr for ApiTokenScope {
+    type Err = ApiTokenScopeParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "events:write" => Ok(Self::EventsWrite),
+            "events:delete" => Ok(Self::EventsDelete),
+            "clearance:read" => Ok(Self::ClearanceRead),
+            "clearance:write" => Ok(Self::ClearanceWrite),
+            "places:write" => Ok(Self::PlacesWrite),
+            _ => Err(ApiTokenScopeParseError),
+        }
+    }
+}
+
+/// A named API token of an organization with limited permissions.
+///
+/// Only a hash of the secret token is stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiToken {
+    pub id: Id,
+    pub org_id: Id,
+    pub name: String,
+    pub token_hash: String,
+    pub scopes: Vec<ApiTokenScope>,
+    pub created_at: TimestampMs,
+    pub expires_at: Option<TimestampMs>,
+    pub last_used_at: Option<TimestampMs>,
+}
+
+impl ApiToken {
+    pub fn is_expired(&self, now: TimestampMs) -> bool {
+        self.expires_at
+            .map_or(false, |expires_at| expires_at <= now)
+    }
+
+    pub fn has_scopes(&self, scopes: &[ApiTokenScope]) -> bool {
+        scopes.iter().all(|scope| self.scopes.contains(scope))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_scopes() {
+        for scope in ApiTokenScope::iter() {
+            assert_eq!(scope, scope.as_str().parse().unwrap());
+        }
+        assert!("events:read".parse::<ApiTokenScope>().is_err());
+    }
+
+    #[test]
+    fn check_scopes() {
+        let token = ApiToken {
+            id: Id::new(),
+            org_id: Id::new(),
+            name: "test".into(),
+            token_hash: "hash".into(),
+            scopes: vec![ApiTokenScope::EventsWrite, ApiTokenScope::EventsDelete],
+            created_at: TimestampMs::from_inner(1000),
+            expires_at: Some(TimestampMs::from_inner(2000)),
+            last_used_at: None,
+        };
+        assert!(token.has_scopes(&[]));
+        assert!(token.has_scopes(&[ApiTokenScope::EventsDelete]));
+        assert!(!token.has_scopes(&[ApiTokenScope::EventsWrite, ApiTokenScope::PlacesWrite]));
+        assert!(!token.is_expired(TimestampMs::from_inner(1999)));
+        assert!(token.is_expired(TimestampMs::from_inner(2000)));
+    }
+}
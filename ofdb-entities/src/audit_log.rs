@@ -0,0 +1,64 @@
+use crate::time::*;
+
+use std::{fmt, str::FromStr};
+
+/// Actions of administrators on user accounts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AdminAction {
+    ChangeRole,
+    DisableUser,
+    EnableUser,
+    ConfirmEmail,
+    ResetPassword,
+    UnlockLogin,
+}
+
+impl AdminAction {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::ChangeRole => "user.change-role",
+            Self::DisableUser => "user.disable",
+            Self::EnableUser => "user.enable",
+            Self::ConfirmEmail => "user.confirm-email",
+            Self::ResetPassword => "user.reset-password",
+            Self::UnlockLogin => "user.unlock-login",
+        }
+    }
+}
+
+impl fmt::Display for AdminAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdminActionParseError;
+
+impl FromStr for AdminAction {
+    type Err = AdminActionParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "user.change-role" => Ok(Self::ChangeRole),
+            "user.disable" => Ok(Self::DisableUser),
+            "user.enable" => Ok(Self::EnableUser),
+            "user.confirm-email" => Ok(Self::ConfirmEmail),
+            "user.reset-password" => Ok(Self::ResetPassword),
+            "user.unlock-login" => Ok(Self::UnlockLogin),
+            _ => Err(AdminActionParseError),
+        }
+    }
+}
+
+/// A record of an action that an administrator
+/// has performed on a user account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditLogEntry {
+    pub at: TimestampMs,
+    /// The email address of the administrator
+    pub actor: String,
+    pub action: AdminAction,
+    /// The email address of the affected user
+    pub target: String,
+    pub details: Option<String>,
+}
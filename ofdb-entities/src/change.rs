@@ -0,0 +1,120 @@
+use crate::{geo::MapPoint, id::*, time::*};
+
+use std::str::FromStr;
+
+/// The kind of a modification of a place or an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeType {
+    Created,
+    Updated,
+    Reviewed,
+    Archived,
+}
+
+impl ChangeType {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Updated => "updated",
+            Self::Reviewed => "reviewed",
+            Self::Archived => "archived",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChangeTypeParseError;
+
+impl FromStr for ChangeType {
+    type Err = ChangeTypeParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "created" => Ok(Self::Created),
+            "updated" => Ok(Self::Updated),
+            "reviewed" => Ok(Self::Reviewed),
+            "archived" => Ok(Self::Archived),
+            _ => Err(ChangeTypeParseError),
+        }
+    }
+}
+
+/// The kind of entity that has been changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangedEntityKind {
+    Place,
+    Event,
+    Rating,
+}
+
+impl ChangedEntityKind {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Place => "place",
+            Self::Event => "event",
+            Self::Rating => "rating",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChangedEntityKindParseError;
+
+impl FromStr for ChangedEntityKind {
+    type Err = ChangedEntityKindParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "place" => Ok(Self::Place),
+            "event" => Ok(Self::Event),
+            "rating" => Ok(Self::Rating),
+            _ => Err(ChangedEntityKindParseError),
+        }
+    }
+}
+
+/// A committed change of a single entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityChange {
+    pub at: TimestampMs,
+    pub kind: ChangedEntityKind,
+    pub id: Id,
+    /// The rated place if the changed entity is a rating
+    pub place_id: Option<Id>,
+    pub change_type: ChangeType,
+    pub title: String,
+    pub pos: Option<MapPoint>,
+    pub tags: Vec<String>,
+}
+
+/// A change with its position in the persistent change log.
+///
+/// The sequence number is strictly increasing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeLogEntry {
+    pub seq: u64,
+    pub change: EntityChange,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_change_type_and_entity_kind() {
+        for t in &[
+            ChangeType::Created,
+            ChangeType::Updated,
+            ChangeType::Reviewed,
+            ChangeType::Archived,
+        ] {
+            assert_eq!(*t, t.as_str().parse().unwrap());
+        }
+        for k in &[
+            ChangedEntityKind::Place,
+            ChangedEntityKind::Event,
+            ChangedEntityKind::Rating,
+        ] {
+            assert_eq!(*k, k.as_str().parse().unwrap());
+        }
+        assert!("deleted".parse::<ChangeType>().is_err());
+    }
+}
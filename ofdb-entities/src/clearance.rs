@@ -1,4 +1,4 @@
-use crate::{id::Id, revision::Revision, time::TimestampMs};
+use crate::{id::Id, place::Place, revision::Revision, time::TimestampMs};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PendingClearanceForPlace {
@@ -12,3 +12,24 @@ pub struct ClearanceForPlace {
     pub place_id: Id,
     pub cleared_revision: Option<Revision>,
 }
+
+/// New pending clearances of an organization that are
+/// announced to its contacts within a single notification.
+#[derive(Debug, Clone)]
+pub struct PendingClearanceDigest {
+    pub org_id: Id,
+    pub org_name: String,
+    /// Owners and clearance reviewers of the organization
+    pub recipients: Vec<String>,
+    /// The first of the new pending clearances, the number
+    /// of places in a single digest is limited
+    pub places: Vec<Place>,
+    /// All new pending clearances including those
+    /// that are not listed
+    pub new_count: usize,
+    /// All pending clearances of the organization including
+    /// those that have been announced before
+    pub total_count: u64,
+    /// The creation time of the latest announced pending clearance
+    pub created_until: TimestampMs,
+}
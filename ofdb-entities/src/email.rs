@@ -1,3 +1,5 @@
+use crate::{id::*, time::*};
+
 use std::{borrow::Borrow, fmt, ops::Deref, str::FromStr};
 
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
@@ -59,3 +61,62 @@ impl fmt::Display for Email {
         f.write_str(self.as_ref())
     }
 }
+
+/// The composed content of an email.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailContent {
+    pub subject: String,
+    /// Plain text
+    pub body: String,
+    /// An optional HTML alternative of the plain text
+    pub html_body: Option<String>,
+    /// A one-click unsubscribe link (RFC 8058) for the
+    /// `List-Unsubscribe` header
+    pub unsubscribe_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutgoingEmailStatus {
+    Pending,
+    Sent,
+    /// Failed permanently after all attempts (dead letter)
+    Failed,
+}
+
+/// A single message in the email outbox.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutgoingEmail {
+    pub id: Id,
+    pub recipient: Email,
+    pub subject: String,
+    pub body: String,
+    pub html_body: Option<String>,
+    pub unsubscribe_url: Option<String>,
+    pub created_at: TimestampMs,
+    pub attempts: u32,
+    /// `None` if either sent or failed permanently
+    pub next_attempt_at: Option<TimestampMs>,
+    pub last_attempt_at: Option<TimestampMs>,
+    pub last_error: Option<String>,
+    pub sent_at: Option<TimestampMs>,
+}
+
+impl OutgoingEmail {
+    pub fn status(&self) -> OutgoingEmailStatus {
+        if self.sent_at.is_some() {
+            OutgoingEmailStatus::Sent
+        } else if self.next_attempt_at.is_some() {
+            OutgoingEmailStatus::Pending
+        } else {
+            OutgoingEmailStatus::Failed
+        }
+    }
+}
+
+/// The number of messages in the email outbox by status.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EmailOutboxStats {
+    pub pending: u64,
+    pub sent: u64,
+    pub failed: u64,
+}
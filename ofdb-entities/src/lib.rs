@@ -11,7 +11,10 @@
 
 pub mod activity;
 pub mod address;
+pub mod api_token;
+pub mod audit_log;
 pub mod category;
+pub mod change;
 pub mod clearance;
 pub mod comment;
 pub mod contact;
@@ -21,17 +24,22 @@ pub mod geo;
 pub mod id;
 pub mod links;
 pub mod location;
+pub mod login_throttle;
 pub mod nonce;
+pub mod oidc;
 pub mod organization;
 pub mod password;
 pub mod place;
 pub mod rating;
 pub mod review;
 pub mod revision;
+pub mod session;
 pub mod subscription;
 pub mod tag;
 pub mod time;
+pub mod two_factor;
 pub mod user;
+pub mod webhook;
 #[cfg(feature = "rusturl")]
 pub mod url {
     pub use url::{ParseError, Url};
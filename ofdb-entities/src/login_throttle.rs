@@ -0,0 +1,28 @@
+use crate::time::*;
+
+/// Failed logins are counted separately per account and per
+/// client.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LoginThrottleKey {
+    /// The email address that has been entered, regardless
+    /// if an account exists or not
+    Account(String),
+    /// The IP address of the client
+    Client(String),
+}
+
+/// Consecutive failed login attempts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailedLogins {
+    pub key: LoginThrottleKey,
+    pub count: u32,
+    pub last_failed_at: TimestampMs,
+    /// No login attempts are accepted before this time.
+    pub locked_until: Option<TimestampMs>,
+}
+
+impl FailedLogins {
+    pub fn is_locked(&self, now: TimestampMs) -> bool {
+        self.locked_until.map(|until| until > now).unwrap_or(false)
+    }
+}
@@ -118,6 +118,17 @@ pub struct UserToken {
     pub expires_at: Timestamp,
 }
 
+/// A pending change of the email address of a user.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct EmailChangeToken {
+    /// The current email address of the user
+    pub email: String,
+    /// The new email address and the nonce that has
+    /// been sent to it for confirmation
+    pub new_email_nonce: EmailNonce,
+    pub expires_at: Timestamp,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,17 @@
+use crate::time::*;
+
+/// The account of a user at an OpenID Connect provider
+/// that is linked to a local user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OidcAccount {
+    pub provider: String,
+    /// The stable identifier of the user at the provider
+    pub subject: String,
+    /// The email address of the local user
+    pub email: String,
+    /// The local user has been created on the first login
+    /// with this account, i.e. its role is managed by the
+    /// provider.
+    pub provisioned: bool,
+    pub created_at: TimestampMs,
+}
@@ -1,4 +1,6 @@
-use crate::id::Id;
+use crate::{api_token::ApiTokenScope, id::Id};
+
+use std::{fmt, str::FromStr};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ModeratedTag {
@@ -25,6 +27,99 @@ impl From<&str> for ModeratedTag {
 pub struct Organization {
     pub id: Id,
     pub name: String,
-    pub api_token: String,
     pub moderated_tags: Vec<ModeratedTag>,
 }
+
+/// The role of a user within an organization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OrganizationRole {
+    Owner,
+    Editor,
+    ClearanceReviewer,
+}
+
+impl OrganizationRole {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Owner => "owner",
+            Self::Editor => "editor",
+            Self::ClearanceReviewer => "clearance-reviewer",
+        }
+    }
+
+    /// The permissions that members with this role are granted
+    /// when acting on behalf of the organization.
+    pub fn scopes(self) -> Vec<ApiTokenScope> {
+        match self {
+            Self::Owner => ApiTokenScope::all(),
+            Self::Editor => vec![
+                ApiTokenScope::EventsWrite,
+                ApiTokenScope::EventsDelete,
+                ApiTokenScope::PlacesWrite,
+            ],
+            Self::ClearanceReviewer => {
+                vec![ApiTokenScope::ClearanceRead, ApiTokenScope::ClearanceWrite]
+            }
+        }
+    }
+
+    pub fn has_scopes(self, scopes: &[ApiTokenScope]) -> bool {
+        let granted = self.scopes();
+        scopes.iter().all(|scope| granted.contains(scope))
+    }
+}
+
+impl fmt::Display for OrganizationRole {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrganizationRoleParseError;
+
+impl FromStr for OrganizationRole {
+    type Err = OrganizationRoleParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "owner" => Ok(Self::Owner),
+            "editor" => Ok(Self::Editor),
+            "clearance-reviewer" => Ok(Self::ClearanceReviewer),
+            _ => Err(OrganizationRoleParseError),
+        }
+    }
+}
+
+/// A user who is a member of an organization.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrganizationMember {
+    pub org_id: Id,
+    pub email: String,
+    pub role: OrganizationRole,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_roles() {
+        for role in &[
+            OrganizationRole::Owner,
+            OrganizationRole::Editor,
+            OrganizationRole::ClearanceReviewer,
+        ] {
+            assert_eq!(*role, role.as_str().parse().unwrap());
+        }
+        assert!("admin".parse::<OrganizationRole>().is_err());
+    }
+
+    #[test]
+    fn check_role_scopes() {
+        assert!(OrganizationRole::Owner.has_scopes(&ApiTokenScope::all()));
+        assert!(OrganizationRole::ClearanceReviewer.has_scopes(&[ApiTokenScope::ClearanceWrite]));
+        assert!(!OrganizationRole::ClearanceReviewer.has_scopes(&[ApiTokenScope::PlacesWrite]));
+        assert!(!OrganizationRole::Editor.has_scopes(&[ApiTokenScope::ClearanceRead]));
+        assert!(OrganizationRole::Editor.has_scopes(&[]));
+    }
+}
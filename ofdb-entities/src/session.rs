@@ -0,0 +1,16 @@
+use crate::{id::*, time::*};
+
+/// A single-use token for obtaining a new access token.
+///
+/// Only a hash of the token is stored. All refresh tokens that
+/// have been derived from the same login share a session id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefreshToken {
+    pub token_hash: String,
+    pub session_id: Id,
+    pub email: String,
+    pub created_at: TimestampMs,
+    pub expires_at: TimestampMs,
+    /// When the token has been exchanged for a new one
+    pub used_at: Option<TimestampMs>,
+}
@@ -1,8 +1,347 @@
-use crate::{geo::*, id::*};
+use crate::{category::*, geo::*, id::*, time::*};
+
+use std::{fmt, str::FromStr, time::Duration};
+
+/// Kinds of entries that are observed by subscriptions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SubscriptionEntityKind {
+    Place,
+    Event,
+}
+
+impl SubscriptionEntityKind {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Place => "place",
+            Self::Event => "event",
+        }
+    }
+}
+
+impl fmt::Display for SubscriptionEntityKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubscriptionEntityKindParseError;
+
+impl FromStr for SubscriptionEntityKind {
+    type Err = SubscriptionEntityKindParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "place" => Ok(Self::Place),
+            "event" => Ok(Self::Event),
+            _ => Err(SubscriptionEntityKindParseError),
+        }
+    }
+}
+
+/// Types of changes of entries that are observed by subscriptions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SubscriptionChangeType {
+    Created,
+    Updated,
+    Reviewed,
+}
+
+impl SubscriptionChangeType {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Updated => "updated",
+            Self::Reviewed => "reviewed",
+        }
+    }
+}
+
+impl fmt::Display for SubscriptionChangeType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubscriptionChangeTypeParseError;
+
+impl FromStr for SubscriptionChangeType {
+    type Err = SubscriptionChangeTypeParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "created" => Ok(Self::Created),
+            "updated" => Ok(Self::Updated),
+            "reviewed" => Ok(Self::Reviewed),
+            _ => Err(SubscriptionChangeTypeParseError),
+        }
+    }
+}
+
+/// How subscribers are notified about changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SubscriptionDeliveryMode {
+    /// One email per change
+    Immediate,
+    /// One digest per day
+    Daily,
+    /// One digest per week
+    Weekly,
+}
+
+impl SubscriptionDeliveryMode {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Immediate => "immediate",
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+        }
+    }
+
+    /// The minimum time between two digests.
+    pub const fn digest_period(self) -> Option<Duration> {
+        match self {
+            Self::Immediate => None,
+            Self::Daily => Some(Duration::from_secs(24 * 60 * 60)),
+            Self::Weekly => Some(Duration::from_secs(7 * 24 * 60 * 60)),
+        }
+    }
+}
+
+impl Default for SubscriptionDeliveryMode {
+    fn default() -> Self {
+        Self::Immediate
+    }
+}
+
+impl fmt::Display for SubscriptionDeliveryMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubscriptionDeliveryModeParseError;
+
+impl FromStr for SubscriptionDeliveryMode {
+    type Err = SubscriptionDeliveryModeParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "immediate" => Ok(Self::Immediate),
+            "daily" => Ok(Self::Daily),
+            "weekly" => Ok(Self::Weekly),
+            _ => Err(SubscriptionDeliveryModeParseError),
+        }
+    }
+}
+
+/// A change of an entry that is matched against subscriptions.
+#[derive(Debug, Clone, Copy)]
+pub struct ObservedChange<'a> {
+    pub entity_kind: SubscriptionEntityKind,
+    pub change_type: SubscriptionChangeType,
+    /// The tags of the entry including the tags of its categories
+    pub tags: &'a [String],
+}
+
+/// Restricts the changes that are observed by a subscription.
+///
+/// Empty lists and missing values don't restrict the changes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SubscriptionFilter {
+    /// Entries with at least one of these tags
+    pub tags: Vec<String>,
+    /// Entries of at least one of these categories
+    pub categories: Vec<Id>,
+    /// Either places or events
+    pub entity_kind: Option<SubscriptionEntityKind>,
+    pub change_types: Vec<SubscriptionChangeType>,
+}
+
+impl SubscriptionFilter {
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+            && self.categories.is_empty()
+            && self.entity_kind.is_none()
+            && self.change_types.is_empty()
+    }
+
+    pub fn matches(&self, change: &ObservedChange) -> bool {
+        if let Some(entity_kind) = self.entity_kind {
+            if entity_kind != change.entity_kind {
+                return false;
+            }
+        }
+        if !self.change_types.is_empty() && !self.change_types.contains(&change.change_type) {
+            return false;
+        }
+        if !self.tags.is_empty() && !self.tags.iter().any(|t| change.tags.contains(t)) {
+            return false;
+        }
+        if !self.categories.is_empty() {
+            let category_tags = Category::merge_ids_into_tags(&self.categories, vec![]);
+            // Events are not tagged with their category
+            let is_event_category = change.entity_kind == SubscriptionEntityKind::Event
+                && category_tags.iter().any(|t| t == Category::TAG_EVENT);
+            if !is_event_category && !category_tags.iter().any(|t| change.tags.contains(t)) {
+                return false;
+            }
+        }
+        true
+    }
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct BboxSubscription {
     pub id: Id,
     pub user_email: String,
+    /// An optional name for distinguishing multiple subscriptions
+    pub name: String,
     pub bbox: MapBbox,
+    pub filter: SubscriptionFilter,
+    pub delivery_mode: SubscriptionDeliveryMode,
+}
+
+impl BboxSubscription {
+    pub fn matches(&self, pos: MapPoint, change: &ObservedChange) -> bool {
+        self.bbox.contains_point(pos) && self.filter.matches(change)
+    }
+}
+
+/// A user that is notified immediately about a change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubscriptionRecipient {
+    pub email: String,
+    /// One of the subscriptions of the user that match the change
+    pub subscription_id: Id,
+}
+
+/// A queued notification about a change that is
+/// delivered with the next digest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubscriptionNotification {
+    pub user_email: String,
+    /// The subscription that matched the change, unknown for
+    /// notifications that have been queued by former versions
+    pub subscription_id: Option<Id>,
+    pub delivery_mode: SubscriptionDeliveryMode,
+    pub created_at: TimestampMs,
+    pub entity_kind: SubscriptionEntityKind,
+    pub change_type: SubscriptionChangeType,
+    pub entity_id: Id,
+    /// The title of the entry at the time of the change
+    pub title: String,
+}
+
+/// The queued notifications of a single subscription
+/// within a digest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubscriptionDigestSection {
+    pub subscription_id: Option<Id>,
+    /// Empty if the subscription has no name
+    pub subscription_name: String,
+    /// Ordered by creation, oldest first
+    pub notifications: Vec<SubscriptionNotification>,
+}
+
+/// The queued notifications of all subscriptions of a user
+/// with the same delivery mode that are delivered in a
+/// single email.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubscriptionDigest {
+    pub user_email: String,
+    pub delivery_mode: SubscriptionDeliveryMode,
+    pub sections: Vec<SubscriptionDigestSection>,
+}
+
+impl SubscriptionDigest {
+    pub fn notification_count(&self) -> usize {
+        self.sections.iter().map(|s| s.notifications.len()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change<'a>(
+        entity_kind: SubscriptionEntityKind,
+        change_type: SubscriptionChangeType,
+        tags: &'a [String],
+    ) -> ObservedChange<'a> {
+        ObservedChange {
+            entity_kind,
+            change_type,
+            tags,
+        }
+    }
+
+    #[test]
+    fn empty_filter_matches_all_changes() {
+        let filter = SubscriptionFilter::default();
+        assert!(filter.is_empty());
+        assert!(filter.matches(&change(
+            SubscriptionEntityKind::Place,
+            SubscriptionChangeType::Created,
+            &[]
+        )));
+        assert!(filter.matches(&change(
+            SubscriptionEntityKind::Event,
+            SubscriptionChangeType::Reviewed,
+            &[]
+        )));
+    }
+
+    #[test]
+    fn match_changes_by_filter() {
+        let filter = SubscriptionFilter {
+            tags: vec!["bio".into(), "fair".into()],
+            categories: vec![Category::ID_NON_PROFIT.into()],
+            entity_kind: Some(SubscriptionEntityKind::Place),
+            change_types: vec![SubscriptionChangeType::Created],
+        };
+        let tags = vec!["fair".to_string(), "non-profit".to_string()];
+        assert!(filter.matches(&change(
+            SubscriptionEntityKind::Place,
+            SubscriptionChangeType::Created,
+            &tags
+        )));
+        assert!(!filter.matches(&change(
+            SubscriptionEntityKind::Place,
+            SubscriptionChangeType::Updated,
+            &tags
+        )));
+        assert!(!filter.matches(&change(
+            SubscriptionEntityKind::Event,
+            SubscriptionChangeType::Created,
+            &tags
+        )));
+        assert!(!filter.matches(&change(
+            SubscriptionEntityKind::Place,
+            SubscriptionChangeType::Created,
+            &["fair".to_string(), "commercial".to_string()]
+        )));
+        assert!(!filter.matches(&change(
+            SubscriptionEntityKind::Place,
+            SubscriptionChangeType::Created,
+            &["non-profit".to_string()]
+        )));
+    }
+
+    #[test]
+    fn match_events_by_category() {
+        let filter = SubscriptionFilter {
+            categories: vec![Category::ID_EVENT.into()],
+            ..Default::default()
+        };
+        assert!(filter.matches(&change(
+            SubscriptionEntityKind::Event,
+            SubscriptionChangeType::Updated,
+            &[]
+        )));
+        assert!(!filter.matches(&change(
+            SubscriptionEntityKind::Place,
+            SubscriptionChangeType::Updated,
+            &["non-profit".to_string()]
+        )));
+    }
 }
@@ -0,0 +1,25 @@
+use crate::time::*;
+
+/// The shared secret of a user for time-based one-time
+/// passwords (TOTP).
+///
+/// The second factor is only enforced after the enrollment
+/// has been confirmed with a valid code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserTotp {
+    pub email: String,
+    /// Base32 encoded
+    pub secret: String,
+    pub created_at: TimestampMs,
+    pub confirmed_at: Option<TimestampMs>,
+    /// The most recent time step that has been used for
+    /// logging in, i.e. codes of this or any preceding time
+    /// step must not be accepted again.
+    pub last_used_step: Option<i64>,
+}
+
+impl UserTotp {
+    pub fn is_enabled(&self) -> bool {
+        self.confirmed_at.is_some()
+    }
+}
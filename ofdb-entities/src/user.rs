@@ -1,5 +1,6 @@
 use crate::password::Password;
 use num_derive::{FromPrimitive, ToPrimitive};
+use std::{fmt, str::FromStr};
 
 #[rustfmt::skip]
 #[derive(Debug, Clone, PartialEq)]
@@ -8,6 +9,9 @@ pub struct User {
     pub email_confirmed : bool,
     pub password        : Password,
     pub role            : Role,
+    pub disabled        : bool,
+    /// The preferred language for emails
+    pub language        : Option<Language>,
 }
 
 #[rustfmt::skip]
@@ -24,3 +28,95 @@ impl Default for Role {
         Role::Guest
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoleParseError;
+
+impl FromStr for Role {
+    type Err = RoleParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "guest" => Ok(Role::Guest),
+            "user" => Ok(Role::User),
+            "scout" => Ok(Role::Scout),
+            "admin" => Ok(Role::Admin),
+            _ => Err(RoleParseError),
+        }
+    }
+}
+
+/// Languages in which users are addressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    German,
+    English,
+}
+
+impl Language {
+    /// The ISO 639-1 code
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::German => "de",
+            Self::English => "en",
+        }
+    }
+}
+
+impl Default for Language {
+    fn default() -> Self {
+        Self::German
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LanguageParseError;
+
+impl FromStr for Language {
+    type Err = LanguageParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "de" => Ok(Self::German),
+            "en" => Ok(Self::English),
+            _ => Err(LanguageParseError),
+        }
+    }
+}
+
+/// Changes of the entries that a user has created or
+/// edited which the user might want to be notified about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthorNotificationKind {
+    /// The review status has been changed by a scout
+    Review,
+    /// A new rating with a comment has been added
+    Rating,
+    /// A new revision is waiting for the clearance
+    /// of an organization
+    Clearance,
+}
+
+/// Opt-in notifications about changes of one's own entries.
+#[rustfmt::skip]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuthorNotifications {
+    pub user_email : String,
+    pub reviews    : bool,
+    pub ratings    : bool,
+    pub clearances : bool,
+}
+
+impl AuthorNotifications {
+    pub fn is_enabled(&self, kind: AuthorNotificationKind) -> bool {
+        match kind {
+            AuthorNotificationKind::Review => self.reviews,
+            AuthorNotificationKind::Rating => self.ratings,
+            AuthorNotificationKind::Clearance => self.clearances,
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{id::*, time::*, url::Url};
+
+use std::{fmt, str::FromStr};
+use strum::{EnumCount, EnumIter};
+
+/// Types of events that are delivered to webhooks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, EnumCount)]
+pub enum WebhookEventType {
+    /// A place with one of the moderated tags of an
+    /// organization has been created or updated
+    PlaceChanged,
+    /// A new revision of a place requires clearance
+    ClearancePending,
+    /// An event with one of the moderated tags of an
+    /// organization has been created, updated or archived
+    EventChanged,
+}
+
+impl WebhookEventType {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::PlaceChanged => "place.changed",
+            Self::ClearancePending => "clearance.pending",
+            Self::EventChanged => "event.changed",
+        }
+    }
+}
+
+impl fmt::Display for WebhookEventType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WebhookEventTypeParseError;
+
+impl FromStr for WebhookEventType {
+    type Err = WebhookEventTypeParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "place.changed" => Ok(Self::PlaceChanged),
+            "clearance.pending" => Ok(Self::ClearancePending),
+            "event.changed" => Ok(Self::EventChanged),
+            _ => Err(WebhookEventTypeParseError),
+        }
+    }
+}
+
+/// An URL of an organization that receives signed notifications.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Webhook {
+    pub id: Id,
+    pub org_id: Id,
+    pub url: Url,
+    /// The shared secret for signing payloads
+    pub secret: String,
+    pub event_types: Vec<WebhookEventType>,
+    pub created_at: TimestampMs,
+}
+
+impl Webhook {
+    pub fn is_subscribed_to(&self, event_type: WebhookEventType) -> bool {
+        self.event_types.contains(&event_type)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+/// A single notification in the outbox of a webhook.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookDelivery {
+    pub id: Id,
+    pub webhook_id: Id,
+    pub event_type: WebhookEventType,
+    /// JSON
+    pub payload: String,
+    pub created_at: TimestampMs,
+    pub attempts: u32,
+    /// `None` if either delivered or failed permanently
+    pub next_attempt_at: Option<TimestampMs>,
+    pub last_attempt_at: Option<TimestampMs>,
+    /// HTTP status code of the last response
+    pub last_response_status: Option<u16>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<TimestampMs>,
+}
+
+impl WebhookDelivery {
+    pub fn status(&self) -> WebhookDeliveryStatus {
+        if self.delivered_at.is_some() {
+            WebhookDeliveryStatus::Delivered
+        } else if self.next_attempt_at.is_some() {
+            WebhookDeliveryStatus::Pending
+        } else {
+            WebhookDeliveryStatus::Failed
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn event_type_string_roundtrip() {
+        for t in WebhookEventType::iter() {
+            assert_eq!(Ok(t), t.as_str().parse());
+        }
+        assert!("place.deleted".parse::<WebhookEventType>().is_err());
+    }
+}
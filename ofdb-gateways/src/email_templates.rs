@@ -0,0 +1,257 @@
+use ofdb_entities::{email::EmailContent, user::Language};
+use serde::Serialize;
+use std::{fs, io, path::Path};
+use tera::{Context, Tera};
+
+pub use tera::{Error, Result};
+
+macro_rules! builtin_templates {
+    ($($lang:literal / $name:literal),* $(,)?) => {
+        &[$((
+            concat!($lang, "/", $name),
+            include_str!(concat!("../templates/email/", $lang, "/", $name)),
+        )),*]
+    };
+}
+
+macro_rules! builtin_languages {
+    ($($lang:literal),*) => {
+        builtin_templates!($(
+            $lang / "base.html",
+            $lang / "signature.txt",
+            $lang / "place.txt",
+            $lang / "place.html",
+            $lang / "event.txt",
+            $lang / "event.html",
+            $lang / "user_registration.subject.txt",
+            $lang / "user_registration.txt",
+            $lang / "user_registration.html",
+            $lang / "user_reset_password.subject.txt",
+            $lang / "user_reset_password.txt",
+            $lang / "user_reset_password.html",
+            $lang / "user_login_locked.subject.txt",
+            $lang / "user_login_locked.txt",
+            $lang / "user_login_locked.html",
+            $lang / "user_email_change.subject.txt",
+            $lang / "user_email_change.txt",
+            $lang / "user_email_change.html",
+            $lang / "user_email_changed.subject.txt",
+            $lang / "user_email_changed.txt",
+            $lang / "user_email_changed.html",
+            $lang / "place_created.subject.txt",
+            $lang / "place_created.txt",
+            $lang / "place_created.html",
+            $lang / "place_updated.subject.txt",
+            $lang / "place_updated.txt",
+            $lang / "place_updated.html",
+            $lang / "place_reviewed.subject.txt",
+            $lang / "place_reviewed.txt",
+            $lang / "place_reviewed.html",
+            $lang / "own_place.txt",
+            $lang / "own_place.html",
+            $lang / "own_place_reviewed.subject.txt",
+            $lang / "own_place_reviewed.txt",
+            $lang / "own_place_reviewed.html",
+            $lang / "own_place_rated.subject.txt",
+            $lang / "own_place_rated.txt",
+            $lang / "own_place_rated.html",
+            $lang / "own_place_clearance_pending.subject.txt",
+            $lang / "own_place_clearance_pending.txt",
+            $lang / "own_place_clearance_pending.html",
+            $lang / "event_created.subject.txt",
+            $lang / "event_created.txt",
+            $lang / "event_created.html",
+            $lang / "event_updated.subject.txt",
+            $lang / "event_updated.txt",
+            $lang / "event_updated.html",
+            $lang / "subscription_digest.subject.txt",
+            $lang / "subscription_digest.txt",
+            $lang / "subscription_digest.html",
+            $lang / "pending_clearance_digest.subject.txt",
+            $lang / "pending_clearance_digest.txt",
+            $lang / "pending_clearance_digest.html"
+        ),*)
+    };
+}
+
+/// The templates that are compiled into the binary.
+const BUILTIN_TEMPLATES: &[(&str, &str)] = builtin_languages!("de", "en");
+
+fn builtin_tera() -> Result<Tera> {
+    let mut tera = Tera::default();
+    tera.add_raw_templates(BUILTIN_TEMPLATES.iter().copied())?;
+    Ok(tera)
+}
+
+/// Renders the subject, the plain text and the HTML
+/// alternative of all emails.
+///
+/// Each email consists of the templates
+/// `<language>/<name>.subject.txt`, `<language>/<name>.txt`
+/// and `<language>/<name>.html`.
+pub struct EmailTemplates {
+    builtin: Tera,
+    overridden: Option<Tera>,
+}
+
+impl EmailTemplates {
+    /// Only the templates that are compiled into the binary.
+    pub fn builtin() -> Self {
+        Self {
+            builtin: builtin_tera().expect("valid builtin email templates"),
+            overridden: None,
+        }
+    }
+
+    /// Overrides the builtin templates with all files
+    /// found in the subdirectories of `dir` that are
+    /// named after a language, e.g. `en/user_registration.txt`.
+    pub fn with_overrides(dir: &Path) -> Result<Self> {
+        let builtin = builtin_tera()?;
+        let mut overridden = builtin.clone();
+        let overrides = read_overrides(dir).map_err(|err| {
+            Error::msg(format!(
+                "Failed to read email templates from {}: {}",
+                dir.display(),
+                err
+            ))
+        })?;
+        for (name, _) in &overrides {
+            info!("Overriding email template {}", name);
+        }
+        overridden.add_raw_templates(overrides)?;
+        Ok(Self {
+            builtin,
+            overridden: Some(overridden),
+        })
+    }
+
+    fn render_template(&self, template: &str, context: &Context) -> Result<String> {
+        if let Some(tera) = &self.overridden {
+            match tera.render(template, context) {
+                Ok(rendered) => return Ok(rendered),
+                Err(err) => {
+                    error!(
+                        "Failed to render overridden email template {}: {}",
+                        template, err
+                    );
+                }
+            }
+        }
+        self.builtin.render(template, context)
+    }
+
+    pub fn render<C: Serialize>(
+        &self,
+        language: Language,
+        name: &str,
+        context: &C,
+    ) -> Result<EmailContent> {
+        let context = Context::from_serialize(context)?;
+        let template = |suffix: &str| format!("{}/{}.{}", language.as_str(), name, suffix);
+        let subject = self.render_template(&template("subject.txt"), &context)?;
+        let body = self.render_template(&template("txt"), &context)?;
+        let html_body = self.render_template(&template("html"), &context)?;
+        Ok(EmailContent {
+            // The subject must not contain any line breaks
+            subject: subject.split_whitespace().collect::<Vec<_>>().join(" "),
+            body: body.trim().to_string(),
+            html_body: Some(html_body),
+            unsubscribe_url: None,
+        })
+    }
+}
+
+impl Default for EmailTemplates {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+fn read_overrides(dir: &Path) -> io::Result<Vec<(String, String)>> {
+    let mut overrides = Vec::new();
+    for lang_dir in fs::read_dir(dir)? {
+        let lang_dir = lang_dir?;
+        if !lang_dir.file_type()?.is_dir() {
+            continue;
+        }
+        let lang = lang_dir.file_name().to_string_lossy().into_owned();
+        if lang.parse::<Language>().is_err() {
+            warn!("Ignoring email templates of unsupported language: {}", lang);
+            continue;
+        }
+        for file in fs::read_dir(lang_dir.path())? {
+            let file = file?;
+            if !file.file_type()?.is_file() {
+                continue;
+            }
+            let name = format!("{}/{}", lang, file.file_name().to_string_lossy());
+            overrides.push((name, fs::read_to_string(file.path())?));
+        }
+    }
+    Ok(overrides)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs};
+
+    #[derive(Serialize)]
+    struct Registration {
+        url: &'static str,
+    }
+
+    #[test]
+    fn render_builtin_templates_in_all_languages() {
+        let templates = EmailTemplates::builtin();
+        let context = Registration {
+            url: "https://example.com/?token=<abc>",
+        };
+        let de = templates
+            .render(Language::German, "user_registration", &context)
+            .unwrap();
+        assert!(de.subject.contains("Email-Adresse"));
+        assert!(de.body.contains("https://example.com/?token=<abc>"));
+        let html = de.html_body.unwrap();
+        // HTML is escaped
+        assert!(html.contains("token=&lt;abc&gt;"));
+        let en = templates
+            .render(Language::English, "user_registration", &context)
+            .unwrap();
+        assert!(en.subject.contains("email address"));
+    }
+
+    #[test]
+    fn override_templates_from_directory() {
+        let dir = env::temp_dir().join(format!("ofdb-email-templates-{}", std::process::id()));
+        fs::create_dir_all(dir.join("en")).unwrap();
+        fs::create_dir_all(dir.join("xx")).unwrap();
+        fs::write(
+            dir.join("en").join("user_registration.subject.txt"),
+            "Welcome!\n",
+        )
+        .unwrap();
+        // Invalid templates are replaced by the builtin templates
+        fs::write(
+            dir.join("en").join("user_registration.txt"),
+            "{{ unknown_variable }}",
+        )
+        .unwrap();
+        let templates = EmailTemplates::with_overrides(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let context = Registration {
+            url: "https://example.com",
+        };
+        let en = templates
+            .render(Language::English, "user_registration", &context)
+            .unwrap();
+        assert_eq!("Welcome!", en.subject);
+        assert!(en.body.contains("https://example.com"));
+        let de = templates
+            .render(Language::German, "user_registration", &context)
+            .unwrap();
+        assert_ne!("Welcome!", de.subject);
+    }
+}
@@ -0,0 +1,244 @@
+use ofdb_core::gateways::geocode::GeoCodingGateway;
+use ofdb_entities::{
+    address::Address,
+    geo::{Distance, MapPoint},
+};
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+};
+
+#[derive(Debug, Clone)]
+struct Entry {
+    // ISO 3166-1 alpha-2 in lowercase
+    country: String,
+    zip: String,
+    city: String,
+    lat: f64,
+    lng: f64,
+}
+
+const MAX_REVERSE_DISTANCE: Distance = Distance::from_meters(10_000.0);
+
+/// Resolves postcodes and cities offline.
+///
+/// The gazetteer is loaded from a file in the format of the
+/// [GeoNames](https://download.geonames.org/export/zip/) postal
+/// code dumps, i.e. tab-separated lines with the country code,
+/// postal code and place name in the first and the latitude
+/// and longitude in the 10th and 11th column.
+///
+/// Streets are ignored and only countries that are given as
+/// two-letter codes are taken into account.
+///
+/// Locations are resolved to the postcode, place name and
+/// country code of the nearest entry within 10 km.
+#[derive(Debug, Clone, Default)]
+pub struct Gazetteer {
+    entries: Vec<Entry>,
+}
+
+impl Gazetteer {
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Lines that cannot be parsed are skipped.
+    pub fn from_reader<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut entries = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match parse_entry(&line) {
+                Some(entry) => entries.push(entry),
+                None => debug!("Skipping invalid gazetteer line {}", index + 1),
+            }
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn lookup(&self, addr: &Address) -> Option<&Entry> {
+        let normalize = |s: &Option<String>| {
+            s.as_deref()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_lowercase)
+        };
+        let country = normalize(&addr.country).filter(|c| c.len() == 2);
+        let zip = normalize(&addr.zip);
+        let city = normalize(&addr.city);
+        let in_country = |e: &&Entry| country.as_ref().map_or(true, |c| c == &e.country);
+        if let Some(zip) = zip {
+            let by_zip: Vec<_> = self
+                .entries
+                .iter()
+                .filter(in_country)
+                .filter(|e| e.zip.to_lowercase() == zip)
+                .collect();
+            if let Some(city) = &city {
+                // Postcodes might be shared by multiple places
+                if let Some(&entry) = by_zip.iter().find(|e| &e.city.to_lowercase() == city) {
+                    return Some(entry);
+                }
+            }
+            if let Some(&entry) = by_zip.first() {
+                return Some(entry);
+            }
+        }
+        let city = city?;
+        self.entries
+            .iter()
+            .filter(in_country)
+            .find(|e| e.city.to_lowercase() == city)
+    }
+
+    fn nearest(&self, pos: MapPoint) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .filter_map(|e| {
+                let distance = MapPoint::distance(pos, MapPoint::from_lat_lng_deg(e.lat, e.lng))?;
+                Some((e, distance))
+            })
+            .filter(|(_, distance)| *distance <= MAX_REVERSE_DISTANCE)
+            .min_by(|(_, d1), (_, d2)| d1.partial_cmp(d2).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(e, _)| e)
+    }
+}
+
+fn parse_entry(line: &str) -> Option<Entry> {
+    let columns: Vec<_> = line.split('\t').map(str::trim).collect();
+    let lat = columns.get(9)?.parse().ok()?;
+    let lng = columns.get(10)?.parse().ok()?;
+    Some(Entry {
+        country: columns.get(0)?.to_lowercase(),
+        zip: (*columns.get(1)?).to_owned(),
+        city: (*columns.get(2)?).to_owned(),
+        lat,
+        lng,
+    })
+}
+
+impl GeoCodingGateway for Gazetteer {
+    fn resolve_address_lat_lng(&self, addr: &Address) -> Option<(f64, f64)> {
+        let entry = self.lookup(addr)?;
+        debug!(
+            "Resolved address location from gazetteer: {} {}",
+            entry.zip, entry.city
+        );
+        Some((entry.lat, entry.lng))
+    }
+
+    fn resolve_lat_lng_address(&self, lat: f64, lng: f64) -> Option<Address> {
+        let pos = MapPoint::try_from_lat_lng_deg(lat, lng).ok()?;
+        let entry = self.nearest(pos)?;
+        debug!(
+            "Resolved address of location ({}, {}) from gazetteer: {} {}",
+            lat, lng, entry.zip, entry.city
+        );
+        Some(Address {
+            zip: Some(entry.zip.clone()),
+            city: Some(entry.city.clone()),
+            country: Some(entry.country.to_uppercase()),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAZETTEER: &str = "\
+DE\t10117\tBerlin\tBerlin\tBE\t\t00\tBerlin, Stadt\t11000\t52.5170\t13.3889\t4
+DE\t79098\tFreiburg im Breisgau\tBaden-Württemberg\tBW\t\t083\t\t08311\t47.9959\t7.8522\t4
+DE\t01945\tGuteborn\tBrandenburg\tBB\t\t00\t\t12066\t51.4167\t13.9333\t4
+DE\t01945\tHohenbocka\tBrandenburg\tBB\t\t00\t\t12066\t51.4310\t14.0098\t4
+AT\t1010\tWien\tWien\t09\t\t900\t\t90001\t48.2077\t16.3705\t4
+invalid line
+";
+
+    fn gazetteer() -> Gazetteer {
+        Gazetteer::from_reader(GAZETTEER.as_bytes()).unwrap()
+    }
+
+    fn address(zip: Option<&str>, city: Option<&str>, country: Option<&str>) -> Address {
+        Address {
+            zip: zip.map(Into::into),
+            city: city.map(Into::into),
+            country: country.map(Into::into),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn skip_invalid_lines() {
+        assert_eq!(5, gazetteer().len());
+    }
+
+    #[test]
+    fn resolve_by_postcode() {
+        let gw = gazetteer();
+        assert_eq!(
+            Some((47.9959, 7.8522)),
+            gw.resolve_address_lat_lng(&address(Some("79098"), None, None))
+        );
+        // Shared postcode
+        assert_eq!(
+            Some((51.4167, 13.9333)),
+            gw.resolve_address_lat_lng(&address(Some("01945"), None, None))
+        );
+        assert_eq!(
+            Some((51.4310, 14.0098)),
+            gw.resolve_address_lat_lng(&address(Some("01945"), Some("hohenbocka"), None))
+        );
+        assert_eq!(
+            None,
+            gw.resolve_address_lat_lng(&address(Some("79098"), None, Some("AT")))
+        );
+    }
+
+    #[test]
+    fn resolve_by_city() {
+        let gw = gazetteer();
+        assert_eq!(
+            Some((48.2077, 16.3705)),
+            gw.resolve_address_lat_lng(&address(None, Some(" WIEN "), Some("Österreich")))
+        );
+        // Unknown postcode
+        assert_eq!(
+            Some((52.5170, 13.3889)),
+            gw.resolve_address_lat_lng(&address(Some("99999"), Some("Berlin"), Some("de")))
+        );
+        assert_eq!(
+            None,
+            gw.resolve_address_lat_lng(&address(None, Some("Hamburg"), None))
+        );
+    }
+
+    #[test]
+    fn resolve_nearest_address() {
+        let gw = gazetteer();
+        assert_eq!(
+            Some(address(Some("01945"), Some("Hohenbocka"), Some("DE"))),
+            gw.resolve_lat_lng_address(51.43, 14.0)
+        );
+        assert_eq!(
+            Some(address(Some("1010"), Some("Wien"), Some("AT"))),
+            gw.resolve_lat_lng_address(48.21, 16.37)
+        );
+        // Too far away
+        assert_eq!(None, gw.resolve_lat_lng_address(53.55, 9.99));
+        assert_eq!(None, gw.resolve_lat_lng_address(91.0, 0.0));
+    }
+}
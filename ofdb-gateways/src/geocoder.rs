@@ -0,0 +1,300 @@
+use ofdb_core::gateways::geocode::GeoCodingGateway;
+use ofdb_entities::address::Address;
+use serde::Deserialize;
+use std::{
+    sync::{Mutex, MutexGuard},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The parts of an address as returned by the reverse
+/// geocoding APIs of OpenCage and Nominatim that both
+/// use the OpenStreetMap address tags.
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct AddressComponents {
+    road: Option<String>,
+    house_number: Option<String>,
+    postcode: Option<String>,
+    city: Option<String>,
+    town: Option<String>,
+    village: Option<String>,
+    state: Option<String>,
+    country: Option<String>,
+}
+
+impl From<AddressComponents> for Address {
+    fn from(from: AddressComponents) -> Self {
+        let AddressComponents {
+            road,
+            house_number,
+            postcode,
+            city,
+            town,
+            village,
+            state,
+            country,
+        } = from;
+        let street = match (road, house_number) {
+            (Some(road), Some(number)) => Some(format!("{} {}", road, number)),
+            (road, _) => road,
+        };
+        Self {
+            street,
+            zip: postcode,
+            city: city.or(town).or(village),
+            country,
+            state,
+        }
+    }
+}
+
+/// Asks multiple geocoders in turn until one of them
+/// resolves the address.
+#[derive(Default)]
+pub struct GeoCoderChain {
+    geocoders: Vec<Box<dyn GeoCodingGateway + Send + Sync>>,
+}
+
+impl GeoCoderChain {
+    pub fn push<G>(&mut self, geocoder: G)
+    where
+        G: GeoCodingGateway + Send + Sync + 'static,
+    {
+        self.geocoders.push(Box::new(geocoder));
+    }
+
+    pub fn len(&self) -> usize {
+        self.geocoders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.geocoders.is_empty()
+    }
+}
+
+impl GeoCodingGateway for GeoCoderChain {
+    fn resolve_address_lat_lng(&self, addr: &Address) -> Option<(f64, f64)> {
+        self.geocoders
+            .iter()
+            .find_map(|geocoder| geocoder.resolve_address_lat_lng(addr))
+    }
+
+    fn resolve_lat_lng_address(&self, lat: f64, lng: f64) -> Option<Address> {
+        self.geocoders
+            .iter()
+            .find_map(|geocoder| geocoder.resolve_lat_lng_address(lat, lng))
+    }
+}
+
+/// Delays subsequent requests to a geocoder to comply
+/// with its usage policy, even if they are sent from
+/// multiple threads.
+///
+/// Requests that would have to wait longer than the
+/// maximum waiting time are not sent at all.
+pub struct ThrottledGeoCoder<'a> {
+    geocoder: &'a (dyn GeoCodingGateway + Send + Sync),
+    delay: Duration,
+    max_wait: Option<Duration>,
+    next_request: Mutex<Option<Instant>>,
+}
+
+impl<'a> ThrottledGeoCoder<'a> {
+    pub fn new(geocoder: &'a (dyn GeoCodingGateway + Send + Sync), delay: Duration) -> Self {
+        Self {
+            geocoder,
+            delay,
+            max_wait: None,
+            next_request: Mutex::new(None),
+        }
+    }
+
+    pub fn with_max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = Some(max_wait);
+        self
+    }
+
+    fn throttled<T>(&self, request: impl FnOnce() -> Option<T>) -> Option<T> {
+        let now = Instant::now();
+        let scheduled = {
+            let mut next_request = self.lock();
+            let scheduled = next_request.map_or(now, |next| next.max(now));
+            if let Some(max_wait) = self.max_wait {
+                if scheduled - now > max_wait {
+                    log::warn!("Skipping geocoding request: too many pending requests");
+                    return None;
+                }
+            }
+            // Reserve the time slot before waiting
+            *next_request = Some(scheduled + self.delay);
+            scheduled
+        };
+        if scheduled > now {
+            thread::sleep(scheduled - now);
+        }
+        request()
+    }
+
+    fn lock(&self) -> MutexGuard<Option<Instant>> {
+        match self.next_request.lock() {
+            Ok(guard) => guard,
+            Err(poison_err) => {
+                log::error!("A poisoned mutex guard for the ThrottledGeoCoder was found.");
+                poison_err.into_inner()
+            }
+        }
+    }
+}
+
+impl GeoCodingGateway for ThrottledGeoCoder<'_> {
+    fn resolve_address_lat_lng(&self, addr: &Address) -> Option<(f64, f64)> {
+        self.throttled(|| self.geocoder.resolve_address_lat_lng(addr))
+    }
+
+    fn resolve_lat_lng_address(&self, lat: f64, lng: f64) -> Option<Address> {
+        self.throttled(|| self.geocoder.resolve_lat_lng_address(lat, lng))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    /// A local stand-in for a geocoder that only knows
+    /// a single city.
+    struct CityGeoCoder {
+        city: &'static str,
+        lat_lng: (f64, f64),
+        requests: Arc<AtomicUsize>,
+    }
+
+    impl GeoCodingGateway for CityGeoCoder {
+        fn resolve_address_lat_lng(&self, addr: &Address) -> Option<(f64, f64)> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            if addr.city.as_deref() == Some(self.city) {
+                Some(self.lat_lng)
+            } else {
+                None
+            }
+        }
+
+        fn resolve_lat_lng_address(&self, lat: f64, lng: f64) -> Option<Address> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            if (lat, lng) == self.lat_lng {
+                Some(city(self.city))
+            } else {
+                None
+            }
+        }
+    }
+
+    fn city(city: &str) -> Address {
+        Address {
+            city: Some(city.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn ask_geocoders_in_turn() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let mut chain = GeoCoderChain::default();
+        assert_eq!(None, chain.resolve_address_lat_lng(&city("Berlin")));
+        chain.push(CityGeoCoder {
+            city: "Berlin",
+            lat_lng: (52.5, 13.4),
+            requests: requests.clone(),
+        });
+        chain.push(CityGeoCoder {
+            city: "Wien",
+            lat_lng: (48.2, 16.4),
+            requests: requests.clone(),
+        });
+        assert_eq!(2, chain.len());
+        assert_eq!(
+            Some((52.5, 13.4)),
+            chain.resolve_address_lat_lng(&city("Berlin"))
+        );
+        assert_eq!(1, requests.load(Ordering::SeqCst));
+        assert_eq!(
+            Some((48.2, 16.4)),
+            chain.resolve_address_lat_lng(&city("Wien"))
+        );
+        assert_eq!(3, requests.load(Ordering::SeqCst));
+        assert_eq!(None, chain.resolve_address_lat_lng(&city("Hamburg")));
+    }
+
+    #[test]
+    fn ask_geocoders_in_turn_for_addresses() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let mut chain = GeoCoderChain::default();
+        assert_eq!(None, chain.resolve_lat_lng_address(48.2, 16.4));
+        chain.push(CityGeoCoder {
+            city: "Berlin",
+            lat_lng: (52.5, 13.4),
+            requests: requests.clone(),
+        });
+        chain.push(CityGeoCoder {
+            city: "Wien",
+            lat_lng: (48.2, 16.4),
+            requests: requests.clone(),
+        });
+        assert_eq!(
+            Some(city("Wien")),
+            chain.resolve_lat_lng_address(48.2, 16.4)
+        );
+        assert_eq!(2, requests.load(Ordering::SeqCst));
+        assert_eq!(None, chain.resolve_lat_lng_address(0.0, 0.0));
+    }
+
+    #[test]
+    fn address_from_components() {
+        let components = AddressComponents {
+            road: Some("Unter den Linden".into()),
+            house_number: Some("1".into()),
+            postcode: Some("10117".into()),
+            town: Some("Berlin".into()),
+            state: Some("Berlin".into()),
+            country: Some("Deutschland".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            Address {
+                street: Some("Unter den Linden 1".into()),
+                zip: Some("10117".into()),
+                city: Some("Berlin".into()),
+                country: Some("Deutschland".into()),
+                state: Some("Berlin".into()),
+            },
+            Address::from(components)
+        );
+        // A house number without a street is useless
+        let components = AddressComponents {
+            house_number: Some("1".into()),
+            ..Default::default()
+        };
+        assert!(Address::from(components).is_empty());
+    }
+
+    #[test]
+    fn skip_requests_that_would_wait_too_long() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let geocoder = CityGeoCoder {
+            city: "Berlin",
+            lat_lng: (52.5, 13.4),
+            requests: requests.clone(),
+        };
+        let throttled = ThrottledGeoCoder::new(&geocoder, Duration::from_secs(60))
+            .with_max_wait(Duration::from_secs(1));
+        assert_eq!(
+            Some((52.5, 13.4)),
+            throttled.resolve_address_lat_lng(&city("Berlin"))
+        );
+        assert_eq!(None, throttled.resolve_address_lat_lng(&city("Berlin")));
+        assert_eq!(1, requests.load(Ordering::SeqCst));
+    }
+}
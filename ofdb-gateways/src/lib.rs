@@ -1,8 +1,17 @@
 #[macro_use]
 extern crate log;
 
+pub mod email_templates;
+pub mod gazetteer;
+pub mod geocoder;
 pub mod mailgun;
+pub mod nominatim;
 pub mod notify;
+pub mod oidc;
 pub mod opencage;
 pub mod sendmail;
+pub mod smtp;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 pub mod user_communication;
+pub mod webhook;
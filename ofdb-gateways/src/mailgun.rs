@@ -1,8 +1,8 @@
-use ofdb_core::gateways::email::EmailGateway;
+use ofdb_core::gateways::email::EmailSender;
 use ofdb_entities::email::*;
+use std::io::Result;
 #[cfg(not(test))]
 use std::io::{Error, ErrorKind};
-use std::{io::Result, thread};
 
 /// An email notification manager based on mailgun.net.
 #[derive(Debug, Clone)]
@@ -13,18 +13,6 @@ pub struct Mailgun {
     pub from_email: Email,
 }
 
-impl Mailgun {
-    fn send(&self, params: Vec<(&'static str, String)>) {
-        let url = self.api_url.clone();
-        let key = self.api_key.clone();
-        thread::spawn(move || {
-            if let Err(err) = send_raw(&url, &key, params) {
-                warn!("Could not send e-mail: {}", err);
-            }
-        });
-    }
-}
-
 #[cfg(not(test))]
 fn send_raw(url: &str, api_key: &str, params: Vec<(&'static str, String)>) -> Result<()> {
     let client = reqwest::blocking::Client::new();
@@ -57,26 +45,25 @@ fn send_raw(_: &str, _: &str, params: Vec<(&'static str, String)>) -> Result<()>
     Ok(())
 }
 
-impl EmailGateway for Mailgun {
-    fn compose_and_send(&self, recipients: &[Email], subject: &str, body: &str) {
-        if recipients.is_empty() {
-            warn!("No valid email adresses specified");
-            return;
-        }
-        debug!("Sending e-mails to: {:?}", recipients);
-        let recipients: String = recipients
-            .iter()
-            .map(std::ops::Deref::deref)
-            .map(String::as_str)
-            .collect::<Vec<_>>()
-            .join(",");
-
-        let params = vec![
+impl EmailSender for Mailgun {
+    fn send(&self, recipient: &Email, content: &EmailContent) -> std::result::Result<(), String> {
+        debug!("Sending e-mail to: {}", recipient);
+        let mut params = vec![
             ("from", (*self.from_email).clone()),
-            ("bcc", recipients),
-            ("subject", subject.to_owned()),
-            ("text", body.to_owned()),
+            ("to", (*recipient).clone()),
+            ("subject", content.subject.clone()),
+            ("text", content.body.clone()),
         ];
-        self.send(params);
+        if let Some(html_body) = &content.html_body {
+            params.push(("html", html_body.clone()));
+        }
+        if let Some(unsubscribe_url) = &content.unsubscribe_url {
+            params.push(("h:List-Unsubscribe", format!("<{}>", unsubscribe_url)));
+            params.push((
+                "h:List-Unsubscribe-Post",
+                "List-Unsubscribe=One-Click".into(),
+            ));
+        }
+        send_raw(&self.api_url, &self.api_key, params).map_err(|err| err.to_string())
     }
 }
@@ -0,0 +1,202 @@
+use crate::{geocoder::AddressComponents, opencage::address_to_forward_query_string};
+use ofdb_core::gateways::geocode::GeoCodingGateway;
+use ofdb_entities::address::Address;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// The public server of the OpenStreetMap Foundation.
+pub const DEFAULT_NOMINATIM_URL: &str = "https://nominatim.openstreetmap.org";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// The usage policy of the public server requires
+// an identifying user agent.
+const USER_AGENT: &str = "openfairdb (https://github.com/kartevonmorgen/openfairdb)";
+
+#[derive(Debug, Deserialize)]
+struct SearchResult {
+    lat: String,
+    lon: String,
+}
+
+// Locations without an address are reported with an error message.
+#[derive(Debug, Deserialize)]
+struct ReverseResult {
+    address: Option<AddressComponents>,
+}
+
+/// Resolves addresses with the search and reverse APIs of a Nominatim server.
+#[derive(Debug, Clone)]
+pub struct Nominatim {
+    url: String,
+    client: reqwest::blocking::Client,
+}
+
+impl Nominatim {
+    pub fn new(url: impl Into<String>) -> Self {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .user_agent(USER_AGENT)
+            .build()
+            .expect("HTTP client");
+        Self {
+            url: url.into(),
+            client,
+        }
+    }
+
+    fn search(&self, query: &str) -> Result<Option<(f64, f64)>, String> {
+        let url = format!("{}/search", self.url.trim_end_matches('/'));
+        let results: Vec<SearchResult> = self
+            .client
+            .get(&url)
+            .query(&[("q", query), ("format", "jsonv2"), ("limit", "1")])
+            .send()
+            .and_then(|res| res.error_for_status())
+            .and_then(|res| res.json())
+            .map_err(|err| err.to_string())?;
+        results
+            .into_iter()
+            .next()
+            .map(|r| match (r.lat.parse(), r.lon.parse()) {
+                (Ok(lat), Ok(lng)) => Ok((lat, lng)),
+                _ => Err(format!("Invalid coordinates: {}, {}", r.lat, r.lon)),
+            })
+            .transpose()
+    }
+
+    fn reverse(&self, lat: f64, lng: f64) -> Result<Option<Address>, String> {
+        let url = format!("{}/reverse", self.url.trim_end_matches('/'));
+        let result: ReverseResult = self
+            .client
+            .get(&url)
+            .query(&[
+                ("lat", lat.to_string().as_str()),
+                ("lon", lng.to_string().as_str()),
+                ("format", "jsonv2"),
+                ("addressdetails", "1"),
+            ])
+            .send()
+            .and_then(|res| res.error_for_status())
+            .and_then(|res| res.json())
+            .map_err(|err| err.to_string())?;
+        Ok(result
+            .address
+            .map(Address::from)
+            .filter(|addr| !addr.is_empty()))
+    }
+}
+
+impl GeoCodingGateway for Nominatim {
+    fn resolve_address_lat_lng(&self, addr: &Address) -> Option<(f64, f64)> {
+        if addr.is_empty() {
+            return None;
+        }
+        let query = address_to_forward_query_string(addr);
+        match self.search(&query) {
+            Ok(Some(lat_lng)) => {
+                debug!("Resolved address location '{}': {:?}", query, lat_lng);
+                Some(lat_lng)
+            }
+            Ok(None) => None,
+            Err(err) => {
+                warn!("Failed to resolve address location '{}': {}", query, err);
+                None
+            }
+        }
+    }
+
+    fn resolve_lat_lng_address(&self, lat: f64, lng: f64) -> Option<Address> {
+        match self.reverse(lat, lng) {
+            Ok(Some(addr)) => {
+                debug!(
+                    "Resolved address of location ({}, {}): {:?}",
+                    lat, lng, addr
+                );
+                Some(addr)
+            }
+            Ok(None) => None,
+            Err(err) => {
+                warn!(
+                    "Failed to resolve address of location ({}, {}): {}",
+                    lat, lng, err
+                );
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use std::sync::mpsc;
+
+    /// A local stand-in for a Nominatim server that responds
+    /// with the given JSON body and returns the request.
+    fn serve_once(body: &str) -> (String, mpsc::Receiver<String>) {
+        test_util::serve_once(200, body)
+    }
+
+    fn address() -> Address {
+        Address {
+            street: Some("Main Street 1".into()),
+            city: Some("Berlin".into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn resolve_first_search_result() {
+        let (url, rx) = serve_once(r#"[{"lat":"52.5170365","lon":"13.3888599"}]"#);
+        let gw = Nominatim::new(url);
+        assert_eq!(
+            Some((52.5170365, 13.3888599)),
+            gw.resolve_address_lat_lng(&address())
+        );
+        let request = rx.recv().unwrap();
+        assert!(request.starts_with("GET /search?q=Main+Street+1%2CBerlin&format=jsonv2"));
+    }
+
+    #[test]
+    fn unknown_address() {
+        let (url, _rx) = serve_once("[]");
+        let gw = Nominatim::new(url);
+        assert_eq!(None, gw.resolve_address_lat_lng(&address()));
+    }
+
+    #[test]
+    fn empty_address() {
+        // No request is sent
+        let gw = Nominatim::new("http://127.0.0.1:1");
+        assert_eq!(None, gw.resolve_address_lat_lng(&Address::default()));
+    }
+
+    #[test]
+    fn resolve_address_of_location() {
+        let (url, rx) = serve_once(
+            r#"{"lat":"52.5170365","lon":"13.3888599","address":{"road":"Unter den Linden","house_number":"1","postcode":"10117","city":"Berlin","state":"Berlin","country":"Deutschland","country_code":"de"}}"#,
+        );
+        let gw = Nominatim::new(url);
+        assert_eq!(
+            Some(Address {
+                street: Some("Unter den Linden 1".into()),
+                zip: Some("10117".into()),
+                city: Some("Berlin".into()),
+                country: Some("Deutschland".into()),
+                state: Some("Berlin".into()),
+            }),
+            gw.resolve_lat_lng_address(52.5170365, 13.3888599)
+        );
+        let request = rx.recv().unwrap();
+        assert!(request.starts_with("GET /reverse?lat=52.5170365&lon=13.3888599&format=jsonv2"));
+    }
+
+    #[test]
+    fn location_without_address() {
+        let (url, _rx) = serve_once(r#"{"error":"Unable to geocode"}"#);
+        let gw = Nominatim::new(url);
+        assert_eq!(None, gw.resolve_lat_lng_address(0.0, 0.0));
+    }
+}
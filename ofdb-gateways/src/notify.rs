@@ -1,18 +1,151 @@
-use crate::user_communication;
-use ofdb_core::gateways::{email::EmailGateway, notify::NotificationGateway};
-use ofdb_entities::{category::*, email::*, event::*, nonce::*, place::*, user::*};
+use crate::{
+    email_templates::{self, EmailTemplates},
+    user_communication,
+};
+use ofdb_core::{
+    gateways::{email::EmailGateway, notify::NotificationGateway},
+    subscription::unsubscribe_token,
+};
+use ofdb_entities::{
+    category::*, clearance::*, comment::*, email::*, event::*, id::*, nonce::*, place::*,
+    rating::*, review::*, subscription::*, time::*, user::*,
+};
+use std::{collections::HashMap, sync::Arc};
+
+/// The one-click unsubscribe endpoint (RFC 8058) that
+/// is followed by the signed token.
+const UNSUBSCRIBE_URL: &str = "https://api.ofdb.io/v0/unsubscribe?token=";
+
+/// The confirmation page for the unsubscribe links
+/// in the email body that is followed by the signed token.
+const UNSUBSCRIBE_PAGE_URL: &str = "https://openfairdb.org/unsubscribe?token=";
+
+/// Looks up the preferred language of email recipients.
+pub trait RecipientLanguages {
+    fn preferred_language(&self, email: &str) -> Option<Language>;
+}
+
+/// All recipients are addressed in the default language.
+pub struct DefaultLanguage;
+
+impl RecipientLanguages for DefaultLanguage {
+    fn preferred_language(&self, _: &str) -> Option<Language> {
+        None
+    }
+}
 
 pub struct Notify {
     email_gw: Box<dyn EmailGateway + Send + Sync + 'static>,
+    templates: Arc<EmailTemplates>,
+    languages: Box<dyn RecipientLanguages + Send + Sync + 'static>,
+    /// The key for signing unsubscribe tokens
+    unsubscribe_secret: String,
+    /// The web app for reviewing pending clearances
+    clearance_url: String,
 }
 
 impl Notify {
-    pub fn new<G>(gw: G) -> Self
+    pub fn new<G, L>(
+        gw: G,
+        templates: Arc<EmailTemplates>,
+        languages: L,
+        unsubscribe_secret: String,
+        clearance_url: String,
+    ) -> Self
     where
         G: EmailGateway + Send + Sync + 'static,
+        L: RecipientLanguages + Send + Sync + 'static,
     {
         Self {
             email_gw: Box::new(gw),
+            templates,
+            languages: Box::new(languages),
+            unsubscribe_secret,
+            clearance_url,
+        }
+    }
+
+    fn unsubscribe_url(&self, subscription_id: &Id) -> String {
+        format!(
+            "{}{}",
+            UNSUBSCRIBE_URL,
+            unsubscribe_token(&self.unsubscribe_secret, subscription_id)
+        )
+    }
+
+    fn unsubscribe_page_url(&self, subscription_id: &Id) -> String {
+        format!(
+            "{}{}",
+            UNSUBSCRIBE_PAGE_URL,
+            unsubscribe_token(&self.unsubscribe_secret, subscription_id)
+        )
+    }
+
+    /// Sends a separate email to each subscriber with
+    /// a link for unsubscribing from the subscription.
+    fn send_to_subscribers<F>(&self, recipients: &[SubscriptionRecipient], render: F)
+    where
+        F: Fn(&EmailTemplates, Language, Option<&str>) -> email_templates::Result<EmailContent>,
+    {
+        for recipient in recipients {
+            let unsubscribe_url = self.unsubscribe_url(&recipient.subscription_id);
+            let unsubscribe_page_url = self.unsubscribe_page_url(&recipient.subscription_id);
+            self.send_to_subscriber(
+                &recipient.email,
+                Some(unsubscribe_url),
+                |templates, language| render(templates, language, Some(&unsubscribe_page_url)),
+            );
+        }
+    }
+
+    /// The `list_unsubscribe_url` is sent in the `List-Unsubscribe` header
+    /// while the rendered body links to the confirmation page.
+    fn send_to_subscriber<F>(&self, email: &str, list_unsubscribe_url: Option<String>, render: F)
+    where
+        F: Fn(&EmailTemplates, Language) -> email_templates::Result<EmailContent>,
+    {
+        let language = self.languages.preferred_language(email).unwrap_or_default();
+        self.send_in_language(
+            language,
+            &[Email::from(email.to_owned())],
+            |templates, language| {
+                let mut content = render(templates, language)?;
+                content.unsubscribe_url = list_unsubscribe_url.clone();
+                Ok(content)
+            },
+        );
+    }
+
+    /// Renders the email once for each preferred language of
+    /// the recipients and sends it to the corresponding recipients.
+    fn send_localized<F>(&self, recipients: &[String], render: F)
+    where
+        F: Fn(&EmailTemplates, Language) -> email_templates::Result<EmailContent>,
+    {
+        let mut recipients_by_language: HashMap<Language, Vec<Email>> = HashMap::new();
+        for email in recipients {
+            let language = self.languages.preferred_language(email).unwrap_or_default();
+            recipients_by_language
+                .entry(language)
+                .or_default()
+                .push(Email::from(email.clone()));
+        }
+        for (language, recipients) in recipients_by_language {
+            self.send_in_language(language, &recipients, &render);
+        }
+    }
+
+    fn send_in_language<F>(&self, language: Language, recipients: &[Email], render: F)
+    where
+        F: Fn(&EmailTemplates, Language) -> email_templates::Result<EmailContent>,
+    {
+        match render(&self.templates, language) {
+            Ok(content) => {
+                self.email_gw.compose_and_send(recipients, &content);
+            }
+            Err(err) => {
+                error!("Failed to render e-mail in language {}: {}", language, err);
+            }
         }
     }
 }
@@ -20,7 +153,7 @@ impl Notify {
 impl NotificationGateway for Notify {
     fn place_added(
         &self,
-        email_addresses: &[String],
+        recipients: &[SubscriptionRecipient],
         place: &Place,
         all_categories: Vec<Category>,
     ) {
@@ -32,25 +165,27 @@ impl NotificationGateway for Notify {
             .filter(|c1| categories.iter().any(|c2| c1.id == c2.id))
             .map(|c| c.name())
             .collect();
-        let content = user_communication::place_created_email(&place, &category_names);
 
         {
             info!(
                 "Sending e-mails to {} recipients after new place {} added",
-                email_addresses.len(),
+                recipients.len(),
                 place.id,
             );
-            compose_and_send_emails(
-                &*self.email_gw,
-                email_addresses,
-                &content.subject,
-                &content.body,
-            );
+            self.send_to_subscribers(recipients, |templates, language, unsubscribe_url| {
+                user_communication::place_created_email(
+                    templates,
+                    language,
+                    &place,
+                    &category_names,
+                    unsubscribe_url,
+                )
+            });
         }
     }
     fn place_updated(
         &self,
-        email_addresses: &[String],
+        recipients: &[SubscriptionRecipient],
         place: &Place,
         all_categories: Vec<Category>,
     ) {
@@ -62,56 +197,169 @@ impl NotificationGateway for Notify {
             .filter(|c1| categories.iter().any(|c2| c1.id == c2.id))
             .map(|c| c.name())
             .collect();
-        let content = user_communication::place_updated_email(&place, &category_names);
 
         {
             info!(
                 "Sending e-mails to {} recipients after place {} updated",
-                email_addresses.len(),
+                recipients.len(),
                 place.id
             );
-            compose_and_send_emails(
-                &*self.email_gw,
-                email_addresses,
-                &content.subject,
-                &content.body,
-            );
+            self.send_to_subscribers(recipients, |templates, language, unsubscribe_url| {
+                user_communication::place_updated_email(
+                    templates,
+                    language,
+                    &place,
+                    &category_names,
+                    unsubscribe_url,
+                )
+            });
         }
     }
-    fn event_created(&self, email_addresses: &[String], event: &Event) {
-        let content = user_communication::event_created_email(&event);
+    fn place_reviewed(
+        &self,
+        recipients: &[SubscriptionRecipient],
+        place: &Place,
+        status: ReviewStatus,
+    ) {
+        let mut place = place.clone();
+        let (tags, categories) = Category::split_from_tags(place.tags);
+        place.tags = tags;
+        let category_names: Vec<String> = categories.into_iter().map(|c| c.name()).collect();
 
+        {
+            info!(
+                "Sending e-mails to {} recipients after place {} reviewed",
+                recipients.len(),
+                place.id
+            );
+            self.send_to_subscribers(recipients, |templates, language, unsubscribe_url| {
+                user_communication::place_reviewed_email(
+                    templates,
+                    language,
+                    &place,
+                    &category_names,
+                    status,
+                    unsubscribe_url,
+                )
+            });
+        }
+    }
+    fn own_place_reviewed(
+        &self,
+        authors: &[String],
+        place: &Place,
+        status: ReviewStatus,
+        comment: Option<&str>,
+    ) {
+        info!(
+            "Sending e-mails to {} authors after place {} reviewed",
+            authors.len(),
+            place.id
+        );
+        self.send_localized(authors, |templates, language| {
+            user_communication::own_place_reviewed_email(
+                templates, language, place, status, comment,
+            )
+        });
+    }
+    fn own_place_rated(
+        &self,
+        authors: &[String],
+        place: &Place,
+        rating: &Rating,
+        comment: &Comment,
+    ) {
+        info!(
+            "Sending e-mails to {} authors after place {} rated",
+            authors.len(),
+            place.id
+        );
+        self.send_localized(authors, |templates, language| {
+            user_communication::own_place_rated_email(templates, language, place, rating, comment)
+        });
+    }
+    fn own_place_clearance_pending(&self, authors: &[String], place: &Place) {
+        info!(
+            "Sending e-mails to {} authors after a change of place {} awaits clearance",
+            authors.len(),
+            place.id
+        );
+        self.send_localized(authors, |templates, language| {
+            user_communication::own_place_clearance_pending_email(templates, language, place)
+        });
+    }
+    fn event_created(&self, recipients: &[SubscriptionRecipient], event: &Event) {
         {
             info!(
                 "Sending e-mails to {} recipients after new event {} created",
-                email_addresses.len(),
+                recipients.len(),
                 event.id,
             );
-            compose_and_send_emails(
-                &*self.email_gw,
-                email_addresses,
-                &content.subject,
-                &content.body,
-            );
+            self.send_to_subscribers(recipients, |templates, language, unsubscribe_url| {
+                user_communication::event_created_email(templates, language, event, unsubscribe_url)
+            });
         }
     }
-    fn event_updated(&self, email_addresses: &[String], event: &Event) {
-        let content = user_communication::event_updated_email(&event);
-
+    fn event_updated(&self, recipients: &[SubscriptionRecipient], event: &Event) {
         {
             info!(
                 "Sending e-mails to {} recipients after event {} updated",
-                email_addresses.len(),
+                recipients.len(),
                 event.id
             );
-            compose_and_send_emails(
-                &*self.email_gw,
-                email_addresses,
-                &content.subject,
-                &content.body,
+            self.send_to_subscribers(recipients, |templates, language, unsubscribe_url| {
+                user_communication::event_updated_email(templates, language, event, unsubscribe_url)
+            });
+        }
+    }
+    fn subscription_digest(&self, digest: &SubscriptionDigest) {
+        {
+            info!(
+                "Sending {} digest with {} changes of {} subscriptions to {}",
+                digest.delivery_mode,
+                digest.notification_count(),
+                digest.sections.len(),
+                digest.user_email
+            );
+            // The one-click unsubscribe header can only refer
+            // to a single subscription
+            let list_unsubscribe_url = match digest.sections.as_slice() {
+                [section] => section
+                    .subscription_id
+                    .as_ref()
+                    .map(|id| self.unsubscribe_url(id)),
+                _ => None,
+            };
+            self.send_to_subscriber(
+                &digest.user_email,
+                list_unsubscribe_url,
+                |templates, language| {
+                    user_communication::subscription_digest_email(
+                        templates,
+                        language,
+                        digest,
+                        |id| self.unsubscribe_page_url(id),
+                    )
+                },
             );
         }
     }
+    fn pending_clearance_digest(&self, digest: &PendingClearanceDigest) {
+        info!(
+            "Sending e-mails to {} contacts of organization '{}' about {} new pending clearances",
+            digest.recipients.len(),
+            digest.org_name,
+            digest.new_count
+        );
+        self.send_localized(&digest.recipients, |templates, language| {
+            user_communication::pending_clearance_digest_email(
+                templates,
+                language,
+                digest,
+                &self.clearance_url,
+            )
+        });
+    }
     fn user_registered_kvm(&self, user: &User) {
         let token = EmailNonce {
             email: user.email.clone(),
@@ -131,15 +379,15 @@ impl NotificationGateway for Notify {
         self.user_registered(user, &url);
     }
     fn user_registered(&self, user: &User, url: &str) {
-        let content = user_communication::user_registration_email(&url);
-
         {
             info!("Sending confirmation e-mail to user {}", user.email);
-            compose_and_send_emails(
-                &*self.email_gw,
-                &[user.email.clone()],
-                &content.subject,
-                &content.body,
+            // The user has just chosen the language during registration
+            self.send_in_language(
+                user.language.unwrap_or_default(),
+                &[user.email.clone().into()],
+                |templates, language| {
+                    user_communication::user_registration_email(templates, language, url)
+                },
             );
         }
     }
@@ -148,30 +396,56 @@ impl NotificationGateway for Notify {
             "https://openfairdb.org/reset-password?token={}",
             email_nonce.encode_to_string()
         );
-        let content = user_communication::user_reset_password_email(&url);
 
         {
             info!(
                 "Sending e-mail to {} after password reset requested",
                 email_nonce.email
             );
-            compose_and_send_emails(
-                &*self.email_gw,
-                &[email_nonce.email.to_owned()],
-                &content.subject,
-                &content.body,
+            self.send_localized(&[email_nonce.email.to_owned()], |templates, language| {
+                user_communication::user_reset_password_email(templates, language, &url)
+            });
+        }
+    }
+    fn user_login_locked(&self, user: &User, locked_until: TimestampMs) {
+        {
+            info!(
+                "Sending e-mail to {} after login has been locked",
+                user.email
             );
+            self.send_localized(&[user.email.clone()], |templates, language| {
+                user_communication::user_login_locked_email(templates, language, locked_until)
+            });
         }
     }
-}
+    fn user_email_change_requested(&self, new_email_nonce: &EmailNonce) {
+        let url = format!(
+            "https://openfairdb.org/confirm-email-change?token={}",
+            new_email_nonce.encode_to_string()
+        );
 
-fn compose_and_send_emails(
-    gw: &dyn EmailGateway,
-    recipients: &[String],
-    subject: &str,
-    body: &str,
-) {
-    // TODO: take &[Email] as argument
-    let rec: Vec<_> = recipients.iter().cloned().map(Email::from).collect();
-    gw.compose_and_send(&rec, subject, body);
+        {
+            info!(
+                "Sending confirmation e-mail to {} after email change requested",
+                new_email_nonce.email
+            );
+            self.send_localized(
+                &[new_email_nonce.email.to_owned()],
+                |templates, language| {
+                    user_communication::user_email_change_email(templates, language, &url)
+                },
+            );
+        }
+    }
+    fn user_email_changed(&self, old_email: &str, new_email: &str) {
+        {
+            info!(
+                "Sending e-mail to {} after email address has been changed",
+                old_email
+            );
+            self.send_localized(&[old_email.to_owned()], |templates, language| {
+                user_communication::user_email_changed_email(templates, language, new_email)
+            });
+        }
+    }
 }
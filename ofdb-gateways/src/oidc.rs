@@ -0,0 +1,196 @@
+use ofdb_core::gateways::oidc::{OidcCodeExchange, OidcGateway, OidcUserInfo};
+use serde::Deserialize;
+use serde_json::Value;
+use std::time::Duration;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+/// Authenticates users with the authorization code flow of
+/// OpenID Connect providers.
+///
+/// The claims are requested from the UserInfo endpoint with
+/// the access token instead of validating the ID token, i.e.
+/// the authenticity relies on the TLS connections to the
+/// provider (OpenID Connect Core 1.0, section 3.1.3.7).
+#[derive(Debug, Clone)]
+pub struct HttpOidcGateway {
+    client: reqwest::blocking::Client,
+}
+
+impl HttpOidcGateway {
+    pub fn new() -> Self {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("HTTP client");
+        Self { client }
+    }
+}
+
+impl Default for HttpOidcGateway {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn claim<'a>(claims: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(claims, |value, name| value.as_object()?.get(name))
+}
+
+fn user_info_from_claims(claims: &Value, role_claim: &str) -> Result<OidcUserInfo, String> {
+    let subject = claims
+        .get("sub")
+        .and_then(Value::as_str)
+        .ok_or_else(|| "Missing subject claim".to_string())?
+        .to_string();
+    let email = claims
+        .get("email")
+        .and_then(Value::as_str)
+        .map(ToString::to_string);
+    let email_verified = claims.get("email_verified").and_then(Value::as_bool);
+    let groups = match claim(claims, role_claim) {
+        Some(Value::Array(values)) => values
+            .iter()
+            .filter_map(Value::as_str)
+            .map(ToString::to_string)
+            .collect(),
+        Some(Value::String(value)) => vec![value.clone()],
+        _ => vec![],
+    };
+    Ok(OidcUserInfo {
+        subject,
+        email,
+        email_verified,
+        groups,
+    })
+}
+
+impl OidcGateway for HttpOidcGateway {
+    fn exchange_code(&self, request: &OidcCodeExchange) -> Result<OidcUserInfo, String> {
+        let OidcCodeExchange {
+            token_endpoint,
+            userinfo_endpoint,
+            client_id,
+            client_secret,
+            redirect_uri,
+            code,
+            code_verifier,
+            role_claim,
+        } = request;
+        debug!("Requesting access token from {}", token_endpoint);
+        let token = self
+            .client
+            .post(token_endpoint.as_str())
+            .basic_auth(client_id, Some(client_secret))
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", *code),
+                ("code_verifier", *code_verifier),
+                ("redirect_uri", redirect_uri.as_str()),
+            ])
+            .send()
+            .and_then(|res| res.error_for_status())
+            .and_then(|res| res.json::<TokenResponse>())
+            .map_err(|err| err.to_string())?;
+        debug!("Requesting user info from {}", userinfo_endpoint);
+        let claims = self
+            .client
+            .get(userinfo_endpoint.as_str())
+            .bearer_auth(&token.access_token)
+            .send()
+            .and_then(|res| res.error_for_status())
+            .and_then(|res| res.json::<Value>())
+            .map_err(|err| err.to_string())?;
+        user_info_from_claims(&claims, role_claim)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::serve_http;
+    use ofdb_entities::url::Url;
+    use serde_json::json;
+
+    fn exchange(base_url: &str, role_claim: &str) -> Result<OidcUserInfo, String> {
+        let token_endpoint: Url = format!("{}/token", base_url).parse().unwrap();
+        let userinfo_endpoint: Url = format!("{}/userinfo", base_url).parse().unwrap();
+        let redirect_uri: Url = "https://ofdb.example.com/callback".parse().unwrap();
+        HttpOidcGateway::new().exchange_code(&OidcCodeExchange {
+            token_endpoint: &token_endpoint,
+            userinfo_endpoint: &userinfo_endpoint,
+            client_id: "ofdb",
+            client_secret: "secret",
+            redirect_uri: &redirect_uri,
+            code: "the-code",
+            code_verifier: "the-verifier",
+            role_claim,
+        })
+    }
+
+    #[test]
+    fn exchange_code_for_user_info() {
+        let (base_url, rx) = serve_http(vec![
+            (
+                200,
+                json!({"access_token": "the-token", "token_type": "Bearer"}).to_string(),
+            ),
+            (
+                200,
+                json!({
+                    "sub": "123",
+                    "email": "foo@bar.tld",
+                    "email_verified": true,
+                    "realm_access": { "roles": ["ofdb-scouts", "other"] }
+                })
+                .to_string(),
+            ),
+        ]);
+        let user_info = exchange(&base_url, "realm_access.roles").unwrap();
+        assert_eq!(
+            OidcUserInfo {
+                subject: "123".into(),
+                email: Some("foo@bar.tld".into()),
+                email_verified: Some(true),
+                groups: vec!["ofdb-scouts".into(), "other".into()],
+            },
+            user_info
+        );
+        let token_request = rx.recv().unwrap();
+        assert!(token_request.starts_with("POST /token HTTP/1.1"));
+        // base64("ofdb:secret")
+        assert!(token_request
+            .to_lowercase()
+            .contains("authorization: basic b2ZkYjpzZWNyZXQ="));
+        assert!(token_request.contains("grant_type=authorization_code"));
+        assert!(token_request.contains("code=the-code"));
+        assert!(token_request.contains("code_verifier=the-verifier"));
+        let userinfo_request = rx.recv().unwrap();
+        assert!(userinfo_request.starts_with("GET /userinfo HTTP/1.1"));
+        assert!(userinfo_request
+            .to_lowercase()
+            .contains("authorization: bearer the-token"));
+    }
+
+    #[test]
+    fn reject_invalid_code() {
+        let (base_url, _rx) =
+            serve_http(vec![(400, json!({"error": "invalid_grant"}).to_string())]);
+        assert!(exchange(&base_url, "groups").is_err());
+    }
+
+    #[test]
+    fn single_valued_role_claim() {
+        let claims = json!({"sub": "123", "role": "admin"});
+        let user_info = user_info_from_claims(&claims, "role").unwrap();
+        assert_eq!(vec!["admin".to_string()], user_info.groups);
+        assert_eq!(None, user_info.email);
+        assert!(user_info_from_claims(&json!({}), "role").is_err());
+    }
+}
@@ -1,7 +1,11 @@
+use crate::geocoder::AddressComponents;
 use ::geocoding::{Forward, Opencage};
 use itertools::Itertools;
 use ofdb_core::gateways::geocode::GeoCodingGateway;
 use ofdb_entities::address::Address;
+use serde::Deserialize;
+
+const OC_API_URL: &str = "https://api.opencagedata.com/geocode/v1/json";
 
 pub struct OpenCage {
     api_key: Option<String>,
@@ -13,7 +17,7 @@ impl OpenCage {
     }
 }
 
-fn address_to_forward_query_string(addr: &Address) -> String {
+pub(crate) fn address_to_forward_query_string(addr: &Address) -> String {
     let addr_parts = [&addr.street, &addr.zip, &addr.city, &addr.country];
     addr_parts.iter().filter_map(|x| x.as_ref()).join(",")
 }
@@ -36,6 +40,49 @@ fn oc_resolve_address_lat_lng(oc_api_key: String, addr: &Address) -> Option<(f64
     None
 }
 
+#[derive(Debug, Deserialize)]
+struct OcReverseResponse {
+    results: Vec<OcReverseResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OcReverseResult {
+    components: AddressComponents,
+}
+
+// The reverse geocoding of the geocoding crate only provides
+// a formatted string instead of the individual components.
+fn oc_resolve_lat_lng_address(oc_api_key: &str, lat: f64, lng: f64) -> Option<Address> {
+    let query = format!("{},{}", lat, lng);
+    let res: Result<OcReverseResponse, _> = reqwest::blocking::Client::new()
+        .get(OC_API_URL)
+        .query(&[
+            ("q", query.as_str()),
+            ("key", oc_api_key),
+            ("no_annotations", "1"),
+            ("limit", "1"),
+        ])
+        .send()
+        .and_then(|res| res.error_for_status())
+        .and_then(|res| res.json());
+    match res {
+        Ok(res) => {
+            let addr = res
+                .results
+                .into_iter()
+                .next()
+                .map(|r| Address::from(r.components))
+                .filter(|addr| !addr.is_empty());
+            debug!("Resolved address of location '{}': {:?}", query, addr);
+            addr
+        }
+        Err(err) => {
+            warn!("Failed to resolve address of location '{}': {}", query, err);
+            None
+        }
+    }
+}
+
 impl GeoCodingGateway for OpenCage {
     fn resolve_address_lat_lng(&self, addr: &Address) -> Option<(f64, f64)> {
         if addr.is_empty() {
@@ -46,6 +93,12 @@ impl GeoCodingGateway for OpenCage {
                 .and_then(|key| oc_resolve_address_lat_lng(key.clone(), addr))
         }
     }
+
+    fn resolve_lat_lng_address(&self, lat: f64, lng: f64) -> Option<Address> {
+        self.api_key
+            .as_ref()
+            .and_then(|key| oc_resolve_lat_lng_address(key, lat, lng))
+    }
 }
 
 #[cfg(test)]
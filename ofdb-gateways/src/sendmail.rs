@@ -1,16 +1,13 @@
 use chrono::*;
 use fast_chemail::is_valid_email;
-use ofdb_core::gateways::email::EmailGateway;
-use ofdb_entities::email::*;
+use ofdb_core::gateways::email::EmailSender;
+use ofdb_entities::{email::*, nonce::Nonce};
+use std::io::{Error, ErrorKind, Result};
 #[cfg(not(test))]
 use std::{
     io::prelude::*,
     process::{Command, Stdio},
 };
-use std::{
-    io::{Error, ErrorKind, Result},
-    thread,
-};
 
 #[derive(Debug, Clone)]
 pub struct Sendmail {
@@ -21,13 +18,6 @@ impl Sendmail {
     pub fn new(from: Email) -> Self {
         Self { from }
     }
-    fn send(&self, mail: String) {
-        thread::spawn(move || {
-            if let Err(err) = send_raw(&mail) {
-                warn!("Could not send e-mail: {}", err);
-            }
-        });
-    }
 }
 
 #[cfg(not(test))]
@@ -41,7 +31,13 @@ fn send_raw(mail: &str) -> Result<()> {
         .as_mut()
         .ok_or_else(|| Error::new(ErrorKind::Other, "Could not get stdin"))?
         .write_all(mail.as_bytes())?;
-    child.wait_with_output()?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(Error::new(
+            ErrorKind::Other,
+            format!("sendmail failed: {}", output.status),
+        ));
+    }
     Ok(())
 }
 
@@ -53,19 +49,22 @@ fn send_raw(email: &str) -> Result<()> {
     Ok(())
 }
 
-impl EmailGateway for Sendmail {
-    fn compose_and_send(&self, recipients: &[Email], subject: &str, body: &str) {
-        debug!("Sending e-mails to: {:?}", recipients);
-        for to in recipients {
-            match compose(&self.from, &[to], subject, body) {
-                Ok(email) => {
-                    self.send(email);
-                }
-                Err(err) => {
-                    warn!("Failed to compose e-mail: {}", err);
-                }
-            }
-        }
+impl EmailSender for Sendmail {
+    fn send(&self, recipient: &Email, content: &EmailContent) -> std::result::Result<(), String> {
+        debug!("Sending e-mail to: {}", recipient);
+        let mail = compose(
+            &self.from,
+            &[recipient],
+            &content.subject,
+            &content.body,
+            content.html_body.as_deref(),
+            content.unsubscribe_url.as_deref(),
+        )
+        .map_err(|err| {
+            warn!("Failed to compose e-mail: {}", err);
+            err.to_string()
+        })?;
+        send_raw(&mail).map_err(|err| err.to_string())
     }
 }
 
@@ -135,7 +134,42 @@ fn encode_header_field(name: &str, input: &str) -> String {
     encoded_output
 }
 
-pub fn compose(from: &str, to: &[&str], subject: &str, body: &str) -> Result<String> {
+fn multipart_alternative(body: &str, html_body: &str) -> (String, String) {
+    let boundary = format!("=_{}", Nonce::new().to_string().replace('-', ""));
+    let part = |content_type: &str, content: &str| {
+        format!(
+            "--{boundary}\r\n\
+             Content-Type:{content_type};charset=utf-8\r\n\
+             Content-Transfer-Encoding:quoted-printable\r\n\r\n\
+             {content}\r\n",
+            boundary = boundary,
+            content_type = content_type,
+            content = quoted_printable::encode_to_str(
+                content
+                    .replace("\r\n", "\n")
+                    .replace('\n', "\r\n")
+                    .as_bytes()
+            ),
+        )
+    };
+    let content = format!(
+        "{text}{html}--{boundary}--\r\n",
+        text = part("text/plain", body),
+        html = part("text/html", html_body),
+        boundary = boundary,
+    );
+    let content_type = format!("multipart/alternative;boundary=\"{}\"", boundary);
+    (content_type, content)
+}
+
+pub fn compose(
+    from: &str,
+    to: &[&str],
+    subject: &str,
+    body: &str,
+    html_body: Option<&str>,
+    unsubscribe_url: Option<&str>,
+) -> Result<String> {
     let to: Vec<_> = to.iter().filter(|m| is_valid_email(m)).cloned().collect();
 
     if to.is_empty() {
@@ -147,18 +181,38 @@ pub fn compose(from: &str, to: &[&str], subject: &str, body: &str) -> Result<Str
 
     let now = Local::now();
 
+    let (content_type, body) = if let Some(html_body) = html_body {
+        multipart_alternative(body, html_body)
+    } else {
+        ("text/plain;charset=utf-8".to_string(), body.to_string())
+    };
+
+    // One-click unsubscribe (RFC 8058)
+    let unsubscribe_headers = unsubscribe_url
+        .map(|url| {
+            format!(
+                "List-Unsubscribe:<{}>\r\n\
+                 List-Unsubscribe-Post:List-Unsubscribe=One-Click\r\n",
+                url
+            )
+        })
+        .unwrap_or_default();
+
     let email = format!(
         "Date:{date}\r\n\
          From:{from}\r\n\
          To:{to}\r\n\
          {subject_header}\r\n\
+         {unsubscribe_headers}\
          MIME-Version:1.0\r\n\
-         Content-Type:text/plain;charset=utf-8\r\n\r\n\
+         Content-Type:{content_type}\r\n\r\n\
          {body}",
         date = now.to_rfc2822(),
+        content_type = content_type,
         from = from,
         to = to.join(","),
         subject_header = encode_header_field("Subject", &subject),
+        unsubscribe_headers = unsubscribe_headers,
         body = body
     );
 
@@ -178,6 +232,8 @@ mod tests {
             &["mail@test.org"],
             "My veeeeerrrrryyyyy looooonnnnnggggg Subject with äöüÄÖÜß Umlaute and even more characters that are distributed onto multiple lines",
             "Hello Mail",
+            None,
+            None,
         ).unwrap();
         let expected = "From:\"OFDB\" <from@ofdb.io>\r\n\
              To:mail@test.org\r\n\
@@ -190,9 +246,45 @@ mod tests {
         assert!(mail.contains(expected));
     }
 
+    #[test]
+    fn create_multipart_mail() {
+        let mail = compose(
+            "from@ofdb.io",
+            &["mail@test.org"],
+            "Subject",
+            "Hello Mail",
+            Some("<p>Hello Mail</p>"),
+            None,
+        )
+        .unwrap();
+        assert!(mail.contains("Content-Type:multipart/alternative;boundary="));
+        assert!(mail.contains("Content-Type:text/plain;charset=utf-8\r\n"));
+        assert!(mail.contains("Content-Type:text/html;charset=utf-8\r\n"));
+        assert!(mail.contains("<p>Hello Mail</p>"));
+    }
+
     #[test]
     fn check_addresses() {
-        assert!(compose("from@mail.org", &[], "foo", "bar").is_err());
-        assert!(compose("from", &["not-valid"], "foo", "bar").is_err());
+        assert!(compose("from@mail.org", &[], "foo", "bar", None, None).is_err());
+        assert!(compose("from", &["not-valid"], "foo", "bar", None, None).is_err());
+    }
+
+    #[test]
+    fn create_mail_with_unsubscribe_link() {
+        let mail = compose(
+            "from@ofdb.io",
+            &["mail@test.org"],
+            "Subject",
+            "Hello Mail",
+            None,
+            Some("https://api.ofdb.io/v0/unsubscribe?token=abc"),
+        )
+        .unwrap();
+        assert!(mail.contains(
+            "Subject:=?UTF-8?Q?Subject?=\r\n\
+             List-Unsubscribe:<https://api.ofdb.io/v0/unsubscribe?token=abc>\r\n\
+             List-Unsubscribe-Post:List-Unsubscribe=One-Click\r\n\
+             MIME-Version:1.0\r\n"
+        ));
     }
 }
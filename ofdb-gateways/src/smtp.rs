@@ -0,0 +1,381 @@
+use lettre::{
+    message::{
+        header::{Header, HeaderName},
+        Mailbox, MultiPart,
+    },
+    transport::smtp::{authentication::Credentials, client::Tls, SmtpTransport},
+    Message, Transport,
+};
+use ofdb_core::gateways::email::EmailSender;
+use ofdb_entities::email::*;
+use std::io::{Error, ErrorKind, Result};
+
+/// `List-Unsubscribe` header (RFC 2369)
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ListUnsubscribe(String);
+
+impl Header for ListUnsubscribe {
+    fn name() -> HeaderName {
+        HeaderName::new_from_ascii_str("List-Unsubscribe")
+    }
+
+    fn parse(s: &str) -> std::result::Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self(
+            s.trim()
+                .trim_start_matches('<')
+                .trim_end_matches('>')
+                .to_owned(),
+        ))
+    }
+
+    fn display(&self) -> String {
+        format!("<{}>", self.0)
+    }
+}
+
+/// `List-Unsubscribe-Post` header for one-click unsubscribing (RFC 8058)
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ListUnsubscribePost;
+
+impl Header for ListUnsubscribePost {
+    fn name() -> HeaderName {
+        HeaderName::new_from_ascii_str("List-Unsubscribe-Post")
+    }
+
+    fn parse(_: &str) -> std::result::Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self)
+    }
+
+    fn display(&self) -> String {
+        "List-Unsubscribe=One-Click".to_owned()
+    }
+}
+
+/// How the connection to the SMTP server is secured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    /// Unencrypted, only for local mail servers and testing
+    None,
+    /// Upgrade the connection with STARTTLS (default port 587)
+    StartTls,
+    /// Implicit TLS (default port 465)
+    Tls,
+}
+
+impl Default for SmtpSecurity {
+    fn default() -> Self {
+        Self::StartTls
+    }
+}
+
+impl std::str::FromStr for SmtpSecurity {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "starttls" => Ok(Self::StartTls),
+            "tls" => Ok(Self::Tls),
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid SMTP security: {}", s),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    /// Defaults to the standard port of the security mode
+    pub port: Option<u16>,
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from_email: Email,
+}
+
+/// Sends emails directly to an SMTP server.
+///
+/// Connections are pooled and reused for subsequent emails.
+#[derive(Clone)]
+pub struct Smtp {
+    from: Mailbox,
+    transport: SmtpTransport,
+}
+
+impl Smtp {
+    pub fn new(cfg: SmtpConfig) -> Result<Self> {
+        let SmtpConfig {
+            host,
+            port,
+            security,
+            username,
+            password,
+            from_email,
+        } = cfg;
+        let from = from_email
+            .parse::<Mailbox>()
+            .map_err(|err| Error::new(ErrorKind::InvalidInput, err))?;
+        let mut builder = match security {
+            SmtpSecurity::None => SmtpTransport::builder_dangerous(&host).tls(Tls::None),
+            SmtpSecurity::StartTls => SmtpTransport::starttls_relay(&host)
+                .map_err(|err| Error::new(ErrorKind::InvalidInput, err))?,
+            SmtpSecurity::Tls => SmtpTransport::relay(&host)
+                .map_err(|err| Error::new(ErrorKind::InvalidInput, err))?,
+        };
+        if let Some(port) = port {
+            builder = builder.port(port);
+        }
+        if let Some(username) = username {
+            builder = builder.credentials(Credentials::new(username, password.unwrap_or_default()));
+        }
+        Ok(Self {
+            from,
+            transport: builder.build(),
+        })
+    }
+
+    fn compose(&self, recipient: &Email, content: &EmailContent) -> Result<Message> {
+        let to = recipient
+            .parse::<Mailbox>()
+            .map_err(|err| Error::new(ErrorKind::InvalidInput, err))?;
+        let mut builder = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(&content.subject);
+        if let Some(unsubscribe_url) = &content.unsubscribe_url {
+            builder = builder
+                .header(ListUnsubscribe(unsubscribe_url.clone()))
+                .header(ListUnsubscribePost);
+        }
+        builder
+            .multipart(MultiPart::alternative_plain_html(
+                content.body.clone(),
+                content
+                    .html_body
+                    .clone()
+                    .unwrap_or_else(|| html_from_plain_text(&content.body)),
+            ))
+            .map_err(|err| Error::new(ErrorKind::Other, err))
+    }
+}
+
+impl EmailSender for Smtp {
+    fn send(&self, recipient: &Email, content: &EmailContent) -> std::result::Result<(), String> {
+        debug!("Sending e-mail to: {}", recipient);
+        let message = self.compose(recipient, content).map_err(|err| {
+            warn!("Failed to compose e-mail: {}", err);
+            err.to_string()
+        })?;
+        let response = self
+            .transport
+            .send(&message)
+            .map_err(|err| err.to_string())?;
+        debug!("SMTP server response: {:?}", response);
+        Ok(())
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Renders paragraphs and line breaks of a plain text
+/// message as HTML if no HTML alternative is provided.
+fn html_from_plain_text(text: &str) -> String {
+    let paragraphs: Vec<_> = text
+        .replace("\r\n", "\n")
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            let lines: Vec<_> = p.lines().map(escape_html).collect();
+            format!("<p>{}</p>", lines.join("<br>\n"))
+        })
+        .collect();
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"></head>\n<body>\n{}\n</body>\n</html>\n",
+        paragraphs.join("\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::{TcpListener, TcpStream},
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    fn content(subject: &str, body: &str, html_body: Option<&str>) -> EmailContent {
+        EmailContent {
+            subject: subject.into(),
+            body: body.into(),
+            html_body: html_body.map(Into::into),
+            unsubscribe_url: None,
+        }
+    }
+
+    #[derive(Default)]
+    struct Sink {
+        connections: usize,
+        messages: Vec<String>,
+    }
+
+    fn handle_connection(stream: TcpStream, sink: &Mutex<Sink>) -> std::io::Result<()> {
+        sink.lock().unwrap().connections += 1;
+        let mut writer = stream.try_clone()?;
+        let mut reader = BufReader::new(stream);
+        writer.write_all(b"220 localhost SMTP sink\r\n")?;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+            let command = line.trim_end().to_uppercase();
+            if command.starts_with("EHLO") {
+                writer.write_all(b"250-localhost\r\n250 8BITMIME\r\n")?;
+            } else if command.starts_with("DATA") {
+                writer.write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n")?;
+                let mut message = String::new();
+                loop {
+                    line.clear();
+                    reader.read_line(&mut line)?;
+                    if line == ".\r\n" {
+                        break;
+                    }
+                    message.push_str(&line);
+                }
+                sink.lock().unwrap().messages.push(message);
+                writer.write_all(b"250 OK\r\n")?;
+            } else if command.starts_with("QUIT") {
+                writer.write_all(b"221 Bye\r\n")?;
+                return Ok(());
+            } else {
+                writer.write_all(b"250 OK\r\n")?;
+            }
+        }
+    }
+
+    fn start_sink() -> (u16, Arc<Mutex<Sink>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let sink = Arc::new(Mutex::new(Sink::default()));
+        let sink_clone = Arc::clone(&sink);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let sink = Arc::clone(&sink_clone);
+                let stream = stream.unwrap();
+                thread::spawn(move || handle_connection(stream, &sink));
+            }
+        });
+        (port, sink)
+    }
+
+    #[test]
+    fn send_multipart_emails_to_local_sink() {
+        let (port, sink) = start_sink();
+        let smtp = Smtp::new(SmtpConfig {
+            host: "127.0.0.1".into(),
+            port: Some(port),
+            security: SmtpSecurity::None,
+            username: None,
+            password: None,
+            from_email: "\"OFDB\" <from@ofdb.io>".into(),
+        })
+        .unwrap();
+        smtp.send(
+            &"a@example.com".into(),
+            &content("Hello", "Hello <a>\n\nBye", None),
+        )
+        .unwrap();
+        smtp.send(
+            &"b@example.com".into(),
+            &content("Grüße", "Hello b", Some("<h1>Hello b</h1>")),
+        )
+        .unwrap();
+
+        let sink = sink.lock().unwrap();
+        // The connection is reused
+        assert_eq!(1, sink.connections);
+        assert_eq!(2, sink.messages.len());
+        let message = &sink.messages[0];
+        assert!(message.contains("To: a@example.com"));
+        assert!(message.contains("Content-Type: multipart/alternative"));
+        assert!(message.contains("Content-Type: text/plain; charset=utf-8"));
+        assert!(message.contains("Content-Type: text/html; charset=utf-8"));
+        assert!(message.contains("<p>Hello &lt;a&gt;</p>"));
+        assert!(sink.messages[1].contains("To: b@example.com"));
+        assert!(sink.messages[1].contains("<h1>Hello b</h1>"));
+    }
+
+    #[test]
+    fn send_unsubscribe_headers() {
+        let (port, sink) = start_sink();
+        let smtp = Smtp::new(SmtpConfig {
+            host: "127.0.0.1".into(),
+            port: Some(port),
+            security: SmtpSecurity::None,
+            username: None,
+            password: None,
+            from_email: "from@ofdb.io".into(),
+        })
+        .unwrap();
+        let mut content = content("Hello", "Hello", None);
+        content.unsubscribe_url = Some("https://api.ofdb.io/v0/unsubscribe?token=abc".into());
+        smtp.send(&"a@example.com".into(), &content).unwrap();
+
+        let sink = sink.lock().unwrap();
+        let message = &sink.messages[0];
+        assert!(
+            message.contains("List-Unsubscribe: <https://api.ofdb.io/v0/unsubscribe?token=abc>")
+        );
+        assert!(message.contains("List-Unsubscribe-Post: List-Unsubscribe=One-Click"));
+    }
+
+    #[test]
+    fn reject_invalid_recipients() {
+        let smtp = Smtp::new(SmtpConfig {
+            host: "127.0.0.1".into(),
+            port: Some(1),
+            security: SmtpSecurity::None,
+            username: None,
+            password: None,
+            from_email: "from@ofdb.io".into(),
+        })
+        .unwrap();
+        assert!(smtp
+            .send(&"not-valid".into(), &content("foo", "bar", None))
+            .is_err());
+    }
+
+    #[test]
+    fn render_plain_text_as_html() {
+        assert_eq!(
+            "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"></head>\n<body>\n\
+             <p>Hi &amp; welcome,<br>\nline</p>\n<p>Bye</p>\n</body>\n</html>\n",
+            html_from_plain_text("Hi & welcome,\r\nline\r\n\r\n\r\nBye\n")
+        );
+    }
+
+    #[test]
+    fn parse_security() {
+        assert_eq!(SmtpSecurity::StartTls, "STARTTLS".parse().unwrap());
+        assert_eq!(SmtpSecurity::Tls, "tls".parse().unwrap());
+        assert_eq!(SmtpSecurity::None, "none".parse().unwrap());
+        assert!("ssl".parse::<SmtpSecurity>().is_err());
+    }
+}
@@ -0,0 +1,70 @@
+//! Local stand-ins of external services for testing.
+
+use std::{
+    io::{Read, Write},
+    net::TcpListener,
+    sync::mpsc,
+    thread,
+};
+
+/// A local stand-in for an HTTP server that responds to each
+/// request with the next of the given status codes and JSON
+/// bodies.
+///
+/// Returns the base URL of the server and a receiver of the
+/// raw requests.
+pub fn serve_http(responses: Vec<(u16, String)>) -> (String, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for (status, body) in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let request = read_request(&mut stream);
+            write!(
+                stream,
+                "HTTP/1.1 {} Status\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+            // The receiver might have been dropped
+            let _ = tx.send(request);
+        }
+    });
+    (base_url, rx)
+}
+
+/// Responds to a single request.
+pub fn serve_once(status: u16, body: &str) -> (String, mpsc::Receiver<String>) {
+    serve_http(vec![(status, body.to_owned())])
+}
+
+// Reads the header and the body with the announced length.
+fn read_request(stream: &mut impl Read) -> String {
+    let mut request = Vec::new();
+    let mut buf = [0; 1024];
+    loop {
+        let n = stream.read(&mut buf).unwrap();
+        request.extend_from_slice(&buf[..n]);
+        if n == 0 {
+            break;
+        }
+        let text = String::from_utf8_lossy(&request);
+        if let Some(header_end) = text.find("\r\n\r\n") {
+            let content_length = text[..header_end]
+                .lines()
+                .find_map(|l| {
+                    l.to_lowercase()
+                        .strip_prefix("content-length:")
+                        .map(|v| v.trim().parse::<usize>().unwrap())
+                })
+                .unwrap_or(0);
+            if request.len() >= header_end + 4 + content_length {
+                break;
+            }
+        }
+    }
+    String::from_utf8(request).unwrap()
+}
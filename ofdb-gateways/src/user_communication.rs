@@ -1,26 +1,24 @@
-use ofdb_entities::{address::*, contact::*, event::*, place::*, url::*};
-
-pub struct EmailContent {
-    pub subject: String,
-    pub body: String,
-}
+use crate::email_templates::{EmailTemplates, Result};
+use chrono::NaiveDateTime;
+use ofdb_entities::{
+    address::*, clearance::*, comment::*, contact::*, email::EmailContent, event::*, id::Id,
+    place::*, rating::*, review::*, subscription::*, time::*, url::*, user::Language,
+};
+use serde::Serialize;
 
 const DATE_TIME_FORMAT: &str = "%Y.%m.%d %H:%M:%S";
 
-const INTRO_ENTRY_CREATED: &str = "ein neuer Eintrag auf der Karte von morgen wurde erstellt";
-
-const INTRO_ENTRY_UPDATED: &str = "folgender Eintrag auf der Karte von morgen wurde verändert";
-
-const OUTRO_HINT: &str = "Weitere Hinweise und Tipps zur Nutzung, z.B. wie du interaktive Karten
-per <iframe> auf deiner Webseite einbettest oder Papierkarten erstellst,
-findest du hier: https://blog.vonmorgen.org";
-
-fn subject_entry_created(entry_title: &str) -> String {
-    format!("Kvm - neuer Eintrag: {}", entry_title)
+fn entry_url(id: &str) -> String {
+    format!("https://kartevonmorgen.org/#/?entry={}", id)
 }
 
-fn subject_entry_updated(entry_title: &str) -> String {
-    format!("Kvm - Eintrag verändert: {}", entry_title)
+fn review_status_name(status: ReviewStatus) -> &'static str {
+    match status {
+        ReviewStatus::Rejected => "rejected",
+        ReviewStatus::Archived => "archived",
+        ReviewStatus::Created => "created",
+        ReviewStatus::Confirmed => "confirmed",
+    }
 }
 
 fn address_line(address: Option<&Address>) -> String {
@@ -44,56 +42,98 @@ fn address_line(address: Option<&Address>) -> String {
     }
 }
 
-pub fn user_registration_email(url: &str) -> EmailContent {
-    let subject = "Karte von morgen: Bitte bestätige deine Email-Adresse".into();
-    let body = format!(
-        "Na du Weltverbesserer*,\n
-wir freuen uns, dass du bei der Karte von morgen mit dabei bist!\n\n
-Bitte bestätige deine Email-Adresse hier:\n
-{url}\n\n
-euphorische Grüße,\n
-das Karte von morgen-Team\n
-{outro_text}",
-        url = url,
-        outro_text = OUTRO_HINT,
-    );
-    EmailContent { subject, body }
-}
-
-pub fn user_reset_password_email(url: &str) -> EmailContent {
-    let subject = "Karte von morgen: Passwort zurücksetzen".into();
-    let body = format!(
-        "Na du Weltverbesserer*,\n
-hast du uns kürzlich gebeten dein Passwort zurücksetzen?\n\n
-Bitte folge zur Eingabe eines neuen Passworts diesem Link:\n
-{url}\n\n
-euphorische Grüße,\n
-das Karte von morgen-Team",
-        url = url,
-    );
-    EmailContent { subject, body }
-}
-
-pub fn place_created_email(place: &Place, category_names: &[String]) -> EmailContent {
-    let subject = subject_entry_created(&place.title);
-    let body = place_email(place, category_names, INTRO_ENTRY_CREATED);
-    EmailContent { subject, body }
+#[derive(Serialize)]
+struct UrlContext<'a> {
+    url: &'a str,
 }
 
-//TODO: calc diff
-pub fn place_updated_email(place: &Place, category_names: &[String]) -> EmailContent {
-    let subject = subject_entry_updated(&place.title);
-    let body = place_email(place, category_names, INTRO_ENTRY_UPDATED);
-    EmailContent { subject, body }
+pub fn user_registration_email(
+    templates: &EmailTemplates,
+    language: Language,
+    url: &str,
+) -> Result<EmailContent> {
+    templates.render(language, "user_registration", &UrlContext { url })
 }
 
-fn place_email(place: &Place, category_names: &[String], intro_sentence: &str) -> String {
-    let category = if !category_names.is_empty() {
-        category_names[0].clone()
-    } else {
-        "".to_string()
+pub fn user_reset_password_email(
+    templates: &EmailTemplates,
+    language: Language,
+    url: &str,
+) -> Result<EmailContent> {
+    templates.render(language, "user_reset_password", &UrlContext { url })
+}
+
+#[derive(Serialize)]
+struct LoginLockedContext {
+    locked_until: String,
+}
+
+pub fn user_login_locked_email(
+    templates: &EmailTemplates,
+    language: Language,
+    locked_until: TimestampMs,
+) -> Result<EmailContent> {
+    let context = LoginLockedContext {
+        locked_until: NaiveDateTime::from(locked_until)
+            .format(DATE_TIME_FORMAT)
+            .to_string(),
     };
+    templates.render(language, "user_login_locked", &context)
+}
 
+pub fn user_email_change_email(
+    templates: &EmailTemplates,
+    language: Language,
+    url: &str,
+) -> Result<EmailContent> {
+    templates.render(language, "user_email_change", &UrlContext { url })
+}
+
+#[derive(Serialize)]
+struct EmailChangedContext<'a> {
+    new_email: &'a str,
+}
+
+pub fn user_email_changed_email(
+    templates: &EmailTemplates,
+    language: Language,
+    new_email: &str,
+) -> Result<EmailContent> {
+    templates.render(
+        language,
+        "user_email_changed",
+        &EmailChangedContext { new_email },
+    )
+}
+
+#[derive(Serialize)]
+struct PlaceContext<'a> {
+    id: &'a str,
+    title: &'a str,
+    description: &'a str,
+    category: String,
+    tags: String,
+    address: String,
+    homepage: &'a str,
+    email: String,
+    phone: String,
+}
+
+#[derive(Serialize)]
+struct PlaceEmailContext<'a> {
+    place: PlaceContext<'a>,
+    entry_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<&'static str>,
+    unsubscribe_url: Option<&'a str>,
+}
+
+fn place_email_context<'a>(
+    place: &'a Place,
+    category_names: &[String],
+    status: Option<ReviewStatus>,
+    unsubscribe_url: Option<&'a str>,
+) -> PlaceEmailContext<'a> {
     let Contact {
         name: _,
         email,
@@ -103,57 +143,279 @@ fn place_email(place: &Place, category_names: &[String], intro_sentence: &str) -
         email: None,
         phone: None,
     });
-
-    format!(
-        "Hallo,\n
-{intro_sentence}:\n
-{title} ({category})
-{description}\n
-    Tags: {tags}
-    Adresse: {address_line}
-    Webseite: {homepage}
-    Email-Adresse: {email}
-    Telefon: {phone}\n
-Eintrag anschauen oder bearbeiten:
-https://kartevonmorgen.org/#/?entry={id}\n
-Du kannst dein Abonnement des Kartenbereichs abbestellen,
-indem du dich auf https://kartevonmorgen.org einloggst.\n
-euphorische Grüße,\n
-das Karte von morgen-Team\n
-{outro_text}",
-        intro_sentence = intro_sentence,
-        outro_text = OUTRO_HINT,
-        id = &place.id,
-        title = &place.title,
-        description = &place.description,
-        address_line = address_line(place.location.address.as_ref()),
-        email = email.map(|e| e.to_string()).unwrap_or_default(),
-        phone = phone.unwrap_or_default(),
-        homepage = place
-            .links
-            .as_ref()
-            .and_then(|l| l.homepage.as_ref())
-            .map(Url::as_str)
-            .unwrap_or_else(|| ""),
-        category = category,
-        tags = place.tags.join(", ")
-    )
+    PlaceEmailContext {
+        place: PlaceContext {
+            id: place.id.as_str(),
+            title: &place.title,
+            description: &place.description,
+            category: category_names.first().cloned().unwrap_or_default(),
+            tags: place.tags.join(", "),
+            address: address_line(place.location.address.as_ref()),
+            homepage: place
+                .links
+                .as_ref()
+                .and_then(|l| l.homepage.as_ref())
+                .map(Url::as_str)
+                .unwrap_or(""),
+            email: email.map(|e| e.to_string()).unwrap_or_default(),
+            phone: phone.unwrap_or_default(),
+        },
+        entry_url: entry_url(place.id.as_str()),
+        status: status.map(review_status_name),
+        unsubscribe_url,
+    }
 }
 
-pub fn event_created_email(event: &Event) -> EmailContent {
-    let subject = subject_entry_created(&event.title);
-    let body = event_email(event, INTRO_ENTRY_CREATED);
-    EmailContent { subject, body }
+pub fn place_created_email(
+    templates: &EmailTemplates,
+    language: Language,
+    place: &Place,
+    category_names: &[String],
+    unsubscribe_url: Option<&str>,
+) -> Result<EmailContent> {
+    let context = place_email_context(place, category_names, None, unsubscribe_url);
+    templates.render(language, "place_created", &context)
 }
 
 //TODO: calc diff
-pub fn event_updated_email(event: &Event) -> EmailContent {
-    let subject = subject_entry_updated(&event.title);
-    let body = event_email(event, INTRO_ENTRY_UPDATED);
-    EmailContent { subject, body }
+pub fn place_updated_email(
+    templates: &EmailTemplates,
+    language: Language,
+    place: &Place,
+    category_names: &[String],
+    unsubscribe_url: Option<&str>,
+) -> Result<EmailContent> {
+    let context = place_email_context(place, category_names, None, unsubscribe_url);
+    templates.render(language, "place_updated", &context)
+}
+
+pub fn place_reviewed_email(
+    templates: &EmailTemplates,
+    language: Language,
+    place: &Place,
+    category_names: &[String],
+    status: ReviewStatus,
+    unsubscribe_url: Option<&str>,
+) -> Result<EmailContent> {
+    let context = place_email_context(place, category_names, Some(status), unsubscribe_url);
+    templates.render(language, "place_reviewed", &context)
+}
+
+#[derive(Serialize)]
+struct OwnPlaceContext<'a> {
+    title: &'a str,
+}
+
+#[derive(Serialize)]
+struct OwnPlaceEmailContext<'a> {
+    place: OwnPlaceContext<'a>,
+    entry_url: String,
+}
+
+fn own_place_email_context(place: &Place) -> OwnPlaceEmailContext<'_> {
+    OwnPlaceEmailContext {
+        place: OwnPlaceContext {
+            title: &place.title,
+        },
+        entry_url: entry_url(place.id.as_str()),
+    }
+}
+
+#[derive(Serialize)]
+struct OwnPlaceReviewedContext<'a> {
+    #[serde(flatten)]
+    own_place: OwnPlaceEmailContext<'a>,
+    status: &'static str,
+    comment: Option<&'a str>,
+}
+
+pub fn own_place_reviewed_email(
+    templates: &EmailTemplates,
+    language: Language,
+    place: &Place,
+    status: ReviewStatus,
+    comment: Option<&str>,
+) -> Result<EmailContent> {
+    let context = OwnPlaceReviewedContext {
+        own_place: own_place_email_context(place),
+        status: review_status_name(status),
+        comment,
+    };
+    templates.render(language, "own_place_reviewed", &context)
+}
+
+#[derive(Serialize)]
+struct RatingEmailContext<'a> {
+    title: &'a str,
+    value: String,
+    comment: &'a str,
+}
+
+#[derive(Serialize)]
+struct OwnPlaceRatedContext<'a> {
+    #[serde(flatten)]
+    own_place: OwnPlaceEmailContext<'a>,
+    rating: RatingEmailContext<'a>,
+}
+
+pub fn own_place_rated_email(
+    templates: &EmailTemplates,
+    language: Language,
+    place: &Place,
+    rating: &Rating,
+    comment: &Comment,
+) -> Result<EmailContent> {
+    let context = OwnPlaceRatedContext {
+        own_place: own_place_email_context(place),
+        rating: RatingEmailContext {
+            title: &rating.title,
+            value: format!("{:+}", i8::from(rating.value)),
+            comment: &comment.text,
+        },
+    };
+    templates.render(language, "own_place_rated", &context)
+}
+
+pub fn own_place_clearance_pending_email(
+    templates: &EmailTemplates,
+    language: Language,
+    place: &Place,
+) -> Result<EmailContent> {
+    let context = own_place_email_context(place);
+    templates.render(language, "own_place_clearance_pending", &context)
+}
+
+#[derive(Serialize)]
+struct DigestChangeContext<'a> {
+    created_at: String,
+    entity_kind: &'static str,
+    change_type: &'static str,
+    title: &'a str,
+    url: String,
+}
+
+#[derive(Serialize)]
+struct DigestSectionContext<'a> {
+    name: &'a str,
+    changes: Vec<DigestChangeContext<'a>>,
+    unsubscribe_url: Option<String>,
+}
+
+#[derive(Serialize)]
+struct DigestContext<'a> {
+    delivery_mode: &'static str,
+    change_count: usize,
+    sections: Vec<DigestSectionContext<'a>>,
+}
+
+/// Renders a digest with a separate unsubscribe link
+/// for each subscription.
+pub fn subscription_digest_email<F>(
+    templates: &EmailTemplates,
+    language: Language,
+    digest: &SubscriptionDigest,
+    unsubscribe_url: F,
+) -> Result<EmailContent>
+where
+    F: Fn(&Id) -> String,
+{
+    let sections = digest
+        .sections
+        .iter()
+        .map(|section| DigestSectionContext {
+            name: &section.subscription_name,
+            changes: section
+                .notifications
+                .iter()
+                .map(|n| DigestChangeContext {
+                    created_at: NaiveDateTime::from(n.created_at)
+                        .format(DATE_TIME_FORMAT)
+                        .to_string(),
+                    entity_kind: n.entity_kind.as_str(),
+                    change_type: n.change_type.as_str(),
+                    title: &n.title,
+                    url: entry_url(n.entity_id.as_str()),
+                })
+                .collect(),
+            unsubscribe_url: section.subscription_id.as_ref().map(&unsubscribe_url),
+        })
+        .collect();
+    let context = DigestContext {
+        delivery_mode: digest.delivery_mode.as_str(),
+        change_count: digest.notification_count(),
+        sections,
+    };
+    templates.render(language, "subscription_digest", &context)
 }
 
-fn event_email(event: &Event, intro_sentence: &str) -> String {
+#[derive(Serialize)]
+struct PendingClearancePlaceContext<'a> {
+    title: &'a str,
+    url: String,
+}
+
+#[derive(Serialize)]
+struct PendingClearanceDigestContext<'a> {
+    org_name: &'a str,
+    places: Vec<PendingClearancePlaceContext<'a>>,
+    new_count: usize,
+    /// New pending clearances that are not listed
+    unlisted_count: usize,
+    total_count: u64,
+    clearance_url: &'a str,
+}
+
+pub fn pending_clearance_digest_email(
+    templates: &EmailTemplates,
+    language: Language,
+    digest: &PendingClearanceDigest,
+    clearance_url: &str,
+) -> Result<EmailContent> {
+    let places = digest
+        .places
+        .iter()
+        .map(|p| PendingClearancePlaceContext {
+            title: &p.title,
+            url: entry_url(p.id.as_str()),
+        })
+        .collect();
+    let context = PendingClearanceDigestContext {
+        org_name: &digest.org_name,
+        new_count: digest.new_count,
+        unlisted_count: digest.new_count.saturating_sub(places.len()),
+        places,
+        total_count: digest.total_count,
+        clearance_url,
+    };
+    templates.render(language, "pending_clearance_digest", &context)
+}
+
+#[derive(Serialize)]
+struct EventContext<'a> {
+    id: &'a str,
+    title: &'a str,
+    description: &'a str,
+    start: String,
+    end: String,
+    organizer: &'a str,
+    tags: String,
+    address: String,
+    homepage: &'a str,
+    email: String,
+    phone: String,
+}
+
+#[derive(Serialize)]
+struct EventEmailContext<'a> {
+    event: EventContext<'a>,
+    entry_url: String,
+    unsubscribe_url: Option<&'a str>,
+}
+
+fn event_email_context<'a>(
+    event: &'a Event,
+    unsubscribe_url: Option<&'a str>,
+) -> EventEmailContext<'a> {
     let Contact {
         name: _,
         email,
@@ -163,45 +425,47 @@ fn event_email(event: &Event, intro_sentence: &str) -> String {
         email: None,
         phone: None,
     });
+    EventEmailContext {
+        event: EventContext {
+            id: event.id.as_str(),
+            title: &event.title,
+            description: event.description.as_deref().unwrap_or(""),
+            start: event.start.format(DATE_TIME_FORMAT).to_string(),
+            end: event
+                .end
+                .map(|end| end.format(DATE_TIME_FORMAT).to_string())
+                .unwrap_or_default(),
+            organizer: event.organizer().map(String::as_str).unwrap_or(""),
+            tags: event.tags.join(", "),
+            address: address_line(event.location.as_ref().and_then(|l| l.address.as_ref())),
+            homepage: event.homepage.as_ref().map(Url::as_str).unwrap_or(""),
+            email: email.map(|e| e.to_string()).unwrap_or_default(),
+            phone: phone.unwrap_or_default(),
+        },
+        entry_url: entry_url(event.id.as_str()),
+        unsubscribe_url,
+    }
+}
 
-    format!(
-        "Hallo,\n
-{intro_sentence}:\n
-{title} ({category})
-{description}\n
-    Beginn: {start}
-    Ende: {end}
-    Tags: {tags}
-    Veranstalter: {organizer}
-    Adresse: {address_line}
-    Webseite: {homepage}
-    Email-Adresse: {email}
-    Telefon: {phone}\n
-Eintrag anschauen oder bearbeiten:
-https://kartevonmorgen.org/#/?entry={id}\n
-Du kannst dein Abonnement des Kartenbereichs abbestellen,
-indem du dich auf https://kartevonmorgen.org einloggst.\n
-euphorische Grüße,\n
-das Karte von morgen-Team\n
-{outro_text}",
-        intro_sentence = intro_sentence,
-        outro_text = OUTRO_HINT,
-        category = "Event",
-        id = &event.id,
-        title = &event.title,
-        start = event.start.format(DATE_TIME_FORMAT),
-        end = event
-            .end
-            .map(|end| end.format(DATE_TIME_FORMAT).to_string())
-            .unwrap_or_default(),
-        description = event.description.as_deref().unwrap_or(""),
-        organizer = event.organizer().map(String::as_str).unwrap_or(""),
-        address_line = address_line(event.location.as_ref().and_then(|l| l.address.as_ref())),
-        email = email.map(|e| e.to_string()).unwrap_or_default(),
-        phone = phone.unwrap_or_default(),
-        homepage = event.homepage.as_ref().map(Url::as_str).unwrap_or(""),
-        tags = event.tags.join(", ")
-    )
+pub fn event_created_email(
+    templates: &EmailTemplates,
+    language: Language,
+    event: &Event,
+    unsubscribe_url: Option<&str>,
+) -> Result<EmailContent> {
+    let context = event_email_context(event, unsubscribe_url);
+    templates.render(language, "event_created", &context)
+}
+
+//TODO: calc diff
+pub fn event_updated_email(
+    templates: &EmailTemplates,
+    language: Language,
+    event: &Event,
+    unsubscribe_url: Option<&str>,
+) -> Result<EmailContent> {
+    let context = event_email_context(event, unsubscribe_url);
+    templates.render(language, "event_updated", &context)
 }
 
 #[cfg(test)]
@@ -210,6 +474,17 @@ mod tests {
     use chrono::Utc;
     use ofdb_entities::{activity::*, geo::*, links::*, location::*, revision::*, time::*};
 
+    const OUTRO_HINT: &str =
+        "Weitere Hinweise und Tipps zur Nutzung, z.B. wie du interaktive Karten
+per <iframe> auf deiner Webseite einbettest oder Papierkarten erstellst,
+findest du hier: https://blog.vonmorgen.org";
+
+    const UNSUBSCRIBE_URL: &str = "https://openfairdb.org/unsubscribe?token=<token>";
+
+    const INTRO_ENTRY_CREATED: &str = "ein neuer Eintrag auf der Karte von morgen wurde erstellt";
+
+    const INTRO_ENTRY_UPDATED: &str = "folgender Eintrag auf der Karte von morgen wurde verändert";
+
     // To verify the formatting manually run these tests and examine
     // the output on stdout:
     //
@@ -228,6 +503,7 @@ mod tests {
             subject = email.subject,
             body = email.body,
         );
+        assert!(email.html_body.is_some());
     }
 
     fn new_place() -> Place {
@@ -300,8 +576,9 @@ mod tests {
 
     #[test]
     fn print_user_registration_email() {
+        let templates = EmailTemplates::builtin();
         let url = "https://kartevonmorgen.org/confirm-email/";
-        let email = user_registration_email(url);
+        let email = user_registration_email(&templates, Language::German, url).unwrap();
         assert!(email.body.contains(OUTRO_HINT));
         assert!(email.body.contains(url));
         print_email(&email);
@@ -309,18 +586,53 @@ mod tests {
 
     #[test]
     fn print_user_reset_password_email() {
+        let templates = EmailTemplates::builtin();
         let url = "https://kartevonmorgen.org/reset-password/";
-        let email = user_reset_password_email(url);
+        let email = user_reset_password_email(&templates, Language::German, url).unwrap();
+        assert!(email.body.contains(url));
+        print_email(&email);
+    }
+
+    #[test]
+    fn print_user_login_locked_email() {
+        let templates = EmailTemplates::builtin();
+        let locked_until = TimestampMs::from_inner(1_600_000_000_000);
+        let email = user_login_locked_email(&templates, Language::German, locked_until).unwrap();
+        assert!(email.body.contains("2020.09.13 12:26:40"));
+        print_email(&email);
+        let email = user_login_locked_email(&templates, Language::English, locked_until).unwrap();
+        assert!(email.body.contains("until 2020.09.13 12:26:40 (UTC)"));
+        print_email(&email);
+    }
+
+    #[test]
+    fn print_user_email_change_emails() {
+        let templates = EmailTemplates::builtin();
+        let url = "https://kartevonmorgen.org/confirm-email-change?token=abc";
+        let email = user_email_change_email(&templates, Language::German, url).unwrap();
         assert!(email.body.contains(url));
         print_email(&email);
+        let email =
+            user_email_changed_email(&templates, Language::German, "new@example.com").unwrap();
+        assert!(email.body.contains("new@example.com"));
+        print_email(&email);
     }
 
     #[test]
     fn print_place_created_email() {
+        let templates = EmailTemplates::builtin();
         let place = new_place();
-        let email = place_created_email(&place, &["<category>".into()]);
+        let email = place_created_email(
+            &templates,
+            Language::German,
+            &place,
+            &["<category>".into()],
+            Some(UNSUBSCRIBE_URL),
+        )
+        .unwrap();
         assert!(email.body.contains(INTRO_ENTRY_CREATED));
         assert!(email.body.contains(OUTRO_HINT));
+        assert!(email.body.contains(UNSUBSCRIBE_URL));
         assert!(email.body.contains(place.id.as_str()));
         assert!(email.body.contains(&place.title));
         print_email(&email);
@@ -328,8 +640,16 @@ mod tests {
 
     #[test]
     fn print_place_updated_email() {
+        let templates = EmailTemplates::builtin();
         let place = new_place();
-        let email = place_updated_email(&place, &["<category>".into()]);
+        let email = place_updated_email(
+            &templates,
+            Language::German,
+            &place,
+            &["<category>".into()],
+            Some(UNSUBSCRIBE_URL),
+        )
+        .unwrap();
         assert!(email.body.contains(INTRO_ENTRY_UPDATED));
         assert!(email.body.contains(OUTRO_HINT));
         assert!(email.body.contains(place.id.as_str()));
@@ -337,10 +657,157 @@ mod tests {
         print_email(&email);
     }
 
+    #[test]
+    fn print_place_reviewed_email() {
+        let templates = EmailTemplates::builtin();
+        let place = new_place();
+        let email = place_reviewed_email(
+            &templates,
+            Language::German,
+            &place,
+            &["<category>".into()],
+            ReviewStatus::Confirmed,
+            None,
+        )
+        .unwrap();
+        assert!(email.body.contains("überprüft und bestätigt"));
+        assert!(email.body.contains(place.id.as_str()));
+        print_email(&email);
+    }
+
+    #[test]
+    fn print_own_place_emails() {
+        let templates = EmailTemplates::builtin();
+        let place = new_place();
+        let email = own_place_reviewed_email(
+            &templates,
+            Language::German,
+            &place,
+            ReviewStatus::Rejected,
+            Some("<comment>"),
+        )
+        .unwrap();
+        assert!(email.body.contains("überprüft und abgelehnt"));
+        assert!(email.body.contains("<comment>"));
+        assert!(email.body.contains("?entry=<id>"));
+        assert!(email.body.contains(OUTRO_HINT));
+        print_email(&email);
+
+        let rating = Rating {
+            id: "<rating>".into(),
+            place_id: place.id.clone(),
+            created_at: Timestamp::now(),
+            archived_at: None,
+            title: "<rating title>".into(),
+            value: RatingValue::new(2),
+            context: RatingContext::Fairness,
+            source: None,
+        };
+        let comment = Comment {
+            id: "<comment>".into(),
+            rating_id: rating.id.clone(),
+            created_at: Timestamp::now(),
+            archived_at: None,
+            text: "<comment text>".into(),
+        };
+        let email = own_place_rated_email(&templates, Language::English, &place, &rating, &comment)
+            .unwrap();
+        assert!(email.subject.contains(&place.title));
+        assert!(email.body.contains("<rating title> (+2)"));
+        assert!(email.body.contains("<comment text>"));
+        print_email(&email);
+
+        let email =
+            own_place_clearance_pending_email(&templates, Language::German, &place).unwrap();
+        assert!(email.body.contains("wartet auf die Freigabe"));
+        assert!(email.body.contains(&place.title));
+        print_email(&email);
+    }
+
+    #[test]
+    fn print_subscription_digest_email() {
+        let templates = EmailTemplates::builtin();
+        let notification = SubscriptionNotification {
+            user_email: "test@example.com".into(),
+            subscription_id: Some("abc".into()),
+            delivery_mode: SubscriptionDeliveryMode::Daily,
+            created_at: TimestampMs::from_inner(1_600_000_000_000),
+            entity_kind: SubscriptionEntityKind::Place,
+            change_type: SubscriptionChangeType::Created,
+            entity_id: "foo".into(),
+            title: "Foo".into(),
+        };
+        let digest = SubscriptionDigest {
+            user_email: "test@example.com".into(),
+            delivery_mode: SubscriptionDeliveryMode::Daily,
+            sections: vec![
+                SubscriptionDigestSection {
+                    subscription_id: Some("abc".into()),
+                    subscription_name: "<subscription name>".into(),
+                    notifications: vec![notification.clone()],
+                },
+                SubscriptionDigestSection {
+                    subscription_id: Some("def".into()),
+                    subscription_name: String::new(),
+                    notifications: vec![SubscriptionNotification {
+                        subscription_id: Some("def".into()),
+                        entity_kind: SubscriptionEntityKind::Event,
+                        change_type: SubscriptionChangeType::Updated,
+                        entity_id: "bar".into(),
+                        title: "Bar".into(),
+                        ..notification
+                    }],
+                },
+            ],
+        };
+        let email = subscription_digest_email(&templates, Language::German, &digest, |id| {
+            UNSUBSCRIBE_URL.replace("<token>", id.as_str())
+        })
+        .unwrap();
+        assert!(email.subject.contains("2 Änderungen"));
+        assert!(email.body.contains("<subscription name>"));
+        assert!(email.body.contains("Abonnierter Kartenbereich"));
+        assert!(email.body.contains("Neuer Eintrag: Foo"));
+        assert!(email.body.contains("Veranstaltung verändert: Bar"));
+        assert!(email.body.contains("?entry=bar"));
+        assert!(email.body.contains("unsubscribe?token=abc"));
+        assert!(email.body.contains("unsubscribe?token=def"));
+        assert!(email.body.contains(OUTRO_HINT));
+        print_email(&email);
+    }
+
+    #[test]
+    fn print_pending_clearance_digest_email() {
+        let templates = EmailTemplates::builtin();
+        let digest = PendingClearanceDigest {
+            org_id: "org".into(),
+            org_name: "<org name>".into(),
+            recipients: vec!["reviewer@example.com".into()],
+            places: vec![new_place()],
+            new_count: 2,
+            total_count: 3,
+            created_until: TimestampMs::now(),
+        };
+        let clearance_url = "https://example.com/clearance";
+        let email =
+            pending_clearance_digest_email(&templates, Language::German, &digest, clearance_url)
+                .unwrap();
+        assert!(email.subject.contains("<org name>"));
+        assert!(email.subject.contains("2 neue Einträge"));
+        assert!(email.body.contains("und 1 weitere Einträge"));
+        assert!(email.body.contains("Insgesamt warten 3 Einträge"));
+        assert!(email.body.contains("?entry=<id>"));
+        assert!(email.body.contains(clearance_url));
+        print_email(&email);
+    }
+
     #[test]
     fn print_event_created_email() {
+        let templates = EmailTemplates::builtin();
         let event = new_event();
-        let email = event_created_email(&event);
+        let email =
+            event_created_email(&templates, Language::German, &event, Some(UNSUBSCRIBE_URL))
+                .unwrap();
         assert!(email.body.contains(INTRO_ENTRY_CREATED));
         assert!(email.body.contains(OUTRO_HINT));
         assert!(email.body.contains(event.id.as_str()));
@@ -350,8 +817,11 @@ mod tests {
 
     #[test]
     fn print_event_updated_email() {
+        let templates = EmailTemplates::builtin();
         let event = new_event();
-        let email = event_updated_email(&event);
+        let email =
+            event_updated_email(&templates, Language::German, &event, Some(UNSUBSCRIBE_URL))
+                .unwrap();
         assert!(email.body.contains(INTRO_ENTRY_UPDATED));
         assert!(email.body.contains(OUTRO_HINT));
         assert!(email.body.contains(event.id.as_str()));
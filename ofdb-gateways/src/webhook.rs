@@ -0,0 +1,255 @@
+use hmac::{Hmac, Mac, NewMac};
+use ofdb_core::{
+    gateways::webhook::{WebhookGateway, WebhookRequest},
+    webhook::is_public_ip,
+};
+use ofdb_entities::url::Url;
+use sha2::Sha256;
+use std::{
+    fmt::Write,
+    net::{IpAddr, SocketAddr, ToSocketAddrs},
+    time::Duration,
+};
+
+pub const EVENT_HEADER: &str = "X-OFDB-Event";
+pub const DELIVERY_HEADER: &str = "X-OFDB-Delivery";
+pub const SIGNATURE_HEADER: &str = "X-OFDB-Signature";
+
+const SIGNATURE_PREFIX: &str = "sha256=";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The value of the signature header, i.e. the hex-encoded
+/// HMAC-SHA256 of the payload using the shared secret as key.
+pub fn sign_payload(secret: &str, payload: &str) -> String {
+    // HMAC accepts keys of any length
+    let mut mac = Hmac::<Sha256>::new_varkey(secret.as_bytes()).expect("HMAC key");
+    mac.update(payload.as_bytes());
+    let mut signature = String::from(SIGNATURE_PREFIX);
+    for b in mac.finalize().into_bytes() {
+        let _ = write!(signature, "{:02x}", b);
+    }
+    signature
+}
+
+/// The addresses of the host of an URL.
+struct ResolvedHost {
+    /// The domain name unless the host is an IP address
+    domain: Option<String>,
+    addrs: Vec<SocketAddr>,
+}
+
+fn resolve_host(url: &Url) -> Result<ResolvedHost, String> {
+    let url = reqwest::Url::parse(url.as_str()).map_err(|err| err.to_string())?;
+    let port = url
+        .port_or_known_default()
+        .ok_or_else(|| format!("Unknown port of {}", url))?;
+    let host = url
+        .host_str()
+        .ok_or_else(|| format!("Missing host of {}", url))?;
+    // IPv6 addresses are enclosed in brackets
+    if let Ok(ip) = host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+    {
+        return Ok(ResolvedHost {
+            domain: None,
+            addrs: vec![SocketAddr::new(ip, port)],
+        });
+    }
+    let addrs: Vec<_> = (host, port)
+        .to_socket_addrs()
+        .map_err(|err| format!("Failed to resolve {}: {}", host, err))?
+        .collect();
+    if addrs.is_empty() {
+        return Err(format!("No address found for {}", host));
+    }
+    Ok(ResolvedHost {
+        domain: Some(host.to_owned()),
+        addrs,
+    })
+}
+
+/// Delivers webhook payloads via HTTP POST requests.
+///
+/// Requests are only sent to public IP addresses and
+/// redirects are not followed.
+#[derive(Debug, Clone)]
+pub struct HttpWebhookGateway {
+    allow_private_addresses: bool,
+}
+
+impl HttpWebhookGateway {
+    pub fn new() -> Self {
+        Self {
+            allow_private_addresses: false,
+        }
+    }
+
+    // For testing with servers on the local host
+    #[cfg(test)]
+    fn allowing_private_addresses() -> Self {
+        Self {
+            allow_private_addresses: true,
+        }
+    }
+
+    fn resolve_checked_host(&self, url: &Url) -> Result<ResolvedHost, String> {
+        let host = resolve_host(url)?;
+        if self.allow_private_addresses {
+            return Ok(host);
+        }
+        if let Some(addr) = host.addrs.iter().find(|addr| !is_public_ip(addr.ip())) {
+            return Err(format!(
+                "{} resolves to the non-public address {}",
+                url,
+                addr.ip()
+            ));
+        }
+        Ok(host)
+    }
+}
+
+impl Default for HttpWebhookGateway {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WebhookGateway for HttpWebhookGateway {
+    fn check_url(&self, url: &Url) -> Result<(), String> {
+        self.resolve_checked_host(url).map(|_| ())
+    }
+
+    fn post(&self, request: &WebhookRequest) -> Result<u16, String> {
+        let WebhookRequest {
+            url,
+            secret,
+            delivery_id,
+            event_type,
+            payload,
+        } = request;
+        // The address might have changed since the webhook has been registered
+        let host = self.resolve_checked_host(url)?;
+        let mut client = reqwest::blocking::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none());
+        if let Some(domain) = &host.domain {
+            // Connect to the checked address instead of resolving
+            // the domain again, which might return another address
+            client = client.resolve(domain, host.addrs[0]);
+        }
+        let client = client.build().map_err(|err| err.to_string())?;
+        debug!("Delivering webhook {} to {}", delivery_id, url);
+        client
+            .post(url.as_str())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, event_type.as_str())
+            .header(DELIVERY_HEADER, *delivery_id)
+            .header(SIGNATURE_HEADER, sign_payload(secret, payload))
+            .body(payload.to_string())
+            .send()
+            .map(|res| res.status().as_u16())
+            .map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use ofdb_entities::{url::Url, webhook::WebhookEventType};
+    use std::{net::TcpListener, sync::mpsc};
+
+    /// A local stand-in for the HTTP server of an organization
+    /// that responds with the given status code and returns
+    /// the raw request.
+    fn serve_once(status: u16) -> (Url, mpsc::Receiver<String>) {
+        let (base_url, rx) = test_util::serve_once(status, "");
+        (format!("{}/hook", base_url).parse().unwrap(), rx)
+    }
+
+    fn request<'a>(url: &'a Url, payload: &'a str) -> WebhookRequest<'a> {
+        WebhookRequest {
+            url,
+            secret: "secret",
+            delivery_id: "delivery-1",
+            event_type: WebhookEventType::PlaceChanged,
+            payload,
+        }
+    }
+
+    #[test]
+    fn sign_payload_with_hmac_sha256() {
+        // Test vector from RFC 4231 (test case 2)
+        assert_eq!(
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            sign_payload("Jefe", "what do ya want for nothing?")
+        );
+    }
+
+    #[test]
+    fn post_signed_payload() {
+        let (url, rx) = serve_once(200);
+        let payload = r#"{"type":"place.changed"}"#;
+        let gw = HttpWebhookGateway::allowing_private_addresses();
+        assert_eq!(Ok(200), gw.post(&request(&url, payload)));
+        let raw = rx.recv().unwrap().to_lowercase();
+        assert!(raw.starts_with("post /hook http/1.1"));
+        assert!(raw.contains("x-ofdb-event: place.changed"));
+        assert!(raw.contains("x-ofdb-delivery: delivery-1"));
+        assert!(raw.contains(&format!(
+            "x-ofdb-signature: {}",
+            sign_payload("secret", payload)
+        )));
+        assert!(raw.ends_with(&payload.to_lowercase()));
+    }
+
+    #[test]
+    fn resolve_domains_but_not_addresses() {
+        let host = resolve_host(&"http://localhost:8080/hook".parse().unwrap()).unwrap();
+        assert_eq!(Some("localhost"), host.domain.as_deref());
+        assert!(host.addrs.iter().all(|addr| addr.port() == 8080));
+        let host = resolve_host(&"https://[::1]/hook".parse().unwrap()).unwrap();
+        assert!(host.domain.is_none());
+        assert_eq!(vec!["[::1]:443".parse::<SocketAddr>().unwrap()], host.addrs);
+    }
+
+    #[test]
+    fn return_error_status() {
+        let (url, _rx) = serve_once(503);
+        let gw = HttpWebhookGateway::allowing_private_addresses();
+        assert_eq!(Ok(503), gw.post(&request(&url, "{}")));
+    }
+
+    #[test]
+    fn unreachable_server() {
+        // Bind and immediately drop the listener to obtain an unused port
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let url = format!("http://{}/hook", addr).parse().unwrap();
+        let gw = HttpWebhookGateway::allowing_private_addresses();
+        assert!(gw.post(&request(&url, "{}")).is_err());
+    }
+
+    #[test]
+    fn reject_non_public_addresses() {
+        let gw = HttpWebhookGateway::new();
+        let check = |url: &str| gw.check_url(&url.parse().unwrap());
+        assert!(check("https://93.184.216.34/hook").is_ok());
+        assert!(check("http://127.0.0.1:8080/hook").is_err());
+        assert!(check("http://[::1]/hook").is_err());
+        assert!(check("http://169.254.169.254/latest/meta-data").is_err());
+        assert!(check("http://localhost/hook").is_err());
+
+        // No request is sent
+        let (url, rx) = serve_once(200);
+        assert!(gw.post(&request(&url, "{}")).is_err());
+        assert!(rx
+            .recv_timeout(std::time::Duration::from_millis(100))
+            .is_err());
+    }
+}
@@ -0,0 +1,346 @@
+//! Syndication feeds of recently changed places and events,
+//! either as [Atom](https://tools.ietf.org/html/rfc4287) or as
+//! [JSON Feed](https://www.jsonfeed.org/version/1.1/).
+
+use crate::core::{
+    entities::{ChangeType, Place},
+    usecases::{EventChange, PlaceChange},
+    util::time::TimestampMs,
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use std::fmt::Write;
+
+pub const ATOM_MEDIA_TYPE_TOP: &str = "application";
+pub const ATOM_MEDIA_TYPE_SUB: &str = "atom+xml";
+
+pub const JSON_FEED_MEDIA_TYPE_TOP: &str = "application";
+pub const JSON_FEED_MEDIA_TYPE_SUB: &str = "feed+json";
+
+const JSON_FEED_VERSION: &str = "https://jsonfeed.org/version/1.1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedKind {
+    Places,
+    Events,
+}
+
+impl FeedKind {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Places => "places",
+            Self::Events => "events",
+        }
+    }
+
+    const fn title(self) -> &'static str {
+        match self {
+            Self::Places => "OpenFairDB: Recently changed places",
+            Self::Events => "OpenFairDB: Recently changed events",
+        }
+    }
+}
+
+/// Absolute links of a feed page.
+#[derive(Debug, Clone)]
+pub struct FeedLinks {
+    /// The current page
+    pub this: String,
+    /// The next page with older changes
+    pub next: Option<String>,
+}
+
+/// A single change, independent of the feed format.
+#[derive(Debug, Clone)]
+pub struct FeedItem {
+    pub id: String,
+    pub url: Option<String>,
+    pub title: String,
+    pub summary: Option<String>,
+    pub change: ChangeType,
+    pub changed_at: TimestampMs,
+    pub tags: Vec<String>,
+    pub pos: Option<(f64, f64)>,
+}
+
+fn rfc3339(at: TimestampMs) -> String {
+    DateTime::<Utc>::from(at).to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn change_urn(kind: &str, id: &str, change: ChangeType, at: TimestampMs) -> String {
+    format!(
+        "urn:ofdb:{}:{}:{}:{}",
+        kind,
+        id,
+        change.as_str(),
+        at.into_inner()
+    )
+}
+
+fn place_summary(place: &Place) -> Option<String> {
+    Some(place.description.clone()).filter(|d| !d.is_empty())
+}
+
+fn entry_url(map_app_url: &str, id: &str) -> String {
+    format!("{}/#/?entry={}", map_app_url.trim_end_matches('/'), id)
+}
+
+impl FeedItem {
+    /// Links to the place in the map app.
+    pub fn from_place_change(from: &PlaceChange, map_app_url: &str) -> Self {
+        let PlaceChange {
+            place,
+            change,
+            changed_at,
+            ..
+        } = from;
+        let (lat, lng) = place.location.pos.to_lat_lng_deg();
+        Self {
+            id: change_urn("place", place.id.as_str(), *change, *changed_at),
+            url: Some(entry_url(map_app_url, place.id.as_str())),
+            title: place.title.clone(),
+            summary: place_summary(place),
+            change: *change,
+            changed_at: *changed_at,
+            tags: place.tags.clone(),
+            pos: Some((lat, lng)),
+        }
+    }
+}
+
+impl From<&EventChange> for FeedItem {
+    fn from(from: &EventChange) -> Self {
+        let EventChange {
+            event,
+            change,
+            changed_at,
+        } = from;
+        let pos = event
+            .location
+            .as_ref()
+            .map(|l| l.pos)
+            .filter(|pos| pos.is_valid())
+            .map(|pos| pos.to_lat_lng_deg());
+        Self {
+            id: change_urn("event", event.id.as_str(), *change, *changed_at),
+            url: event.homepage.as_ref().map(ToString::to_string),
+            title: event.title.clone(),
+            summary: event.description.clone().filter(|d| !d.is_empty()),
+            change: *change,
+            changed_at: *changed_at,
+            tags: event.tags.clone(),
+            pos,
+        }
+    }
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters are not allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Renders an Atom feed document.
+///
+/// The change type of each entry is provided as a category
+/// with the scheme `urn:ofdb:change`.
+pub fn to_atom(kind: FeedKind, links: &FeedLinks, items: &[FeedItem]) -> String {
+    let updated = items
+        .iter()
+        .map(|i| i.changed_at)
+        .max()
+        .unwrap_or_else(TimestampMs::now);
+    let mut xml = String::new();
+    // Writing into a String never fails
+    let _ = write!(
+        xml,
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom" xmlns:georss="http://www.georss.org/georss">
+<id>urn:ofdb:feed:{kind}</id>
+<title>{title}</title>
+<updated>{updated}</updated>
+<link rel="self" href="{this}"/>
+"#,
+        kind = kind.as_str(),
+        title = escape_xml(kind.title()),
+        updated = rfc3339(updated),
+        this = escape_xml(&links.this),
+    );
+    if let Some(next) = &links.next {
+        let _ = writeln!(xml, r#"<link rel="next" href="{}"/>"#, escape_xml(next));
+    }
+    for item in items {
+        let _ = write!(
+            xml,
+            r#"<entry>
+<id>{id}</id>
+<title>{title}</title>
+<updated>{updated}</updated>
+<author><name>OpenFairDB</name></author>
+<category scheme="urn:ofdb:change" term="{change}"/>
+"#,
+            id = escape_xml(&item.id),
+            title = escape_xml(&item.title),
+            updated = rfc3339(item.changed_at),
+            change = item.change.as_str(),
+        );
+        if let Some(url) = &item.url {
+            let _ = writeln!(xml, r#"<link rel="alternate" href="{}"/>"#, escape_xml(url));
+        }
+        for tag in &item.tags {
+            let _ = writeln!(xml, r#"<category term="{}"/>"#, escape_xml(tag));
+        }
+        if let Some(summary) = &item.summary {
+            let _ = writeln!(xml, "<summary>{}</summary>", escape_xml(summary));
+        }
+        if let Some((lat, lng)) = item.pos {
+            let _ = writeln!(xml, "<georss:point>{} {}</georss:point>", lat, lng);
+        }
+        xml.push_str("</entry>\n");
+    }
+    xml.push_str("</feed>\n");
+    xml
+}
+
+#[derive(Serialize)]
+pub struct JsonFeed {
+    pub version: &'static str,
+    pub title: &'static str,
+    pub feed_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_url: Option<String>,
+    pub items: Vec<JsonFeedItem>,
+}
+
+#[derive(Serialize)]
+pub struct JsonFeedItem {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_text: Option<String>,
+    pub date_modified: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(rename = "_ofdb")]
+    pub ofdb: JsonFeedItemExtension,
+}
+
+/// Custom extension of JSON Feed items.
+#[derive(Serialize)]
+pub struct JsonFeedItemExtension {
+    pub change: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lat: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lng: Option<f64>,
+}
+
+impl From<FeedItem> for JsonFeedItem {
+    fn from(from: FeedItem) -> Self {
+        let FeedItem {
+            id,
+            url,
+            title,
+            summary,
+            change,
+            changed_at,
+            tags,
+            pos,
+        } = from;
+        Self {
+            id,
+            url,
+            title,
+            content_text: summary,
+            date_modified: rfc3339(changed_at),
+            tags,
+            ofdb: JsonFeedItemExtension {
+                change: change.as_str(),
+                lat: pos.map(|(lat, _)| lat),
+                lng: pos.map(|(_, lng)| lng),
+            },
+        }
+    }
+}
+
+pub fn to_json_feed(kind: FeedKind, links: FeedLinks, items: Vec<FeedItem>) -> JsonFeed {
+    let FeedLinks { this, next } = links;
+    JsonFeed {
+        version: JSON_FEED_VERSION,
+        title: kind.title(),
+        feed_url: this,
+        next_url: next,
+        items: items.into_iter().map(Into::into).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item() -> FeedItem {
+        FeedItem {
+            id: "urn:ofdb:place:foo:created:1000".into(),
+            url: Some(entry_url("https://kartevonmorgen.org/", "foo")),
+            title: "Fish & <Chips>".into(),
+            summary: None,
+            change: ChangeType::Created,
+            changed_at: TimestampMs::from_inner(1_000),
+            tags: vec!["bio".into()],
+            pos: Some((48.5, 9.25)),
+        }
+    }
+
+    fn links() -> FeedLinks {
+        FeedLinks {
+            this: "https://api.ofdb.io/v0/feeds/places.atom?tags=a&b".into(),
+            next: Some("https://api.ofdb.io/v0/feeds/places.atom?offset=1".into()),
+        }
+    }
+
+    #[test]
+    fn escape_atom_entries() {
+        let xml = to_atom(FeedKind::Places, &links(), &[item()]);
+        assert!(xml.contains(
+            r#"<link rel="self" href="https://api.ofdb.io/v0/feeds/places.atom?tags=a&amp;b"/>"#
+        ));
+        assert!(xml.contains(
+            r#"<link rel="next" href="https://api.ofdb.io/v0/feeds/places.atom?offset=1"/>"#
+        ));
+        assert!(xml
+            .contains(r#"<link rel="alternate" href="https://kartevonmorgen.org/#/?entry=foo"/>"#));
+        assert!(xml.contains("<title>Fish &amp; &lt;Chips&gt;</title>"));
+        assert!(xml.contains("<updated>1970-01-01T00:00:01Z</updated>"));
+        assert!(xml.contains(r#"<category scheme="urn:ofdb:change" term="created"/>"#));
+        assert!(xml.contains(r#"<category term="bio"/>"#));
+        assert!(xml.contains("<georss:point>48.5 9.25</georss:point>"));
+        assert!(!xml.contains("<summary>"));
+    }
+
+    #[test]
+    fn json_feed_items() {
+        let feed = to_json_feed(FeedKind::Events, links(), vec![item()]);
+        let json = serde_json::to_value(&feed).unwrap();
+        assert_eq!(JSON_FEED_VERSION, json["version"]);
+        assert_eq!(
+            "https://api.ofdb.io/v0/feeds/places.atom?offset=1",
+            json["next_url"]
+        );
+        assert_eq!("created", json["items"][0]["_ofdb"]["change"]);
+        assert_eq!("1970-01-01T00:00:01Z", json["items"][0]["date_modified"]);
+        assert!(json["items"][0].get("content_text").is_none());
+    }
+}
@@ -1,11 +1,154 @@
-use crate::core::{db::IndexedPlace, entities as e, usecases};
+use crate::core::{db::IndexedPlace, entities as e, error::ParameterError, usecases};
+use std::convert::TryFrom;
 
 pub use ofdb_boundary::*;
 
 impl From<Credentials> for usecases::Login {
     fn from(from: Credentials) -> Self {
-        let Credentials { email, password } = from;
-        Self { email, password }
+        let Credentials {
+            email,
+            password,
+            totp_code,
+        } = from;
+        Self {
+            email,
+            password,
+            totp_code,
+        }
+    }
+}
+
+impl From<NewApiToken> for usecases::NewApiToken {
+    fn from(from: NewApiToken) -> Self {
+        let NewApiToken {
+            name,
+            scopes,
+            expires_at,
+        } = from;
+        Self {
+            name,
+            scopes,
+            expires_at: expires_at.map(e::TimestampMs::from_inner),
+        }
+    }
+}
+
+impl From<usecases::IssuedApiToken> for IssuedApiToken {
+    fn from(from: usecases::IssuedApiToken) -> Self {
+        let usecases::IssuedApiToken { api_token, secret } = from;
+        Self {
+            secret,
+            api_token: api_token.into(),
+        }
+    }
+}
+
+impl From<usecases::CreatedOrganization> for CreatedOrganization {
+    fn from(from: usecases::CreatedOrganization) -> Self {
+        let usecases::CreatedOrganization {
+            organization,
+            api_token,
+        } = from;
+        Self {
+            api_token,
+            organization: organization.into(),
+        }
+    }
+}
+
+impl From<NewOrganization> for usecases::NewOrganization {
+    fn from(from: NewOrganization) -> Self {
+        let NewOrganization {
+            name,
+            moderated_tags,
+        } = from;
+        Self {
+            name,
+            moderated_tags: moderated_tags.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<usecases::Membership> for OrganizationMembership {
+    fn from(from: usecases::Membership) -> Self {
+        let usecases::Membership { organization, role } = from;
+        Self {
+            organization: organization.into(),
+            role: role.as_str().to_string(),
+        }
+    }
+}
+
+impl From<usecases::TotpEnrollment> for TotpEnrollment {
+    fn from(from: usecases::TotpEnrollment) -> Self {
+        let usecases::TotpEnrollment {
+            secret,
+            otpauth_uri,
+        } = from;
+        Self {
+            secret,
+            otpauth_uri,
+        }
+    }
+}
+
+impl From<usecases::TwoFactorStatus> for TwoFactorStatus {
+    fn from(from: usecases::TwoFactorStatus) -> Self {
+        let usecases::TwoFactorStatus {
+            enabled,
+            unused_recovery_codes,
+        } = from;
+        Self {
+            enabled,
+            unused_recovery_codes,
+        }
+    }
+}
+
+impl TryFrom<NewBboxSubscription> for usecases::NewBboxSubscription {
+    type Error = ParameterError;
+    fn try_from(from: NewBboxSubscription) -> Result<Self, Self::Error> {
+        let NewBboxSubscription {
+            name,
+            bbox,
+            filter,
+            delivery_mode,
+        } = from;
+        let SubscriptionFilter {
+            tags,
+            categories,
+            entity_kind,
+            change_types,
+        } = filter;
+        let MapBbox { sw, ne } = bbox;
+        let sw =
+            e::MapPoint::try_from_lat_lng_deg(sw.lat, sw.lng).map_err(|_| ParameterError::Bbox)?;
+        let ne =
+            e::MapPoint::try_from_lat_lng_deg(ne.lat, ne.lng).map_err(|_| ParameterError::Bbox)?;
+        Ok(Self {
+            name,
+            bbox: e::MapBbox::new(sw, ne),
+            tags,
+            categories,
+            entity_kind,
+            change_types,
+            delivery_mode,
+        })
+    }
+}
+
+impl From<NewWebhook> for usecases::NewWebhook {
+    fn from(from: NewWebhook) -> Self {
+        let NewWebhook {
+            url,
+            secret,
+            event_types,
+        } = from;
+        Self {
+            url,
+            secret,
+            event_types,
+        }
     }
 }
 
@@ -61,6 +204,26 @@ impl From<IndexedPlace> for PlaceSearchResult {
     }
 }
 
+impl From<usecases::PlaceCluster> for PlaceCluster {
+    fn from(from: usecases::PlaceCluster) -> Self {
+        let usecases::PlaceCluster {
+            pos,
+            count,
+            categories,
+            place_id,
+            truncated,
+        } = from;
+        Self {
+            lat: pos.lat().to_deg(),
+            lng: pos.lng().to_deg(),
+            count: count as u64,
+            categories,
+            id: place_id,
+            truncated,
+        }
+    }
+}
+
 impl From<CustomLink> for usecases::CustomLinkParam {
     fn from(from: CustomLink) -> Self {
         let CustomLink {
@@ -254,3 +417,15 @@ pub fn entry_from_place_with_ratings(place: e::Place, ratings: Vec<e::Rating>) -
         custom_links: custom_links.into_iter().map(Into::into).collect(),
     }
 }
+
+impl From<usecases::EmailOutboxStatus> for EmailOutboxStatus {
+    fn from(from: usecases::EmailOutboxStatus) -> Self {
+        let usecases::EmailOutboxStatus { stats, failed } = from;
+        Self {
+            pending: stats.pending,
+            sent: stats.sent,
+            failed: stats.failed,
+            dead_letters: failed.into_iter().map(Into::into).collect(),
+        }
+    }
+}
@@ -0,0 +1,423 @@
+//! Linked data representations of places and events
+//! according to the [schema.org](https://schema.org) vocabulary.
+
+use crate::core::entities as e;
+use serde::Serialize;
+
+pub const CONTEXT: &str = "https://schema.org";
+
+pub const MEDIA_TYPE_TOP: &str = "application";
+pub const MEDIA_TYPE_SUB: &str = "ld+json";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeoCoordinates {
+    #[serde(rename = "@type")]
+    pub type_: &'static str,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostalAddress {
+    #[serde(rename = "@type")]
+    pub type_: &'static str,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub street_address: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub postal_code: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address_locality: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address_region: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address_country: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Place {
+    #[serde(rename = "@type")]
+    pub type_: &'static str,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub geo: Option<GeoCoordinates>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<PostalAddress>,
+}
+
+/// Either a `LocalBusiness` or a plain `Organization`.
+///
+/// Places with opening hours are considered to be local businesses
+/// that could be visited by customers. The geo coordinates of an
+/// `Organization` are nested within its `location`, because the
+/// `geo` property is only defined for `Place`s and their subtypes
+/// like `LocalBusiness`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Organization {
+    #[serde(rename = "@context", skip_serializing_if = "Option::is_none")]
+    pub context: Option<&'static str>,
+
+    #[serde(rename = "@type")]
+    pub type_: &'static str,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub telephone: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<PostalAddress>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub geo: Option<GeoCoordinates>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Place>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub opening_hours: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub founding_date: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Event {
+    #[serde(rename = "@context")]
+    pub context: &'static str,
+
+    #[serde(rename = "@type")]
+    pub type_: &'static str,
+
+    pub identifier: String,
+
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    pub start_date: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Place>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organizer: Option<Organization>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<String>,
+}
+
+impl From<&e::MapPoint> for GeoCoordinates {
+    fn from(from: &e::MapPoint) -> Self {
+        Self {
+            type_: "GeoCoordinates",
+            latitude: from.lat().to_deg(),
+            longitude: from.lng().to_deg(),
+        }
+    }
+}
+
+impl From<&e::Address> for PostalAddress {
+    fn from(from: &e::Address) -> Self {
+        let e::Address {
+            street,
+            zip,
+            city,
+            country,
+            state,
+        } = from;
+        Self {
+            type_: "PostalAddress",
+            street_address: street.clone(),
+            postal_code: zip.clone(),
+            address_locality: city.clone(),
+            address_region: state.clone(),
+            address_country: country.clone(),
+        }
+    }
+}
+
+fn postal_address(address: Option<&e::Address>) -> Option<PostalAddress> {
+    address.filter(|a| !a.is_empty()).map(Into::into)
+}
+
+fn keywords(tags: &[String]) -> Option<String> {
+    if tags.is_empty() {
+        None
+    } else {
+        Some(tags.join(","))
+    }
+}
+
+// All events are stored as UTC timestamps
+fn utc_date_time(dt: &chrono::NaiveDateTime) -> String {
+    dt.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+impl From<&e::Place> for Organization {
+    fn from(from: &e::Place) -> Self {
+        let e::Place {
+            id,
+            title,
+            description,
+            location,
+            contact,
+            opening_hours,
+            founded_on,
+            links,
+            tags,
+            ..
+        } = from;
+        let (tags, _) = e::Category::split_from_tags(tags.clone());
+        let address = postal_address(location.address.as_ref());
+        let geo = Some(GeoCoordinates::from(&location.pos)).filter(|_| location.pos.is_valid());
+        let (type_, address, geo, location) = if opening_hours.is_some() {
+            ("LocalBusiness", address, geo, None)
+        } else {
+            let location = Place {
+                type_: "Place",
+                geo,
+                address: postal_address(location.address.as_ref()),
+            };
+            ("Organization", address, None, Some(location))
+        };
+        let (email, telephone) = contact
+            .as_ref()
+            .map(|c| (c.email.as_ref().map(ToString::to_string), c.phone.clone()))
+            .unwrap_or_default();
+        let (url, image) = links
+            .as_ref()
+            .map(|l| {
+                (
+                    l.homepage.as_ref().map(ToString::to_string),
+                    l.image.as_ref().map(ToString::to_string),
+                )
+            })
+            .unwrap_or_default();
+        Self {
+            context: Some(CONTEXT),
+            type_,
+            identifier: Some(id.to_string()),
+            name: Some(title.clone()),
+            description: Some(description.clone()).filter(|d| !d.is_empty()),
+            url,
+            email,
+            telephone,
+            image,
+            address,
+            geo,
+            location,
+            opening_hours: opening_hours.clone().map(Into::into),
+            founding_date: founded_on.map(|d| d.format("%Y-%m-%d").to_string()),
+            keywords: keywords(&tags),
+        }
+    }
+}
+
+impl From<&e::Event> for Event {
+    fn from(from: &e::Event) -> Self {
+        let e::Event {
+            id,
+            title,
+            description,
+            start,
+            end,
+            location,
+            contact,
+            tags,
+            homepage,
+            image_url,
+            ..
+        } = from;
+        let location = location.as_ref().map(|l| Place {
+            type_: "Place",
+            geo: Some(GeoCoordinates::from(&l.pos)).filter(|_| l.pos.is_valid()),
+            address: postal_address(l.address.as_ref()),
+        });
+        let organizer = contact
+            .as_ref()
+            .filter(|c| c.name.is_some())
+            .map(|c| Organization {
+                context: None,
+                type_: "Organization",
+                identifier: None,
+                name: c.name.clone(),
+                description: None,
+                url: None,
+                email: c.email.as_ref().map(ToString::to_string),
+                telephone: c.phone.clone(),
+                image: None,
+                address: None,
+                geo: None,
+                location: None,
+                opening_hours: None,
+                founding_date: None,
+                keywords: None,
+            });
+        Self {
+            context: CONTEXT,
+            type_: "Event",
+            identifier: id.to_string(),
+            name: title.clone(),
+            description: description.clone(),
+            start_date: utc_date_time(start),
+            end_date: end.as_ref().map(utc_date_time),
+            url: homepage.as_ref().map(ToString::to_string),
+            image: image_url.as_ref().map(ToString::to_string),
+            location,
+            organizer,
+            keywords: keywords(tags),
+        }
+    }
+}
+
+/// Serialize a JSON-LD object for embedding it
+/// into a `<script type="application/ld+json">`
+/// element of an HTML page.
+pub fn to_script_content<T: Serialize>(obj: &T) -> String {
+    serde_json::to_string(obj)
+        .map(|json| json.replace("</", "<\\/"))
+        .unwrap_or_else(|err| {
+            log::warn!("Failed to serialize JSON-LD: {}", err);
+            String::new()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{entities::*, util::geo::MapPoint};
+
+    fn new_place() -> e::Place {
+        e::Place::build()
+            .id("foo")
+            .title("A title")
+            .description("A description")
+            .pos(MapPoint::from_lat_lng_deg(48.1, 9.2))
+            .tags(vec!["bio", "fair", e::Category::TAG_NON_PROFIT])
+            .finish()
+    }
+
+    #[test]
+    fn place_without_opening_hours_is_an_organization() {
+        let place = new_place();
+        let json = serde_json::to_value(Organization::from(&place)).unwrap();
+        assert_eq!(json["@context"], CONTEXT);
+        assert_eq!(json["@type"], "Organization");
+        assert_eq!(json["identifier"], "foo");
+        assert_eq!(json["name"], "A title");
+        assert_eq!(json["location"]["geo"]["@type"], "GeoCoordinates");
+        assert_eq!(json["location"]["geo"]["latitude"], 48.1);
+        assert_eq!(json["location"]["geo"]["longitude"], 9.2);
+        assert_eq!(json["keywords"], "bio,fair");
+        assert!(json.get("geo").is_none());
+        assert!(json.get("openingHours").is_none());
+    }
+
+    #[test]
+    fn place_with_opening_hours_is_a_local_business() {
+        let mut place = new_place();
+        place.opening_hours = Some("Mo-Fr 08:00-18:00".parse().unwrap());
+        place.location.address = Some(Address {
+            street: Some("Hauptstr. 1".into()),
+            zip: Some("12345".into()),
+            city: Some("Stuttgart".into()),
+            country: Some("Germany".into()),
+            state: None,
+        });
+        let json = serde_json::to_value(Organization::from(&place)).unwrap();
+        assert_eq!(json["@type"], "LocalBusiness");
+        assert_eq!(json["openingHours"], "Mo-Fr 08:00-18:00");
+        assert_eq!(json["geo"]["latitude"], 48.1);
+        assert_eq!(json["address"]["@type"], "PostalAddress");
+        assert_eq!(json["address"]["streetAddress"], "Hauptstr. 1");
+        assert_eq!(json["address"]["postalCode"], "12345");
+        assert_eq!(json["address"]["addressLocality"], "Stuttgart");
+        assert_eq!(json["address"]["addressCountry"], "Germany");
+        assert!(json["address"].get("addressRegion").is_none());
+        assert!(json.get("location").is_none());
+    }
+
+    #[test]
+    fn event_with_location_and_organizer() {
+        let event = e::Event {
+            id: "bar".into(),
+            title: "An event".into(),
+            description: None,
+            start: chrono::NaiveDateTime::from_timestamp(1_600_000_000, 0),
+            end: None,
+            location: Some(Location {
+                pos: MapPoint::from_lat_lng_deg(48.1, 9.2),
+                address: None,
+            }),
+            contact: Some(Contact {
+                name: Some("Organizer".into()),
+                email: None,
+                phone: None,
+            }),
+            tags: vec![],
+            homepage: None,
+            created_by: None,
+            registration: None,
+            archived: None,
+            image_url: None,
+            image_link_url: None,
+        };
+        let json = serde_json::to_value(Event::from(&event)).unwrap();
+        assert_eq!(json["@context"], CONTEXT);
+        assert_eq!(json["@type"], "Event");
+        assert_eq!(json["startDate"], "2020-09-13T12:26:40Z");
+        assert!(json.get("endDate").is_none());
+        assert_eq!(json["location"]["geo"]["longitude"], 9.2);
+        assert_eq!(json["organizer"]["@type"], "Organization");
+        assert_eq!(json["organizer"]["name"], "Organizer");
+        assert!(json["organizer"].get("@context").is_none());
+    }
+
+    #[test]
+    fn escape_closing_script_tags() {
+        let mut place = new_place();
+        place.description = "</script><script>alert(1)</script>".into();
+        let content = to_script_content(&Organization::from(&place));
+        assert!(!content.contains("</"));
+    }
+}
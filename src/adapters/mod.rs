@@ -1,2 +1,5 @@
 pub mod csv;
+pub mod feed;
 pub mod json;
+pub mod json_ld;
+pub mod sse;
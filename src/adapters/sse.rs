@@ -0,0 +1,109 @@
+//! Changes as [Server-Sent Events](https://html.spec.whatwg.org/multipage/server-sent-events.html).
+
+use crate::core::{entities::ChangeLogEntry, usecases::ChangeStreamBatch};
+use serde::Serialize;
+use std::fmt::Write;
+
+pub const MEDIA_TYPE_TOP: &str = "text";
+pub const MEDIA_TYPE_SUB: &str = "event-stream";
+
+#[derive(Serialize)]
+pub struct StreamedChange<'a> {
+    pub kind: &'static str,
+    pub id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub place_id: Option<&'a str>,
+    pub change: &'static str,
+    pub at: i64,
+    pub title: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lat: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lng: Option<f64>,
+    pub tags: &'a [String],
+}
+
+impl<'a> From<&'a ChangeLogEntry> for StreamedChange<'a> {
+    fn from(from: &'a ChangeLogEntry) -> Self {
+        let change = &from.change;
+        let pos = change.pos.map(|pos| pos.to_lat_lng_deg());
+        Self {
+            kind: change.kind.as_str(),
+            id: change.id.as_str(),
+            place_id: change.place_id.as_ref().map(|id| id.as_str()),
+            change: change.change_type.as_str(),
+            at: change.at.into_inner(),
+            title: &change.title,
+            lat: pos.map(|(lat, _)| lat),
+            lng: pos.map(|(_, lng)| lng),
+            tags: &change.tags,
+        }
+    }
+}
+
+/// Renders a batch of changes as an event stream.
+///
+/// The stream always ends with the id of the last scanned change,
+/// even if it has been filtered out or if there are no changes at
+/// all. Reconnecting clients resume from there.
+pub fn to_event_stream(batch: &ChangeStreamBatch, retry_millis: u64) -> String {
+    let mut stream = String::new();
+    // Writing into a String never fails
+    let _ = write!(stream, "retry: {}\n\n", retry_millis);
+    let mut last_id = None;
+    for entry in &batch.changes {
+        // Serializing into a single line never fails
+        let data = serde_json::to_string(&StreamedChange::from(entry)).unwrap_or_default();
+        let _ = write!(stream, "id: {}\ndata: {}\n\n", entry.seq, data);
+        last_id = Some(entry.seq);
+    }
+    if last_id != Some(batch.last_seq) {
+        let _ = write!(stream, "id: {}\n\n", batch.last_seq);
+    }
+    stream
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{entities::*, util::time::TimestampMs};
+
+    fn entry(seq: u64) -> ChangeLogEntry {
+        ChangeLogEntry {
+            seq,
+            change: EntityChange {
+                at: TimestampMs::from_inner(1_000),
+                kind: ChangedEntityKind::Rating,
+                id: "r".into(),
+                place_id: Some("p".into()),
+                change_type: ChangeType::Created,
+                title: "Line\nbreak".into(),
+                pos: None,
+                tags: vec!["foo".into()],
+            },
+        }
+    }
+
+    #[test]
+    fn render_event_stream() {
+        let batch = ChangeStreamBatch {
+            changes: vec![entry(3)],
+            last_seq: 5,
+        };
+        let stream = to_event_stream(&batch, 1000);
+        assert!(stream.starts_with("retry: 1000\n\n"));
+        assert!(stream.contains(
+            "id: 3\ndata: {\"kind\":\"rating\",\"id\":\"r\",\"place_id\":\"p\",\"change\":\"created\",\"at\":1000,\"title\":\"Line\\nbreak\",\"tags\":[\"foo\"]}\n\n"
+        ));
+        assert!(stream.ends_with("id: 5\n\n"));
+    }
+
+    #[test]
+    fn render_empty_event_stream() {
+        let batch = ChangeStreamBatch {
+            changes: vec![],
+            last_seq: 0,
+        };
+        assert_eq!("retry: 500\n\nid: 0\n\n", to_event_stream(&batch, 500));
+    }
+}
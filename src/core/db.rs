@@ -18,6 +18,14 @@ pub struct MostPopularTagsParams {
     pub max_count: Option<u64>,
 }
 
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UserSearchParams {
+    /// Matches any part of the email address
+    pub email: Option<String>,
+    pub role: Option<Role>,
+    pub disabled: Option<bool>,
+}
+
 #[derive(Clone, Debug)]
 pub struct RecentlyChangedEntriesParams {
     pub since: Option<TimestampMs>,
@@ -67,6 +75,14 @@ pub trait EventGateway {
 
     fn all_events_chronologically(&self) -> Result<Vec<Event>>;
 
+    /// The most recent change of each event, ordered by
+    /// the time of the change in descending order.
+    fn recently_changed_events(
+        &self,
+        params: &RecentlyChangedEntriesParams,
+        pagination: &Pagination,
+    ) -> Result<Vec<(Event, ChangeType, Timestamp)>>;
+
     fn count_events(&self) -> Result<usize>;
 
     // Delete an event, but only if tagged with at least one of the given tags.
@@ -83,9 +99,16 @@ pub trait UserGateway {
     fn create_user(&self, user: &User) -> Result<()>;
     fn update_user(&self, user: &User) -> Result<()>;
     fn delete_user_by_email(&self, email: &str) -> Result<()>;
+    // Keeps all references to the user, but discards
+    // tokens that have been sent to the old address and
+    // ends all sessions of the user
+    fn change_user_email(&self, email: &str, new_email: &str) -> Result<()>;
 
     fn all_users(&self) -> Result<Vec<User>>;
     fn count_users(&self) -> Result<usize>;
+    // Ordered by email address
+    fn search_users(&self, params: &UserSearchParams, pagination: &Pagination)
+        -> Result<Vec<User>>;
 
     fn get_user_by_email(&self, email: &str) -> Result<User>;
     fn try_get_user_by_email(&self, email: &str) -> Result<Option<User>>;
@@ -93,7 +116,10 @@ pub trait UserGateway {
 
 pub trait OrganizationRepo {
     fn create_org(&mut self, _: Organization) -> Result<()>;
-    fn get_org_by_api_token(&self, token: &str) -> Result<Organization>;
+    fn update_org(&self, _: &Organization) -> Result<()>;
+    fn delete_org(&self, id: &Id) -> Result<()>;
+    fn all_orgs(&self) -> Result<Vec<Organization>>;
+    fn get_org_by_id(&self, id: &Id) -> Result<Organization>;
     fn map_tag_to_clearance_org_id(&self, tag: &str) -> Result<Option<Id>>;
     fn get_moderated_tags_by_org(
         &self,
@@ -124,6 +150,106 @@ pub trait PlaceClearanceRepo {
         clearances: &[ClearanceForPlace],
     ) -> Result<usize>;
     fn cleanup_pending_clearances_for_places(&self, org_id: &Id) -> Result<u64>;
+    // Ordered by creation time
+    fn load_pending_clearances_for_places_created_after(
+        &self,
+        org_id: &Id,
+        created_after: Option<TimestampMs>,
+    ) -> Result<Vec<PendingClearanceForPlace>>;
+    // The creation time of the latest pending clearance that
+    // has been announced to the contacts of the organization
+    fn get_pending_clearances_notified_until(&self, org_id: &Id) -> Result<Option<TimestampMs>>;
+    fn set_pending_clearances_notified_until(
+        &self,
+        org_id: &Id,
+        notified_until: TimestampMs,
+    ) -> Result<()>;
+    // Organizations with pending clearances that have been created
+    // until the given time and have not been announced yet
+    fn load_org_ids_with_unannounced_pending_clearances(
+        &self,
+        created_until: TimestampMs,
+    ) -> Result<Vec<Id>>;
+}
+
+pub trait ApiTokenRepo {
+    fn create_api_token(&self, token: &ApiToken) -> Result<()>;
+    fn get_api_token(&self, id: &Id) -> Result<ApiToken>;
+    fn get_api_token_by_hash(&self, token_hash: &str) -> Result<ApiToken>;
+    // Ordered by creation time
+    fn load_api_tokens_of_org(&self, org_id: &Id) -> Result<Vec<ApiToken>>;
+    fn replace_api_token_hash(&self, id: &Id, token_hash: &str) -> Result<()>;
+    fn update_api_token_last_used_at(&self, id: &Id, last_used_at: TimestampMs) -> Result<()>;
+    fn delete_api_token(&self, id: &Id) -> Result<()>;
+}
+
+pub trait OrganizationMemberRepo {
+    // Adds a new member or replaces the role of an existing member
+    fn set_org_member(&self, member: &OrganizationMember) -> Result<()>;
+    fn remove_org_member(&self, org_id: &Id, email: &str) -> Result<()>;
+    fn load_org_members(&self, org_id: &Id) -> Result<Vec<OrganizationMember>>;
+    fn load_org_memberships_of_user(&self, email: &str) -> Result<Vec<OrganizationMember>>;
+}
+
+pub trait WebhookRepo {
+    fn create_webhook(&self, webhook: &Webhook) -> Result<()>;
+    fn get_webhook(&self, id: &Id) -> Result<Webhook>;
+    fn load_webhooks_of_orgs(&self, org_ids: &[Id]) -> Result<Vec<Webhook>>;
+    // Deletes a webhook together with its delivery history
+    fn delete_webhook(&self, id: &Id) -> Result<()>;
+
+    fn add_webhook_deliveries(&self, deliveries: &[WebhookDelivery]) -> Result<usize>;
+    // Pending deliveries with the next attempt not later than `due_at`,
+    // ordered by the time of the next attempt
+    fn load_due_webhook_deliveries(
+        &self,
+        due_at: TimestampMs,
+        limit: u64,
+    ) -> Result<Vec<WebhookDelivery>>;
+    fn update_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<()>;
+    // Newest first
+    fn list_webhook_deliveries(
+        &self,
+        webhook_id: &Id,
+        pagination: &Pagination,
+    ) -> Result<Vec<WebhookDelivery>>;
+}
+
+pub trait EmailOutboxRepo {
+    fn add_outgoing_emails(&self, emails: &[OutgoingEmail]) -> Result<usize>;
+    // Pending messages with the next attempt not later than `due_at`,
+    // ordered by the time of the next attempt
+    fn load_due_outgoing_emails(
+        &self,
+        due_at: TimestampMs,
+        limit: u64,
+    ) -> Result<Vec<OutgoingEmail>>;
+    fn update_outgoing_email(&self, email: &OutgoingEmail) -> Result<()>;
+    // Most recent attempt first
+    fn load_failed_outgoing_emails(&self, pagination: &Pagination) -> Result<Vec<OutgoingEmail>>;
+    fn count_outgoing_emails(&self) -> Result<EmailOutboxStats>;
+    fn delete_sent_outgoing_emails_before(&self, before: TimestampMs) -> Result<usize>;
+}
+
+pub trait ChangeLogRepo {
+    // Returns the sequence number of the new entry
+    fn log_change(&self, change: &EntityChange) -> Result<u64>;
+    // Entries with a sequence number greater than `after_seq`
+    // in ascending order
+    fn load_change_log(&self, after_seq: u64, limit: u64) -> Result<Vec<ChangeLogEntry>>;
+    fn latest_change_log_seq(&self) -> Result<Option<u64>>;
+    fn delete_change_log_entries_before(&self, before: TimestampMs) -> Result<usize>;
+}
+
+pub trait AuditLogRepo {
+    fn add_audit_log_entry(&self, entry: &AuditLogEntry) -> Result<()>;
+    // The most recent entries first, optionally only those
+    // that affect the given user
+    fn load_audit_log(
+        &self,
+        target: Option<&str>,
+        pagination: &Pagination,
+    ) -> Result<Vec<AuditLogEntry>>;
 }
 
 //TODO:
@@ -141,10 +267,24 @@ pub trait Db:
     + UserGateway
     + EventGateway
     + OrganizationRepo
+    + OrganizationMemberRepo
+    + ApiTokenRepo
     + CommentRepository
     + RatingRepository
     + UserTokenRepo
+    + EmailChangeRepo
+    + SubscriptionNotificationRepo
+    + AuthorNotificationRepo
+    + SessionTokenRepo
+    + TwoFactorRepo
+    + OidcAccountRepo
+    + LoginThrottleRepo
+    + GeoCodeCacheRepo
     + PlaceClearanceRepo
+    + WebhookRepo
+    + EmailOutboxRepo
+    + ChangeLogRepo
+    + AuditLogRepo
 {
     fn create_tag_if_it_does_not_exist(&self, _: &Tag) -> Result<()>;
 
@@ -159,8 +299,11 @@ pub trait Db:
     fn count_tags(&self) -> Result<usize>;
 
     fn create_bbox_subscription(&self, _: &BboxSubscription) -> Result<()>;
+    fn update_bbox_subscription(&self, _: &BboxSubscription) -> Result<()>;
+    fn get_bbox_subscription(&self, id: &Id) -> Result<BboxSubscription>;
     fn all_bbox_subscriptions(&self) -> Result<Vec<BboxSubscription>>;
     fn all_bbox_subscriptions_by_email(&self, user_email: &str) -> Result<Vec<BboxSubscription>>;
+    fn delete_bbox_subscription(&self, id: &Id) -> Result<()>;
     fn delete_bbox_subscriptions_by_email(&self, user_email: &str) -> Result<()>;
 }
 
@@ -1,7 +1,9 @@
 pub use ofdb_entities::{
-    activity::*, address::*, category::*, clearance::*, comment::*, contact::*, email::*, event::*,
-    geo::*, id::*, links::*, location::*, nonce::*, organization::*, password::*, place::*,
-    rating::*, review::*, revision::*, subscription::*, tag::*, time::*, url::Url, user::*,
+    activity::*, address::*, api_token::*, audit_log::*, category::*, change::*, clearance::*,
+    comment::*, contact::*, email::*, event::*, geo::*, id::*, links::*, location::*,
+    login_throttle::*, nonce::*, oidc::*, organization::*, password::*, place::*, rating::*,
+    review::*, revision::*, session::*, subscription::*, tag::*, time::*, two_factor::*, url::Url,
+    user::*, webhook::*,
 };
 
 #[cfg(test)]
@@ -7,6 +7,8 @@ pub enum ParameterError {
     Title,
     #[error("Bounding box is invalid")]
     Bbox,
+    #[error("Map tile is invalid")]
+    Tile,
     #[error("Unsupported license")]
     License,
     #[error("Invalid email address")]
@@ -25,6 +27,8 @@ pub enum ParameterError {
     UserDoesNotExist,
     #[error("Invalid password")]
     Password,
+    #[error("Invalid role")]
+    Role,
     #[error("Empty comment")]
     EmptyComment,
     #[error("Rating value out of range")]
@@ -35,6 +39,18 @@ pub enum ParameterError {
     Credentials,
     #[error("Email not confirmed")]
     EmailNotConfirmed,
+    #[error("The user account has been disabled")]
+    UserDisabled,
+    #[error("The identity provider did not confirm the email address")]
+    UnverifiedEmail,
+    #[error("The second factor is required")]
+    SecondFactorRequired,
+    #[error("Invalid second factor")]
+    SecondFactor,
+    #[error("The second factor must be enabled for this account")]
+    SecondFactorEnrollmentRequired,
+    #[error("Too many failed login attempts, retry in {0} seconds")]
+    TooManyLoginAttempts(u64),
     #[error("This is not allowed")]
     Forbidden,
     #[error("This is not allowed without auth")]
@@ -61,6 +77,30 @@ pub enum ParameterError {
     InvalidNonce,
     #[error("Missing id list")]
     EmptyIdList,
+    #[error("Invalid webhook event type")]
+    WebhookEventType,
+    #[error("The webhook secret is too short")]
+    WebhookSecret,
+    #[error("The webhook URL must resolve to a public address")]
+    WebhookUrl,
+    #[error("Invalid API token scope")]
+    ApiTokenScope,
+    #[error("The name of the API token is empty")]
+    ApiTokenName,
+    #[error("The name of the organization is empty")]
+    OrganizationName,
+    #[error("Invalid or duplicate moderated tag")]
+    ModeratedTagLabel,
+    #[error("Invalid organization role")]
+    OrganizationRole,
+    #[error("Invalid subscription filter")]
+    SubscriptionFilter,
+    #[error("Invalid delivery mode of the subscription")]
+    SubscriptionDeliveryMode,
+    #[error("Too many subscriptions")]
+    TooManySubscriptions,
+    #[error("Unsupported language")]
+    Language,
 }
 
 #[derive(Debug, Error)]
@@ -4,7 +4,11 @@
 // by their id and never modified or loaded by another
 // repository.
 
-use super::{entities::*, error::RepoError, util::time::Timestamp};
+use super::{
+    entities::*,
+    error::RepoError,
+    util::time::{Timestamp, TimestampMs},
+};
 
 type Result<T> = std::result::Result<T, RepoError>;
 
@@ -62,3 +66,89 @@ pub trait UserTokenRepo {
 
     fn get_user_token_by_email(&self, email: &str) -> Result<UserToken>;
 }
+
+pub trait EmailChangeRepo {
+    // Replaces a pending change of the same user
+    fn replace_email_change_token(&self, token: &EmailChangeToken) -> Result<()>;
+    fn consume_email_change_token(&self, new_email_nonce: &EmailNonce) -> Result<EmailChangeToken>;
+    fn delete_expired_email_change_tokens(&self, expired_before: Timestamp) -> Result<usize>;
+}
+
+pub trait SubscriptionNotificationRepo {
+    fn add_subscription_notifications(
+        &self,
+        notifications: &[SubscriptionNotification],
+    ) -> Result<usize>;
+    // Ordered by user, subscription and creation, oldest first
+    fn load_subscription_notifications(
+        &self,
+        delivery_mode: SubscriptionDeliveryMode,
+    ) -> Result<Vec<SubscriptionNotification>>;
+    fn delete_subscription_notifications(
+        &self,
+        user_email: &str,
+        delivery_mode: SubscriptionDeliveryMode,
+        subscription_id: Option<&Id>,
+        created_until: TimestampMs,
+    ) -> Result<usize>;
+}
+
+pub trait AuthorNotificationRepo {
+    fn replace_author_notifications(&self, settings: &AuthorNotifications) -> Result<()>;
+    fn get_author_notifications(&self, email: &str) -> Result<AuthorNotifications>;
+}
+
+pub trait SessionTokenRepo {
+    fn create_refresh_token(&self, token: &RefreshToken) -> Result<()>;
+    fn get_refresh_token(&self, token_hash: &str) -> Result<RefreshToken>;
+    // Returns false if the token has already been used before
+    fn use_refresh_token(&self, token_hash: &str, used_at: TimestampMs) -> Result<bool>;
+    fn delete_refresh_tokens_of_session(&self, session_id: &Id) -> Result<usize>;
+    fn delete_refresh_tokens_of_user(&self, email: &str) -> Result<usize>;
+    // Returns false if the session has ended or belongs to another user
+    fn is_session_of_user(&self, session_id: &Id, email: &str) -> Result<bool>;
+    fn delete_expired_refresh_tokens(&self, expired_before: TimestampMs) -> Result<usize>;
+
+    // Revoking an already revoked token has no effect
+    fn revoke_access_token(&self, jti: &str, expires_at: TimestampMs) -> Result<()>;
+    fn is_access_token_revoked(&self, jti: &str) -> Result<bool>;
+    fn delete_expired_revoked_access_tokens(&self, expired_before: TimestampMs) -> Result<usize>;
+}
+
+pub trait OidcAccountRepo {
+    fn create_oidc_account(&self, account: &OidcAccount) -> Result<()>;
+    fn get_oidc_account(&self, provider: &str, subject: &str) -> Result<OidcAccount>;
+}
+
+pub trait TwoFactorRepo {
+    fn replace_user_totp(&self, totp: &UserTotp) -> Result<()>;
+    fn get_user_totp(&self, email: &str) -> Result<UserTotp>;
+    fn confirm_user_totp(&self, email: &str, confirmed_at: TimestampMs) -> Result<()>;
+    // Returns false if the same or a later time step has already been used before
+    fn use_user_totp_step(&self, email: &str, step: i64) -> Result<bool>;
+    // Also deletes all recovery codes
+    fn delete_user_totp(&self, email: &str) -> Result<()>;
+
+    fn replace_recovery_codes(&self, email: &str, code_hashes: &[String]) -> Result<()>;
+    // Returns false if the code does not exist or has already been used before
+    fn use_recovery_code(&self, email: &str, code_hash: &str, used_at: TimestampMs)
+        -> Result<bool>;
+    fn count_unused_recovery_codes(&self, email: &str) -> Result<usize>;
+}
+
+pub trait LoginThrottleRepo {
+    fn get_failed_logins(&self, key: &LoginThrottleKey) -> Result<FailedLogins>;
+    fn replace_failed_logins(&self, failed_logins: &FailedLogins) -> Result<()>;
+    fn delete_failed_logins(&self, key: &LoginThrottleKey) -> Result<()>;
+    // Keeps entries that are still locked at the given time
+    fn delete_failed_logins_before(&self, last_failed_before: TimestampMs) -> Result<usize>;
+}
+
+pub trait GeoCodeCacheRepo {
+    fn get_cached_geocode(&self, address: &str) -> Result<CachedGeoCode>;
+    fn replace_cached_geocode(&self, geocode: &CachedGeoCode) -> Result<()>;
+    fn delete_cached_geocodes_before(&self, created_before: TimestampMs) -> Result<usize>;
+    fn get_cached_address(&self, pos: &str) -> Result<CachedAddress>;
+    fn replace_cached_address(&self, address: &CachedAddress) -> Result<()>;
+    fn delete_cached_addresses_before(&self, created_before: TimestampMs) -> Result<usize>;
+}
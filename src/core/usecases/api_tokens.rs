@@ -0,0 +1,202 @@
+use crate::core::{prelude::*, util::hash_token};
+
+#[derive(Debug, Clone)]
+pub struct NewApiToken {
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<TimestampMs>,
+}
+
+/// An API token together with its secret.
+///
+/// The secret is only revealed once after it has been
+/// created or rotated.
+#[derive(Debug, Clone)]
+pub struct IssuedApiToken {
+    pub api_token: ApiToken,
+    pub secret: String,
+}
+
+fn parse_scopes(scopes: Vec<String>) -> Result<Vec<ApiTokenScope>> {
+    let mut unique_scopes = Vec::with_capacity(scopes.len());
+    for s in scopes {
+        let s = s
+            .parse::<ApiTokenScope>()
+            .map_err(|_| ParameterError::ApiTokenScope)?;
+        if !unique_scopes.contains(&s) {
+            unique_scopes.push(s);
+        }
+    }
+    if unique_scopes.is_empty() {
+        return Err(ParameterError::ApiTokenScope.into());
+    }
+    Ok(unique_scopes)
+}
+
+pub fn create_api_token<R: OrganizationRepo + ApiTokenRepo>(
+    repo: &R,
+    org_id: &Id,
+    new_token: NewApiToken,
+) -> Result<IssuedApiToken> {
+    let NewApiToken {
+        name,
+        scopes,
+        expires_at,
+    } = new_token;
+    let org = repo.get_org_by_id(org_id)?;
+    let name = name.trim().to_owned();
+    if name.is_empty() {
+        return Err(ParameterError::ApiTokenName.into());
+    }
+    let scopes = parse_scopes(scopes)?;
+    let created_at = TimestampMs::now();
+    if expires_at.map_or(false, |expires_at| expires_at <= created_at) {
+        return Err(ParameterError::DateTimeOutOfRange.into());
+    }
+    let secret = Nonce::new().to_string();
+    let api_token = ApiToken {
+        id: Id::new(),
+        org_id: org.id,
+        name,
+        token_hash: hash_token(&secret),
+        scopes,
+        created_at,
+        expires_at,
+        last_used_at: None,
+    };
+    repo.create_api_token(&api_token)?;
+    info!(
+        "Created API token {} for organization '{}'",
+        api_token.id, org.name
+    );
+    Ok(IssuedApiToken { api_token, secret })
+}
+
+/// The name of the API token that grants all permissions
+/// on behalf of an organization.
+const PRIMARY_API_TOKEN_NAME: &str = "Primary";
+
+/// Creates an API token with all permissions for the
+/// given secret.
+pub fn create_primary_api_token<R: ApiTokenRepo>(
+    repo: &R,
+    org_id: &Id,
+    secret: &str,
+) -> Result<ApiToken> {
+    let api_token = ApiToken {
+        id: Id::new(),
+        org_id: org_id.clone(),
+        name: PRIMARY_API_TOKEN_NAME.to_owned(),
+        token_hash: hash_token(secret),
+        scopes: ApiTokenScope::all(),
+        created_at: TimestampMs::now(),
+        expires_at: None,
+        last_used_at: None,
+    };
+    repo.create_api_token(&api_token)?;
+    info!(
+        "Created primary API token {} for organization {}",
+        api_token.id, org_id
+    );
+    Ok(api_token)
+}
+
+pub fn list_api_tokens<R: OrganizationRepo + ApiTokenRepo>(
+    repo: &R,
+    org_id: &Id,
+) -> Result<Vec<ApiToken>> {
+    let org = repo.get_org_by_id(org_id)?;
+    Ok(repo.load_api_tokens_of_org(&org.id)?)
+}
+
+// Tokens of other organizations are treated as if they don't exist.
+fn get_api_token_of_org<R: ApiTokenRepo>(repo: &R, org_id: &Id, id: &Id) -> Result<ApiToken> {
+    let api_token = repo.get_api_token(id)?;
+    if &api_token.org_id != org_id {
+        return Err(RepoError::NotFound.into());
+    }
+    Ok(api_token)
+}
+
+/// Replaces the secret of an API token while preserving
+/// its name, scopes and expiration.
+pub fn rotate_api_token<R: ApiTokenRepo>(repo: &R, org_id: &Id, id: &Id) -> Result<IssuedApiToken> {
+    let mut api_token = get_api_token_of_org(repo, org_id, id)?;
+    let secret = Nonce::new().to_string();
+    api_token.token_hash = hash_token(&secret);
+    repo.replace_api_token_hash(&api_token.id, &api_token.token_hash)?;
+    info!("Rotated API token {}", api_token.id);
+    Ok(IssuedApiToken { api_token, secret })
+}
+
+pub fn delete_api_token<R: ApiTokenRepo>(repo: &R, org_id: &Id, id: &Id) -> Result<()> {
+    let api_token = get_api_token_of_org(repo, org_id, id)?;
+    repo.delete_api_token(&api_token.id)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockDb;
+    use super::*;
+
+    fn new_db() -> MockDb {
+        let mut db = MockDb::default();
+        db.orgs = vec![
+            Organization {
+                id: "org1".into(),
+                name: "Org 1".into(),
+                moderated_tags: vec![],
+            },
+            Organization {
+                id: "org2".into(),
+                name: "Org 2".into(),
+                moderated_tags: vec![],
+            },
+        ]
+        .into();
+        db
+    }
+
+    fn new_token(scopes: &[&str]) -> NewApiToken {
+        NewApiToken {
+            name: "Importer".into(),
+            scopes: scopes.iter().map(ToString::to_string).collect(),
+            expires_at: None,
+        }
+    }
+
+    #[test]
+    fn create_and_rotate_api_token() {
+        let db = new_db();
+        let org_id = Id::from("org1");
+        let issued = create_api_token(&db, &org_id, new_token(&["events:write"])).unwrap();
+        assert_ne!(issued.secret, issued.api_token.token_hash);
+        assert_eq!(hash_token(&issued.secret), issued.api_token.token_hash);
+        assert_eq!(1, list_api_tokens(&db, &org_id).unwrap().len());
+
+        let rotated = rotate_api_token(&db, &org_id, &issued.api_token.id).unwrap();
+        assert_ne!(issued.secret, rotated.secret);
+        assert_eq!(issued.api_token.scopes, rotated.api_token.scopes);
+        let stored = db.get_api_token(&issued.api_token.id).unwrap();
+        assert_eq!(hash_token(&rotated.secret), stored.token_hash);
+
+        // Tokens of other organizations are hidden
+        assert!(rotate_api_token(&db, &"org2".into(), &issued.api_token.id).is_err());
+        assert!(delete_api_token(&db, &"org2".into(), &issued.api_token.id).is_err());
+        delete_api_token(&db, &org_id, &issued.api_token.id).unwrap();
+        assert!(list_api_tokens(&db, &org_id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn reject_invalid_api_tokens() {
+        let db = new_db();
+        let org_id = Id::from("org1");
+        assert!(create_api_token(&db, &org_id, new_token(&[])).is_err());
+        assert!(create_api_token(&db, &org_id, new_token(&["events:read"])).is_err());
+        let mut expired = new_token(&["events:write"]);
+        expired.expires_at = Some(TimestampMs::from_inner(0));
+        assert!(create_api_token(&db, &org_id, expired).is_err());
+        assert!(create_api_token(&db, &"unknown".into(), new_token(&["events:write"])).is_err());
+    }
+}
@@ -3,5 +3,10 @@ use crate::core::prelude::*;
 pub fn archive_events<D: Db>(db: &D, ids: &[&str]) -> Result<usize> {
     debug!("Archiving events {:?}", ids);
     let archived = Timestamp::now();
-    Ok(db.archive_events(ids, archived)?)
+    let events = db.get_events_chronologically(ids)?;
+    let count = db.archive_events(ids, archived)?;
+    for event in &events {
+        super::enqueue_event_webhooks(db, event, ChangeType::Archived)?;
+    }
+    Ok(count)
 }
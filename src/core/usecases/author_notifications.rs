@@ -0,0 +1,139 @@
+use crate::core::prelude::*;
+
+/// The opt-in notifications of a registered user
+/// that are all disabled by default.
+pub fn get_author_notifications<D: Db>(db: &D, email: &str) -> Result<AuthorNotifications> {
+    match db.get_author_notifications(email) {
+        Ok(settings) => Ok(settings),
+        Err(RepoError::NotFound) => {
+            let user = db.get_user_by_email(email)?;
+            Ok(AuthorNotifications {
+                user_email: user.email,
+                ..Default::default()
+            })
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub fn update_author_notifications<D: Db>(
+    db: &D,
+    settings: AuthorNotifications,
+) -> Result<AuthorNotifications> {
+    db.replace_author_notifications(&settings)?;
+    Ok(settings)
+}
+
+/// The authors of all revisions of a place who want to
+/// be notified about this kind of change.
+///
+/// The user who caused the change is not notified.
+pub fn author_recipients_of_place<D: Db>(
+    db: &D,
+    place_id: &str,
+    kind: AuthorNotificationKind,
+    changed_by: Option<&str>,
+) -> Result<Vec<String>> {
+    let history = db.get_place_history(place_id, None)?;
+    let mut authors: Vec<String> = history
+        .revisions
+        .into_iter()
+        .filter_map(|(revision, _)| revision.created.by)
+        .map(String::from)
+        .filter(|email| Some(email.as_str()) != changed_by)
+        .collect();
+    authors.sort_unstable();
+    authors.dedup();
+    let mut recipients = Vec::with_capacity(authors.len());
+    for email in authors {
+        let settings = match db.get_author_notifications(&email) {
+            Ok(settings) => settings,
+            Err(RepoError::NotFound) => continue,
+            Err(err) => return Err(err.into()),
+        };
+        if !settings.is_enabled(kind) {
+            continue;
+        }
+        match db.try_get_user_by_email(&email)? {
+            Some(user) if user.email_confirmed && !user.disabled => {
+                recipients.push(email);
+            }
+            _ => {}
+        }
+    }
+    Ok(recipients)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockDb;
+    use super::*;
+
+    #[test]
+    fn all_notifications_are_disabled_by_default() {
+        let db = MockDb::default();
+        db.create_test_user("a@example.com", Role::User);
+        let settings = get_author_notifications(&db, "a@example.com").unwrap();
+        assert_eq!("a@example.com", settings.user_email);
+        assert!(!settings.reviews);
+        assert!(!settings.ratings);
+        assert!(!settings.clearances);
+        assert!(get_author_notifications(&db, "b@example.com").is_err());
+
+        update_author_notifications(
+            &db,
+            AuthorNotifications {
+                user_email: "a@example.com".into(),
+                ratings: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let settings = get_author_notifications(&db, "a@example.com").unwrap();
+        assert!(!settings.reviews);
+        assert!(settings.ratings);
+        assert!(update_author_notifications(
+            &db,
+            AuthorNotifications {
+                user_email: "b@example.com".into(),
+                ..Default::default()
+            }
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn notify_authors_who_opted_in() {
+        let db = MockDb::default();
+        db.create_test_user("a@example.com", Role::User);
+        let mut place = Place::build().id("place").finish();
+        place.created = Activity::now(Some("a@example.com".into()));
+        db.create_or_update_place(place).unwrap();
+
+        let recipients =
+            |kind, changed_by| author_recipients_of_place(&db, "place", kind, changed_by).unwrap();
+        assert!(recipients(AuthorNotificationKind::Review, Some("scout@example.com")).is_empty());
+
+        update_author_notifications(
+            &db,
+            AuthorNotifications {
+                user_email: "a@example.com".into(),
+                reviews: true,
+                clearances: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            vec!["a@example.com"],
+            recipients(AuthorNotificationKind::Review, Some("scout@example.com"))
+        );
+        assert_eq!(
+            vec!["a@example.com"],
+            recipients(AuthorNotificationKind::Clearance, None)
+        );
+        assert!(recipients(AuthorNotificationKind::Rating, None).is_empty());
+        // Don't notify authors about their own changes
+        assert!(recipients(AuthorNotificationKind::Clearance, Some("a@example.com")).is_empty());
+    }
+}
@@ -1,21 +1,42 @@
-use crate::core::prelude::*;
+use crate::core::{prelude::*, util::hash_token};
 
-pub fn authorize_organization_by_possible_api_tokens<D: OrganizationRepo>(
-    db: &D,
+/// Authorizes an organization by one of the given API tokens.
+///
+/// The token must neither be expired nor lack any of the
+/// required scopes. Returns the organization together with
+/// the matching token.
+pub fn authorize_organization_by_possible_api_tokens<R: OrganizationRepo + ApiTokenRepo>(
+    repo: &R,
     tokens: &[String],
-) -> Result<Organization> {
+    required_scopes: &[ApiTokenScope],
+) -> Result<(Organization, ApiToken)> {
+    let mut missing_scopes = false;
     for token in tokens {
-        match db.get_org_by_api_token(token) {
-            Ok(org) => return Ok(org),
-            Err(RepoError::NotFound) => (),
+        let api_token = match repo.get_api_token_by_hash(&hash_token(token)) {
+            Ok(api_token) => api_token,
+            Err(RepoError::NotFound) => continue,
             Err(e) => return Err(Error::Repo(e)),
+        };
+        let now = TimestampMs::now();
+        if api_token.is_expired(now) {
+            continue;
+        }
+        if !api_token.has_scopes(required_scopes) {
+            missing_scopes = true;
+            continue;
         }
+        let org = repo.get_org_by_id(&api_token.org_id)?;
+        return Ok((org, api_token));
+    }
+    if missing_scopes {
+        Err(Error::Parameter(ParameterError::Forbidden))
+    } else {
+        Err(Error::Parameter(ParameterError::Unauthorized))
     }
-    Err(Error::Parameter(ParameterError::Unauthorized))
 }
 
 pub fn authorize_user_by_email(db: &dyn Db, email: &str, min_required_role: Role) -> Result<User> {
-    if let Some(user) = db.try_get_user_by_email(email)? {
+    if let Some(user) = db.try_get_user_by_email(email)?.filter(|u| !u.disabled) {
         return ofdb_core::user::authorize_role(&user, min_required_role)
             .map(|()| user)
             .map_err(|_| Error::Parameter(ParameterError::Unauthorized));
@@ -45,3 +66,73 @@ pub fn authorize_editing_of_tagged_entry<R: OrganizationRepo>(
     )
     .map_err(|_| ParameterError::ModeratedTag.into())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::{tests::MockDb, *};
+
+    #[test]
+    fn authorize_organization_by_scoped_api_tokens() {
+        let mut db = MockDb::default();
+        db.orgs = vec![Organization {
+            id: "org".into(),
+            name: "Org".into(),
+            moderated_tags: vec![],
+        }]
+        .into();
+        let org_id = Id::from("org");
+        let issued = create_api_token(
+            &db,
+            &org_id,
+            NewApiToken {
+                name: "Events".into(),
+                scopes: vec!["events:write".into()],
+                expires_at: None,
+            },
+        )
+        .unwrap();
+        let tokens = vec![issued.secret.clone()];
+
+        let (org, api_token) = authorize_organization_by_possible_api_tokens(
+            &db,
+            &tokens,
+            &[ApiTokenScope::EventsWrite],
+        )
+        .unwrap();
+        assert_eq!(org_id, org.id);
+        assert_eq!(issued.api_token.id, api_token.id);
+
+        assert!(matches!(
+            authorize_organization_by_possible_api_tokens(
+                &db,
+                &tokens,
+                &[ApiTokenScope::EventsDelete],
+            ),
+            Err(Error::Parameter(ParameterError::Forbidden))
+        ));
+        assert!(matches!(
+            authorize_organization_by_possible_api_tokens(&db, &["unknown".to_string()], &[],),
+            Err(Error::Parameter(ParameterError::Unauthorized))
+        ));
+
+        // The primary token grants all permissions
+        create_primary_api_token(&db, &org_id, "primary").unwrap();
+        assert!(authorize_organization_by_possible_api_tokens(
+            &db,
+            &["primary".to_string()],
+            &ApiTokenScope::all(),
+        )
+        .is_ok());
+
+        // Expired tokens are rejected
+        db.api_tokens.borrow_mut()[0].expires_at = Some(TimestampMs::from_inner(0));
+        assert!(matches!(
+            authorize_organization_by_possible_api_tokens(
+                &db,
+                &tokens,
+                &[ApiTokenScope::EventsWrite],
+            ),
+            Err(Error::Parameter(ParameterError::Unauthorized))
+        ));
+    }
+}
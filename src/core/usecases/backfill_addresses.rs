@@ -0,0 +1,113 @@
+use crate::core::prelude::*;
+
+const BACKFILL_COMMENT: &str = "address filled in by reverse geocoding";
+
+/// All places that have not been archived or rejected
+/// and are missing an address.
+pub fn places_without_address<R: PlaceRepo>(repo: &R) -> Result<Vec<(Place, ReviewStatus)>> {
+    Ok(repo
+        .all_places()?
+        .into_iter()
+        .filter(|(place, status)| {
+            status.exists()
+                && place
+                    .location
+                    .address
+                    .as_ref()
+                    .map_or(true, |addr| addr.is_empty())
+        })
+        .collect())
+}
+
+/// Stores the resolved address of a place as a new
+/// revision of the system.
+///
+/// Places that have been changed since their address
+/// has been resolved are skipped. Confirmed places stay
+/// confirmed and the system review is recorded in the
+/// activity log.
+pub fn fill_in_place_address<D: Db>(
+    db: &D,
+    id: &str,
+    revision: Revision,
+    addr: Address,
+) -> Result<Option<(Place, ReviewStatus)>> {
+    let (mut place, status) = db.get_place(id)?;
+    if place.revision != revision {
+        debug!("Place {} has been changed meanwhile", id);
+        return Ok(None);
+    }
+    let activity_log = ActivityLog::system(BACKFILL_COMMENT);
+    place.location.address = Some(addr);
+    place.revision = place.revision.next();
+    place.created = activity_log.activity.clone();
+    db.create_or_update_place(place.clone())?;
+    // New revisions are created unreviewed
+    if status == ReviewStatus::Confirmed {
+        db.review_places(&[place.id.as_str()], status, &activity_log)?;
+    }
+    super::log_place_change(db, &place, ChangeType::Updated)?;
+    info!("Filled in address of place {}", place.id);
+    Ok(Some((place, status)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockDb;
+    use super::*;
+
+    fn berlin() -> Address {
+        Address {
+            city: Some("Berlin".into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn find_places_without_address() {
+        let db = MockDb::default();
+        let mut with_address = Place::build()
+            .id("with_address")
+            .pos(MapPoint::from_lat_lng_deg(52.5, 13.4))
+            .finish();
+        with_address.location.address = Some(berlin());
+        db.create_or_update_place(with_address).unwrap();
+        let without_address = Place::build()
+            .id("without_address")
+            .pos(MapPoint::from_lat_lng_deg(52.5, 13.4))
+            .finish();
+        db.create_or_update_place(without_address).unwrap();
+
+        let places = places_without_address(&db).unwrap();
+        assert_eq!(1, places.len());
+        assert_eq!("without_address", places[0].0.id.as_str());
+    }
+
+    #[test]
+    fn fill_in_address_as_new_revision() {
+        let db = MockDb::default();
+        let mut place = Place::build()
+            .id("without_address")
+            .pos(MapPoint::from_lat_lng_deg(52.5, 13.4))
+            .finish();
+        place.created = Activity::now(Some("a@example.com".into()));
+        db.create_or_update_place(place).unwrap();
+
+        let (place, _) =
+            fill_in_place_address(&db, "without_address", Revision::initial(), berlin())
+                .unwrap()
+                .unwrap();
+        assert_eq!(Some(berlin()), place.location.address);
+        assert_eq!(Revision::initial().next(), place.revision);
+        assert_eq!(None, place.created.by);
+        assert_eq!(1, db.change_log.borrow().len());
+
+        // Outdated revisions are skipped
+        assert!(
+            fill_in_place_address(&db, "without_address", Revision::initial(), berlin())
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(1, db.change_log.borrow().len());
+    }
+}
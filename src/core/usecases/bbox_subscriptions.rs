@@ -0,0 +1,445 @@
+use super::prepare_tag_list;
+use crate::core::{
+    prelude::*,
+    util::{geo::MapBbox, validate},
+};
+use ofdb_core::subscription::verify_unsubscribe_token;
+
+/// Limits the number of subscriptions that are evaluated
+/// for each change of an entry.
+pub const MAX_BBOX_SUBSCRIPTIONS_PER_USER: usize = 50;
+
+#[derive(Debug, Clone)]
+pub struct NewBboxSubscription {
+    pub name: String,
+    pub bbox: MapBbox,
+    pub tags: Vec<String>,
+    pub categories: Vec<String>,
+    pub entity_kind: Option<String>,
+    pub change_types: Vec<String>,
+    pub delivery_mode: Option<String>,
+}
+
+fn parse_filter(new: &NewBboxSubscription) -> Result<SubscriptionFilter> {
+    let NewBboxSubscription {
+        tags,
+        categories,
+        entity_kind,
+        change_types,
+        ..
+    } = new;
+    // Commas are not allowed within tags
+    let tags = prepare_tag_list(tags.iter().flat_map(|t| t.split(',')));
+    let all_categories = [
+        Category::ID_NON_PROFIT,
+        Category::ID_COMMERCIAL,
+        Category::ID_EVENT,
+    ];
+    let mut unique_categories: Vec<Id> = Vec::with_capacity(categories.len());
+    for c in categories {
+        if !all_categories.contains(&c.as_str()) {
+            return Err(ParameterError::SubscriptionFilter.into());
+        }
+        if !unique_categories.iter().any(|id| id.as_str() == c) {
+            unique_categories.push(c.as_str().into());
+        }
+    }
+    let entity_kind = entity_kind
+        .as_ref()
+        .map(|k| k.parse::<SubscriptionEntityKind>())
+        .transpose()
+        .map_err(|_| ParameterError::SubscriptionFilter)?;
+    let mut unique_change_types = Vec::with_capacity(change_types.len());
+    for t in change_types {
+        let t = t
+            .parse::<SubscriptionChangeType>()
+            .map_err(|_| ParameterError::SubscriptionFilter)?;
+        if !unique_change_types.contains(&t) {
+            unique_change_types.push(t);
+        }
+    }
+    Ok(SubscriptionFilter {
+        tags,
+        categories: unique_categories,
+        entity_kind,
+        change_types: unique_change_types,
+    })
+}
+
+fn bbox_subscription_from_new(
+    id: Id,
+    user_email: String,
+    new: NewBboxSubscription,
+) -> Result<BboxSubscription> {
+    validate::bbox(&new.bbox)?;
+    let filter = parse_filter(&new)?;
+    let delivery_mode = new
+        .delivery_mode
+        .as_ref()
+        .map(|m| m.parse::<SubscriptionDeliveryMode>())
+        .transpose()
+        .map_err(|_| ParameterError::SubscriptionDeliveryMode)?
+        .unwrap_or_default();
+    let NewBboxSubscription { name, bbox, .. } = new;
+    Ok(BboxSubscription {
+        id,
+        user_email,
+        name: name.trim().to_owned(),
+        bbox,
+        filter,
+        delivery_mode,
+    })
+}
+
+/// Unnamed subscriptions of the legacy API only observe
+/// created and updated entries.
+fn legacy_filter() -> SubscriptionFilter {
+    SubscriptionFilter {
+        change_types: vec![
+            SubscriptionChangeType::Created,
+            SubscriptionChangeType::Updated,
+        ],
+        ..Default::default()
+    }
+}
+
+/// Replaces the single subscription that is managed by
+/// the KVM frontend.
+///
+/// Named subscriptions and subscriptions with filters
+/// or digests are kept.
+pub fn subscribe_to_bbox(db: &dyn Db, user_email: String, bbox: MapBbox) -> Result<()> {
+    validate::bbox(&bbox)?;
+
+    // TODO: support multiple subscriptions in KVM (frontend)
+    // In the meanwhile we just replace the unnamed subscription
+    // with a new one.
+    for s in db.all_bbox_subscriptions_by_email(&user_email)? {
+        if s.name.is_empty()
+            && s.filter == legacy_filter()
+            && s.delivery_mode == SubscriptionDeliveryMode::Immediate
+        {
+            db.delete_bbox_subscription(&s.id)?;
+        }
+    }
+
+    let id = Id::new();
+    db.create_bbox_subscription(&BboxSubscription {
+        id,
+        user_email,
+        name: String::new(),
+        bbox,
+        filter: legacy_filter(),
+        delivery_mode: SubscriptionDeliveryMode::Immediate,
+    })?;
+    Ok(())
+}
+
+pub fn create_bbox_subscription(
+    db: &dyn Db,
+    user_email: &str,
+    new: NewBboxSubscription,
+) -> Result<BboxSubscription> {
+    let subscription = bbox_subscription_from_new(Id::new(), user_email.to_owned(), new)?;
+    if db.all_bbox_subscriptions_by_email(user_email)?.len() >= MAX_BBOX_SUBSCRIPTIONS_PER_USER {
+        return Err(ParameterError::TooManySubscriptions.into());
+    }
+    db.create_bbox_subscription(&subscription)?;
+    Ok(subscription)
+}
+
+// Subscriptions of other users are treated as if they don't exist.
+fn get_bbox_subscription_of_user(
+    db: &dyn Db,
+    user_email: &str,
+    id: &Id,
+) -> Result<BboxSubscription> {
+    let subscription = db.get_bbox_subscription(id)?;
+    if subscription.user_email != user_email {
+        return Err(RepoError::NotFound.into());
+    }
+    Ok(subscription)
+}
+
+pub fn update_bbox_subscription(
+    db: &dyn Db,
+    user_email: &str,
+    id: &Id,
+    new: NewBboxSubscription,
+) -> Result<BboxSubscription> {
+    let old = get_bbox_subscription_of_user(db, user_email, id)?;
+    let subscription = bbox_subscription_from_new(old.id, old.user_email, new)?;
+    db.update_bbox_subscription(&subscription)?;
+    Ok(subscription)
+}
+
+pub fn delete_bbox_subscription(db: &dyn Db, user_email: &str, id: &Id) -> Result<()> {
+    let subscription = get_bbox_subscription_of_user(db, user_email, id)?;
+    Ok(db.delete_bbox_subscription(&subscription.id)?)
+}
+
+/// The subscription of a signed unsubscribe token.
+pub fn get_bbox_subscription_by_unsubscribe_token(
+    db: &dyn Db,
+    secret: &str,
+    token: &str,
+) -> Result<BboxSubscription> {
+    let id = verify_unsubscribe_token(secret, token).ok_or(ParameterError::TokenInvalid)?;
+    Ok(db.get_bbox_subscription(&id)?)
+}
+
+/// Deletes a single subscription without authentication,
+/// e.g. after clicking an unsubscribe link in an email.
+pub fn unsubscribe_with_token(db: &dyn Db, secret: &str, token: &str) -> Result<BboxSubscription> {
+    let subscription = get_bbox_subscription_by_unsubscribe_token(db, secret, token)?;
+    db.delete_bbox_subscription(&subscription.id)?;
+    Ok(subscription)
+}
+
+pub fn unsubscribe_all_bboxes(db: &dyn Db, user_email: &str) -> Result<()> {
+    Ok(db.delete_bbox_subscriptions_by_email(&user_email)?)
+}
+
+pub fn get_bbox_subscriptions(db: &dyn Db, user_email: &str) -> Result<Vec<BboxSubscription>> {
+    Ok(db.all_bbox_subscriptions_by_email(user_email)?)
+}
+
+pub fn bbox_subscriptions_by_coordinate(
+    db: &dyn Db,
+    pos: MapPoint,
+    change: &ObservedChange,
+) -> Result<Vec<BboxSubscription>> {
+    Ok(db
+        .all_bbox_subscriptions()?
+        .into_iter()
+        .filter(|s| s.matches(pos, change))
+        .collect())
+}
+
+/// All users that are notified immediately about a change.
+///
+/// Each user is notified only once about a change even if
+/// multiple subscriptions match.
+pub fn subscription_recipients_by_coordinate(
+    db: &dyn Db,
+    pos: MapPoint,
+    change: &ObservedChange,
+) -> Result<Vec<SubscriptionRecipient>> {
+    let mut recipients: Vec<_> = bbox_subscriptions_by_coordinate(db, pos, change)?
+        .into_iter()
+        .filter(|s| s.delivery_mode == SubscriptionDeliveryMode::Immediate)
+        .map(|s| SubscriptionRecipient {
+            email: s.user_email,
+            subscription_id: s.id,
+        })
+        .collect();
+    recipients.sort_unstable_by(|a, b| {
+        a.email
+            .cmp(&b.email)
+            .then_with(|| a.subscription_id.cmp(&b.subscription_id))
+    });
+    recipients.dedup_by(|a, b| a.email == b.email);
+    Ok(recipients)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockDb;
+    use super::*;
+    use ofdb_core::subscription::unsubscribe_token;
+
+    fn new_subscription(name: &str) -> NewBboxSubscription {
+        NewBboxSubscription {
+            name: name.into(),
+            bbox: MapBbox::new(
+                MapPoint::from_lat_lng_deg(0.0, 0.0),
+                MapPoint::from_lat_lng_deg(10.0, 10.0),
+            ),
+            tags: vec![],
+            categories: vec![],
+            entity_kind: None,
+            change_types: vec![],
+            delivery_mode: None,
+        }
+    }
+
+    fn recipient_emails(db: &MockDb, pos: MapPoint, change: &ObservedChange) -> Vec<String> {
+        subscription_recipients_by_coordinate(db, pos, change)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.email)
+            .collect()
+    }
+
+    #[test]
+    fn manage_multiple_subscriptions() {
+        let db = MockDb::default();
+        db.create_test_user("a@example.com", Role::User);
+        db.create_test_user("b@example.com", Role::User);
+
+        let first = create_bbox_subscription(
+            &db,
+            "a@example.com",
+            NewBboxSubscription {
+                tags: vec!["#Bio".into(), "fair".into()],
+                change_types: vec!["created".into()],
+                ..new_subscription(" Home ")
+            },
+        )
+        .unwrap();
+        assert_eq!("Home", first.name);
+        assert_eq!(vec!["bio", "fair"], first.filter.tags);
+        let second =
+            create_bbox_subscription(&db, "a@example.com", new_subscription("Work")).unwrap();
+        assert_eq!(
+            2,
+            get_bbox_subscriptions(&db, "a@example.com").unwrap().len()
+        );
+
+        // The legacy subscription doesn't replace named subscriptions
+        subscribe_to_bbox(&db, "a@example.com".into(), new_subscription("").bbox).unwrap();
+        subscribe_to_bbox(&db, "a@example.com".into(), new_subscription("").bbox).unwrap();
+        assert_eq!(
+            3,
+            get_bbox_subscriptions(&db, "a@example.com").unwrap().len()
+        );
+
+        assert!(matches!(
+            create_bbox_subscription(
+                &db,
+                "a@example.com",
+                NewBboxSubscription {
+                    entity_kind: Some("organization".into()),
+                    ..new_subscription("Invalid")
+                }
+            ),
+            Err(Error::Parameter(ParameterError::SubscriptionFilter))
+        ));
+
+        // Subscriptions of other users are not accessible
+        assert!(matches!(
+            update_bbox_subscription(&db, "b@example.com", &first.id, new_subscription("Other")),
+            Err(Error::Repo(RepoError::NotFound))
+        ));
+        assert!(matches!(
+            delete_bbox_subscription(&db, "b@example.com", &first.id),
+            Err(Error::Repo(RepoError::NotFound))
+        ));
+
+        let updated = update_bbox_subscription(
+            &db,
+            "a@example.com",
+            &first.id,
+            NewBboxSubscription {
+                entity_kind: Some("event".into()),
+                ..new_subscription("Home")
+            },
+        )
+        .unwrap();
+        assert_eq!(first.id, updated.id);
+        assert_eq!(
+            Some(SubscriptionEntityKind::Event),
+            db.get_bbox_subscription(&first.id)
+                .unwrap()
+                .filter
+                .entity_kind
+        );
+
+        delete_bbox_subscription(&db, "a@example.com", &second.id).unwrap();
+        assert_eq!(
+            2,
+            get_bbox_subscriptions(&db, "a@example.com").unwrap().len()
+        );
+    }
+
+    #[test]
+    fn notify_each_user_once_about_matching_changes() {
+        let db = MockDb::default();
+        db.create_test_user("a@example.com", Role::User);
+        db.create_test_user("b@example.com", Role::User);
+        create_bbox_subscription(&db, "a@example.com", new_subscription("All")).unwrap();
+        create_bbox_subscription(
+            &db,
+            "a@example.com",
+            NewBboxSubscription {
+                categories: vec![Category::ID_NON_PROFIT.into()],
+                ..new_subscription("Non-profit")
+            },
+        )
+        .unwrap();
+        create_bbox_subscription(
+            &db,
+            "b@example.com",
+            NewBboxSubscription {
+                entity_kind: Some("place".into()),
+                change_types: vec!["reviewed".into()],
+                ..new_subscription("Reviews")
+            },
+        )
+        .unwrap();
+
+        let pos = MapPoint::from_lat_lng_deg(5.0, 5.0);
+        let tags = vec![Category::TAG_NON_PROFIT.to_string()];
+        let created = ObservedChange {
+            entity_kind: SubscriptionEntityKind::Place,
+            change_type: SubscriptionChangeType::Created,
+            tags: &tags,
+        };
+        assert_eq!(vec!["a@example.com"], recipient_emails(&db, pos, &created));
+        let reviewed = ObservedChange {
+            change_type: SubscriptionChangeType::Reviewed,
+            ..created
+        };
+        assert_eq!(
+            vec!["a@example.com", "b@example.com"],
+            recipient_emails(&db, pos, &reviewed)
+        );
+        assert!(
+            recipient_emails(&db, MapPoint::from_lat_lng_deg(20.0, 20.0), &reviewed).is_empty()
+        );
+    }
+
+    #[test]
+    fn legacy_subscriptions_ignore_reviews() {
+        let db = MockDb::default();
+        db.create_test_user("a@example.com", Role::User);
+        subscribe_to_bbox(&db, "a@example.com".into(), new_subscription("").bbox).unwrap();
+
+        let pos = MapPoint::from_lat_lng_deg(5.0, 5.0);
+        let updated = ObservedChange {
+            entity_kind: SubscriptionEntityKind::Event,
+            change_type: SubscriptionChangeType::Updated,
+            tags: &[],
+        };
+        assert_eq!(vec!["a@example.com"], recipient_emails(&db, pos, &updated));
+        let reviewed = ObservedChange {
+            change_type: SubscriptionChangeType::Reviewed,
+            ..updated
+        };
+        assert!(recipient_emails(&db, pos, &reviewed).is_empty());
+    }
+
+    #[test]
+    fn unsubscribe_from_a_single_subscription_with_token() {
+        let db = MockDb::default();
+        db.create_test_user("a@example.com", Role::User);
+        let home =
+            create_bbox_subscription(&db, "a@example.com", new_subscription("Home")).unwrap();
+        let work =
+            create_bbox_subscription(&db, "a@example.com", new_subscription("Work")).unwrap();
+        let token = unsubscribe_token("secret", &home.id);
+
+        assert!(unsubscribe_with_token(&db, "other", &token).is_err());
+        assert!(unsubscribe_with_token(&db, "secret", &home.id.to_string()).is_err());
+        assert_eq!(
+            home,
+            get_bbox_subscription_by_unsubscribe_token(&db, "secret", &token).unwrap()
+        );
+        assert_eq!(home, unsubscribe_with_token(&db, "secret", &token).unwrap());
+        assert_eq!(
+            vec![work],
+            get_bbox_subscriptions(&db, "a@example.com").unwrap()
+        );
+        assert!(unsubscribe_with_token(&db, "secret", &token).is_err());
+    }
+}
@@ -0,0 +1,152 @@
+use super::super::util::validate;
+use crate::core::prelude::*;
+
+use chrono::{Duration, Utc};
+
+/// Requests to change the email address of a user.
+///
+/// The returned nonce must be sent to the new address
+/// for confirming the change.
+pub fn request_email_change<R: UserGateway + EmailChangeRepo>(
+    repo: &R,
+    email: &str,
+    new_email: &str,
+) -> Result<EmailNonce> {
+    validate::email(new_email)?;
+    let user = repo.get_user_by_email(email)?;
+    if repo.try_get_user_by_email(new_email)?.is_some() {
+        return Err(ParameterError::UserExists.into());
+    }
+    let token = EmailChangeToken {
+        email: user.email,
+        new_email_nonce: EmailNonce {
+            email: new_email.to_owned(),
+            nonce: Nonce::new(),
+        },
+        expires_at: Timestamp::from(Utc::now() + Duration::days(1)),
+    };
+    repo.replace_email_change_token(&token)?;
+    Ok(token.new_email_nonce)
+}
+
+/// Changes the email address of a user after the new address
+/// has been confirmed.
+///
+/// Returns the previous email address.
+pub fn confirm_email_change<R: UserGateway + EmailChangeRepo>(
+    repo: &R,
+    new_email_nonce: &EmailNonce,
+) -> Result<String> {
+    let token = repo
+        .consume_email_change_token(new_email_nonce)
+        .map_err(|err| match err {
+            RepoError::NotFound => Error::Parameter(ParameterError::TokenInvalid),
+            err => err.into(),
+        })?;
+    if token.expires_at < Timestamp::now() {
+        return Err(Error::Parameter(ParameterError::TokenExpired));
+    }
+    let new_email = &token.new_email_nonce.email;
+    // The address might have been registered in the meantime
+    if repo.try_get_user_by_email(new_email)?.is_some() {
+        return Err(ParameterError::UserExists.into());
+    }
+    info!("Changing email address of {} to {}", token.email, new_email);
+    repo.change_user_email(&token.email, new_email)?;
+    Ok(token.email)
+}
+
+pub fn delete_expired_email_change_tokens<R: EmailChangeRepo>(repo: &R) -> Result<usize> {
+    let expired_before = Timestamp::now();
+    Ok(repo.delete_expired_email_change_tokens(expired_before)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockDb;
+    use super::*;
+    use crate::core::util::geo::MapBbox;
+
+    #[test]
+    fn change_email_after_confirmation() {
+        let db = MockDb::default();
+        db.create_test_user("old@example.com", Role::User);
+        db.create_test_user("other@example.com", Role::User);
+        db.bbox_subscriptions.borrow_mut().push(BboxSubscription {
+            id: Id::new(),
+            user_email: "old@example.com".into(),
+            name: String::new(),
+            bbox: MapBbox::new(
+                MapPoint::from_lat_lng_deg(0.0, 0.0),
+                MapPoint::from_lat_lng_deg(1.0, 1.0),
+            ),
+            filter: Default::default(),
+            delivery_mode: Default::default(),
+        });
+
+        assert!(matches!(
+            request_email_change(&db, "old@example.com", "invalid"),
+            Err(Error::Parameter(ParameterError::Email))
+        ));
+        assert!(matches!(
+            request_email_change(&db, "old@example.com", "other@example.com"),
+            Err(Error::Parameter(ParameterError::UserExists))
+        ));
+
+        let email_nonce = request_email_change(&db, "old@example.com", "new@example.com").unwrap();
+        assert_eq!("new@example.com", email_nonce.email);
+        // The address is not changed before the confirmation
+        assert!(db
+            .try_get_user_by_email("old@example.com")
+            .unwrap()
+            .is_some());
+
+        let invalid_nonce = EmailNonce {
+            email: "new@example.com".into(),
+            nonce: Nonce::new(),
+        };
+        assert!(matches!(
+            confirm_email_change(&db, &invalid_nonce),
+            Err(Error::Parameter(ParameterError::TokenInvalid))
+        ));
+
+        assert_eq!(
+            "old@example.com",
+            confirm_email_change(&db, &email_nonce).unwrap()
+        );
+        assert!(db
+            .try_get_user_by_email("old@example.com")
+            .unwrap()
+            .is_none());
+        assert!(db
+            .try_get_user_by_email("new@example.com")
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            "new@example.com",
+            db.bbox_subscriptions.borrow()[0].user_email
+        );
+
+        // The nonce can only be used once
+        assert!(matches!(
+            confirm_email_change(&db, &email_nonce),
+            Err(Error::Parameter(ParameterError::TokenInvalid))
+        ));
+    }
+
+    #[test]
+    fn reject_change_if_address_has_been_taken() {
+        let db = MockDb::default();
+        db.create_test_user("old@example.com", Role::User);
+        let email_nonce = request_email_change(&db, "old@example.com", "new@example.com").unwrap();
+        db.create_test_user("new@example.com", Role::User);
+        assert!(matches!(
+            confirm_email_change(&db, &email_nonce),
+            Err(Error::Parameter(ParameterError::UserExists))
+        ));
+        assert!(db
+            .try_get_user_by_email("old@example.com")
+            .unwrap()
+            .is_some());
+    }
+}
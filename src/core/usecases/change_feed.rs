@@ -0,0 +1,337 @@
+use crate::core::prelude::*;
+
+use std::collections::HashSet;
+
+/// The number of changes that are loaded from the
+/// database at once before filtering them.
+const SCANNED_CHANGES_PAGE_SIZE: u64 = 1000;
+
+/// The maximum number of changes that are scanned for a
+/// single page of the feed.
+const MAX_SCANNED_CHANGES: u64 = 10 * SCANNED_CHANGES_PAGE_SIZE;
+
+/// The maximum number of changes on a single page of the feed.
+pub const MAX_CHANGE_FEED_LIMIT: usize = 100;
+
+#[derive(Debug, Clone, Default)]
+pub struct ChangeFeedFilter<'a> {
+    pub bbox: Option<MapBbox>,
+    /// All tags must be present
+    pub tags: Vec<&'a str>,
+    /// Changes of places with pending clearances are omitted
+    pub org_tag: Option<&'a str>,
+}
+
+impl<'a> ChangeFeedFilter<'a> {
+    fn matches_tags(&self, tags: &[String]) -> bool {
+        self.tags
+            .iter()
+            .chain(self.org_tag.iter())
+            .all(|t| tags.iter().any(|tag| tag == t))
+    }
+
+    fn matches_pos(&self, pos: Option<MapPoint>) -> bool {
+        match (self.bbox, pos) {
+            (None, _) => true,
+            (Some(bbox), Some(pos)) => bbox.contains_point(pos),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// Filtered changes, newest first.
+#[derive(Debug, Clone)]
+pub struct ChangeFeedPage<T> {
+    pub changes: Vec<T>,
+    /// The offset for resuming the scan with the next page,
+    /// `None` if all changes have been scanned.
+    pub next_offset: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PlaceChange {
+    pub place: Place,
+    pub status: ReviewStatus,
+    pub change: ChangeType,
+    pub changed_at: TimestampMs,
+}
+
+#[derive(Debug, Clone)]
+pub struct EventChange {
+    pub event: Event,
+    pub change: ChangeType,
+    pub changed_at: TimestampMs,
+}
+
+/// Derives the kind of a change from the review of a place revision.
+///
+/// Each new revision is reviewed initially with the status `Created`
+/// at the same time when the revision has been created.
+pub fn place_change_type(place: &Place, status: ReviewStatus, log: &ActivityLog) -> ChangeType {
+    match status {
+        ReviewStatus::Archived => ChangeType::Archived,
+        ReviewStatus::Created if log.activity.at == place.created.at => {
+            if place.revision.is_initial() {
+                ChangeType::Created
+            } else {
+                ChangeType::Updated
+            }
+        }
+        _ => ChangeType::Reviewed,
+    }
+}
+
+/// Loads pages of changes starting at the given offset until
+/// enough of them have passed the filter, all changes have been
+/// scanned or the maximum number of scanned changes is reached.
+///
+/// The filter receives and returns the changes together with
+/// their offsets.
+fn scan_changes<T, U>(
+    page_size: u64,
+    max_scanned: u64,
+    offset: u64,
+    limit: usize,
+    mut load_page: impl FnMut(&Pagination) -> Result<Vec<T>>,
+    mut filter_page: impl FnMut(Vec<(u64, T)>) -> Result<Vec<(u64, U)>>,
+) -> Result<ChangeFeedPage<U>> {
+    let limit = limit.min(MAX_CHANGE_FEED_LIMIT);
+    let mut changes = vec![];
+    let mut scan_offset = offset;
+    while changes.len() < limit {
+        let scanned = scan_offset - offset;
+        if scanned >= max_scanned {
+            break;
+        }
+        let page_size = page_size.min(max_scanned - scanned);
+        let pagination = Pagination {
+            offset: Some(scan_offset),
+            limit: Some(page_size),
+        };
+        let page = load_page(&pagination)?;
+        let exhausted = (page.len() as u64) < page_size;
+        let page_offset = scan_offset;
+        scan_offset += page.len() as u64;
+        for (change_offset, change) in filter_page((page_offset..).zip(page).collect())? {
+            if changes.len() == limit {
+                // Resume with the first change that has not been returned
+                return Ok(ChangeFeedPage {
+                    changes,
+                    next_offset: Some(change_offset),
+                });
+            }
+            changes.push(change);
+        }
+        if exhausted {
+            return Ok(ChangeFeedPage {
+                changes,
+                next_offset: None,
+            });
+        }
+    }
+    Ok(ChangeFeedPage {
+        changes,
+        next_offset: Some(scan_offset),
+    })
+}
+
+/// The most recent changes of places, newest first.
+pub fn recent_place_changes<R: PlaceRepo + PlaceClearanceRepo + OrganizationRepo>(
+    repo: &R,
+    filter: &ChangeFeedFilter,
+    offset: u64,
+    limit: usize,
+) -> Result<ChangeFeedPage<PlaceChange>> {
+    let params = RecentlyChangedEntriesParams {
+        since: None,
+        until: None,
+    };
+    let clearance_org_id = match filter.org_tag {
+        Some(org_tag) => repo.map_tag_to_clearance_org_id(org_tag)?,
+        None => None,
+    };
+    scan_changes(
+        SCANNED_CHANGES_PAGE_SIZE,
+        MAX_SCANNED_CHANGES,
+        offset,
+        limit,
+        |pagination| Ok(repo.recently_changed_places(&params, pagination)?),
+        |page| {
+            let mut changes: Vec<_> = page
+                .into_iter()
+                .filter(|(_, (place, _, _))| {
+                    filter.matches_tags(&place.tags) && filter.matches_pos(Some(place.location.pos))
+                })
+                .map(|(offset, (place, status, log))| {
+                    let change = PlaceChange {
+                        change: place_change_type(&place, status, &log),
+                        changed_at: log.activity.at,
+                        place,
+                        status,
+                    };
+                    (offset, change)
+                })
+                .collect();
+            if let Some(org_id) = &clearance_org_id {
+                let place_ids: Vec<_> = changes.iter().map(|(_, c)| c.place.id.as_str()).collect();
+                let pending: HashSet<_> = repo
+                    .load_pending_clearances_for_places(org_id, &place_ids)?
+                    .into_iter()
+                    .map(|p| p.place_id.to_string())
+                    .collect();
+                changes.retain(|(_, c)| !pending.contains(c.place.id.as_str()));
+            }
+            Ok(changes)
+        },
+    )
+}
+
+/// The most recent changes of events, newest first.
+pub fn recent_event_changes<R: EventGateway>(
+    repo: &R,
+    filter: &ChangeFeedFilter,
+    offset: u64,
+    limit: usize,
+) -> Result<ChangeFeedPage<EventChange>> {
+    let params = RecentlyChangedEntriesParams {
+        since: None,
+        until: None,
+    };
+    scan_changes(
+        SCANNED_CHANGES_PAGE_SIZE,
+        MAX_SCANNED_CHANGES,
+        offset,
+        limit,
+        |pagination| Ok(repo.recently_changed_events(&params, pagination)?),
+        |page| {
+            Ok(page
+                .into_iter()
+                .filter(|(_, (event, _, _))| {
+                    let pos = event
+                        .location
+                        .as_ref()
+                        .map(|l| l.pos)
+                        .filter(|pos| pos.is_valid());
+                    filter.matches_tags(&event.tags) && filter.matches_pos(pos)
+                })
+                .map(|(offset, (event, change, changed_at))| {
+                    let change = EventChange {
+                        event,
+                        change,
+                        changed_at: TimestampMs::from_seconds(changed_at.into_seconds()),
+                    };
+                    (offset, change)
+                })
+                .collect())
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn place_log(at: TimestampMs) -> ActivityLog {
+        ActivityLog {
+            activity: Activity { at, by: None },
+            context: None,
+            comment: None,
+        }
+    }
+
+    #[test]
+    fn derive_place_change_type() {
+        let mut place = Place::build().id("foo").finish();
+        let created_at = place.created.at;
+        let reviewed_at = TimestampMs::from_inner(created_at.into_inner() + 1);
+        assert_eq!(
+            ChangeType::Created,
+            place_change_type(&place, ReviewStatus::Created, &place_log(created_at))
+        );
+        assert_eq!(
+            ChangeType::Reviewed,
+            place_change_type(&place, ReviewStatus::Confirmed, &place_log(reviewed_at))
+        );
+        assert_eq!(
+            ChangeType::Reviewed,
+            place_change_type(&place, ReviewStatus::Created, &place_log(reviewed_at))
+        );
+        assert_eq!(
+            ChangeType::Archived,
+            place_change_type(&place, ReviewStatus::Archived, &place_log(reviewed_at))
+        );
+        place.revision = place.revision.next();
+        assert_eq!(
+            ChangeType::Updated,
+            place_change_type(&place, ReviewStatus::Created, &place_log(created_at))
+        );
+    }
+
+    #[test]
+    fn filter_changes_by_tags_and_bbox() {
+        let filter = ChangeFeedFilter {
+            bbox: Some(MapBbox::new(
+                MapPoint::from_lat_lng_deg(0.0, 0.0),
+                MapPoint::from_lat_lng_deg(10.0, 10.0),
+            )),
+            tags: vec!["foo"],
+            org_tag: Some("bar"),
+        };
+        let tags = vec!["foo".to_string(), "bar".to_string(), "baz".to_string()];
+        assert!(filter.matches_tags(&tags));
+        assert!(!filter.matches_tags(&tags[..1]));
+        assert!(filter.matches_pos(Some(MapPoint::from_lat_lng_deg(5.0, 5.0))));
+        assert!(!filter.matches_pos(Some(MapPoint::from_lat_lng_deg(15.0, 5.0))));
+        assert!(!filter.matches_pos(None));
+        assert!(ChangeFeedFilter::default().matches_pos(None));
+    }
+
+    #[test]
+    fn scan_pages_until_enough_changes_are_found() {
+        let log: Vec<u64> = (0..25).collect();
+        let loaded_pages = std::cell::Cell::new(0);
+        let scan = |offset, limit, divisor| {
+            loaded_pages.set(0);
+            scan_changes(
+                10,
+                20,
+                offset,
+                limit,
+                |pagination| {
+                    loaded_pages.set(loaded_pages.get() + 1);
+                    let offset = pagination.offset.unwrap() as usize;
+                    let limit = pagination.limit.unwrap() as usize;
+                    Ok(log.iter().skip(offset).take(limit).copied().collect())
+                },
+                |page| Ok(page.into_iter().filter(|(_, x)| x % divisor == 0).collect()),
+            )
+            .unwrap()
+        };
+        // Matches beyond the first page are found
+        let page = scan(5, 5, 12);
+        assert_eq!(vec![12, 24], page.changes);
+        assert_eq!(Some(25), page.next_offset);
+        assert_eq!(2, loaded_pages.get());
+        // Stop as soon as enough matches have been found
+        let page = scan(0, 3, 1);
+        assert_eq!(vec![0, 1, 2], page.changes);
+        assert_eq!(Some(3), page.next_offset);
+        assert_eq!(1, loaded_pages.get());
+        let page = scan(0, 1, 12);
+        assert_eq!(vec![0], page.changes);
+        assert_eq!(Some(10), page.next_offset);
+        // The number of scanned changes is limited
+        let page = scan(0, 5, 100);
+        assert_eq!(vec![0], page.changes);
+        assert_eq!(Some(20), page.next_offset);
+        assert_eq!(2, loaded_pages.get());
+        let page = scan(20, 5, 100);
+        assert!(page.changes.is_empty());
+        assert_eq!(None, page.next_offset);
+        // The page size is limited
+        let page = scan(0, MAX_CHANGE_FEED_LIMIT + 1, 1);
+        assert_eq!(20, page.changes.len());
+        assert!(scan(0, 0, 1).changes.is_empty());
+        assert_eq!(0, loaded_pages.get());
+    }
+}
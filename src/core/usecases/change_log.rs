@@ -0,0 +1,216 @@
+use crate::core::{prelude::*, util::geo::MapBbox};
+
+/// Changes are kept in the log for resuming streams
+/// during this period.
+const CHANGE_LOG_RETENTION_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Default)]
+pub struct ChangeStreamFilter {
+    pub bbox: Option<MapBbox>,
+    /// All tags must be present
+    pub tags: Vec<String>,
+}
+
+impl ChangeStreamFilter {
+    pub fn matches(&self, change: &EntityChange) -> bool {
+        let matches_pos = match (self.bbox, change.pos) {
+            (None, _) => true,
+            (Some(bbox), Some(pos)) => bbox.contains_point(pos),
+            (Some(_), None) => false,
+        };
+        matches_pos && self.tags.iter().all(|t| change.tags.contains(t))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChangeStreamBatch {
+    pub changes: Vec<ChangeLogEntry>,
+    /// The sequence number of the last scanned entry,
+    /// even if it didn't match the filter.
+    pub last_seq: u64,
+}
+
+fn place_change(place: &Place, change_type: ChangeType) -> EntityChange {
+    EntityChange {
+        at: TimestampMs::now(),
+        kind: ChangedEntityKind::Place,
+        id: place.id.clone(),
+        place_id: None,
+        change_type,
+        title: place.title.clone(),
+        pos: Some(place.location.pos),
+        tags: place.tags.clone(),
+    }
+}
+
+pub fn log_place_change<R: ChangeLogRepo>(
+    repo: &R,
+    place: &Place,
+    change_type: ChangeType,
+) -> Result<u64> {
+    Ok(repo.log_change(&place_change(place, change_type))?)
+}
+
+pub fn log_place_reviews<R: PlaceRepo + ChangeLogRepo>(
+    repo: &R,
+    ids: &[&str],
+    status: ReviewStatus,
+) -> Result<()> {
+    let change_type = if status == ReviewStatus::Archived {
+        ChangeType::Archived
+    } else {
+        ChangeType::Reviewed
+    };
+    for (place, _) in repo.get_places(ids)? {
+        repo.log_change(&place_change(&place, change_type))?;
+    }
+    Ok(())
+}
+
+pub fn log_event_change<R: ChangeLogRepo>(
+    repo: &R,
+    event: &Event,
+    change_type: ChangeType,
+) -> Result<u64> {
+    let change = EntityChange {
+        at: TimestampMs::now(),
+        kind: ChangedEntityKind::Event,
+        id: event.id.clone(),
+        place_id: None,
+        change_type,
+        title: event.title.clone(),
+        pos: event
+            .location
+            .as_ref()
+            .map(|l| l.pos)
+            .filter(|pos| pos.is_valid()),
+        tags: event.tags.clone(),
+    };
+    Ok(repo.log_change(&change)?)
+}
+
+/// Ratings inherit the position and the tags of the rated place.
+pub fn log_rating_change<R: ChangeLogRepo>(
+    repo: &R,
+    rating: &Rating,
+    place: &Place,
+    change_type: ChangeType,
+) -> Result<u64> {
+    debug_assert_eq!(rating.place_id, place.id);
+    let change = EntityChange {
+        at: TimestampMs::now(),
+        kind: ChangedEntityKind::Rating,
+        id: rating.id.clone(),
+        place_id: Some(place.id.clone()),
+        change_type,
+        title: rating.title.clone(),
+        pos: Some(place.location.pos),
+        tags: place.tags.clone(),
+    };
+    Ok(repo.log_change(&change)?)
+}
+
+pub fn log_rating_changes<R: PlaceRepo + RatingRepository + ChangeLogRepo>(
+    repo: &R,
+    ids: &[&str],
+    change_type: ChangeType,
+) -> Result<()> {
+    for rating in repo.load_ratings(ids)? {
+        let (place, _) = repo.get_place(rating.place_id.as_str())?;
+        log_rating_change(repo, &rating, &place, change_type)?;
+    }
+    Ok(())
+}
+
+/// The sequence number of the most recent change or 0
+/// if no changes have been logged yet.
+pub fn latest_change_seq<R: ChangeLogRepo>(repo: &R) -> Result<u64> {
+    Ok(repo.latest_change_log_seq()?.unwrap_or(0))
+}
+
+/// Loads the matching changes that follow the given sequence number.
+///
+/// At most `limit` entries are scanned.
+pub fn load_next_changes<R: ChangeLogRepo>(
+    repo: &R,
+    filter: &ChangeStreamFilter,
+    after_seq: u64,
+    limit: u64,
+) -> Result<ChangeStreamBatch> {
+    let entries = repo.load_change_log(after_seq, limit)?;
+    let last_seq = entries.last().map(|e| e.seq).unwrap_or(after_seq);
+    let changes = entries
+        .into_iter()
+        .filter(|e| filter.matches(&e.change))
+        .collect();
+    Ok(ChangeStreamBatch { changes, last_seq })
+}
+
+pub fn delete_outdated_change_log_entries<R: ChangeLogRepo>(repo: &R) -> Result<usize> {
+    let retention_ms = CHANGE_LOG_RETENTION_DAYS * 24 * 60 * 60 * 1000;
+    let before = TimestampMs::from_inner(TimestampMs::now().into_inner() - retention_ms);
+    Ok(repo.delete_change_log_entries_before(before)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockDb;
+    use super::*;
+
+    #[test]
+    fn load_next_matching_changes() {
+        let db = MockDb::default();
+        let inside = Place::build()
+            .id("a")
+            .pos(MapPoint::from_lat_lng_deg(5.0, 5.0))
+            .tags(vec!["foo"])
+            .finish();
+        let outside = Place::build()
+            .id("b")
+            .pos(MapPoint::from_lat_lng_deg(15.0, 5.0))
+            .tags(vec!["foo"])
+            .finish();
+        assert_eq!(0, latest_change_seq(&db).unwrap());
+        assert_eq!(
+            1,
+            log_place_change(&db, &inside, ChangeType::Created).unwrap()
+        );
+        assert_eq!(
+            2,
+            log_place_change(&db, &outside, ChangeType::Created).unwrap()
+        );
+        assert_eq!(
+            3,
+            log_place_change(&db, &inside, ChangeType::Updated).unwrap()
+        );
+        assert_eq!(3, latest_change_seq(&db).unwrap());
+
+        let filter = ChangeStreamFilter {
+            bbox: Some(MapBbox::new(
+                MapPoint::from_lat_lng_deg(0.0, 0.0),
+                MapPoint::from_lat_lng_deg(10.0, 10.0),
+            )),
+            tags: vec!["foo".into()],
+        };
+        let batch = load_next_changes(&db, &filter, 0, 2).unwrap();
+        assert_eq!(2, batch.last_seq);
+        assert_eq!(
+            vec![1],
+            batch.changes.iter().map(|e| e.seq).collect::<Vec<_>>()
+        );
+        let batch = load_next_changes(&db, &filter, batch.last_seq, 2).unwrap();
+        assert_eq!(3, batch.last_seq);
+        assert_eq!(ChangeType::Updated, batch.changes[0].change.change_type);
+        let batch = load_next_changes(&db, &filter, batch.last_seq, 2).unwrap();
+        assert_eq!(3, batch.last_seq);
+        assert!(batch.changes.is_empty());
+
+        let filter = ChangeStreamFilter {
+            bbox: None,
+            tags: vec!["bar".into()],
+        };
+        let batch = load_next_changes(&db, &filter, 0, 10).unwrap();
+        assert_eq!(3, batch.last_seq);
+        assert!(batch.changes.is_empty());
+    }
+}
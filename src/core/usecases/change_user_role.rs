@@ -1,3 +1,4 @@
+use super::log_admin_action;
 use crate::core::prelude::*;
 
 pub fn change_user_role<D: Db>(
@@ -17,9 +18,16 @@ pub fn change_user_role<D: Db>(
         .try_get_user_by_email(user_email)?
         .ok_or(ParameterError::UserDoesNotExist)?;
     if account.role > user.role && role < account.role {
+        let details = format!("{:?} -> {:?}", user.role, role);
         user.role = role;
         db.update_user(&user)?;
-        Ok(())
+        log_admin_action(
+            db,
+            &account.email,
+            AdminAction::ChangeRole,
+            &user.email,
+            Some(details),
+        )
     } else {
         Err(ParameterError::Forbidden.into())
     }
@@ -1,6 +1,6 @@
 use crate::core::prelude::*;
 
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 
 pub(crate) fn add_pending_clearance<R: PlaceClearanceRepo>(
     repo: &R,
@@ -91,3 +91,82 @@ pub fn clear_repo_results<R: PlaceRepo + PlaceClearanceRepo>(
     }
     Ok(cleared_results)
 }
+
+/// The maximum number of places that are listed in a single digest.
+///
+/// All new pending clearances are announced with the digest
+/// even if not all of them are listed.
+const MAX_PLACES_PER_PENDING_CLEARANCE_DIGEST: usize = 100;
+
+/// Selects all organizations whose oldest new pending clearance
+/// has been waiting for at least the batch period.
+pub fn due_pending_clearance_digest_org_ids<R: PlaceClearanceRepo>(
+    repo: &R,
+    now: TimestampMs,
+    batch_period: Duration,
+) -> Result<Vec<Id>> {
+    let due_until = TimestampMs::from_inner(now.into_inner() - batch_period.as_millis() as i64);
+    Ok(repo.load_org_ids_with_unannounced_pending_clearances(due_until)?)
+}
+
+/// Collects the new pending clearances of an organization
+/// that have not been announced to its contacts yet.
+///
+/// The owners and clearance reviewers of an organization
+/// are notified about new pending clearances.
+pub fn new_pending_clearance_digest<D: Db>(
+    db: &D,
+    org_id: &Id,
+) -> Result<Option<PendingClearanceDigest>> {
+    let notified_until = db.get_pending_clearances_notified_until(org_id)?;
+    let pending_clearances =
+        db.load_pending_clearances_for_places_created_after(org_id, notified_until)?;
+    let last = match pending_clearances.last() {
+        Some(last) => last,
+        None => return Ok(None),
+    };
+    let org = db.get_org_by_id(org_id)?;
+    let mut recipients = Vec::new();
+    for member in db.load_org_members(org_id)? {
+        if !matches!(
+            member.role,
+            OrganizationRole::Owner | OrganizationRole::ClearanceReviewer
+        ) {
+            continue;
+        }
+        match db.try_get_user_by_email(&member.email)? {
+            Some(user) if user.email_confirmed && !user.disabled => {
+                recipients.push(member.email);
+            }
+            _ => {}
+        }
+    }
+    let place_ids: Vec<_> = pending_clearances
+        .iter()
+        .take(MAX_PLACES_PER_PENDING_CLEARANCE_DIGEST)
+        .map(|p| p.place_id.as_str())
+        .collect();
+    let places = db
+        .get_places(&place_ids)?
+        .into_iter()
+        .map(|(place, _)| place)
+        .collect();
+    Ok(Some(PendingClearanceDigest {
+        total_count: db.count_pending_clearances_for_places(org_id)?,
+        new_count: pending_clearances.len(),
+        created_until: last.created_at,
+        org_id: org.id,
+        org_name: org.name,
+        recipients,
+        places,
+    }))
+}
+
+/// Pending clearances that have been created in the meantime
+/// are kept for the next digest.
+pub fn mark_pending_clearance_digest_delivered<R: PlaceClearanceRepo>(
+    repo: &R,
+    digest: &PendingClearanceDigest,
+) -> Result<()> {
+    Ok(repo.set_pending_clearances_notified_until(&digest.org_id, digest.created_until)?)
+}
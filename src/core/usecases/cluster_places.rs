@@ -0,0 +1,223 @@
+use super::search::{query_cleared_places, visible_places_query, SearchRequest};
+use crate::core::prelude::*;
+use ofdb_core::tile::TileId;
+
+use std::collections::{BTreeMap, HashMap};
+
+/// The number of grid cells per axis of a tile.
+///
+/// Each tile results in at most `CLUSTER_GRID_SIZE^2` clusters.
+pub const CLUSTER_GRID_SIZE: u32 = 8;
+
+#[derive(Debug, Clone)]
+pub struct PlaceCluster {
+    /// The center of mass of all places in the cluster
+    pub pos: MapPoint,
+    pub count: usize,
+    /// Category ids ordered by descending frequency
+    pub categories: Vec<String>,
+    /// Only available if the cluster contains a single place
+    pub place_id: Option<String>,
+    /// The tile contains more places than have been clustered,
+    /// i.e. `count` is only a lower bound
+    pub truncated: bool,
+}
+
+#[derive(Default)]
+struct ClusterAccumulator {
+    lat_deg_sum: f64,
+    lng_deg_sum: f64,
+    count: usize,
+    categories: HashMap<String, usize>,
+    place_id: Option<String>,
+}
+
+impl ClusterAccumulator {
+    fn add(&mut self, place: IndexedPlace) {
+        let IndexedPlace { id, pos, tags, .. } = place;
+        let (lat, lng) = pos.to_lat_lng_deg();
+        self.lat_deg_sum += lat;
+        self.lng_deg_sum += lng;
+        self.count += 1;
+        let (_, categories) = Category::split_from_tags(tags);
+        for c in categories {
+            *self.categories.entry(c.id.to_string()).or_default() += 1;
+        }
+        self.place_id = if self.count == 1 { Some(id) } else { None };
+    }
+
+    fn finish(self, truncated: bool) -> PlaceCluster {
+        let Self {
+            lat_deg_sum,
+            lng_deg_sum,
+            count,
+            categories,
+            place_id,
+        } = self;
+        debug_assert!(count > 0);
+        let pos =
+            MapPoint::from_lat_lng_deg(lat_deg_sum / count as f64, lng_deg_sum / count as f64);
+        let mut categories: Vec<_> = categories.into_iter().collect();
+        categories.sort_unstable_by(|(lhs_id, lhs_count), (rhs_id, rhs_count)| {
+            rhs_count.cmp(lhs_count).then_with(|| lhs_id.cmp(rhs_id))
+        });
+        PlaceCluster {
+            pos,
+            count,
+            categories: categories.into_iter().map(|(id, _)| id).collect(),
+            place_id,
+            truncated,
+        }
+    }
+}
+
+/// Aggregates all matching places within a map tile into clusters.
+///
+/// The bounding box of the request is replaced by the bounding box
+/// of the tile. All other filters of the search request apply.
+/// At most `limit` places are considered for clustering and
+/// all clusters are marked as truncated if the tile contains
+/// more places.
+pub fn cluster_places<D: Db>(
+    db: &D,
+    index: &dyn PlaceIndex,
+    tile: TileId,
+    req: SearchRequest,
+    limit: usize,
+) -> Result<Vec<PlaceCluster>> {
+    let org_tag = req.org_tag;
+    let req = SearchRequest {
+        bbox: tile.bbox(),
+        ..req
+    };
+    let query = visible_places_query(req);
+    // Query one more place to detect if the limit has been exceeded
+    let mut places = query_cleared_places(db, index, &query, org_tag, limit + 1)?;
+    let truncated = places.len() > limit;
+    if truncated {
+        log::info!(
+            "Clustering of tile {:?} is limited to {} places",
+            tile,
+            limit
+        );
+        places.truncate(limit);
+    }
+    // Ordered by grid rows (y) from north to south
+    let mut cells: BTreeMap<(u32, u32), ClusterAccumulator> = BTreeMap::new();
+    for place in places {
+        // Places on the boundary between two tiles must only be counted once
+        if let Some((x, y)) = tile.grid_cell(place.pos, CLUSTER_GRID_SIZE) {
+            cells.entry((y, x)).or_default().add(place);
+        }
+    }
+    Ok(cells
+        .into_iter()
+        .map(|(_, acc)| acc.finish(truncated))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockDb;
+    use super::*;
+    use anyhow::Result as Fallible;
+
+    struct InMemoryPlaceIndex(Vec<IndexedPlace>);
+
+    impl PlaceIndex for InMemoryPlaceIndex {
+        fn query_places(&self, query: &IndexQuery, limit: usize) -> Fallible<Vec<IndexedPlace>> {
+            let bbox = query.include_bbox.unwrap();
+            Ok(self
+                .0
+                .iter()
+                .filter(|p| bbox.contains_point(p.pos))
+                .take(limit)
+                .cloned()
+                .collect())
+        }
+    }
+
+    fn indexed_place(id: &str, lat: f64, lng: f64, tags: &[&str]) -> IndexedPlace {
+        IndexedPlace {
+            id: id.into(),
+            pos: MapPoint::from_lat_lng_deg(lat, lng),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn request() -> SearchRequest<'static> {
+        SearchRequest {
+            bbox: Default::default(),
+            ids: vec![],
+            categories: vec![],
+            org_tag: None,
+            hash_tags: vec![],
+            text: None,
+            status: vec![],
+        }
+    }
+
+    #[test]
+    fn cluster_places_of_a_tile() {
+        let db = MockDb::default();
+        let index = InMemoryPlaceIndex(vec![
+            indexed_place("a", 48.1, 9.1, &[Category::TAG_NON_PROFIT]),
+            indexed_place("b", 48.2, 9.2, &[Category::TAG_COMMERCIAL]),
+            indexed_place("c", 48.3, 9.3, &[Category::TAG_COMMERCIAL]),
+            indexed_place("d", -33.9, 18.4, &[Category::TAG_NON_PROFIT]),
+        ]);
+        let tile = TileId::try_new(0, 0, 0).unwrap();
+        let clusters = cluster_places(&db, &index, tile, request(), 100).unwrap();
+        assert_eq!(2, clusters.len());
+
+        let north = &clusters[0];
+        assert_eq!(3, north.count);
+        assert!(north.place_id.is_none());
+        assert_eq!(
+            vec![Category::ID_COMMERCIAL, Category::ID_NON_PROFIT],
+            north.categories
+        );
+        let (lat, lng) = north.pos.to_lat_lng_deg();
+        assert!((lat - 48.2).abs() < 1e-6);
+        assert!((lng - 9.2).abs() < 1e-6);
+
+        let south = &clusters[1];
+        assert_eq!(1, south.count);
+        assert_eq!(Some("d"), south.place_id.as_deref());
+        assert_eq!(vec![Category::ID_NON_PROFIT], south.categories);
+        assert!(clusters.iter().all(|c| !c.truncated));
+    }
+
+    #[test]
+    fn mark_clusters_of_a_truncated_tile() {
+        let db = MockDb::default();
+        let index = InMemoryPlaceIndex(vec![
+            indexed_place("a", 48.1, 9.1, &[]),
+            indexed_place("b", 48.2, 9.2, &[]),
+            indexed_place("c", 48.3, 9.3, &[]),
+        ]);
+        let tile = TileId::try_new(0, 0, 0).unwrap();
+        let clusters = cluster_places(&db, &index, tile, request(), 3).unwrap();
+        assert_eq!(3, clusters[0].count);
+        assert!(!clusters[0].truncated);
+
+        let clusters = cluster_places(&db, &index, tile, request(), 2).unwrap();
+        assert_eq!(1, clusters.len());
+        assert_eq!(2, clusters[0].count);
+        assert!(clusters[0].truncated);
+    }
+
+    #[test]
+    fn ignore_places_outside_of_the_tile() {
+        let db = MockDb::default();
+        let index = InMemoryPlaceIndex(vec![
+            indexed_place("a", 48.1, 9.1, &[]),
+            indexed_place("b", 48.1, -9.1, &[]),
+        ]);
+        let tile = TileId::try_new(1, 1, 0).unwrap();
+        let clusters = cluster_places(&db, &index, tile, request(), 100).unwrap();
+        assert_eq!(1, clusters.len());
+        assert_eq!(Some("a"), clusters[0].place_id.as_deref());
+    }
+}
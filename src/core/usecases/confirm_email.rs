@@ -29,6 +29,8 @@ mod tests {
             email_confirmed: false,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::Guest,
+            disabled: false,
+            language: None,
         });
         let email_nonce = EmailNonce {
             email: email.into(),
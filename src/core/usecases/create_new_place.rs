@@ -181,6 +181,7 @@ pub fn store_new_place<D: Db>(db: &D, s: Storable) -> Result<(Place, Vec<Rating>
         };
         super::clearance::place::add_pending_clearance(db, &clearance_org_ids, &pending_clearance)?;
     }
+    super::enqueue_place_webhooks(db, &place, ChangeType::Created, &clearance_org_ids)?;
     // No initial ratings so far
     let ratings = vec![];
     Ok((place, ratings))
@@ -6,11 +6,15 @@ use passwords::PasswordGenerator;
 pub struct NewUser {
     pub email: String,
     pub password: String,
+    /// The preferred language, see [`Language`]
+    #[serde(default)]
+    pub language: Option<String>,
 }
 
 pub fn create_new_user<D: UserGateway>(db: &D, u: NewUser) -> Result<()> {
     let password = u.password.parse::<Password>()?;
     validate::email(&u.email)?;
+    let language = parse_language(u.language.as_deref())?;
     if db.try_get_user_by_email(&u.email)?.is_some() {
         return Err(ParameterError::UserExists.into());
     }
@@ -19,6 +23,8 @@ pub fn create_new_user<D: UserGateway>(db: &D, u: NewUser) -> Result<()> {
         email_confirmed: false,
         password,
         role: Role::Guest,
+        disabled: false,
+        language,
     };
     debug!("Creating new user: email = {}", new_user.email);
     db.create_user(&new_user)?;
@@ -45,6 +51,7 @@ pub fn create_user_from_email<D: Db>(db: &D, email: &str) -> Result<User> {
     let u = NewUser {
         email: email.into(),
         password,
+        language: None,
     };
     create_new_user(db, u)?;
     Ok(db.get_user_by_email(email)?)
@@ -63,6 +70,7 @@ mod tests {
         let u = NewUser {
             email: "foo@bar.de".into(),
             password: "secret1".into(),
+            language: None,
         };
         assert!(create_new_user(&db, u).is_ok());
         assert!(db.get_user_by_email("foo@bar.de").is_ok());
@@ -71,6 +79,7 @@ mod tests {
         let u = NewUser {
             email: "baz@bar.de".into(),
             password: "secret2".into(),
+            language: None,
         };
         assert!(create_new_user(&db, u).is_ok());
         assert!(db.get_user_by_email("foo@bar.de").is_ok());
@@ -83,11 +92,13 @@ mod tests {
         let u = NewUser {
             email: "foo@baz.io".into(),
             password: "hello".into(),
+            language: None,
         };
         assert!(create_new_user(&db, u).is_err());
         let u = NewUser {
             email: "foo@baz.io".into(),
             password: "valid pass".into(),
+            language: None,
         };
         assert!(create_new_user(&db, u).is_ok());
     }
@@ -98,16 +109,19 @@ mod tests {
         let u = NewUser {
             email: "".into(),
             password: "secret".into(),
+            language: None,
         };
         assert!(create_new_user(&db, u).is_err());
         let u = NewUser {
             email: "fooo@".into(),
             password: "secret".into(),
+            language: None,
         };
         assert!(create_new_user(&db, u).is_err());
         let u = NewUser {
             email: "fooo@bar.io".into(),
             password: "secret".into(),
+            language: None,
         };
         assert!(create_new_user(&db, u).is_ok());
     }
@@ -120,10 +134,13 @@ mod tests {
             email_confirmed: true,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::Guest,
+            disabled: false,
+            language: None,
         });
         let u = NewUser {
             email: "baz@foo.bar".into(),
             password: "secret".into(),
+            language: None,
         };
         match create_new_user(&db, u).err().unwrap() {
             Error::Parameter(err) => {
@@ -144,6 +161,7 @@ mod tests {
         let u = NewUser {
             email: "foo@bar.io".into(),
             password: "secret".into(),
+            language: None,
         };
         assert!(create_new_user(&db, u).is_ok());
         assert_eq!(db.users.borrow()[0].email_confirmed, false);
@@ -155,6 +173,7 @@ mod tests {
         let u = NewUser {
             email: "foo@bar.io".into(),
             password: "secret".into(),
+            language: None,
         };
         assert!(create_new_user(&db, u).is_ok());
         assert!(db.users.borrow()[0].password.as_ref() != "secret");
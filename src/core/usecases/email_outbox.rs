@@ -0,0 +1,211 @@
+use crate::core::prelude::*;
+use ofdb_core::{email::record_send_attempt, gateways::email::EmailSender};
+
+/// Sent emails are kept in the outbox during this period.
+const SENT_EMAIL_RETENTION_DAYS: i64 = 7;
+
+#[derive(Debug, Clone)]
+pub struct EmailOutboxStatus {
+    pub stats: EmailOutboxStats,
+    /// Dead letters, most recent attempt first
+    pub failed: Vec<OutgoingEmail>,
+}
+
+/// Puts a separate message for each recipient into the outbox.
+pub fn enqueue_emails<R: EmailOutboxRepo>(
+    repo: &R,
+    recipients: &[Email],
+    content: &EmailContent,
+) -> Result<usize> {
+    let created_at = TimestampMs::now();
+    let emails: Vec<_> = recipients
+        .iter()
+        .filter(|r| !r.trim().is_empty())
+        .map(|recipient| OutgoingEmail {
+            id: Id::new(),
+            recipient: recipient.clone(),
+            subject: content.subject.clone(),
+            body: content.body.clone(),
+            html_body: content.html_body.clone(),
+            unsubscribe_url: content.unsubscribe_url.clone(),
+            created_at,
+            attempts: 0,
+            next_attempt_at: Some(created_at),
+            last_attempt_at: None,
+            last_error: None,
+            sent_at: None,
+        })
+        .collect();
+    if emails.is_empty() {
+        return Ok(0);
+    }
+    Ok(repo.add_outgoing_emails(&emails)?)
+}
+
+pub fn load_due_outgoing_emails<R: EmailOutboxRepo>(
+    repo: &R,
+    due_at: TimestampMs,
+    limit: u64,
+) -> Result<Vec<OutgoingEmail>> {
+    Ok(repo.load_due_outgoing_emails(due_at, limit)?)
+}
+
+/// Tries to send a single message.
+///
+/// The message must be stored afterwards to record the outcome.
+pub fn attempt_to_send_email(sender: &dyn EmailSender, mut email: OutgoingEmail) -> OutgoingEmail {
+    let content = EmailContent {
+        subject: email.subject.clone(),
+        body: email.body.clone(),
+        html_body: email.html_body.clone(),
+        unsubscribe_url: email.unsubscribe_url.clone(),
+    };
+    let result = sender.send(&email.recipient, &content);
+    if let Err(err) = &result {
+        warn!(
+            "Failed to send e-mail {} to {}: {}",
+            email.id, email.recipient, err
+        );
+    }
+    record_send_attempt(&mut email, TimestampMs::now(), result);
+    email
+}
+
+pub fn store_email_send_attempts<R: EmailOutboxRepo>(
+    repo: &R,
+    emails: &[OutgoingEmail],
+) -> Result<()> {
+    for email in emails {
+        if email.status() == OutgoingEmailStatus::Failed {
+            error!(
+                "Giving up sending e-mail {} to {} after {} attempts",
+                email.id, email.recipient, email.attempts
+            );
+        }
+        repo.update_outgoing_email(email)?;
+    }
+    Ok(())
+}
+
+pub fn load_email_outbox_status<R: EmailOutboxRepo>(
+    repo: &R,
+    pagination: &Pagination,
+) -> Result<EmailOutboxStatus> {
+    let stats = repo.count_outgoing_emails()?;
+    let failed = repo.load_failed_outgoing_emails(pagination)?;
+    Ok(EmailOutboxStatus { stats, failed })
+}
+
+pub fn delete_sent_outgoing_emails<R: EmailOutboxRepo>(repo: &R) -> Result<usize> {
+    let before = TimestampMs::from_inner(
+        TimestampMs::now().into_inner() - SENT_EMAIL_RETENTION_DAYS * 24 * 60 * 60 * 1000,
+    );
+    Ok(repo.delete_sent_outgoing_emails_before(before)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockDb;
+    use super::*;
+    use std::cell::Cell;
+
+    struct FlakySender {
+        failures: Cell<usize>,
+    }
+
+    impl EmailSender for FlakySender {
+        fn send(&self, _: &Email, _: &EmailContent) -> std::result::Result<(), String> {
+            if self.failures.get() > 0 {
+                self.failures.set(self.failures.get() - 1);
+                return Err("connection refused".into());
+            }
+            Ok(())
+        }
+    }
+
+    fn content() -> EmailContent {
+        EmailContent {
+            subject: "subject".into(),
+            body: "body".into(),
+            html_body: Some("<p>body</p>".into()),
+            unsubscribe_url: None,
+        }
+    }
+
+    #[test]
+    fn retry_sending_emails() {
+        let db = MockDb::default();
+        let recipients = vec![Email::from("a@example.com"), Email::from("b@example.com")];
+        assert_eq!(2, enqueue_emails(&db, &recipients, &content()).unwrap());
+
+        let sender = FlakySender {
+            failures: Cell::new(1),
+        };
+        let now = TimestampMs::now();
+        let attempts: Vec<_> = load_due_outgoing_emails(&db, now, 10)
+            .unwrap()
+            .into_iter()
+            .map(|e| attempt_to_send_email(&sender, e))
+            .collect();
+        store_email_send_attempts(&db, &attempts).unwrap();
+
+        let status = load_email_outbox_status(&db, &Pagination::default()).unwrap();
+        assert_eq!(
+            EmailOutboxStats {
+                pending: 1,
+                sent: 1,
+                failed: 0,
+            },
+            status.stats
+        );
+        assert!(status.failed.is_empty());
+
+        // The retry is delayed
+        assert!(load_due_outgoing_emails(&db, now, 10).unwrap().is_empty());
+        let later = TimestampMs::from_inner(now.into_inner() + 10 * 60 * 1000);
+        let due = load_due_outgoing_emails(&db, later, 10).unwrap();
+        assert_eq!(1, due.len());
+        let attempts: Vec<_> = due
+            .into_iter()
+            .map(|e| attempt_to_send_email(&sender, e))
+            .collect();
+        store_email_send_attempts(&db, &attempts).unwrap();
+        assert_eq!(
+            2,
+            load_email_outbox_status(&db, &Pagination::default())
+                .unwrap()
+                .stats
+                .sent
+        );
+    }
+
+    #[test]
+    fn dead_letters() {
+        let db = MockDb::default();
+        enqueue_emails(&db, &["a@example.com".into()], &content()).unwrap();
+        let sender = FlakySender {
+            failures: Cell::new(usize::MAX),
+        };
+        let mut due_at = TimestampMs::now();
+        loop {
+            let due = load_due_outgoing_emails(&db, due_at, 10).unwrap();
+            if due.is_empty() {
+                break;
+            }
+            let attempts: Vec<_> = due
+                .into_iter()
+                .map(|e| attempt_to_send_email(&sender, e))
+                .collect();
+            store_email_send_attempts(&db, &attempts).unwrap();
+            due_at = TimestampMs::from_inner(due_at.into_inner() + 24 * 60 * 60 * 1000);
+        }
+        let status = load_email_outbox_status(&db, &Pagination::default()).unwrap();
+        assert_eq!(1, status.stats.failed);
+        assert_eq!(0, status.stats.pending);
+        assert_eq!(1, status.failed.len());
+        assert_eq!(
+            Some("connection refused"),
+            status.failed[0].last_error.as_deref()
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use crate::core::prelude::*;
+
+use std::time::Duration;
+
+/// Unresolved addresses and locations are cached only briefly,
+/// because the geocoder might have been unavailable temporarily.
+pub const UNRESOLVED_GEOCODE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// The cache key of an address, i.e. all parts in lowercase
+/// with collapsed whitespace.
+pub fn normalize_address(addr: &Address) -> String {
+    let Address {
+        street,
+        zip,
+        city,
+        country,
+        state,
+    } = addr;
+    [street, zip, city, country, state]
+        .iter()
+        .map(|part| {
+            part.as_deref()
+                .unwrap_or_default()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_lowercase()
+        })
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+/// The cached location of an address unless it has expired.
+///
+/// Returns `Some(None)` if the address could not be resolved
+/// recently.
+pub fn get_cached_geocode<R: GeoCodeCacheRepo>(
+    repo: &R,
+    addr: &Address,
+    now: TimestampMs,
+    ttl: Duration,
+) -> Result<Option<Option<(f64, f64)>>> {
+    match repo.get_cached_geocode(&normalize_address(addr)) {
+        Ok(geocode) => {
+            let ttl = if geocode.lat_lng.is_some() {
+                ttl
+            } else {
+                ttl.min(UNRESOLVED_GEOCODE_TTL)
+            };
+            let expired_before = TimestampMs::from_inner(now.into_inner() - ttl.as_millis() as i64);
+            if geocode.created_at >= expired_before {
+                Ok(Some(geocode.lat_lng))
+            } else {
+                Ok(None)
+            }
+        }
+        Err(RepoError::NotFound) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Stores the location of an address and discards all expired ones.
+///
+/// Unresolved addresses are stored without a location.
+pub fn cache_geocode<R: GeoCodeCacheRepo>(
+    repo: &R,
+    addr: &Address,
+    lat_lng: Option<(f64, f64)>,
+    now: TimestampMs,
+    ttl: Duration,
+) -> Result<()> {
+    repo.replace_cached_geocode(&CachedGeoCode {
+        address: normalize_address(addr),
+        lat_lng,
+        created_at: now,
+    })?;
+    let expired_before = TimestampMs::from_inner(now.into_inner() - ttl.as_millis() as i64);
+    let count = repo.delete_cached_geocodes_before(expired_before)?;
+    if count > 0 {
+        log::debug!("Discarded {} expired geocodes", count);
+    }
+    Ok(())
+}
+
+/// The cache key of a location, i.e. rounded to about a meter.
+pub fn normalize_lat_lng(lat: f64, lng: f64) -> String {
+    format!("{:.5},{:.5}", lat, lng)
+}
+
+/// The cached address of a location unless it has expired.
+///
+/// Returns `Some(None)` if the location could not be resolved
+/// recently.
+pub fn get_cached_address<R: GeoCodeCacheRepo>(
+    repo: &R,
+    (lat, lng): (f64, f64),
+    now: TimestampMs,
+    ttl: Duration,
+) -> Result<Option<Option<Address>>> {
+    match repo.get_cached_address(&normalize_lat_lng(lat, lng)) {
+        Ok(cached) => {
+            let ttl = if cached.address.is_some() {
+                ttl
+            } else {
+                ttl.min(UNRESOLVED_GEOCODE_TTL)
+            };
+            let expired_before = TimestampMs::from_inner(now.into_inner() - ttl.as_millis() as i64);
+            if cached.created_at >= expired_before {
+                Ok(Some(cached.address))
+            } else {
+                Ok(None)
+            }
+        }
+        Err(RepoError::NotFound) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Stores the address of a location and discards all expired ones.
+///
+/// Unresolved locations are stored without an address.
+pub fn cache_address<R: GeoCodeCacheRepo>(
+    repo: &R,
+    (lat, lng): (f64, f64),
+    address: Option<&Address>,
+    now: TimestampMs,
+    ttl: Duration,
+) -> Result<()> {
+    repo.replace_cached_address(&CachedAddress {
+        pos: normalize_lat_lng(lat, lng),
+        address: address.cloned(),
+        created_at: now,
+    })?;
+    let expired_before = TimestampMs::from_inner(now.into_inner() - ttl.as_millis() as i64);
+    let count = repo.delete_cached_addresses_before(expired_before)?;
+    if count > 0 {
+        log::debug!("Discarded {} expired addresses", count);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockDb;
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    fn address(street: &str, city: &str) -> Address {
+        Address {
+            street: Some(street.into()),
+            city: Some(city.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn normalize_case_and_whitespace() {
+        assert_eq!(
+            normalize_address(&address(" Main  Street 1", "Berlin")),
+            normalize_address(&address("main street 1 ", "BERLIN"))
+        );
+        assert_ne!(
+            normalize_address(&address("Berlin", "")),
+            normalize_address(&address("", "Berlin"))
+        );
+    }
+
+    #[test]
+    fn expire_cached_geocodes() {
+        let db = MockDb::default();
+        let now = TimestampMs::now();
+        let addr = address("Main Street 1", "Berlin");
+        assert_eq!(None, get_cached_geocode(&db, &addr, now, TTL).unwrap());
+
+        cache_geocode(&db, &addr, Some((52.5, 13.4)), now, TTL).unwrap();
+        assert_eq!(
+            Some(Some((52.5, 13.4))),
+            get_cached_geocode(&db, &address("main street 1", "berlin"), now, TTL).unwrap()
+        );
+
+        let later = TimestampMs::from_inner(now.into_inner() + 2 * TTL.as_millis() as i64);
+        assert_eq!(None, get_cached_geocode(&db, &addr, later, TTL).unwrap());
+        // Expired geocodes are discarded when caching new ones
+        cache_geocode(
+            &db,
+            &address("Other Street", "Berlin"),
+            Some((52.0, 13.0)),
+            later,
+            TTL,
+        )
+        .unwrap();
+        assert_eq!(1, db.geocodes.borrow().len());
+    }
+
+    #[test]
+    fn expire_unresolved_geocodes_early() {
+        let db = MockDb::default();
+        let now = TimestampMs::now();
+        let addr = address("Nowhere", "Berlin");
+        cache_geocode(&db, &addr, None, now, TTL).unwrap();
+        assert_eq!(
+            Some(None),
+            get_cached_geocode(&db, &addr, now, UNRESOLVED_GEOCODE_TTL * 2).unwrap()
+        );
+
+        let later = TimestampMs::from_inner(
+            now.into_inner() + 2 * UNRESOLVED_GEOCODE_TTL.as_millis() as i64,
+        );
+        assert_eq!(
+            None,
+            get_cached_geocode(&db, &addr, later, UNRESOLVED_GEOCODE_TTL * 4).unwrap()
+        );
+    }
+
+    #[test]
+    fn expire_cached_addresses() {
+        let db = MockDb::default();
+        let now = TimestampMs::now();
+        let addr = address("Main Street 1", "Berlin");
+        assert_eq!(
+            None,
+            get_cached_address(&db, (52.5, 13.4), now, TTL).unwrap()
+        );
+
+        cache_address(&db, (52.5, 13.4), Some(&addr), now, TTL).unwrap();
+        assert_eq!(
+            Some(Some(addr)),
+            get_cached_address(&db, (52.500_001, 13.4), now, TTL).unwrap()
+        );
+
+        let later = TimestampMs::from_inner(now.into_inner() + 2 * TTL.as_millis() as i64);
+        assert_eq!(
+            None,
+            get_cached_address(&db, (52.5, 13.4), later, TTL).unwrap()
+        );
+        // Expired addresses are discarded when caching new ones
+        cache_address(
+            &db,
+            (52.0, 13.0),
+            Some(&address("Other Street", "Berlin")),
+            later,
+            TTL,
+        )
+        .unwrap();
+        assert_eq!(1, db.addresses.borrow().len());
+    }
+
+    #[test]
+    fn expire_unresolved_addresses_early() {
+        let db = MockDb::default();
+        let now = TimestampMs::now();
+        cache_address(&db, (0.0, 0.0), None, now, TTL).unwrap();
+        assert_eq!(
+            Some(None),
+            get_cached_address(&db, (0.0, 0.0), now, UNRESOLVED_GEOCODE_TTL * 2).unwrap()
+        );
+
+        let later = TimestampMs::from_inner(
+            now.into_inner() + 2 * UNRESOLVED_GEOCODE_TTL.as_millis() as i64,
+        );
+        assert_eq!(
+            None,
+            get_cached_address(&db, (0.0, 0.0), later, UNRESOLVED_GEOCODE_TTL * 4).unwrap()
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use super::verify_second_factor;
 use crate::core::prelude::*;
 
 //TODO: remove and use Credentials instead
@@ -5,6 +6,7 @@ use crate::core::prelude::*;
 pub struct Login {
     pub email: String,
     pub password: String,
+    pub totp_code: Option<String>,
 }
 
 pub struct Credentials<'a> {
@@ -12,27 +14,152 @@ pub struct Credentials<'a> {
     pub password: &'a str,
 }
 
-pub fn login_with_email<D: Db>(db: &D, login: &Credentials) -> Result<Role> {
-    db.try_get_user_by_email(&login.email)
-        .map_err(Error::Repo)
-        .and_then(|user| {
-            if let Some(u) = user {
-                if u.password.verify(&login.password) {
-                    if u.email_confirmed {
-                        Ok(u.role)
-                    } else {
-                        Err(Error::Parameter(ParameterError::EmailNotConfirmed))
-                    }
-                } else {
-                    Err(Error::Parameter(ParameterError::Credentials))
-                }
+/// Verifies the password of an enabled user with a confirmed
+/// email address without considering a second factor.
+pub fn authenticate_user<R: UserGateway>(repo: &R, login: &Credentials) -> Result<User> {
+    match repo.try_get_user_by_email(&login.email)? {
+        Some(u) if u.password.verify(&login.password) => {
+            if !u.email_confirmed {
+                Err(Error::Parameter(ParameterError::EmailNotConfirmed))
+            } else if u.disabled {
+                Err(Error::Parameter(ParameterError::UserDisabled))
             } else {
-                Err(Error::Parameter(ParameterError::Credentials))
+                Ok(u)
             }
-        })
+        }
+        _ => Err(Error::Parameter(ParameterError::Credentials)),
+    }
+}
+
+/// Logs in a user with the password and, if enabled for the
+/// account, a one-time password or recovery code.
+///
+/// Users with one of the given roles are not allowed to log in
+/// without having enabled the second factor.
+pub fn login_with_email<R: UserGateway + TwoFactorRepo>(
+    repo: &R,
+    login: &Credentials,
+    second_factor: Option<&str>,
+    second_factor_required_roles: &[Role],
+) -> Result<Role> {
+    let user = authenticate_user(repo, login)?;
+    let totp = match repo.get_user_totp(&user.email) {
+        Ok(totp) => Some(totp).filter(UserTotp::is_enabled),
+        Err(RepoError::NotFound) => None,
+        Err(err) => return Err(err.into()),
+    };
+    if totp.is_some() {
+        let code = second_factor
+            .map(str::trim)
+            .filter(|code| !code.is_empty())
+            .ok_or(ParameterError::SecondFactorRequired)?;
+        verify_second_factor(repo, &user.email, code)?;
+    } else if second_factor_required_roles.contains(&user.role) {
+        return Err(ParameterError::SecondFactorEnrollmentRequired.into());
+    }
+    Ok(user.role)
 }
 
 #[cfg(test)]
 mod tests {
-    //TODO: write tests
+    use super::super::{begin_totp_enrollment, confirm_totp_enrollment, tests::MockDb};
+    use super::*;
+    use crate::core::util::totp;
+
+    fn credentials(email: &str) -> Credentials {
+        Credentials {
+            email,
+            password: "secret",
+        }
+    }
+
+    #[test]
+    fn login_without_second_factor() {
+        let db = MockDb::default();
+        db.create_test_user("user@example.com", Role::User);
+        db.create_test_user("scout@example.com", Role::Scout);
+        assert!(matches!(
+            login_with_email(
+                &db,
+                &Credentials {
+                    email: "user@example.com",
+                    password: "invalid",
+                },
+                None,
+                &[]
+            ),
+            Err(Error::Parameter(ParameterError::Credentials))
+        ));
+        assert_eq!(
+            Role::User,
+            login_with_email(&db, &credentials("user@example.com"), None, &[Role::Scout]).unwrap()
+        );
+        assert!(matches!(
+            login_with_email(&db, &credentials("scout@example.com"), None, &[Role::Scout]),
+            Err(Error::Parameter(
+                ParameterError::SecondFactorEnrollmentRequired
+            ))
+        ));
+    }
+
+    #[test]
+    fn reject_disabled_user() {
+        let db = MockDb::default();
+        db.create_test_user("user@example.com", Role::User);
+        let mut user = db.get_user_by_email("user@example.com").unwrap();
+        user.disabled = true;
+        db.update_user(&user).unwrap();
+        assert!(matches!(
+            login_with_email(
+                &db,
+                &Credentials {
+                    email: "user@example.com",
+                    password: "invalid",
+                },
+                None,
+                &[]
+            ),
+            Err(Error::Parameter(ParameterError::Credentials))
+        ));
+        assert!(matches!(
+            login_with_email(&db, &credentials("user@example.com"), None, &[]),
+            Err(Error::Parameter(ParameterError::UserDisabled))
+        ));
+    }
+
+    #[test]
+    fn login_with_second_factor() {
+        let db = MockDb::default();
+        let email = "scout@example.com";
+        db.create_test_user(email, Role::Scout);
+        let enrollment = begin_totp_enrollment(&db, email, "OpenFairDB").unwrap();
+        let secret = totp::base32_decode(&enrollment.secret).unwrap();
+        let step = totp::time_step(Timestamp::now().into_seconds());
+        // Use the preceding time step to allow a subsequent login
+        let recovery_codes =
+            confirm_totp_enrollment(&db, email, &totp::totp(&secret, step - 1)).unwrap();
+
+        assert!(matches!(
+            login_with_email(&db, &credentials(email), None, &[Role::Scout]),
+            Err(Error::Parameter(ParameterError::SecondFactorRequired))
+        ));
+        assert!(matches!(
+            login_with_email(&db, &credentials(email), Some("000000x"), &[]),
+            Err(Error::Parameter(ParameterError::SecondFactor))
+        ));
+        let code = totp::totp(&secret, step);
+        assert_eq!(
+            Role::Scout,
+            login_with_email(&db, &credentials(email), Some(&code), &[Role::Scout]).unwrap()
+        );
+        // Replayed code
+        assert!(matches!(
+            login_with_email(&db, &credentials(email), Some(&code), &[]),
+            Err(Error::Parameter(ParameterError::SecondFactor))
+        ));
+        assert_eq!(
+            Role::Scout,
+            login_with_email(&db, &credentials(email), Some(&recovery_codes[0]), &[]).unwrap()
+        );
+    }
 }
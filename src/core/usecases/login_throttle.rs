@@ -0,0 +1,225 @@
+use super::sessions::timestamp_after;
+use crate::core::prelude::*;
+
+use std::time::Duration;
+
+/// Limits the failed login attempts of either an account
+/// or a client.
+#[derive(Debug, Clone)]
+pub struct LoginThrottlePolicy {
+    /// Failed attempts that are tolerated without any delay
+    pub free_attempts: u32,
+    /// The delay after exceeding the free attempts that is
+    /// doubled with each further failed attempt
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    /// Failed attempts until the login is locked, 0 disables
+    /// the lockout
+    pub lockout_threshold: u32,
+    /// Failed attempts are also forgotten after this period
+    /// of time without any further failed attempt
+    pub lockout_duration: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct LoginThrottling {
+    pub account: LoginThrottlePolicy,
+    pub client: LoginThrottlePolicy,
+}
+
+impl LoginThrottlePolicy {
+    fn delay(&self, count: u32) -> Option<Duration> {
+        let exceeded = count.checked_sub(self.free_attempts)?;
+        let factor = 2u32.checked_pow(exceeded).unwrap_or(u32::MAX);
+        let delay = self
+            .initial_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay);
+        Some(delay.min(self.max_delay))
+    }
+
+    fn is_expired(&self, failed: &FailedLogins, now: TimestampMs) -> bool {
+        match failed.locked_until {
+            Some(locked_until) => locked_until <= now,
+            None => timestamp_after(failed.last_failed_at, self.lockout_duration) <= now,
+        }
+    }
+
+    /// The earliest time of the next login attempt, if
+    /// the login is currently blocked.
+    fn retry_at(&self, failed: &FailedLogins, now: TimestampMs) -> Option<TimestampMs> {
+        if self.is_expired(failed, now) {
+            return None;
+        }
+        if failed.locked_until.is_some() {
+            return failed.locked_until;
+        }
+        let retry_at = timestamp_after(failed.last_failed_at, self.delay(failed.count)?);
+        Some(retry_at).filter(|retry_at| *retry_at > now)
+    }
+}
+
+/// Rejects the login attempt while backing off after
+/// previous failed attempts or while the login is locked.
+pub fn check_login_throttle<R: LoginThrottleRepo>(
+    repo: &R,
+    key: &LoginThrottleKey,
+    policy: &LoginThrottlePolicy,
+    now: TimestampMs,
+) -> Result<()> {
+    let failed = match repo.get_failed_logins(key) {
+        Ok(failed) => failed,
+        Err(RepoError::NotFound) => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    if let Some(retry_at) = policy.retry_at(&failed, now) {
+        let millis = retry_at.into_inner() - now.into_inner();
+        let secs = (millis + 999) / 1000;
+        return Err(ParameterError::TooManyLoginAttempts(secs as u64).into());
+    }
+    Ok(())
+}
+
+/// Counts a failed login attempt.
+///
+/// Returns the end of the lockout if the login has been
+/// locked due to this attempt.
+pub fn record_failed_login<R: LoginThrottleRepo>(
+    repo: &R,
+    key: LoginThrottleKey,
+    policy: &LoginThrottlePolicy,
+    now: TimestampMs,
+) -> Result<Option<TimestampMs>> {
+    let previous_count = match repo.get_failed_logins(&key) {
+        Ok(failed) if !policy.is_expired(&failed, now) => failed.count,
+        Ok(_) | Err(RepoError::NotFound) => 0,
+        Err(err) => return Err(err.into()),
+    };
+    let count = previous_count + 1;
+    let locked_until = if policy.lockout_threshold > 0 && count >= policy.lockout_threshold {
+        Some(timestamp_after(now, policy.lockout_duration))
+    } else {
+        None
+    };
+    repo.replace_failed_logins(&FailedLogins {
+        key,
+        count,
+        last_failed_at: now,
+        locked_until,
+    })?;
+    Ok(locked_until)
+}
+
+/// Resets the failed login attempts of an account,
+/// e.g. after a successful login or by an admin.
+pub fn unlock_login<R: UserGateway + LoginThrottleRepo>(repo: &R, email: &str) -> Result<()> {
+    // Fails if the user doesn't exist
+    repo.get_user_by_email(email)?;
+    repo.delete_failed_logins(&LoginThrottleKey::Account(email.to_owned()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockDb;
+    use super::*;
+
+    fn policy() -> LoginThrottlePolicy {
+        LoginThrottlePolicy {
+            free_attempts: 2,
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(4),
+            lockout_threshold: 6,
+            lockout_duration: Duration::from_secs(60),
+        }
+    }
+
+    fn at(secs: i64) -> TimestampMs {
+        TimestampMs::from_inner(1_600_000_000_000 + secs * 1000)
+    }
+
+    fn retry_after(db: &MockDb, key: &LoginThrottleKey, now: TimestampMs) -> Option<u64> {
+        match check_login_throttle(db, key, &policy(), now) {
+            Ok(()) => None,
+            Err(Error::Parameter(ParameterError::TooManyLoginAttempts(secs))) => Some(secs),
+            Err(err) => panic!("Unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn back_off_exponentially() {
+        let db = MockDb::default();
+        let key = LoginThrottleKey::Account("foo@bar.tld".into());
+        assert_eq!(None, retry_after(&db, &key, at(0)));
+        // Free attempts
+        record_failed_login(&db, key.clone(), &policy(), at(0)).unwrap();
+        assert_eq!(None, retry_after(&db, &key, at(0)));
+        record_failed_login(&db, key.clone(), &policy(), at(0)).unwrap();
+        assert_eq!(Some(1), retry_after(&db, &key, at(0)));
+        assert_eq!(None, retry_after(&db, &key, at(1)));
+        // 2s, 4s and then limited by the max. delay
+        record_failed_login(&db, key.clone(), &policy(), at(1)).unwrap();
+        assert_eq!(Some(2), retry_after(&db, &key, at(1)));
+        record_failed_login(&db, key.clone(), &policy(), at(3)).unwrap();
+        assert_eq!(Some(4), retry_after(&db, &key, at(3)));
+        record_failed_login(&db, key.clone(), &policy(), at(7)).unwrap();
+        assert_eq!(Some(4), retry_after(&db, &key, at(7)));
+        assert_eq!(Some(1), retry_after(&db, &key, at(10)));
+        // Other keys are not affected
+        let other = LoginThrottleKey::Client("127.0.0.1".into());
+        assert_eq!(None, retry_after(&db, &other, at(7)));
+    }
+
+    #[test]
+    fn lock_after_too_many_failed_attempts() {
+        let db = MockDb::default();
+        let key = LoginThrottleKey::Account("foo@bar.tld".into());
+        for _ in 0..5 {
+            assert_eq!(
+                None,
+                record_failed_login(&db, key.clone(), &policy(), at(0)).unwrap()
+            );
+        }
+        assert_eq!(
+            Some(at(70)),
+            record_failed_login(&db, key.clone(), &policy(), at(10)).unwrap()
+        );
+        assert_eq!(Some(60), retry_after(&db, &key, at(10)));
+        assert_eq!(Some(1), retry_after(&db, &key, at(69)));
+        assert_eq!(None, retry_after(&db, &key, at(70)));
+        // Counting starts again after the lockout
+        record_failed_login(&db, key.clone(), &policy(), at(70)).unwrap();
+        assert_eq!(1, db.get_failed_logins(&key).unwrap().count);
+    }
+
+    #[test]
+    fn forget_failed_attempts_after_a_while() {
+        let db = MockDb::default();
+        let key = LoginThrottleKey::Client("127.0.0.1".into());
+        for _ in 0..3 {
+            record_failed_login(&db, key.clone(), &policy(), at(0)).unwrap();
+        }
+        assert!(retry_after(&db, &key, at(0)).is_some());
+        record_failed_login(&db, key.clone(), &policy(), at(60)).unwrap();
+        assert_eq!(1, db.get_failed_logins(&key).unwrap().count);
+        assert_eq!(None, retry_after(&db, &key, at(60)));
+    }
+
+    #[test]
+    fn unlock_account() {
+        let db = MockDb::default();
+        let email = "foo@bar.tld";
+        let key = LoginThrottleKey::Account(email.into());
+        assert!(matches!(
+            unlock_login(&db, email),
+            Err(Error::Repo(RepoError::NotFound))
+        ));
+        db.create_test_user(email, Role::User);
+        for _ in 0..6 {
+            record_failed_login(&db, key.clone(), &policy(), at(0)).unwrap();
+        }
+        assert!(retry_after(&db, &key, at(1)).is_some());
+        unlock_login(&db, email).unwrap();
+        assert_eq!(None, retry_after(&db, &key, at(1)));
+    }
+}
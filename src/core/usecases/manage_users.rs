@@ -0,0 +1,210 @@
+use crate::core::prelude::*;
+
+/// Records an action of an administrator on a user account.
+pub fn log_admin_action<R: AuditLogRepo>(
+    repo: &R,
+    admin_email: &str,
+    action: AdminAction,
+    user_email: &str,
+    details: Option<String>,
+) -> Result<()> {
+    info!(
+        "Admin {} performed {} on {}",
+        admin_email, action, user_email
+    );
+    let entry = AuditLogEntry {
+        at: TimestampMs::now(),
+        actor: admin_email.to_owned(),
+        action,
+        target: user_email.to_owned(),
+        details,
+    };
+    Ok(repo.add_audit_log_entry(&entry)?)
+}
+
+pub fn search_users<R: UserGateway>(
+    repo: &R,
+    params: &UserSearchParams,
+    pagination: &Pagination,
+) -> Result<Vec<User>> {
+    Ok(repo.search_users(params, pagination)?)
+}
+
+/// Disables or enables the account of a user with a lower role.
+///
+/// Disabled users are not able to log in and all of their
+/// sessions are ended.
+pub fn set_user_disabled<R: UserGateway + SessionTokenRepo + AuditLogRepo>(
+    repo: &R,
+    admin: &User,
+    user_email: &str,
+    disabled: bool,
+) -> Result<()> {
+    let mut user = repo
+        .try_get_user_by_email(user_email)?
+        .ok_or(ParameterError::UserDoesNotExist)?;
+    if admin.role <= user.role {
+        return Err(ParameterError::Forbidden.into());
+    }
+    if user.disabled != disabled {
+        user.disabled = disabled;
+        repo.update_user(&user)?;
+    }
+    let action = if disabled {
+        repo.delete_refresh_tokens_of_user(&user.email)?;
+        AdminAction::DisableUser
+    } else {
+        AdminAction::EnableUser
+    };
+    log_admin_action(repo, &admin.email, action, &user.email, None)
+}
+
+/// Confirms the email address of a user without
+/// verifying it.
+pub fn confirm_user_email_by_admin<R: UserGateway + AuditLogRepo>(
+    repo: &R,
+    admin: &User,
+    user_email: &str,
+) -> Result<()> {
+    let mut user = repo
+        .try_get_user_by_email(user_email)?
+        .ok_or(ParameterError::UserDoesNotExist)?;
+    if !user.email_confirmed {
+        user.email_confirmed = true;
+        repo.update_user(&user)?;
+    }
+    log_admin_action(
+        repo,
+        &admin.email,
+        AdminAction::ConfirmEmail,
+        &user.email,
+        None,
+    )
+}
+
+pub fn load_audit_log<R: AuditLogRepo>(
+    repo: &R,
+    user_email: Option<&str>,
+    pagination: &Pagination,
+) -> Result<Vec<AuditLogEntry>> {
+    Ok(repo.load_audit_log(user_email, pagination)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockDb;
+    use super::*;
+
+    #[test]
+    fn disable_and_enable_user() {
+        let db = MockDb::default();
+        let admin = db.create_test_user("admin@example.com", Role::Admin);
+        let other_admin = db.create_test_user("other@example.com", Role::Admin);
+        db.create_test_user("user@example.com", Role::User);
+        db.refresh_tokens.borrow_mut().push(RefreshToken {
+            session_id: Id::new(),
+            token_hash: "hash".into(),
+            email: "user@example.com".into(),
+            created_at: TimestampMs::now(),
+            expires_at: TimestampMs::now(),
+            used_at: None,
+        });
+
+        assert!(matches!(
+            set_user_disabled(&db, &admin, "admin@example.com", true),
+            Err(Error::Parameter(ParameterError::Forbidden))
+        ));
+        assert!(matches!(
+            set_user_disabled(&db, &admin, &other_admin.email, true),
+            Err(Error::Parameter(ParameterError::Forbidden))
+        ));
+        assert!(matches!(
+            set_user_disabled(&db, &admin, "unknown@example.com", true),
+            Err(Error::Parameter(ParameterError::UserDoesNotExist))
+        ));
+
+        set_user_disabled(&db, &admin, "user@example.com", true).unwrap();
+        assert!(db.get_user_by_email("user@example.com").unwrap().disabled);
+        assert!(db.refresh_tokens.borrow().is_empty());
+
+        set_user_disabled(&db, &admin, "user@example.com", false).unwrap();
+        assert!(!db.get_user_by_email("user@example.com").unwrap().disabled);
+
+        let audit_log = load_audit_log(&db, None, &Pagination::default()).unwrap();
+        assert_eq!(2, audit_log.len());
+        assert_eq!(AdminAction::EnableUser, audit_log[0].action);
+        assert_eq!(AdminAction::DisableUser, audit_log[1].action);
+        assert_eq!("admin@example.com", audit_log[1].actor);
+        assert_eq!("user@example.com", audit_log[1].target);
+    }
+
+    #[test]
+    fn confirm_email_by_admin() {
+        let db = MockDb::default();
+        let admin = db.create_test_user("admin@example.com", Role::Admin);
+        let mut user = db.create_test_user("user@example.com", Role::Guest);
+        user.email_confirmed = false;
+        db.update_user(&user).unwrap();
+        confirm_user_email_by_admin(&db, &admin, "user@example.com").unwrap();
+        assert!(
+            db.get_user_by_email("user@example.com")
+                .unwrap()
+                .email_confirmed
+        );
+        let audit_log =
+            load_audit_log(&db, Some("user@example.com"), &Pagination::default()).unwrap();
+        assert_eq!(1, audit_log.len());
+        assert_eq!(AdminAction::ConfirmEmail, audit_log[0].action);
+    }
+
+    #[test]
+    fn search_users_by_email_role_and_status() {
+        let db = MockDb::default();
+        let admin = db.create_test_user("admin@example.com", Role::Admin);
+        db.create_test_user("b@example.com", Role::Scout);
+        db.create_test_user("a@example.com", Role::Scout);
+        db.create_test_user("c@example.org", Role::User);
+        set_user_disabled(&db, &admin, "c@example.org", true).unwrap();
+
+        let emails = |params: UserSearchParams, pagination: Pagination| {
+            search_users(&db, &params, &pagination)
+                .unwrap()
+                .into_iter()
+                .map(|u| u.email)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            vec!["a@example.com", "admin@example.com", "b@example.com"],
+            emails(
+                UserSearchParams {
+                    email: Some("example.com".into()),
+                    ..Default::default()
+                },
+                Pagination::default()
+            )
+        );
+        assert_eq!(
+            vec!["b@example.com"],
+            emails(
+                UserSearchParams {
+                    role: Some(Role::Scout),
+                    ..Default::default()
+                },
+                Pagination {
+                    offset: Some(1),
+                    limit: Some(1),
+                }
+            )
+        );
+        assert_eq!(
+            vec!["c@example.org"],
+            emails(
+                UserSearchParams {
+                    disabled: Some(true),
+                    ..Default::default()
+                },
+                Pagination::default()
+            )
+        );
+    }
+}
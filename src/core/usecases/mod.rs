@@ -1,50 +1,71 @@
 use crate::core::{
     error::ParameterError,
     prelude::*,
-    util::{
-        geo::{MapBbox, MapPoint},
-        parse::parse_url_param,
-        validate,
-    },
+    util::{geo::MapBbox, parse::parse_url_param},
 };
 
+mod api_tokens;
 mod archive_comments;
 mod archive_events;
 mod archive_ratings;
+mod author_notifications;
 mod authorize;
+mod backfill_addresses;
+mod bbox_subscriptions;
+mod change_email;
+mod change_feed;
+mod change_log;
 mod change_user_role;
 pub mod clearance;
+mod cluster_places;
 mod confirm_email;
 mod confirm_email_and_reset_password;
 mod create_new_place;
 mod create_new_user;
 mod delete_event;
+mod email_outbox;
 mod export_event;
 mod export_place;
 mod filter_event;
 mod filter_place;
 mod find_duplicates;
+mod geocode_cache;
 mod indexing;
 mod load_places;
 mod login;
+mod login_throttle;
+mod manage_users;
+mod oidc;
+mod organization_members;
+mod organizations;
 mod query_events;
 mod rate_place;
 mod register;
 mod review_places;
 mod search;
+mod sessions;
 mod store_event;
+mod subscription_digests;
+mod two_factor;
 mod update_place;
+mod user_language;
 mod user_tokens;
+mod webhooks;
 
 #[cfg(test)]
 pub mod tests;
 
 pub use self::{
-    archive_comments::*, archive_events::*, archive_ratings::*, authorize::*, change_user_role::*,
+    api_tokens::*, archive_comments::*, archive_events::*, archive_ratings::*,
+    author_notifications::*, authorize::*, backfill_addresses::*, bbox_subscriptions::*,
+    change_email::*, change_feed::*, change_log::*, change_user_role::*, cluster_places::*,
     confirm_email::*, confirm_email_and_reset_password::*, create_new_place::*, create_new_user::*,
-    delete_event::*, export_event::*, export_place::*, filter_event::*, filter_place::*,
-    find_duplicates::*, indexing::*, load_places::*, login::*, query_events::*, rate_place::*,
-    register::*, review_places::*, search::*, store_event::*, update_place::*, user_tokens::*,
+    delete_event::*, email_outbox::*, export_event::*, export_place::*, filter_event::*,
+    filter_place::*, find_duplicates::*, geocode_cache::*, indexing::*, load_places::*, login::*,
+    login_throttle::*, manage_users::*, oidc::*, organization_members::*, organizations::*,
+    query_events::*, rate_place::*, register::*, review_places::*, search::*, sessions::*,
+    store_event::*, subscription_digests::*, two_factor::*, update_place::*, user_language::*,
+    user_tokens::*, webhooks::*,
 };
 
 //TODO: move usecases into separate files
@@ -109,53 +130,6 @@ pub fn delete_user(db: &dyn Db, login_email: &str, email: &str) -> Result<()> {
     Ok(db.delete_user_by_email(email)?)
 }
 
-pub fn subscribe_to_bbox(db: &dyn Db, user_email: String, bbox: MapBbox) -> Result<()> {
-    validate::bbox(&bbox)?;
-
-    // TODO: support multiple subscriptions in KVM (frontend)
-    // In the meanwhile we just replace existing subscriptions
-    // with a new one.
-    unsubscribe_all_bboxes(db, &user_email)?;
-
-    let id = Id::new();
-    db.create_bbox_subscription(&BboxSubscription {
-        id,
-        user_email,
-        bbox,
-    })?;
-    Ok(())
-}
-
-pub fn unsubscribe_all_bboxes(db: &dyn Db, user_email: &str) -> Result<()> {
-    Ok(db.delete_bbox_subscriptions_by_email(&user_email)?)
-}
-
-pub fn get_bbox_subscriptions(db: &dyn Db, user_email: &str) -> Result<Vec<BboxSubscription>> {
-    Ok(db
-        .all_bbox_subscriptions()?
-        .into_iter()
-        .filter(|s| s.user_email == user_email)
-        .collect())
-}
-
-pub fn bbox_subscriptions_by_coordinate(
-    db: &dyn Db,
-    pos: MapPoint,
-) -> Result<Vec<BboxSubscription>> {
-    Ok(db
-        .all_bbox_subscriptions()?
-        .into_iter()
-        .filter(|s| s.bbox.contains_point(pos))
-        .collect())
-}
-
-pub fn email_addresses_by_coordinate(db: &dyn Db, pos: MapPoint) -> Result<Vec<String>> {
-    Ok(bbox_subscriptions_by_coordinate(db, pos)?
-        .into_iter()
-        .map(|s| s.user_email)
-        .collect())
-}
-
 pub fn prepare_tag_list<'a>(tags: impl IntoIterator<Item = &'a str>) -> Vec<String> {
     let mut tags: Vec<_> = tags
         .into_iter()
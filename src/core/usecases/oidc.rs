@@ -0,0 +1,500 @@
+use crate::core::prelude::*;
+use ofdb_core::gateways::oidc::{OidcCodeExchange, OidcGateway, OidcUserInfo};
+use rand::{rngs::OsRng, RngCore};
+use sha2::{Digest, Sha256};
+
+/// An OpenID Connect provider for logging in with the
+/// authorization code flow.
+#[derive(Debug, Clone)]
+pub struct OidcProvider {
+    pub id: String,
+    pub name: String,
+    pub authorization_endpoint: Url,
+    pub token_endpoint: Url,
+    pub userinfo_endpoint: Url,
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_uri: Url,
+    pub scopes: Vec<String>,
+    /// The claim that contains the groups of a user
+    pub role_claim: String,
+    /// Maps groups to roles, the highest role of all
+    /// matching groups wins
+    pub role_mapping: Vec<(String, Role)>,
+    /// The role of new users without any matching group
+    pub default_role: Role,
+    /// The email domains for which the provider is authoritative.
+    /// Existing users with an email address of these domains are
+    /// linked to their account at the provider on the first login.
+    pub email_domains: Vec<String>,
+}
+
+impl OidcProvider {
+    /// The URL for redirecting the user to the provider.
+    pub fn authorization_url(&self, state: &str, code_verifier: &str) -> Url {
+        let mut url = self.authorization_endpoint.clone();
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.client_id)
+            .append_pair("redirect_uri", self.redirect_uri.as_str())
+            .append_pair("scope", &self.scopes.join(" "))
+            .append_pair("state", state)
+            .append_pair("code_challenge", &pkce_code_challenge(code_verifier))
+            .append_pair("code_challenge_method", "S256");
+        url
+    }
+
+    fn is_authoritative_for(&self, email: &str) -> bool {
+        email
+            .rsplitn(2, '@')
+            .next()
+            .map(|domain| {
+                self.email_domains
+                    .iter()
+                    .any(|d| d.eq_ignore_ascii_case(domain))
+            })
+            .unwrap_or(false)
+    }
+
+    fn map_role(&self, groups: &[String]) -> Option<Role> {
+        self.role_mapping
+            .iter()
+            .filter(|(group, _)| groups.contains(group))
+            .map(|(_, role)| *role)
+            .max()
+    }
+}
+
+/// Creates a secret for binding the authorization code to
+/// the login request of the user (PKCE, RFC 7636).
+pub fn new_pkce_code_verifier() -> String {
+    let mut bytes = [0; 32];
+    OsRng.fill_bytes(&mut bytes);
+    base64::encode_config(&bytes, base64::URL_SAFE_NO_PAD)
+}
+
+fn pkce_code_challenge(code_verifier: &str) -> String {
+    base64::encode_config(
+        Sha256::digest(code_verifier.as_bytes()),
+        base64::URL_SAFE_NO_PAD,
+    )
+}
+
+/// A user who has been authenticated by the provider.
+#[derive(Debug, Clone)]
+pub struct OidcIdentity {
+    pub subject: String,
+    pub email: String,
+    pub groups: Vec<String>,
+}
+
+/// Exchanges the authorization code for the identity of
+/// the user.
+///
+/// Only users with an email address that has explicitly
+/// been verified by the provider are accepted.
+pub fn authenticate_with_oidc<G: OidcGateway>(
+    gateway: &G,
+    provider: &OidcProvider,
+    code: &str,
+    code_verifier: &str,
+) -> Result<OidcIdentity> {
+    let user_info = gateway
+        .exchange_code(&OidcCodeExchange {
+            token_endpoint: &provider.token_endpoint,
+            userinfo_endpoint: &provider.userinfo_endpoint,
+            client_id: &provider.client_id,
+            client_secret: &provider.client_secret,
+            redirect_uri: &provider.redirect_uri,
+            code,
+            code_verifier,
+            role_claim: &provider.role_claim,
+        })
+        .map_err(|err| {
+            warn!(
+                "Login with OpenID provider '{}' failed: {}",
+                provider.id, err
+            );
+            ParameterError::Unauthorized
+        })?;
+    let OidcUserInfo {
+        subject,
+        email,
+        email_verified,
+        groups,
+    } = user_info;
+    match email {
+        Some(email) if email_verified == Some(true) => Ok(OidcIdentity {
+            subject,
+            email,
+            groups,
+        }),
+        _ => {
+            warn!(
+                "OpenID provider '{}' did not provide a verified email address of user {}",
+                provider.id, subject
+            );
+            Err(ParameterError::UnverifiedEmail.into())
+        }
+    }
+}
+
+fn check_existing_user<R: TwoFactorRepo>(
+    repo: &R,
+    provider: &OidcProvider,
+    user: &User,
+) -> Result<()> {
+    if user.disabled {
+        return Err(ParameterError::UserDisabled.into());
+    }
+    let totp_enabled = match repo.get_user_totp(&user.email) {
+        Ok(totp) => totp.is_enabled(),
+        Err(RepoError::NotFound) => false,
+        Err(err) => return Err(err.into()),
+    };
+    if totp_enabled {
+        info!(
+            "Refused login of user {} with OpenID provider '{}' who has enabled the second factor",
+            user.email, provider.id
+        );
+        return Err(ParameterError::SecondFactorRequired.into());
+    }
+    Ok(())
+}
+
+/// Logs in a user who has been authenticated by the provider.
+///
+/// Users are identified by their account at the provider. On
+/// the first login a new user is created, unless a user with
+/// the same email address already exists. Existing users are
+/// only linked to their account if the provider is authoritative
+/// for the domain of their email address.
+///
+/// The provider only manages the roles of users that it has
+/// created. Their role is changed if any of their groups is
+/// mapped to a role.
+///
+/// The provider cannot vouch for the second factor. Users who
+/// have enabled it or whose role requires it must log in with
+/// their password instead.
+pub fn login_with_oidc<R: UserGateway + TwoFactorRepo + OidcAccountRepo>(
+    repo: &R,
+    provider: &OidcProvider,
+    identity: OidcIdentity,
+    second_factor_required_roles: &[Role],
+) -> Result<User> {
+    let OidcIdentity {
+        subject,
+        email,
+        groups,
+    } = identity;
+    let mapped_role = provider.map_role(&groups);
+    let account = match repo.get_oidc_account(&provider.id, &subject) {
+        Ok(account) => Some(account),
+        Err(RepoError::NotFound) => None,
+        Err(err) => return Err(err.into()),
+    };
+    let user = match account {
+        Some(account) => {
+            let mut user = repo.get_user_by_email(&account.email)?;
+            check_existing_user(repo, provider, &user)?;
+            let role = if account.provisioned {
+                mapped_role.unwrap_or(user.role)
+            } else {
+                user.role
+            };
+            if second_factor_required_roles.contains(&role) {
+                return Err(ParameterError::SecondFactorEnrollmentRequired.into());
+            }
+            if user.role != role {
+                info!(
+                    "Changing role of user {} from {:?} to {:?} as mapped by OpenID provider '{}'",
+                    user.email, user.role, role, provider.id
+                );
+                user.role = role;
+                repo.update_user(&user)?;
+            }
+            user
+        }
+        None => {
+            let provisioned = match repo.try_get_user_by_email(&email)? {
+                Some(user) => {
+                    if !provider.is_authoritative_for(&email) {
+                        warn!(
+                            "Refused to link user {} to account {} at OpenID provider '{}'",
+                            email, subject, provider.id
+                        );
+                        return Err(ParameterError::UserExists.into());
+                    }
+                    check_existing_user(repo, provider, &user)?;
+                    if second_factor_required_roles.contains(&user.role) {
+                        return Err(ParameterError::SecondFactorEnrollmentRequired.into());
+                    }
+                    if !user.email_confirmed {
+                        repo.update_user(&User {
+                            email_confirmed: true,
+                            ..user
+                        })?;
+                    }
+                    info!(
+                        "Linked user {} to account {} at OpenID provider '{}'",
+                        email, subject, provider.id
+                    );
+                    false
+                }
+                None => {
+                    let role = mapped_role.unwrap_or(provider.default_role);
+                    if second_factor_required_roles.contains(&role) {
+                        return Err(ParameterError::SecondFactorEnrollmentRequired.into());
+                    }
+                    // The password is never revealed and can only be reset
+                    let password = Nonce::new().to_string().parse::<Password>()?;
+                    let user = User {
+                        email: email.clone(),
+                        email_confirmed: true,
+                        password,
+                        role,
+                        disabled: false,
+                        language: None,
+                    };
+                    repo.create_user(&user)?;
+                    info!(
+                        "Created user {} with role {:?} on first login with OpenID provider '{}'",
+                        user.email, user.role, provider.id
+                    );
+                    true
+                }
+            };
+            repo.create_oidc_account(&OidcAccount {
+                provider: provider.id.clone(),
+                subject,
+                email: email.clone(),
+                provisioned,
+                created_at: TimestampMs::now(),
+            })?;
+            repo.get_user_by_email(&email)?
+        }
+    };
+    Ok(user)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{begin_totp_enrollment, confirm_totp_enrollment, tests::MockDb};
+    use super::*;
+    use crate::core::util::totp;
+
+    struct MockIdp(OidcUserInfo);
+
+    impl OidcGateway for MockIdp {
+        fn exchange_code(
+            &self,
+            request: &OidcCodeExchange,
+        ) -> std::result::Result<OidcUserInfo, String> {
+            if request.code == "valid" && request.code_verifier == "verifier" {
+                Ok(self.0.clone())
+            } else {
+                Err("invalid_grant".into())
+            }
+        }
+    }
+
+    fn provider() -> OidcProvider {
+        OidcProvider {
+            id: "partner".into(),
+            name: "Partner".into(),
+            authorization_endpoint: "https://idp.example.com/auth?foo=bar".parse().unwrap(),
+            token_endpoint: "https://idp.example.com/token".parse().unwrap(),
+            userinfo_endpoint: "https://idp.example.com/userinfo".parse().unwrap(),
+            client_id: "ofdb".into(),
+            client_secret: "secret".into(),
+            redirect_uri: "https://ofdb.example.com/login/oidc/partner/callback"
+                .parse()
+                .unwrap(),
+            scopes: vec!["openid".into(), "email".into()],
+            role_claim: "groups".into(),
+            role_mapping: vec![
+                ("ofdb-scouts".into(), Role::Scout),
+                ("ofdb-admins".into(), Role::Admin),
+            ],
+            default_role: Role::User,
+            email_domains: vec![],
+        }
+    }
+
+    fn user_info(subject: &str, email: &str, groups: &[&str]) -> OidcUserInfo {
+        OidcUserInfo {
+            subject: subject.into(),
+            email: Some(email.into()),
+            email_verified: Some(true),
+            groups: groups.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    fn login_with(
+        db: &MockDb,
+        provider: &OidcProvider,
+        idp: &MockIdp,
+        second_factor_required_roles: &[Role],
+    ) -> Result<User> {
+        let identity = authenticate_with_oidc(idp, provider, "valid", "verifier")?;
+        login_with_oidc(db, provider, identity, second_factor_required_roles)
+    }
+
+    fn login(db: &MockDb, idp: &MockIdp) -> Result<User> {
+        login_with(db, &provider(), idp, &[Role::Admin])
+    }
+
+    #[test]
+    fn create_authorization_url() {
+        // Example from RFC 7636, appendix B
+        let url =
+            provider().authorization_url("xyz", "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk");
+        assert_eq!(
+            "https://idp.example.com/auth?foo=bar&response_type=code&client_id=ofdb&redirect_uri=https%3A%2F%2Fofdb.example.com%2Flogin%2Foidc%2Fpartner%2Fcallback&scope=openid+email&state=xyz&code_challenge=E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM&code_challenge_method=S256",
+            url.as_str()
+        );
+        let code_verifier = new_pkce_code_verifier();
+        assert_eq!(43, code_verifier.len());
+        assert_ne!(code_verifier, new_pkce_code_verifier());
+    }
+
+    #[test]
+    fn provision_user_on_first_login() {
+        let db = MockDb::default();
+        let idp = MockIdp(user_info("1", "foo@bar.tld", &["other", "ofdb-scouts"]));
+        assert!(matches!(
+            authenticate_with_oidc(&idp, &provider(), "invalid", "verifier"),
+            Err(Error::Parameter(ParameterError::Unauthorized))
+        ));
+        // The code is bound to the login request
+        assert!(matches!(
+            authenticate_with_oidc(&idp, &provider(), "valid", "other"),
+            Err(Error::Parameter(ParameterError::Unauthorized))
+        ));
+        let user = login(&db, &idp).unwrap();
+        assert_eq!("foo@bar.tld", user.email);
+        assert_eq!(Role::Scout, user.role);
+        assert!(user.email_confirmed);
+        assert_eq!(1, db.all_users().unwrap().len());
+        let account = db.get_oidc_account("partner", "1").unwrap();
+        assert_eq!("foo@bar.tld", account.email);
+        assert!(account.provisioned);
+
+        let idp = MockIdp(user_info(
+            "2",
+            "bar@foo.tld",
+            &["ofdb-scouts", "ofdb-admins"],
+        ));
+        let user = login_with(&db, &provider(), &idp, &[]).unwrap();
+        assert_eq!(Role::Admin, user.role);
+
+        let idp = MockIdp(user_info("3", "baz@foo.tld", &[]));
+        let user = login(&db, &idp).unwrap();
+        assert_eq!(Role::User, user.role);
+
+        // Users are identified by their subject
+        let idp = MockIdp(user_info("1", "changed@bar.tld", &[]));
+        let user = login(&db, &idp).unwrap();
+        assert_eq!("foo@bar.tld", user.email);
+        assert_eq!(3, db.all_users().unwrap().len());
+    }
+
+    #[test]
+    fn update_role_of_provisioned_user() {
+        let db = MockDb::default();
+        let idp = MockIdp(user_info("1", "foo@bar.tld", &["ofdb-scouts"]));
+        login(&db, &idp).unwrap();
+
+        // Keep the role without a matching group
+        let idp = MockIdp(user_info("1", "foo@bar.tld", &["other"]));
+        let user = login(&db, &idp).unwrap();
+        assert_eq!(Role::Scout, user.role);
+
+        let idp = MockIdp(user_info("1", "foo@bar.tld", &["ofdb-admins"]));
+        login_with(&db, &provider(), &idp, &[]).unwrap();
+        assert_eq!(
+            Role::Admin,
+            db.get_user_by_email("foo@bar.tld").unwrap().role
+        );
+    }
+
+    #[test]
+    fn link_existing_users_only_for_authoritative_domains() {
+        let db = MockDb::default();
+        db.create_test_user("admin@bar.tld", Role::Admin);
+        db.create_test_user("scout@bar.tld", Role::Scout);
+
+        let idp = MockIdp(user_info("1", "admin@bar.tld", &["ofdb-admins"]));
+        assert!(matches!(
+            login_with(&db, &provider(), &idp, &[]),
+            Err(Error::Parameter(ParameterError::UserExists))
+        ));
+        assert!(db.oidc_accounts.borrow().is_empty());
+
+        let provider = OidcProvider {
+            email_domains: vec!["Bar.tld".into()],
+            ..provider()
+        };
+        let idp = MockIdp(user_info("2", "scout@bar.tld", &["ofdb-admins"]));
+        let user = login_with(&db, &provider, &idp, &[]).unwrap();
+        assert_eq!("scout@bar.tld", user.email);
+        assert!(!db.get_oidc_account("partner", "2").unwrap().provisioned);
+
+        // The provider doesn't manage the roles of linked users
+        assert_eq!(Role::Scout, user.role);
+        let user = login_with(&db, &provider, &idp, &[]).unwrap();
+        assert_eq!(Role::Scout, user.role);
+    }
+
+    #[test]
+    fn reject_unverified_email() {
+        let db = MockDb::default();
+        let mut info = user_info("1", "foo@bar.tld", &[]);
+        info.email_verified = Some(false);
+        assert!(matches!(
+            login(&db, &MockIdp(info)),
+            Err(Error::Parameter(ParameterError::UnverifiedEmail))
+        ));
+        // The claim must be present
+        let mut info = user_info("1", "foo@bar.tld", &[]);
+        info.email_verified = None;
+        assert!(matches!(
+            login(&db, &MockIdp(info)),
+            Err(Error::Parameter(ParameterError::UnverifiedEmail))
+        ));
+        let mut info = user_info("1", "foo@bar.tld", &[]);
+        info.email = None;
+        assert!(matches!(
+            login(&db, &MockIdp(info)),
+            Err(Error::Parameter(ParameterError::UnverifiedEmail))
+        ));
+        assert!(db.all_users().unwrap().is_empty());
+    }
+
+    #[test]
+    fn reject_users_that_require_a_second_factor() {
+        let db = MockDb::default();
+
+        // Roles that require a second factor are not provisioned
+        let idp = MockIdp(user_info("1", "admin@bar.tld", &["ofdb-admins"]));
+        assert!(matches!(
+            login(&db, &idp),
+            Err(Error::Parameter(
+                ParameterError::SecondFactorEnrollmentRequired
+            ))
+        ));
+        assert!(db.all_users().unwrap().is_empty());
+
+        let idp = MockIdp(user_info("2", "foo@bar.tld", &[]));
+        login(&db, &idp).unwrap();
+        let enrollment = begin_totp_enrollment(&db, "foo@bar.tld", "OpenFairDB").unwrap();
+        let secret = totp::base32_decode(&enrollment.secret).unwrap();
+        let step = totp::time_step(Timestamp::now().into_seconds());
+        confirm_totp_enrollment(&db, "foo@bar.tld", &totp::totp(&secret, step)).unwrap();
+        assert!(matches!(
+            login(&db, &idp),
+            Err(Error::Parameter(ParameterError::SecondFactorRequired))
+        ));
+    }
+}
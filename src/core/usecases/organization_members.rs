@@ -0,0 +1,213 @@
+use crate::core::prelude::*;
+
+/// An organization together with the role of a member.
+#[derive(Debug, Clone)]
+pub struct Membership {
+    pub organization: Organization,
+    pub role: OrganizationRole,
+}
+
+/// Checks if a user is allowed to manage the members of
+/// an organization, i.e. is either an admin or an owner.
+pub fn authorize_organization_owner<R: UserGateway + OrganizationMemberRepo>(
+    repo: &R,
+    org_id: &Id,
+    email: &str,
+) -> Result<()> {
+    let user = repo
+        .try_get_user_by_email(email)?
+        .ok_or(ParameterError::Unauthorized)?;
+    if ofdb_core::user::authorize_role(&user, Role::Admin).is_ok() {
+        return Ok(());
+    }
+    if repo
+        .load_org_memberships_of_user(email)?
+        .into_iter()
+        .any(|m| &m.org_id == org_id && m.role == OrganizationRole::Owner)
+    {
+        return Ok(());
+    }
+    Err(ParameterError::Forbidden.into())
+}
+
+/// Authorizes a logged-in user to act on behalf of an
+/// organization according to the role of the membership.
+pub fn authorize_organization_member<R: OrganizationRepo + OrganizationMemberRepo>(
+    repo: &R,
+    email: &str,
+    org_id: &Id,
+    required_scopes: &[ApiTokenScope],
+) -> Result<Organization> {
+    let member = repo
+        .load_org_memberships_of_user(email)?
+        .into_iter()
+        .find(|m| &m.org_id == org_id)
+        .ok_or(ParameterError::Unauthorized)?;
+    if !member.role.has_scopes(required_scopes) {
+        return Err(ParameterError::Forbidden.into());
+    }
+    Ok(repo.get_org_by_id(&member.org_id)?)
+}
+
+pub fn list_organization_members<R: OrganizationRepo + OrganizationMemberRepo>(
+    repo: &R,
+    org_id: &Id,
+) -> Result<Vec<OrganizationMember>> {
+    let org = repo.get_org_by_id(org_id)?;
+    Ok(repo.load_org_members(&org.id)?)
+}
+
+/// Adds a registered user as a member or changes the role
+/// of an existing member.
+pub fn set_organization_member<R: OrganizationRepo + OrganizationMemberRepo + UserGateway>(
+    repo: &R,
+    org_id: &Id,
+    email: &str,
+    role: &str,
+) -> Result<OrganizationMember> {
+    let org = repo.get_org_by_id(org_id)?;
+    let role = role
+        .parse::<OrganizationRole>()
+        .map_err(|_| ParameterError::OrganizationRole)?;
+    let user = repo
+        .try_get_user_by_email(email)?
+        .ok_or(ParameterError::UserDoesNotExist)?;
+    let member = OrganizationMember {
+        org_id: org.id,
+        email: user.email,
+        role,
+    };
+    repo.set_org_member(&member)?;
+    info!(
+        "User {} is now {} of organization '{}'",
+        member.email, member.role, org.name
+    );
+    Ok(member)
+}
+
+pub fn remove_organization_member<R: OrganizationRepo + OrganizationMemberRepo>(
+    repo: &R,
+    org_id: &Id,
+    email: &str,
+) -> Result<()> {
+    let org = repo.get_org_by_id(org_id)?;
+    repo.remove_org_member(&org.id, email)?;
+    info!(
+        "User {} is no longer a member of organization '{}'",
+        email, org.name
+    );
+    Ok(())
+}
+
+pub fn list_memberships_of_user<R: OrganizationRepo + OrganizationMemberRepo>(
+    repo: &R,
+    email: &str,
+) -> Result<Vec<Membership>> {
+    repo.load_org_memberships_of_user(email)?
+        .into_iter()
+        .map(|m| {
+            Ok(Membership {
+                organization: repo.get_org_by_id(&m.org_id)?,
+                role: m.role,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockDb;
+    use super::*;
+
+    fn setup() -> MockDb {
+        let db = MockDb::default();
+        db.orgs.borrow_mut().push(Organization {
+            id: "org".into(),
+            name: "Org".into(),
+            moderated_tags: vec![],
+        });
+        for (email, role) in &[
+            ("admin@example.com", Role::Admin),
+            ("owner@example.com", Role::User),
+            ("reviewer@example.com", Role::User),
+        ] {
+            db.create_user(&User {
+                email: email.to_string(),
+                email_confirmed: true,
+                password: "secret".parse::<Password>().unwrap(),
+                role: *role,
+                disabled: false,
+                language: None,
+            })
+            .unwrap();
+        }
+        db
+    }
+
+    #[test]
+    fn manage_members() {
+        let db = setup();
+        let org_id = Id::from("org");
+        assert!(matches!(
+            set_organization_member(&db, &org_id, "reviewer@example.com", "admin"),
+            Err(Error::Parameter(ParameterError::OrganizationRole))
+        ));
+        assert!(matches!(
+            set_organization_member(&db, &org_id, "unknown@example.com", "editor"),
+            Err(Error::Parameter(ParameterError::UserDoesNotExist))
+        ));
+        set_organization_member(&db, &org_id, "reviewer@example.com", "editor").unwrap();
+        set_organization_member(&db, &org_id, "reviewer@example.com", "clearance-reviewer")
+            .unwrap();
+        let members = list_organization_members(&db, &org_id).unwrap();
+        assert_eq!(1, members.len());
+        assert_eq!(OrganizationRole::ClearanceReviewer, members[0].role);
+
+        let memberships = list_memberships_of_user(&db, "reviewer@example.com").unwrap();
+        assert_eq!(1, memberships.len());
+        assert_eq!(org_id, memberships[0].organization.id);
+
+        remove_organization_member(&db, &org_id, "reviewer@example.com").unwrap();
+        assert!(list_organization_members(&db, &org_id).unwrap().is_empty());
+        assert!(remove_organization_member(&db, &org_id, "reviewer@example.com").is_err());
+    }
+
+    #[test]
+    fn authorize_members_by_role() {
+        let db = setup();
+        let org_id = Id::from("org");
+        set_organization_member(&db, &org_id, "owner@example.com", "owner").unwrap();
+        set_organization_member(&db, &org_id, "reviewer@example.com", "clearance-reviewer")
+            .unwrap();
+
+        assert!(authorize_organization_owner(&db, &org_id, "admin@example.com").is_ok());
+        assert!(authorize_organization_owner(&db, &org_id, "owner@example.com").is_ok());
+        assert!(matches!(
+            authorize_organization_owner(&db, &org_id, "reviewer@example.com"),
+            Err(Error::Parameter(ParameterError::Forbidden))
+        ));
+
+        let org = authorize_organization_member(
+            &db,
+            "reviewer@example.com",
+            &org_id,
+            &[ApiTokenScope::ClearanceWrite],
+        )
+        .unwrap();
+        assert_eq!(org_id, org.id);
+        assert!(matches!(
+            authorize_organization_member(
+                &db,
+                "reviewer@example.com",
+                &org_id,
+                &[ApiTokenScope::PlacesWrite]
+            ),
+            Err(Error::Parameter(ParameterError::Forbidden))
+        ));
+        // Admins are not implicitly members
+        assert!(matches!(
+            authorize_organization_member(&db, "admin@example.com", &org_id, &[]),
+            Err(Error::Parameter(ParameterError::Unauthorized))
+        ));
+    }
+}
@@ -0,0 +1,340 @@
+use super::create_primary_api_token;
+use crate::core::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct NewOrganization {
+    pub name: String,
+    pub moderated_tags: Vec<ModeratedTag>,
+}
+
+pub type UpdateOrganization = NewOrganization;
+
+/// A newly created organization together with the secret
+/// of its primary API token.
+///
+/// The secret is only revealed once after creation.
+#[derive(Debug, Clone)]
+pub struct CreatedOrganization {
+    pub organization: Organization,
+    pub api_token: String,
+}
+
+fn validate_organization<R: OrganizationRepo>(
+    repo: &R,
+    org_id: &Id,
+    name: String,
+    moderated_tags: Vec<ModeratedTag>,
+) -> Result<(String, Vec<ModeratedTag>)> {
+    let name = name.trim().to_owned();
+    if name.is_empty() {
+        return Err(ParameterError::OrganizationName.into());
+    }
+    let mut unique_tags: Vec<ModeratedTag> = Vec::with_capacity(moderated_tags.len());
+    for mut mod_tag in moderated_tags {
+        let label = mod_tag.label.trim().to_lowercase();
+        if label.is_empty()
+            || label.contains(char::is_whitespace)
+            || label.contains('#')
+            || unique_tags.iter().any(|t| t.label == label)
+        {
+            return Err(ParameterError::ModeratedTagLabel.into());
+        }
+        if mod_tag.require_clearance {
+            if let Some(clearance_org_id) = repo.map_tag_to_clearance_org_id(&label)? {
+                if &clearance_org_id != org_id {
+                    // Only a single organization is allowed to
+                    // require clearance for a tag
+                    return Err(ParameterError::ModeratedTag.into());
+                }
+            }
+        }
+        mod_tag.label = label;
+        unique_tags.push(mod_tag);
+    }
+    Ok((name, unique_tags))
+}
+
+fn clearance_tags(org: &Organization) -> Vec<&str> {
+    org.moderated_tags
+        .iter()
+        .filter(|t| t.require_clearance)
+        .map(|t| t.label.as_str())
+        .collect()
+}
+
+/// Creates a new organization.
+///
+/// A primary API token with all permissions is issued for
+/// accessing the API on behalf of the organization. Only
+/// its hash is stored.
+pub fn create_organization<R: OrganizationRepo + ApiTokenRepo>(
+    repo: &mut R,
+    new_org: NewOrganization,
+) -> Result<CreatedOrganization> {
+    let NewOrganization {
+        name,
+        moderated_tags,
+    } = new_org;
+    let id = Id::new();
+    let (name, moderated_tags) = validate_organization(repo, &id, name, moderated_tags)?;
+    let org = Organization {
+        id,
+        name,
+        moderated_tags,
+    };
+    repo.create_org(org.clone())?;
+    info!("Created organization {} '{}'", org.id, org.name);
+    let api_token = Nonce::new().to_string();
+    create_primary_api_token(repo, &org.id, &api_token)?;
+    Ok(CreatedOrganization {
+        organization: org,
+        api_token,
+    })
+}
+
+pub fn list_organizations<R: OrganizationRepo>(repo: &R) -> Result<Vec<Organization>> {
+    Ok(repo.all_orgs()?)
+}
+
+pub fn get_organization<R: OrganizationRepo>(repo: &R, org_id: &Id) -> Result<Organization> {
+    Ok(repo.get_org_by_id(org_id)?)
+}
+
+/// Updates the name and the moderated tags of an organization.
+///
+/// Places that already carry a tag that now requires clearance
+/// become pending until they have been cleared. Pending clearances
+/// that are no longer covered by any tag that requires clearance
+/// are discarded.
+pub fn update_organization<R: OrganizationRepo + PlaceRepo + PlaceClearanceRepo>(
+    repo: &R,
+    org_id: &Id,
+    update: UpdateOrganization,
+) -> Result<Organization> {
+    let UpdateOrganization {
+        name,
+        moderated_tags,
+    } = update;
+    let old_org = repo.get_org_by_id(org_id)?;
+    let (name, moderated_tags) = validate_organization(repo, &old_org.id, name, moderated_tags)?;
+    let org = Organization {
+        name,
+        moderated_tags,
+        ..old_org.clone()
+    };
+    repo.update_org(&org)?;
+    info!("Updated organization {} '{}'", org.id, org.name);
+    let old_clearance_tags = clearance_tags(&old_org);
+    let new_clearance_tags = clearance_tags(&org);
+    let added_clearance_tags: Vec<_> = new_clearance_tags
+        .iter()
+        .copied()
+        .filter(|tag| !old_clearance_tags.contains(tag))
+        .collect();
+    if !added_clearance_tags.is_empty() {
+        add_pending_clearances_for_tagged_places(repo, &org, &added_clearance_tags)?;
+    }
+    if old_clearance_tags
+        .iter()
+        .any(|tag| !new_clearance_tags.contains(tag))
+    {
+        discard_irrelevant_pending_clearances(repo, &org)?;
+    }
+    Ok(org)
+}
+
+pub fn delete_organization<R: OrganizationRepo>(repo: &R, org_id: &Id) -> Result<()> {
+    let org = repo.get_org_by_id(org_id)?;
+    repo.delete_org(&org.id)?;
+    info!("Deleted organization {} '{}'", org.id, org.name);
+    Ok(())
+}
+
+/// Adds pending clearances for all existing places that are
+/// tagged with any of the given tags. These places have never
+/// been cleared by the organization.
+fn add_pending_clearances_for_tagged_places<R: PlaceRepo + PlaceClearanceRepo>(
+    repo: &R,
+    org: &Organization,
+    clearance_tags: &[&str],
+) -> Result<usize> {
+    let org_ids = [org.id.clone()];
+    let mut count = 0;
+    for (place, status) in repo.all_places()? {
+        if !status.exists()
+            || !place
+                .tags
+                .iter()
+                .any(|tag| clearance_tags.contains(&tag.as_str()))
+        {
+            continue;
+        }
+        let pending_clearance = PendingClearanceForPlace {
+            place_id: place.id,
+            created_at: place.created.at,
+            last_cleared_revision: None,
+        };
+        count += repo.add_pending_clearance_for_places(&org_ids, &pending_clearance)?;
+    }
+    if count > 0 {
+        info!(
+            "Added {} pending clearance(s) for places of organization '{}' after its moderated tags have changed",
+            count, org.name
+        );
+    }
+    Ok(count)
+}
+
+/// Implicitly clears all pending clearances of places that
+/// neither in their current nor in their last cleared revision
+/// are tagged with any of the tags that require clearance.
+fn discard_irrelevant_pending_clearances<R: PlaceRepo + PlaceClearanceRepo>(
+    repo: &R,
+    org: &Organization,
+) -> Result<usize> {
+    let clearance_tags = clearance_tags(org);
+    let is_relevant = |tags: &[String]| {
+        tags.iter()
+            .any(|tag| clearance_tags.contains(&tag.as_str()))
+    };
+    let pending_clearances =
+        repo.list_pending_clearances_for_places(&org.id, &Pagination::default())?;
+    let mut irrelevant_clearances = Vec::with_capacity(pending_clearances.len());
+    for PendingClearanceForPlace {
+        place_id,
+        last_cleared_revision,
+        ..
+    } in pending_clearances
+    {
+        if !clearance_tags.is_empty() {
+            let (place, _) = repo.get_place(place_id.as_str())?;
+            if is_relevant(&place.tags) {
+                continue;
+            }
+            if let Some(last_cleared_revision) = last_cleared_revision {
+                let (last_cleared_place, _) =
+                    repo.load_place_revision(place_id.as_str(), last_cleared_revision)?;
+                if is_relevant(&last_cleared_place.tags) {
+                    continue;
+                }
+            }
+        }
+        irrelevant_clearances.push(ClearanceForPlace {
+            place_id,
+            cleared_revision: None,
+        });
+    }
+    if irrelevant_clearances.is_empty() {
+        return Ok(0);
+    }
+    let count = repo.update_pending_clearances_for_places(&org.id, &irrelevant_clearances)?;
+    repo.cleanup_pending_clearances_for_places(&org.id)?;
+    info!(
+        "Discarded {} pending clearance(s) for places of organization '{}' after its moderated tags have changed",
+        count, org.name
+    );
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockDb;
+    use super::*;
+    use crate::core::util::hash_token;
+
+    fn moderated_tag(label: &str, require_clearance: bool) -> ModeratedTag {
+        ModeratedTag {
+            label: label.into(),
+            allow_add: true,
+            allow_remove: true,
+            require_clearance,
+        }
+    }
+
+    #[test]
+    fn create_and_update_organization() {
+        let mut db = MockDb::default();
+        let CreatedOrganization {
+            organization: org,
+            api_token,
+        } = create_organization(
+            &mut db,
+            NewOrganization {
+                name: " Foo ".into(),
+                moderated_tags: vec![moderated_tag(" Bar ", true)],
+            },
+        )
+        .unwrap();
+        assert_eq!("Foo", org.name);
+        assert_eq!("bar", org.moderated_tags[0].label);
+        let primary_api_token = db.get_api_token_by_hash(&hash_token(&api_token)).unwrap();
+        assert_eq!(org.id, primary_api_token.org_id);
+        assert_eq!(ApiTokenScope::all(), primary_api_token.scopes);
+        assert_eq!(vec![org.clone()], list_organizations(&db).unwrap());
+
+        let updated = update_organization(
+            &db,
+            &org.id,
+            UpdateOrganization {
+                name: "Baz".into(),
+                moderated_tags: vec![moderated_tag("bar", false), moderated_tag("qux", true)],
+            },
+        )
+        .unwrap();
+        assert_eq!(org.id, updated.id);
+        assert_eq!(updated, get_organization(&db, &org.id).unwrap());
+
+        delete_organization(&db, &org.id).unwrap();
+        assert!(list_organizations(&db).unwrap().is_empty());
+        assert!(delete_organization(&db, &org.id).is_err());
+    }
+
+    #[test]
+    fn reject_invalid_organizations() {
+        let mut db = MockDb::default();
+        assert!(matches!(
+            create_organization(
+                &mut db,
+                NewOrganization {
+                    name: " ".into(),
+                    moderated_tags: vec![],
+                },
+            ),
+            Err(Error::Parameter(ParameterError::OrganizationName))
+        ));
+        for labels in &[vec![""], vec!["foo bar"], vec!["#foo"], vec!["foo", "Foo"]] {
+            assert!(matches!(
+                create_organization(
+                    &mut db,
+                    NewOrganization {
+                        name: "Foo".into(),
+                        moderated_tags: labels.iter().map(|l| moderated_tag(l, false)).collect(),
+                    },
+                ),
+                Err(Error::Parameter(ParameterError::ModeratedTagLabel))
+            ));
+        }
+        assert!(db.orgs.borrow().is_empty());
+    }
+
+    #[test]
+    fn reject_tags_that_already_require_clearance() {
+        let mut db = MockDb::default();
+        let new_org = |label: &str, require_clearance| NewOrganization {
+            name: "Foo".into(),
+            moderated_tags: vec![moderated_tag(label, require_clearance)],
+        };
+        create_organization(&mut db, new_org("foo", true)).unwrap();
+        assert!(matches!(
+            create_organization(&mut db, new_org("foo", true)),
+            Err(Error::Parameter(ParameterError::ModeratedTag))
+        ));
+        let other = create_organization(&mut db, new_org("foo", false))
+            .unwrap()
+            .organization;
+        assert!(matches!(
+            update_organization(&db, &other.id, new_org("foo", true)),
+            Err(Error::Parameter(ParameterError::ModeratedTag))
+        ));
+    }
+}
@@ -23,6 +23,12 @@ impl Storable {
     pub fn comment_id(&self) -> &str {
         &self.3.id.as_ref()
     }
+    pub fn rating(&self) -> &Rating {
+        &self.2
+    }
+    pub fn comment(&self) -> &Comment {
+        &self.3
+    }
 }
 
 pub fn prepare_new_rating<D: Db>(db: &D, r: NewPlaceRating) -> Result<Storable> {
@@ -79,12 +79,9 @@ pub fn clear_search_results<D: Db>(
     Ok(cleared_results)
 }
 
-pub fn search<D: Db>(
-    db: &D,
-    index: &dyn PlaceIndex,
-    req: SearchRequest,
-    limit: usize,
-) -> Result<(Vec<IndexedPlace>, Vec<IndexedPlace>)> {
+/// Translates a search request into a query for all places
+/// that are visible within the requested bounding box.
+pub(crate) fn visible_places_query(req: SearchRequest) -> IndexQuery {
     let SearchRequest {
         bbox: visible_bbox,
         ids,
@@ -117,7 +114,7 @@ pub fn search<D: Db>(
         .map(tag::split_text_into_tags)
         .unwrap_or_default();
 
-    let visible_places_query = IndexQuery {
+    IndexQuery {
         include_bbox: Some(visible_bbox),
         exclude_bbox: None,
         categories,
@@ -127,44 +124,66 @@ pub fn search<D: Db>(
         text,
         status: Some(status),
         ..Default::default()
-    };
+    }
+}
+
+/// Queries the index and replaces the results with their
+/// last cleared revision on behalf of the organization
+/// that owns the given tag.
+pub(crate) fn query_cleared_places<D: Db>(
+    db: &D,
+    index: &dyn PlaceIndex,
+    query: &IndexQuery,
+    org_tag: Option<&str>,
+    limit: usize,
+) -> Result<Vec<IndexedPlace>> {
+    let places = index.query_places(query, limit).map_err(RepoError::Other)?;
+    if let Some(org_tag) = org_tag {
+        if let Some(org_id) = db.map_tag_to_clearance_org_id(org_tag)? {
+            return clear_search_results(db, &org_id, org_tag, places);
+        }
+    }
+    Ok(places)
+}
+
+pub fn search<D: Db>(
+    db: &D,
+    index: &dyn PlaceIndex,
+    req: SearchRequest,
+    limit: usize,
+) -> Result<(Vec<IndexedPlace>, Vec<IndexedPlace>)> {
+    let visible_bbox = req.bbox;
+    let org_tag = req.org_tag;
+    let visible_places_query = visible_places_query(req);
 
     // 1st query: Search for visible results only
     // This is required to reliably retrieve all available results!
     // See also: https://github.com/slowtec/openfairdb/issues/183
-    let mut visible_places = index
-        .query_places(&visible_places_query, limit)
-        .map_err(RepoError::Other)?;
+    let visible_places = query_cleared_places(db, index, &visible_places_query, org_tag, limit)?;
     debug_assert!(visible_places
         .iter()
         .all(|e| visible_bbox.contains_point(e.pos)));
-    if let Some(org_tag) = org_tag {
-        if let Some(org_id) = db.map_tag_to_clearance_org_id(org_tag)? {
-            visible_places = clear_search_results(db, &org_id, org_tag, visible_places)?;
-        }
-    }
 
     // 2nd query: Search for remaining invisible results
-    let mut invisible_places = if visible_places.len() < limit {
+    let invisible_places = if visible_places.len() < limit {
         let invisible_places_query = IndexQuery {
             include_bbox: Some(bbox::extend_bbox(&visible_bbox)),
             exclude_bbox: visible_places_query.include_bbox,
             ..visible_places_query
         };
-        index
-            .query_places(&invisible_places_query, limit - visible_places.len())
-            .map_err(RepoError::Other)?
+        query_cleared_places(
+            db,
+            index,
+            &invisible_places_query,
+            org_tag,
+            limit - visible_places.len(),
+        )?
     } else {
         vec![]
     };
     debug_assert!(!invisible_places
         .iter()
         .any(|e| visible_bbox.contains_point(e.pos)));
-    if let Some(org_tag) = org_tag {
-        if let Some(org_id) = db.map_tag_to_clearance_org_id(org_tag)? {
-            invisible_places = clear_search_results(db, &org_id, &org_tag, invisible_places)?;
-        }
-    }
 
     Ok((visible_places, invisible_places))
 }
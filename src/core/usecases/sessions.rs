@@ -0,0 +1,182 @@
+use crate::core::{prelude::*, util::hash_token};
+
+use std::time::Duration;
+
+/// A login session that can be extended with the
+/// contained single-use refresh token.
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub email: String,
+    pub id: Id,
+    pub refresh_token: String,
+}
+
+pub(super) fn timestamp_after(from: TimestampMs, lifetime: Duration) -> TimestampMs {
+    TimestampMs::from_inner(from.into_inner() + lifetime.as_millis() as i64)
+}
+
+fn create_refresh_token<R: SessionTokenRepo>(
+    repo: &R,
+    email: String,
+    session_id: Id,
+    lifetime: Duration,
+) -> Result<Session> {
+    let refresh_token = Nonce::new().to_string();
+    let created_at = TimestampMs::now();
+    let token = RefreshToken {
+        token_hash: hash_token(&refresh_token),
+        session_id,
+        email,
+        created_at,
+        expires_at: timestamp_after(created_at, lifetime),
+        used_at: None,
+    };
+    repo.create_refresh_token(&token)?;
+    let RefreshToken {
+        email, session_id, ..
+    } = token;
+    Ok(Session {
+        email,
+        id: session_id,
+        refresh_token,
+    })
+}
+
+/// Starts a new session after a successful login.
+pub fn start_session<R: SessionTokenRepo>(
+    repo: &R,
+    email: &str,
+    refresh_token_lifetime: Duration,
+) -> Result<Session> {
+    create_refresh_token(repo, email.to_owned(), Id::new(), refresh_token_lifetime)
+}
+
+/// Exchanges a refresh token for a new one.
+///
+/// Each refresh token can only be used once. Reusing a
+/// refresh token indicates that it has been stolen and
+/// ends the whole session, including all access tokens
+/// that have been issued for it.
+pub fn refresh_session<R: SessionTokenRepo>(
+    repo: &R,
+    refresh_token: &str,
+    refresh_token_lifetime: Duration,
+) -> Result<Session> {
+    let token_hash = hash_token(refresh_token);
+    let token = repo
+        .get_refresh_token(&token_hash)
+        .map_err(|err| match err {
+            RepoError::NotFound => Error::Parameter(ParameterError::Unauthorized),
+            err => err.into(),
+        })?;
+    let now = TimestampMs::now();
+    if token.expires_at < now {
+        return Err(Error::Parameter(ParameterError::TokenExpired));
+    }
+    if !repo.use_refresh_token(&token_hash, now)? {
+        warn!(
+            "Refresh token of session {} has been reused: Ending session",
+            token.session_id
+        );
+        repo.delete_refresh_tokens_of_session(&token.session_id)?;
+        return Err(Error::Parameter(ParameterError::Unauthorized));
+    }
+    create_refresh_token(repo, token.email, token.session_id, refresh_token_lifetime)
+}
+
+/// Ends a session on logout by revoking the current
+/// access token and all refresh tokens.
+pub fn end_session<R: SessionTokenRepo>(
+    repo: &R,
+    session_id: &Id,
+    access_token_id: &str,
+    access_token_expires_at: TimestampMs,
+) -> Result<()> {
+    repo.revoke_access_token(access_token_id, access_token_expires_at)?;
+    repo.delete_refresh_tokens_of_session(session_id)?;
+    Ok(())
+}
+
+/// Access tokens are revoked when ending the session. They
+/// are also revoked implicitly when all sessions of the user
+/// have been ended, e.g. after changing the email address.
+pub fn is_access_token_revoked<R: SessionTokenRepo>(
+    repo: &R,
+    access_token_id: &str,
+    session_id: &Id,
+    email: &str,
+) -> Result<bool> {
+    Ok(repo.is_access_token_revoked(access_token_id)?
+        || !repo.is_session_of_user(session_id, email)?)
+}
+
+pub fn delete_expired_session_tokens<R: SessionTokenRepo>(repo: &R) -> Result<usize> {
+    let now = TimestampMs::now();
+    Ok(
+        repo.delete_expired_refresh_tokens(now)?
+            + repo.delete_expired_revoked_access_tokens(now)?,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockDb;
+    use super::*;
+
+    const LIFETIME: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn rotate_refresh_tokens() {
+        let db = MockDb::default();
+        let session = start_session(&db, "foo@bar.tld", LIFETIME).unwrap();
+        let refreshed = refresh_session(&db, &session.refresh_token, LIFETIME).unwrap();
+        assert_eq!("foo@bar.tld", refreshed.email);
+        assert_eq!(session.id, refreshed.id);
+        assert_ne!(session.refresh_token, refreshed.refresh_token);
+        assert!(db
+            .refresh_tokens
+            .borrow()
+            .iter()
+            .all(|t| t.token_hash != session.refresh_token
+                && t.token_hash != refreshed.refresh_token));
+
+        assert!(!is_access_token_revoked(&db, "jti", &session.id, "foo@bar.tld").unwrap());
+
+        // Reusing a refresh token ends the session
+        assert!(refresh_session(&db, &session.refresh_token, LIFETIME).is_err());
+        assert!(db.refresh_tokens.borrow().is_empty());
+        assert!(is_access_token_revoked(&db, "jti", &session.id, "foo@bar.tld").unwrap());
+        assert!(refresh_session(&db, &refreshed.refresh_token, LIFETIME).is_err());
+    }
+
+    #[test]
+    fn refresh_with_unknown_or_expired_token() {
+        let db = MockDb::default();
+        assert!(matches!(
+            refresh_session(&db, "unknown", LIFETIME),
+            Err(Error::Parameter(ParameterError::Unauthorized))
+        ));
+        let session = start_session(&db, "foo@bar.tld", Duration::from_secs(0)).unwrap();
+        db.refresh_tokens.borrow_mut()[0].expires_at = TimestampMs::from_inner(0);
+        assert!(matches!(
+            refresh_session(&db, &session.refresh_token, LIFETIME),
+            Err(Error::Parameter(ParameterError::TokenExpired))
+        ));
+        assert_eq!(1, delete_expired_session_tokens(&db).unwrap());
+    }
+
+    #[test]
+    fn end_session_revokes_tokens() {
+        let db = MockDb::default();
+        let session = start_session(&db, "foo@bar.tld", LIFETIME).unwrap();
+        assert!(!is_access_token_revoked(&db, "jti", &session.id, "foo@bar.tld").unwrap());
+        // The session belongs to another user
+        assert!(is_access_token_revoked(&db, "jti", &session.id, "bar@foo.tld").unwrap());
+        let expires_at = timestamp_after(TimestampMs::now(), LIFETIME);
+        end_session(&db, &session.id, "jti", expires_at).unwrap();
+        end_session(&db, &session.id, "jti", expires_at).unwrap();
+        assert!(is_access_token_revoked(&db, "jti", &session.id, "foo@bar.tld").unwrap());
+        assert_eq!(1, db.revoked_access_tokens.borrow().len());
+        assert!(refresh_session(&db, &session.refresh_token, LIFETIME).is_err());
+    }
+}
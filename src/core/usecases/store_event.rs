@@ -44,7 +44,7 @@ pub struct Storable(Event);
 
 pub fn import_new_event<D: Db>(
     db: &D,
-    token: Option<&str>,
+    org: Option<&Organization>,
     e: NewEvent,
     mode: NewEventMode,
 ) -> Result<Storable> {
@@ -71,17 +71,6 @@ pub fn import_new_event<D: Db>(
         image_link_url,
         ..
     } = e;
-    let org = token
-        .map(|t| {
-            db.get_org_by_api_token(t).map_err(|e| {
-                log::warn!("Unknown or invalid API token: {}", t);
-                match e {
-                    RepoError::NotFound => Error::Parameter(ParameterError::Unauthorized),
-                    _ => Error::Repo(e),
-                }
-            })
-        })
-        .transpose()?;
     let mut new_tags = super::prepare_tag_list(tags.unwrap_or_default().iter().map(String::as_str));
     let _clearance_org_ids = if let Some(org) = org {
         // Implicitly add missing owned tags to prevent events with
@@ -110,7 +99,7 @@ pub fn import_new_event<D: Db>(
                 }
                 new_tags.sort_unstable();
                 new_tags.dedup();
-                super::authorize_editing_of_tagged_entry(db, &[], &new_tags, Some(&org))?
+                super::authorize_editing_of_tagged_entry(db, &[], &new_tags, Some(org))?
             }
             NewEventMode::Update(id) => {
                 let old_event = db.get_event(id)?;
@@ -301,6 +290,7 @@ pub fn store_created_event<D: Db>(db: &D, storable: Storable) -> Result<Event> {
         db.create_tag_if_it_does_not_exist(&Tag { id: t.clone() })?;
     }
     db.create_event(event.clone())?;
+    super::enqueue_event_webhooks(db, &event, ChangeType::Created)?;
     Ok(event)
 }
 
@@ -311,6 +301,7 @@ pub fn store_updated_event<D: Db>(db: &D, storable: Storable) -> Result<Event> {
         db.create_tag_if_it_does_not_exist(&Tag { id: t.clone() })?;
     }
     db.update_event(&event)?;
+    super::enqueue_event_webhooks(db, &event, ChangeType::Updated)?;
     Ok(event)
 }
 
@@ -320,8 +311,8 @@ mod tests {
     use super::super::tests::MockDb;
     use super::*;
 
-    fn create_new_event<D: Db>(db: &D, token: Option<&str>, e: NewEvent) -> Result<Event> {
-        let s = import_new_event(db, token, e, NewEventMode::Create)?;
+    fn create_new_event<D: Db>(db: &D, org: Option<&Organization>, e: NewEvent) -> Result<Event> {
+        let s = import_new_event(db, org, e, NewEventMode::Create)?;
         store_created_event(db, s)
     }
 
@@ -443,6 +434,8 @@ mod tests {
                 email_confirmed: true,
                 password: "secret".parse::<Password>().unwrap(),
                 role: Role::User,
+                disabled: false,
+                language: None,
             })
             .unwrap();
         let users = mock_db.all_users().unwrap();
@@ -0,0 +1,333 @@
+use super::bbox_subscriptions_by_coordinate;
+use crate::core::prelude::*;
+
+const DIGEST_DELIVERY_MODES: [SubscriptionDeliveryMode; 2] = [
+    SubscriptionDeliveryMode::Daily,
+    SubscriptionDeliveryMode::Weekly,
+];
+
+/// Queues a change for all users that receive digests.
+///
+/// Users that are notified immediately by one of their
+/// subscriptions don't receive the same change again
+/// within a digest.
+pub fn queue_subscription_notifications(
+    db: &dyn Db,
+    pos: MapPoint,
+    change: &ObservedChange,
+    entity_id: &Id,
+    title: &str,
+) -> Result<usize> {
+    let subscriptions = bbox_subscriptions_by_coordinate(db, pos, change)?;
+    let notified_immediately: Vec<_> = subscriptions
+        .iter()
+        .filter(|s| s.delivery_mode == SubscriptionDeliveryMode::Immediate)
+        .map(|s| s.user_email.as_str())
+        .collect();
+    let created_at = TimestampMs::now();
+    let mut notifications: Vec<SubscriptionNotification> = Vec::new();
+    for s in &subscriptions {
+        if s.delivery_mode == SubscriptionDeliveryMode::Immediate
+            || notified_immediately.contains(&s.user_email.as_str())
+        {
+            continue;
+        }
+        if notifications
+            .iter()
+            .any(|n| n.user_email == s.user_email && n.delivery_mode == s.delivery_mode)
+        {
+            continue;
+        }
+        notifications.push(SubscriptionNotification {
+            user_email: s.user_email.clone(),
+            subscription_id: Some(s.id.clone()),
+            delivery_mode: s.delivery_mode,
+            created_at,
+            entity_kind: change.entity_kind,
+            change_type: change.change_type,
+            entity_id: entity_id.clone(),
+            title: title.to_owned(),
+        });
+    }
+    if notifications.is_empty() {
+        return Ok(0);
+    }
+    Ok(db.add_subscription_notifications(&notifications)?)
+}
+
+/// Collects the queued notifications of each user and delivery
+/// mode into a single digest with one section per subscription.
+///
+/// A digest is due if its oldest notification has been waiting
+/// for at least the digest period.
+pub fn due_subscription_digests(db: &dyn Db, now: TimestampMs) -> Result<Vec<SubscriptionDigest>> {
+    let mut digests = Vec::new();
+    for delivery_mode in DIGEST_DELIVERY_MODES.iter().copied() {
+        let period = delivery_mode
+            .digest_period()
+            .expect("digest period")
+            .as_millis() as i64;
+        let due_until = TimestampMs::from_inner(now.into_inner() - period);
+        let mut user_digests: Vec<SubscriptionDigest> = Vec::new();
+        // Ordered by user and subscription
+        for n in db.load_subscription_notifications(delivery_mode)? {
+            if user_digests.last().map(|d| &d.user_email) != Some(&n.user_email) {
+                user_digests.push(SubscriptionDigest {
+                    user_email: n.user_email.clone(),
+                    delivery_mode,
+                    sections: vec![],
+                });
+            }
+            let digest = user_digests.last_mut().expect("digest");
+            match digest.sections.last_mut() {
+                Some(section) if section.subscription_id == n.subscription_id => {
+                    section.notifications.push(n);
+                }
+                _ => digest.sections.push(SubscriptionDigestSection {
+                    subscription_id: n.subscription_id.clone(),
+                    subscription_name: String::new(),
+                    notifications: vec![n],
+                }),
+            }
+        }
+        for mut digest in user_digests {
+            let oldest_created_at = digest
+                .sections
+                .iter()
+                .filter_map(|s| s.notifications.first())
+                .map(|n| n.created_at)
+                .min();
+            if !matches!(oldest_created_at, Some(created_at) if created_at <= due_until) {
+                continue;
+            }
+            let subscriptions = db.all_bbox_subscriptions_by_email(&digest.user_email)?;
+            for section in &mut digest.sections {
+                if let Some(s) = subscriptions
+                    .iter()
+                    .find(|s| Some(&s.id) == section.subscription_id.as_ref())
+                {
+                    section.subscription_name = s.name.clone();
+                }
+            }
+            digests.push(digest);
+        }
+    }
+    Ok(digests)
+}
+
+/// Removes all notifications of a digest after it has been sent.
+///
+/// Notifications that have been queued in the meantime are kept
+/// for the next digest.
+pub fn delete_delivered_subscription_digest<R: SubscriptionNotificationRepo>(
+    repo: &R,
+    digest: &SubscriptionDigest,
+) -> Result<usize> {
+    let mut count = 0;
+    for section in &digest.sections {
+        let created_until = match section.notifications.last() {
+            Some(n) => n.created_at,
+            None => continue,
+        };
+        count += repo.delete_subscription_notifications(
+            &digest.user_email,
+            digest.delivery_mode,
+            section.subscription_id.as_ref(),
+            created_until,
+        )?;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{tests::MockDb, *};
+    use super::*;
+
+    fn subscribe(db: &MockDb, email: &str, delivery_mode: &str) {
+        db.create_test_user(email, Role::User);
+        create_bbox_subscription(
+            db,
+            email,
+            NewBboxSubscription {
+                name: delivery_mode.into(),
+                bbox: MapBbox::new(
+                    MapPoint::from_lat_lng_deg(0.0, 0.0),
+                    MapPoint::from_lat_lng_deg(10.0, 10.0),
+                ),
+                tags: vec![],
+                categories: vec![],
+                entity_kind: None,
+                change_types: vec![],
+                delivery_mode: Some(delivery_mode.into()),
+            },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn queue_changes_for_digests() {
+        let db = MockDb::default();
+        subscribe(&db, "a@example.com", "immediate");
+        subscribe(&db, "b@example.com", "daily");
+        subscribe(&db, "c@example.com", "weekly");
+
+        let change = ObservedChange {
+            entity_kind: SubscriptionEntityKind::Place,
+            change_type: SubscriptionChangeType::Created,
+            tags: &[],
+        };
+        let pos = MapPoint::from_lat_lng_deg(5.0, 5.0);
+        assert_eq!(
+            2,
+            queue_subscription_notifications(&db, pos, &change, &"foo".into(), "Foo").unwrap()
+        );
+        assert_eq!(
+            0,
+            queue_subscription_notifications(
+                &db,
+                MapPoint::from_lat_lng_deg(20.0, 20.0),
+                &change,
+                &"bar".into(),
+                "Bar"
+            )
+            .unwrap()
+        );
+
+        // Nothing is due yet
+        let now = TimestampMs::now();
+        assert!(due_subscription_digests(&db, now).unwrap().is_empty());
+
+        let tomorrow = TimestampMs::from_inner(now.into_inner() + 24 * 60 * 60 * 1000);
+        let digests = due_subscription_digests(&db, tomorrow).unwrap();
+        assert_eq!(1, digests.len());
+        assert_eq!("b@example.com", digests[0].user_email);
+        assert_eq!(SubscriptionDeliveryMode::Daily, digests[0].delivery_mode);
+        assert_eq!("daily", digests[0].sections[0].subscription_name);
+        assert_eq!("Foo", digests[0].sections[0].notifications[0].title);
+
+        assert_eq!(
+            1,
+            delete_delivered_subscription_digest(&db, &digests[0]).unwrap()
+        );
+        assert!(due_subscription_digests(&db, tomorrow).unwrap().is_empty());
+
+        let next_week = TimestampMs::from_inner(now.into_inner() + 7 * 24 * 60 * 60 * 1000);
+        let digests = due_subscription_digests(&db, next_week).unwrap();
+        assert_eq!(1, digests.len());
+        assert_eq!("c@example.com", digests[0].user_email);
+    }
+
+    #[test]
+    fn skip_digest_if_notified_immediately() {
+        let db = MockDb::default();
+        subscribe(&db, "a@example.com", "daily");
+        create_bbox_subscription(
+            &db,
+            "a@example.com",
+            NewBboxSubscription {
+                name: "Home".into(),
+                bbox: MapBbox::new(
+                    MapPoint::from_lat_lng_deg(4.0, 4.0),
+                    MapPoint::from_lat_lng_deg(6.0, 6.0),
+                ),
+                tags: vec![],
+                categories: vec![],
+                entity_kind: None,
+                change_types: vec![],
+                delivery_mode: None,
+            },
+        )
+        .unwrap();
+        let change = ObservedChange {
+            entity_kind: SubscriptionEntityKind::Event,
+            change_type: SubscriptionChangeType::Updated,
+            tags: &[],
+        };
+        assert_eq!(
+            0,
+            queue_subscription_notifications(
+                &db,
+                MapPoint::from_lat_lng_deg(5.0, 5.0),
+                &change,
+                &"foo".into(),
+                "Foo"
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            1,
+            queue_subscription_notifications(
+                &db,
+                MapPoint::from_lat_lng_deg(8.0, 8.0),
+                &change,
+                &"bar".into(),
+                "Bar"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn single_digest_with_a_section_for_each_subscription() {
+        let db = MockDb::default();
+        subscribe(&db, "a@example.com", "daily");
+        create_bbox_subscription(
+            &db,
+            "a@example.com",
+            NewBboxSubscription {
+                name: "Far away".into(),
+                bbox: MapBbox::new(
+                    MapPoint::from_lat_lng_deg(20.0, 20.0),
+                    MapPoint::from_lat_lng_deg(30.0, 30.0),
+                ),
+                tags: vec![],
+                categories: vec![],
+                entity_kind: None,
+                change_types: vec![],
+                delivery_mode: Some("daily".into()),
+            },
+        )
+        .unwrap();
+        let change = ObservedChange {
+            entity_kind: SubscriptionEntityKind::Place,
+            change_type: SubscriptionChangeType::Updated,
+            tags: &[],
+        };
+        for (pos, id) in &[(5.0, "foo"), (25.0, "bar")] {
+            queue_subscription_notifications(
+                &db,
+                MapPoint::from_lat_lng_deg(*pos, *pos),
+                &change,
+                &(*id).into(),
+                id,
+            )
+            .unwrap();
+        }
+
+        let now = TimestampMs::now();
+        let tomorrow = TimestampMs::from_inner(now.into_inner() + 24 * 60 * 60 * 1000);
+        let digests = due_subscription_digests(&db, tomorrow).unwrap();
+        assert_eq!(1, digests.len());
+        let sections = &digests[0].sections;
+        assert_eq!(2, sections.len());
+        assert_ne!(sections[0].subscription_id, sections[1].subscription_id);
+        assert!(sections.iter().all(|s| s.notifications.len() == 1));
+        assert_eq!(2, digests[0].notification_count());
+        let far_away = sections
+            .iter()
+            .find(|s| s.subscription_name == "Far away")
+            .and_then(|s| s.subscription_id.clone())
+            .unwrap();
+
+        // Pending changes of deleted subscriptions are dropped
+        delete_bbox_subscription(&db, "a@example.com", &far_away).unwrap();
+        let digests = due_subscription_digests(&db, tomorrow).unwrap();
+        assert_eq!(1, digests[0].sections.len());
+        assert_eq!(
+            1,
+            delete_delivered_subscription_digest(&db, &digests[0]).unwrap()
+        );
+        assert!(due_subscription_digests(&db, tomorrow).unwrap().is_empty());
+    }
+}
@@ -77,8 +77,44 @@ pub struct MockDb {
     pub ratings: RefCell<Vec<Rating>>,
     pub comments: RefCell<Vec<Comment>>,
     pub bbox_subscriptions: RefCell<Vec<BboxSubscription>>,
-    pub orgs: Vec<Organization>,
+    pub orgs: RefCell<Vec<Organization>>,
+    pub api_tokens: RefCell<Vec<ApiToken>>,
+    pub org_members: RefCell<Vec<OrganizationMember>>,
     pub token: RefCell<Vec<UserToken>>,
+    pub email_change_tokens: RefCell<Vec<EmailChangeToken>>,
+    pub subscription_notifications: RefCell<Vec<SubscriptionNotification>>,
+    pub author_notifications: RefCell<Vec<AuthorNotifications>>,
+    pub webhooks: RefCell<Vec<Webhook>>,
+    pub webhook_deliveries: RefCell<Vec<WebhookDelivery>>,
+    pub outgoing_emails: RefCell<Vec<OutgoingEmail>>,
+    pub change_log: RefCell<Vec<ChangeLogEntry>>,
+    pub refresh_tokens: RefCell<Vec<RefreshToken>>,
+    pub revoked_access_tokens: RefCell<Vec<(String, TimestampMs)>>,
+    pub totps: RefCell<Vec<UserTotp>>,
+    pub oidc_accounts: RefCell<Vec<OidcAccount>>,
+    // (email, code hash, used at)
+    pub recovery_codes: RefCell<Vec<(String, String, Option<TimestampMs>)>>,
+    pub failed_logins: RefCell<Vec<FailedLogins>>,
+    pub geocodes: RefCell<Vec<CachedGeoCode>>,
+    pub addresses: RefCell<Vec<CachedAddress>>,
+    pub audit_log: RefCell<Vec<AuditLogEntry>>,
+}
+
+impl MockDb {
+    /// Creates a user with a confirmed email address
+    /// and the password "secret".
+    pub fn create_test_user(&self, email: &str, role: Role) -> User {
+        let user = User {
+            email: email.into(),
+            email_confirmed: true,
+            password: "secret".parse::<Password>().unwrap(),
+            role,
+            disabled: false,
+            language: None,
+        };
+        self.create_user(&user).unwrap();
+        user
+    }
 }
 
 impl UserTokenRepo for MockDb {
@@ -123,6 +159,370 @@ impl UserTokenRepo for MockDb {
     }
 }
 
+impl OidcAccountRepo for MockDb {
+    fn create_oidc_account(&self, account: &OidcAccount) -> RepoResult<()> {
+        let mut accounts = self.oidc_accounts.borrow_mut();
+        if accounts
+            .iter()
+            .any(|a| a.provider == account.provider && a.subject == account.subject)
+        {
+            return Err(RepoError::AlreadyExists);
+        }
+        accounts.push(account.clone());
+        Ok(())
+    }
+
+    fn get_oidc_account(&self, provider: &str, subject: &str) -> RepoResult<OidcAccount> {
+        self.oidc_accounts
+            .borrow()
+            .iter()
+            .find(|a| a.provider == provider && a.subject == subject)
+            .cloned()
+            .ok_or(RepoError::NotFound)
+    }
+}
+
+impl TwoFactorRepo for MockDb {
+    fn replace_user_totp(&self, totp: &UserTotp) -> RepoResult<()> {
+        let mut totps = self.totps.borrow_mut();
+        totps.retain(|t| t.email != totp.email);
+        totps.push(totp.clone());
+        Ok(())
+    }
+
+    fn get_user_totp(&self, email: &str) -> RepoResult<UserTotp> {
+        self.totps
+            .borrow()
+            .iter()
+            .find(|t| t.email == email)
+            .cloned()
+            .ok_or(RepoError::NotFound)
+    }
+
+    fn confirm_user_totp(&self, email: &str, confirmed_at: TimestampMs) -> RepoResult<()> {
+        self.totps
+            .borrow_mut()
+            .iter_mut()
+            .find(|t| t.email == email)
+            .map(|t| t.confirmed_at = Some(confirmed_at))
+            .ok_or(RepoError::NotFound)
+    }
+
+    fn use_user_totp_step(&self, email: &str, step: i64) -> RepoResult<bool> {
+        Ok(self
+            .totps
+            .borrow_mut()
+            .iter_mut()
+            .find(|t| t.email == email && t.last_used_step.map(|s| s < step).unwrap_or(true))
+            .map(|t| t.last_used_step = Some(step))
+            .is_some())
+    }
+
+    fn delete_user_totp(&self, email: &str) -> RepoResult<()> {
+        self.totps.borrow_mut().retain(|t| t.email != email);
+        self.recovery_codes
+            .borrow_mut()
+            .retain(|(e, _, _)| e != email);
+        Ok(())
+    }
+
+    fn replace_recovery_codes(&self, email: &str, code_hashes: &[String]) -> RepoResult<()> {
+        let mut recovery_codes = self.recovery_codes.borrow_mut();
+        recovery_codes.retain(|(e, _, _)| e != email);
+        recovery_codes.extend(
+            code_hashes
+                .iter()
+                .map(|code_hash| (email.to_owned(), code_hash.clone(), None)),
+        );
+        Ok(())
+    }
+
+    fn use_recovery_code(
+        &self,
+        email: &str,
+        code_hash: &str,
+        used_at: TimestampMs,
+    ) -> RepoResult<bool> {
+        Ok(self
+            .recovery_codes
+            .borrow_mut()
+            .iter_mut()
+            .find(|(e, h, u)| e == email && h == code_hash && u.is_none())
+            .map(|(_, _, u)| *u = Some(used_at))
+            .is_some())
+    }
+
+    fn count_unused_recovery_codes(&self, email: &str) -> RepoResult<usize> {
+        Ok(self
+            .recovery_codes
+            .borrow()
+            .iter()
+            .filter(|(e, _, u)| e == email && u.is_none())
+            .count())
+    }
+}
+
+impl LoginThrottleRepo for MockDb {
+    fn get_failed_logins(&self, key: &LoginThrottleKey) -> RepoResult<FailedLogins> {
+        self.failed_logins
+            .borrow()
+            .iter()
+            .find(|f| &f.key == key)
+            .cloned()
+            .ok_or(RepoError::NotFound)
+    }
+
+    fn replace_failed_logins(&self, failed_logins: &FailedLogins) -> RepoResult<()> {
+        let mut all = self.failed_logins.borrow_mut();
+        all.retain(|f| f.key != failed_logins.key);
+        all.push(failed_logins.clone());
+        Ok(())
+    }
+
+    fn delete_failed_logins(&self, key: &LoginThrottleKey) -> RepoResult<()> {
+        self.failed_logins.borrow_mut().retain(|f| &f.key != key);
+        Ok(())
+    }
+
+    fn delete_failed_logins_before(&self, last_failed_before: TimestampMs) -> RepoResult<usize> {
+        let mut all = self.failed_logins.borrow_mut();
+        let count = all.len();
+        all.retain(|f| f.last_failed_at >= last_failed_before || f.is_locked(last_failed_before));
+        Ok(count - all.len())
+    }
+}
+
+impl GeoCodeCacheRepo for MockDb {
+    fn get_cached_geocode(&self, address: &str) -> RepoResult<CachedGeoCode> {
+        self.geocodes
+            .borrow()
+            .iter()
+            .find(|g| g.address == address)
+            .cloned()
+            .ok_or(RepoError::NotFound)
+    }
+
+    fn replace_cached_geocode(&self, geocode: &CachedGeoCode) -> RepoResult<()> {
+        let mut all = self.geocodes.borrow_mut();
+        all.retain(|g| g.address != geocode.address);
+        all.push(geocode.clone());
+        Ok(())
+    }
+
+    fn delete_cached_geocodes_before(&self, created_before: TimestampMs) -> RepoResult<usize> {
+        let mut all = self.geocodes.borrow_mut();
+        let count = all.len();
+        all.retain(|g| g.created_at >= created_before);
+        Ok(count - all.len())
+    }
+
+    fn get_cached_address(&self, pos: &str) -> RepoResult<CachedAddress> {
+        self.addresses
+            .borrow()
+            .iter()
+            .find(|a| a.pos == pos)
+            .cloned()
+            .ok_or(RepoError::NotFound)
+    }
+
+    fn replace_cached_address(&self, address: &CachedAddress) -> RepoResult<()> {
+        let mut all = self.addresses.borrow_mut();
+        all.retain(|a| a.pos != address.pos);
+        all.push(address.clone());
+        Ok(())
+    }
+
+    fn delete_cached_addresses_before(&self, created_before: TimestampMs) -> RepoResult<usize> {
+        let mut all = self.addresses.borrow_mut();
+        let count = all.len();
+        all.retain(|a| a.created_at >= created_before);
+        Ok(count - all.len())
+    }
+}
+
+impl EmailChangeRepo for MockDb {
+    fn replace_email_change_token(&self, token: &EmailChangeToken) -> RepoResult<()> {
+        let mut tokens = self.email_change_tokens.borrow_mut();
+        tokens.retain(|t| t.email != token.email);
+        tokens.push(token.clone());
+        Ok(())
+    }
+
+    fn consume_email_change_token(
+        &self,
+        new_email_nonce: &EmailNonce,
+    ) -> RepoResult<EmailChangeToken> {
+        let mut tokens = self.email_change_tokens.borrow_mut();
+        let index = tokens
+            .iter()
+            .position(|t| &t.new_email_nonce == new_email_nonce)
+            .ok_or(RepoError::NotFound)?;
+        Ok(tokens.remove(index))
+    }
+
+    fn delete_expired_email_change_tokens(&self, expired_before: Timestamp) -> RepoResult<usize> {
+        let len_before = self.email_change_tokens.borrow().len();
+        self.email_change_tokens
+            .borrow_mut()
+            .retain(|t| t.expires_at >= expired_before);
+        Ok(len_before - self.email_change_tokens.borrow().len())
+    }
+}
+
+impl SubscriptionNotificationRepo for MockDb {
+    fn add_subscription_notifications(
+        &self,
+        notifications: &[SubscriptionNotification],
+    ) -> RepoResult<usize> {
+        self.subscription_notifications
+            .borrow_mut()
+            .extend(notifications.iter().cloned());
+        Ok(notifications.len())
+    }
+
+    fn load_subscription_notifications(
+        &self,
+        delivery_mode: SubscriptionDeliveryMode,
+    ) -> RepoResult<Vec<SubscriptionNotification>> {
+        let mut notifications: Vec<_> = self
+            .subscription_notifications
+            .borrow()
+            .iter()
+            .filter(|n| n.delivery_mode == delivery_mode)
+            .cloned()
+            .collect();
+        notifications.sort_by(|a, b| {
+            a.user_email
+                .cmp(&b.user_email)
+                .then(a.subscription_id.cmp(&b.subscription_id))
+                .then(a.created_at.cmp(&b.created_at))
+        });
+        Ok(notifications)
+    }
+
+    fn delete_subscription_notifications(
+        &self,
+        user_email: &str,
+        delivery_mode: SubscriptionDeliveryMode,
+        subscription_id: Option<&Id>,
+        created_until: TimestampMs,
+    ) -> RepoResult<usize> {
+        let len_before = self.subscription_notifications.borrow().len();
+        self.subscription_notifications.borrow_mut().retain(|n| {
+            n.user_email != user_email
+                || n.delivery_mode != delivery_mode
+                || n.subscription_id.as_ref() != subscription_id
+                || n.created_at > created_until
+        });
+        Ok(len_before - self.subscription_notifications.borrow().len())
+    }
+}
+
+impl AuthorNotificationRepo for MockDb {
+    fn replace_author_notifications(&self, settings: &AuthorNotifications) -> RepoResult<()> {
+        self.get_user_by_email(&settings.user_email)?;
+        let mut author_notifications = self.author_notifications.borrow_mut();
+        author_notifications.retain(|s| s.user_email != settings.user_email);
+        author_notifications.push(settings.clone());
+        Ok(())
+    }
+
+    fn get_author_notifications(&self, email: &str) -> RepoResult<AuthorNotifications> {
+        self.author_notifications
+            .borrow()
+            .iter()
+            .find(|s| s.user_email == email)
+            .cloned()
+            .ok_or(RepoError::NotFound)
+    }
+}
+
+impl SessionTokenRepo for MockDb {
+    fn create_refresh_token(&self, token: &RefreshToken) -> RepoResult<()> {
+        self.refresh_tokens.borrow_mut().push(token.clone());
+        Ok(())
+    }
+
+    fn get_refresh_token(&self, token_hash: &str) -> RepoResult<RefreshToken> {
+        self.refresh_tokens
+            .borrow()
+            .iter()
+            .find(|t| t.token_hash == token_hash)
+            .cloned()
+            .ok_or(RepoError::NotFound)
+    }
+
+    fn use_refresh_token(&self, token_hash: &str, used_at: TimestampMs) -> RepoResult<bool> {
+        Ok(self
+            .refresh_tokens
+            .borrow_mut()
+            .iter_mut()
+            .find(|t| t.token_hash == token_hash && t.used_at.is_none())
+            .map(|t| t.used_at = Some(used_at))
+            .is_some())
+    }
+
+    fn delete_refresh_tokens_of_session(&self, session_id: &Id) -> RepoResult<usize> {
+        let len_before = self.refresh_tokens.borrow().len();
+        self.refresh_tokens
+            .borrow_mut()
+            .retain(|t| &t.session_id != session_id);
+        Ok(len_before - self.refresh_tokens.borrow().len())
+    }
+
+    fn delete_refresh_tokens_of_user(&self, email: &str) -> RepoResult<usize> {
+        let len_before = self.refresh_tokens.borrow().len();
+        self.refresh_tokens
+            .borrow_mut()
+            .retain(|t| t.email != email);
+        Ok(len_before - self.refresh_tokens.borrow().len())
+    }
+
+    fn is_session_of_user(&self, session_id: &Id, email: &str) -> RepoResult<bool> {
+        Ok(self
+            .refresh_tokens
+            .borrow()
+            .iter()
+            .any(|t| &t.session_id == session_id && t.email == email))
+    }
+
+    fn delete_expired_refresh_tokens(&self, expired_before: TimestampMs) -> RepoResult<usize> {
+        let len_before = self.refresh_tokens.borrow().len();
+        self.refresh_tokens
+            .borrow_mut()
+            .retain(|t| t.expires_at >= expired_before);
+        Ok(len_before - self.refresh_tokens.borrow().len())
+    }
+
+    fn revoke_access_token(&self, jti: &str, expires_at: TimestampMs) -> RepoResult<()> {
+        if !self.is_access_token_revoked(jti)? {
+            self.revoked_access_tokens
+                .borrow_mut()
+                .push((jti.to_owned(), expires_at));
+        }
+        Ok(())
+    }
+
+    fn is_access_token_revoked(&self, jti: &str) -> RepoResult<bool> {
+        Ok(self
+            .revoked_access_tokens
+            .borrow()
+            .iter()
+            .any(|(x, _)| x == jti))
+    }
+
+    fn delete_expired_revoked_access_tokens(
+        &self,
+        expired_before: TimestampMs,
+    ) -> RepoResult<usize> {
+        let len_before = self.revoked_access_tokens.borrow().len();
+        self.revoked_access_tokens
+            .borrow_mut()
+            .retain(|(_, expires_at)| *expires_at >= expired_before);
+        Ok(len_before - self.revoked_access_tokens.borrow().len())
+    }
+}
+
 pub struct DummySearchEngine;
 
 impl Indexer for DummySearchEngine {
@@ -272,12 +672,14 @@ impl PlaceRepo for MockDb {
         unimplemented!();
     }
 
-    fn get_place_history(
-        &self,
-        _id: &str,
-        _revision: Option<Revision>,
-    ) -> RepoResult<PlaceHistory> {
-        unimplemented!();
+    // Only the current revision is stored
+    fn get_place_history(&self, id: &str, _revision: Option<Revision>) -> RepoResult<PlaceHistory> {
+        let (place, _) = get(&self.entries.borrow(), id)?;
+        let (place, revision) = place.into();
+        Ok(PlaceHistory {
+            place,
+            revisions: vec![(revision, vec![])],
+        })
     }
 
     fn load_place_revision(&self, _id: &str, _rev: Revision) -> RepoResult<(Place, ReviewStatus)> {
@@ -325,6 +727,14 @@ impl EventGateway for MockDb {
         Ok(events)
     }
 
+    fn recently_changed_events(
+        &self,
+        _params: &RecentlyChangedEntriesParams,
+        _pagination: &Pagination,
+    ) -> RepoResult<Vec<(Event, ChangeType, Timestamp)>> {
+        unimplemented!();
+    }
+
     fn count_events(&self) -> RepoResult<usize> {
         self.all_events_chronologically().map(|v| v.len())
     }
@@ -373,14 +783,95 @@ impl UserGateway for MockDb {
         self.all_users().map(|v| v.len())
     }
 
+    fn search_users(
+        &self,
+        params: &UserSearchParams,
+        pagination: &Pagination,
+    ) -> RepoResult<Vec<User>> {
+        let mut users: Vec<_> = self
+            .users
+            .borrow()
+            .iter()
+            .filter(|u| {
+                params
+                    .email
+                    .as_ref()
+                    .map(|email| u.email.contains(email.as_str()))
+                    .unwrap_or(true)
+                    && params.role.map(|role| u.role == role).unwrap_or(true)
+                    && params
+                        .disabled
+                        .map(|disabled| u.disabled == disabled)
+                        .unwrap_or(true)
+            })
+            .cloned()
+            .collect();
+        users.sort_by(|a, b| a.email.cmp(&b.email));
+        Ok(users
+            .into_iter()
+            .skip(pagination.offset.unwrap_or(0) as usize)
+            .take(pagination.limit.unwrap_or(u64::MAX) as usize)
+            .collect())
+    }
+
     fn delete_user_by_email(&self, email: &str) -> RepoResult<()> {
         self.users.borrow_mut().retain(|u| u.email != email);
+        self.subscription_notifications
+            .borrow_mut()
+            .retain(|n| n.user_email != email);
         Ok(())
     }
 
     fn update_user(&self, u: &User) -> RepoResult<()> {
         update(&mut self.users.borrow_mut(), u)
     }
+
+    fn change_user_email(&self, email: &str, new_email: &str) -> RepoResult<()> {
+        let mut users = self.users.borrow_mut();
+        if users.iter().any(|u| u.email == new_email) {
+            return Err(RepoError::AlreadyExists);
+        }
+        let user = users
+            .iter_mut()
+            .find(|u| u.email == email)
+            .ok_or(RepoError::NotFound)?;
+        user.email = new_email.to_owned();
+        for s in self.bbox_subscriptions.borrow_mut().iter_mut() {
+            if s.user_email == email {
+                s.user_email = new_email.to_owned();
+            }
+        }
+        self.refresh_tokens
+            .borrow_mut()
+            .retain(|t| t.email != email);
+        for m in self.org_members.borrow_mut().iter_mut() {
+            if m.email == email {
+                m.email = new_email.to_owned();
+            }
+        }
+        for t in self.totps.borrow_mut().iter_mut() {
+            if t.email == email {
+                t.email = new_email.to_owned();
+            }
+        }
+        for (e, _, _) in self.recovery_codes.borrow_mut().iter_mut() {
+            if e == email {
+                *e = new_email.to_owned();
+            }
+        }
+        self.token
+            .borrow_mut()
+            .retain(|t| t.email_nonce.email != email);
+        self.email_change_tokens
+            .borrow_mut()
+            .retain(|t| t.email != email);
+        for n in self.subscription_notifications.borrow_mut().iter_mut() {
+            if n.user_email == email {
+                n.user_email = new_email.to_owned();
+            }
+        }
+        Ok(())
+    }
 }
 
 impl CommentRepository for MockDb {
@@ -439,19 +930,35 @@ impl CommentRepository for MockDb {
 
 impl OrganizationRepo for MockDb {
     fn create_org(&mut self, o: Organization) -> RepoResult<()> {
-        create(&mut self.orgs, o)
+        create(&mut self.orgs.borrow_mut(), o)
     }
-    fn get_org_by_api_token(&self, token: &str) -> RepoResult<Organization> {
-        let o = self
-            .orgs
+    fn update_org(&self, o: &Organization) -> RepoResult<()> {
+        update(&mut self.orgs.borrow_mut(), o)
+    }
+    fn delete_org(&self, id: &Id) -> RepoResult<()> {
+        let mut orgs = self.orgs.borrow_mut();
+        let pos = orgs
             .iter()
-            .find(|o| o.api_token == token)
+            .position(|o| &o.id == id)
             .ok_or(RepoError::NotFound)?;
-        Ok(o.clone())
+        orgs.remove(pos);
+        Ok(())
+    }
+    fn all_orgs(&self) -> RepoResult<Vec<Organization>> {
+        Ok(self.orgs.borrow().clone())
+    }
+    fn get_org_by_id(&self, id: &Id) -> RepoResult<Organization> {
+        self.orgs
+            .borrow()
+            .iter()
+            .find(|o| &o.id == id)
+            .cloned()
+            .ok_or(RepoError::NotFound)
     }
     fn map_tag_to_clearance_org_id(&self, tag: &str) -> RepoResult<Option<Id>> {
         Ok(self
             .orgs
+            .borrow()
             .iter()
             .find(|o| {
                 o.moderated_tags
@@ -466,6 +973,7 @@ impl OrganizationRepo for MockDb {
     ) -> RepoResult<Vec<(Id, ModeratedTag)>> {
         Ok(self
             .orgs
+            .borrow()
             .iter()
             .filter(|o| Some(&o.id) != excluded_org_id)
             .flat_map(|o| {
@@ -568,6 +1076,352 @@ impl PlaceClearanceRepo for MockDb {
     fn cleanup_pending_clearances_for_places(&self, _org_id: &Id) -> RepoResult<u64> {
         Ok(0)
     }
+
+    fn load_pending_clearances_for_places_created_after(
+        &self,
+        _org_id: &Id,
+        _created_after: Option<TimestampMs>,
+    ) -> RepoResult<Vec<PendingClearanceForPlace>> {
+        Ok(vec![])
+    }
+
+    fn get_pending_clearances_notified_until(
+        &self,
+        _org_id: &Id,
+    ) -> RepoResult<Option<TimestampMs>> {
+        Ok(None)
+    }
+
+    fn set_pending_clearances_notified_until(
+        &self,
+        _org_id: &Id,
+        _notified_until: TimestampMs,
+    ) -> RepoResult<()> {
+        Ok(())
+    }
+
+    fn load_org_ids_with_unannounced_pending_clearances(
+        &self,
+        _created_until: TimestampMs,
+    ) -> RepoResult<Vec<Id>> {
+        Ok(vec![])
+    }
+}
+
+impl OrganizationMemberRepo for MockDb {
+    fn set_org_member(&self, member: &OrganizationMember) -> RepoResult<()> {
+        self.get_org_by_id(&member.org_id)?;
+        self.get_user_by_email(&member.email)?;
+        let mut members = self.org_members.borrow_mut();
+        members.retain(|m| !(m.org_id == member.org_id && m.email == member.email));
+        members.push(member.clone());
+        Ok(())
+    }
+
+    fn remove_org_member(&self, org_id: &Id, email: &str) -> RepoResult<()> {
+        let mut members = self.org_members.borrow_mut();
+        let pos = members
+            .iter()
+            .position(|m| &m.org_id == org_id && m.email == email)
+            .ok_or(RepoError::NotFound)?;
+        members.remove(pos);
+        Ok(())
+    }
+
+    fn load_org_members(&self, org_id: &Id) -> RepoResult<Vec<OrganizationMember>> {
+        Ok(self
+            .org_members
+            .borrow()
+            .iter()
+            .filter(|m| &m.org_id == org_id)
+            .cloned()
+            .collect())
+    }
+
+    fn load_org_memberships_of_user(&self, email: &str) -> RepoResult<Vec<OrganizationMember>> {
+        Ok(self
+            .org_members
+            .borrow()
+            .iter()
+            .filter(|m| m.email == email)
+            .cloned()
+            .collect())
+    }
+}
+
+impl ApiTokenRepo for MockDb {
+    fn create_api_token(&self, token: &ApiToken) -> RepoResult<()> {
+        self.api_tokens.borrow_mut().push(token.clone());
+        Ok(())
+    }
+
+    fn get_api_token(&self, id: &Id) -> RepoResult<ApiToken> {
+        self.api_tokens
+            .borrow()
+            .iter()
+            .find(|t| &t.id == id)
+            .cloned()
+            .ok_or(RepoError::NotFound)
+    }
+
+    fn get_api_token_by_hash(&self, token_hash: &str) -> RepoResult<ApiToken> {
+        self.api_tokens
+            .borrow()
+            .iter()
+            .find(|t| t.token_hash == token_hash)
+            .cloned()
+            .ok_or(RepoError::NotFound)
+    }
+
+    fn load_api_tokens_of_org(&self, org_id: &Id) -> RepoResult<Vec<ApiToken>> {
+        Ok(self
+            .api_tokens
+            .borrow()
+            .iter()
+            .filter(|t| &t.org_id == org_id)
+            .cloned()
+            .collect())
+    }
+
+    fn replace_api_token_hash(&self, id: &Id, token_hash: &str) -> RepoResult<()> {
+        self.api_tokens
+            .borrow_mut()
+            .iter_mut()
+            .find(|t| &t.id == id)
+            .map(|t| t.token_hash = token_hash.to_owned())
+            .ok_or(RepoError::NotFound)
+    }
+
+    fn update_api_token_last_used_at(&self, id: &Id, last_used_at: TimestampMs) -> RepoResult<()> {
+        self.api_tokens
+            .borrow_mut()
+            .iter_mut()
+            .find(|t| &t.id == id)
+            .map(|t| t.last_used_at = Some(last_used_at))
+            .ok_or(RepoError::NotFound)
+    }
+
+    fn delete_api_token(&self, id: &Id) -> RepoResult<()> {
+        let len_before = self.api_tokens.borrow().len();
+        self.api_tokens.borrow_mut().retain(|t| &t.id != id);
+        if self.api_tokens.borrow().len() == len_before {
+            return Err(RepoError::NotFound);
+        }
+        Ok(())
+    }
+}
+
+impl WebhookRepo for MockDb {
+    fn create_webhook(&self, webhook: &Webhook) -> RepoResult<()> {
+        self.webhooks.borrow_mut().push(webhook.clone());
+        Ok(())
+    }
+
+    fn get_webhook(&self, id: &Id) -> RepoResult<Webhook> {
+        self.webhooks
+            .borrow()
+            .iter()
+            .find(|w| &w.id == id)
+            .cloned()
+            .ok_or(RepoError::NotFound)
+    }
+
+    fn load_webhooks_of_orgs(&self, org_ids: &[Id]) -> RepoResult<Vec<Webhook>> {
+        Ok(self
+            .webhooks
+            .borrow()
+            .iter()
+            .filter(|w| org_ids.contains(&w.org_id))
+            .cloned()
+            .collect())
+    }
+
+    fn delete_webhook(&self, id: &Id) -> RepoResult<()> {
+        self.get_webhook(id)?;
+        self.webhooks.borrow_mut().retain(|w| &w.id != id);
+        self.webhook_deliveries
+            .borrow_mut()
+            .retain(|d| &d.webhook_id != id);
+        Ok(())
+    }
+
+    fn add_webhook_deliveries(&self, deliveries: &[WebhookDelivery]) -> RepoResult<usize> {
+        self.webhook_deliveries
+            .borrow_mut()
+            .extend(deliveries.iter().cloned());
+        Ok(deliveries.len())
+    }
+
+    fn load_due_webhook_deliveries(
+        &self,
+        due_at: TimestampMs,
+        limit: u64,
+    ) -> RepoResult<Vec<WebhookDelivery>> {
+        let mut deliveries: Vec<_> = self
+            .webhook_deliveries
+            .borrow()
+            .iter()
+            .filter(|d| d.next_attempt_at.map(|t| t <= due_at).unwrap_or(false))
+            .cloned()
+            .collect();
+        deliveries.sort_by_key(|d| d.next_attempt_at);
+        deliveries.truncate(limit as usize);
+        Ok(deliveries)
+    }
+
+    fn update_webhook_delivery(&self, delivery: &WebhookDelivery) -> RepoResult<()> {
+        let mut deliveries = self.webhook_deliveries.borrow_mut();
+        let d = deliveries
+            .iter_mut()
+            .find(|d| d.id == delivery.id)
+            .ok_or(RepoError::NotFound)?;
+        *d = delivery.clone();
+        Ok(())
+    }
+
+    fn list_webhook_deliveries(
+        &self,
+        webhook_id: &Id,
+        _pagination: &Pagination,
+    ) -> RepoResult<Vec<WebhookDelivery>> {
+        Ok(self
+            .webhook_deliveries
+            .borrow()
+            .iter()
+            .rev()
+            .filter(|d| &d.webhook_id == webhook_id)
+            .cloned()
+            .collect())
+    }
+}
+
+impl EmailOutboxRepo for MockDb {
+    fn add_outgoing_emails(&self, emails: &[OutgoingEmail]) -> RepoResult<usize> {
+        self.outgoing_emails
+            .borrow_mut()
+            .extend(emails.iter().cloned());
+        Ok(emails.len())
+    }
+
+    fn load_due_outgoing_emails(
+        &self,
+        due_at: TimestampMs,
+        limit: u64,
+    ) -> RepoResult<Vec<OutgoingEmail>> {
+        let mut emails: Vec<_> = self
+            .outgoing_emails
+            .borrow()
+            .iter()
+            .filter(|e| e.next_attempt_at.map(|t| t <= due_at).unwrap_or(false))
+            .cloned()
+            .collect();
+        emails.sort_by_key(|e| e.next_attempt_at);
+        emails.truncate(limit as usize);
+        Ok(emails)
+    }
+
+    fn update_outgoing_email(&self, email: &OutgoingEmail) -> RepoResult<()> {
+        let mut emails = self.outgoing_emails.borrow_mut();
+        let e = emails
+            .iter_mut()
+            .find(|e| e.id == email.id)
+            .ok_or(RepoError::NotFound)?;
+        *e = email.clone();
+        Ok(())
+    }
+
+    fn load_failed_outgoing_emails(
+        &self,
+        _pagination: &Pagination,
+    ) -> RepoResult<Vec<OutgoingEmail>> {
+        let mut emails: Vec<_> = self
+            .outgoing_emails
+            .borrow()
+            .iter()
+            .filter(|e| e.status() == OutgoingEmailStatus::Failed)
+            .cloned()
+            .collect();
+        emails.sort_by(|a, b| b.last_attempt_at.cmp(&a.last_attempt_at));
+        Ok(emails)
+    }
+
+    fn count_outgoing_emails(&self) -> RepoResult<EmailOutboxStats> {
+        let mut stats = EmailOutboxStats::default();
+        for e in self.outgoing_emails.borrow().iter() {
+            match e.status() {
+                OutgoingEmailStatus::Pending => stats.pending += 1,
+                OutgoingEmailStatus::Sent => stats.sent += 1,
+                OutgoingEmailStatus::Failed => stats.failed += 1,
+            }
+        }
+        Ok(stats)
+    }
+
+    fn delete_sent_outgoing_emails_before(&self, before: TimestampMs) -> RepoResult<usize> {
+        let len_before = self.outgoing_emails.borrow().len();
+        self.outgoing_emails
+            .borrow_mut()
+            .retain(|e| e.sent_at.map(|t| t >= before).unwrap_or(true));
+        Ok(len_before - self.outgoing_emails.borrow().len())
+    }
+}
+
+impl ChangeLogRepo for MockDb {
+    fn log_change(&self, change: &EntityChange) -> RepoResult<u64> {
+        let mut change_log = self.change_log.borrow_mut();
+        let seq = change_log.last().map(|e| e.seq).unwrap_or(0) + 1;
+        change_log.push(ChangeLogEntry {
+            seq,
+            change: change.clone(),
+        });
+        Ok(seq)
+    }
+
+    fn load_change_log(&self, after_seq: u64, limit: u64) -> RepoResult<Vec<ChangeLogEntry>> {
+        Ok(self
+            .change_log
+            .borrow()
+            .iter()
+            .filter(|e| e.seq > after_seq)
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    fn latest_change_log_seq(&self) -> RepoResult<Option<u64>> {
+        Ok(self.change_log.borrow().last().map(|e| e.seq))
+    }
+
+    fn delete_change_log_entries_before(&self, before: TimestampMs) -> RepoResult<usize> {
+        let mut change_log = self.change_log.borrow_mut();
+        let len = change_log.len();
+        change_log.retain(|e| e.change.at >= before);
+        Ok(len - change_log.len())
+    }
+}
+
+impl AuditLogRepo for MockDb {
+    fn add_audit_log_entry(&self, entry: &AuditLogEntry) -> RepoResult<()> {
+        self.audit_log.borrow_mut().push(entry.clone());
+        Ok(())
+    }
+
+    fn load_audit_log(
+        &self,
+        target: Option<&str>,
+        pagination: &Pagination,
+    ) -> RepoResult<Vec<AuditLogEntry>> {
+        Ok(self
+            .audit_log
+            .borrow()
+            .iter()
+            .rev()
+            .filter(|e| target.map(|t| e.target == t).unwrap_or(true))
+            .skip(pagination.offset.unwrap_or(0) as usize)
+            .take(pagination.limit.unwrap_or(u64::MAX) as usize)
+            .cloned()
+            .collect())
+    }
 }
 
 impl Db for MockDb {
@@ -587,6 +1441,14 @@ impl Db for MockDb {
         create(&mut self.bbox_subscriptions.borrow_mut(), s.clone())
     }
 
+    fn update_bbox_subscription(&self, s: &BboxSubscription) -> RepoResult<()> {
+        update(&mut self.bbox_subscriptions.borrow_mut(), s)
+    }
+
+    fn get_bbox_subscription(&self, id: &Id) -> RepoResult<BboxSubscription> {
+        get(&self.bbox_subscriptions.borrow(), id.as_ref())
+    }
+
     fn all_tags(&self) -> RepoResult<Vec<Tag>> {
         Ok(self.tags.borrow().clone())
     }
@@ -611,10 +1473,26 @@ impl Db for MockDb {
             .collect())
     }
 
+    fn delete_bbox_subscription(&self, id: &Id) -> RepoResult<()> {
+        let mut subscriptions = self.bbox_subscriptions.borrow_mut();
+        let len = subscriptions.len();
+        subscriptions.retain(|s| &s.id != id);
+        if subscriptions.len() == len {
+            return Err(RepoError::NotFound);
+        }
+        self.subscription_notifications
+            .borrow_mut()
+            .retain(|n| n.subscription_id.as_ref() != Some(id));
+        Ok(())
+    }
+
     fn delete_bbox_subscriptions_by_email(&self, user_email: &str) -> RepoResult<()> {
         self.bbox_subscriptions
             .borrow_mut()
             .retain(|s| s.user_email != user_email);
+        self.subscription_notifications
+            .borrow_mut()
+            .retain(|n| n.user_email != user_email);
         Ok(())
     }
 }
@@ -627,12 +1505,16 @@ fn receive_different_user() {
         email_confirmed: true,
         password: "secret".parse::<Password>().unwrap(),
         role: Role::Guest,
+        disabled: false,
+        language: None,
     });
     db.users.borrow_mut().push(User {
         email: "b@foo.bar".into(),
         email_confirmed: true,
         password: "secret".parse::<Password>().unwrap(),
         role: Role::Guest,
+        disabled: false,
+        language: None,
     });
     assert!(get_user(&db, "a@foo.bar", "b@foo.bar").is_err());
     assert!(get_user(&db, "a@foo.bar", "a@foo.bar").is_ok());
@@ -652,6 +1534,8 @@ fn create_bbox_subscription() {
             email_confirmed: true,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::Guest,
+            disabled: false,
+            language: None,
         })
         .is_ok());
     assert!(usecases::subscribe_to_bbox(&db, "abc@abc.de".into(), bbox_new).is_ok());
@@ -683,16 +1567,12 @@ fn modify_bbox_subscription() {
             email_confirmed: true,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::Guest,
+            disabled: false,
+            language: None,
         })
         .is_ok());
 
-    let bbox_subscription = BboxSubscription {
-        id: "123".into(),
-        user_email: "abc@abc.de".into(),
-        bbox: bbox_old,
-    };
-    db.create_bbox_subscription(&bbox_subscription).unwrap();
-
+    usecases::subscribe_to_bbox(&db, "abc@abc.de".into(), bbox_old).unwrap();
     usecases::subscribe_to_bbox(&db, "abc@abc.de".into(), bbox_new).unwrap();
 
     let bbox_subscriptions: Vec<_> = db
@@ -729,12 +1609,17 @@ fn get_bbox_subscriptions() {
             email_confirmed: true,
             password: "secret1".parse::<Password>().unwrap(),
             role: Role::Guest,
+            disabled: false,
+            language: None,
         })
         .is_ok());
     let bbox_subscription = BboxSubscription {
         id: "1".into(),
         user_email: "a@abc.de".into(),
+        name: String::new(),
         bbox: bbox1,
+        filter: Default::default(),
+        delivery_mode: Default::default(),
     };
     assert!(db.create_bbox_subscription(&bbox_subscription).is_ok());
 
@@ -744,12 +1629,17 @@ fn get_bbox_subscriptions() {
             email_confirmed: true,
             password: "secret2".parse::<Password>().unwrap(),
             role: Role::Guest,
+            disabled: false,
+            language: None,
         })
         .is_ok());
     let bbox_subscription2 = BboxSubscription {
         id: "2".into(),
         user_email: "b@abc.de".into(),
+        name: String::new(),
         bbox: bbox2,
+        filter: Default::default(),
+        delivery_mode: Default::default(),
     };
     assert!(db.create_bbox_subscription(&bbox_subscription2).is_ok());
     let bbox_subscriptions = usecases::get_bbox_subscriptions(&db, "b@abc.de");
@@ -758,7 +1648,7 @@ fn get_bbox_subscriptions() {
 }
 
 #[test]
-fn email_addresses_by_coordinate() {
+fn subscription_recipients_by_coordinate() {
     let db = MockDb::default();
     let bbox_new = geo::MapBbox::new(
         MapPoint::from_lat_lng_deg(0.0, 0.0),
@@ -770,20 +1660,38 @@ fn email_addresses_by_coordinate() {
         email_confirmed: true,
         password: "secret".parse::<Password>().unwrap(),
         role: Role::Guest,
+        disabled: false,
+        language: None,
     })
     .unwrap();
 
     usecases::subscribe_to_bbox(&db, "abc@abc.de".into(), bbox_new).unwrap();
 
-    let email_addresses =
-        usecases::email_addresses_by_coordinate(&db, MapPoint::from_lat_lng_deg(5.0, 5.0)).unwrap();
-    assert_eq!(email_addresses.len(), 1);
-    assert_eq!(email_addresses[0], "abc@abc.de");
+    let change = ObservedChange {
+        entity_kind: SubscriptionEntityKind::Place,
+        change_type: SubscriptionChangeType::Created,
+        tags: &[],
+    };
+    let recipients = usecases::subscription_recipients_by_coordinate(
+        &db,
+        MapPoint::from_lat_lng_deg(5.0, 5.0),
+        &change,
+    )
+    .unwrap();
+    assert_eq!(recipients.len(), 1);
+    assert_eq!(recipients[0].email, "abc@abc.de");
+    assert_eq!(
+        recipients[0].subscription_id,
+        db.bbox_subscriptions.borrow()[0].id
+    );
 
-    let no_email_addresses =
-        usecases::email_addresses_by_coordinate(&db, MapPoint::from_lat_lng_deg(20.0, 20.0))
-            .unwrap();
-    assert_eq!(no_email_addresses.len(), 0);
+    let no_recipients = usecases::subscription_recipients_by_coordinate(
+        &db,
+        MapPoint::from_lat_lng_deg(20.0, 20.0),
+        &change,
+    )
+    .unwrap();
+    assert_eq!(no_recipients.len(), 0);
 }
 
 #[test]
@@ -795,6 +1703,8 @@ fn delete_user() {
             email_confirmed: true,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::Guest,
+            disabled: false,
+            language: None,
         })
         .is_ok());
     assert!(db
@@ -803,6 +1713,8 @@ fn delete_user() {
             email_confirmed: true,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::Guest,
+            disabled: false,
+            language: None,
         })
         .is_ok());
     assert_eq!(db.count_users().unwrap(), 2);
@@ -819,6 +1731,8 @@ fn receive_event_with_creators_email() {
         email_confirmed: true,
         password: "secret".parse::<Password>().unwrap(),
         role: Role::Guest,
+        disabled: false,
+        language: None,
     })
     .unwrap();
     db.create_event(Event {
@@ -0,0 +1,246 @@
+use crate::core::{
+    prelude::*,
+    util::{hash_token, totp},
+};
+
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+/// The secret of a pending enrollment that needs to be added
+/// to an authenticator app, e.g. by scanning the URI as a QR code.
+#[derive(Debug, Clone)]
+pub struct TotpEnrollment {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct TwoFactorStatus {
+    pub enabled: bool,
+    pub unused_recovery_codes: usize,
+}
+
+fn try_get_user_totp<R: TwoFactorRepo>(repo: &R, email: &str) -> Result<Option<UserTotp>> {
+    match repo.get_user_totp(email) {
+        Ok(user_totp) => Ok(Some(user_totp)),
+        Err(RepoError::NotFound) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub fn get_two_factor_status<R: TwoFactorRepo>(repo: &R, email: &str) -> Result<TwoFactorStatus> {
+    let enabled = try_get_user_totp(repo, email)?
+        .map(|t| t.is_enabled())
+        .unwrap_or(false);
+    let unused_recovery_codes = if enabled {
+        repo.count_unused_recovery_codes(email)?
+    } else {
+        0
+    };
+    Ok(TwoFactorStatus {
+        enabled,
+        unused_recovery_codes,
+    })
+}
+
+/// Generates a new secret for the user. A previous enrollment
+/// that has not been confirmed yet is discarded.
+pub fn begin_totp_enrollment<R: UserGateway + TwoFactorRepo>(
+    repo: &R,
+    email: &str,
+    issuer: &str,
+) -> Result<TotpEnrollment> {
+    let user = repo.get_user_by_email(email)?;
+    if try_get_user_totp(repo, &user.email)?
+        .map(|t| t.is_enabled())
+        .unwrap_or(false)
+    {
+        return Err(ParameterError::Forbidden.into());
+    }
+    let secret = totp::generate_secret();
+    let user_totp = UserTotp {
+        email: user.email,
+        secret: totp::base32_encode(&secret),
+        created_at: TimestampMs::now(),
+        confirmed_at: None,
+        last_used_step: None,
+    };
+    repo.replace_user_totp(&user_totp)?;
+    Ok(TotpEnrollment {
+        otpauth_uri: totp::otpauth_uri(issuer, &user_totp.email, &secret),
+        secret: user_totp.secret,
+    })
+}
+
+/// Enables the second factor after verifying the first code
+/// of the authenticator app and returns the recovery codes.
+pub fn confirm_totp_enrollment<R: TwoFactorRepo>(
+    repo: &R,
+    email: &str,
+    code: &str,
+) -> Result<Vec<String>> {
+    let user_totp = try_get_user_totp(repo, email)?.ok_or(ParameterError::SecondFactor)?;
+    if user_totp.is_enabled() {
+        return Err(ParameterError::Forbidden.into());
+    }
+    verify_totp_code(repo, &user_totp, code)?;
+    repo.confirm_user_totp(email, TimestampMs::now())?;
+    info!("User {} enabled the second factor", email);
+    replace_recovery_codes(repo, email)
+}
+
+/// Invalidates all previous recovery codes.
+pub fn regenerate_recovery_codes<R: TwoFactorRepo>(repo: &R, email: &str) -> Result<Vec<String>> {
+    if !try_get_user_totp(repo, email)?
+        .map(|t| t.is_enabled())
+        .unwrap_or(false)
+    {
+        return Err(ParameterError::Forbidden.into());
+    }
+    replace_recovery_codes(repo, email)
+}
+
+pub fn disable_totp<R: TwoFactorRepo>(repo: &R, email: &str) -> Result<()> {
+    repo.delete_user_totp(email)?;
+    info!("User {} disabled the second factor", email);
+    Ok(())
+}
+
+/// Verifies either a one-time password or an unused recovery code.
+/// Each code is accepted only once.
+pub fn verify_second_factor<R: TwoFactorRepo>(repo: &R, email: &str, code: &str) -> Result<()> {
+    let user_totp = try_get_user_totp(repo, email)?
+        .filter(UserTotp::is_enabled)
+        .ok_or(ParameterError::SecondFactor)?;
+    let code = code.trim();
+    if code.len() == totp::DIGITS && code.bytes().all(|b| b.is_ascii_digit()) {
+        return verify_totp_code(repo, &user_totp, code);
+    }
+    let code_hash = hash_token(&normalize_recovery_code(code));
+    if !repo.use_recovery_code(email, &code_hash, TimestampMs::now())? {
+        return Err(ParameterError::SecondFactor.into());
+    }
+    warn!("User {} logged in with a recovery code", email);
+    Ok(())
+}
+
+fn verify_totp_code<R: TwoFactorRepo>(repo: &R, user_totp: &UserTotp, code: &str) -> Result<()> {
+    let secret = totp::base32_decode(&user_totp.secret)
+        .ok_or_else(|| Error::Internal(format!("Invalid TOTP secret of {}", user_totp.email)))?;
+    let current_step = totp::time_step(Timestamp::now().into_seconds());
+    // Tolerate a clock skew of one time step in both directions
+    let step = (current_step - 1..=current_step + 1)
+        .find(|step| totp::totp(&secret, *step) == code.trim())
+        .ok_or(ParameterError::SecondFactor)?;
+    if !repo.use_user_totp_step(&user_totp.email, step)? {
+        // Replayed code
+        return Err(ParameterError::SecondFactor.into());
+    }
+    Ok(())
+}
+
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn replace_recovery_codes<R: TwoFactorRepo>(repo: &R, email: &str) -> Result<Vec<String>> {
+    let codes: Vec<_> = (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let nonce = Nonce::new().to_string();
+            format!("{}-{}", &nonce[..5], &nonce[5..10])
+        })
+        .collect();
+    let code_hashes: Vec<_> = codes
+        .iter()
+        .map(|code| hash_token(&normalize_recovery_code(code)))
+        .collect();
+    repo.replace_recovery_codes(email, &code_hashes)?;
+    Ok(codes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockDb;
+    use super::*;
+
+    fn setup(email: &str) -> MockDb {
+        let db = MockDb::default();
+        db.create_test_user(email, Role::Admin);
+        db
+    }
+
+    fn current_code(enrollment: &TotpEnrollment) -> String {
+        let secret = totp::base32_decode(&enrollment.secret).unwrap();
+        totp::totp(&secret, totp::time_step(Timestamp::now().into_seconds()))
+    }
+
+    #[test]
+    fn enroll_and_disable() {
+        let email = "admin@example.com";
+        let db = setup(email);
+        assert!(!get_two_factor_status(&db, email).unwrap().enabled);
+
+        let enrollment = begin_totp_enrollment(&db, email, "OpenFairDB").unwrap();
+        assert!(enrollment
+            .otpauth_uri
+            .starts_with("otpauth://totp/OpenFairDB:"));
+        assert!(enrollment.otpauth_uri.contains(&enrollment.secret));
+        // Not enabled before being confirmed
+        assert!(matches!(
+            verify_second_factor(&db, email, &current_code(&enrollment)),
+            Err(Error::Parameter(ParameterError::SecondFactor))
+        ));
+        assert!(matches!(
+            confirm_totp_enrollment(&db, email, "123"),
+            Err(Error::Parameter(ParameterError::SecondFactor))
+        ));
+        let codes = confirm_totp_enrollment(&db, email, &current_code(&enrollment)).unwrap();
+        assert_eq!(RECOVERY_CODE_COUNT, codes.len());
+        let status = get_two_factor_status(&db, email).unwrap();
+        assert!(status.enabled);
+        assert_eq!(RECOVERY_CODE_COUNT, status.unused_recovery_codes);
+        assert!(matches!(
+            begin_totp_enrollment(&db, email, "OpenFairDB"),
+            Err(Error::Parameter(ParameterError::Forbidden))
+        ));
+
+        disable_totp(&db, email).unwrap();
+        let status = get_two_factor_status(&db, email).unwrap();
+        assert!(!status.enabled);
+        assert_eq!(0, status.unused_recovery_codes);
+        assert!(matches!(
+            regenerate_recovery_codes(&db, email),
+            Err(Error::Parameter(ParameterError::Forbidden))
+        ));
+    }
+
+    #[test]
+    fn use_recovery_codes_only_once() {
+        let email = "admin@example.com";
+        let db = setup(email);
+        let enrollment = begin_totp_enrollment(&db, email, "OpenFairDB").unwrap();
+        let codes = confirm_totp_enrollment(&db, email, &current_code(&enrollment)).unwrap();
+
+        // Case and separators are ignored
+        verify_second_factor(&db, email, &codes[0].to_uppercase().replace('-', " ")).unwrap();
+        assert!(matches!(
+            verify_second_factor(&db, email, &codes[0]),
+            Err(Error::Parameter(ParameterError::SecondFactor))
+        ));
+        assert_eq!(
+            RECOVERY_CODE_COUNT - 1,
+            get_two_factor_status(&db, email)
+                .unwrap()
+                .unused_recovery_codes
+        );
+
+        let new_codes = regenerate_recovery_codes(&db, email).unwrap();
+        assert!(matches!(
+            verify_second_factor(&db, email, &codes[1]),
+            Err(Error::Parameter(ParameterError::SecondFactor))
+        ));
+        verify_second_factor(&db, email, &new_codes[1]).unwrap();
+    }
+}
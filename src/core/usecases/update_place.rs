@@ -98,6 +98,14 @@ pub struct Storable {
     last_cleared_revision: Revision,
 }
 
+impl Storable {
+    /// The new revision needs to be cleared by at least
+    /// one organization.
+    pub fn is_pending_clearance(&self) -> bool {
+        !self.clearance_org_ids.is_empty()
+    }
+}
+
 pub fn prepare_updated_place<D: Db>(
     db: &D,
     place_id: Id,
@@ -248,6 +256,7 @@ pub fn store_updated_place<D: Db>(db: &D, s: Storable) -> Result<(Place, Vec<Rat
         };
         super::clearance::place::add_pending_clearance(db, &clearance_org_ids, &pending_clearance)?;
     }
+    super::enqueue_place_webhooks(db, &place, ChangeType::Updated, &clearance_org_ids)?;
     let ratings = db.load_ratings_of_place(place.id.as_ref())?;
     Ok((place, ratings))
 }
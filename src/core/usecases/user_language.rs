@@ -0,0 +1,55 @@
+use crate::core::prelude::*;
+
+pub fn parse_language(language: Option<&str>) -> Result<Option<Language>> {
+    Ok(language
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(|l| l.to_lowercase().parse::<Language>())
+        .transpose()
+        .map_err(|_| ParameterError::Language)?)
+}
+
+/// Changes the language in which emails are sent to the user.
+///
+/// Without a language emails are sent in the default language.
+pub fn change_user_language<D: UserGateway>(
+    db: &D,
+    email: &str,
+    language: Option<&str>,
+) -> Result<User> {
+    let language = parse_language(language)?;
+    let mut user = db.get_user_by_email(email)?;
+    if user.language != language {
+        user.language = language;
+        db.update_user(&user)?;
+    }
+    Ok(user)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockDb;
+    use super::*;
+
+    #[test]
+    fn change_language() {
+        let db = MockDb::default();
+        db.create_test_user("a@example.com", Role::User);
+        let user = change_user_language(&db, "a@example.com", Some(" EN ")).unwrap();
+        assert_eq!(Some(Language::English), user.language);
+        assert_eq!(
+            Some(Language::English),
+            db.get_user_by_email("a@example.com").unwrap().language
+        );
+        assert!(matches!(
+            change_user_language(&db, "a@example.com", Some("xx")),
+            Err(Error::Parameter(ParameterError::Language))
+        ));
+        assert_eq!(
+            None,
+            change_user_language(&db, "a@example.com", None)
+                .unwrap()
+                .language
+        );
+    }
+}
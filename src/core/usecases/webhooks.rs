@@ -0,0 +1,537 @@
+use crate::core::prelude::*;
+use ofdb_core::{
+    gateways::webhook::{WebhookGateway, WebhookRequest},
+    webhook::record_delivery_attempt,
+};
+use std::collections::HashSet;
+
+/// Shared secrets must not be guessable.
+pub const MIN_WEBHOOK_SECRET_LEN: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct NewWebhook {
+    pub url: String,
+    pub secret: String,
+    pub event_types: Vec<String>,
+}
+
+/// Validates a new webhook of an organization.
+///
+/// The host of the URL is resolved and thereby the database
+/// should not be locked while invoking this function.
+pub fn prepare_webhook(
+    gateway: &dyn WebhookGateway,
+    org: &Organization,
+    new_webhook: NewWebhook,
+) -> Result<Webhook> {
+    let NewWebhook {
+        url,
+        secret,
+        event_types,
+    } = new_webhook;
+    let url = url
+        .parse::<Url>()
+        .ok()
+        .filter(|url| url.scheme() == "https" || url.scheme() == "http")
+        .ok_or(ParameterError::Url)?;
+    if let Err(err) = gateway.check_url(&url) {
+        warn!("Rejecting webhook URL {}: {}", url, err);
+        return Err(ParameterError::WebhookUrl.into());
+    }
+    if secret.len() < MIN_WEBHOOK_SECRET_LEN {
+        return Err(ParameterError::WebhookSecret.into());
+    }
+    let mut unique_event_types = Vec::with_capacity(event_types.len());
+    for t in event_types {
+        let t = t
+            .parse::<WebhookEventType>()
+            .map_err(|_| ParameterError::WebhookEventType)?;
+        if !unique_event_types.contains(&t) {
+            unique_event_types.push(t);
+        }
+    }
+    if unique_event_types.is_empty() {
+        return Err(ParameterError::WebhookEventType.into());
+    }
+    Ok(Webhook {
+        id: Id::new(),
+        org_id: org.id.clone(),
+        url,
+        secret,
+        event_types: unique_event_types,
+        created_at: TimestampMs::now(),
+    })
+}
+
+pub fn register_webhook<R: WebhookRepo>(repo: &R, webhook: Webhook) -> Result<Webhook> {
+    repo.create_webhook(&webhook)?;
+    info!(
+        "Registered webhook {} for organization {}",
+        webhook.id, webhook.org_id
+    );
+    Ok(webhook)
+}
+
+pub fn list_webhooks<R: WebhookRepo>(repo: &R, org: &Organization) -> Result<Vec<Webhook>> {
+    Ok(repo.load_webhooks_of_orgs(&[org.id.clone()])?)
+}
+
+// Webhooks of other organizations are treated as if they don't exist.
+fn get_webhook_of_org<R: WebhookRepo>(repo: &R, org: &Organization, id: &Id) -> Result<Webhook> {
+    let webhook = repo.get_webhook(id)?;
+    if webhook.org_id != org.id {
+        return Err(RepoError::NotFound.into());
+    }
+    Ok(webhook)
+}
+
+pub fn delete_webhook<R: WebhookRepo>(repo: &R, org: &Organization, id: &Id) -> Result<()> {
+    let webhook = get_webhook_of_org(repo, org, id)?;
+    repo.delete_webhook(&webhook.id)?;
+    Ok(())
+}
+
+pub fn list_webhook_deliveries<R: WebhookRepo>(
+    repo: &R,
+    org: &Organization,
+    webhook_id: &Id,
+    pagination: &Pagination,
+) -> Result<Vec<WebhookDelivery>> {
+    let webhook = get_webhook_of_org(repo, org, webhook_id)?;
+    Ok(repo.list_webhook_deliveries(&webhook.id, pagination)?)
+}
+
+#[derive(Serialize)]
+struct Payload<'a, T> {
+    id: &'a str,
+    #[serde(rename = "type")]
+    event_type: &'static str,
+    created_at: i64,
+    data: T,
+}
+
+#[derive(Serialize)]
+struct PlacePayloadData<'a> {
+    place_id: &'a str,
+    revision: u64,
+    change: &'static str,
+    title: &'a str,
+    tags: &'a [String],
+}
+
+#[derive(Serialize)]
+struct ClearancePayloadData<'a> {
+    place_id: &'a str,
+    revision: u64,
+}
+
+#[derive(Serialize)]
+struct EventPayloadData<'a> {
+    event_id: &'a str,
+    change: &'static str,
+    title: &'a str,
+    start: i64,
+    tags: &'a [String],
+}
+
+// Organizations that moderate at least one of the given tags
+fn org_ids_by_moderated_tags<R: OrganizationRepo>(repo: &R, tags: &[String]) -> Result<Vec<Id>> {
+    let mut org_ids = Vec::new();
+    for (org_id, moderated_tag) in repo.get_moderated_tags_by_org(None)? {
+        if tags.contains(&moderated_tag.label) && !org_ids.contains(&org_id) {
+            org_ids.push(org_id);
+        }
+    }
+    Ok(org_ids)
+}
+
+fn enqueue_webhook_deliveries<R: WebhookRepo>(
+    repo: &R,
+    org_ids: &[Id],
+    event_type: WebhookEventType,
+    data: impl serde::Serialize,
+) -> Result<usize> {
+    if org_ids.is_empty() {
+        return Ok(0);
+    }
+    let created_at = TimestampMs::now();
+    let deliveries = repo
+        .load_webhooks_of_orgs(org_ids)?
+        .into_iter()
+        .filter(|webhook| webhook.is_subscribed_to(event_type))
+        .map(|webhook| {
+            let id = Id::new();
+            let payload = serde_json::to_string(&Payload {
+                id: id.as_str(),
+                event_type: event_type.as_str(),
+                created_at: created_at.into_inner(),
+                data: &data,
+            })
+            .map_err(|err| Error::Internal(err.to_string()))?;
+            Ok(WebhookDelivery {
+                id,
+                webhook_id: webhook.id,
+                event_type,
+                payload,
+                created_at,
+                attempts: 0,
+                next_attempt_at: Some(created_at),
+                last_attempt_at: None,
+                last_response_status: None,
+                last_error: None,
+                delivered_at: None,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if deliveries.is_empty() {
+        return Ok(0);
+    }
+    Ok(repo.add_webhook_deliveries(&deliveries)?)
+}
+
+/// Adds the notifications about a new revision of a place to
+/// the outbox, including pending clearances.
+pub fn enqueue_place_webhooks<R: OrganizationRepo + WebhookRepo>(
+    repo: &R,
+    place: &Place,
+    change: ChangeType,
+    clearance_org_ids: &[Id],
+) -> Result<usize> {
+    let revision = u64::from(place.revision);
+    let org_ids = org_ids_by_moderated_tags(repo, &place.tags)?;
+    let mut count = enqueue_webhook_deliveries(
+        repo,
+        &org_ids,
+        WebhookEventType::PlaceChanged,
+        PlacePayloadData {
+            place_id: place.id.as_str(),
+            revision,
+            change: change.as_str(),
+            title: &place.title,
+            tags: &place.tags,
+        },
+    )?;
+    count += enqueue_webhook_deliveries(
+        repo,
+        clearance_org_ids,
+        WebhookEventType::ClearancePending,
+        ClearancePayloadData {
+            place_id: place.id.as_str(),
+            revision,
+        },
+    )?;
+    Ok(count)
+}
+
+pub fn enqueue_event_webhooks<R: OrganizationRepo + WebhookRepo>(
+    repo: &R,
+    event: &Event,
+    change: ChangeType,
+) -> Result<usize> {
+    let org_ids = org_ids_by_moderated_tags(repo, &event.tags)?;
+    enqueue_webhook_deliveries(
+        repo,
+        &org_ids,
+        WebhookEventType::EventChanged,
+        EventPayloadData {
+            event_id: event.id.as_str(),
+            change: change.as_str(),
+            title: &event.title,
+            start: event.start.timestamp(),
+            tags: &event.tags,
+        },
+    )
+}
+
+/// Pending deliveries that are due together with their webhook.
+pub fn load_due_webhook_deliveries<R: WebhookRepo>(
+    repo: &R,
+    due_at: TimestampMs,
+    limit: u64,
+) -> Result<Vec<(Webhook, WebhookDelivery)>> {
+    let deliveries = repo.load_due_webhook_deliveries(due_at, limit)?;
+    let mut webhooks: Vec<Webhook> = Vec::new();
+    let mut results = Vec::with_capacity(deliveries.len());
+    for delivery in deliveries {
+        let webhook = match webhooks.iter().find(|w| w.id == delivery.webhook_id) {
+            Some(webhook) => webhook.clone(),
+            None => {
+                let webhook = repo.get_webhook(&delivery.webhook_id)?;
+                webhooks.push(webhook.clone());
+                webhook
+            }
+        };
+        results.push((webhook, delivery));
+    }
+    Ok(results)
+}
+
+/// Tries to deliver a single notification.
+///
+/// The delivery must be stored afterwards to record the outcome.
+pub fn attempt_webhook_delivery(
+    gateway: &dyn WebhookGateway,
+    webhook: &Webhook,
+    mut delivery: WebhookDelivery,
+) -> WebhookDelivery {
+    let result = gateway.post(&WebhookRequest {
+        url: &webhook.url,
+        secret: &webhook.secret,
+        delivery_id: delivery.id.as_str(),
+        event_type: delivery.event_type,
+        payload: &delivery.payload,
+    });
+    if let Err(err) = &result {
+        warn!(
+            "Failed to deliver {} to webhook {}: {}",
+            delivery.id, webhook.id, err
+        );
+    }
+    record_delivery_attempt(&mut delivery, TimestampMs::now(), result);
+    delivery
+}
+
+pub fn store_webhook_delivery_attempts<R: WebhookRepo>(
+    repo: &R,
+    deliveries: &[WebhookDelivery],
+) -> Result<()> {
+    let mut failed = HashSet::new();
+    for delivery in deliveries {
+        if delivery.status() == WebhookDeliveryStatus::Failed {
+            failed.insert(delivery.webhook_id.as_str());
+        }
+        repo.update_webhook_delivery(delivery)?;
+    }
+    for webhook_id in failed {
+        warn!(
+            "Giving up delivering notifications to webhook {}",
+            webhook_id
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockDb;
+    use super::*;
+    use std::cell::RefCell;
+
+    struct RecordingWebhookGateway {
+        status: u16,
+        requests: RefCell<Vec<(String, String)>>,
+    }
+
+    impl WebhookGateway for RecordingWebhookGateway {
+        fn check_url(&self, url: &Url) -> std::result::Result<(), String> {
+            if url.as_str().contains("://127.0.0.1") {
+                Err("loopback address".into())
+            } else {
+                Ok(())
+            }
+        }
+
+        fn post(&self, request: &WebhookRequest) -> std::result::Result<u16, String> {
+            self.requests
+                .borrow_mut()
+                .push((request.url.to_string(), request.payload.to_string()));
+            Ok(self.status)
+        }
+    }
+
+    fn gateway() -> RecordingWebhookGateway {
+        RecordingWebhookGateway {
+            status: 200,
+            requests: Default::default(),
+        }
+    }
+
+    fn register(db: &MockDb, org: &Organization, new_webhook: NewWebhook) -> Result<Webhook> {
+        register_webhook(db, prepare_webhook(&gateway(), org, new_webhook)?)
+    }
+
+    fn org_with_moderated_tag(tag: &str, require_clearance: bool) -> Organization {
+        Organization {
+            id: Id::new(),
+            name: "org".into(),
+            moderated_tags: vec![ModeratedTag {
+                label: tag.into(),
+                allow_add: true,
+                allow_remove: true,
+                require_clearance,
+            }],
+        }
+    }
+
+    fn new_webhook(event_types: &[&str]) -> NewWebhook {
+        NewWebhook {
+            url: "https://example.com/hook".into(),
+            secret: "0123456789abcdef".into(),
+            event_types: event_types.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn reject_invalid_webhooks() {
+        let db = MockDb::default();
+        let org = org_with_moderated_tag("foo", false);
+        assert!(register(&db, &org, new_webhook(&["place.changed"])).is_ok());
+        assert!(register(&db, &org, new_webhook(&[])).is_err());
+        assert!(register(&db, &org, new_webhook(&["place.deleted"])).is_err());
+        assert!(register(
+            &db,
+            &org,
+            NewWebhook {
+                secret: "short".into(),
+                ..new_webhook(&["place.changed"])
+            }
+        )
+        .is_err());
+        assert!(register(
+            &db,
+            &org,
+            NewWebhook {
+                url: "ftp://example.com".into(),
+                ..new_webhook(&["place.changed"])
+            }
+        )
+        .is_err());
+        assert!(matches!(
+            register(
+                &db,
+                &org,
+                NewWebhook {
+                    url: "http://127.0.0.1:8080/hook".into(),
+                    ..new_webhook(&["place.changed"])
+                }
+            ),
+            Err(Error::Parameter(ParameterError::WebhookUrl))
+        ));
+        assert_eq!(1, list_webhooks(&db, &org).unwrap().len());
+    }
+
+    #[test]
+    fn hide_webhooks_of_other_organizations() {
+        let db = MockDb::default();
+        let org = org_with_moderated_tag("foo", false);
+        let other_org = org_with_moderated_tag("bar", false);
+        let webhook = register(&db, &org, new_webhook(&["place.changed"])).unwrap();
+        assert!(list_webhooks(&db, &other_org).unwrap().is_empty());
+        assert!(delete_webhook(&db, &other_org, &webhook.id).is_err());
+        assert!(delete_webhook(&db, &org, &webhook.id).is_ok());
+        assert!(list_webhooks(&db, &org).unwrap().is_empty());
+    }
+
+    #[test]
+    fn enqueue_place_changes_and_pending_clearances() {
+        let mut db = MockDb::default();
+        let org = org_with_moderated_tag("foo", true);
+        let other_org = org_with_moderated_tag("bar", false);
+        db.orgs = vec![org.clone(), other_org.clone()].into();
+        register(&db, &org, new_webhook(&["place.changed"])).unwrap();
+        register(&db, &org, new_webhook(&["clearance.pending"])).unwrap();
+        register(&db, &other_org, new_webhook(&["place.changed"])).unwrap();
+
+        let place = Place::build().id("p").tags(vec!["foo"]).finish();
+        let count =
+            enqueue_place_webhooks(&db, &place, ChangeType::Created, &[org.id.clone()]).unwrap();
+        assert_eq!(2, count);
+        let deliveries = db.webhook_deliveries.borrow();
+        assert_eq!(
+            vec![
+                WebhookEventType::PlaceChanged,
+                WebhookEventType::ClearancePending
+            ],
+            deliveries.iter().map(|d| d.event_type).collect::<Vec<_>>()
+        );
+        let payload: serde_json::Value = serde_json::from_str(&deliveries[0].payload).unwrap();
+        assert_eq!(deliveries[0].id.as_str(), payload["id"]);
+        assert_eq!("place.changed", payload["type"]);
+        assert_eq!("p", payload["data"]["place_id"]);
+        assert_eq!("created", payload["data"]["change"]);
+    }
+
+    #[test]
+    fn enqueue_event_changes() {
+        let mut db = MockDb::default();
+        let org = org_with_moderated_tag("foo", false);
+        db.orgs = vec![org.clone()].into();
+        register(&db, &org, new_webhook(&["event.changed"])).unwrap();
+        let mut event = Event {
+            id: "e".into(),
+            title: "x".into(),
+            description: None,
+            start: chrono::NaiveDateTime::from_timestamp(0, 0),
+            end: None,
+            location: None,
+            contact: None,
+            tags: vec!["bar".into()],
+            homepage: None,
+            created_by: None,
+            registration: None,
+            archived: None,
+            image_url: None,
+            image_link_url: None,
+        };
+        assert_eq!(
+            0,
+            enqueue_event_webhooks(&db, &event, ChangeType::Updated).unwrap()
+        );
+        event.tags.push("foo".into());
+        assert_eq!(
+            1,
+            enqueue_event_webhooks(&db, &event, ChangeType::Archived).unwrap()
+        );
+    }
+
+    #[test]
+    fn deliver_and_retry() {
+        let mut db = MockDb::default();
+        let org = org_with_moderated_tag("foo", false);
+        db.orgs = vec![org.clone()].into();
+        register(&db, &org, new_webhook(&["place.changed"])).unwrap();
+        let place = Place::build().id("p").tags(vec!["foo"]).finish();
+        enqueue_place_webhooks(&db, &place, ChangeType::Updated, &[]).unwrap();
+
+        let failing_gw = RecordingWebhookGateway {
+            status: 500,
+            requests: Default::default(),
+        };
+        let due = load_due_webhook_deliveries(&db, TimestampMs::now(), 10).unwrap();
+        assert_eq!(1, due.len());
+        let attempts: Vec<_> = due
+            .into_iter()
+            .map(|(w, d)| attempt_webhook_delivery(&failing_gw, &w, d))
+            .collect();
+        store_webhook_delivery_attempts(&db, &attempts).unwrap();
+        assert_eq!(1, failing_gw.requests.borrow().len());
+        assert_eq!(
+            "https://example.com/hook",
+            failing_gw.requests.borrow()[0].0
+        );
+        // Not yet due again
+        assert!(load_due_webhook_deliveries(&db, TimestampMs::now(), 10)
+            .unwrap()
+            .is_empty());
+
+        let gw = RecordingWebhookGateway {
+            status: 200,
+            requests: Default::default(),
+        };
+        let later = TimestampMs::from_inner(TimestampMs::now().into_inner() + 60_000);
+        let attempts: Vec<_> = load_due_webhook_deliveries(&db, later, 10)
+            .unwrap()
+            .into_iter()
+            .map(|(w, d)| attempt_webhook_delivery(&gw, &w, d))
+            .collect();
+        store_webhook_delivery_attempts(&db, &attempts).unwrap();
+        let webhook_id = &attempts[0].webhook_id;
+        let history = list_webhook_deliveries(&db, &org, webhook_id, &Default::default()).unwrap();
+        assert_eq!(1, history.len());
+        assert_eq!(2, history[0].attempts);
+        assert_eq!(WebhookDeliveryStatus::Delivered, history[0].status());
+        assert!(load_due_webhook_deliveries(&db, later, 10)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,7 +1,11 @@
 pub mod parse;
+pub mod rate_limit;
+pub mod totp;
 pub mod validate;
 
 use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
 
 pub use ofdb_entities::{geo, nonce, time};
 
@@ -14,6 +18,16 @@ pub fn split_ids(ids: &str) -> Vec<&str> {
         .collect()
 }
 
+/// The hex-encoded SHA-256 hash of a secret token for storing
+/// it in the database.
+pub fn hash_token(token: &str) -> String {
+    let mut hash = String::with_capacity(64);
+    for b in Sha256::digest(token.as_bytes()) {
+        let _ = write!(hash, "{:02x}", b);
+    }
+    hash
+}
+
 lazy_static! {
     static ref HASH_TAG_REGEX: Regex = Regex::new(r"#(?P<tag>\w+((-\w+)*)?)").unwrap();
 }
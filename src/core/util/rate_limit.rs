@@ -0,0 +1,214 @@
+//! Rate limiting with token buckets.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+/// The least recently used bucket is dropped when the
+/// number of buckets exceeds this limit.
+const MAX_BUCKETS: usize = 10_000;
+
+/// The number of requests that are allowed within a period.
+///
+/// All requests may be sent at once, afterwards the bucket
+/// is refilled evenly over the period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    pub requests: u32,
+    pub period: Duration,
+}
+
+impl Quota {
+    pub fn scaled(self, factor: u32) -> Self {
+        Self {
+            requests: self.requests.saturating_mul(factor),
+            period: self.period,
+        }
+    }
+
+    fn refill_interval(&self) -> Duration {
+        self.period / self.requests.max(1)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn refill(&mut self, quota: &Quota, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at);
+        let refilled = elapsed.as_secs_f64() / quota.refill_interval().as_secs_f64();
+        self.tokens = (self.tokens + refilled).min(f64::from(quota.requests));
+        self.updated_at = now;
+    }
+}
+
+#[derive(Debug, Default)]
+struct Buckets {
+    buckets: HashMap<String, (TokenBucket, u64)>,
+    // The keys of all buckets ordered by their last use
+    lru: BTreeMap<u64, String>,
+    last_use: u64,
+}
+
+/// Token buckets of arbitrary clients.
+#[derive(Debug)]
+pub struct RateLimiter {
+    buckets: Mutex<Buckets>,
+    max_buckets: usize,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::with_max_buckets(MAX_BUCKETS)
+    }
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with_max_buckets(max_buckets: usize) -> Self {
+        Self {
+            buckets: Default::default(),
+            max_buckets,
+        }
+    }
+
+    /// Takes a token from the bucket with the given key.
+    ///
+    /// Returns the time to wait until the next token is
+    /// available if the bucket is empty.
+    pub fn check(&self, key: &str, quota: Quota, now: Instant) -> Result<(), Duration> {
+        let mut guard = self.lock();
+        let Buckets {
+            buckets,
+            lru,
+            last_use,
+        } = &mut *guard;
+        *last_use += 1;
+        match buckets.get(key) {
+            Some((_, used)) => {
+                lru.remove(used);
+            }
+            None => {
+                while buckets.len() >= self.max_buckets {
+                    let oldest = match lru.keys().next() {
+                        Some(oldest) => *oldest,
+                        None => break,
+                    };
+                    if let Some(evicted) = lru.remove(&oldest) {
+                        buckets.remove(&evicted);
+                    }
+                }
+            }
+        }
+        let (bucket, used) = buckets.entry(key.to_owned()).or_insert_with(|| {
+            let bucket = TokenBucket {
+                tokens: f64::from(quota.requests),
+                updated_at: now,
+            };
+            (bucket, 0)
+        });
+        *used = *last_use;
+        lru.insert(*last_use, key.to_owned());
+        bucket.refill(&quota, now);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        let missing = 1.0 - bucket.tokens;
+        Err(quota.refill_interval().mul_f64(missing))
+    }
+
+    fn lock(&self) -> MutexGuard<Buckets> {
+        match self.buckets.lock() {
+            Ok(guard) => guard,
+            Err(poison_err) => {
+                log::error!("A poisoned mutex guard for the RateLimiter was found.");
+                poison_err.into_inner()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUOTA: Quota = Quota {
+        requests: 3,
+        period: Duration::from_secs(60),
+    };
+
+    #[test]
+    fn allow_bursts_and_refill_evenly() {
+        let limiter = RateLimiter::new();
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert!(limiter.check("a", QUOTA, start).is_ok());
+        }
+        assert_eq!(
+            Err(Duration::from_secs(20)),
+            limiter.check("a", QUOTA, start)
+        );
+        assert_eq!(
+            Err(Duration::from_secs(5)),
+            limiter.check("a", QUOTA, start + Duration::from_secs(15))
+        );
+        assert!(limiter
+            .check("a", QUOTA, start + Duration::from_secs(20))
+            .is_ok());
+        assert!(limiter
+            .check("a", QUOTA, start + Duration::from_secs(20))
+            .is_err());
+        // Other buckets are not affected
+        assert!(limiter.check("b", QUOTA, start).is_ok());
+        // Never more tokens than requests
+        let later = start + Duration::from_secs(3600);
+        for _ in 0..3 {
+            assert!(limiter.check("a", QUOTA, later).is_ok());
+        }
+        assert!(limiter.check("a", QUOTA, later).is_err());
+    }
+
+    #[test]
+    fn evict_least_recently_used_buckets() {
+        let limiter = RateLimiter::with_max_buckets(2);
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert!(limiter.check("a", QUOTA, now).is_ok());
+        }
+        assert!(limiter.check("b", QUOTA, now).is_ok());
+        assert!(limiter.check("a", QUOTA, now).is_err());
+        // Evicts the bucket of "b"
+        assert!(limiter.check("c", QUOTA, now).is_ok());
+        assert_eq!(2, limiter.lock().buckets.len());
+        assert_eq!(2, limiter.lock().lru.len());
+        assert!(limiter.check("a", QUOTA, now).is_err());
+        // Evicts the bucket of "c"
+        assert!(limiter.check("b", QUOTA, now).is_ok());
+        assert!(limiter.check("a", QUOTA, now).is_err());
+        assert!(limiter.lock().buckets.contains_key("b"));
+        assert!(!limiter.lock().buckets.contains_key("c"));
+    }
+
+    #[test]
+    fn scale_quota() {
+        let limiter = RateLimiter::new();
+        let now = Instant::now();
+        for _ in 0..30 {
+            assert!(limiter.check("org", QUOTA.scaled(10), now).is_ok());
+        }
+        assert_eq!(
+            Err(Duration::from_secs(2)),
+            limiter.check("org", QUOTA.scaled(10), now)
+        );
+    }
+}
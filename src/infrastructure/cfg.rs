@@ -1,9 +1,51 @@
-use std::{collections::HashSet, env};
+use crate::core::{
+    entities::{Role, Url},
+    usecases::{LoginThrottlePolicy, LoginThrottling, OidcProvider},
+    util::rate_limit::Quota,
+};
+use std::{collections::HashSet, env, net::IpAddr, time::Duration};
 
 const DEFAULT_ACCEPTED_LICENSES: &str = "CC0-1.0,ODbL-1.0";
 const DEFAULT_DB_URL: &str = "openfair.db";
 const DB_CONNECTION_POOL_SIZE: u32 = 10;
 const DEFAULT_PROTECT_WITH_CAPTCHA: bool = false;
+const DEFAULT_JWT_ACCESS_TOKEN_LIFETIME: Duration = Duration::from_secs(24 * 60 * 60);
+const DEFAULT_JWT_REFRESH_TOKEN_LIFETIME: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+const DEFAULT_OIDC_SCOPES: &str = "openid email profile";
+const DEFAULT_OIDC_ROLE_CLAIM: &str = "groups";
+const DEFAULT_LOGIN_ACCOUNT_FREE_ATTEMPTS: u32 = 3;
+const DEFAULT_LOGIN_ACCOUNT_LOCKOUT_THRESHOLD: u32 = 10;
+const DEFAULT_LOGIN_ACCOUNT_LOCKOUT_DURATION: Duration = Duration::from_secs(15 * 60);
+const DEFAULT_LOGIN_CLIENT_FREE_ATTEMPTS: u32 = 10;
+const DEFAULT_LOGIN_CLIENT_LOCKOUT_THRESHOLD: u32 = 100;
+const DEFAULT_LOGIN_CLIENT_LOCKOUT_DURATION: Duration = Duration::from_secs(60 * 60);
+const DEFAULT_LOGIN_INITIAL_DELAY: Duration = Duration::from_secs(1);
+const DEFAULT_LOGIN_MAX_DELAY: Duration = Duration::from_secs(60);
+const HOUR: Duration = Duration::from_secs(60 * 60);
+const DEFAULT_RATE_LIMIT_ENTRIES: Quota = Quota {
+    requests: 60,
+    period: HOUR,
+};
+const DEFAULT_RATE_LIMIT_RATINGS: Quota = Quota {
+    requests: 60,
+    period: HOUR,
+};
+const DEFAULT_RATE_LIMIT_USERS: Quota = Quota {
+    requests: 10,
+    period: HOUR,
+};
+const DEFAULT_RATE_LIMIT_CAPTCHA: Quota = Quota {
+    requests: 120,
+    period: HOUR,
+};
+const DEFAULT_RATE_LIMIT_ORGANIZATION_FACTOR: u32 = 10;
+const DEFAULT_PENDING_CLEARANCE_BATCH_PERIOD: Duration = HOUR;
+const DEFAULT_GEOCODE_CACHE_TTL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+// The usage policy of Nominatim allows a single request per second
+const DEFAULT_GEOCODING_DELAY: Duration = Duration::from_secs(1);
+const DEFAULT_PUBLIC_URL: &str = "https://openfairdb.org";
+const DEFAULT_API_URL: &str = "https://api.ofdb.io/v0";
+const DEFAULT_MAP_APP_URL: &str = "https://kartevonmorgen.org";
 
 #[derive(Debug, Clone)]
 pub struct Cfg {
@@ -11,6 +53,63 @@ pub struct Cfg {
     pub db_url: String,
     pub db_connection_pool_size: u32,
     pub protect_with_captcha: bool,
+    /// The key for signing access tokens. A random key is
+    /// generated on startup if missing, i.e. all issued
+    /// tokens become invalid after a restart.
+    pub jwt_secret: Option<String>,
+    pub jwt_access_token_lifetime: Duration,
+    pub jwt_refresh_token_lifetime: Duration,
+    /// The key for signing the unsubscribe links in emails.
+    /// Required for sending emails, otherwise the links of
+    /// sent emails would become invalid after a restart.
+    pub unsubscribe_secret: Option<String>,
+    /// Users with one of these roles are not allowed to log in
+    /// without a second factor.
+    pub totp_required_roles: Vec<Role>,
+    pub oidc_providers: Vec<OidcProvider>,
+    /// Failed logins are throttled per account and per
+    /// IP address of the client.
+    pub login_throttling: LoginThrottling,
+    pub rate_limits: RateLimits,
+    /// Only requests from these reverse proxies may forward
+    /// the IP address of the client.
+    pub trusted_proxies: Vec<IpAddr>,
+    /// New pending clearances are collected for this period
+    /// before the owners and clearance reviewers of an
+    /// organization are notified, `None` disables the
+    /// notifications.
+    pub pending_clearance_batch_period: Option<Duration>,
+    /// Resolved addresses are cached for this period,
+    /// `None` disables the cache.
+    pub geocode_cache_ttl: Option<Duration>,
+    /// The minimum period between subsequent requests to the
+    /// geocoder, that is shared by all requests of the API.
+    pub geocoding_delay: Duration,
+    /// The URL under which the API is publicly reachable,
+    /// e.g. for absolute links in feeds
+    pub api_url: Url,
+    /// The web app that displays the places on a map
+    pub map_app_url: Url,
+    /// The URL under which this instance is publicly reachable
+    pub public_url: Url,
+    /// The web app for reviewing pending clearances that is
+    /// linked in notifications
+    pub clearance_app_url: Url,
+}
+
+/// Limits the requests per client to groups of public
+/// endpoints, `None` disables the limit of a group.
+#[derive(Debug, Clone)]
+pub struct RateLimits {
+    /// Creating and updating places
+    pub entries: Option<Quota>,
+    pub ratings: Option<Quota>,
+    /// Registration and password reset requests
+    pub users: Option<Quota>,
+    pub captcha: Option<Quota>,
+    /// Organizations that authenticate with an API token
+    /// get multiplied quotas.
+    pub organization_factor: u32,
 }
 
 impl Cfg {
@@ -25,6 +124,105 @@ impl Cfg {
         if let Ok(p) = env::var("PROTECT_WITH_CAPTCHA").map(|s| s.to_lowercase()) {
             cfg.protect_with_captcha = p == "true" || p == "1" || p == "yes";
         }
+        if let Ok(secret) = env::var("JWT_SECRET") {
+            cfg.jwt_secret = Some(secret).filter(|s| !s.is_empty());
+        }
+        if let Ok(secret) = env::var("UNSUBSCRIBE_SECRET") {
+            cfg.unsubscribe_secret = Some(secret).filter(|s| !s.is_empty());
+        }
+        if let Some(secs) = env::var("JWT_ACCESS_TOKEN_LIFETIME")
+            .ok()
+            .and_then(|s| s.parse().ok())
+        {
+            cfg.jwt_access_token_lifetime = Duration::from_secs(secs);
+        }
+        if let Some(secs) = env::var("JWT_REFRESH_TOKEN_LIFETIME")
+            .ok()
+            .and_then(|s| s.parse().ok())
+        {
+            cfg.jwt_refresh_token_lifetime = Duration::from_secs(secs);
+        }
+        if let Ok(roles) = env::var("TOTP_REQUIRED_ROLES") {
+            cfg.totp_required_roles = roles
+                .split(',')
+                .map(str::trim)
+                .filter(|r| !r.is_empty())
+                .filter_map(|r| {
+                    let role = parse_role(r);
+                    if role.is_none() {
+                        log::warn!("Ignoring invalid role in TOTP_REQUIRED_ROLES: {}", r);
+                    }
+                    role
+                })
+                .collect();
+        }
+        if let Ok(ids) = env::var("OIDC_PROVIDERS") {
+            cfg.oidc_providers = ids
+                .split(',')
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .filter_map(|id| match oidc_provider_from_env(id) {
+                    Ok(provider) => Some(provider),
+                    Err(err) => {
+                        log::warn!("Ignoring OpenID provider '{}': {}", id, err);
+                        None
+                    }
+                })
+                .collect();
+        }
+        login_throttle_policy_from_env("LOGIN_ACCOUNT_", &mut cfg.login_throttling.account);
+        login_throttle_policy_from_env("LOGIN_CLIENT_", &mut cfg.login_throttling.client);
+        rate_limit_from_env("RATE_LIMIT_ENTRIES", &mut cfg.rate_limits.entries);
+        rate_limit_from_env("RATE_LIMIT_RATINGS", &mut cfg.rate_limits.ratings);
+        rate_limit_from_env("RATE_LIMIT_USERS", &mut cfg.rate_limits.users);
+        rate_limit_from_env("RATE_LIMIT_CAPTCHA", &mut cfg.rate_limits.captcha);
+        if let Some(factor) = env::var("RATE_LIMIT_ORGANIZATION_FACTOR")
+            .ok()
+            .and_then(|s| s.parse().ok())
+        {
+            cfg.rate_limits.organization_factor = factor;
+        }
+        if let Ok(proxies) = env::var("TRUSTED_PROXIES") {
+            cfg.trusted_proxies = proxies
+                .split(',')
+                .map(str::trim)
+                .filter(|ip| !ip.is_empty())
+                .filter_map(|ip| {
+                    let parsed = ip.parse().ok();
+                    if parsed.is_none() {
+                        log::warn!("Ignoring invalid IP address in TRUSTED_PROXIES: {}", ip);
+                    }
+                    parsed
+                })
+                .collect();
+        }
+        optional_duration_from_env(
+            "PENDING_CLEARANCE_BATCH_PERIOD",
+            &mut cfg.pending_clearance_batch_period,
+        );
+        optional_duration_from_env("GEOCODE_CACHE_TTL", &mut cfg.geocode_cache_ttl);
+        if let Some(secs) = env::var("GEOCODING_DELAY")
+            .ok()
+            .and_then(|s| s.parse().ok())
+        {
+            cfg.geocoding_delay = Duration::from_secs(secs);
+        }
+        if let Some(url) = env::var("API_URL").ok().and_then(|s| s.parse().ok()) {
+            cfg.api_url = url;
+        }
+        if let Some(url) = env::var("MAP_APP_URL").ok().and_then(|s| s.parse().ok()) {
+            cfg.map_app_url = url;
+        }
+        if let Some(url) = env::var("PUBLIC_URL").ok().and_then(|s| s.parse().ok()) {
+            cfg.public_url = url;
+            cfg.clearance_app_url = clearance_app_url(&cfg.public_url);
+        }
+        if let Some(url) = env::var("CLEARANCE_APP_URL")
+            .ok()
+            .and_then(|s| s.parse().ok())
+        {
+            cfg.clearance_app_url = url;
+        }
         cfg
     }
 }
@@ -38,11 +236,189 @@ impl Default for Cfg {
         let db_url = DEFAULT_DB_URL.to_string();
         let db_connection_pool_size = DB_CONNECTION_POOL_SIZE;
         let protect_with_captcha = DEFAULT_PROTECT_WITH_CAPTCHA;
+        let public_url: Url = DEFAULT_PUBLIC_URL.parse().unwrap();
+        let clearance_app_url = clearance_app_url(&public_url);
         Self {
             accepted_licenses,
             db_url,
             db_connection_pool_size,
             protect_with_captcha,
+            jwt_secret: None,
+            unsubscribe_secret: None,
+            jwt_access_token_lifetime: DEFAULT_JWT_ACCESS_TOKEN_LIFETIME,
+            jwt_refresh_token_lifetime: DEFAULT_JWT_REFRESH_TOKEN_LIFETIME,
+            totp_required_roles: vec![],
+            oidc_providers: vec![],
+            login_throttling: LoginThrottling {
+                account: LoginThrottlePolicy {
+                    free_attempts: DEFAULT_LOGIN_ACCOUNT_FREE_ATTEMPTS,
+                    initial_delay: DEFAULT_LOGIN_INITIAL_DELAY,
+                    max_delay: DEFAULT_LOGIN_MAX_DELAY,
+                    lockout_threshold: DEFAULT_LOGIN_ACCOUNT_LOCKOUT_THRESHOLD,
+                    lockout_duration: DEFAULT_LOGIN_ACCOUNT_LOCKOUT_DURATION,
+                },
+                client: LoginThrottlePolicy {
+                    free_attempts: DEFAULT_LOGIN_CLIENT_FREE_ATTEMPTS,
+                    initial_delay: DEFAULT_LOGIN_INITIAL_DELAY,
+                    max_delay: DEFAULT_LOGIN_MAX_DELAY,
+                    lockout_threshold: DEFAULT_LOGIN_CLIENT_LOCKOUT_THRESHOLD,
+                    lockout_duration: DEFAULT_LOGIN_CLIENT_LOCKOUT_DURATION,
+                },
+            },
+            rate_limits: RateLimits {
+                entries: Some(DEFAULT_RATE_LIMIT_ENTRIES),
+                ratings: Some(DEFAULT_RATE_LIMIT_RATINGS),
+                users: Some(DEFAULT_RATE_LIMIT_USERS),
+                captcha: Some(DEFAULT_RATE_LIMIT_CAPTCHA),
+                organization_factor: DEFAULT_RATE_LIMIT_ORGANIZATION_FACTOR,
+            },
+            trusted_proxies: vec![],
+            pending_clearance_batch_period: Some(DEFAULT_PENDING_CLEARANCE_BATCH_PERIOD),
+            geocode_cache_ttl: Some(DEFAULT_GEOCODE_CACHE_TTL),
+            geocoding_delay: DEFAULT_GEOCODING_DELAY,
+            api_url: DEFAULT_API_URL.parse().unwrap(),
+            map_app_url: DEFAULT_MAP_APP_URL.parse().unwrap(),
+            public_url,
+            clearance_app_url,
         }
     }
 }
+
+fn clearance_app_url(public_url: &Url) -> Url {
+    format!("{}/clearance", public_url.as_str().trim_end_matches('/'))
+        .parse()
+        .unwrap()
+}
+
+fn parse_role(s: &str) -> Option<Role> {
+    s.trim().to_lowercase().parse().ok()
+}
+
+/// Overrides the settings of a policy with the variables
+/// `<PREFIX><SETTING>`, e.g. `LOGIN_ACCOUNT_LOCKOUT_THRESHOLD`.
+/// All durations are given in seconds.
+fn login_throttle_policy_from_env(prefix: &str, policy: &mut LoginThrottlePolicy) {
+    let var = |name: &str| {
+        let name = format!("{}{}", prefix, name);
+        let value = env::var(&name).ok()?;
+        let parsed = value.trim().parse::<u64>().ok();
+        if parsed.is_none() {
+            log::warn!("Ignoring invalid value of {}: {}", name, value);
+        }
+        parsed
+    };
+    if let Some(n) = var("FREE_ATTEMPTS") {
+        policy.free_attempts = n as u32;
+    }
+    if let Some(secs) = var("INITIAL_DELAY") {
+        policy.initial_delay = Duration::from_secs(secs);
+    }
+    if let Some(secs) = var("MAX_DELAY") {
+        policy.max_delay = Duration::from_secs(secs);
+    }
+    if let Some(n) = var("LOCKOUT_THRESHOLD") {
+        policy.lockout_threshold = n as u32;
+    }
+    if let Some(secs) = var("LOCKOUT_DURATION") {
+        policy.lockout_duration = Duration::from_secs(secs);
+    }
+}
+
+/// Parses a quota of the form `<requests>/<seconds>`,
+/// e.g. `60/3600`, or `off` for disabling the limit.
+fn rate_limit_from_env(name: &str, quota: &mut Option<Quota>) {
+    let value = match env::var(name) {
+        Ok(value) => value,
+        Err(_) => return,
+    };
+    if value.trim().eq_ignore_ascii_case("off") {
+        *quota = None;
+        return;
+    }
+    let mut parts = value.splitn(2, '/');
+    let requests = parts.next().and_then(|r| r.trim().parse().ok());
+    let secs = parts.next().and_then(|s| s.trim().parse().ok());
+    match (requests, secs) {
+        (Some(requests), Some(secs)) if requests > 0 && secs > 0 => {
+            *quota = Some(Quota {
+                requests,
+                period: Duration::from_secs(secs),
+            });
+        }
+        _ => {
+            log::warn!("Ignoring invalid value of {}: {}", name, value);
+        }
+    }
+}
+
+/// Parses a duration in seconds or `off`.
+fn optional_duration_from_env(name: &str, duration: &mut Option<Duration>) {
+    let value = match env::var(name) {
+        Ok(value) => value,
+        Err(_) => return,
+    };
+    if value.trim().eq_ignore_ascii_case("off") {
+        *duration = None;
+    } else if let Ok(secs) = value.trim().parse() {
+        *duration = Some(Duration::from_secs(secs));
+    } else {
+        log::warn!("Ignoring invalid value of {}: {}", name, value);
+    }
+}
+
+/// Reads the settings of a provider from the variables
+/// `OIDC_<ID>_<SETTING>`, e.g. `OIDC_PARTNER_CLIENT_ID`.
+fn oidc_provider_from_env(id: &str) -> Result<OidcProvider, String> {
+    let prefix = format!("OIDC_{}_", id.to_uppercase().replace('-', "_"));
+    let var = |name: &str| env::var(format!("{}{}", prefix, name)).ok();
+    let required = |name: &str| var(name).ok_or_else(|| format!("Missing {}{}", prefix, name));
+    let url = |name: &str| -> Result<Url, String> {
+        required(name)?
+            .parse()
+            .map_err(|_| format!("Invalid URL in {}{}", prefix, name))
+    };
+    // A comma-separated list of <group>=<role>
+    let role_mapping = var("ROLES")
+        .unwrap_or_default()
+        .split(',')
+        .filter(|m| !m.trim().is_empty())
+        .map(|m| {
+            let mut parts = m.splitn(2, '=');
+            let group = parts.next().unwrap_or_default().trim();
+            parts
+                .next()
+                .and_then(parse_role)
+                .filter(|_| !group.is_empty())
+                .map(|role| (group.to_string(), role))
+                .ok_or_else(|| format!("Invalid role mapping in {}ROLES: {}", prefix, m))
+        })
+        .collect::<Result<_, _>>()?;
+    let default_role = match var("DEFAULT_ROLE") {
+        Some(r) => parse_role(&r).ok_or_else(|| format!("Invalid {}DEFAULT_ROLE", prefix))?,
+        None => Role::User,
+    };
+    Ok(OidcProvider {
+        id: id.to_string(),
+        name: var("NAME").unwrap_or_else(|| id.to_string()),
+        authorization_endpoint: url("AUTHORIZATION_ENDPOINT")?,
+        token_endpoint: url("TOKEN_ENDPOINT")?,
+        userinfo_endpoint: url("USERINFO_ENDPOINT")?,
+        client_id: required("CLIENT_ID")?,
+        client_secret: required("CLIENT_SECRET")?,
+        redirect_uri: url("REDIRECT_URI")?,
+        scopes: var("SCOPES")
+            .unwrap_or_else(|| DEFAULT_OIDC_SCOPES.to_string())
+            .split_whitespace()
+            .map(ToString::to_string)
+            .collect(),
+        role_claim: var("ROLE_CLAIM").unwrap_or_else(|| DEFAULT_OIDC_ROLE_CLAIM.to_string()),
+        role_mapping,
+        default_role,
+        email_domains: var("EMAIL_DOMAINS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|d| d.trim().to_lowercase())
+            .filter(|d| !d.is_empty())
+            .collect(),
+    })
+}
@@ -7,7 +7,7 @@ use diesel::{
     prelude::{Connection as DieselConnection, *},
     result::{DatabaseErrorKind, Error as DieselError},
 };
-use std::result;
+use std::{collections::HashMap, result};
 
 type Result<T> = result::Result<T, RepoError>;
 
@@ -1021,6 +1021,8 @@ fn into_new_event_with_tags(
             archived: archived.map(Timestamp::into_inner),
             image_url: image_url.map(Into::into),
             image_link_url: image_link_url.map(Into::into),
+            created_at: None,
+            updated_at: None,
         },
         tags,
     ))
@@ -1036,7 +1038,8 @@ fn resolve_event_id(conn: &SqliteConnection, uid: &str) -> Result<i64> {
 
 impl EventGateway for SqliteConnection {
     fn create_event(&self, e: Event) -> Result<()> {
-        let (new_event, tags) = into_new_event_with_tags(self, e)?;
+        let (mut new_event, tags) = into_new_event_with_tags(self, e)?;
+        new_event.created_at = Some(Timestamp::now().into_inner());
         self.transaction::<_, diesel::result::Error, _>(|| {
             // Insert event
             diesel::insert_into(schema::events::table)
@@ -1067,7 +1070,8 @@ impl EventGateway for SqliteConnection {
 
     fn update_event(&self, event: &Event) -> Result<()> {
         let id = resolve_event_id(self, event.id.as_ref())?;
-        let (new_event, new_tags) = into_new_event_with_tags(self, event.clone())?;
+        let (mut new_event, new_tags) = into_new_event_with_tags(self, event.clone())?;
+        new_event.updated_at = Some(Timestamp::now().into_inner());
         self.transaction::<_, diesel::result::Error, _>(|| {
             use schema::event_tags::dsl as et_dsl;
             use schema::events::dsl as e_dsl;
@@ -1283,6 +1287,93 @@ impl EventGateway for SqliteConnection {
             .collect())
     }
 
+    fn recently_changed_events(
+        &self,
+        params: &RecentlyChangedEntriesParams,
+        pagination: &Pagination,
+    ) -> Result<Vec<(Event, ChangeType, Timestamp)>> {
+        use diesel::sql_types::{BigInt, Bool};
+        use schema::{event_tags::dsl as et_dsl, events::dsl as e_dsl, users::dsl as u_dsl};
+        // Only the most recent change of each event is available
+        const CHANGED_AT: &str = "COALESCE(events.archived, events.updated_at, events.created_at)";
+        let mut query = e_dsl::events
+            .left_outer_join(u_dsl::users)
+            .select((
+                (
+                    e_dsl::id,
+                    e_dsl::uid,
+                    e_dsl::title,
+                    e_dsl::description,
+                    e_dsl::start,
+                    e_dsl::end,
+                    e_dsl::lat,
+                    e_dsl::lng,
+                    e_dsl::street,
+                    e_dsl::zip,
+                    e_dsl::city,
+                    e_dsl::country,
+                    e_dsl::state,
+                    e_dsl::email,
+                    e_dsl::telephone,
+                    e_dsl::homepage,
+                    e_dsl::created_by,
+                    e_dsl::registration,
+                    e_dsl::organizer,
+                    e_dsl::archived,
+                    e_dsl::image_url,
+                    e_dsl::image_link_url,
+                    u_dsl::email.nullable(),
+                ),
+                e_dsl::created_at,
+                e_dsl::updated_at,
+            ))
+            .filter(diesel::dsl::sql::<Bool>(&format!(
+                "{} IS NOT NULL",
+                CHANGED_AT
+            )))
+            .order_by(diesel::dsl::sql::<BigInt>(&format!("{} DESC", CHANGED_AT)))
+            .into_boxed();
+        // Since (inclusive)
+        if let Some(since) = params.since {
+            query = query.filter(
+                diesel::dsl::sql::<Bool>(&format!("{} >= ", CHANGED_AT))
+                    .bind::<BigInt, _>(since.into_seconds()),
+            );
+        }
+        // Until (exclusive)
+        if let Some(until) = params.until {
+            query = query.filter(
+                diesel::dsl::sql::<Bool>(&format!("{} < ", CHANGED_AT))
+                    .bind::<BigInt, _>(until.into_seconds()),
+            );
+        }
+        if let Some(offset) = pagination.offset {
+            query = query.offset(offset as i64);
+        }
+        if let Some(limit) = pagination.limit {
+            query = query.limit(limit as i64);
+        }
+        let rows = query.load::<(models::EventEntity, Option<i64>, Option<i64>)>(self)?;
+        let ids: Vec<_> = rows.iter().map(|(e, _, _)| e.id).collect();
+        let tag_rels = et_dsl::event_tags
+            .filter(et_dsl::event_id.eq_any(ids))
+            .load(self)?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(e, created_at, updated_at)| {
+                let change = if let Some(archived) = e.archived {
+                    (ChangeType::Archived, archived)
+                } else if let Some(updated_at) = updated_at {
+                    (ChangeType::Updated, updated_at)
+                } else {
+                    (ChangeType::Created, created_at?)
+                };
+                let event = util::event_from_event_entity_and_tags(e, &tag_rels);
+                Some((event, change.0, Timestamp::from_inner(change.1)))
+            })
+            .collect())
+    }
+
     fn count_events(&self) -> Result<usize> {
         use schema::events::dsl;
         Ok(dsl::events
@@ -1376,7 +1467,95 @@ impl UserGateway for SqliteConnection {
 
     fn delete_user_by_email(&self, email: &str) -> Result<()> {
         use schema::users::dsl;
-        diesel::delete(dsl::users.filter(dsl::email.eq(email))).execute(self)?;
+        let user_id_subselect = dsl::users.select(dsl::id).filter(dsl::email.eq(email));
+        self.transaction::<_, diesel::result::Error, _>(|| {
+            diesel::delete(
+                schema::user_refresh_token::table
+                    .filter(schema::user_refresh_token::user_rowid.eq_any(user_id_subselect)),
+            )
+            .execute(self)?;
+            diesel::delete(
+                schema::organization_member::table
+                    .filter(schema::organization_member::user_rowid.eq_any(user_id_subselect)),
+            )
+            .execute(self)?;
+            diesel::delete(
+                schema::user_totp::table
+                    .filter(schema::user_totp::user_rowid.eq_any(user_id_subselect)),
+            )
+            .execute(self)?;
+            diesel::delete(
+                schema::user_recovery_code::table
+                    .filter(schema::user_recovery_code::user_rowid.eq_any(user_id_subselect)),
+            )
+            .execute(self)?;
+            diesel::delete(
+                schema::user_oidc_account::table
+                    .filter(schema::user_oidc_account::user_rowid.eq_any(user_id_subselect)),
+            )
+            .execute(self)?;
+            diesel::delete(
+                schema::user_author_notifications::table.filter(
+                    schema::user_author_notifications::user_rowid.eq_any(user_id_subselect),
+                ),
+            )
+            .execute(self)?;
+            diesel::delete(
+                schema::user_email_change::table
+                    .filter(schema::user_email_change::user_rowid.eq_any(user_id_subselect)),
+            )
+            .execute(self)?;
+            diesel::delete(schema::subscription_notification_queue::table.filter(
+                schema::subscription_notification_queue::user_rowid.eq_any(user_id_subselect),
+            ))
+            .execute(self)?;
+            diesel::delete(
+                schema::failed_login::table
+                    .filter(schema::failed_login::kind.eq("account"))
+                    .filter(schema::failed_login::subject.eq(email)),
+            )
+            .execute(self)?;
+            diesel::delete(dsl::users.filter(dsl::email.eq(email))).execute(self)?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    fn change_user_email(&self, email: &str, new_email: &str) -> Result<()> {
+        use schema::users::dsl;
+        let user_id_subselect = dsl::users.select(dsl::id).filter(dsl::email.eq(email));
+        self.transaction::<_, diesel::result::Error, _>(|| {
+            // All other tables reference the user by rowid
+            diesel::delete(
+                schema::user_tokens::table
+                    .filter(schema::user_tokens::user_id.eq_any(user_id_subselect)),
+            )
+            .execute(self)?;
+            diesel::delete(
+                schema::user_email_change::table
+                    .filter(schema::user_email_change::user_rowid.eq_any(user_id_subselect)),
+            )
+            .execute(self)?;
+            // Access tokens are bound to the sessions and the old address
+            diesel::delete(
+                schema::user_refresh_token::table
+                    .filter(schema::user_refresh_token::user_rowid.eq_any(user_id_subselect)),
+            )
+            .execute(self)?;
+            diesel::delete(
+                schema::failed_login::table
+                    .filter(schema::failed_login::kind.eq("account"))
+                    .filter(schema::failed_login::subject.eq(email)),
+            )
+            .execute(self)?;
+            let count = diesel::update(dsl::users.filter(dsl::email.eq(email)))
+                .set(dsl::email.eq(new_email))
+                .execute(self)?;
+            if count == 0 {
+                return Err(diesel::result::Error::NotFound);
+            }
+            Ok(())
+        })?;
         Ok(())
     }
 
@@ -1412,6 +1591,47 @@ impl UserGateway for SqliteConnection {
             .select(diesel::dsl::count(dsl::id))
             .first::<i64>(self)? as usize)
     }
+
+    fn search_users(
+        &self,
+        params: &UserSearchParams,
+        pagination: &Pagination,
+    ) -> Result<Vec<User>> {
+        use schema::users::dsl;
+        let mut query = dsl::users.order_by(dsl::email).into_boxed();
+
+        if let Some(ref email) = params.email {
+            let pattern = format!(
+                "%{}%",
+                email
+                    .replace('\\', "\\\\")
+                    .replace('%', "\\%")
+                    .replace('_', "\\_")
+            );
+            query = query.filter(dsl::email.like(pattern).escape('\\'));
+        }
+        if let Some(role) = params.role {
+            query = query.filter(dsl::role.eq(util::role_into_i16(role)));
+        }
+        if let Some(disabled) = params.disabled {
+            query = query.filter(dsl::disabled.eq(disabled));
+        }
+
+        // Pagination
+        let offset = pagination.offset.unwrap_or(0);
+        if offset > 0 {
+            query = query.offset(offset as i64);
+        }
+        if let Some(limit) = pagination.limit {
+            query = query.limit(limit as i64);
+        }
+
+        Ok(query
+            .load::<models::UserEntity>(self)?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
 }
 
 impl RatingRepository for SqliteConnection {
@@ -1764,6 +1984,14 @@ impl Db for SqliteConnection {
             south_west_lng,
             north_east_lat,
             north_east_lng,
+            name: &new.name,
+            filter_tags: new.filter.tags.join(","),
+            filter_categories: util::ids_into_string(&new.filter.categories),
+            filter_entity_kind: new.filter.entity_kind.map(|k| k.as_str()),
+            filter_change_types: util::subscription_change_types_into_string(
+                &new.filter.change_types,
+            ),
+            delivery_mode: new.delivery_mode.as_str(),
         };
         diesel::insert_into(schema::bbox_subscriptions::table)
             .values(&insertable)
@@ -1771,6 +1999,61 @@ impl Db for SqliteConnection {
         Ok(())
     }
 
+    fn update_bbox_subscription(&self, subscription: &BboxSubscription) -> Result<()> {
+        use schema::bbox_subscriptions::dsl as s_dsl;
+        let (south_west_lat, south_west_lng) = subscription.bbox.southwest().to_lat_lng_deg();
+        let (north_east_lat, north_east_lng) = subscription.bbox.northeast().to_lat_lng_deg();
+        let changeset = models::UpdateBboxSubscription {
+            south_west_lat,
+            south_west_lng,
+            north_east_lat,
+            north_east_lng,
+            name: &subscription.name,
+            filter_tags: subscription.filter.tags.join(","),
+            filter_categories: util::ids_into_string(&subscription.filter.categories),
+            filter_entity_kind: subscription.filter.entity_kind.map(|k| k.as_str()),
+            filter_change_types: util::subscription_change_types_into_string(
+                &subscription.filter.change_types,
+            ),
+            delivery_mode: subscription.delivery_mode.as_str(),
+        };
+        let count = diesel::update(
+            s_dsl::bbox_subscriptions.filter(s_dsl::uid.eq(subscription.id.as_str())),
+        )
+        .set(&changeset)
+        .execute(self)?;
+        if count == 0 {
+            return Err(RepoError::NotFound);
+        }
+        Ok(())
+    }
+
+    fn get_bbox_subscription(&self, id: &Id) -> Result<BboxSubscription> {
+        use schema::bbox_subscriptions::dsl as s_dsl;
+        use schema::users::dsl as u_dsl;
+        Ok(s_dsl::bbox_subscriptions
+            .inner_join(u_dsl::users)
+            .filter(s_dsl::uid.eq(id.as_str()))
+            .select((
+                s_dsl::id,
+                s_dsl::uid,
+                s_dsl::user_id,
+                s_dsl::south_west_lat,
+                s_dsl::south_west_lng,
+                s_dsl::north_east_lat,
+                s_dsl::north_east_lng,
+                s_dsl::name,
+                s_dsl::filter_tags,
+                s_dsl::filter_categories,
+                s_dsl::filter_entity_kind,
+                s_dsl::filter_change_types,
+                s_dsl::delivery_mode,
+                u_dsl::email,
+            ))
+            .first::<models::BboxSubscriptionEntity>(self)?
+            .into())
+    }
+
     fn all_bbox_subscriptions(&self) -> Result<Vec<BboxSubscription>> {
         use schema::bbox_subscriptions::dsl as s_dsl;
         use schema::users::dsl as u_dsl;
@@ -1784,6 +2067,12 @@ impl Db for SqliteConnection {
                 s_dsl::south_west_lng,
                 s_dsl::north_east_lat,
                 s_dsl::north_east_lng,
+                s_dsl::name,
+                s_dsl::filter_tags,
+                s_dsl::filter_categories,
+                s_dsl::filter_entity_kind,
+                s_dsl::filter_change_types,
+                s_dsl::delivery_mode,
                 u_dsl::email,
             ))
             .load::<models::BboxSubscriptionEntity>(self)?
@@ -1805,6 +2094,12 @@ impl Db for SqliteConnection {
                 s_dsl::south_west_lng,
                 s_dsl::north_east_lat,
                 s_dsl::north_east_lng,
+                s_dsl::name,
+                s_dsl::filter_tags,
+                s_dsl::filter_categories,
+                s_dsl::filter_entity_kind,
+                s_dsl::filter_change_types,
+                s_dsl::delivery_mode,
                 u_dsl::email,
             ))
             .load::<models::BboxSubscriptionEntity>(self)?
@@ -1812,14 +2107,43 @@ impl Db for SqliteConnection {
             .map(BboxSubscription::from)
             .collect())
     }
+    fn delete_bbox_subscription(&self, id: &Id) -> Result<()> {
+        use schema::bbox_subscriptions::dsl as s_dsl;
+        use schema::subscription_notification_queue::dsl as q_dsl;
+        let count = self.transaction::<_, diesel::result::Error, _>(|| {
+            let count =
+                diesel::delete(s_dsl::bbox_subscriptions.filter(s_dsl::uid.eq(id.as_str())))
+                    .execute(self)?;
+            // Pending changes are not delivered anymore
+            diesel::delete(
+                q_dsl::subscription_notification_queue
+                    .filter(q_dsl::subscription_uid.eq(id.as_str())),
+            )
+            .execute(self)?;
+            Ok(count)
+        })?;
+        if count == 0 {
+            return Err(RepoError::NotFound);
+        }
+        Ok(())
+    }
     fn delete_bbox_subscriptions_by_email(&self, email: &str) -> Result<()> {
         use schema::bbox_subscriptions::dsl as s_dsl;
+        use schema::subscription_notification_queue::dsl as q_dsl;
         use schema::users::dsl as u_dsl;
         let users_id = u_dsl::users
             .select(u_dsl::id)
             .filter(u_dsl::email.eq(email));
-        diesel::delete(s_dsl::bbox_subscriptions.filter(s_dsl::user_id.eq_any(users_id)))
+        self.transaction::<_, diesel::result::Error, _>(|| {
+            diesel::delete(s_dsl::bbox_subscriptions.filter(s_dsl::user_id.eq_any(users_id)))
+                .execute(self)?;
+            // Pending changes are not delivered anymore
+            diesel::delete(
+                q_dsl::subscription_notification_queue.filter(q_dsl::user_rowid.eq_any(users_id)),
+            )
             .execute(self)?;
+            Ok(())
+        })?;
         Ok(())
     }
     fn all_tags(&self) -> Result<Vec<Tag>> {
@@ -1836,6 +2160,51 @@ impl Db for SqliteConnection {
     }
 }
 
+fn load_organization(conn: &SqliteConnection, model: models::Organization) -> Result<Organization> {
+    use schema::organization_tag::dsl;
+
+    let models::Organization { rowid, id, name } = model;
+
+    let moderated_tags = dsl::organization_tag
+        .filter(dsl::org_rowid.eq(rowid))
+        .load::<models::OrganizationTag>(conn)?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+
+    Ok(Organization {
+        id: id.into(),
+        name,
+        moderated_tags,
+    })
+}
+
+fn insert_organization_tags(
+    conn: &SqliteConnection,
+    org_rowid: i64,
+    moderated_tags: &[ModeratedTag],
+) -> diesel::QueryResult<()> {
+    for ModeratedTag {
+        label,
+        allow_add,
+        allow_remove,
+        require_clearance,
+    } in moderated_tags
+    {
+        let org_tag = models::NewOrganizationTag {
+            org_rowid,
+            tag_label: label,
+            tag_allow_add: if *allow_add { 1 } else { 0 },
+            tag_allow_remove: if *allow_remove { 1 } else { 0 },
+            require_clearance: if *require_clearance { 1 } else { 0 },
+        };
+        diesel::insert_into(schema::organization_tag::table)
+            .values(&org_tag)
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
 impl OrganizationRepo for SqliteConnection {
     fn create_org(&mut self, mut o: Organization) -> Result<()> {
         let org_id = o.id.clone();
@@ -1852,54 +2221,97 @@ impl OrganizationRepo for SqliteConnection {
                 );
                 diesel::result::Error::RollbackTransaction
             })?;
-            for ModeratedTag {
-                label,
-                allow_add,
-                allow_remove,
-                require_clearance,
-            } in &moderated_tags
-            {
-                let org_tag = models::NewOrganizationTag {
-                    org_rowid,
-                    tag_label: label,
-                    tag_allow_add: if *allow_add { 1 } else { 0 },
-                    tag_allow_remove: if *allow_remove { 1 } else { 0 },
-                    require_clearance: if *require_clearance { 1 } else { 0 },
-                };
-                diesel::insert_into(schema::organization_tag::table)
-                    .values(&org_tag)
-                    .execute(self)?;
-            }
-            Ok(())
+            insert_organization_tags(self, org_rowid, &moderated_tags)
         })?;
         Ok(())
     }
 
-    fn get_org_by_api_token(&self, token: &str) -> Result<Organization> {
-        use schema::{organization::dsl as org_dsl, organization_tag::dsl as org_tag_dsl};
+    fn update_org(&self, org: &Organization) -> Result<()> {
+        use schema::organization::dsl;
+        use schema::organization_tag::dsl as tag_dsl;
+        let org_rowid = resolve_organization_rowid(self, &org.id)?;
+        self.transaction::<_, diesel::result::Error, _>(|| {
+            diesel::update(schema::organization::table.filter(dsl::rowid.eq(org_rowid)))
+                .set(dsl::name.eq(&org.name))
+                .execute(self)?;
+            diesel::delete(
+                schema::organization_tag::table.filter(tag_dsl::org_rowid.eq(org_rowid)),
+            )
+            .execute(self)?;
+            insert_organization_tags(self, org_rowid, &org.moderated_tags)
+        })?;
+        Ok(())
+    }
 
-        let models::Organization {
-            rowid,
-            id,
-            name,
-            api_token,
-        } = org_dsl::organization
-            .filter(org_dsl::api_token.eq(token))
-            .first(self)?;
+    fn delete_org(&self, id: &Id) -> Result<()> {
+        use schema::organization::dsl;
+        use schema::organization_api_token::dsl as token_dsl;
+        use schema::organization_place_clearance::dsl as clearance_dsl;
+        use schema::organization_tag::dsl as tag_dsl;
+        use schema::organization_webhook::dsl as webhook_dsl;
+        use schema::organization_webhook_delivery::dsl as delivery_dsl;
+        let org_rowid = resolve_organization_rowid(self, id)?;
+        self.transaction::<_, diesel::result::Error, _>(|| {
+            diesel::delete(
+                schema::organization_webhook_delivery::table.filter(
+                    delivery_dsl::webhook_rowid.eq_any(
+                        schema::organization_webhook::table
+                            .select(webhook_dsl::rowid)
+                            .filter(webhook_dsl::org_rowid.eq(org_rowid)),
+                    ),
+                ),
+            )
+            .execute(self)?;
+            diesel::delete(
+                schema::organization_webhook::table.filter(webhook_dsl::org_rowid.eq(org_rowid)),
+            )
+            .execute(self)?;
+            diesel::delete(
+                schema::organization_api_token::table.filter(token_dsl::org_rowid.eq(org_rowid)),
+            )
+            .execute(self)?;
+            diesel::delete(
+                schema::organization_place_clearance::table
+                    .filter(clearance_dsl::org_rowid.eq(org_rowid)),
+            )
+            .execute(self)?;
+            diesel::delete(
+                schema::organization_clearance_notification::table
+                    .filter(schema::organization_clearance_notification::org_rowid.eq(org_rowid)),
+            )
+            .execute(self)?;
+            diesel::delete(
+                schema::organization_member::table
+                    .filter(schema::organization_member::org_rowid.eq(org_rowid)),
+            )
+            .execute(self)?;
+            diesel::delete(
+                schema::organization_tag::table.filter(tag_dsl::org_rowid.eq(org_rowid)),
+            )
+            .execute(self)?;
+            diesel::delete(schema::organization::table.filter(dsl::rowid.eq(org_rowid)))
+                .execute(self)?;
+            Ok(())
+        })?;
+        Ok(())
+    }
 
-        let moderated_tags = org_tag_dsl::organization_tag
-            .filter(org_tag_dsl::org_rowid.eq(rowid))
-            .load::<models::OrganizationTag>(self)?
+    fn all_orgs(&self) -> Result<Vec<Organization>> {
+        use schema::organization::dsl;
+        dsl::organization
+            .order_by(dsl::name)
+            .load::<models::Organization>(self)?
             .into_iter()
-            .map(Into::into)
-            .collect();
+            .map(|model| load_organization(self, model))
+            .collect()
+    }
 
-        Ok(Organization {
-            id: id.into(),
-            name,
-            api_token,
-            moderated_tags,
-        })
+    fn get_org_by_id(&self, id: &Id) -> Result<Organization> {
+        use schema::organization::dsl;
+        let model = dsl::organization
+            .filter(dsl::id.eq(id.as_str()))
+            .first(self)?;
+        load_organization(self, model)
     }
 
     fn map_tag_to_clearance_org_id(&self, tag: &str) -> Result<Option<Id>> {
@@ -1941,6 +2353,68 @@ impl OrganizationRepo for SqliteConnection {
     }
 }
 
+impl OrganizationMemberRepo for SqliteConnection {
+    fn set_org_member(&self, member: &OrganizationMember) -> Result<()> {
+        let model = models::NewOrganizationMember {
+            org_rowid: resolve_organization_rowid(self, &member.org_id)?,
+            user_rowid: resolve_user_created_by_email(self, &member.email)?,
+            role: member.role.as_str(),
+        };
+        diesel::replace_into(schema::organization_member::table)
+            .values(&model)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn remove_org_member(&self, org_id: &Id, email: &str) -> Result<()> {
+        use schema::organization_member::dsl;
+        let org_rowid = resolve_organization_rowid(self, org_id)?;
+        let user_rowid = resolve_user_created_by_email(self, email)?;
+        let count = diesel::delete(
+            schema::organization_member::table
+                .filter(dsl::org_rowid.eq(org_rowid))
+                .filter(dsl::user_rowid.eq(user_rowid)),
+        )
+        .execute(self)?;
+        if count == 0 {
+            return Err(RepoError::NotFound);
+        }
+        Ok(())
+    }
+
+    fn load_org_members(&self, org_id: &Id) -> Result<Vec<OrganizationMember>> {
+        use schema::organization::dsl as org_dsl;
+        use schema::organization_member::dsl;
+        use schema::users::dsl as u_dsl;
+        Ok(dsl::organization_member
+            .inner_join(org_dsl::organization)
+            .inner_join(u_dsl::users)
+            .select((dsl::role, org_dsl::id, u_dsl::email))
+            .filter(org_dsl::id.eq(org_id.as_str()))
+            .order_by(u_dsl::email)
+            .load::<models::OrganizationMember>(self)?
+            .into_iter()
+            .filter_map(util::org_member_from_model)
+            .collect())
+    }
+
+    fn load_org_memberships_of_user(&self, email: &str) -> Result<Vec<OrganizationMember>> {
+        use schema::organization::dsl as org_dsl;
+        use schema::organization_member::dsl;
+        use schema::users::dsl as u_dsl;
+        Ok(dsl::organization_member
+            .inner_join(org_dsl::organization)
+            .inner_join(u_dsl::users)
+            .select((dsl::role, org_dsl::id, u_dsl::email))
+            .filter(u_dsl::email.eq(email))
+            .order_by(org_dsl::name)
+            .load::<models::OrganizationMember>(self)?
+            .into_iter()
+            .filter_map(util::org_member_from_model)
+            .collect())
+    }
+}
+
 impl PlaceClearanceRepo for SqliteConnection {
     fn add_pending_clearance_for_places(
         &self,
@@ -2124,15 +2598,677 @@ impl PlaceClearanceRepo for SqliteConnection {
         .execute(self)?;
         Ok(delete_count as u64)
     }
-}
 
-impl UserTokenRepo for SqliteConnection {
-    fn replace_user_token(&self, token: UserToken) -> Result<EmailNonce> {
-        use schema::user_tokens::dsl;
-        let user_id = resolve_user_created_by_email(self, &token.email_nonce.email)?;
-        let model = models::NewUserToken {
-            user_id,
-            nonce: token.email_nonce.nonce.to_string(),
+    fn load_pending_clearances_for_places_created_after(
+        &self,
+        org_id: &Id,
+        created_after: Option<TimestampMs>,
+    ) -> Result<Vec<PendingClearanceForPlace>> {
+        let org_rowid = resolve_organization_rowid(self, org_id)?;
+        use schema::organization_place_clearance::dsl;
+        use schema::place::dsl as place_dsl;
+        let mut query = schema::organization_place_clearance::table
+            .inner_join(schema::place::table)
+            .select((place_dsl::id, dsl::created_at, dsl::last_cleared_revision))
+            .filter(dsl::org_rowid.eq(org_rowid))
+            .order_by(dsl::created_at)
+            .into_boxed();
+        if let Some(created_after) = created_after {
+            query = query.filter(dsl::created_at.gt(created_after.into_inner()));
+        }
+        Ok(query
+            .load::<models::PendingClearanceForPlace>(self)?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    fn get_pending_clearances_notified_until(&self, org_id: &Id) -> Result<Option<TimestampMs>> {
+        let org_rowid = resolve_organization_rowid(self, org_id)?;
+        use schema::organization_clearance_notification::dsl;
+        Ok(schema::organization_clearance_notification::table
+            .select(dsl::notified_until)
+            .filter(dsl::org_rowid.eq(org_rowid))
+            .first::<i64>(self)
+            .optional()?
+            .map(TimestampMs::from_inner))
+    }
+
+    fn set_pending_clearances_notified_until(
+        &self,
+        org_id: &Id,
+        notified_until: TimestampMs,
+    ) -> Result<()> {
+        let org_rowid = resolve_organization_rowid(self, org_id)?;
+        let model = models::NewClearanceNotification {
+            org_rowid,
+            notified_until: notified_until.into_inner(),
+        };
+        diesel::replace_into(schema::organization_clearance_notification::table)
+            .values(&model)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn load_org_ids_with_unannounced_pending_clearances(
+        &self,
+        created_until: TimestampMs,
+    ) -> Result<Vec<Id>> {
+        use schema::organization::dsl as org_dsl;
+        use schema::organization_clearance_notification::dsl as notification_dsl;
+        use schema::organization_place_clearance::dsl;
+        let notified_until: HashMap<i64, i64> = schema::organization_clearance_notification::table
+            .select((
+                notification_dsl::org_rowid,
+                notification_dsl::notified_until,
+            ))
+            .load(self)?
+            .into_iter()
+            .collect();
+        let latest_created_at = schema::organization_place_clearance::table
+            .inner_join(schema::organization::table)
+            .select((
+                dsl::org_rowid,
+                org_dsl::id,
+                diesel::dsl::max(dsl::created_at),
+            ))
+            .filter(dsl::created_at.le(created_until.into_inner()))
+            .group_by(dsl::org_rowid)
+            .load::<(i64, String, Option<i64>)>(self)?;
+        Ok(latest_created_at
+            .into_iter()
+            .filter_map(|(org_rowid, org_id, created_at)| {
+                let created_at = created_at?;
+                if let Some(notified_until) = notified_until.get(&org_rowid) {
+                    if created_at <= *notified_until {
+                        return None;
+                    }
+                }
+                Some(org_id.into())
+            })
+            .collect())
+    }
+}
+
+impl ApiTokenRepo for SqliteConnection {
+    fn create_api_token(&self, token: &ApiToken) -> Result<()> {
+        let ApiToken {
+            id,
+            org_id,
+            name,
+            token_hash,
+            scopes,
+            created_at,
+            expires_at,
+            last_used_at,
+        } = token;
+        let org_rowid = resolve_organization_rowid(self, org_id)?;
+        let insertable = models::NewApiToken {
+            org_rowid,
+            id: id.as_str(),
+            name,
+            token_hash,
+            scopes: util::api_token_scopes_into_string(scopes),
+            created_at: created_at.into_inner(),
+            expires_at: expires_at.map(TimestampMs::into_inner),
+            last_used_at: last_used_at.map(TimestampMs::into_inner),
+        };
+        diesel::insert_into(schema::organization_api_token::table)
+            .values(&insertable)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn get_api_token(&self, id: &Id) -> Result<ApiToken> {
+        use schema::organization::dsl as org_dsl;
+        use schema::organization_api_token::dsl;
+        Ok(schema::organization_api_token::table
+            .inner_join(schema::organization::table)
+            .select((
+                dsl::id,
+                dsl::name,
+                dsl::token_hash,
+                dsl::scopes,
+                dsl::created_at,
+                dsl::expires_at,
+                dsl::last_used_at,
+                org_dsl::id,
+            ))
+            .filter(dsl::id.eq(id.as_str()))
+            .first::<models::ApiToken>(self)?
+            .into())
+    }
+
+    fn get_api_token_by_hash(&self, token_hash: &str) -> Result<ApiToken> {
+        use schema::organization::dsl as org_dsl;
+        use schema::organization_api_token::dsl;
+        Ok(schema::organization_api_token::table
+            .inner_join(schema::organization::table)
+            .select((
+                dsl::id,
+                dsl::name,
+                dsl::token_hash,
+                dsl::scopes,
+                dsl::created_at,
+                dsl::expires_at,
+                dsl::last_used_at,
+                org_dsl::id,
+            ))
+            .filter(dsl::token_hash.eq(token_hash))
+            .first::<models::ApiToken>(self)?
+            .into())
+    }
+
+    fn load_api_tokens_of_org(&self, org_id: &Id) -> Result<Vec<ApiToken>> {
+        use schema::organization::dsl as org_dsl;
+        use schema::organization_api_token::dsl;
+        Ok(schema::organization_api_token::table
+            .inner_join(schema::organization::table)
+            .select((
+                dsl::id,
+                dsl::name,
+                dsl::token_hash,
+                dsl::scopes,
+                dsl::created_at,
+                dsl::expires_at,
+                dsl::last_used_at,
+                org_dsl::id,
+            ))
+            .filter(org_dsl::id.eq(org_id.as_str()))
+            .order_by(dsl::created_at)
+            .load::<models::ApiToken>(self)?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    fn replace_api_token_hash(&self, id: &Id, token_hash: &str) -> Result<()> {
+        use schema::organization_api_token::dsl;
+        let count = diesel::update(dsl::organization_api_token.filter(dsl::id.eq(id.as_str())))
+            .set(dsl::token_hash.eq(token_hash))
+            .execute(self)?;
+        if count == 0 {
+            return Err(RepoError::NotFound);
+        }
+        Ok(())
+    }
+
+    fn update_api_token_last_used_at(&self, id: &Id, last_used_at: TimestampMs) -> Result<()> {
+        use schema::organization_api_token::dsl;
+        diesel::update(dsl::organization_api_token.filter(dsl::id.eq(id.as_str())))
+            .set(dsl::last_used_at.eq(last_used_at.into_inner()))
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn delete_api_token(&self, id: &Id) -> Result<()> {
+        use schema::organization_api_token::dsl;
+        let count = diesel::delete(dsl::organization_api_token.filter(dsl::id.eq(id.as_str())))
+            .execute(self)?;
+        if count == 0 {
+            return Err(RepoError::NotFound);
+        }
+        Ok(())
+    }
+}
+
+fn resolve_webhook_rowid(conn: &SqliteConnection, id: &Id) -> Result<i64> {
+    use schema::organization_webhook::dsl;
+    Ok(schema::organization_webhook::table
+        .select(dsl::rowid)
+        .filter(dsl::id.eq(id.as_str()))
+        .first::<i64>(conn)?)
+}
+
+impl WebhookRepo for SqliteConnection {
+    fn create_webhook(&self, webhook: &Webhook) -> Result<()> {
+        let Webhook {
+            id,
+            org_id,
+            url,
+            secret,
+            event_types,
+            created_at,
+        } = webhook;
+        let org_rowid = resolve_organization_rowid(self, org_id)?;
+        let insertable = models::NewWebhook {
+            org_rowid,
+            id: id.as_str(),
+            url: url.as_str(),
+            secret,
+            event_types: util::webhook_event_types_into_string(event_types),
+            created_at: created_at.into_inner(),
+        };
+        diesel::insert_into(schema::organization_webhook::table)
+            .values(&insertable)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn get_webhook(&self, id: &Id) -> Result<Webhook> {
+        use schema::organization::dsl as org_dsl;
+        use schema::organization_webhook::dsl;
+        let model = schema::organization_webhook::table
+            .inner_join(schema::organization::table)
+            .select((
+                dsl::rowid,
+                dsl::id,
+                dsl::url,
+                dsl::secret,
+                dsl::event_types,
+                dsl::created_at,
+                org_dsl::id,
+            ))
+            .filter(dsl::id.eq(id.as_str()))
+            .first::<models::Webhook>(self)?;
+        util::webhook_from_model(model).ok_or(RepoError::NotFound)
+    }
+
+    fn load_webhooks_of_orgs(&self, org_ids: &[Id]) -> Result<Vec<Webhook>> {
+        use schema::organization::dsl as org_dsl;
+        use schema::organization_webhook::dsl;
+        let org_ids: Vec<_> = org_ids.iter().map(Id::as_str).collect();
+        Ok(schema::organization_webhook::table
+            .inner_join(schema::organization::table)
+            .select((
+                dsl::rowid,
+                dsl::id,
+                dsl::url,
+                dsl::secret,
+                dsl::event_types,
+                dsl::created_at,
+                org_dsl::id,
+            ))
+            .filter(org_dsl::id.eq_any(org_ids))
+            .order_by(dsl::created_at)
+            .load::<models::Webhook>(self)?
+            .into_iter()
+            .filter_map(util::webhook_from_model)
+            .collect())
+    }
+
+    fn delete_webhook(&self, id: &Id) -> Result<()> {
+        use schema::organization_webhook::dsl;
+        use schema::organization_webhook_delivery::dsl as delivery_dsl;
+        let rowid = resolve_webhook_rowid(self, id)?;
+        diesel::delete(
+            schema::organization_webhook_delivery::table
+                .filter(delivery_dsl::webhook_rowid.eq(rowid)),
+        )
+        .execute(self)?;
+        diesel::delete(schema::organization_webhook::table.filter(dsl::rowid.eq(rowid)))
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn add_webhook_deliveries(&self, deliveries: &[WebhookDelivery]) -> Result<usize> {
+        let mut insert_count = 0;
+        for delivery in deliveries {
+            let WebhookDelivery {
+                id,
+                webhook_id,
+                event_type,
+                payload,
+                created_at,
+                attempts,
+                next_attempt_at,
+                ..
+            } = delivery;
+            let webhook_rowid = resolve_webhook_rowid(self, webhook_id)?;
+            let insertable = models::NewWebhookDelivery {
+                webhook_rowid,
+                id: id.as_str(),
+                event_type: event_type.as_str(),
+                payload,
+                created_at: created_at.into_inner(),
+                attempts: *attempts as i32,
+                next_attempt_at: next_attempt_at.map(TimestampMs::into_inner),
+            };
+            insert_count += diesel::insert_into(schema::organization_webhook_delivery::table)
+                .values(&insertable)
+                .execute(self)?;
+        }
+        Ok(insert_count)
+    }
+
+    fn load_due_webhook_deliveries(
+        &self,
+        due_at: TimestampMs,
+        limit: u64,
+    ) -> Result<Vec<WebhookDelivery>> {
+        use schema::organization_webhook::dsl as webhook_dsl;
+        use schema::organization_webhook_delivery::dsl;
+        Ok(schema::organization_webhook_delivery::table
+            .inner_join(schema::organization_webhook::table)
+            .select((
+                dsl::id,
+                dsl::event_type,
+                dsl::payload,
+                dsl::created_at,
+                dsl::attempts,
+                dsl::next_attempt_at,
+                dsl::last_attempt_at,
+                dsl::last_response_status,
+                dsl::last_error,
+                dsl::delivered_at,
+                webhook_dsl::id,
+            ))
+            .filter(dsl::next_attempt_at.le(due_at.into_inner()))
+            .order_by(dsl::next_attempt_at)
+            .then_order_by(dsl::rowid)
+            .limit(limit as i64)
+            .load::<models::WebhookDelivery>(self)?
+            .into_iter()
+            .filter_map(util::webhook_delivery_from_model)
+            .collect())
+    }
+
+    fn update_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<()> {
+        use schema::organization_webhook_delivery::dsl;
+        let WebhookDelivery {
+            id,
+            attempts,
+            next_attempt_at,
+            last_attempt_at,
+            last_response_status,
+            last_error,
+            delivered_at,
+            ..
+        } = delivery;
+        let updatable = models::WebhookDeliveryAttempt {
+            attempts: *attempts as i32,
+            next_attempt_at: next_attempt_at.map(TimestampMs::into_inner),
+            last_attempt_at: last_attempt_at.map(TimestampMs::into_inner),
+            last_response_status: last_response_status.map(i32::from),
+            last_error: last_error.as_deref(),
+            delivered_at: delivered_at.map(TimestampMs::into_inner),
+        };
+        let rows_affected = diesel::update(
+            schema::organization_webhook_delivery::table.filter(dsl::id.eq(id.as_str())),
+        )
+        .set(&updatable)
+        .execute(self)?;
+        if rows_affected < 1 {
+            return Err(RepoError::NotFound);
+        }
+        debug_assert_eq!(1, rows_affected);
+        Ok(())
+    }
+
+    fn list_webhook_deliveries(
+        &self,
+        webhook_id: &Id,
+        pagination: &Pagination,
+    ) -> Result<Vec<WebhookDelivery>> {
+        use schema::organization_webhook::dsl as webhook_dsl;
+        use schema::organization_webhook_delivery::dsl;
+        let mut query = schema::organization_webhook_delivery::table
+            .inner_join(schema::organization_webhook::table)
+            .select((
+                dsl::id,
+                dsl::event_type,
+                dsl::payload,
+                dsl::created_at,
+                dsl::attempts,
+                dsl::next_attempt_at,
+                dsl::last_attempt_at,
+                dsl::last_response_status,
+                dsl::last_error,
+                dsl::delivered_at,
+                webhook_dsl::id,
+            ))
+            .filter(webhook_dsl::id.eq(webhook_id.as_str()))
+            .order_by(dsl::created_at.desc())
+            .then_order_by(dsl::rowid.desc())
+            .into_boxed();
+
+        // Pagination
+        let offset = pagination.offset.unwrap_or(0);
+        if offset > 0 {
+            query = query.offset(offset as i64);
+        }
+        if let Some(limit) = pagination.limit {
+            query = query.limit(limit as i64);
+        }
+
+        Ok(query
+            .load::<models::WebhookDelivery>(self)?
+            .into_iter()
+            .filter_map(util::webhook_delivery_from_model)
+            .collect())
+    }
+}
+
+impl EmailOutboxRepo for SqliteConnection {
+    fn add_outgoing_emails(&self, emails: &[OutgoingEmail]) -> Result<usize> {
+        let mut insert_count = 0;
+        for email in emails {
+            let insertable = models::NewOutgoingEmail {
+                id: email.id.as_str(),
+                recipient: &email.recipient,
+                subject: &email.subject,
+                body: &email.body,
+                html_body: email.html_body.as_deref(),
+                unsubscribe_url: email.unsubscribe_url.as_deref(),
+                created_at: email.created_at.into_inner(),
+                attempts: email.attempts as i32,
+                next_attempt_at: email.next_attempt_at.map(TimestampMs::into_inner),
+            };
+            insert_count += diesel::insert_into(schema::email_outbox::table)
+                .values(&insertable)
+                .execute(self)?;
+        }
+        Ok(insert_count)
+    }
+
+    fn load_due_outgoing_emails(
+        &self,
+        due_at: TimestampMs,
+        limit: u64,
+    ) -> Result<Vec<OutgoingEmail>> {
+        use schema::email_outbox::dsl;
+        Ok(schema::email_outbox::table
+            .filter(dsl::next_attempt_at.le(due_at.into_inner()))
+            .order_by(dsl::next_attempt_at)
+            .then_order_by(dsl::rowid)
+            .limit(limit as i64)
+            .load::<models::OutgoingEmail>(self)?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    fn update_outgoing_email(&self, email: &OutgoingEmail) -> Result<()> {
+        use schema::email_outbox::dsl;
+        let updatable = models::OutgoingEmailAttempt {
+            body: &email.body,
+            html_body: email.html_body.as_deref(),
+            unsubscribe_url: email.unsubscribe_url.as_deref(),
+            attempts: email.attempts as i32,
+            next_attempt_at: email.next_attempt_at.map(TimestampMs::into_inner),
+            last_attempt_at: email.last_attempt_at.map(TimestampMs::into_inner),
+            last_error: email.last_error.as_deref(),
+            sent_at: email.sent_at.map(TimestampMs::into_inner),
+        };
+        let rows_affected =
+            diesel::update(schema::email_outbox::table.filter(dsl::id.eq(email.id.as_str())))
+                .set(&updatable)
+                .execute(self)?;
+        if rows_affected < 1 {
+            return Err(RepoError::NotFound);
+        }
+        debug_assert_eq!(1, rows_affected);
+        Ok(())
+    }
+
+    fn load_failed_outgoing_emails(&self, pagination: &Pagination) -> Result<Vec<OutgoingEmail>> {
+        use schema::email_outbox::dsl;
+        let mut query = schema::email_outbox::table
+            .filter(dsl::next_attempt_at.is_null())
+            .filter(dsl::sent_at.is_null())
+            .order_by(dsl::last_attempt_at.desc())
+            .then_order_by(dsl::rowid.desc())
+            .into_boxed();
+
+        // Pagination
+        let offset = pagination.offset.unwrap_or(0);
+        if offset > 0 {
+            query = query.offset(offset as i64);
+        }
+        if let Some(limit) = pagination.limit {
+            query = query.limit(limit as i64);
+        }
+
+        Ok(query
+            .load::<models::OutgoingEmail>(self)?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    fn count_outgoing_emails(&self) -> Result<EmailOutboxStats> {
+        use schema::email_outbox::dsl;
+        let pending = schema::email_outbox::table
+            .filter(dsl::next_attempt_at.is_not_null())
+            .filter(dsl::sent_at.is_null())
+            .count()
+            .get_result::<i64>(self)?;
+        let sent = schema::email_outbox::table
+            .filter(dsl::sent_at.is_not_null())
+            .count()
+            .get_result::<i64>(self)?;
+        let failed = schema::email_outbox::table
+            .filter(dsl::next_attempt_at.is_null())
+            .filter(dsl::sent_at.is_null())
+            .count()
+            .get_result::<i64>(self)?;
+        Ok(EmailOutboxStats {
+            pending: pending as u64,
+            sent: sent as u64,
+            failed: failed as u64,
+        })
+    }
+
+    fn delete_sent_outgoing_emails_before(&self, before: TimestampMs) -> Result<usize> {
+        use schema::email_outbox::dsl;
+        Ok(
+            diesel::delete(
+                schema::email_outbox::table.filter(dsl::sent_at.lt(before.into_inner())),
+            )
+            .execute(self)?,
+        )
+    }
+}
+
+impl ChangeLogRepo for SqliteConnection {
+    fn log_change(&self, change: &EntityChange) -> Result<u64> {
+        let (lat, lng) = change
+            .pos
+            .map(|pos| pos.to_lat_lng_deg())
+            .map_or((None, None), |(lat, lng)| (Some(lat), Some(lng)));
+        let model = models::NewChangeLogEntry {
+            created_at: change.at.into_inner(),
+            entity_kind: change.kind.as_str(),
+            entity_id: change.id.as_str(),
+            place_id: change.place_id.as_ref().map(Id::as_str),
+            change_type: change.change_type.as_str(),
+            title: &change.title,
+            lat,
+            lng,
+            tags: change.tags.join(","),
+        };
+        diesel::insert_into(schema::change_log::table)
+            .values(&model)
+            .execute(self)?;
+        let seq = diesel::select(diesel::dsl::sql::<diesel::sql_types::BigInt>(
+            "last_insert_rowid()",
+        ))
+        .get_result::<i64>(self)?;
+        Ok(seq as u64)
+    }
+
+    fn load_change_log(&self, after_seq: u64, limit: u64) -> Result<Vec<ChangeLogEntry>> {
+        use schema::change_log::dsl;
+        Ok(schema::change_log::table
+            .filter(dsl::rowid.gt(after_seq as i64))
+            .order_by(dsl::rowid)
+            .limit(limit as i64)
+            .load::<models::ChangeLogEntry>(self)?
+            .into_iter()
+            .filter_map(util::change_log_entry_from_model)
+            .collect())
+    }
+
+    fn latest_change_log_seq(&self) -> Result<Option<u64>> {
+        use schema::change_log::dsl;
+        Ok(schema::change_log::table
+            .select(diesel::dsl::max(dsl::rowid))
+            .first::<Option<i64>>(self)?
+            .map(|seq| seq as u64))
+    }
+
+    fn delete_change_log_entries_before(&self, before: TimestampMs) -> Result<usize> {
+        use schema::change_log::dsl;
+        Ok(diesel::delete(
+            schema::change_log::table.filter(dsl::created_at.lt(before.into_inner())),
+        )
+        .execute(self)?)
+    }
+}
+
+impl AuditLogRepo for SqliteConnection {
+    fn add_audit_log_entry(&self, entry: &AuditLogEntry) -> Result<()> {
+        let model = models::NewAuditLogEntry {
+            created_at: entry.at.into_inner(),
+            actor_email: &entry.actor,
+            action: entry.action.as_str(),
+            target_email: &entry.target,
+            details: entry.details.as_deref(),
+        };
+        diesel::insert_into(schema::admin_audit_log::table)
+            .values(&model)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn load_audit_log(
+        &self,
+        target: Option<&str>,
+        pagination: &Pagination,
+    ) -> Result<Vec<AuditLogEntry>> {
+        use schema::admin_audit_log::dsl;
+        let mut query = schema::admin_audit_log::table
+            .order_by(dsl::rowid.desc())
+            .into_boxed();
+
+        if let Some(target) = target {
+            query = query.filter(dsl::target_email.eq(target));
+        }
+
+        // Pagination
+        let offset = pagination.offset.unwrap_or(0);
+        if offset > 0 {
+            query = query.offset(offset as i64);
+        }
+        if let Some(limit) = pagination.limit {
+            query = query.limit(limit as i64);
+        }
+
+        Ok(query
+            .load::<models::AuditLogEntry>(self)?
+            .into_iter()
+            .filter_map(util::audit_log_entry_from_model)
+            .collect())
+    }
+}
+
+impl UserTokenRepo for SqliteConnection {
+    fn replace_user_token(&self, token: UserToken) -> Result<EmailNonce> {
+        use schema::user_tokens::dsl;
+        let user_id = resolve_user_created_by_email(self, &token.email_nonce.email)?;
+        let model = models::NewUserToken {
+            user_id,
+            nonce: token.email_nonce.nonce.to_string(),
             expires_at: token.expires_at.into_inner(),
         };
         // Insert...
@@ -2189,3 +3325,567 @@ impl UserTokenRepo for SqliteConnection {
             .into())
     }
 }
+
+impl EmailChangeRepo for SqliteConnection {
+    fn replace_email_change_token(&self, token: &EmailChangeToken) -> Result<()> {
+        let user_rowid = resolve_user_created_by_email(self, &token.email)?;
+        let model = models::NewUserEmailChange {
+            user_rowid,
+            new_email: &token.new_email_nonce.email,
+            nonce: token.new_email_nonce.nonce.to_string(),
+            expires_at: token.expires_at.into_inner(),
+        };
+        diesel::replace_into(schema::user_email_change::table)
+            .values(&model)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn consume_email_change_token(&self, new_email_nonce: &EmailNonce) -> Result<EmailChangeToken> {
+        use schema::user_email_change::dsl as t_dsl;
+        use schema::users::dsl as u_dsl;
+        let (user_rowid, token) = t_dsl::user_email_change
+            .inner_join(u_dsl::users)
+            .select((
+                t_dsl::user_rowid,
+                (
+                    t_dsl::new_email,
+                    t_dsl::nonce,
+                    t_dsl::expires_at,
+                    u_dsl::email,
+                ),
+            ))
+            .filter(t_dsl::new_email.eq(&new_email_nonce.email))
+            .filter(t_dsl::nonce.eq(new_email_nonce.nonce.to_string()))
+            .first::<(i64, models::UserEmailChange)>(self)?;
+        diesel::delete(t_dsl::user_email_change.filter(t_dsl::user_rowid.eq(user_rowid)))
+            .execute(self)?;
+        Ok(token.into())
+    }
+
+    fn delete_expired_email_change_tokens(&self, expired_before: Timestamp) -> Result<usize> {
+        use schema::user_email_change::dsl;
+        Ok(diesel::delete(
+            dsl::user_email_change.filter(dsl::expires_at.lt(expired_before.into_inner())),
+        )
+        .execute(self)?)
+    }
+}
+
+impl OidcAccountRepo for SqliteConnection {
+    fn create_oidc_account(&self, account: &OidcAccount) -> Result<()> {
+        let user_rowid = resolve_user_created_by_email(self, &account.email)?;
+        let model = models::NewUserOidcAccount {
+            user_rowid,
+            provider: &account.provider,
+            subject: &account.subject,
+            provisioned: account.provisioned,
+            created_at: account.created_at.into_inner(),
+        };
+        diesel::insert_into(schema::user_oidc_account::table)
+            .values(&model)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn get_oidc_account(&self, provider: &str, subject: &str) -> Result<OidcAccount> {
+        use schema::user_oidc_account::dsl as a_dsl;
+        use schema::users::dsl as u_dsl;
+        Ok(a_dsl::user_oidc_account
+            .inner_join(u_dsl::users)
+            .select((
+                a_dsl::provider,
+                a_dsl::subject,
+                a_dsl::provisioned,
+                a_dsl::created_at,
+                u_dsl::email,
+            ))
+            .filter(a_dsl::provider.eq(provider))
+            .filter(a_dsl::subject.eq(subject))
+            .first::<models::UserOidcAccount>(self)?
+            .into())
+    }
+}
+
+impl SubscriptionNotificationRepo for SqliteConnection {
+    fn add_subscription_notifications(
+        &self,
+        notifications: &[SubscriptionNotification],
+    ) -> Result<usize> {
+        let mut count = 0;
+        for n in notifications {
+            let user_rowid = resolve_user_created_by_email(self, &n.user_email)?;
+            let model = models::NewSubscriptionNotification {
+                user_rowid,
+                delivery_mode: n.delivery_mode.as_str(),
+                created_at: n.created_at.into_inner(),
+                entity_kind: n.entity_kind.as_str(),
+                change_type: n.change_type.as_str(),
+                entity_id: n.entity_id.as_str(),
+                title: &n.title,
+                subscription_uid: n.subscription_id.as_ref().map(Id::as_str),
+            };
+            count += diesel::insert_into(schema::subscription_notification_queue::table)
+                .values(&model)
+                .execute(self)?;
+        }
+        Ok(count)
+    }
+
+    fn load_subscription_notifications(
+        &self,
+        delivery_mode: SubscriptionDeliveryMode,
+    ) -> Result<Vec<SubscriptionNotification>> {
+        use schema::subscription_notification_queue::dsl as q_dsl;
+        use schema::users::dsl as u_dsl;
+        Ok(q_dsl::subscription_notification_queue
+            .inner_join(u_dsl::users)
+            .filter(q_dsl::delivery_mode.eq(delivery_mode.as_str()))
+            .select((
+                q_dsl::delivery_mode,
+                q_dsl::created_at,
+                q_dsl::entity_kind,
+                q_dsl::change_type,
+                q_dsl::entity_id,
+                q_dsl::title,
+                q_dsl::subscription_uid,
+                u_dsl::email,
+            ))
+            .order_by(u_dsl::email)
+            .then_order_by(q_dsl::subscription_uid)
+            .then_order_by(q_dsl::created_at)
+            .then_order_by(q_dsl::rowid)
+            .load::<models::SubscriptionNotificationEntity>(self)?
+            .into_iter()
+            .filter_map(util::subscription_notification_from_model)
+            .collect())
+    }
+
+    fn delete_subscription_notifications(
+        &self,
+        user_email: &str,
+        delivery_mode: SubscriptionDeliveryMode,
+        subscription_id: Option<&Id>,
+        created_until: TimestampMs,
+    ) -> Result<usize> {
+        use schema::subscription_notification_queue::dsl as q_dsl;
+        use schema::users::dsl as u_dsl;
+        let user_id_subselect = u_dsl::users
+            .select(u_dsl::id)
+            .filter(u_dsl::email.eq(user_email));
+        let query = diesel::delete(
+            q_dsl::subscription_notification_queue
+                .filter(q_dsl::user_rowid.eq_any(user_id_subselect))
+                .filter(q_dsl::delivery_mode.eq(delivery_mode.as_str()))
+                .filter(q_dsl::created_at.le(created_until.into_inner())),
+        );
+        let count = match subscription_id {
+            Some(id) => query
+                .filter(q_dsl::subscription_uid.eq(id.as_str()))
+                .execute(self)?,
+            None => query
+                .filter(q_dsl::subscription_uid.is_null())
+                .execute(self)?,
+        };
+        Ok(count)
+    }
+}
+
+impl AuthorNotificationRepo for SqliteConnection {
+    fn replace_author_notifications(&self, settings: &AuthorNotifications) -> Result<()> {
+        let user_rowid = resolve_user_created_by_email(self, &settings.user_email)?;
+        let model = models::NewUserAuthorNotifications {
+            user_rowid,
+            reviews: settings.reviews,
+            ratings: settings.ratings,
+            clearances: settings.clearances,
+        };
+        diesel::replace_into(schema::user_author_notifications::table)
+            .values(&model)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn get_author_notifications(&self, email: &str) -> Result<AuthorNotifications> {
+        use schema::user_author_notifications::dsl as n_dsl;
+        use schema::users::dsl as u_dsl;
+        Ok(n_dsl::user_author_notifications
+            .inner_join(u_dsl::users)
+            .select((
+                n_dsl::reviews,
+                n_dsl::ratings,
+                n_dsl::clearances,
+                u_dsl::email,
+            ))
+            .filter(u_dsl::email.eq(email))
+            .first::<models::UserAuthorNotifications>(self)?
+            .into())
+    }
+}
+
+impl TwoFactorRepo for SqliteConnection {
+    fn replace_user_totp(&self, totp: &UserTotp) -> Result<()> {
+        let user_rowid = resolve_user_created_by_email(self, &totp.email)?;
+        let model = models::NewUserTotp {
+            user_rowid,
+            secret: &totp.secret,
+            created_at: totp.created_at.into_inner(),
+            confirmed_at: totp.confirmed_at.map(TimestampMs::into_inner),
+            last_used_step: totp.last_used_step,
+        };
+        diesel::replace_into(schema::user_totp::table)
+            .values(&model)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn get_user_totp(&self, email: &str) -> Result<UserTotp> {
+        use schema::user_totp::dsl as t_dsl;
+        use schema::users::dsl as u_dsl;
+        Ok(t_dsl::user_totp
+            .inner_join(u_dsl::users)
+            .select((
+                t_dsl::secret,
+                t_dsl::created_at,
+                t_dsl::confirmed_at,
+                t_dsl::last_used_step,
+                u_dsl::email,
+            ))
+            .filter(u_dsl::email.eq(email))
+            .first::<models::UserTotp>(self)?
+            .into())
+    }
+
+    fn confirm_user_totp(&self, email: &str, confirmed_at: TimestampMs) -> Result<()> {
+        use schema::user_totp::dsl;
+        let user_rowid = resolve_user_created_by_email(self, email)?;
+        let count = diesel::update(dsl::user_totp.filter(dsl::user_rowid.eq(user_rowid)))
+            .set(dsl::confirmed_at.eq(confirmed_at.into_inner()))
+            .execute(self)?;
+        if count < 1 {
+            return Err(RepoError::NotFound);
+        }
+        Ok(())
+    }
+
+    fn use_user_totp_step(&self, email: &str, step: i64) -> Result<bool> {
+        use schema::user_totp::dsl;
+        let user_rowid = resolve_user_created_by_email(self, email)?;
+        let count = diesel::update(
+            dsl::user_totp
+                .filter(dsl::user_rowid.eq(user_rowid))
+                .filter(
+                    dsl::last_used_step
+                        .is_null()
+                        .or(dsl::last_used_step.lt(step)),
+                ),
+        )
+        .set(dsl::last_used_step.eq(step))
+        .execute(self)?;
+        debug_assert!(count <= 1);
+        Ok(count > 0)
+    }
+
+    fn delete_user_totp(&self, email: &str) -> Result<()> {
+        let user_rowid = resolve_user_created_by_email(self, email)?;
+        diesel::delete(
+            schema::user_recovery_code::table
+                .filter(schema::user_recovery_code::user_rowid.eq(user_rowid)),
+        )
+        .execute(self)?;
+        diesel::delete(
+            schema::user_totp::table.filter(schema::user_totp::user_rowid.eq(user_rowid)),
+        )
+        .execute(self)?;
+        Ok(())
+    }
+
+    fn replace_recovery_codes(&self, email: &str, code_hashes: &[String]) -> Result<()> {
+        use schema::user_recovery_code::dsl;
+        let user_rowid = resolve_user_created_by_email(self, email)?;
+        diesel::delete(dsl::user_recovery_code.filter(dsl::user_rowid.eq(user_rowid)))
+            .execute(self)?;
+        let models: Vec<_> = code_hashes
+            .iter()
+            .map(|code_hash| models::NewUserRecoveryCode {
+                user_rowid,
+                code_hash: code_hash.as_str(),
+                used_at: None,
+            })
+            .collect();
+        diesel::insert_into(schema::user_recovery_code::table)
+            .values(&models)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn use_recovery_code(
+        &self,
+        email: &str,
+        code_hash: &str,
+        used_at: TimestampMs,
+    ) -> Result<bool> {
+        use schema::user_recovery_code::dsl;
+        let user_rowid = resolve_user_created_by_email(self, email)?;
+        let count = diesel::update(
+            dsl::user_recovery_code
+                .filter(dsl::user_rowid.eq(user_rowid))
+                .filter(dsl::code_hash.eq(code_hash))
+                .filter(dsl::used_at.is_null()),
+        )
+        .set(dsl::used_at.eq(used_at.into_inner()))
+        .execute(self)?;
+        debug_assert!(count <= 1);
+        Ok(count > 0)
+    }
+
+    fn count_unused_recovery_codes(&self, email: &str) -> Result<usize> {
+        use schema::user_recovery_code::dsl;
+        let user_rowid = resolve_user_created_by_email(self, email)?;
+        let count = dsl::user_recovery_code
+            .filter(dsl::user_rowid.eq(user_rowid))
+            .filter(dsl::used_at.is_null())
+            .count()
+            .get_result::<i64>(self)?;
+        Ok(count as usize)
+    }
+}
+
+impl LoginThrottleRepo for SqliteConnection {
+    fn get_failed_logins(&self, key: &LoginThrottleKey) -> Result<FailedLogins> {
+        use schema::failed_login::dsl;
+        let (kind, subject) = util::login_throttle_key_into_parts(key);
+        Ok(dsl::failed_login
+            .filter(dsl::kind.eq(kind))
+            .filter(dsl::subject.eq(subject))
+            .first::<models::FailedLogin>(self)?
+            .into())
+    }
+
+    fn replace_failed_logins(&self, failed_logins: &FailedLogins) -> Result<()> {
+        let (kind, subject) = util::login_throttle_key_into_parts(&failed_logins.key);
+        let model = models::FailedLogin {
+            kind: kind.to_string(),
+            subject: subject.to_string(),
+            count: failed_logins.count as i32,
+            last_failed_at: failed_logins.last_failed_at.into_inner(),
+            locked_until: failed_logins.locked_until.map(TimestampMs::into_inner),
+        };
+        diesel::replace_into(schema::failed_login::table)
+            .values(&model)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn delete_failed_logins(&self, key: &LoginThrottleKey) -> Result<()> {
+        use schema::failed_login::dsl;
+        let (kind, subject) = util::login_throttle_key_into_parts(key);
+        diesel::delete(
+            dsl::failed_login
+                .filter(dsl::kind.eq(kind))
+                .filter(dsl::subject.eq(subject)),
+        )
+        .execute(self)?;
+        Ok(())
+    }
+
+    fn delete_failed_logins_before(&self, last_failed_before: TimestampMs) -> Result<usize> {
+        use schema::failed_login::dsl;
+        let before = last_failed_before.into_inner();
+        Ok(diesel::delete(
+            dsl::failed_login
+                .filter(dsl::last_failed_at.lt(before))
+                .filter(dsl::locked_until.is_null().or(dsl::locked_until.le(before))),
+        )
+        .execute(self)?)
+    }
+}
+
+impl GeoCodeCacheRepo for SqliteConnection {
+    fn get_cached_geocode(&self, address: &str) -> Result<CachedGeoCode> {
+        use schema::geocode_cache::dsl;
+        Ok(dsl::geocode_cache
+            .filter(dsl::address.eq(address))
+            .first::<models::CachedGeoCode>(self)?
+            .into())
+    }
+
+    fn replace_cached_geocode(&self, geocode: &CachedGeoCode) -> Result<()> {
+        let model = models::CachedGeoCode {
+            address: geocode.address.clone(),
+            lat: geocode.lat_lng.map(|(lat, _)| lat),
+            lng: geocode.lat_lng.map(|(_, lng)| lng),
+            created_at: geocode.created_at.into_inner(),
+        };
+        diesel::replace_into(schema::geocode_cache::table)
+            .values(&model)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn delete_cached_geocodes_before(&self, created_before: TimestampMs) -> Result<usize> {
+        use schema::geocode_cache::dsl;
+        Ok(diesel::delete(
+            dsl::geocode_cache.filter(dsl::created_at.lt(created_before.into_inner())),
+        )
+        .execute(self)?)
+    }
+
+    fn get_cached_address(&self, pos: &str) -> Result<CachedAddress> {
+        use schema::reverse_geocode_cache::dsl;
+        Ok(dsl::reverse_geocode_cache
+            .filter(dsl::pos.eq(pos))
+            .first::<models::CachedAddress>(self)?
+            .into())
+    }
+
+    fn replace_cached_address(&self, address: &CachedAddress) -> Result<()> {
+        let Address {
+            street,
+            zip,
+            city,
+            country,
+            state,
+        } = address.address.clone().unwrap_or_default();
+        let model = models::CachedAddress {
+            pos: address.pos.clone(),
+            street,
+            zip,
+            city,
+            country,
+            state,
+            created_at: address.created_at.into_inner(),
+        };
+        diesel::replace_into(schema::reverse_geocode_cache::table)
+            .values(&model)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn delete_cached_addresses_before(&self, created_before: TimestampMs) -> Result<usize> {
+        use schema::reverse_geocode_cache::dsl;
+        Ok(diesel::delete(
+            dsl::reverse_geocode_cache.filter(dsl::created_at.lt(created_before.into_inner())),
+        )
+        .execute(self)?)
+    }
+}
+
+impl SessionTokenRepo for SqliteConnection {
+    fn create_refresh_token(&self, token: &RefreshToken) -> Result<()> {
+        let user_rowid = resolve_user_created_by_email(self, &token.email)?;
+        let model = models::NewRefreshToken {
+            user_rowid,
+            session_id: token.session_id.as_str(),
+            token_hash: &token.token_hash,
+            created_at: token.created_at.into_inner(),
+            expires_at: token.expires_at.into_inner(),
+            used_at: token.used_at.map(TimestampMs::into_inner),
+        };
+        diesel::insert_into(schema::user_refresh_token::table)
+            .values(&model)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn get_refresh_token(&self, token_hash: &str) -> Result<RefreshToken> {
+        use schema::user_refresh_token::dsl as t_dsl;
+        use schema::users::dsl as u_dsl;
+        Ok(t_dsl::user_refresh_token
+            .inner_join(u_dsl::users)
+            .select((
+                t_dsl::session_id,
+                t_dsl::token_hash,
+                t_dsl::created_at,
+                t_dsl::expires_at,
+                t_dsl::used_at,
+                u_dsl::email,
+            ))
+            .filter(t_dsl::token_hash.eq(token_hash))
+            .first::<models::RefreshToken>(self)?
+            .into())
+    }
+
+    fn use_refresh_token(&self, token_hash: &str, used_at: TimestampMs) -> Result<bool> {
+        use schema::user_refresh_token::dsl;
+        let count = diesel::update(
+            dsl::user_refresh_token
+                .filter(dsl::token_hash.eq(token_hash))
+                .filter(dsl::used_at.is_null()),
+        )
+        .set(dsl::used_at.eq(used_at.into_inner()))
+        .execute(self)?;
+        debug_assert!(count <= 1);
+        Ok(count > 0)
+    }
+
+    fn delete_refresh_tokens_of_session(&self, session_id: &Id) -> Result<usize> {
+        use schema::user_refresh_token::dsl;
+        Ok(
+            diesel::delete(dsl::user_refresh_token.filter(dsl::session_id.eq(session_id.as_str())))
+                .execute(self)?,
+        )
+    }
+
+    fn delete_refresh_tokens_of_user(&self, email: &str) -> Result<usize> {
+        use schema::user_refresh_token::dsl;
+        use schema::users::dsl as u_dsl;
+        let user_id_subselect = u_dsl::users
+            .select(u_dsl::id)
+            .filter(u_dsl::email.eq(email));
+        Ok(diesel::delete(
+            dsl::user_refresh_token.filter(dsl::user_rowid.eq_any(user_id_subselect)),
+        )
+        .execute(self)?)
+    }
+
+    fn is_session_of_user(&self, session_id: &Id, email: &str) -> Result<bool> {
+        use schema::user_refresh_token::dsl;
+        use schema::users::dsl as u_dsl;
+        let user_id_subselect = u_dsl::users
+            .select(u_dsl::id)
+            .filter(u_dsl::email.eq(email));
+        let count = dsl::user_refresh_token
+            .filter(dsl::session_id.eq(session_id.as_str()))
+            .filter(dsl::user_rowid.eq_any(user_id_subselect))
+            .count()
+            .get_result::<i64>(self)?;
+        Ok(count > 0)
+    }
+
+    fn delete_expired_refresh_tokens(&self, expired_before: TimestampMs) -> Result<usize> {
+        use schema::user_refresh_token::dsl;
+        Ok(diesel::delete(
+            dsl::user_refresh_token.filter(dsl::expires_at.lt(expired_before.into_inner())),
+        )
+        .execute(self)?)
+    }
+
+    fn revoke_access_token(&self, jti: &str, expires_at: TimestampMs) -> Result<()> {
+        let model = models::NewRevokedAccessToken {
+            jti,
+            expires_at: expires_at.into_inner(),
+        };
+        diesel::insert_or_ignore_into(schema::revoked_access_token::table)
+            .values(&model)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn is_access_token_revoked(&self, jti: &str) -> Result<bool> {
+        use schema::revoked_access_token::dsl;
+        let count = dsl::revoked_access_token
+            .filter(dsl::jti.eq(jti))
+            .count()
+            .get_result::<i64>(self)?;
+        Ok(count > 0)
+    }
+
+    fn delete_expired_revoked_access_tokens(&self, expired_before: TimestampMs) -> Result<usize> {
+        use schema::revoked_access_token::dsl;
+        Ok(diesel::delete(
+            dsl::revoked_access_token.filter(dsl::expires_at.lt(expired_before.into_inner())),
+        )
+        .execute(self)?)
+    }
+}
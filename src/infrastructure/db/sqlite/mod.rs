@@ -3,8 +3,9 @@ mod models;
 mod schema;
 mod util;
 
+use crate::core::util::hash_token;
 use anyhow::Result as Fallible;
-use diesel::{r2d2, sqlite::SqliteConnection};
+use diesel::{r2d2, sql_types::Text, sqlite::SqliteConnection};
 use owning_ref::{RwLockReadGuardRef, RwLockWriteGuardRefMut};
 use std::{
     ops::{Deref, DerefMut},
@@ -85,6 +86,19 @@ impl<'a> DerefMut for DbReadWrite<'a> {
     }
 }
 
+// Used by migrations that need to hash API tokens
+sql_function!(fn sha256(token: Text) -> Text);
+
+#[derive(Debug)]
+struct ConnectionCustomizer;
+
+impl r2d2::CustomizeConnection<Connection, r2d2::Error> for ConnectionCustomizer {
+    fn on_acquire(&self, conn: &mut Connection) -> Result<(), r2d2::Error> {
+        sha256::register_impl(conn, |token: String| hash_token(&token))
+            .map_err(r2d2::Error::QueryError)
+    }
+}
+
 #[derive(Clone)]
 pub struct Connections {
     // Only a single connection with write access will be
@@ -101,6 +115,7 @@ impl Connections {
         let manager = ConnectionManager::new(url);
         let pool = ConnectionPool::builder()
             .max_size(pool_size)
+            .connection_customizer(Box::new(ConnectionCustomizer))
             .build(manager)?;
         Ok(Self::new(pool))
     }
@@ -119,3 +134,18 @@ impl Connections {
         DbReadWrite::try_new(&self.pool)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diesel::RunQueryDsl;
+
+    #[test]
+    fn hash_tokens_in_sql() {
+        let connections = Connections::init(":memory:", 1).unwrap();
+        let hash = diesel::select(sha256("secret"))
+            .get_result::<String>(&*connections.shared().unwrap())
+            .unwrap();
+        assert_eq!(hash_token("secret"), hash);
+    }
+}
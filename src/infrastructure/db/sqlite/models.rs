@@ -241,6 +241,8 @@ pub struct NewEvent {
     pub archived: Option<i64>,
     pub image_url: Option<String>,
     pub image_link_url: Option<String>,
+    pub created_at: Option<i64>,
+    pub updated_at: Option<i64>,
 }
 
 #[derive(Queryable)]
@@ -276,7 +278,6 @@ pub struct EventEntity {
 pub struct NewOrganization {
     pub id: String,
     pub name: String,
-    pub api_token: String,
 }
 
 #[derive(Queryable)]
@@ -284,7 +285,6 @@ pub struct Organization {
     pub rowid: i64,
     pub id: String,
     pub name: String,
-    pub api_token: String,
 }
 
 #[derive(Queryable)]
@@ -336,11 +336,14 @@ pub struct Tag {
 
 #[derive(Insertable, AsChangeset)]
 #[table_name = "users"]
+#[changeset_options(treat_none_as_null = "true")]
 pub struct NewUser<'a> {
     pub email: &'a str,
     pub email_confirmed: bool,
     pub password: String,
     pub role: i16,
+    pub disabled: bool,
+    pub language: Option<&'static str>,
 }
 
 #[derive(Queryable)]
@@ -350,6 +353,8 @@ pub struct UserEntity {
     pub email_confirmed: bool,
     pub password: String,
     pub role: i16,
+    pub disabled: bool,
+    pub language: Option<String>,
 }
 
 #[derive(Insertable)]
@@ -361,6 +366,28 @@ pub struct NewBboxSubscription<'a> {
     pub south_west_lng: f64,
     pub north_east_lat: f64,
     pub north_east_lng: f64,
+    pub name: &'a str,
+    pub filter_tags: String,
+    pub filter_categories: String,
+    pub filter_entity_kind: Option<&'a str>,
+    pub filter_change_types: String,
+    pub delivery_mode: &'a str,
+}
+
+#[derive(AsChangeset)]
+#[table_name = "bbox_subscriptions"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct UpdateBboxSubscription<'a> {
+    pub south_west_lat: f64,
+    pub south_west_lng: f64,
+    pub north_east_lat: f64,
+    pub north_east_lng: f64,
+    pub name: &'a str,
+    pub filter_tags: String,
+    pub filter_categories: String,
+    pub filter_entity_kind: Option<&'a str>,
+    pub filter_change_types: String,
+    pub delivery_mode: &'a str,
 }
 
 #[derive(Queryable)]
@@ -372,6 +399,38 @@ pub struct BboxSubscriptionEntity {
     pub south_west_lng: f64,
     pub north_east_lat: f64,
     pub north_east_lng: f64,
+    pub name: String,
+    pub filter_tags: String,
+    pub filter_categories: String,
+    pub filter_entity_kind: Option<String>,
+    pub filter_change_types: String,
+    pub delivery_mode: String,
+    // Joined columns
+    pub user_email: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "subscription_notification_queue"]
+pub struct NewSubscriptionNotification<'a> {
+    pub user_rowid: i64,
+    pub delivery_mode: &'a str,
+    pub created_at: i64,
+    pub entity_kind: &'a str,
+    pub change_type: &'a str,
+    pub entity_id: &'a str,
+    pub title: &'a str,
+    pub subscription_uid: Option<&'a str>,
+}
+
+#[derive(Queryable)]
+pub struct SubscriptionNotificationEntity {
+    pub delivery_mode: String,
+    pub created_at: i64,
+    pub entity_kind: String,
+    pub change_type: String,
+    pub entity_id: String,
+    pub title: String,
+    pub subscription_uid: Option<String>,
     // Joined columns
     pub user_email: String,
 }
@@ -393,6 +452,150 @@ pub struct UserTokenEntity {
     pub user_email: String,
 }
 
+#[derive(Insertable)]
+#[table_name = "user_refresh_token"]
+pub struct NewRefreshToken<'a> {
+    pub user_rowid: i64,
+    pub session_id: &'a str,
+    pub token_hash: &'a str,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub used_at: Option<i64>,
+}
+
+#[derive(Queryable)]
+pub struct RefreshToken {
+    pub session_id: String,
+    pub token_hash: String,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub used_at: Option<i64>,
+    // Joined columns
+    pub user_email: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "user_email_change"]
+pub struct NewUserEmailChange<'a> {
+    pub user_rowid: i64,
+    pub new_email: &'a str,
+    pub nonce: String,
+    pub expires_at: i64,
+}
+
+#[derive(Queryable)]
+pub struct UserEmailChange {
+    pub new_email: String,
+    pub nonce: String,
+    pub expires_at: i64,
+    // Joined columns
+    pub user_email: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "user_totp"]
+pub struct NewUserTotp<'a> {
+    pub user_rowid: i64,
+    pub secret: &'a str,
+    pub created_at: i64,
+    pub confirmed_at: Option<i64>,
+    pub last_used_step: Option<i64>,
+}
+
+#[derive(Queryable)]
+pub struct UserTotp {
+    pub secret: String,
+    pub created_at: i64,
+    pub confirmed_at: Option<i64>,
+    pub last_used_step: Option<i64>,
+    // Joined columns
+    pub user_email: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "user_oidc_account"]
+pub struct NewUserOidcAccount<'a> {
+    pub user_rowid: i64,
+    pub provider: &'a str,
+    pub subject: &'a str,
+    pub provisioned: bool,
+    pub created_at: i64,
+}
+
+#[derive(Queryable)]
+pub struct UserOidcAccount {
+    pub provider: String,
+    pub subject: String,
+    pub provisioned: bool,
+    pub created_at: i64,
+    // Joined columns
+    pub user_email: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "user_author_notifications"]
+pub struct NewUserAuthorNotifications {
+    pub user_rowid: i64,
+    pub reviews: bool,
+    pub ratings: bool,
+    pub clearances: bool,
+}
+
+#[derive(Queryable)]
+pub struct UserAuthorNotifications {
+    pub reviews: bool,
+    pub ratings: bool,
+    pub clearances: bool,
+    // Joined columns
+    pub user_email: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "user_recovery_code"]
+pub struct NewUserRecoveryCode<'a> {
+    pub user_rowid: i64,
+    pub code_hash: &'a str,
+    pub used_at: Option<i64>,
+}
+
+#[derive(Insertable, Queryable)]
+#[table_name = "failed_login"]
+pub struct FailedLogin {
+    pub kind: String,
+    pub subject: String,
+    pub count: i32,
+    pub last_failed_at: i64,
+    pub locked_until: Option<i64>,
+}
+
+#[derive(Insertable, Queryable)]
+#[table_name = "geocode_cache"]
+pub struct CachedGeoCode {
+    pub address: String,
+    pub lat: Option<f64>,
+    pub lng: Option<f64>,
+    pub created_at: i64,
+}
+
+#[derive(Insertable, Queryable)]
+#[table_name = "reverse_geocode_cache"]
+pub struct CachedAddress {
+    pub pos: String,
+    pub street: Option<String>,
+    pub zip: Option<String>,
+    pub city: Option<String>,
+    pub country: Option<String>,
+    pub state: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Insertable)]
+#[table_name = "revoked_access_token"]
+pub struct NewRevokedAccessToken<'a> {
+    pub jti: &'a str,
+    pub expires_at: i64,
+}
+
 #[derive(Insertable, AsChangeset)]
 #[table_name = "organization_place_clearance"]
 #[changeset_options(treat_none_as_null = "true")]
@@ -409,3 +612,208 @@ pub struct PendingClearanceForPlace {
     pub created_at: i64,
     pub last_cleared_revision: Option<i64>,
 }
+
+#[derive(Insertable)]
+#[table_name = "organization_clearance_notification"]
+pub struct NewClearanceNotification {
+    pub org_rowid: i64,
+    pub notified_until: i64,
+}
+
+#[derive(Insertable)]
+#[table_name = "organization_api_token"]
+pub struct NewApiToken<'a> {
+    pub org_rowid: i64,
+    pub id: &'a str,
+    pub name: &'a str,
+    pub token_hash: &'a str,
+    pub scopes: String,
+    pub created_at: i64,
+    pub expires_at: Option<i64>,
+    pub last_used_at: Option<i64>,
+}
+
+#[derive(Queryable)]
+pub struct ApiToken {
+    pub id: String,
+    pub name: String,
+    pub token_hash: String,
+    pub scopes: String,
+    pub created_at: i64,
+    pub expires_at: Option<i64>,
+    pub last_used_at: Option<i64>,
+    // Joined columns
+    pub org_id: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "organization_member"]
+pub struct NewOrganizationMember<'a> {
+    pub org_rowid: i64,
+    pub user_rowid: i64,
+    pub role: &'a str,
+}
+
+#[derive(Queryable)]
+pub struct OrganizationMember {
+    pub role: String,
+    // Joined columns
+    pub org_id: String,
+    pub user_email: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "organization_webhook"]
+pub struct NewWebhook<'a> {
+    pub org_rowid: i64,
+    pub id: &'a str,
+    pub url: &'a str,
+    pub secret: &'a str,
+    pub event_types: String,
+    pub created_at: i64,
+}
+
+#[derive(Queryable)]
+pub struct Webhook {
+    pub rowid: i64,
+    pub id: String,
+    pub url: String,
+    pub secret: String,
+    pub event_types: String,
+    pub created_at: i64,
+    // Joined columns
+    pub org_id: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "organization_webhook_delivery"]
+pub struct NewWebhookDelivery<'a> {
+    pub webhook_rowid: i64,
+    pub id: &'a str,
+    pub event_type: &'a str,
+    pub payload: &'a str,
+    pub created_at: i64,
+    pub attempts: i32,
+    pub next_attempt_at: Option<i64>,
+}
+
+#[derive(AsChangeset)]
+#[table_name = "organization_webhook_delivery"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct WebhookDeliveryAttempt<'a> {
+    pub attempts: i32,
+    pub next_attempt_at: Option<i64>,
+    pub last_attempt_at: Option<i64>,
+    pub last_response_status: Option<i32>,
+    pub last_error: Option<&'a str>,
+    pub delivered_at: Option<i64>,
+}
+
+#[derive(Queryable)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub event_type: String,
+    pub payload: String,
+    pub created_at: i64,
+    pub attempts: i32,
+    pub next_attempt_at: Option<i64>,
+    pub last_attempt_at: Option<i64>,
+    pub last_response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<i64>,
+    // Joined columns
+    pub webhook_id: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "email_outbox"]
+pub struct NewOutgoingEmail<'a> {
+    pub id: &'a str,
+    pub recipient: &'a str,
+    pub subject: &'a str,
+    pub body: &'a str,
+    pub html_body: Option<&'a str>,
+    pub unsubscribe_url: Option<&'a str>,
+    pub created_at: i64,
+    pub attempts: i32,
+    pub next_attempt_at: Option<i64>,
+}
+
+#[derive(AsChangeset)]
+#[table_name = "email_outbox"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct OutgoingEmailAttempt<'a> {
+    pub body: &'a str,
+    pub html_body: Option<&'a str>,
+    pub unsubscribe_url: Option<&'a str>,
+    pub attempts: i32,
+    pub next_attempt_at: Option<i64>,
+    pub last_attempt_at: Option<i64>,
+    pub last_error: Option<&'a str>,
+    pub sent_at: Option<i64>,
+}
+
+#[derive(Queryable)]
+pub struct OutgoingEmail {
+    pub rowid: i64,
+    pub id: String,
+    pub recipient: String,
+    pub subject: String,
+    pub body: String,
+    pub created_at: i64,
+    pub attempts: i32,
+    pub next_attempt_at: Option<i64>,
+    pub last_attempt_at: Option<i64>,
+    pub last_error: Option<String>,
+    pub sent_at: Option<i64>,
+    pub html_body: Option<String>,
+    pub unsubscribe_url: Option<String>,
+}
+
+#[derive(Insertable)]
+#[table_name = "change_log"]
+pub struct NewChangeLogEntry<'a> {
+    pub created_at: i64,
+    pub entity_kind: &'a str,
+    pub entity_id: &'a str,
+    pub place_id: Option<&'a str>,
+    pub change_type: &'a str,
+    pub title: &'a str,
+    pub lat: Option<f64>,
+    pub lng: Option<f64>,
+    pub tags: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "admin_audit_log"]
+pub struct NewAuditLogEntry<'a> {
+    pub created_at: i64,
+    pub actor_email: &'a str,
+    pub action: &'a str,
+    pub target_email: &'a str,
+    pub details: Option<&'a str>,
+}
+
+#[derive(Queryable)]
+pub struct AuditLogEntry {
+    pub rowid: i64,
+    pub created_at: i64,
+    pub actor_email: String,
+    pub action: String,
+    pub target_email: String,
+    pub details: Option<String>,
+}
+
+#[derive(Queryable)]
+pub struct ChangeLogEntry {
+    pub rowid: i64,
+    pub created_at: i64,
+    pub entity_kind: String,
+    pub entity_id: String,
+    pub place_id: Option<String>,
+    pub change_type: String,
+    pub title: String,
+    pub lat: Option<f64>,
+    pub lng: Option<f64>,
+    pub tags: String,
+}
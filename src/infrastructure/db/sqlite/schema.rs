@@ -17,7 +17,6 @@ table! {
         rowid -> BigInt,
         id -> Text,
         name -> Text,
-        api_token -> Text,
     }
 }
 
@@ -47,6 +46,78 @@ table! {
 joinable!(organization_place_clearance -> organization (org_rowid));
 joinable!(organization_place_clearance -> place (place_rowid));
 
+table! {
+    organization_clearance_notification (org_rowid) {
+        org_rowid -> BigInt,
+        notified_until -> BigInt,
+    }
+}
+
+joinable!(organization_clearance_notification -> organization (org_rowid));
+
+table! {
+    organization_api_token (rowid) {
+        rowid -> BigInt,
+        org_rowid -> BigInt,
+        id -> Text,
+        name -> Text,
+        token_hash -> Text,
+        // comma-separated list
+        scopes -> Text,
+        created_at -> BigInt,
+        expires_at -> Nullable<BigInt>,
+        last_used_at -> Nullable<BigInt>,
+    }
+}
+
+joinable!(organization_api_token -> organization (org_rowid));
+
+table! {
+    organization_member (org_rowid, user_rowid) {
+        org_rowid -> BigInt,
+        user_rowid -> BigInt,
+        role -> Text,
+    }
+}
+
+joinable!(organization_member -> organization (org_rowid));
+joinable!(organization_member -> users (user_rowid));
+
+table! {
+    organization_webhook (rowid) {
+        rowid -> BigInt,
+        org_rowid -> BigInt,
+        id -> Text,
+        url -> Text,
+        secret -> Text,
+        // comma-separated list
+        event_types -> Text,
+        created_at -> BigInt,
+    }
+}
+
+joinable!(organization_webhook -> organization (org_rowid));
+
+table! {
+    organization_webhook_delivery (rowid) {
+        rowid -> BigInt,
+        webhook_rowid -> BigInt,
+        id -> Text,
+        event_type -> Text,
+        payload -> Text,
+        created_at -> BigInt,
+        attempts -> Integer,
+        // NULL if either delivered or failed permanently
+        next_attempt_at -> Nullable<BigInt>,
+        last_attempt_at -> Nullable<BigInt>,
+        last_response_status -> Nullable<Integer>,
+        last_error -> Nullable<Text>,
+        delivered_at -> Nullable<BigInt>,
+    }
+}
+
+joinable!(organization_webhook_delivery -> organization_webhook (webhook_rowid));
+
 ///////////////////////////////////////////////////////////////////////
 // Users
 ///////////////////////////////////////////////////////////////////////
@@ -58,6 +129,8 @@ table! {
         email_confirmed -> Bool,
         password -> Text,
         role -> SmallInt,
+        disabled -> Bool,
+        language -> Nullable<Text>,
     }
 }
 
@@ -72,6 +145,116 @@ table! {
 
 joinable!(user_tokens -> users (user_id));
 
+table! {
+    user_email_change (user_rowid) {
+        user_rowid -> BigInt,
+        new_email -> Text,
+        nonce -> Text,
+        expires_at -> BigInt,
+    }
+}
+
+joinable!(user_email_change -> users (user_rowid));
+
+table! {
+    user_refresh_token (rowid) {
+        rowid -> BigInt,
+        user_rowid -> BigInt,
+        session_id -> Text,
+        token_hash -> Text,
+        created_at -> BigInt,
+        expires_at -> BigInt,
+        used_at -> Nullable<BigInt>,
+    }
+}
+
+joinable!(user_refresh_token -> users (user_rowid));
+
+table! {
+    user_totp (user_rowid) {
+        user_rowid -> BigInt,
+        secret -> Text,
+        created_at -> BigInt,
+        confirmed_at -> Nullable<BigInt>,
+        last_used_step -> Nullable<BigInt>,
+    }
+}
+
+joinable!(user_totp -> users (user_rowid));
+
+table! {
+    user_oidc_account (rowid) {
+        rowid -> BigInt,
+        user_rowid -> BigInt,
+        provider -> Text,
+        subject -> Text,
+        provisioned -> Bool,
+        created_at -> BigInt,
+    }
+}
+
+joinable!(user_oidc_account -> users (user_rowid));
+
+table! {
+    user_recovery_code (rowid) {
+        rowid -> BigInt,
+        user_rowid -> BigInt,
+        code_hash -> Text,
+        used_at -> Nullable<BigInt>,
+    }
+}
+
+joinable!(user_recovery_code -> users (user_rowid));
+
+table! {
+    user_author_notifications (user_rowid) {
+        user_rowid -> BigInt,
+        reviews -> Bool,
+        ratings -> Bool,
+        clearances -> Bool,
+    }
+}
+
+joinable!(user_author_notifications -> users (user_rowid));
+
+table! {
+    failed_login (kind, subject) {
+        kind -> Text,
+        subject -> Text,
+        count -> Integer,
+        last_failed_at -> BigInt,
+        locked_until -> Nullable<BigInt>,
+    }
+}
+
+table! {
+    geocode_cache (address) {
+        address -> Text,
+        lat -> Nullable<Double>,
+        lng -> Nullable<Double>,
+        created_at -> BigInt,
+    }
+}
+
+table! {
+    reverse_geocode_cache (pos) {
+        pos -> Text,
+        street -> Nullable<Text>,
+        zip -> Nullable<Text>,
+        city -> Nullable<Text>,
+        country -> Nullable<Text>,
+        state -> Nullable<Text>,
+        created_at -> BigInt,
+    }
+}
+
+table! {
+    revoked_access_token (jti) {
+        jti -> Text,
+        expires_at -> BigInt,
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////
 // Places
 ///////////////////////////////////////////////////////////////////////
@@ -213,6 +396,8 @@ table! {
         archived -> Nullable<BigInt>,
         image_url -> Nullable<Text>,
         image_link_url -> Nullable<Text>,
+        created_at -> Nullable<BigInt>,
+        updated_at -> Nullable<BigInt>,
     }
 }
 
@@ -243,17 +428,105 @@ table! {
         south_west_lng -> Double,
         north_east_lat -> Double,
         north_east_lng -> Double,
+        name -> Text,
+        // comma-separated list
+        filter_tags -> Text,
+        // comma-separated list
+        filter_categories -> Text,
+        filter_entity_kind -> Nullable<Text>,
+        // comma-separated list
+        filter_change_types -> Text,
+        delivery_mode -> Text,
     }
 }
 
 joinable!(bbox_subscriptions -> users (user_id));
 
+table! {
+    subscription_notification_queue (rowid) {
+        rowid -> BigInt,
+        user_rowid -> BigInt,
+        delivery_mode -> Text,
+        created_at -> BigInt,
+        entity_kind -> Text,
+        change_type -> Text,
+        entity_id -> Text,
+        title -> Text,
+        subscription_uid -> Nullable<Text>,
+    }
+}
+
+joinable!(subscription_notification_queue -> users (user_rowid));
+
+///////////////////////////////////////////////////////////////////////
+// Email outbox
+///////////////////////////////////////////////////////////////////////
+
+table! {
+    email_outbox (rowid) {
+        rowid -> BigInt,
+        id -> Text,
+        recipient -> Text,
+        subject -> Text,
+        body -> Text,
+        created_at -> BigInt,
+        attempts -> Integer,
+        // NULL if either sent or failed permanently
+        next_attempt_at -> Nullable<BigInt>,
+        last_attempt_at -> Nullable<BigInt>,
+        last_error -> Nullable<Text>,
+        sent_at -> Nullable<BigInt>,
+        html_body -> Nullable<Text>,
+        unsubscribe_url -> Nullable<Text>,
+    }
+}
+
+///////////////////////////////////////////////////////////////////////
+// Change log
+///////////////////////////////////////////////////////////////////////
+
+table! {
+    change_log (rowid) {
+        rowid -> BigInt,
+        created_at -> BigInt,
+        entity_kind -> Text,
+        entity_id -> Text,
+        // Only for ratings
+        place_id -> Nullable<Text>,
+        change_type -> Text,
+        title -> Text,
+        lat -> Nullable<Double>,
+        lng -> Nullable<Double>,
+        tags -> Text,
+    }
+}
+
+///////////////////////////////////////////////////////////////////////
+// Audit log
+///////////////////////////////////////////////////////////////////////
+
+table! {
+    admin_audit_log (rowid) {
+        rowid -> BigInt,
+        created_at -> BigInt,
+        actor_email -> Text,
+        action -> Text,
+        target_email -> Text,
+        details -> Nullable<Text>,
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////
 
 allow_tables_to_appear_in_same_query!(
+    admin_audit_log,
     bbox_subscriptions,
+    change_log,
+    email_outbox,
     events,
     event_tags,
+    failed_login,
+    geocode_cache,
     place,
     place_rating,
     place_rating_comment,
@@ -261,10 +534,24 @@ allow_tables_to_appear_in_same_query!(
     place_revision_review,
     place_revision_tag,
     place_revision_custom_link,
+    reverse_geocode_cache,
+    revoked_access_token,
+    subscription_notification_queue,
     organization,
     organization_tag,
     organization_place_clearance,
+    organization_clearance_notification,
+    organization_api_token,
+    organization_member,
+    organization_webhook,
+    organization_webhook_delivery,
     tags,
     users,
+    user_author_notifications,
+    user_email_change,
+    user_oidc_account,
+    user_recovery_code,
+    user_refresh_token,
     user_tokens,
+    user_totp,
 );
@@ -178,17 +178,23 @@ impl From<e::Tag> for Tag {
     }
 }
 
+pub(crate) fn role_into_i16(role: e::Role) -> i16 {
+    use num_traits::ToPrimitive;
+    role.to_i16().unwrap_or_else(|| {
+        warn!("Could not convert role {:?} to i16. Use 0 instead.", role);
+        0
+    })
+}
+
 impl<'a> From<&'a e::User> for NewUser<'a> {
     fn from(u: &'a e::User) -> NewUser<'a> {
-        use num_traits::ToPrimitive;
         Self {
             email: &u.email,
             email_confirmed: u.email_confirmed,
             password: u.password.to_string(),
-            role: u.role.to_i16().unwrap_or_else(|| {
-                warn!("Could not convert role {:?} to i16. Use 0 instead.", u.role);
-                0
-            }),
+            role: role_into_i16(u.role),
+            disabled: u.disabled,
+            language: u.language.map(e::Language::as_str),
         }
     }
 }
@@ -201,8 +207,15 @@ impl From<UserEntity> for e::User {
             email_confirmed,
             password,
             role,
+            disabled,
+            language,
             ..
         } = u;
+        let language = language.and_then(|l| {
+            l.parse()
+                .map_err(|_| warn!("Invalid language of user {}: {}", email, l))
+                .ok()
+        });
         Self {
             email,
             email_confirmed,
@@ -215,6 +228,8 @@ impl From<UserEntity> for e::User {
                 );
                 e::Role::default()
             }),
+            disabled,
+            language,
         }
     }
 }
@@ -265,6 +280,56 @@ impl From<PlaceRating> for e::Rating {
     }
 }
 
+pub(crate) fn ids_into_string(ids: &[e::Id]) -> String {
+    ids.iter().map(e::Id::as_str).collect::<Vec<_>>().join(",")
+}
+
+pub(crate) fn subscription_change_types_into_string(
+    change_types: &[e::SubscriptionChangeType],
+) -> String {
+    change_types
+        .iter()
+        .map(|t| t.as_str())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn load_subscription_entity_kind(entity_kind: &str) -> Option<e::SubscriptionEntityKind> {
+    match entity_kind.parse() {
+        Ok(entity_kind) => Some(entity_kind),
+        Err(_) => {
+            // The database should only contain valid entity kinds
+            log::error!("Failed to load subscription entity kind '{}'", entity_kind);
+            None
+        }
+    }
+}
+
+fn load_subscription_change_type(change_type: &str) -> Option<e::SubscriptionChangeType> {
+    match change_type.parse() {
+        Ok(change_type) => Some(change_type),
+        Err(_) => {
+            // The database should only contain valid change types
+            log::error!("Failed to load subscription change type '{}'", change_type);
+            None
+        }
+    }
+}
+
+fn load_subscription_delivery_mode(delivery_mode: &str) -> Option<e::SubscriptionDeliveryMode> {
+    match delivery_mode.parse() {
+        Ok(delivery_mode) => Some(delivery_mode),
+        Err(_) => {
+            // The database should only contain valid delivery modes
+            log::error!(
+                "Failed to load subscription delivery mode '{}'",
+                delivery_mode
+            );
+            None
+        }
+    }
+}
+
 impl From<BboxSubscriptionEntity> for e::BboxSubscription {
     fn from(from: BboxSubscriptionEntity) -> Self {
         let BboxSubscriptionEntity {
@@ -274,6 +339,12 @@ impl From<BboxSubscriptionEntity> for e::BboxSubscription {
             south_west_lng,
             north_east_lat,
             north_east_lng,
+            name,
+            filter_tags,
+            filter_categories,
+            filter_entity_kind,
+            filter_change_types,
+            delivery_mode,
             ..
         } = from;
         let south_west =
@@ -281,14 +352,62 @@ impl From<BboxSubscriptionEntity> for e::BboxSubscription {
         let north_east =
             MapPoint::try_from_lat_lng_deg(north_east_lat, north_east_lng).unwrap_or_default();
         let bbox = MapBbox::new(south_west, north_east);
+        let filter = e::SubscriptionFilter {
+            tags: filter_tags
+                .split(',')
+                .filter(|t| !t.is_empty())
+                .map(ToOwned::to_owned)
+                .collect(),
+            categories: filter_categories
+                .split(',')
+                .filter(|c| !c.is_empty())
+                .map(Into::into)
+                .collect(),
+            entity_kind: filter_entity_kind
+                .as_deref()
+                .and_then(load_subscription_entity_kind),
+            change_types: filter_change_types
+                .split(',')
+                .filter(|t| !t.is_empty())
+                .filter_map(load_subscription_change_type)
+                .collect(),
+        };
         Self {
             id: uid.into(),
             user_email,
+            name,
             bbox,
+            filter,
+            delivery_mode: load_subscription_delivery_mode(&delivery_mode).unwrap_or_default(),
         }
     }
 }
 
+pub(crate) fn subscription_notification_from_model(
+    from: SubscriptionNotificationEntity,
+) -> Option<e::SubscriptionNotification> {
+    let SubscriptionNotificationEntity {
+        delivery_mode,
+        created_at,
+        entity_kind,
+        change_type,
+        entity_id,
+        title,
+        subscription_uid,
+        user_email,
+    } = from;
+    Some(e::SubscriptionNotification {
+        user_email,
+        subscription_id: subscription_uid.map(Into::into),
+        delivery_mode: load_subscription_delivery_mode(&delivery_mode)?,
+        created_at: e::TimestampMs::from_inner(created_at),
+        entity_kind: load_subscription_entity_kind(&entity_kind)?,
+        change_type: load_subscription_change_type(&change_type)?,
+        entity_id: entity_id.into(),
+        title,
+    })
+}
+
 impl From<UserTokenEntity> for e::UserToken {
     fn from(from: UserTokenEntity) -> Self {
         Self {
@@ -301,6 +420,131 @@ impl From<UserTokenEntity> for e::UserToken {
     }
 }
 
+impl From<UserEmailChange> for e::EmailChangeToken {
+    fn from(from: UserEmailChange) -> Self {
+        let UserEmailChange {
+            new_email,
+            nonce,
+            expires_at,
+            user_email,
+        } = from;
+        Self {
+            email: user_email,
+            new_email_nonce: e::EmailNonce {
+                email: new_email,
+                nonce: nonce.parse::<Nonce>().unwrap_or_default(),
+            },
+            expires_at: Timestamp::from_inner(expires_at),
+        }
+    }
+}
+
+impl From<RefreshToken> for e::RefreshToken {
+    fn from(from: RefreshToken) -> Self {
+        let RefreshToken {
+            session_id,
+            token_hash,
+            created_at,
+            expires_at,
+            used_at,
+            user_email,
+        } = from;
+        Self {
+            token_hash,
+            session_id: session_id.into(),
+            email: user_email,
+            created_at: e::TimestampMs::from_inner(created_at),
+            expires_at: e::TimestampMs::from_inner(expires_at),
+            used_at: used_at.map(e::TimestampMs::from_inner),
+        }
+    }
+}
+
+impl From<UserTotp> for e::UserTotp {
+    fn from(from: UserTotp) -> Self {
+        let UserTotp {
+            secret,
+            created_at,
+            confirmed_at,
+            last_used_step,
+            user_email,
+        } = from;
+        Self {
+            email: user_email,
+            secret,
+            created_at: e::TimestampMs::from_inner(created_at),
+            confirmed_at: confirmed_at.map(e::TimestampMs::from_inner),
+            last_used_step,
+        }
+    }
+}
+
+impl From<UserOidcAccount> for e::OidcAccount {
+    fn from(from: UserOidcAccount) -> Self {
+        let UserOidcAccount {
+            provider,
+            subject,
+            provisioned,
+            created_at,
+            user_email,
+        } = from;
+        Self {
+            provider,
+            subject,
+            email: user_email,
+            provisioned,
+            created_at: e::TimestampMs::from_inner(created_at),
+        }
+    }
+}
+
+impl From<UserAuthorNotifications> for e::AuthorNotifications {
+    fn from(from: UserAuthorNotifications) -> Self {
+        let UserAuthorNotifications {
+            reviews,
+            ratings,
+            clearances,
+            user_email,
+        } = from;
+        Self {
+            user_email,
+            reviews,
+            ratings,
+            clearances,
+        }
+    }
+}
+
+pub(crate) fn login_throttle_key_into_parts(key: &e::LoginThrottleKey) -> (&'static str, &str) {
+    match key {
+        e::LoginThrottleKey::Account(email) => ("account", email),
+        e::LoginThrottleKey::Client(ip) => ("client", ip),
+    }
+}
+
+impl From<FailedLogin> for e::FailedLogins {
+    fn from(from: FailedLogin) -> Self {
+        let FailedLogin {
+            kind,
+            subject,
+            count,
+            last_failed_at,
+            locked_until,
+        } = from;
+        let key = match kind.as_str() {
+            "account" => e::LoginThrottleKey::Account(subject),
+            "client" => e::LoginThrottleKey::Client(subject),
+            _ => unreachable!("Invalid kind of failed login: {}", kind),
+        };
+        Self {
+            key,
+            count: count as u32,
+            last_failed_at: e::TimestampMs::from_inner(last_failed_at),
+            locked_until: locked_until.map(e::TimestampMs::from_inner),
+        }
+    }
+}
+
 pub(crate) fn rating_context_to_string(context: e::RatingContext) -> String {
     match context {
         e::RatingContext::Diversity => "diversity",
@@ -332,13 +576,11 @@ impl From<e::Organization> for NewOrganization {
         let e::Organization {
             id,
             name,
-            api_token,
             moderated_tags: _,
         } = o;
         NewOrganization {
             id: id.into(),
             name,
-            api_token,
         }
     }
 }
@@ -430,6 +672,249 @@ impl From<PendingClearanceForPlace> for e::PendingClearanceForPlace {
     }
 }
 
+pub(crate) fn api_token_scopes_into_string(scopes: &[e::ApiTokenScope]) -> String {
+    scopes
+        .iter()
+        .map(|s| s.as_str())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn load_api_token_scope(scope: &str) -> Option<e::ApiTokenScope> {
+    match scope.parse() {
+        Ok(scope) => Some(scope),
+        Err(_) => {
+            // The database should only contain valid scopes
+            log::error!("Failed to load API token scope '{}'", scope);
+            None
+        }
+    }
+}
+
+impl From<ApiToken> for e::ApiToken {
+    fn from(from: ApiToken) -> Self {
+        let ApiToken {
+            id,
+            name,
+            token_hash,
+            scopes,
+            created_at,
+            expires_at,
+            last_used_at,
+            org_id,
+        } = from;
+        Self {
+            id: id.into(),
+            org_id: org_id.into(),
+            name,
+            token_hash,
+            scopes: scopes
+                .split(',')
+                .filter(|s| !s.is_empty())
+                .filter_map(load_api_token_scope)
+                .collect(),
+            created_at: e::TimestampMs::from_inner(created_at),
+            expires_at: expires_at.map(e::TimestampMs::from_inner),
+            last_used_at: last_used_at.map(e::TimestampMs::from_inner),
+        }
+    }
+}
+
+pub(crate) fn org_member_from_model(from: OrganizationMember) -> Option<e::OrganizationMember> {
+    let OrganizationMember {
+        role,
+        org_id,
+        user_email,
+    } = from;
+    let role = match role.parse() {
+        Ok(role) => role,
+        Err(_) => {
+            // The database should only contain valid roles
+            log::error!("Failed to load organization role '{}'", role);
+            return None;
+        }
+    };
+    Some(e::OrganizationMember {
+        org_id: org_id.into(),
+        email: user_email,
+        role,
+    })
+}
+
+pub(crate) fn webhook_event_types_into_string(event_types: &[e::WebhookEventType]) -> String {
+    event_types
+        .iter()
+        .map(|t| t.as_str())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn load_webhook_event_type(event_type: &str) -> Option<e::WebhookEventType> {
+    match event_type.parse() {
+        Ok(event_type) => Some(event_type),
+        Err(_) => {
+            // The database should only contain valid event types
+            log::error!("Failed to load webhook event type '{}'", event_type);
+            None
+        }
+    }
+}
+
+pub(crate) fn webhook_from_model(from: Webhook) -> Option<e::Webhook> {
+    let Webhook {
+        id,
+        url,
+        secret,
+        event_types,
+        created_at,
+        org_id,
+        ..
+    } = from;
+    Some(e::Webhook {
+        id: id.into(),
+        org_id: org_id.into(),
+        url: load_url(url)?,
+        secret,
+        event_types: event_types
+            .split(',')
+            .filter(|t| !t.is_empty())
+            .filter_map(load_webhook_event_type)
+            .collect(),
+        created_at: e::TimestampMs::from_inner(created_at),
+    })
+}
+
+pub(crate) fn webhook_delivery_from_model(from: WebhookDelivery) -> Option<e::WebhookDelivery> {
+    let WebhookDelivery {
+        id,
+        event_type,
+        payload,
+        created_at,
+        attempts,
+        next_attempt_at,
+        last_attempt_at,
+        last_response_status,
+        last_error,
+        delivered_at,
+        webhook_id,
+    } = from;
+    Some(e::WebhookDelivery {
+        id: id.into(),
+        webhook_id: webhook_id.into(),
+        event_type: load_webhook_event_type(&event_type)?,
+        payload,
+        created_at: e::TimestampMs::from_inner(created_at),
+        attempts: attempts as u32,
+        next_attempt_at: next_attempt_at.map(e::TimestampMs::from_inner),
+        last_attempt_at: last_attempt_at.map(e::TimestampMs::from_inner),
+        last_response_status: last_response_status.map(|s| s as u16),
+        last_error,
+        delivered_at: delivered_at.map(e::TimestampMs::from_inner),
+    })
+}
+
+impl From<OutgoingEmail> for e::OutgoingEmail {
+    fn from(from: OutgoingEmail) -> Self {
+        let OutgoingEmail {
+            rowid: _,
+            id,
+            recipient,
+            subject,
+            body,
+            created_at,
+            attempts,
+            next_attempt_at,
+            last_attempt_at,
+            last_error,
+            sent_at,
+            html_body,
+            unsubscribe_url,
+        } = from;
+        Self {
+            id: id.into(),
+            recipient: recipient.into(),
+            subject,
+            body,
+            html_body,
+            unsubscribe_url,
+            created_at: e::TimestampMs::from_inner(created_at),
+            attempts: attempts as u32,
+            next_attempt_at: next_attempt_at.map(e::TimestampMs::from_inner),
+            last_attempt_at: last_attempt_at.map(e::TimestampMs::from_inner),
+            last_error,
+            sent_at: sent_at.map(e::TimestampMs::from_inner),
+        }
+    }
+}
+
+pub(crate) fn change_log_entry_from_model(from: ChangeLogEntry) -> Option<e::ChangeLogEntry> {
+    let ChangeLogEntry {
+        rowid,
+        created_at,
+        entity_kind,
+        entity_id,
+        place_id,
+        change_type,
+        title,
+        lat,
+        lng,
+        tags,
+    } = from;
+    // The database should only contain valid values
+    let kind = entity_kind
+        .parse()
+        .map_err(|_| log::error!("Invalid entity kind '{}' in change log", entity_kind))
+        .ok()?;
+    let change_type = change_type
+        .parse()
+        .map_err(|_| log::error!("Invalid change type '{}' in change log", change_type))
+        .ok()?;
+    let pos = match (lat, lng) {
+        (Some(lat), Some(lng)) => MapPoint::try_from_lat_lng_deg(lat, lng).ok(),
+        _ => None,
+    };
+    Some(e::ChangeLogEntry {
+        seq: rowid as u64,
+        change: e::EntityChange {
+            at: e::TimestampMs::from_inner(created_at),
+            kind,
+            id: entity_id.into(),
+            place_id: place_id.map(Into::into),
+            change_type,
+            title,
+            pos,
+            tags: tags
+                .split(',')
+                .filter(|t| !t.is_empty())
+                .map(ToString::to_string)
+                .collect(),
+        },
+    })
+}
+
+pub(crate) fn audit_log_entry_from_model(from: AuditLogEntry) -> Option<e::AuditLogEntry> {
+    let AuditLogEntry {
+        rowid: _,
+        created_at,
+        actor_email,
+        action,
+        target_email,
+        details,
+    } = from;
+    // The database should only contain valid values
+    let action = action
+        .parse()
+        .map_err(|_| log::error!("Invalid action '{}' in audit log", action))
+        .ok()?;
+    Some(e::AuditLogEntry {
+        at: e::TimestampMs::from_inner(created_at),
+        actor: actor_email,
+        action,
+        target: target_email,
+        details,
+    })
+}
+
 #[test]
 fn test_tag_diff() {
     let x = tags_diff(&[], &["b".into()]);
@@ -448,3 +933,50 @@ fn test_tag_diff() {
     assert!(x.added.is_empty());
     assert_eq!(x.deleted, vec!["a"]);
 }
+
+impl From<CachedGeoCode> for e::CachedGeoCode {
+    fn from(from: CachedGeoCode) -> Self {
+        let CachedGeoCode {
+            address,
+            lat,
+            lng,
+            created_at,
+        } = from;
+        let lat_lng = match (lat, lng) {
+            (Some(lat), Some(lng)) => Some((lat, lng)),
+            _ => None,
+        };
+        Self {
+            address,
+            lat_lng,
+            created_at: e::TimestampMs::from_inner(created_at),
+        }
+    }
+}
+
+impl From<CachedAddress> for e::CachedAddress {
+    fn from(from: CachedAddress) -> Self {
+        let CachedAddress {
+            pos,
+            street,
+            zip,
+            city,
+            country,
+            state,
+            created_at,
+        } = from;
+        let address = e::Address {
+            street,
+            zip,
+            city,
+            country,
+            state,
+        };
+        Self {
+            pos,
+            // Unresolved locations are stored without any address
+            address: Some(address).filter(|a| !a.is_empty()),
+            created_at: e::TimestampMs::from_inner(created_at),
+        }
+    }
+}
@@ -45,6 +45,7 @@ mod tests {
             usecases::NewUser {
                 email: "scout@foo.tld".into(),
                 password: "123456".into(),
+                language: None,
             },
             Some(Role::Scout),
         );
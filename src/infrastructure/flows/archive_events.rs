@@ -11,11 +11,21 @@ fn exec_archive_events(
     let connection = connections.exclusive()?;
     Ok(connection
         .transaction::<_, diesel::result::Error, _>(|| {
-            usecases::archive_events(&*connection, ids).map_err(|err| {
-                warn!("Failed to archive {} events: {}", ids.len(), err);
-                repo_err = Some(err);
-                diesel::result::Error::RollbackTransaction
-            })
+            connection
+                .get_events_chronologically(ids)
+                .map_err(Error::from)
+                .and_then(|events| {
+                    let count = usecases::archive_events(&*connection, ids)?;
+                    for event in &events {
+                        usecases::log_event_change(&*connection, event, ChangeType::Archived)?;
+                    }
+                    Ok(count)
+                })
+                .map_err(|err| {
+                    warn!("Failed to archive {} events: {}", ids.len(), err);
+                    repo_err = Some(err);
+                    diesel::result::Error::RollbackTransaction
+                })
         })
         .map_err(|err| {
             if let Some(repo_err) = repo_err {
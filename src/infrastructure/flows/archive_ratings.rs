@@ -12,11 +12,15 @@ pub fn exec_archive_ratings(
     let connection = connections.exclusive()?;
     Ok(connection
         .transaction::<_, diesel::result::Error, _>(|| {
-            usecases::archive_ratings(&*connection, account_email, ids).map_err(|err| {
-                warn!("Failed to archive {} ratings: {}", ids.len(), err);
-                repo_err = Some(err);
-                diesel::result::Error::RollbackTransaction
-            })
+            // Ratings are logged before they are archived, but
+            // within the same transaction
+            usecases::log_rating_changes(&*connection, ids, ChangeType::Archived)
+                .and_then(|_| usecases::archive_ratings(&*connection, account_email, ids))
+                .map_err(|err| {
+                    warn!("Failed to archive {} ratings: {}", ids.len(), err);
+                    repo_err = Some(err);
+                    diesel::result::Error::RollbackTransaction
+                })
         })
         .map_err(|err| {
             if let Some(repo_err) = repo_err {
@@ -105,6 +109,7 @@ mod tests {
             usecases::NewUser {
                 email: "scout@foo.tld".into(),
                 password: "123456".into(),
+                language: None,
             },
             Some(Role::Scout),
         );
@@ -0,0 +1,177 @@
+use super::{geocode::resolve_lat_lng_address, *};
+use crate::infrastructure::cfg::Cfg;
+use diesel::connection::Connection;
+use ofdb_core::gateways::geocode::GeoCodingGateway;
+use ofdb_gateways::geocoder::ThrottledGeoCoder;
+
+/// Fills in the missing addresses of all places and
+/// updates the search index accordingly.
+///
+/// The database is not locked while waiting for the geocoder.
+pub fn backfill_place_addresses(
+    connections: &sqlite::Connections,
+    indexer: &mut dyn PlaceIndexer,
+    geocoder: &(dyn GeoCodingGateway + Send + Sync),
+    cfg: &Cfg,
+) -> Result<usize> {
+    let places = usecases::places_without_address(&*connections.shared()?)?;
+    info!("Resolving the addresses of {} places", places.len());
+    let geocoder = ThrottledGeoCoder::new(geocoder, cfg.geocoding_delay);
+    let mut count = 0;
+    for (place, _) in places {
+        let lat_lng = place.location.pos.to_lat_lng_deg();
+        let addr =
+            match resolve_lat_lng_address(connections, &geocoder, lat_lng, cfg.geocode_cache_ttl) {
+                Some(addr) => addr,
+                None => {
+                    debug!("No address found for place {}", place.id);
+                    continue;
+                }
+            };
+        let (place, status, ratings) = match store_place_address(connections, &place, addr)? {
+            Some(backfilled) => backfilled,
+            None => continue,
+        };
+        count += 1;
+        if let Err(err) = usecases::reindex_place(indexer, &place, status, &ratings) {
+            error!(
+                "Failed to (re-)index place {} after filling in its address: {}",
+                place.id, err
+            );
+        }
+    }
+    if let Err(err) = indexer.flush_index() {
+        error!(
+            "Failed to flush search index after filling in addresses: {}",
+            err
+        );
+    }
+    Ok(count)
+}
+
+fn store_place_address(
+    connections: &sqlite::Connections,
+    place: &Place,
+    addr: Address,
+) -> Result<Option<(Place, ReviewStatus, Vec<Rating>)>> {
+    let connection = connections.exclusive()?;
+    let mut store_err = None;
+    connection
+        .transaction::<_, diesel::result::Error, _>(|| {
+            let filled_in = usecases::fill_in_place_address(
+                &*connection,
+                place.id.as_str(),
+                place.revision,
+                addr,
+            )
+            .and_then(|filled_in| match filled_in {
+                Some((place, status)) => {
+                    let ratings = connection.load_ratings_of_place(place.id.as_str())?;
+                    Ok(Some((place, status, ratings)))
+                }
+                None => Ok(None),
+            });
+            filled_in.map_err(|err| {
+                store_err = Some(err);
+                diesel::result::Error::RollbackTransaction
+            })
+        })
+        .map_err(|err| {
+            if let Some(err) = store_err {
+                err.into()
+            } else {
+                RepoError::from(err).into()
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::prelude::*;
+    use super::*;
+    use std::time::Duration;
+
+    struct FixedGeoCoder;
+
+    impl GeoCodingGateway for FixedGeoCoder {
+        fn resolve_address_lat_lng(&self, _addr: &Address) -> Option<(f64, f64)> {
+            None
+        }
+
+        fn resolve_lat_lng_address(&self, lat: f64, _lng: f64) -> Option<Address> {
+            if lat > 0.0 {
+                Some(Address {
+                    city: Some("Berlin".into()),
+                    ..Default::default()
+                })
+            } else {
+                None
+            }
+        }
+    }
+
+    #[test]
+    fn fill_in_missing_addresses_as_system_revisions() {
+        let fixture = BackendFixture::new();
+        let unknown_location = fixture.create_place(0.into(), None);
+        let unreviewed = fixture.create_place(1.into(), None);
+        let confirmed = fixture.create_place(2.into(), None);
+        fixture
+            .db_connections
+            .exclusive()
+            .unwrap()
+            .review_places(
+                &[confirmed.as_str()],
+                ReviewStatus::Confirmed,
+                &ActivityLog {
+                    activity: Activity::now(None),
+                    context: None,
+                    comment: None,
+                },
+            )
+            .unwrap();
+
+        let cfg = Cfg {
+            geocoding_delay: Duration::from_secs(0),
+            ..Default::default()
+        };
+        let count = super::backfill_place_addresses(
+            &fixture.db_connections,
+            &mut *fixture.search_engine.borrow_mut(),
+            &FixedGeoCoder,
+            &cfg,
+        )
+        .unwrap();
+        assert_eq!(2, count);
+
+        let (place, _) = fixture.try_get_place(&unknown_location).unwrap();
+        assert!(place.location.address.is_none());
+        let (place, status) = fixture.try_get_place(&unreviewed).unwrap();
+        assert_eq!(ReviewStatus::Created, status);
+        assert_eq!(Revision::initial().next(), place.revision);
+        let (place, status) = fixture.try_get_place(&confirmed).unwrap();
+        assert_eq!(ReviewStatus::Confirmed, status);
+        assert_eq!(
+            Some("Berlin"),
+            place
+                .location
+                .address
+                .as_ref()
+                .and_then(|addr| addr.city.as_deref())
+        );
+        let history = fixture
+            .db_connections
+            .shared()
+            .unwrap()
+            .get_place_history(&confirmed, None)
+            .unwrap();
+        let (_, reviews) = history
+            .revisions
+            .iter()
+            .find(|(rev, _)| rev.revision == place.revision)
+            .unwrap();
+        assert!(reviews
+            .iter()
+            .any(|r| r.status == ReviewStatus::Confirmed && r.activity.is_system()));
+    }
+}
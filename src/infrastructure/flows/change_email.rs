@@ -0,0 +1,43 @@
+use super::*;
+use crate::core::error::Error;
+use diesel::connection::Connection;
+use ofdb_core::gateways::notify::NotificationGateway;
+
+pub fn request_email_change(
+    connections: &sqlite::Connections,
+    notify: &dyn NotificationGateway,
+    email: &str,
+    new_email: &str,
+) -> Result<()> {
+    let new_email_nonce =
+        usecases::request_email_change(&*connections.exclusive()?, email, new_email)?;
+    notify.user_email_change_requested(&new_email_nonce);
+    Ok(())
+}
+
+/// Changes the email address and notifies the previous
+/// address about the change.
+pub fn confirm_email_change(
+    connections: &sqlite::Connections,
+    notify: &dyn NotificationGateway,
+    new_email_nonce: &EmailNonce,
+) -> Result<()> {
+    let mut rollback_err: Option<Error> = None;
+    let connection = connections.exclusive()?;
+    let old_email = connection
+        .transaction::<_, diesel::result::Error, _>(|| {
+            usecases::confirm_email_change(&*connection, new_email_nonce).map_err(|err| {
+                warn!(
+                    "Failed to change email address to {}: {}",
+                    new_email_nonce.email, err
+                );
+                rollback_err = Some(err);
+                diesel::result::Error::RollbackTransaction
+            })
+        })
+        .map_err(|err| rollback_err.unwrap_or_else(|| Error::from(RepoError::from(err))))?;
+    // The connection must be released before notifying
+    drop(connection);
+    notify.user_email_changed(&old_email, &new_email_nonce.email);
+    Ok(())
+}
@@ -48,6 +48,7 @@ mod tests {
             usecases::NewUser {
                 email: "user@bar.tld".into(),
                 password: "123456".into(),
+                language: None,
             },
             None,
         );
@@ -55,6 +56,7 @@ mod tests {
             usecases::NewUser {
                 email: "admin@foo.tld".into(),
                 password: "123456".into(),
+                language: None,
             },
             Some(Role::Admin),
         );
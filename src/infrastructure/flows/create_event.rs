@@ -7,7 +7,7 @@ pub fn create_event(
     connections: &sqlite::Connections,
     indexer: &mut dyn EventIndexer,
     notify: &dyn NotificationGateway,
-    token: Option<&str>,
+    org: Option<&Organization>,
     new_event: usecases::NewEvent,
 ) -> Result<Event> {
     // Create and add new event
@@ -18,17 +18,24 @@ pub fn create_event(
             .transaction::<_, diesel::result::Error, _>(|| {
                 match usecases::import_new_event(
                     &*connection,
-                    token,
+                    org,
                     new_event,
                     usecases::NewEventMode::Create,
                 ) {
                     Ok(storable) => {
-                        let event = usecases::store_created_event(&*connection, storable).map_err(
-                            |err| {
+                        let event = usecases::store_created_event(&*connection, storable)
+                            .and_then(|event| {
+                                usecases::log_event_change(
+                                    &*connection,
+                                    &event,
+                                    ChangeType::Created,
+                                )?;
+                                Ok(event)
+                            })
+                            .map_err(|err| {
                                 warn!("Failed to store newly created event: {}", err);
                                 diesel::result::Error::RollbackTransaction
-                            },
-                        )?;
+                            })?;
                         Ok(event)
                     }
                     Err(err) => {
@@ -70,11 +77,23 @@ fn notify_event_created(
     event: &Event,
 ) -> Result<()> {
     if let Some(ref location) = event.location {
-        let email_addresses = {
+        let change = ObservedChange {
+            entity_kind: SubscriptionEntityKind::Event,
+            change_type: SubscriptionChangeType::Created,
+            tags: &event.tags,
+        };
+        let recipients = {
             let conn = connections.shared()?;
-            usecases::email_addresses_by_coordinate(&*conn, location.pos)?
+            usecases::subscription_recipients_by_coordinate(&*conn, location.pos, &change)?
         };
-        notify.event_created(&email_addresses, event);
+        notify.event_created(&recipients, event);
+        usecases::queue_subscription_notifications(
+            &*connections.exclusive()?,
+            location.pos,
+            &change,
+            &event.id,
+            &event.title,
+        )?;
     }
     Ok(())
 }
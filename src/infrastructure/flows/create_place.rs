@@ -27,6 +27,14 @@ pub fn create_place(
                 ) {
                     Ok(storable) => {
                         let (place, ratings) = usecases::store_new_place(&*connection, storable)
+                            .and_then(|(place, ratings)| {
+                                usecases::log_place_change(
+                                    &*connection,
+                                    &place,
+                                    ChangeType::Created,
+                                )?;
+                                Ok((place, ratings))
+                            })
                             .map_err(|err| {
                                 warn!("Failed to store newly created place: {}", err);
                                 diesel::result::Error::RollbackTransaction
@@ -74,13 +82,28 @@ fn notify_place_added(
     notify: &dyn NotificationGateway,
     place: &Place,
 ) -> Result<()> {
-    let (email_addresses, all_categories) = {
+    let change = ObservedChange {
+        entity_kind: SubscriptionEntityKind::Place,
+        change_type: SubscriptionChangeType::Created,
+        tags: &place.tags,
+    };
+    let (recipients, all_categories) = {
         let connection = connections.shared()?;
-        let email_addresses =
-            usecases::email_addresses_by_coordinate(&*connection, place.location.pos)?;
+        let recipients = usecases::subscription_recipients_by_coordinate(
+            &*connection,
+            place.location.pos,
+            &change,
+        )?;
         let all_categories = connection.all_categories()?;
-        (email_addresses, all_categories)
+        (recipients, all_categories)
     };
-    notify.place_added(&email_addresses, place, all_categories);
+    notify.place_added(&recipients, place, all_categories);
+    usecases::queue_subscription_notifications(
+        &*connections.exclusive()?,
+        place.location.pos,
+        &change,
+        &place.id,
+        &place.title,
+    )?;
     Ok(())
 }
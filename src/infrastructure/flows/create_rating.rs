@@ -1,28 +1,44 @@
 use super::*;
 
 use diesel::connection::Connection;
+use ofdb_core::gateways::notify::NotificationGateway;
 
 pub fn create_rating(
     connections: &sqlite::Connections,
     indexer: &mut dyn PlaceIndexer,
+    notify: &dyn NotificationGateway,
     rate_entry: usecases::NewPlaceRating,
 ) -> Result<(String, String)> {
+    let rated_by = rate_entry.user.clone();
     // Add new rating to existing entry
-    let (rating_id, comment_id, place, status, ratings) = {
+    let (rating, comment, place, status, ratings) = {
         let connection = connections.exclusive()?;
         let mut prepare_err = None;
         connection
             .transaction::<_, diesel::result::Error, _>(|| {
                 match usecases::prepare_new_rating(&*connection, rate_entry) {
                     Ok(storable) => {
-                        let rating_id = storable.rating_id().to_owned();
-                        let comment_id = storable.comment_id().to_owned();
+                        let rating = storable.rating().clone();
+                        let comment = storable.comment().clone();
                         let (place, status, ratings) =
-                            usecases::store_new_rating(&*connection, storable).map_err(|err| {
-                                warn!("Failed to store new rating for entry: {}", err);
-                                diesel::result::Error::RollbackTransaction
-                            })?;
-                        Ok((rating_id, comment_id, place, status, ratings))
+                            usecases::store_new_rating(&*connection, storable)
+                                .and_then(|(place, status, ratings)| {
+                                    if let Some(rating) = ratings.iter().find(|r| r.id == rating.id)
+                                    {
+                                        usecases::log_rating_change(
+                                            &*connection,
+                                            rating,
+                                            &place,
+                                            ChangeType::Created,
+                                        )?;
+                                    }
+                                    Ok((place, status, ratings))
+                                })
+                                .map_err(|err| {
+                                    warn!("Failed to store new rating for entry: {}", err);
+                                    diesel::result::Error::RollbackTransaction
+                                })?;
+                        Ok((rating, comment, place, status, ratings))
                     }
                     Err(err) => {
                         prepare_err = Some(err);
@@ -50,5 +66,36 @@ pub fn create_rating(
         );
     }
 
-    Ok((rating_id, comment_id))
+    // Notify the authors of the entry
+    // TODO: Move to a separate task/thread that doesn't delay this request
+    if let Err(err) =
+        notify_authors_of_rated_place(connections, notify, &place, &rating, &comment, rated_by)
+    {
+        error!(
+            "Failed to notify authors of place {} about a new rating: {}",
+            place.id, err
+        );
+    }
+
+    Ok((rating.id.into(), comment.id.into()))
+}
+
+fn notify_authors_of_rated_place(
+    connections: &sqlite::Connections,
+    notify: &dyn NotificationGateway,
+    place: &Place,
+    rating: &Rating,
+    comment: &Comment,
+    rated_by: Option<String>,
+) -> Result<()> {
+    let authors = usecases::author_recipients_of_place(
+        &*connections.shared()?,
+        place.id.as_str(),
+        AuthorNotificationKind::Rating,
+        rated_by.as_deref(),
+    )?;
+    if !authors.is_empty() {
+        notify.own_place_rated(&authors, place, rating, comment);
+    }
+    Ok(())
 }
@@ -0,0 +1,30 @@
+use super::*;
+
+use ofdb_core::gateways::webhook::WebhookGateway;
+
+/// The maximum number of deliveries per run.
+const MAX_DUE_DELIVERIES: u64 = 100;
+
+/// Sends all pending webhook notifications that are due.
+///
+/// No database connection is held while waiting for the
+/// responses of the remote servers.
+pub fn deliver_due_webhooks(
+    connections: &sqlite::Connections,
+    gateway: &dyn WebhookGateway,
+) -> Result<usize> {
+    let due = {
+        let connection = connections.shared()?;
+        usecases::load_due_webhook_deliveries(&*connection, TimestampMs::now(), MAX_DUE_DELIVERIES)?
+    };
+    if due.is_empty() {
+        return Ok(0);
+    }
+    let attempts: Vec<_> = due
+        .into_iter()
+        .map(|(webhook, delivery)| usecases::attempt_webhook_delivery(gateway, &webhook, delivery))
+        .collect();
+    let connection = connections.exclusive()?;
+    usecases::store_webhook_delivery_attempts(&*connection, &attempts)?;
+    Ok(attempts.len())
+}
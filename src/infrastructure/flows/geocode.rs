@@ -0,0 +1,238 @@
+use super::*;
+
+use ofdb_core::gateways::geocode::GeoCodingGateway;
+use std::time::Duration;
+
+/// Resolves the location of an address with the geocoder
+/// unless it has been cached before.
+///
+/// Failures of the cache are logged and don't prevent
+/// resolving the address.
+pub fn resolve_address_lat_lng(
+    connections: &sqlite::Connections,
+    geocoder: &dyn GeoCodingGateway,
+    addr: &Address,
+    cache_ttl: Option<Duration>,
+) -> Option<(f64, f64)> {
+    if addr.is_empty() {
+        return None;
+    }
+    let ttl = match cache_ttl {
+        Some(ttl) => ttl,
+        None => return geocoder.resolve_address_lat_lng(addr),
+    };
+    match get_cached_geocode(connections, addr, ttl) {
+        Ok(Some(lat_lng)) => return lat_lng,
+        Ok(None) => {}
+        Err(err) => warn!("Failed to look up cached geocode: {}", err),
+    }
+    let lat_lng = geocoder.resolve_address_lat_lng(addr);
+    if let Err(err) = cache_geocode(connections, addr, lat_lng, ttl) {
+        warn!("Failed to cache geocode: {}", err);
+    }
+    lat_lng
+}
+
+/// Resolves the address of a location with the geocoder
+/// unless it has been cached before.
+///
+/// Failures of the cache are logged and don't prevent
+/// resolving the location.
+pub fn resolve_lat_lng_address(
+    connections: &sqlite::Connections,
+    geocoder: &dyn GeoCodingGateway,
+    lat_lng: (f64, f64),
+    cache_ttl: Option<Duration>,
+) -> Option<Address> {
+    let (lat, lng) = lat_lng;
+    let ttl = match cache_ttl {
+        Some(ttl) => ttl,
+        None => return geocoder.resolve_lat_lng_address(lat, lng),
+    };
+    match get_cached_address(connections, lat_lng, ttl) {
+        Ok(Some(addr)) => return addr,
+        Ok(None) => {}
+        Err(err) => warn!("Failed to look up cached address: {}", err),
+    }
+    let addr = geocoder.resolve_lat_lng_address(lat, lng);
+    if let Err(err) = cache_address(connections, lat_lng, addr.as_ref(), ttl) {
+        warn!("Failed to cache address: {}", err);
+    }
+    addr
+}
+
+/// Completes an address that is blank by resolving the location.
+///
+/// Returns the resolved address or `None` if the given
+/// address is not blank or could not be resolved.
+pub fn fill_in_missing_address(
+    connections: &sqlite::Connections,
+    geocoder: &dyn GeoCodingGateway,
+    addr: &Address,
+    lat_lng: (f64, f64),
+    cache_ttl: Option<Duration>,
+) -> Option<Address> {
+    let Address {
+        street,
+        zip,
+        city,
+        country,
+        state,
+    } = addr;
+    let is_blank = |s: &Option<String>| s.as_deref().map_or(true, |s| s.trim().is_empty());
+    if ![street, zip, city, country, state]
+        .iter()
+        .all(|s| is_blank(s))
+    {
+        return None;
+    }
+    let addr = resolve_lat_lng_address(connections, geocoder, lat_lng, cache_ttl)?;
+    debug!("Filling in missing address: {:?}", addr);
+    Some(addr)
+}
+
+fn get_cached_geocode(
+    connections: &sqlite::Connections,
+    addr: &Address,
+    ttl: Duration,
+) -> Result<Option<Option<(f64, f64)>>> {
+    let connection = connections.shared()?;
+    Ok(usecases::get_cached_geocode(
+        &*connection,
+        addr,
+        TimestampMs::now(),
+        ttl,
+    )?)
+}
+
+fn cache_geocode(
+    connections: &sqlite::Connections,
+    addr: &Address,
+    lat_lng: Option<(f64, f64)>,
+    ttl: Duration,
+) -> Result<()> {
+    let connection = connections.exclusive()?;
+    Ok(usecases::cache_geocode(
+        &*connection,
+        addr,
+        lat_lng,
+        TimestampMs::now(),
+        ttl,
+    )?)
+}
+
+fn get_cached_address(
+    connections: &sqlite::Connections,
+    lat_lng: (f64, f64),
+    ttl: Duration,
+) -> Result<Option<Option<Address>>> {
+    let connection = connections.shared()?;
+    Ok(usecases::get_cached_address(
+        &*connection,
+        lat_lng,
+        TimestampMs::now(),
+        ttl,
+    )?)
+}
+
+fn cache_address(
+    connections: &sqlite::Connections,
+    lat_lng: (f64, f64),
+    addr: Option<&Address>,
+    ttl: Duration,
+) -> Result<()> {
+    let connection = connections.exclusive()?;
+    Ok(usecases::cache_address(
+        &*connection,
+        lat_lng,
+        addr,
+        TimestampMs::now(),
+        ttl,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::prelude::*;
+    use super::*;
+    use std::cell::Cell;
+
+    /// A local stand-in for a remote geocoder that counts
+    /// the requests.
+    #[derive(Default)]
+    struct CountingGeoCoder {
+        requests: Cell<usize>,
+    }
+
+    impl GeoCodingGateway for CountingGeoCoder {
+        fn resolve_address_lat_lng(&self, addr: &Address) -> Option<(f64, f64)> {
+            self.requests.set(self.requests.get() + 1);
+            addr.city.as_ref().map(|_| (52.5, 13.4))
+        }
+
+        fn resolve_lat_lng_address(&self, lat: f64, _lng: f64) -> Option<Address> {
+            self.requests.set(self.requests.get() + 1);
+            if lat > 0.0 {
+                Some(Address {
+                    city: Some("Berlin".into()),
+                    ..Default::default()
+                })
+            } else {
+                None
+            }
+        }
+    }
+
+    #[test]
+    fn resolve_each_address_only_once() {
+        let fixture = BackendFixture::new();
+        let geocoder = CountingGeoCoder::default();
+        let ttl = Some(Duration::from_secs(60));
+        let addr = Address {
+            city: Some("Berlin".into()),
+            ..Default::default()
+        };
+        let resolve = |addr: &Address| {
+            super::resolve_address_lat_lng(&fixture.db_connections, &geocoder, addr, ttl)
+        };
+        assert_eq!(Some((52.5, 13.4)), resolve(&addr));
+        assert_eq!(Some((52.5, 13.4)), resolve(&addr));
+        assert_eq!(1, geocoder.requests.get());
+
+        // Unresolved addresses are cached briefly
+        let addr = Address {
+            street: Some("Nowhere".into()),
+            ..Default::default()
+        };
+        assert_eq!(None, resolve(&addr));
+        assert_eq!(None, resolve(&addr));
+        assert_eq!(2, geocoder.requests.get());
+    }
+
+    #[test]
+    fn fill_in_only_blank_addresses() {
+        let fixture = BackendFixture::new();
+        let geocoder = CountingGeoCoder::default();
+        let ttl = Some(Duration::from_secs(60));
+        let fill_in = |addr: &Address, lat_lng| {
+            super::fill_in_missing_address(&fixture.db_connections, &geocoder, addr, lat_lng, ttl)
+        };
+        let blank = Address {
+            street: Some(" ".into()),
+            ..Default::default()
+        };
+        let berlin = Address {
+            city: Some("Berlin".into()),
+            ..Default::default()
+        };
+        assert_eq!(Some(berlin.clone()), fill_in(&blank, (52.5, 13.4)));
+        assert_eq!(Some(berlin.clone()), fill_in(&blank, (52.5, 13.4)));
+        assert_eq!(1, geocoder.requests.get());
+        assert_eq!(None, fill_in(&berlin, (52.5, 13.4)));
+        assert_eq!(None, fill_in(&blank, (-10.0, 13.4)));
+        assert_eq!(2, geocoder.requests.get());
+        // Unresolved locations are cached briefly
+        assert_eq!(None, fill_in(&blank, (-10.0, 13.4)));
+        assert_eq!(2, geocoder.requests.get());
+    }
+}
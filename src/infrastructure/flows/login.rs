@@ -0,0 +1,108 @@
+use super::*;
+use ofdb_core::gateways::notify::NotificationGateway;
+use std::net::IpAddr;
+
+fn is_failed_attempt(err: &Error) -> bool {
+    matches!(
+        err,
+        Error::Parameter(ParameterError::Credentials)
+            | Error::Parameter(ParameterError::SecondFactor)
+    )
+}
+
+/// Logs in a user while throttling failed attempts per
+/// account and per client.
+///
+/// The owner of an account is notified when the login
+/// has been locked.
+#[allow(clippy::too_many_arguments)]
+pub fn login_with_email(
+    connections: &sqlite::Connections,
+    notify: &dyn NotificationGateway,
+    throttling: &usecases::LoginThrottling,
+    login: &usecases::Credentials,
+    second_factor: Option<&str>,
+    second_factor_required_roles: &[Role],
+    client_ip: Option<IpAddr>,
+) -> Result<Role> {
+    with_login_throttle(
+        connections,
+        notify,
+        throttling,
+        login.email,
+        client_ip,
+        |connection| {
+            usecases::login_with_email(
+                connection,
+                login,
+                second_factor,
+                second_factor_required_roles,
+            )
+        },
+    )
+}
+
+/// Verifies the credentials of a user with the given function
+/// while throttling failed attempts in the same way as a login.
+pub fn with_login_throttle<T>(
+    connections: &sqlite::Connections,
+    notify: &dyn NotificationGateway,
+    throttling: &usecases::LoginThrottling,
+    email: &str,
+    client_ip: Option<IpAddr>,
+    authenticate: impl FnOnce(&sqlite::Connection) -> std::result::Result<T, Error>,
+) -> Result<T> {
+    let now = TimestampMs::now();
+    let account = LoginThrottleKey::Account(email.to_owned());
+    let client = client_ip.map(|ip| LoginThrottleKey::Client(ip.to_string()));
+    let connection = connections.exclusive()?;
+    if let Some(client) = &client {
+        usecases::check_login_throttle(&*connection, client, &throttling.client, now)?;
+    }
+    usecases::check_login_throttle(&*connection, &account, &throttling.account, now)?;
+    let err = match authenticate(&*connection) {
+        Ok(res) => {
+            connection.delete_failed_logins(&account)?;
+            return Ok(res);
+        }
+        Err(err) => err,
+    };
+    if !is_failed_attempt(&err) {
+        return Err(err.into());
+    }
+    let mut locked_user = None;
+    if let Some(locked_until) =
+        usecases::record_failed_login(&*connection, account, &throttling.account, now)?
+    {
+        warn!(
+            "Locked login of account {} until {} after too many failed attempts",
+            email, locked_until
+        );
+        if let Some(user) = connection.try_get_user_by_email(email)? {
+            locked_user = Some((user, locked_until));
+        }
+    }
+    if let (Some(ip), Some(client)) = (client_ip, client) {
+        if let Some(locked_until) =
+            usecases::record_failed_login(&*connection, client, &throttling.client, now)?
+        {
+            warn!(
+                "Locked login from {} until {} after too many failed attempts",
+                ip, locked_until
+            );
+        }
+    }
+    let forget_before = std::cmp::max(
+        throttling.account.lockout_duration,
+        throttling.client.lockout_duration,
+    );
+    connection.delete_failed_logins_before(TimestampMs::from_inner(
+        now.into_inner() - forget_before.as_millis() as i64,
+    ))?;
+    // The connection must be released before notifying
+    drop(connection);
+    if let Some((user, locked_until)) = locked_user {
+        notify.user_login_locked(&user, locked_until);
+    }
+    Err(err.into())
+}
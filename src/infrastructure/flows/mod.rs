@@ -1,19 +1,29 @@
 mod archive_comments;
 mod archive_events;
 mod archive_ratings;
+mod backfill_addresses;
+mod change_email;
 mod change_user_role;
 mod create_event;
 mod create_place;
 mod create_rating;
+mod deliver_webhooks;
+mod geocode;
+mod login;
 mod reset_password;
 mod review_places;
+mod send_emails;
+mod send_pending_clearance_digests;
+mod send_subscription_digests;
 mod update_event;
 mod update_place;
 
 pub mod prelude {
     pub use super::{
-        archive_comments::*, archive_events::*, archive_ratings::*, change_user_role::*,
-        create_event::*, create_place::*, create_rating::*, reset_password::*, review_places::*,
+        archive_comments::*, archive_events::*, archive_ratings::*, backfill_addresses::*,
+        change_email::*, change_user_role::*, create_event::*, create_place::*, create_rating::*,
+        deliver_webhooks::*, geocode::*, login::*, reset_password::*, review_places::*,
+        send_emails::*, send_pending_clearance_digests::*, send_subscription_digests::*,
         update_event::*, update_place::*,
     };
 }
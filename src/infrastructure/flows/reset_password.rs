@@ -30,6 +30,24 @@ pub fn reset_password_request(
     Ok(email_nonce)
 }
 
+/// Sends a password reset email on behalf of the user.
+pub fn reset_password_request_by_admin(
+    connections: &sqlite::Connections,
+    notify: &dyn NotificationGateway,
+    admin: &User,
+    email: &str,
+) -> Result<EmailNonce> {
+    let email_nonce = reset_password_request(connections, notify, email)?;
+    usecases::log_admin_action(
+        &*connections.exclusive()?,
+        &admin.email,
+        AdminAction::ResetPassword,
+        email,
+        None,
+    )?;
+    Ok(email_nonce)
+}
+
 pub fn reset_password_with_email_nonce(
     connections: &sqlite::Connections,
     email_nonce: EmailNonce,
@@ -108,6 +126,7 @@ mod tests {
             usecases::NewUser {
                 email: email1.to_string(),
                 password: "old pass1".to_string(),
+                language: None,
             },
             None,
         );
@@ -122,6 +141,7 @@ mod tests {
             usecases::NewUser {
                 email: email2.to_string(),
                 password: "old pass2".to_string(),
+                language: None,
             },
             None,
         );
@@ -129,12 +149,16 @@ mod tests {
         // Verify that password is invalid for both users
         debug_assert!(usecases::login_with_email(
             &*fixture.db_connections.shared().unwrap(),
-            &credentials1
+            &credentials1,
+            None,
+            &[]
         )
         .is_err());
         debug_assert!(usecases::login_with_email(
             &*fixture.db_connections.shared().unwrap(),
-            &credentials2
+            &credentials2,
+            None,
+            &[]
         )
         .is_err());
 
@@ -164,12 +188,16 @@ mod tests {
         // Check that user 1 is able to login with the new password
         debug_assert!(usecases::login_with_email(
             &*fixture.db_connections.shared().unwrap(),
-            &credentials1
+            &credentials1,
+            None,
+            &[]
         )
         .is_ok());
         debug_assert!(usecases::login_with_email(
             &*fixture.db_connections.shared().unwrap(),
-            &credentials2
+            &credentials2,
+            None,
+            &[]
         )
         .is_err());
 
@@ -190,12 +218,16 @@ mod tests {
         // Check that both users are able to login with their new passwords
         debug_assert!(usecases::login_with_email(
             &*fixture.db_connections.shared().unwrap(),
-            &credentials1
+            &credentials1,
+            None,
+            &[]
         )
         .is_ok());
         debug_assert!(usecases::login_with_email(
             &*fixture.db_connections.shared().unwrap(),
-            &credentials2
+            &credentials2,
+            None,
+            &[]
         )
         .is_ok());
     }
@@ -1,6 +1,7 @@
 use super::*;
 
 use diesel::connection::Connection;
+use ofdb_core::gateways::notify::NotificationGateway;
 
 fn exec_review_places(
     connections: &sqlite::Connections,
@@ -8,14 +9,20 @@ fn exec_review_places(
     review: usecases::Review,
 ) -> Result<usize> {
     let mut repo_err = None;
+    let status = review.status;
     let connection = connections.exclusive()?;
     Ok(connection
         .transaction::<_, diesel::result::Error, _>(|| {
-            usecases::review_places(&*connection, ids, review).map_err(|err| {
-                warn!("Failed to review {} places: {}", ids.len(), err);
-                repo_err = Some(err);
-                diesel::result::Error::RollbackTransaction
-            })
+            usecases::review_places(&*connection, ids, review)
+                .and_then(|count| {
+                    usecases::log_place_reviews(&*connection, ids, status)?;
+                    Ok(count)
+                })
+                .map_err(|err| {
+                    warn!("Failed to review {} places: {}", ids.len(), err);
+                    repo_err = Some(err);
+                    diesel::result::Error::RollbackTransaction
+                })
         })
         .map_err(|err| {
             if let Some(repo_err) = repo_err {
@@ -29,11 +36,12 @@ fn exec_review_places(
 fn post_review_places(
     connections: &sqlite::Connections,
     indexer: &mut dyn PlaceIndexer,
+    notify: &dyn NotificationGateway,
     ids: &[&str],
 ) -> Result<()> {
     let db = connections.shared()?;
     let places_with_status = db.get_places(ids)?;
-    for (place, status) in places_with_status {
+    for (place, status) in &places_with_status {
         let ratings = match db.load_ratings_of_place(place.id.as_str()) {
             Ok(ratings) => ratings,
             Err(err) => {
@@ -45,7 +53,7 @@ fn post_review_places(
                 continue;
             }
         };
-        if let Err(err) = usecases::reindex_place(indexer, &place, status, &ratings) {
+        if let Err(err) = usecases::reindex_place(indexer, place, *status, &ratings) {
             error!(
                 "Failed to (re-)index place {} after reviewing: {}",
                 place.id, err
@@ -58,18 +66,85 @@ fn post_review_places(
             err
         );
     }
+    drop(db);
+    for (place, status) in &places_with_status {
+        if let Err(err) = notify_place_reviewed(connections, notify, place, *status) {
+            error!(
+                "Failed to send notifications for reviewed place {}: {}",
+                place.id, err
+            );
+        }
+    }
+    Ok(())
+}
+
+fn notify_place_reviewed(
+    connections: &sqlite::Connections,
+    notify: &dyn NotificationGateway,
+    place: &Place,
+    status: ReviewStatus,
+) -> Result<()> {
+    let change = ObservedChange {
+        entity_kind: SubscriptionEntityKind::Place,
+        change_type: SubscriptionChangeType::Reviewed,
+        tags: &place.tags,
+    };
+    let recipients = {
+        let connection = connections.shared()?;
+        usecases::subscription_recipients_by_coordinate(&*connection, place.location.pos, &change)?
+    };
+    notify.place_reviewed(&recipients, place, status);
+    usecases::queue_subscription_notifications(
+        &*connections.exclusive()?,
+        place.location.pos,
+        &change,
+        &place.id,
+        &place.title,
+    )?;
+    Ok(())
+}
+
+fn notify_authors_of_reviewed_place(
+    connections: &sqlite::Connections,
+    notify: &dyn NotificationGateway,
+    place: &Place,
+    review: &usecases::Review,
+) -> Result<()> {
+    let authors = usecases::author_recipients_of_place(
+        &*connections.shared()?,
+        place.id.as_str(),
+        AuthorNotificationKind::Review,
+        Some(review.reviewer_email.as_str()),
+    )?;
+    if !authors.is_empty() {
+        notify.own_place_reviewed(&authors, place, review.status, review.comment.as_deref());
+    }
     Ok(())
 }
 
 pub fn review_places(
     connections: &sqlite::Connections,
     indexer: &mut dyn PlaceIndexer,
+    notify: &dyn NotificationGateway,
     ids: &[&str],
     review: usecases::Review,
 ) -> Result<usize> {
-    let count = exec_review_places(connections, ids, review)?;
+    // Archived places could not be loaded after the review
+    let places = connections.shared()?.get_places(ids)?;
+    let count = exec_review_places(connections, ids, review.clone())?;
     // TODO: Move post processing to a separate task/thread that doesn't delay this request?
-    post_review_places(connections, indexer, ids)?;
+    post_review_places(connections, indexer, notify, ids)?;
+    for (place, old_status) in &places {
+        if *old_status == review.status {
+            continue;
+        }
+        if let Err(err) = notify_authors_of_reviewed_place(connections, notify, place, &review) {
+            error!(
+                "Failed to notify authors of reviewed place {}: {}",
+                place.id, err
+            );
+        }
+    }
     Ok(count)
 }
 
@@ -85,6 +160,7 @@ mod tests {
         super::review_places(
             &fixture.db_connections,
             &mut *fixture.search_engine.borrow_mut(),
+            &fixture.notify,
             ids,
             review,
         )
@@ -107,6 +183,7 @@ mod tests {
             usecases::NewUser {
                 email: "test@example.com".into(),
                 password: "test123".into(),
+                language: None,
             },
             None,
         );
@@ -231,6 +308,7 @@ mod tests {
             usecases::NewUser {
                 email: "test@example.com".into(),
                 password: "test123".into(),
+                language: None,
             },
             None,
         );
@@ -0,0 +1,52 @@
+use super::*;
+
+use ofdb_core::gateways::email::EmailSender;
+
+/// The maximum number of emails per run.
+const MAX_DUE_EMAILS: u64 = 100;
+
+/// Puts emails into the outbox.
+///
+/// Must not be invoked while holding a database connection.
+pub fn enqueue_emails(
+    connections: &sqlite::Connections,
+    recipients: &[Email],
+    content: &EmailContent,
+) -> Result<usize> {
+    let connection = connections.exclusive()?;
+    Ok(usecases::enqueue_emails(&*connection, recipients, content)?)
+}
+
+/// Sends all emails in the outbox that are due.
+///
+/// No database connection is held while waiting for the
+/// mail server.
+pub fn send_due_emails(
+    connections: &sqlite::Connections,
+    sender: &dyn EmailSender,
+) -> Result<usize> {
+    match delete_sent_emails(connections) {
+        Ok(0) => {}
+        Ok(count) => debug!("Deleted {} sent e-mails from the outbox", count),
+        Err(err) => error!("Failed to delete sent e-mails from the outbox: {}", err),
+    }
+    let due = {
+        let connection = connections.shared()?;
+        usecases::load_due_outgoing_emails(&*connection, TimestampMs::now(), MAX_DUE_EMAILS)?
+    };
+    if due.is_empty() {
+        return Ok(0);
+    }
+    let attempts: Vec<_> = due
+        .into_iter()
+        .map(|email| usecases::attempt_to_send_email(sender, email))
+        .collect();
+    let connection = connections.exclusive()?;
+    usecases::store_email_send_attempts(&*connection, &attempts)?;
+    Ok(attempts.len())
+}
+
+fn delete_sent_emails(connections: &sqlite::Connections) -> Result<usize> {
+    let connection = connections.exclusive()?;
+    Ok(usecases::delete_sent_outgoing_emails(&*connection)?)
+}
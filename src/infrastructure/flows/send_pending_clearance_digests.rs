@@ -0,0 +1,49 @@
+use super::*;
+
+use ofdb_core::gateways::notify::NotificationGateway;
+use std::time::Duration;
+
+/// Notifies the contacts of all organizations whose new
+/// pending clearances are due.
+///
+/// Organizations without contacts are not notified about
+/// these pending clearances later on, which is logged.
+pub fn send_due_pending_clearance_digests(
+    connections: &sqlite::Connections,
+    notify: &dyn NotificationGateway,
+    batch_period: Duration,
+) -> Result<usize> {
+    let org_ids = {
+        let connection = connections.shared()?;
+        usecases::clearance::place::due_pending_clearance_digest_org_ids(
+            &*connection,
+            TimestampMs::now(),
+            batch_period,
+        )?
+    };
+    let mut sent_count = 0;
+    for org_id in &org_ids {
+        // The connection is only held while collecting
+        // the digest of a single organization
+        let digest = {
+            let connection = connections.shared()?;
+            usecases::clearance::place::new_pending_clearance_digest(&*connection, org_id)?
+        };
+        let digest = match digest {
+            Some(digest) => digest,
+            None => continue,
+        };
+        if digest.recipients.is_empty() {
+            warn!(
+                "No contacts of organization '{}' to notify about {} new pending clearances",
+                digest.org_name, digest.new_count
+            );
+        } else {
+            notify.pending_clearance_digest(&digest);
+            sent_count += 1;
+        }
+        let connection = connections.exclusive()?;
+        usecases::clearance::place::mark_pending_clearance_digest_delivered(&*connection, &digest)?;
+    }
+    Ok(sent_count)
+}
@@ -0,0 +1,77 @@
+use super::*;
+
+use ofdb_core::gateways::notify::NotificationGateway;
+
+/// Sends a digest to all users whose queued changes are due.
+///
+/// Notifications are removed from the queue right after the
+/// digest has been handed over to the gateway.
+pub fn send_due_subscription_digests(
+    connections: &sqlite::Connections,
+    notify: &dyn NotificationGateway,
+) -> Result<usize> {
+    let digests = {
+        let connection = connections.shared()?;
+        usecases::due_subscription_digests(&*connection, TimestampMs::now())?
+    };
+    for digest in &digests {
+        notify.subscription_digest(digest);
+        let connection = connections.exclusive()?;
+        usecases::delete_delivered_subscription_digest(&*connection, digest)?;
+    }
+    Ok(digests.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::prelude::*;
+    use crate::core::util::geo::MapBbox;
+
+    #[test]
+    fn should_discard_queued_changes_after_unsubscribing_from_all_bboxes() {
+        let fixture = BackendFixture::new();
+        fixture.create_user(
+            usecases::NewUser {
+                email: "test@example.com".into(),
+                password: "test123".into(),
+                language: None,
+            },
+            None,
+        );
+        usecases::create_bbox_subscription(
+            &*fixture.db_connections.exclusive().unwrap(),
+            "test@example.com",
+            usecases::NewBboxSubscription {
+                name: "Daily".into(),
+                bbox: MapBbox::new(
+                    MapPoint::from_lat_lng_deg(-1.0, -1.0),
+                    MapPoint::from_lat_lng_deg(1.0, 1.0),
+                ),
+                tags: vec![],
+                categories: vec![],
+                entity_kind: None,
+                change_types: vec![],
+                delivery_mode: Some("daily".into()),
+            },
+        )
+        .unwrap();
+        fixture.create_place(0.into(), None);
+        let queued = || {
+            fixture
+                .db_connections
+                .shared()
+                .unwrap()
+                .load_subscription_notifications(SubscriptionDeliveryMode::Daily)
+                .unwrap()
+                .len()
+        };
+        assert_eq!(1, queued());
+
+        usecases::unsubscribe_all_bboxes(
+            &*fixture.db_connections.exclusive().unwrap(),
+            "test@example.com",
+        )
+        .unwrap();
+        assert_eq!(0, queued());
+    }
+}
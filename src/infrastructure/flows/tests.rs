@@ -92,6 +92,7 @@ pub mod prelude {
             flows::create_rating(
                 &self.db_connections,
                 &mut *self.search_engine.borrow_mut(),
+                &self.notify,
                 rate_entry,
             )
             .unwrap()
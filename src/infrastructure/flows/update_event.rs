@@ -7,7 +7,7 @@ pub fn update_event(
     connections: &sqlite::Connections,
     indexer: &mut dyn EventIndexer,
     notify: &dyn NotificationGateway,
-    token: Option<&str>,
+    org: Option<&Organization>,
     id: Id,
     new_event: usecases::NewEvent,
 ) -> Result<Event> {
@@ -19,17 +19,24 @@ pub fn update_event(
             .transaction::<_, diesel::result::Error, _>(|| {
                 match usecases::import_new_event(
                     &*connection,
-                    token,
+                    org,
                     new_event,
                     usecases::NewEventMode::Update(id.as_str()),
                 ) {
                     Ok(storable) => {
-                        let event = usecases::store_updated_event(&*connection, storable).map_err(
-                            |err| {
+                        let event = usecases::store_updated_event(&*connection, storable)
+                            .and_then(|event| {
+                                usecases::log_event_change(
+                                    &*connection,
+                                    &event,
+                                    ChangeType::Updated,
+                                )?;
+                                Ok(event)
+                            })
+                            .map_err(|err| {
                                 warn!("Failed to store updated event: {}", err);
                                 diesel::result::Error::RollbackTransaction
-                            },
-                        )?;
+                            })?;
                         Ok(event)
                     }
                     Err(err) => {
@@ -71,11 +78,23 @@ fn notify_event_updated(
     event: &Event,
 ) -> Result<()> {
     if let Some(ref location) = event.location {
-        let email_addresses = {
+        let change = ObservedChange {
+            entity_kind: SubscriptionEntityKind::Event,
+            change_type: SubscriptionChangeType::Updated,
+            tags: &event.tags,
+        };
+        let recipients = {
             let conn = connections.shared()?;
-            usecases::email_addresses_by_coordinate(&*conn, location.pos)?
+            usecases::subscription_recipients_by_coordinate(&*conn, location.pos, &change)?
         };
-        notify.event_updated(&email_addresses, event);
+        notify.event_updated(&recipients, event);
+        usecases::queue_subscription_notifications(
+            &*connections.exclusive()?,
+            location.pos,
+            &change,
+            &event.id,
+            &event.title,
+        )?;
     }
     Ok(())
 }
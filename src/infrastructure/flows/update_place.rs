@@ -14,7 +14,7 @@ pub fn update_place(
     cfg: &Cfg,
 ) -> Result<Place> {
     // Update existing entry
-    let (place, ratings) = {
+    let (place, ratings, pending_clearance) = {
         let connection = connections.exclusive()?;
         let mut prepare_err = None;
         connection
@@ -28,14 +28,22 @@ pub fn update_place(
                     &cfg.accepted_licenses,
                 ) {
                     Ok(storable) => {
+                        let pending_clearance = storable.is_pending_clearance();
                         let (place, ratings) =
-                            usecases::store_updated_place(&*connection, storable).map_err(
-                                |err| {
+                            usecases::store_updated_place(&*connection, storable)
+                                .and_then(|(place, ratings)| {
+                                    usecases::log_place_change(
+                                        &*connection,
+                                        &place,
+                                        ChangeType::Updated,
+                                    )?;
+                                    Ok((place, ratings))
+                                })
+                                .map_err(|err| {
                                     warn!("Failed to store updated place: {}", err);
                                     diesel::result::Error::RollbackTransaction
-                                },
-                            )?;
-                        Ok((place, ratings))
+                                })?;
+                        Ok((place, ratings, pending_clearance))
                     }
                     Err(err) => {
                         prepare_err = Some(err);
@@ -68,22 +76,65 @@ pub fn update_place(
             place.id, err
         );
     }
+    if pending_clearance {
+        if let Err(err) =
+            notify_authors_clearance_pending(connections, notify, &place, created_by_email)
+        {
+            error!(
+                "Failed to notify authors of place {} about pending clearance: {}",
+                place.id, err
+            );
+        }
+    }
 
     Ok(place)
 }
 
+fn notify_authors_clearance_pending(
+    connections: &sqlite::Connections,
+    notify: &dyn NotificationGateway,
+    place: &Place,
+    changed_by: Option<&str>,
+) -> Result<()> {
+    let authors = usecases::author_recipients_of_place(
+        &*connections.shared()?,
+        place.id.as_str(),
+        AuthorNotificationKind::Clearance,
+        changed_by,
+    )?;
+    if !authors.is_empty() {
+        notify.own_place_clearance_pending(&authors, place);
+    }
+    Ok(())
+}
+
 fn notify_place_updated(
     connections: &sqlite::Connections,
     notify: &dyn NotificationGateway,
     place: &Place,
 ) -> Result<()> {
-    let (email_addresses, all_categories) = {
+    let change = ObservedChange {
+        entity_kind: SubscriptionEntityKind::Place,
+        change_type: SubscriptionChangeType::Updated,
+        tags: &place.tags,
+    };
+    let (recipients, all_categories) = {
         let connection = connections.shared()?;
-        let email_addresses =
-            usecases::email_addresses_by_coordinate(&*connection, place.location.pos)?;
+        let recipients = usecases::subscription_recipients_by_coordinate(
+            &*connection,
+            place.location.pos,
+            &change,
+        )?;
         let all_categories = connection.all_categories()?;
-        (email_addresses, all_categories)
+        (recipients, all_categories)
     };
-    notify.place_updated(&email_addresses, &place, all_categories);
+    notify.place_updated(&recipients, &place, all_categories);
+    usecases::queue_subscription_notifications(
+        &*connections.exclusive()?,
+        place.location.pos,
+        &change,
+        &place.id,
+        &place.title,
+    )?;
     Ok(())
 }
@@ -3,22 +3,50 @@ pub mod db;
 pub mod error;
 pub mod flows;
 
+use ofdb_core::gateways::email::EmailSender;
 use ofdb_entities::email::*;
-use ofdb_gateways::{mailgun::*, opencage::*, sendmail::*};
-use std::env;
+use ofdb_gateways::{
+    email_templates::EmailTemplates, gazetteer::*, geocoder::*, mailgun::*, nominatim::*,
+    opencage::*, sendmail::*, smtp::*,
+};
+use std::{env, path::Path, sync::Arc};
 
 lazy_static! {
 
-    // TODO: move this to crate::cfg
-    pub static ref GEO_CODING_GW: OpenCage = {
-        let key = match env::var("OPENCAGE_API_KEY") {
-            Ok(key) => Some(key),
-            Err(_) => {
-                warn!("No OpenCage API key found");
-                None
+    /// The geocoders are asked in the order given by the
+    /// comma-separated list in `GEOCODERS`, i.e. `opencage`,
+    /// `nominatim` or `gazetteer`.
+    pub static ref GEO_CODING_GW: GeoCoderChain = {
+        // TODO: move this to crate::cfg
+        let names = env::var("GEOCODERS").unwrap_or_else(|_| "opencage".to_string());
+        let mut chain = GeoCoderChain::default();
+        for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            match name {
+                "opencage" => match env::var("OPENCAGE_API_KEY") {
+                    Ok(key) => chain.push(OpenCage::new(Some(key))),
+                    Err(_) => warn!("No OpenCage API key found"),
+                },
+                "nominatim" => {
+                    let url = env::var("NOMINATIM_URL").unwrap_or_else(|_| DEFAULT_NOMINATIM_URL.to_string());
+                    chain.push(Nominatim::new(url));
+                }
+                "gazetteer" => match env::var("GAZETTEER_FILE") {
+                    Ok(path) => match Gazetteer::from_file(Path::new(&path)) {
+                        Ok(gazetteer) => {
+                            info!("Loaded {} gazetteer entries from {}", gazetteer.len(), path);
+                            chain.push(gazetteer);
+                        }
+                        Err(err) => error!("Failed to load gazetteer from {}: {}", path, err),
+                    },
+                    Err(_) => warn!("No GAZETTEER_FILE found"),
+                },
+                _ => warn!("Unknown geocoder: {}", name),
             }
-        };
-        OpenCage::new(key)
+        }
+        if chain.is_empty() {
+            warn!("No geocoder configured: addresses are not resolved");
+        }
+        chain
     };
 
     pub static ref MAILGUN_GW: Option<Mailgun> = {
@@ -54,6 +82,83 @@ lazy_static! {
             None
         }
     };
+
+    pub static ref SMTP_GW: Option<Smtp> = {
+        // TODO: move this to crate::cfg
+        let host = env::var("SMTP_HOST");
+        let from = env::var("MAIL_GATEWAY_SENDER_ADDRESS");
+        if let (Ok(host), Ok(mail)) = (host, from) {
+            let port = match env::var("SMTP_PORT").ok().map(|p| p.parse::<u16>()).transpose() {
+                Ok(port) => port,
+                Err(err) => {
+                    error!("Invalid SMTP port: {}", err);
+                    return None;
+                }
+            };
+            let security = match env::var("SMTP_SECURITY").ok().map(|s| s.parse::<SmtpSecurity>()).transpose() {
+                Ok(security) => security.unwrap_or_default(),
+                Err(err) => {
+                    error!("{}", err);
+                    return None;
+                }
+            };
+            let cfg = SmtpConfig {
+                host,
+                port,
+                security,
+                username: env::var("SMTP_USERNAME").ok(),
+                password: env::var("SMTP_PASSWORD").ok(),
+                from_email: Email::from(mail),
+            };
+            match Smtp::new(cfg) {
+                Ok(gw) => Some(gw),
+                Err(err) => {
+                    error!("Invalid SMTP configuration: {}", err);
+                    None
+                }
+            }
+        } else {
+            None
+        }
+    };
+
+    pub static ref EMAIL_TEMPLATES: Arc<EmailTemplates> = {
+        // TODO: move this to crate::cfg
+        let templates = match env::var("EMAIL_TEMPLATES_DIR") {
+            Ok(dir) => EmailTemplates::with_overrides(Path::new(&dir)).unwrap_or_else(|err| {
+                error!("Invalid email templates in {}: {}", dir, err);
+                EmailTemplates::builtin()
+            }),
+            Err(_) => EmailTemplates::builtin(),
+        };
+        Arc::new(templates)
+    };
+}
+
+/// The configured gateway for sending emails, if any.
+///
+/// The gateway can be selected explicitly by setting
+/// `MAIL_GATEWAY` to either `smtp`, `mailgun` or `sendmail`.
+/// Otherwise the first configured gateway is used.
+pub fn email_sender() -> Option<&'static (dyn EmailSender + Send + Sync)> {
+    fn sender<T: EmailSender + Send + Sync>(
+        gw: &'static Option<T>,
+    ) -> Option<&'static (dyn EmailSender + Send + Sync)> {
+        gw.as_ref()
+            .map(|gw| gw as &'static (dyn EmailSender + Send + Sync))
+    }
+    match env::var("MAIL_GATEWAY").ok().as_deref() {
+        Some("smtp") => sender(&*SMTP_GW),
+        Some("mailgun") => sender(&*MAILGUN_GW),
+        Some("sendmail") => sender(&*SENDMAIL_GW),
+        Some(gw) => {
+            warn!("Unknown e-mail gateway: {}", gw);
+            None
+        }
+        None => sender(&*MAILGUN_GW)
+            .or_else(|| sender(&*SMTP_GW))
+            .or_else(|| sender(&*SENDMAIL_GW)),
+    }
 }
 
 #[cfg(test)]
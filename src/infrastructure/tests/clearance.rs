@@ -1,5 +1,7 @@
 use super::*;
 
+use std::time::Duration;
+
 pub struct PlaceClearanceFixture {
     backend: flows::BackendFixture,
 
@@ -74,6 +76,7 @@ impl PlaceClearanceFixture {
         flows::review_places(
             &backend.db_connections,
             &mut *backend.search_engine.borrow_mut(),
+            &backend.notify,
             &[archived_place.id.as_str()],
             usecases::Review {
                 status: ReviewStatus::Archived,
@@ -101,6 +104,7 @@ impl PlaceClearanceFixture {
         flows::review_places(
             &backend.db_connections,
             &mut *backend.search_engine.borrow_mut(),
+            &backend.notify,
             &[rejected_place.id.as_str()],
             usecases::Review {
                 status: ReviewStatus::Archived,
@@ -115,13 +119,11 @@ impl PlaceClearanceFixture {
         let organization_without_moderated_tags = Organization {
             id: Id::new(),
             name: "organization_without_moderated_tags".into(),
-            api_token: "organization_without_moderated_tags".into(),
             moderated_tags: vec![],
         };
         let organization_with_add_clearance_tag = Organization {
             id: Id::new(),
             name: "organization_with_add_clearance_tag".into(),
-            api_token: "organization_with_add_clearance_tag".into(),
             moderated_tags: vec![ModeratedTag {
                 label: "add_clearance".into(),
                 allow_add: true,
@@ -132,7 +134,6 @@ impl PlaceClearanceFixture {
         let organization_with_remove_clearance_tag = Organization {
             id: Id::new(),
             name: "organization_with_remove_clearance_tag".into(),
-            api_token: "organization_with_remove_clearance_tag".into(),
             moderated_tags: vec![ModeratedTag {
                 label: "remove_clearance".into(),
                 allow_add: false,
@@ -143,7 +144,6 @@ impl PlaceClearanceFixture {
         let organization_with_add_remove_clearance_tag = Organization {
             id: Id::new(),
             name: "organization_with_add_remove_clearance_tag".into(),
-            api_token: "organization_with_add_remove_clearance_tag".into(),
             moderated_tags: vec![ModeratedTag {
                 label: "add_remove_clearance".into(),
                 allow_add: true,
@@ -187,6 +187,7 @@ impl PlaceClearanceFixture {
         flows::review_places(
             &backend.db_connections,
             &mut *backend.search_engine.borrow_mut(),
+            &backend.notify,
             &[confirmed_place.id.as_str()],
             usecases::Review {
                 status: ReviewStatus::Confirmed,
@@ -605,6 +606,7 @@ fn should_return_the_last_cleared_revision_when_loading_or_searching_cleared_pla
     flows::review_places(
         &fixture.backend.db_connections,
         &mut *fixture.backend.search_engine.get_mut(),
+        &fixture.backend.notify,
         &[place_id.as_ref()],
         usecases::Review {
             status: ReviewStatus::Archived,
@@ -648,6 +650,7 @@ fn should_return_the_last_cleared_revision_when_loading_or_searching_cleared_pla
     flows::review_places(
         &fixture.backend.db_connections,
         &mut *fixture.backend.search_engine.get_mut(),
+        &fixture.backend.notify,
         &[place_id.as_ref()],
         usecases::Review {
             status: ReviewStatus::Confirmed,
@@ -932,3 +935,258 @@ fn should_do_nothing_when_clearing_places_without_pending_clearances() -> flows:
 
     Ok(())
 }
+
+#[test]
+fn should_discard_pending_clearances_when_tag_no_longer_requires_clearance() -> flows::Result<()> {
+    let mut fixture = PlaceClearanceFixture::new();
+    let org = fixture.organization_with_add_clearance_tag;
+    let tag = &org.moderated_tags.first().unwrap().label;
+
+    flows::create_place(
+        &fixture.backend.db_connections,
+        fixture.backend.search_engine.get_mut(),
+        &fixture.backend.notify,
+        usecases::NewPlace {
+            title: "created_place".into(),
+            description: "created_place".into(),
+            tags: vec![tag.clone()],
+            ..default_new_place()
+        },
+        None,
+        None,
+        &Cfg::default(),
+    )?;
+    let count_pending_clearances = || {
+        fixture
+            .backend
+            .db_connections
+            .shared()
+            .unwrap()
+            .count_pending_clearances_for_places(&org.id)
+            .unwrap()
+    };
+    assert_eq!(1, count_pending_clearances());
+
+    // Renaming the organization keeps pending clearances
+    let update = usecases::UpdateOrganization {
+        name: "renamed".into(),
+        moderated_tags: org.moderated_tags.clone(),
+    };
+    usecases::update_organization(
+        &*fixture.backend.db_connections.exclusive()?,
+        &org.id,
+        update,
+    )?;
+    assert_eq!(1, count_pending_clearances());
+
+    // Pending clearances are discarded if the tag
+    // no longer requires clearance
+    let mut moderated_tags = org.moderated_tags.clone();
+    moderated_tags[0].require_clearance = false;
+    let update = usecases::UpdateOrganization {
+        name: "renamed".into(),
+        moderated_tags,
+    };
+    usecases::update_organization(
+        &*fixture.backend.db_connections.exclusive()?,
+        &org.id,
+        update,
+    )?;
+    assert_eq!(0, count_pending_clearances());
+
+    Ok(())
+}
+
+#[test]
+fn should_add_pending_clearances_when_tag_starts_to_require_clearance() -> flows::Result<()> {
+    let mut fixture = PlaceClearanceFixture::new();
+    let org = fixture.organization_with_add_clearance_tag;
+
+    flows::create_place(
+        &fixture.backend.db_connections,
+        fixture.backend.search_engine.get_mut(),
+        &fixture.backend.notify,
+        usecases::NewPlace {
+            title: "tagged_place".into(),
+            description: "tagged_place".into(),
+            tags: vec!["new_clearance".into()],
+            ..default_new_place()
+        },
+        None,
+        None,
+        &Cfg::default(),
+    )?;
+    let count_pending_clearances = || {
+        fixture
+            .backend
+            .db_connections
+            .shared()
+            .unwrap()
+            .count_pending_clearances_for_places(&org.id)
+            .unwrap()
+    };
+    assert_eq!(0, count_pending_clearances());
+
+    let mut moderated_tags = org.moderated_tags.clone();
+    moderated_tags.push(ModeratedTag {
+        label: "new_clearance".into(),
+        allow_add: true,
+        allow_remove: true,
+        require_clearance: true,
+    });
+    let update = usecases::UpdateOrganization {
+        name: org.name.clone(),
+        moderated_tags,
+    };
+    usecases::update_organization(
+        &*fixture.backend.db_connections.exclusive()?,
+        &org.id,
+        update.clone(),
+    )?;
+    assert_eq!(1, count_pending_clearances());
+    let pending_clearances = fixture
+        .backend
+        .db_connections
+        .shared()?
+        .list_pending_clearances_for_places(&org.id, &Default::default())?;
+    assert_eq!(None, pending_clearances[0].last_cleared_revision);
+
+    // Updating the organization again doesn't add any
+    // more pending clearances
+    usecases::update_organization(
+        &*fixture.backend.db_connections.exclusive()?,
+        &org.id,
+        update,
+    )?;
+    assert_eq!(1, count_pending_clearances());
+
+    Ok(())
+}
+
+#[test]
+fn should_delete_pending_clearances_when_deleting_organization() -> flows::Result<()> {
+    let fixture = PlaceClearanceFixture::new();
+    let org = fixture.organization_with_add_remove_clearance_tag;
+
+    usecases::delete_organization(&*fixture.backend.db_connections.exclusive()?, &org.id)?;
+    let db = fixture.backend.db_connections.shared()?;
+    assert!(db.get_org_by_id(&org.id).is_err());
+    assert_eq!(
+        None,
+        db.map_tag_to_clearance_org_id("add_remove_clearance")?
+    );
+    assert_eq!(0, db.count_pending_clearances_for_places(&org.id)?);
+
+    Ok(())
+}
+
+#[test]
+fn should_notify_contacts_about_new_pending_clearances() -> flows::Result<()> {
+    let fixture = PlaceClearanceFixture::new();
+    let org = fixture.organization_with_add_clearance_tag;
+    let tag = &org.moderated_tags.first().unwrap().label;
+    {
+        let db = fixture.backend.db_connections.exclusive()?;
+        let mut user = db.get_user_by_email(&fixture.user_email)?;
+        user.email_confirmed = true;
+        db.update_user(&user)?;
+        db.set_org_member(&OrganizationMember {
+            org_id: org.id.clone(),
+            email: fixture.user_email.to_string(),
+            role: OrganizationRole::ClearanceReviewer,
+        })?;
+    }
+    let due_digests = |batch_period| {
+        let db = fixture.backend.db_connections.shared().unwrap();
+        usecases::clearance::place::due_pending_clearance_digest_org_ids(
+            &*db,
+            TimestampMs::now(),
+            batch_period,
+        )
+        .unwrap()
+        .iter()
+        .filter_map(|org_id| {
+            usecases::clearance::place::new_pending_clearance_digest(&*db, org_id).unwrap()
+        })
+        .collect::<Vec<_>>()
+    };
+    assert!(due_digests(Duration::from_secs(0)).is_empty());
+
+    let created_place = flows::create_place(
+        &fixture.backend.db_connections,
+        &mut *fixture.backend.search_engine.borrow_mut(),
+        &fixture.backend.notify,
+        usecases::NewPlace {
+            title: "created_place".into(),
+            description: "created_place".into(),
+            tags: vec![tag.clone()],
+            ..default_new_place()
+        },
+        None,
+        None,
+        &Cfg::default(),
+    )?;
+
+    // Not yet due
+    assert!(due_digests(Duration::from_secs(60 * 60)).is_empty());
+
+    let digests = due_digests(Duration::from_secs(0));
+    assert_eq!(1, digests.len());
+    let digest = &digests[0];
+    assert_eq!(org.id, digest.org_id);
+    assert_eq!(vec![fixture.user_email.to_string()], digest.recipients);
+    assert_eq!(1, digest.places.len());
+    assert_eq!(created_place.id, digest.places[0].id);
+    assert_eq!(1, digest.new_count);
+    assert_eq!(1, digest.total_count);
+
+    usecases::clearance::place::mark_pending_clearance_digest_delivered(
+        &*fixture.backend.db_connections.exclusive()?,
+        digest,
+    )?;
+    // Each pending clearance is only announced once
+    assert!(due_digests(Duration::from_secs(0)).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn should_skip_organizations_without_contacts_when_notifying_about_new_pending_clearances(
+) -> flows::Result<()> {
+    let fixture = PlaceClearanceFixture::new();
+    let org = fixture.organization_with_add_clearance_tag;
+    let tag = &org.moderated_tags.first().unwrap().label;
+    flows::create_place(
+        &fixture.backend.db_connections,
+        &mut *fixture.backend.search_engine.borrow_mut(),
+        &fixture.backend.notify,
+        usecases::NewPlace {
+            title: "created_place".into(),
+            description: "created_place".into(),
+            tags: vec![tag.clone()],
+            ..default_new_place()
+        },
+        None,
+        None,
+        &Cfg::default(),
+    )?;
+
+    let sent_count = flows::send_due_pending_clearance_digests(
+        &fixture.backend.db_connections,
+        &fixture.backend.notify,
+        Duration::from_secs(0),
+    )?;
+    assert_eq!(0, sent_count);
+    // The pending clearance is not announced again
+    // after a contact has joined the organization
+    assert!(
+        usecases::clearance::place::due_pending_clearance_digest_org_ids(
+            &*fixture.backend.db_connections.shared()?,
+            TimestampMs::now(),
+            Duration::from_secs(0),
+        )?
+        .is_empty()
+    );
+
+    Ok(())
+}
@@ -1,14 +1,14 @@
 use crate::{
-    core::prelude::*,
+    core::{prelude::*, usecases},
     infrastructure::{
         cfg::Cfg,
         db::{sqlite, tantivy},
-        GEO_CODING_GW,
+        email_sender, flows, GEO_CODING_GW,
     },
     ports::web,
 };
 
-use clap::{crate_authors, App, Arg};
+use clap::{crate_authors, App, AppSettings, Arg, ArgMatches, SubCommand};
 use dotenv::dotenv;
 use ofdb_core::gateways::geocode::GeoCodingGateway;
 use std::{env, path::Path};
@@ -37,6 +37,182 @@ fn update_event_locations<D: Db>(db: &mut D) -> Result<()> {
     Ok(())
 }
 
+fn print_org(org: &Organization) {
+    println!("{}\t{}", org.id, org.name);
+    for t in &org.moderated_tags {
+        println!(
+            "\t#{}\tadd={}\tremove={}\tclearance={}",
+            t.label, t.allow_add, t.allow_remove, t.require_clearance
+        );
+    }
+}
+
+fn update_org_with(
+    connections: &sqlite::Connections,
+    org_id: &Id,
+    modify: impl FnOnce(&mut usecases::UpdateOrganization),
+) -> flows::Result<Organization> {
+    let db = connections.exclusive()?;
+    let Organization {
+        name,
+        moderated_tags,
+        ..
+    } = usecases::get_organization(&*db, org_id)?;
+    let mut update = usecases::UpdateOrganization {
+        name,
+        moderated_tags,
+    };
+    modify(&mut update);
+    Ok(usecases::update_organization(&*db, org_id, update)?)
+}
+
+fn run_org_command(connections: &sqlite::Connections, matches: &ArgMatches) -> flows::Result<()> {
+    match matches.subcommand() {
+        ("list", _) => {
+            for org in usecases::list_organizations(&*connections.shared()?)? {
+                print_org(&org);
+            }
+        }
+        ("create", Some(args)) => {
+            let new_org = usecases::NewOrganization {
+                name: args.value_of("name").unwrap_or_default().to_owned(),
+                moderated_tags: vec![],
+            };
+            let usecases::CreatedOrganization {
+                organization,
+                api_token,
+            } = usecases::create_organization(&mut *connections.exclusive()?, new_org)?;
+            print_org(&organization);
+            // The API token is only revealed once
+            println!("API token: {}", api_token);
+        }
+        ("rename", Some(args)) => {
+            let org_id = args.value_of("id").unwrap_or_default().into();
+            let name = args.value_of("name").unwrap_or_default().to_owned();
+            let org = update_org_with(connections, &org_id, |update| update.name = name)?;
+            print_org(&org);
+        }
+        ("set-tag", Some(args)) => {
+            let org_id = args.value_of("id").unwrap_or_default().into();
+            let mod_tag = ModeratedTag {
+                label: args.value_of("label").unwrap_or_default().to_lowercase(),
+                allow_add: args.is_present("allow-add"),
+                allow_remove: args.is_present("allow-remove"),
+                require_clearance: args.is_present("require-clearance"),
+            };
+            let org = update_org_with(connections, &org_id, |update| {
+                update.moderated_tags.retain(|t| t.label != mod_tag.label);
+                update.moderated_tags.push(mod_tag);
+            })?;
+            print_org(&org);
+        }
+        ("remove-tag", Some(args)) => {
+            let org_id = args.value_of("id").unwrap_or_default().into();
+            let label = args.value_of("label").unwrap_or_default().to_lowercase();
+            let org = update_org_with(connections, &org_id, |update| {
+                update.moderated_tags.retain(|t| t.label != label)
+            })?;
+            print_org(&org);
+        }
+        ("members", Some(args)) => {
+            let org_id = args.value_of("id").unwrap_or_default().into();
+            for m in usecases::list_organization_members(&*connections.shared()?, &org_id)? {
+                println!("{}\t{}", m.email, m.role);
+            }
+        }
+        ("set-member", Some(args)) => {
+            let org_id = args.value_of("id").unwrap_or_default().into();
+            let m = usecases::set_organization_member(
+                &*connections.exclusive()?,
+                &org_id,
+                args.value_of("email").unwrap_or_default(),
+                args.value_of("role").unwrap_or_default(),
+            )?;
+            println!("{}\t{}", m.email, m.role);
+        }
+        ("remove-member", Some(args)) => {
+            let org_id = args.value_of("id").unwrap_or_default().into();
+            usecases::remove_organization_member(
+                &*connections.exclusive()?,
+                &org_id,
+                args.value_of("email").unwrap_or_default(),
+            )?;
+        }
+        ("delete", Some(args)) => {
+            let org_id = args.value_of("id").unwrap_or_default().into();
+            usecases::delete_organization(&*connections.exclusive()?, &org_id)?;
+        }
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+fn org_subcommand<'a, 'b>() -> App<'a, 'b> {
+    let id_arg = Arg::with_name("id")
+        .required(true)
+        .help("The id of the organization");
+    let label_arg = Arg::with_name("label")
+        .required(true)
+        .help("The label of the moderated tag");
+    SubCommand::with_name("org")
+        .about("Manage organizations and their moderated tags")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(SubCommand::with_name("list").about("List all organizations"))
+        .subcommand(
+            SubCommand::with_name("create")
+                .about("Create a new organization and print its API token")
+                .arg(Arg::with_name("name").required(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("rename")
+                .about("Rename an organization")
+                .arg(id_arg.clone())
+                .arg(Arg::with_name("name").required(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("set-tag")
+                .about("Add or replace a moderated tag of an organization")
+                .arg(id_arg.clone())
+                .arg(label_arg.clone())
+                .arg(Arg::with_name("allow-add").long("allow-add"))
+                .arg(Arg::with_name("allow-remove").long("allow-remove"))
+                .arg(Arg::with_name("require-clearance").long("require-clearance")),
+        )
+        .subcommand(
+            SubCommand::with_name("remove-tag")
+                .about("Remove a moderated tag from an organization")
+                .arg(id_arg.clone())
+                .arg(label_arg),
+        )
+        .subcommand(
+            SubCommand::with_name("members")
+                .about("List the members of an organization")
+                .arg(id_arg.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("set-member")
+                .about("Add a member or change the role of a member")
+                .arg(id_arg.clone())
+                .arg(Arg::with_name("email").required(true))
+                .arg(Arg::with_name("role").required(true).possible_values(&[
+                    "owner",
+                    "editor",
+                    "clearance-reviewer",
+                ])),
+        )
+        .subcommand(
+            SubCommand::with_name("remove-member")
+                .about("Remove a member from an organization")
+                .arg(id_arg.clone())
+                .arg(Arg::with_name("email").required(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("delete")
+                .about("Delete an organization")
+                .arg(id_arg),
+        )
+}
+
 #[allow(deprecated)]
 pub fn run() {
     dotenv().ok(); // TODO: either use environment variables XOR cli arguments
@@ -65,6 +241,11 @@ pub fn run() {
                 .long("fix-event-address-location")
                 .help("Update the location of ALL events by resolving their address"),
         )
+        .subcommand(org_subcommand())
+        .subcommand(
+            SubCommand::with_name("backfill-addresses")
+                .about("Fill in the missing addresses of ALL places by resolving their location"),
+        )
         .get_matches();
 
     let mut cfg = Cfg::from_env_or_default();
@@ -87,11 +268,35 @@ pub fn run() {
         .or_else(|| env::var("INDEX_DIR").map(Option::Some).unwrap_or(None));
     let idx_path = idx_dir.as_ref().map(|dir| Path::new(dir));
     info!("Initializing Tantivy full-text search engine");
-    let search_engine = tantivy::SearchEngine::init_with_path(idx_path).unwrap();
+    let mut search_engine = tantivy::SearchEngine::init_with_path(idx_path).unwrap();
 
-    #[allow(clippy::match_single_binding)]
     match matches.subcommand() {
+        ("org", Some(org_matches)) => {
+            if let Err(err) = run_org_command(&connections, org_matches) {
+                error!("{}", err);
+                std::process::exit(1);
+            }
+        }
+        ("backfill-addresses", _) => {
+            info!("Filling in missing place addresses...");
+            match flows::prelude::backfill_place_addresses(
+                &connections,
+                &mut search_engine,
+                &*GEO_CODING_GW,
+                &cfg,
+            ) {
+                Ok(count) => println!("Filled in the addresses of {} places", count),
+                Err(err) => {
+                    error!("{}", err);
+                    std::process::exit(1);
+                }
+            }
+        }
         _ => {
+            if email_sender().is_some() && cfg.unsubscribe_secret.is_none() {
+                error!("Sending e-mails requires an UNSUBSCRIBE_SECRET");
+                std::process::exit(1);
+            }
             if matches.is_present("fix-event-address-location") {
                 info!("Updating all event locations...");
                 update_event_locations(&mut *connections.exclusive().unwrap()).unwrap();
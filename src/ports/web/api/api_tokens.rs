@@ -0,0 +1,59 @@
+use super::*;
+
+// All API tokens are managed by admins on behalf of the organizations.
+
+#[get("/organizations/<org_id>/api-tokens")]
+pub fn get_api_tokens(
+    db: sqlite::Connections,
+    auth: Auth,
+    org_id: String,
+) -> Result<Vec<json::ApiToken>> {
+    let db = db.shared()?;
+    auth.user_with_min_role(&*db, Role::Admin)?;
+    let api_tokens = usecases::list_api_tokens(&*db, &org_id.into())?;
+    Ok(Json(api_tokens.into_iter().map(Into::into).collect()))
+}
+
+#[post(
+    "/organizations/<org_id>/api-tokens",
+    format = "application/json",
+    data = "<new_token>"
+)]
+pub fn post_api_token(
+    db: sqlite::Connections,
+    auth: Auth,
+    org_id: String,
+    new_token: Json<json::NewApiToken>,
+) -> Result<json::IssuedApiToken> {
+    auth.user_with_min_role(&*db.shared()?, Role::Admin)?;
+    let issued = usecases::create_api_token(
+        &*db.exclusive()?,
+        &org_id.into(),
+        new_token.into_inner().into(),
+    )?;
+    Ok(Json(issued.into()))
+}
+
+#[post("/organizations/<org_id>/api-tokens/<id>/rotate")]
+pub fn post_rotate_api_token(
+    db: sqlite::Connections,
+    auth: Auth,
+    org_id: String,
+    id: String,
+) -> Result<json::IssuedApiToken> {
+    auth.user_with_min_role(&*db.shared()?, Role::Admin)?;
+    let issued = usecases::rotate_api_token(&*db.exclusive()?, &org_id.into(), &id.into())?;
+    Ok(Json(issued.into()))
+}
+
+#[delete("/organizations/<org_id>/api-tokens/<id>")]
+pub fn delete_api_token(
+    db: sqlite::Connections,
+    auth: Auth,
+    org_id: String,
+    id: String,
+) -> StatusResult {
+    auth.user_with_min_role(&*db.shared()?, Role::Admin)?;
+    usecases::delete_api_token(&*db.exclusive()?, &org_id.into(), &id.into())?;
+    Ok(Status::NoContent)
+}
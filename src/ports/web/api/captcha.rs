@@ -1,4 +1,7 @@
-use super::super::guards::{COOKIE_CAPTCHA_KEY, MAX_CAPTCHA_TTL};
+use super::super::{
+    guards::{COOKIE_CAPTCHA_KEY, MAX_CAPTCHA_TTL},
+    rate_limit::{groups, RateLimit},
+};
 use ::captcha::{gen, Difficulty};
 use chrono::prelude::*;
 use rocket::{
@@ -48,13 +51,17 @@ impl CaptchaCache {
 }
 
 #[post("/captcha", rank = 2)]
-pub fn post_captcha(captcha_cache: State<CaptchaCache>) -> Result<String, Status> {
+pub fn post_captcha(
+    _rate_limit: RateLimit<groups::Captcha>,
+    captcha_cache: State<CaptchaCache>,
+) -> Result<String, Status> {
     let uuid = captcha_cache.prepare();
     Ok(uuid.to_simple().to_string())
 }
 
 #[get("/captcha/<token>")]
 pub fn get_captcha(
+    _rate_limit: RateLimit<groups::Captcha>,
     captcha_cache: State<CaptchaCache>,
     token: &RawStr,
 ) -> Result<Content<Vec<u8>>, Status> {
@@ -71,6 +78,7 @@ pub fn get_captcha(
 
 #[post("/captcha/<token>/verify", format = "plain", data = "<data>")]
 pub fn post_captcha_verify(
+    _rate_limit: RateLimit<groups::Captcha>,
     mut cookies: Cookies,
     captcha_cache: State<CaptchaCache>,
     token: &RawStr,
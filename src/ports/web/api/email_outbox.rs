@@ -0,0 +1,21 @@
+use super::*;
+
+const EMAILS_MAX_LIMIT: u64 = 1000;
+const EMAILS_DEFAULT_LIMIT: u64 = 100;
+
+#[get("/email-outbox?<offset>&<limit>")]
+pub fn get_email_outbox(
+    db: sqlite::Connections,
+    auth: Auth,
+    offset: Option<u64>,
+    limit: Option<u64>,
+) -> Result<json::EmailOutboxStatus> {
+    let pagination = Pagination {
+        offset,
+        limit: Some(limit.unwrap_or(EMAILS_DEFAULT_LIMIT).min(EMAILS_MAX_LIMIT)),
+    };
+    let db = db.shared()?;
+    auth.user_with_min_role(&*db, Role::Admin)?;
+    let status = usecases::load_email_outbox_status(&*db, &pagination)?;
+    Ok(Json(status.into()))
+}
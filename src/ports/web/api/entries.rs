@@ -1,6 +1,6 @@
-use super::{super::guards::*, Result};
+use super::{super::guards::*, linked_data, JsonLdResult, Result};
 use crate::{
-    adapters::json,
+    adapters::{json, json_ld},
     core::{prelude::*, usecases, util},
     infrastructure::{
         cfg::Cfg,
@@ -9,6 +9,7 @@ use crate::{
     },
     ports::web::{notify::*, popular_tags_cache::PopularTagsCache},
 };
+use ofdb_gateways::geocoder::ThrottledGeoCoder;
 use rocket::{self, request::Form, State};
 use rocket_contrib::json::Json;
 use std::time::Duration;
@@ -19,6 +20,24 @@ pub struct GetEntryQuery {
 }
 
 #[get("/entries/<ids>?<query..>")]
+pub fn get_entry_json_ld(
+    db: sqlite::Connections,
+    _accept: AcceptJsonLd,
+    ids: String,
+    query: Form<GetEntryQuery>,
+) -> JsonLdResult<Vec<json_ld::Organization>> {
+    let ids = util::split_ids(&ids);
+    if ids.is_empty() {
+        return Ok(linked_data(vec![]));
+    }
+    let GetEntryQuery { ref org_tag } = query.into_inner();
+    let places = usecases::load_places(&*db.shared()?, &ids, org_tag.as_ref().map(String::as_str))?;
+    Ok(linked_data(
+        places.iter().map(|(place, _)| place.into()).collect(),
+    ))
+}
+
+#[get("/entries/<ids>?<query..>", rank = 2)]
 pub fn get_entry(
     db: sqlite::Connections,
     ids: String,
@@ -163,20 +182,55 @@ pub fn get_entries_most_popular_tags(
     Ok(Json(results))
 }
 
+// Places that have only been given a location
+// are completed by reverse geocoding.
+fn fill_in_missing_address(
+    connections: &sqlite::Connections,
+    geocoder: &ThrottledGeoCoder,
+    cfg: &Cfg,
+    p: &mut usecases::NewPlace,
+) {
+    let addr = Address {
+        street: p.street.clone(),
+        zip: p.zip.clone(),
+        city: p.city.clone(),
+        country: p.country.clone(),
+        state: p.state.clone(),
+    };
+    if let Some(addr) = flows::fill_in_missing_address(
+        connections,
+        geocoder,
+        &addr,
+        (p.lat, p.lng),
+        cfg.geocode_cache_ttl,
+    ) {
+        p.street = addr.street;
+        p.zip = addr.zip;
+        p.city = addr.city;
+        p.country = addr.country;
+        p.state = addr.state;
+    }
+}
+
 #[post("/entries", format = "application/json", data = "<body>")]
 pub fn post_entry(
+    _rate_limit: RateLimit<groups::Entries>,
     auth: Auth,
     connections: sqlite::Connections,
     notify: Notify,
     mut search_engine: tantivy::SearchEngine,
     body: Json<json::NewPlace>,
     cfg: State<Cfg>,
+    geocoder: State<ThrottledGeoCoder<'static>>,
 ) -> Result<String> {
-    let org = auth.organization(&*connections.shared()?).ok();
+    let org = auth
+        .organization(&connections, &[ApiTokenScope::PlacesWrite])
+        .ok();
     if org.is_none() && auth.account_email().is_err() && cfg.protect_with_captcha {
         auth.has_captcha()?;
     }
-    let new_place = body.into_inner().into();
+    let mut new_place: usecases::NewPlace = body.into_inner().into();
+    fill_in_missing_address(&connections, &geocoder, &cfg, &mut new_place);
     Ok(Json(
         flows::create_place(
             &connections,
@@ -194,6 +248,7 @@ pub fn post_entry(
 
 #[put("/entries/<id>", format = "application/json", data = "<data>")]
 pub fn put_entry(
+    _rate_limit: RateLimit<groups::Entries>,
     auth: Auth,
     connections: sqlite::Connections,
     mut search_engine: tantivy::SearchEngine,
@@ -202,7 +257,9 @@ pub fn put_entry(
     data: Json<json::UpdatePlace>,
     cfg: State<Cfg>,
 ) -> Result<String> {
-    let org = auth.organization(&*connections.shared()?).ok();
+    let org = auth
+        .organization(&connections, &[ApiTokenScope::PlacesWrite])
+        .ok();
     if org.is_none() && auth.account_email().is_err() && cfg.protect_with_captcha {
         auth.has_captcha()?;
     }
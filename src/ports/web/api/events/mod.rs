@@ -5,9 +5,9 @@ use crate::{
         prelude::Result as CoreResult,
         util::{geo::MapBbox, validate},
     },
-    infrastructure::{flows::prelude as flows, GEO_CODING_GW},
+    infrastructure::flows::prelude as flows,
 };
-use ofdb_core::gateways::geocode::GeoCodingGateway;
+use ofdb_gateways::geocoder::ThrottledGeoCoder;
 
 use rocket::{
     http::{RawStr, Status as HttpStatus},
@@ -17,7 +17,12 @@ use rocket::{
 #[cfg(test)]
 mod tests;
 
-fn check_and_set_address_location(e: &mut usecases::NewEvent) -> Option<MapPoint> {
+fn check_and_set_address_location(
+    connections: &sqlite::Connections,
+    geocoder: &ThrottledGeoCoder,
+    cfg: &Cfg,
+    e: &mut usecases::NewEvent,
+) -> Option<MapPoint> {
     let pos = if let (Some(lat), Some(lng)) = (e.lat, e.lng) {
         MapPoint::try_from_lat_lng_deg(lat, lng)
             .map(Some)
@@ -38,9 +43,8 @@ fn check_and_set_address_location(e: &mut usecases::NewEvent) -> Option<MapPoint
         state: e.state.clone(),
     };
 
-    GEO_CODING_GW
-        .resolve_address_lat_lng(&addr)
-        .and_then(|(lat, lng)| {
+    flows::resolve_address_lat_lng(connections, geocoder, &addr, cfg.geocode_cache_ttl).and_then(
+        |(lat, lng)| {
             if let Ok(pos) = MapPoint::try_from_lat_lng_deg(lat, lng) {
                 log::debug!(
                     "Updating event location: ({:?}, {:?}) -> {:?}",
@@ -52,7 +56,38 @@ fn check_and_set_address_location(e: &mut usecases::NewEvent) -> Option<MapPoint
                 e.lng = Some(lng);
             }
             pos
-        })
+        },
+    )
+}
+
+// Events that have only been given a location
+// are completed by reverse geocoding.
+fn fill_in_missing_address(
+    connections: &sqlite::Connections,
+    geocoder: &ThrottledGeoCoder,
+    cfg: &Cfg,
+    e: &mut usecases::NewEvent,
+) {
+    let lat_lng = match (e.lat, e.lng) {
+        (Some(lat), Some(lng)) => (lat, lng),
+        _ => return,
+    };
+    let addr = Address {
+        street: e.street.clone(),
+        zip: e.zip.clone(),
+        city: e.city.clone(),
+        country: e.country.clone(),
+        state: e.state.clone(),
+    };
+    if let Some(addr) =
+        flows::fill_in_missing_address(connections, geocoder, &addr, lat_lng, cfg.geocode_cache_ttl)
+    {
+        e.street = addr.street;
+        e.zip = addr.zip;
+        e.city = addr.city;
+        e.country = addr.country;
+        e.state = addr.state;
+    }
 }
 
 #[post("/events", format = "application/json", data = "<e>")]
@@ -61,18 +96,15 @@ pub fn post_event_with_token(
     mut search_engine: tantivy::SearchEngine,
     notify: Notify,
     auth: Auth,
+    cfg: State<Cfg>,
+    geocoder: State<ThrottledGeoCoder<'static>>,
     e: Json<usecases::NewEvent>,
 ) -> Result<String> {
-    let org = auth.organization(&*connections.shared()?)?;
+    let org = auth.organization(&connections, &[ApiTokenScope::EventsWrite])?;
     let mut e = e.into_inner();
-    check_and_set_address_location(&mut e);
-    let event = flows::create_event(
-        &connections,
-        &mut search_engine,
-        &*notify,
-        Some(&org.api_token),
-        e,
-    )?;
+    check_and_set_address_location(&connections, &geocoder, &cfg, &mut e);
+    fill_in_missing_address(&connections, &geocoder, &cfg, &mut e);
+    let event = flows::create_event(&connections, &mut search_engine, &*notify, Some(&org), e)?;
     Ok(Json(event.id.to_string()))
 }
 
@@ -94,6 +126,16 @@ pub fn post_event(mut _db: sqlite::Connections, _e: Json<usecases::NewEvent>) ->
 // }
 
 #[get("/events/<id>")]
+pub fn get_event_json_ld(
+    db: sqlite::Connections,
+    _accept: AcceptJsonLd,
+    id: String,
+) -> JsonLdResult<adapters::json_ld::Event> {
+    let ev = usecases::get_event(&*db.shared()?, &id)?;
+    Ok(linked_data((&ev).into()))
+}
+
+#[get("/events/<id>", rank = 2)]
 pub fn get_event(db: sqlite::Connections, id: String) -> Result<json::Event> {
     let mut ev = usecases::get_event(&*db.shared()?, &id)?;
     ev.created_by = None; // don't show creators email to unregistered users
@@ -117,17 +159,19 @@ pub fn put_event_with_token(
     mut search_engine: tantivy::SearchEngine,
     notify: Notify,
     auth: Auth,
+    cfg: State<Cfg>,
+    geocoder: State<ThrottledGeoCoder<'static>>,
     id: &RawStr,
     e: Json<usecases::NewEvent>,
 ) -> Result<()> {
-    let org = auth.organization(&*connections.shared()?)?;
+    let org = auth.organization(&connections, &[ApiTokenScope::EventsWrite])?;
     let mut e = e.into_inner();
-    check_and_set_address_location(&mut e);
+    check_and_set_address_location(&connections, &geocoder, &cfg, &mut e);
     flows::update_event(
         &connections,
         &mut search_engine,
         &*notify,
-        Some(&org.api_token),
+        Some(&org),
         id.to_string().into(),
         e,
     )?;
@@ -247,15 +291,14 @@ pub fn get_events_with_token(
     auth: Auth,
     query: usecases::EventQuery,
 ) -> Result<Vec<json::Event>> {
-    let db = connections.shared()?;
-    let org = match auth.organization(&*db) {
+    let org = match auth.organization(&connections, &[]) {
         Ok(org) => org,
         Err(AppError::Business(Error::Parameter(ParameterError::Unauthorized))) => {
-            drop(db);
             return get_events_chronologically(connections, search_engine, query);
         }
         Err(e) => return Err(e),
     };
+    let db = connections.shared()?;
     let events = usecases::query_events(&*db, &search_engine, query)?;
     // Release the database connection asap
     drop(db);
@@ -309,14 +352,14 @@ pub fn csv_export(
     auth: Auth,
     query: usecases::EventQuery,
 ) -> result::Result<Content<String>, AppError> {
-    let db = connections.shared()?;
-
-    let moderated_tags = if let Ok(org) = auth.organization(&*db) {
+    let moderated_tags = if let Ok(org) = auth.organization(&connections, &[]) {
         org.moderated_tags
     } else {
         vec![]
     };
 
+    let db = connections.shared()?;
+
     let user = auth.user_with_min_role(&*db, Role::Scout)?;
 
     let limit = if let Some(limit) = query.limit {
@@ -393,8 +436,8 @@ pub fn delete_event(mut _db: sqlite::Connections, _id: &RawStr) -> HttpStatus {
 
 #[delete("/events/<id>")]
 pub fn delete_event_with_token(db: sqlite::Connections, auth: Auth, id: &RawStr) -> StatusResult {
-    let org = auth.organization(&*db.shared()?)?;
-    usecases::delete_event(&mut *db.exclusive()?, &org.api_token, &id.to_string())?;
+    let org = auth.organization(&db, &[ApiTokenScope::EventsDelete])?;
+    usecases::delete_event(&mut *db.exclusive()?, &org, &id.to_string())?;
     // TODO: Replace with HttpStatus::NoContent
     Ok(HttpStatus::Ok)
 }
@@ -10,18 +10,24 @@ fn only_scouts_and_admins_can_archive_events() {
             email_confirmed: true,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::Admin,
+            disabled: false,
+            language: None,
         },
         User {
             email: "scout@example.com".into(),
             email_confirmed: true,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::Scout,
+            disabled: false,
+            language: None,
         },
         User {
             email: "user@example.com".into(),
             email_confirmed: true,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::User,
+            disabled: false,
+            language: None,
         },
     ];
     for u in users {
@@ -68,6 +74,8 @@ fn archive_events() {
         email_confirmed: true,
         password: "secret".parse::<Password>().unwrap(),
         role: Role::Admin,
+        disabled: false,
+        language: None,
     };
     db.exclusive().unwrap().create_user(&admin).unwrap();
 
@@ -78,9 +86,9 @@ fn archive_events() {
             id: "foo".into(),
             name: "bar".into(),
             moderated_tags: vec!["tag".into()],
-            api_token: "foo".into(),
         })
         .unwrap();
+    register_api_token(&db, "foo", "foo");
     let e1 = usecases::NewEvent {
         title: "x".into(),
         start: Utc::now().naive_utc().timestamp(),
@@ -88,7 +96,8 @@ fn archive_events() {
         created_by: Some("foo@bar.com".into()),
         ..Default::default()
     };
-    let id1 = flows::create_event(&db, &mut search_engine, &notify, Some("foo"), e1)
+    let org = get_org(&db, "foo");
+    let id1 = flows::create_event(&db, &mut search_engine, &notify, Some(&org), e1)
         .unwrap()
         .id;
     let e2 = usecases::NewEvent {
@@ -98,7 +107,7 @@ fn archive_events() {
         created_by: Some("foo@bar.com".into()),
         ..Default::default()
     };
-    let id2 = flows::create_event(&db, &mut search_engine, &notify, Some("foo"), e2)
+    let id2 = flows::create_event(&db, &mut search_engine, &notify, Some(&org), e2)
         .unwrap()
         .id;
 
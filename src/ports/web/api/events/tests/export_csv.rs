@@ -11,18 +11,24 @@ fn export_csv() {
             email_confirmed: true,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::Admin,
+            disabled: false,
+            language: None,
         },
         User {
             email: "scout@example.com".into(),
             email_confirmed: true,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::Scout,
+            disabled: false,
+            language: None,
         },
         User {
             email: "user@example.com".into(),
             email_confirmed: true,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::User,
+            disabled: false,
+            language: None,
         },
     ];
     for u in users {
@@ -35,18 +41,18 @@ fn export_csv() {
             id: "foo".into(),
             name: "foo_name".into(),
             moderated_tags: vec!["tag".into()],
-            api_token: "foo".into(),
         })
         .unwrap();
+    register_api_token(&db, "foo", "foo");
     db.exclusive()
         .unwrap()
         .create_org(Organization {
             id: "bar".into(),
             name: "bar_name".into(),
             moderated_tags: vec!["tag2".into()],
-            api_token: "bar".into(),
         })
         .unwrap();
+    register_api_token(&db, "bar", "bar");
     let start1 = Utc::now().naive_utc().timestamp();
     let e1 = usecases::NewEvent {
         title: "title1".into(),
@@ -58,7 +64,8 @@ fn export_csv() {
         state: Some("state".into()),
         ..Default::default()
     };
-    let id1 = flows::create_event(&db, &mut search_engine, &notify, Some("foo"), e1)
+    let foo_org = get_org(&db, "foo");
+    let id1 = flows::create_event(&db, &mut search_engine, &notify, Some(&foo_org), e1)
         .unwrap()
         .id;
     let start2 = Utc::now().naive_utc().timestamp();
@@ -71,7 +78,8 @@ fn export_csv() {
         telephone: Some("phone2".into()),
         ..Default::default()
     };
-    let id2 = flows::create_event(&db, &mut search_engine, &notify, Some("bar"), e2)
+    let bar_org = get_org(&db, "bar");
+    let id2 = flows::create_event(&db, &mut search_engine, &notify, Some(&bar_org), e2)
         .unwrap()
         .id;
 
@@ -9,3 +9,7 @@ mod delete;
 mod export_csv;
 mod read;
 mod update;
+
+fn get_org(db: &sqlite::Connections, id: &str) -> Organization {
+    db.shared().unwrap().get_org_by_id(&id.into()).unwrap()
+}
@@ -27,6 +27,40 @@ fn by_id() {
             );
 }
 
+#[test]
+fn by_id_as_json_ld() {
+    let (client, db, mut search_engine, notify) = setup2();
+    let e = usecases::NewEvent {
+        title: "x".into(),
+        start: 1_600_000_000,
+        lat: Some(48.1),
+        lng: Some(9.2),
+        organizer: Some("foo".into()),
+        email: Some("test@example.com".into()),
+        created_by: Some("test@example.com".into()),
+        ..Default::default()
+    };
+    let e = flows::create_event(&db, &mut search_engine, &notify, None, e).unwrap();
+    let req = client
+        .get(format!("/events/{}", e.id))
+        .header(Header::new("Accept", "application/ld+json"));
+    let mut response = req.dispatch();
+    assert_eq!(response.status(), HttpStatus::Ok);
+    assert_eq!(
+        response.headers().get_one("Content-Type"),
+        Some("application/ld+json")
+    );
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    let ld: serde_json::Value = serde_json::from_str(&body_str).unwrap();
+    assert_eq!(ld["@context"], "https://schema.org");
+    assert_eq!(ld["@type"], "Event");
+    assert_eq!(ld["identifier"], e.id.to_string());
+    assert_eq!(ld["name"], "x");
+    assert_eq!(ld["startDate"], "2020-09-13T12:26:40Z");
+    assert_eq!(ld["location"]["geo"]["latitude"], 48.1);
+    assert_eq!(ld["organizer"]["name"], "foo");
+}
+
 #[test]
 fn all() {
     let (client, db) = setup();
@@ -159,9 +193,9 @@ fn filtered_by_creator_with_valid_api_token() {
             id: "foo".into(),
             name: "bar".into(),
             moderated_tags: vec!["org-tag".into()],
-            api_token: "foo".into(),
         })
         .unwrap();
+    register_api_token(&db, "foo", "foo");
     let ids: Vec<_> = ["foo@bar.com", "test@test.com", "bla@bla.bla"]
         .iter()
         .map(|m| {
@@ -172,7 +206,8 @@ fn filtered_by_creator_with_valid_api_token() {
                 start: Utc::now().naive_utc().timestamp(),
                 ..Default::default()
             };
-            flows::create_event(&db, &mut search_engine, &notify, Some("foo"), new_event)
+            let org = get_org(&db, "foo");
+            flows::create_event(&db, &mut search_engine, &notify, Some(&org), new_event)
                 .unwrap()
                 .id
         })
@@ -198,9 +233,9 @@ fn filtered_by_creator_with_invalid_api_token() {
             id: "foo".into(),
             name: "bar".into(),
             moderated_tags: vec!["org-tag".into()],
-            api_token: "foo".into(),
         })
         .unwrap();
+    register_api_token(&db, "foo", "foo");
 
     let res = client
         .get("/events?created_by=foo@bar.com")
@@ -307,3 +342,43 @@ fn filtered_by_bounding_box() {
     assert!(!body_str.contains("\"title\":\"0.3-5\""));
     assert!(body_str.contains("\"title\":\"12-0\""));
 }
+
+#[test]
+fn recently_changed_as_feed() {
+    let (client, db, mut search_engine, notify) = setup2();
+    let new_event = |title: &str| usecases::NewEvent {
+        title: title.into(),
+        start: 1_600_000_000,
+        tags: Some(vec!["feed".into()]),
+        created_by: Some("test@example.com".into()),
+        ..Default::default()
+    };
+    let created =
+        flows::create_event(&db, &mut search_engine, &notify, None, new_event("a")).unwrap();
+    let archived =
+        flows::create_event(&db, &mut search_engine, &notify, None, new_event("b")).unwrap();
+    db.exclusive()
+        .unwrap()
+        .archive_events(&[archived.id.as_str()], Timestamp::now())
+        .unwrap();
+
+    let mut response = client.get("/feeds/events.json?tags=feed").dispatch();
+    assert_eq!(response.status(), HttpStatus::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    let feed: serde_json::Value = serde_json::from_str(&body_str).unwrap();
+    let items = feed["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    let change_of = |id: &str| {
+        items
+            .iter()
+            .find(|i| i["id"].as_str().unwrap().contains(id))
+            .map(|i| i["_ofdb"]["change"].as_str().unwrap())
+    };
+    assert_eq!(change_of(created.id.as_str()), Some("created"));
+    assert_eq!(change_of(archived.id.as_str()), Some("archived"));
+
+    let mut response = client.get("/feeds/events.json?tags=other").dispatch();
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    let feed: serde_json::Value = serde_json::from_str(&body_str).unwrap();
+    assert!(feed["items"].as_array().unwrap().is_empty());
+}
@@ -0,0 +1,188 @@
+use super::*;
+use crate::adapters::feed::{self, FeedItem, FeedKind, FeedLinks};
+
+type AtomResult = result::Result<Content<String>, AppError>;
+type JsonFeedResult = result::Result<Content<Json<feed::JsonFeed>>, AppError>;
+
+#[derive(FromForm, Clone)]
+pub struct FeedQuery {
+    bbox: Option<String>,
+    tags: Option<String>,
+    org_tag: Option<String>,
+    offset: Option<u64>,
+    limit: Option<usize>,
+}
+
+impl FeedQuery {
+    fn filter(&self) -> result::Result<usecases::ChangeFeedFilter, AppError> {
+        let FeedQuery {
+            bbox,
+            tags,
+            org_tag,
+            ..
+        } = self;
+        let bbox = bbox
+            .as_deref()
+            .map(|bbox| {
+                bbox.parse::<geo::MapBbox>()
+                    .map_err(|_| Error::Parameter(ParameterError::Bbox))
+            })
+            .transpose()?;
+        Ok(usecases::ChangeFeedFilter {
+            bbox,
+            tags: tags.as_deref().map(util::split_ids).unwrap_or_default(),
+            org_tag: org_tag.as_deref(),
+        })
+    }
+
+    fn offset(&self) -> u64 {
+        self.offset.unwrap_or_default()
+    }
+
+    fn limit(&self) -> usize {
+        self.limit
+            .unwrap_or(usecases::MAX_CHANGE_FEED_LIMIT)
+            .min(usecases::MAX_CHANGE_FEED_LIMIT)
+    }
+
+    /// The absolute URL of the feed with this query
+    /// starting at the given offset.
+    fn feed_url(&self, api_url: &Url, path: &str, offset: u64) -> String {
+        let mut url = api_url.clone();
+        if let Ok(mut segments) = url.path_segments_mut() {
+            segments.pop_if_empty().extend(path.split('/'));
+        }
+        {
+            let mut pairs = url.query_pairs_mut();
+            let params = [
+                ("bbox", &self.bbox),
+                ("tags", &self.tags),
+                ("org_tag", &self.org_tag),
+            ];
+            for (name, value) in params.iter() {
+                if let Some(value) = value {
+                    pairs.append_pair(name, value);
+                }
+            }
+            if offset > 0 {
+                pairs.append_pair("offset", &offset.to_string());
+            }
+            if let Some(limit) = self.limit {
+                pairs.append_pair("limit", &limit.to_string());
+            }
+        }
+        if url.query() == Some("") {
+            url.set_query(None);
+        }
+        url.into_string()
+    }
+
+    fn links(&self, cfg: &Cfg, path: &str, next_offset: Option<u64>) -> FeedLinks {
+        FeedLinks {
+            this: self.feed_url(&cfg.api_url, path, self.offset()),
+            next: next_offset.map(|offset| self.feed_url(&cfg.api_url, path, offset)),
+        }
+    }
+}
+
+fn place_feed_items(
+    db: &sqlite::Connections,
+    cfg: &Cfg,
+    query: &FeedQuery,
+) -> result::Result<(Vec<FeedItem>, Option<u64>), AppError> {
+    let usecases::ChangeFeedPage {
+        changes,
+        next_offset,
+    } = usecases::recent_place_changes(
+        &*db.shared()?,
+        &query.filter()?,
+        query.offset(),
+        query.limit(),
+    )?;
+    let items = changes
+        .iter()
+        .map(|change| FeedItem::from_place_change(change, cfg.map_app_url.as_str()))
+        .collect();
+    Ok((items, next_offset))
+}
+
+fn event_feed_items(
+    db: &sqlite::Connections,
+    query: &FeedQuery,
+) -> result::Result<(Vec<FeedItem>, Option<u64>), AppError> {
+    let usecases::ChangeFeedPage {
+        changes,
+        next_offset,
+    } = usecases::recent_event_changes(
+        &*db.shared()?,
+        &query.filter()?,
+        query.offset(),
+        query.limit(),
+    )?;
+    Ok((changes.iter().map(Into::into).collect(), next_offset))
+}
+
+fn atom(kind: FeedKind, links: &FeedLinks, items: &[FeedItem]) -> Content<String> {
+    Content(
+        ContentType::new(feed::ATOM_MEDIA_TYPE_TOP, feed::ATOM_MEDIA_TYPE_SUB),
+        feed::to_atom(kind, links, items),
+    )
+}
+
+fn json_feed(
+    kind: FeedKind,
+    links: FeedLinks,
+    items: Vec<FeedItem>,
+) -> Content<Json<feed::JsonFeed>> {
+    Content(
+        ContentType::new(
+            feed::JSON_FEED_MEDIA_TYPE_TOP,
+            feed::JSON_FEED_MEDIA_TYPE_SUB,
+        ),
+        Json(feed::to_json_feed(kind, links, items)),
+    )
+}
+
+#[get("/feeds/places.atom?<query..>")]
+pub fn get_places_atom_feed(
+    db: sqlite::Connections,
+    cfg: State<Cfg>,
+    query: Form<FeedQuery>,
+) -> AtomResult {
+    let (items, next_offset) = place_feed_items(&db, &cfg, &query)?;
+    let links = query.links(&cfg, "feeds/places.atom", next_offset);
+    Ok(atom(FeedKind::Places, &links, &items))
+}
+
+#[get("/feeds/places.json?<query..>")]
+pub fn get_places_json_feed(
+    db: sqlite::Connections,
+    cfg: State<Cfg>,
+    query: Form<FeedQuery>,
+) -> JsonFeedResult {
+    let (items, next_offset) = place_feed_items(&db, &cfg, &query)?;
+    let links = query.links(&cfg, "feeds/places.json", next_offset);
+    Ok(json_feed(FeedKind::Places, links, items))
+}
+
+#[get("/feeds/events.atom?<query..>")]
+pub fn get_events_atom_feed(
+    db: sqlite::Connections,
+    cfg: State<Cfg>,
+    query: Form<FeedQuery>,
+) -> AtomResult {
+    let (items, next_offset) = event_feed_items(&db, &query)?;
+    let links = query.links(&cfg, "feeds/events.atom", next_offset);
+    Ok(atom(FeedKind::Events, &links, &items))
+}
+
+#[get("/feeds/events.json?<query..>")]
+pub fn get_events_json_feed(
+    db: sqlite::Connections,
+    cfg: State<Cfg>,
+    query: Form<FeedQuery>,
+) -> JsonFeedResult {
+    let (items, next_offset) = event_feed_items(&db, &query)?;
+    let links = query.links(&cfg, "feeds/events.json", next_offset);
+    Ok(json_feed(FeedKind::Events, links, items))
+}
@@ -1,12 +1,16 @@
-use super::guards::*;
+use super::{
+    guards::*,
+    rate_limit::{groups, RateLimit},
+};
 use crate::{
-    adapters::{self, json},
+    adapters::{self, json, json_ld},
     core::{
         prelude::*,
         usecases::{self, DuplicateType},
         util::{self, geo},
     },
     infrastructure::{
+        cfg::Cfg,
         db::{sqlite, tantivy},
         error::AppError,
         flows::prelude as flows,
@@ -23,40 +27,67 @@ use rocket::{
 use rocket_contrib::json::Json;
 use std::result;
 
+mod api_tokens;
 pub mod captcha;
 mod count;
+mod email_outbox;
 mod entries;
 pub mod events;
+mod feeds;
+mod oidc;
+mod organizations;
 mod places;
 mod ratings;
 mod search;
+pub mod stream;
+mod subscriptions;
 #[cfg(test)]
 pub mod tests;
+mod tiles;
 mod users;
+mod webhooks;
 
 type Result<T> = result::Result<Json<T>, AppError>;
 type StatusResult = result::Result<Status, AppError>;
+type JsonLdResult<T> = result::Result<Content<Json<T>>, AppError>;
+
+fn linked_data<T>(obj: T) -> Content<Json<T>> {
+    Content(
+        ContentType::new(json_ld::MEDIA_TYPE_TOP, json_ld::MEDIA_TYPE_SUB),
+        Json(obj),
+    )
+}
 
 pub fn routes() -> Vec<Route> {
     routes![
         post_login,
         post_logout,
+        oidc::get_oidc_login,
+        oidc::get_oidc_callback,
+        post_token_refresh,
         confirm_email_address,
         subscribe_to_bbox,
-        get_bbox_subscriptions,
         unsubscribe_all_bboxes,
+        subscriptions::get_bbox_subscriptions,
+        subscriptions::post_bbox_subscription,
+        subscriptions::put_bbox_subscription,
+        subscriptions::delete_bbox_subscription,
+        subscriptions::post_unsubscribe,
         entries::get_entry,
+        entries::get_entry_json_ld,
         entries::get_entries_recently_changed,
         entries::get_entries_most_popular_tags,
         entries::post_entry,
         entries::put_entry,
         get_place,
+        get_place_json_ld,
         get_place_history,
         get_place_history_revision,
         post_places_review,
         events::post_event,
         events::post_event_with_token,
         events::get_event,
+        events::get_event_json_ld,
         events::get_events_chronologically,
         events::get_events_with_token,
         events::put_event,
@@ -65,18 +96,43 @@ pub fn routes() -> Vec<Route> {
         events::delete_event,
         events::delete_event_with_token,
         events::csv_export,
+        feeds::get_places_atom_feed,
+        feeds::get_places_json_feed,
+        feeds::get_events_atom_feed,
+        feeds::get_events_json_feed,
+        stream::get_change_stream,
         users::post_request_password_reset,
+        users::post_user_unlock,
+        users::get_users,
+        users::post_user_disable,
+        users::post_user_enable,
+        users::post_user_confirm_email,
+        users::post_user_reset_password,
+        users::get_audit_log,
+        email_outbox::get_email_outbox,
         users::post_reset_password,
         users::post_user,
         ratings::post_rating,
         ratings::load_rating,
         users::get_user,
         users::get_current_user,
+        users::get_current_user_organizations,
+        users::post_current_user_email,
+        users::post_current_user_language,
+        users::get_current_user_notifications,
+        users::post_current_user_notifications,
+        users::post_confirm_email_change,
+        users::get_current_user_totp,
+        users::post_totp_enrollment,
+        users::post_totp_confirmation,
+        users::post_totp_disable,
+        users::post_totp_recovery_codes,
         users::delete_user,
         get_categories,
         get_category,
         get_tags,
         search::get_search,
+        tiles::get_tile_clusters,
         get_duplicates,
         search::post_search_duplicates,
         count::get_count_entries,
@@ -87,6 +143,22 @@ pub fn routes() -> Vec<Route> {
         places::count_pending_clearances,
         places::list_pending_clearances,
         places::update_pending_clearances,
+        webhooks::get_webhooks,
+        webhooks::post_webhook,
+        webhooks::delete_webhook,
+        webhooks::get_webhook_deliveries,
+        organizations::get_organizations,
+        organizations::get_organization,
+        organizations::post_organization,
+        organizations::put_organization,
+        organizations::delete_organization,
+        organizations::get_organization_members,
+        organizations::put_organization_member,
+        organizations::delete_organization_member,
+        api_tokens::get_api_tokens,
+        api_tokens::post_api_token,
+        api_tokens::post_rotate_api_token,
+        api_tokens::delete_api_token,
         captcha::post_captcha,
         captcha::get_captcha,
         captcha::post_captcha_verify,
@@ -94,6 +166,16 @@ pub fn routes() -> Vec<Route> {
 }
 
 #[get("/places/<id>")]
+pub fn get_place_json_ld(
+    db: sqlite::Connections,
+    _accept: AcceptJsonLd,
+    id: String,
+) -> JsonLdResult<json_ld::Organization> {
+    let (place, _) = db.shared()?.get_place(&id)?;
+    Ok(linked_data((&place).into()))
+}
+
+#[get("/places/<id>", rank = 2)]
 pub fn get_place(
     db: sqlite::Connections,
     id: String,
@@ -117,17 +199,15 @@ pub fn get_place_history_revision(
     id: String,
     revision: RevisionValue,
 ) -> Result<json::PlaceHistory> {
-    let place_history = {
-        let db = db.shared()?;
-
-        // The history contains e-mail addresses of registered users
-        // is only permitted for scouts and admins or organizations!
-        if auth.user_with_min_role(&*db, Role::Scout).is_err() {
-            auth.organization(&*db)?;
-        }
-
-        db.get_place_history(&id, Some(revision.into()))?
-    };
+    // The history contains e-mail addresses of registered users
+    // is only permitted for scouts and admins or organizations!
+    if auth
+        .user_with_min_role(&*db.shared()?, Role::Scout)
+        .is_err()
+    {
+        auth.organization(&db, &[ApiTokenScope::ClearanceRead])?;
+    }
+    let place_history = db.shared()?.get_place_history(&id, Some(revision.into()))?;
     Ok(Json(place_history.into()))
 }
 
@@ -137,17 +217,15 @@ pub fn get_place_history(
     auth: Auth,
     id: String,
 ) -> Result<json::PlaceHistory> {
-    let place_history = {
-        let db = db.shared()?;
-
-        // The history contains e-mail addresses of registered users
-        // is only permitted for scouts and admins or for organizations!
-        if auth.user_with_min_role(&*db, Role::Scout).is_err() {
-            auth.organization(&*db)?;
-        }
-
-        db.get_place_history(&id, None)?
-    };
+    // The history contains e-mail addresses of registered users
+    // is only permitted for scouts and admins or for organizations!
+    if auth
+        .user_with_min_role(&*db.shared()?, Role::Scout)
+        .is_err()
+    {
+        auth.organization(&db, &[ApiTokenScope::ClearanceRead])?;
+    }
+    let place_history = db.shared()?.get_place_history(&id, None)?;
     Ok(Json(place_history.into()))
 }
 
@@ -156,6 +234,7 @@ pub fn post_places_review(
     auth: Auth,
     db: sqlite::Connections,
     mut search_engine: tantivy::SearchEngine,
+    notify: Notify,
     ids: String,
     review: Json<json::Review>,
 ) -> Result<()> {
@@ -177,7 +256,7 @@ pub fn post_places_review(
         status: status.into(),
         comment,
     };
-    let update_count = flows::review_places(&db, &mut search_engine, &ids, review)?;
+    let update_count = flows::review_places(&db, &mut search_engine, &*notify, &ids, review)?;
     if update_count < ids.len() {
         log::warn!(
             "Applied review to only {} of {} place(s): {:?}",
@@ -224,9 +303,12 @@ fn get_api() -> Content<&'static str> {
 #[post("/login", format = "application/json", data = "<login>")]
 fn post_login(
     db: sqlite::Connections,
+    notify: Notify,
     mut cookies: Cookies,
     login: Json<json::Credentials>,
+    client_ip: ClientIp,
     jwt_state: State<jwt::JwtState>,
+    cfg: State<Cfg>,
 ) -> Result<Option<ofdb_boundary::JwtToken>> {
     let login = usecases::Login::from(login.into_inner());
     {
@@ -234,13 +316,29 @@ fn post_login(
             email: &login.email,
             password: &login.password,
         };
-        usecases::login_with_email(&*db.shared()?, &credentials)?;
+        flows::login_with_email(
+            &db,
+            &*notify,
+            &cfg.login_throttling,
+            &credentials,
+            login.totp_code.as_deref(),
+            &cfg.totp_required_roles,
+            client_ip.0,
+        )?;
     }
 
     let mut response = None;
     if cfg!(feature = "jwt") {
-        let token = jwt_state.generate_token(&login.email)?;
-        response = Some(ofdb_boundary::JwtToken { token });
+        let session = usecases::start_session(
+            &*db.exclusive()?,
+            &login.email,
+            cfg.jwt_refresh_token_lifetime,
+        )?;
+        let token = jwt_state.generate_token(&login.email, &session.id)?;
+        response = Some(ofdb_boundary::JwtToken {
+            token,
+            refresh_token: Some(session.refresh_token),
+        });
     }
     if cfg!(feature = "cookies") {
         cookies.add_private(
@@ -253,14 +351,50 @@ fn post_login(
 }
 
 #[post("/logout", format = "application/json")]
-fn post_logout(auth: Auth, mut cookies: Cookies, jwt_state: State<jwt::JwtState>) -> Json<()> {
+fn post_logout(
+    db: sqlite::Connections,
+    auth: Auth,
+    mut cookies: Cookies,
+    jwt_state: State<jwt::JwtState>,
+) -> Result<()> {
     cookies.remove_private(Cookie::named(COOKIE_EMAIL_KEY));
     if cfg!(feature = "jwt") {
-        for bearer in auth.bearer_tokens() {
-            jwt_state.blacklist_token(bearer.to_owned());
+        for claims in auth
+            .bearer_tokens()
+            .iter()
+            .filter_map(|bearer| jwt_state.decode_token(bearer).ok())
+        {
+            usecases::end_session(
+                &*db.exclusive()?,
+                &claims.session_id(),
+                &claims.jti,
+                claims.expires_at(),
+            )?;
         }
     }
-    Json(())
+    Ok(Json(()))
+}
+
+#[post("/token/refresh", format = "application/json", data = "<token>")]
+fn post_token_refresh(
+    db: sqlite::Connections,
+    token: Json<ofdb_boundary::JwtRefreshToken>,
+    jwt_state: State<jwt::JwtState>,
+    cfg: State<Cfg>,
+) -> Result<ofdb_boundary::JwtToken> {
+    if !cfg!(feature = "jwt") {
+        return Err(Error::Parameter(ParameterError::Unauthorized).into());
+    }
+    let session = usecases::refresh_session(
+        &*db.exclusive()?,
+        &token.into_inner().refresh_token,
+        cfg.jwt_refresh_token_lifetime,
+    )?;
+    let token = jwt_state.generate_token(&session.email, &session.id)?;
+    Ok(Json(ofdb_boundary::JwtToken {
+        token,
+        refresh_token: Some(session.refresh_token),
+    }))
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -310,25 +444,6 @@ fn unsubscribe_all_bboxes(db: sqlite::Connections, auth: Auth) -> Result<()> {
     Ok(Json(()))
 }
 
-#[get("/bbox-subscriptions")]
-fn get_bbox_subscriptions(
-    db: sqlite::Connections,
-    account: Account,
-) -> Result<Vec<json::BboxSubscription>> {
-    let email = account.email();
-    let user_subscriptions = usecases::get_bbox_subscriptions(&*db.shared()?, &email)?
-        .into_iter()
-        .map(|s| json::BboxSubscription {
-            id: s.id.into(),
-            south_west_lat: s.bbox.southwest().lat().to_deg(),
-            south_west_lng: s.bbox.southwest().lng().to_deg(),
-            north_east_lat: s.bbox.northeast().lat().to_deg(),
-            north_east_lng: s.bbox.northeast().lng().to_deg(),
-        })
-        .collect();
-    Ok(Json(user_subscriptions))
-}
-
 #[get("/tags")]
 fn get_tags(connections: sqlite::Connections) -> Result<Vec<String>> {
     let tags = connections.shared()?.all_tags()?;
@@ -371,13 +486,13 @@ fn entries_csv_export(
     auth: Auth,
     query: Form<search::SearchQuery>,
 ) -> result::Result<Content<String>, AppError> {
-    let db = connections.shared()?;
-
-    let moderated_tags = match auth.organization(&*db) {
+    let moderated_tags = match auth.organization(&connections, &[]) {
         Ok(org) => org.moderated_tags,
         _ => vec![],
     };
 
+    let db = connections.shared()?;
+
     let user = auth.user_with_min_role(&*db, Role::Scout)?;
 
     let (req, limit) = search::parse_search_query(&query)?;
@@ -455,9 +570,23 @@ impl<'r> Responder<'r> for AppError {
                         ParameterError::EmailNotConfirmed => {
                             <Status>::new(403, "EmailNotConfirmed")
                         }
+                        ParameterError::UserDisabled => <Status>::new(403, "UserDisabled"),
+                        ParameterError::SecondFactorRequired => {
+                            <Status>::new(401, "SecondFactorRequired")
+                        }
+                        ParameterError::SecondFactor => <Status>::new(401, "InvalidSecondFactor"),
+                        ParameterError::SecondFactorEnrollmentRequired => {
+                            <Status>::new(403, "SecondFactorEnrollmentRequired")
+                        }
                         ParameterError::Forbidden | ParameterError::ModeratedTag => {
                             Status::Forbidden
                         }
+                        ParameterError::TooManyLoginAttempts(retry_after) => {
+                            return Response::build()
+                                .status(Status::TooManyRequests)
+                                .raw_header("Retry-After", retry_after.to_string())
+                                .ok();
+                        }
                         _ => Status::BadRequest,
                     });
                 }
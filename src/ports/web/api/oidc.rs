@@ -0,0 +1,98 @@
+use super::*;
+use ofdb_gateways::oidc::HttpOidcGateway;
+use rocket::{http::SameSite, response::Redirect};
+
+const COOKIE_OIDC_STATE_KEY: &str = "ofdb-oidc-state";
+
+fn find_provider<'a>(
+    cfg: &'a Cfg,
+    id: &str,
+) -> result::Result<&'a usecases::OidcProvider, AppError> {
+    cfg.oidc_providers
+        .iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| Error::Repo(RepoError::NotFound).into())
+}
+
+fn unauthorized() -> AppError {
+    Error::Parameter(ParameterError::Unauthorized).into()
+}
+
+/// Redirects the user to the identity provider.
+#[get("/login/oidc/<provider_id>")]
+pub fn get_oidc_login(
+    mut cookies: Cookies,
+    cfg: State<Cfg>,
+    provider_id: String,
+) -> result::Result<Redirect, AppError> {
+    let provider = find_provider(&cfg, &provider_id)?;
+    let state = Nonce::new().to_string();
+    let code_verifier = usecases::new_pkce_code_verifier();
+    cookies.add_private(
+        Cookie::build(
+            COOKIE_OIDC_STATE_KEY,
+            format!("{} {} {}", provider.id, state, code_verifier),
+        )
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .finish(),
+    );
+    Ok(Redirect::to(
+        provider
+            .authorization_url(&state, &code_verifier)
+            .to_string(),
+    ))
+}
+
+/// The identity provider redirects the user back to this
+/// endpoint after the authentication.
+#[get("/login/oidc/<provider_id>/callback?<code>&<state>&<error>")]
+#[allow(clippy::too_many_arguments)]
+pub fn get_oidc_callback(
+    db: sqlite::Connections,
+    mut cookies: Cookies,
+    cfg: State<Cfg>,
+    gateway: State<HttpOidcGateway>,
+    provider_id: String,
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+) -> result::Result<Redirect, AppError> {
+    let provider = find_provider(&cfg, &provider_id)?;
+    let login_request = cookies
+        .get_private(COOKIE_OIDC_STATE_KEY)
+        .map(|c| c.value().to_string());
+    cookies.remove_private(Cookie::named(COOKIE_OIDC_STATE_KEY));
+    if let Some(error) = error {
+        info!(
+            "Login with OpenID provider '{}' failed: {}",
+            provider.id, error
+        );
+        return Err(unauthorized());
+    }
+    let login_request = login_request.ok_or_else(unauthorized)?;
+    let mut login_request = login_request.splitn(3, ' ');
+    let expected_provider_id = login_request.next();
+    let expected_state = login_request.next();
+    let code_verifier = login_request.next().ok_or_else(unauthorized)?;
+    // The state must match to prevent cross-site request forgery
+    if expected_provider_id != Some(provider.id.as_str()) || expected_state != state.as_deref() {
+        return Err(unauthorized());
+    }
+    let code = code.ok_or_else(unauthorized)?;
+    // Don't block the database while waiting for the provider
+    let identity = usecases::authenticate_with_oidc(&*gateway, provider, &code, code_verifier)?;
+    let user = usecases::login_with_oidc(
+        &*db.exclusive()?,
+        provider,
+        identity,
+        &cfg.totp_required_roles,
+    )?;
+    cookies.add_private(
+        Cookie::build(COOKIE_EMAIL_KEY, user.email)
+            .http_only(true)
+            .same_site(SameSite::Lax)
+            .finish(),
+    );
+    Ok(Redirect::to("/"))
+}
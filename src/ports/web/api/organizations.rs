@@ -0,0 +1,100 @@
+use super::*;
+
+// Organizations are managed by admins.
+
+#[get("/organizations")]
+pub fn get_organizations(db: sqlite::Connections, auth: Auth) -> Result<Vec<json::Organization>> {
+    let db = db.shared()?;
+    auth.user_with_min_role(&*db, Role::Admin)?;
+    let orgs = usecases::list_organizations(&*db)?;
+    Ok(Json(orgs.into_iter().map(Into::into).collect()))
+}
+
+#[get("/organizations/<id>")]
+pub fn get_organization(
+    db: sqlite::Connections,
+    auth: Auth,
+    id: String,
+) -> Result<json::Organization> {
+    let db = db.shared()?;
+    auth.user_with_min_role(&*db, Role::Admin)?;
+    let org = usecases::get_organization(&*db, &id.into())?;
+    Ok(Json(org.into()))
+}
+
+#[post("/organizations", format = "application/json", data = "<new_org>")]
+pub fn post_organization(
+    db: sqlite::Connections,
+    auth: Auth,
+    new_org: Json<json::NewOrganization>,
+) -> Result<json::CreatedOrganization> {
+    auth.user_with_min_role(&*db.shared()?, Role::Admin)?;
+    let org = usecases::create_organization(&mut *db.exclusive()?, new_org.into_inner().into())?;
+    Ok(Json(org.into()))
+}
+
+#[put("/organizations/<id>", format = "application/json", data = "<update>")]
+pub fn put_organization(
+    db: sqlite::Connections,
+    auth: Auth,
+    id: String,
+    update: Json<json::NewOrganization>,
+) -> Result<json::Organization> {
+    auth.user_with_min_role(&*db.shared()?, Role::Admin)?;
+    let org =
+        usecases::update_organization(&*db.exclusive()?, &id.into(), update.into_inner().into())?;
+    Ok(Json(org.into()))
+}
+
+#[delete("/organizations/<id>")]
+pub fn delete_organization(db: sqlite::Connections, auth: Auth, id: String) -> StatusResult {
+    auth.user_with_min_role(&*db.shared()?, Role::Admin)?;
+    usecases::delete_organization(&*db.exclusive()?, &id.into())?;
+    Ok(Status::NoContent)
+}
+
+// Members are managed by admins or by the owners of an organization.
+
+#[get("/organizations/<id>/members")]
+pub fn get_organization_members(
+    db: sqlite::Connections,
+    auth: Auth,
+    id: String,
+) -> Result<Vec<json::OrganizationMember>> {
+    let db = db.shared()?;
+    let org_id = id.into();
+    usecases::authorize_organization_owner(&*db, &org_id, auth.account_email()?)?;
+    let members = usecases::list_organization_members(&*db, &org_id)?;
+    Ok(Json(members.into_iter().map(Into::into).collect()))
+}
+
+#[put(
+    "/organizations/<id>/members",
+    format = "application/json",
+    data = "<member>"
+)]
+pub fn put_organization_member(
+    db: sqlite::Connections,
+    auth: Auth,
+    id: String,
+    member: Json<json::OrganizationMember>,
+) -> Result<json::OrganizationMember> {
+    let org_id = id.into();
+    usecases::authorize_organization_owner(&*db.shared()?, &org_id, auth.account_email()?)?;
+    let json::OrganizationMember { email, role } = member.into_inner();
+    let member = usecases::set_organization_member(&*db.exclusive()?, &org_id, &email, &role)?;
+    Ok(Json(member.into()))
+}
+
+#[delete("/organizations/<id>/members/<email>")]
+pub fn delete_organization_member(
+    db: sqlite::Connections,
+    auth: Auth,
+    id: String,
+    email: String,
+) -> StatusResult {
+    let org_id = id.into();
+    usecases::authorize_organization_owner(&*db.shared()?, &org_id, auth.account_email()?)?;
+    usecases::remove_organization_member(&*db.exclusive()?, &org_id, &email)?;
+    Ok(Status::NoContent)
+}
@@ -2,9 +2,8 @@ use super::*;
 
 #[get("/places/clearance/count")]
 pub fn count_pending_clearances(db: sqlite::Connections, auth: Auth) -> Result<json::ResultCount> {
-    let db = db.shared()?;
-    let count =
-        usecases::clearance::place::count_pending_clearances(&*db, &auth.organization(&*db)?)?;
+    let org = auth.organization(&db, &[ApiTokenScope::ClearanceRead])?;
+    let count = usecases::clearance::place::count_pending_clearances(&*db.shared()?, &org)?;
     Ok(Json(json::ResultCount { count }))
 }
 
@@ -16,12 +15,9 @@ pub fn list_pending_clearances(
     limit: Option<u64>,
 ) -> Result<Vec<json::PendingClearanceForPlace>> {
     let pagination = Pagination { offset, limit };
-    let db = db.shared()?;
-    let pending_clearances = usecases::clearance::place::list_pending_clearances(
-        &*db,
-        &auth.organization(&*db)?,
-        &pagination,
-    )?;
+    let org = auth.organization(&db, &[ApiTokenScope::ClearanceRead])?;
+    let pending_clearances =
+        usecases::clearance::place::list_pending_clearances(&*db.shared()?, &org, &pagination)?;
     Ok(Json(
         pending_clearances.into_iter().map(Into::into).collect(),
     ))
@@ -38,7 +34,7 @@ pub fn update_pending_clearances(
         .into_iter()
         .map(Into::into)
         .collect();
-    let org = auth.organization(&*db.shared()?)?;
+    let org = auth.organization(&db, &[ApiTokenScope::ClearanceWrite])?;
     let count = usecases::clearance::place::update_pending_clearances(
         &*db.exclusive()?,
         &org,
@@ -4,11 +4,18 @@ use crate::{core::util, infrastructure::flows::prelude as flows};
 
 #[post("/ratings", format = "application/json", data = "<data>")]
 pub fn post_rating(
+    _rate_limit: RateLimit<groups::Ratings>,
     connections: sqlite::Connections,
     mut search_engine: tantivy::SearchEngine,
+    notify: Notify,
     data: Json<usecases::NewPlaceRating>,
 ) -> Result<()> {
-    let _ = flows::create_rating(&connections, &mut search_engine, data.into_inner())?;
+    let _ = flows::create_rating(
+        &connections,
+        &mut search_engine,
+        &*notify,
+        data.into_inner(),
+    )?;
     Ok(Json(()))
 }
 
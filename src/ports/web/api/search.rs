@@ -27,6 +27,35 @@ pub struct SearchQuery {
     limit: Option<usize>,
 }
 
+pub fn parse_place_categories(categories: Option<&str>) -> Vec<&str> {
+    categories
+        .map(util::split_ids)
+        .map(|ids| {
+            ids.into_iter()
+                // Only places, not events
+                .filter(|id| id != &Category::ID_EVENT)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn parse_review_status(status: Option<&str>) -> Vec<ReviewStatus> {
+    status
+        .map(util::split_ids)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|s| {
+            serde_json::from_str::<json::ReviewStatus>(&format!("\"{}\"", s))
+                .map_err(|e| {
+                    log::warn!("Failed to parse status '{}' from search query: {}", s, e);
+                    e
+                })
+                .map(ReviewStatus::from)
+                .ok()
+        })
+        .collect()
+}
+
 pub fn parse_search_query(
     query: &'_ SearchQuery,
 ) -> result::Result<(usecases::SearchRequest<'_>, Option<usize>), AppError> {
@@ -49,36 +78,13 @@ pub fn parse_search_query(
 
     let ids = ids.as_deref().map(util::split_ids).unwrap_or_default();
 
-    let categories = categories
-        .as_deref()
-        .map(util::split_ids)
-        .map(|ids| {
-            ids.into_iter()
-                // Only places, not events
-                .filter(|id| id != &Category::ID_EVENT)
-                .collect()
-        })
-        .unwrap_or_default();
+    let categories = parse_place_categories(categories.as_deref());
 
     let hash_tags = tags.as_deref().map(util::split_ids).unwrap_or_default();
 
     let text = text.as_deref();
 
-    let status = status
-        .as_deref()
-        .map(util::split_ids)
-        .unwrap_or_default()
-        .into_iter()
-        .filter_map(|s| {
-            serde_json::from_str::<json::ReviewStatus>(&format!("\"{}\"", s))
-                .map_err(|e| {
-                    log::warn!("Failed to parse status '{}' from search query: {}", s, e);
-                    e
-                })
-                .map(ReviewStatus::from)
-                .ok()
-        })
-        .collect();
+    let status = parse_review_status(status.as_deref());
 
     Ok((
         usecases::SearchRequest {
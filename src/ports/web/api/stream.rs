@@ -0,0 +1,65 @@
+use super::*;
+use crate::adapters::sse;
+use rocket::request::Request;
+use std::io::Cursor;
+
+/// The maximum number of log entries that are scanned at once.
+const MAX_SCANNED_CHANGES: u64 = 100;
+
+/// The delay before clients reconnect to poll for new changes.
+const RETRY_MILLIS: u64 = 3000;
+
+type EventStreamResult = result::Result<EventStream, AppError>;
+
+pub struct EventStream(String);
+
+impl<'r> Responder<'r> for EventStream {
+    fn respond_to(self, _: &Request) -> result::Result<Response<'r>, Status> {
+        Response::build()
+            .header(ContentType::new(sse::MEDIA_TYPE_TOP, sse::MEDIA_TYPE_SUB))
+            .raw_header("Cache-Control", "no-cache")
+            .sized_body(Cursor::new(self.0))
+            .ok()
+    }
+}
+
+/// Streams committed changes of places, events and ratings.
+///
+/// Each response contains the changes that are available and
+/// is finished immediately without waiting for new changes.
+/// Clients poll by reconnecting after the `retry` delay and
+/// send the id of the last received event in `Last-Event-ID`
+/// which is done automatically by `EventSource`. Clients without
+/// this header only receive future changes.
+#[get("/stream/changes?<bbox>&<tags>")]
+pub fn get_change_stream(
+    db: sqlite::Connections,
+    last_event_id: LastEventId,
+    bbox: Option<String>,
+    tags: Option<String>,
+) -> EventStreamResult {
+    let bbox = bbox
+        .as_deref()
+        .map(|bbox| {
+            bbox.parse::<geo::MapBbox>()
+                .map_err(|_| Error::Parameter(ParameterError::Bbox))
+        })
+        .transpose()?;
+    let filter = usecases::ChangeStreamFilter {
+        bbox,
+        tags: tags
+            .as_deref()
+            .map(util::split_ids)
+            .unwrap_or_default()
+            .into_iter()
+            .map(ToString::to_string)
+            .collect(),
+    };
+    let db = db.shared()?;
+    let after_seq = match last_event_id.0 {
+        Some(seq) => seq,
+        None => usecases::latest_change_seq(&*db)?,
+    };
+    let batch = usecases::load_next_changes(&*db, &filter, after_seq, MAX_SCANNED_CHANGES)?;
+    Ok(EventStream(sse::to_event_stream(&batch, RETRY_MILLIS)))
+}
@@ -0,0 +1,69 @@
+use super::*;
+
+use crate::ports::web::unsubscribe_secret;
+use std::convert::TryFrom;
+
+#[get("/bbox-subscriptions")]
+pub fn get_bbox_subscriptions(
+    db: sqlite::Connections,
+    account: Account,
+) -> Result<Vec<json::BboxSubscription>> {
+    let subscriptions = usecases::get_bbox_subscriptions(&*db.shared()?, account.email())?;
+    Ok(Json(subscriptions.into_iter().map(Into::into).collect()))
+}
+
+#[post(
+    "/bbox-subscriptions",
+    format = "application/json",
+    data = "<new_subscription>"
+)]
+pub fn post_bbox_subscription(
+    db: sqlite::Connections,
+    account: Account,
+    new_subscription: Json<json::NewBboxSubscription>,
+) -> Result<json::BboxSubscription> {
+    let new_subscription = usecases::NewBboxSubscription::try_from(new_subscription.into_inner())
+        .map_err(Error::Parameter)?;
+    let subscription =
+        usecases::create_bbox_subscription(&*db.exclusive()?, account.email(), new_subscription)?;
+    Ok(Json(subscription.into()))
+}
+
+#[put(
+    "/bbox-subscriptions/<id>",
+    format = "application/json",
+    data = "<new_subscription>"
+)]
+pub fn put_bbox_subscription(
+    db: sqlite::Connections,
+    account: Account,
+    id: String,
+    new_subscription: Json<json::NewBboxSubscription>,
+) -> Result<json::BboxSubscription> {
+    let new_subscription = usecases::NewBboxSubscription::try_from(new_subscription.into_inner())
+        .map_err(Error::Parameter)?;
+    let subscription = usecases::update_bbox_subscription(
+        &*db.exclusive()?,
+        account.email(),
+        &id.into(),
+        new_subscription,
+    )?;
+    Ok(Json(subscription.into()))
+}
+
+#[delete("/bbox-subscriptions/<id>")]
+pub fn delete_bbox_subscription(
+    db: sqlite::Connections,
+    account: Account,
+    id: String,
+) -> StatusResult {
+    usecases::delete_bbox_subscription(&*db.exclusive()?, account.email(), &id.into())?;
+    Ok(Status::NoContent)
+}
+
+/// One-click unsubscribe (RFC 8058) without authentication.
+#[post("/unsubscribe?<token>")]
+pub fn post_unsubscribe(db: sqlite::Connections, cfg: State<Cfg>, token: String) -> StatusResult {
+    usecases::unsubscribe_with_token(&*db.exclusive()?, unsubscribe_secret(&cfg)?, &token)?;
+    Ok(Status::NoContent)
+}
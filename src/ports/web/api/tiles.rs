@@ -0,0 +1,52 @@
+use super::{
+    search::{parse_place_categories, parse_review_status},
+    *,
+};
+use ofdb_core::tile::TileId;
+
+/// Limits the number of places that are aggregated into
+/// the clusters of a single tile.
+const MAX_CLUSTERED_PLACES_PER_TILE: usize = 10_000;
+
+#[derive(FromForm, Clone)]
+pub struct TileQuery {
+    categories: Option<String>,
+    org_tag: Option<String>,
+    tags: Option<String>,
+    status: Option<String>,
+}
+
+#[get("/tiles/<z>/<x>/<y>?<query..>")]
+pub fn get_tile_clusters(
+    connections: sqlite::Connections,
+    search_engine: tantivy::SearchEngine,
+    z: u8,
+    x: u32,
+    y: u32,
+    query: Form<TileQuery>,
+) -> Result<Vec<json::PlaceCluster>> {
+    let tile = TileId::try_new(z, x, y).ok_or(Error::Parameter(ParameterError::Tile))?;
+    let TileQuery {
+        categories,
+        org_tag,
+        tags,
+        status,
+    } = &*query;
+    let req = usecases::SearchRequest {
+        bbox: tile.bbox(),
+        ids: vec![],
+        categories: parse_place_categories(categories.as_deref()),
+        org_tag: org_tag.as_deref(),
+        hash_tags: tags.as_deref().map(util::split_ids).unwrap_or_default(),
+        text: None,
+        status: parse_review_status(status.as_deref()),
+    };
+    let clusters = usecases::cluster_places(
+        &*connections.shared()?,
+        &search_engine,
+        tile,
+        req,
+        MAX_CLUSTERED_PLACES_PER_TILE,
+    )?;
+    Ok(Json(clusters.into_iter().map(Into::into).collect()))
+}
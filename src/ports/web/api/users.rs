@@ -1,7 +1,17 @@
 use super::*;
 
+const TOTP_ISSUER: &str = "OpenFairDB";
+
+const USERS_MAX_LIMIT: u64 = 1000;
+const USERS_DEFAULT_LIMIT: u64 = 100;
+
 #[post("/users", format = "application/json", data = "<u>")]
-pub fn post_user(db: sqlite::Connections, n: Notify, u: Json<usecases::NewUser>) -> Result<()> {
+pub fn post_user(
+    _rate_limit: RateLimit<groups::Users>,
+    db: sqlite::Connections,
+    n: Notify,
+    u: Json<usecases::NewUser>,
+) -> Result<()> {
     let new_user = u.into_inner();
     let user = {
         let db = db.exclusive()?;
@@ -18,6 +28,7 @@ pub fn post_user(db: sqlite::Connections, n: Notify, u: Json<usecases::NewUser>)
     data = "<data>"
 )]
 pub fn post_request_password_reset(
+    _rate_limit: RateLimit<groups::Users>,
     connections: sqlite::Connections,
     notify: Notify,
     data: Json<json::RequestPasswordReset>,
@@ -48,12 +59,342 @@ pub fn delete_user(db: sqlite::Connections, account: Account, email: String) ->
     Ok(Json(()))
 }
 
+/// Resets the failed login attempts of a locked account.
+#[post("/users/<email>/unlock")]
+pub fn post_user_unlock(db: sqlite::Connections, auth: Auth, email: String) -> Result<()> {
+    let db = db.exclusive()?;
+    let admin = auth.user_with_min_role(&*db, Role::Admin)?;
+    usecases::unlock_login(&*db, &email)?;
+    usecases::log_admin_action(&*db, &admin.email, AdminAction::UnlockLogin, &email, None)?;
+    Ok(Json(()))
+}
+
+#[get("/users?<email>&<role>&<disabled>&<offset>&<limit>")]
+pub fn get_users(
+    db: sqlite::Connections,
+    auth: Auth,
+    email: Option<String>,
+    role: Option<String>,
+    disabled: Option<bool>,
+    offset: Option<u64>,
+    limit: Option<u64>,
+) -> Result<Vec<json::User>> {
+    let role = role
+        .map(|role| role.parse::<Role>())
+        .transpose()
+        .map_err(|_| Error::Parameter(ParameterError::Role))?;
+    let params = UserSearchParams {
+        email,
+        role,
+        disabled,
+    };
+    let pagination = Pagination {
+        offset,
+        limit: Some(limit.unwrap_or(USERS_DEFAULT_LIMIT).min(USERS_MAX_LIMIT)),
+    };
+    let db = db.shared()?;
+    auth.user_with_min_role(&*db, Role::Admin)?;
+    let users = usecases::search_users(&*db, &params, &pagination)?;
+    Ok(Json(users.into_iter().map(Into::into).collect()))
+}
+
+#[post("/users/<email>/disable")]
+pub fn post_user_disable(db: sqlite::Connections, auth: Auth, email: String) -> Result<()> {
+    let db = db.exclusive()?;
+    let admin = auth.user_with_min_role(&*db, Role::Admin)?;
+    usecases::set_user_disabled(&*db, &admin, &email, true)?;
+    Ok(Json(()))
+}
+
+#[post("/users/<email>/enable")]
+pub fn post_user_enable(db: sqlite::Connections, auth: Auth, email: String) -> Result<()> {
+    let db = db.exclusive()?;
+    let admin = auth.user_with_min_role(&*db, Role::Admin)?;
+    usecases::set_user_disabled(&*db, &admin, &email, false)?;
+    Ok(Json(()))
+}
+
+#[post("/users/<email>/confirm-email")]
+pub fn post_user_confirm_email(db: sqlite::Connections, auth: Auth, email: String) -> Result<()> {
+    let db = db.exclusive()?;
+    let admin = auth.user_with_min_role(&*db, Role::Admin)?;
+    usecases::confirm_user_email_by_admin(&*db, &admin, &email)?;
+    Ok(Json(()))
+}
+
+/// Sends a password reset email to the user.
+#[post("/users/<email>/reset-password")]
+pub fn post_user_reset_password(
+    connections: sqlite::Connections,
+    notify: Notify,
+    auth: Auth,
+    email: String,
+) -> Result<()> {
+    let admin = auth.user_with_min_role(&*connections.shared()?, Role::Admin)?;
+    flows::reset_password_request_by_admin(&connections, &*notify, &admin, &email)?;
+    Ok(Json(()))
+}
+
+#[get("/audit-log?<email>&<offset>&<limit>")]
+pub fn get_audit_log(
+    db: sqlite::Connections,
+    auth: Auth,
+    email: Option<String>,
+    offset: Option<u64>,
+    limit: Option<u64>,
+) -> Result<Vec<json::AuditLogEntry>> {
+    let pagination = Pagination {
+        offset,
+        limit: Some(limit.unwrap_or(USERS_DEFAULT_LIMIT).min(USERS_MAX_LIMIT)),
+    };
+    let db = db.shared()?;
+    auth.user_with_min_role(&*db, Role::Admin)?;
+    let entries = usecases::load_audit_log(&*db, email.as_deref(), &pagination)?;
+    Ok(Json(entries.into_iter().map(Into::into).collect()))
+}
+
 #[get("/users/current", format = "application/json")]
 pub fn get_current_user(db: sqlite::Connections, account: Account) -> Result<json::User> {
     let user = usecases::get_user(&*db.shared()?, account.email(), account.email())?;
     Ok(Json(user.into()))
 }
 
+/// Sends a confirmation email to the new address. The address
+/// is changed after it has been confirmed.
+#[post("/users/current/email", format = "application/json", data = "<data>")]
+pub fn post_current_user_email(
+    _rate_limit: RateLimit<groups::Users>,
+    connections: sqlite::Connections,
+    notify: Notify,
+    account: Account,
+    data: Json<json::RequestEmailChange>,
+) -> Result<()> {
+    let req = data.into_inner();
+    flows::request_email_change(&connections, &*notify, account.email(), &req.new_email)?;
+    Ok(Json(()))
+}
+
+#[post(
+    "/users/current/language",
+    format = "application/json",
+    data = "<data>"
+)]
+pub fn post_current_user_language(
+    db: sqlite::Connections,
+    account: Account,
+    data: Json<json::UserLanguage>,
+) -> Result<json::User> {
+    let json::UserLanguage { language } = data.into_inner();
+    let user =
+        usecases::change_user_language(&*db.exclusive()?, account.email(), language.as_deref())?;
+    Ok(Json(user.into()))
+}
+
+#[get("/users/current/notifications", format = "application/json")]
+pub fn get_current_user_notifications(
+    db: sqlite::Connections,
+    account: Account,
+) -> Result<json::AuthorNotifications> {
+    let settings = usecases::get_author_notifications(&*db.shared()?, account.email())?;
+    Ok(Json(settings.into()))
+}
+
+#[post(
+    "/users/current/notifications",
+    format = "application/json",
+    data = "<data>"
+)]
+pub fn post_current_user_notifications(
+    db: sqlite::Connections,
+    account: Account,
+    data: Json<json::AuthorNotifications>,
+) -> Result<json::AuthorNotifications> {
+    let json::AuthorNotifications {
+        reviews,
+        ratings,
+        clearances,
+    } = data.into_inner();
+    let settings = usecases::update_author_notifications(
+        &*db.exclusive()?,
+        AuthorNotifications {
+            user_email: account.email().to_owned(),
+            reviews,
+            ratings,
+            clearances,
+        },
+    )?;
+    Ok(Json(settings.into()))
+}
+
+#[post(
+    "/users/confirm-email-change",
+    format = "application/json",
+    data = "<data>"
+)]
+pub fn post_confirm_email_change(
+    connections: sqlite::Connections,
+    notify: Notify,
+    data: Json<json::ConfirmEmailChange>,
+) -> Result<()> {
+    let req = data.into_inner();
+    let new_email_nonce = EmailNonce::decode_from_str(&req.token)?;
+    flows::confirm_email_change(&connections, &*notify, &new_email_nonce)?;
+    Ok(Json(()))
+}
+
+#[get("/users/current/organizations", format = "application/json")]
+pub fn get_current_user_organizations(
+    db: sqlite::Connections,
+    account: Account,
+) -> Result<Vec<json::OrganizationMembership>> {
+    let memberships = usecases::list_memberships_of_user(&*db.shared()?, account.email())?;
+    Ok(Json(memberships.into_iter().map(Into::into).collect()))
+}
+
+#[get("/users/current/totp", format = "application/json")]
+pub fn get_current_user_totp(
+    db: sqlite::Connections,
+    account: Account,
+) -> Result<json::TwoFactorStatus> {
+    let status = usecases::get_two_factor_status(&*db.shared()?, account.email())?;
+    Ok(Json(status.into()))
+}
+
+// The second factor is managed with the credentials instead of
+// a session, because users who are required to use a second
+// factor are not able to log in before enabling it. Failed
+// attempts are throttled in the same way as a login.
+fn authenticate(
+    db: &sqlite::Connection,
+    credentials: &json::Credentials,
+) -> result::Result<User, Error> {
+    let credentials = usecases::Credentials {
+        email: &credentials.email,
+        password: &credentials.password,
+    };
+    usecases::authenticate_user(db, &credentials)
+}
+
+fn second_factor(credentials: &json::Credentials) -> result::Result<&str, Error> {
+    credentials
+        .totp_code
+        .as_deref()
+        .ok_or(Error::Parameter(ParameterError::SecondFactorRequired))
+}
+
+fn verify_second_factor(
+    db: &sqlite::Connection,
+    credentials: &json::Credentials,
+) -> result::Result<User, Error> {
+    let user = authenticate(db, credentials)?;
+    usecases::verify_second_factor(db, &user.email, second_factor(credentials)?)?;
+    Ok(user)
+}
+
+#[post(
+    "/users/totp/enroll",
+    format = "application/json",
+    data = "<credentials>"
+)]
+pub fn post_totp_enrollment(
+    db: sqlite::Connections,
+    notify: Notify,
+    cfg: State<Cfg>,
+    client_ip: ClientIp,
+    credentials: Json<json::Credentials>,
+) -> Result<json::TotpEnrollment> {
+    let enrollment = flows::with_login_throttle(
+        &db,
+        &*notify,
+        &cfg.login_throttling,
+        &credentials.email,
+        client_ip.0,
+        |db| {
+            let user = authenticate(db, &credentials)?;
+            usecases::begin_totp_enrollment(db, &user.email, TOTP_ISSUER)
+        },
+    )?;
+    Ok(Json(enrollment.into()))
+}
+
+#[post(
+    "/users/totp/confirm",
+    format = "application/json",
+    data = "<credentials>"
+)]
+pub fn post_totp_confirmation(
+    db: sqlite::Connections,
+    notify: Notify,
+    cfg: State<Cfg>,
+    client_ip: ClientIp,
+    credentials: Json<json::Credentials>,
+) -> Result<Vec<String>> {
+    let recovery_codes = flows::with_login_throttle(
+        &db,
+        &*notify,
+        &cfg.login_throttling,
+        &credentials.email,
+        client_ip.0,
+        |db| {
+            let user = authenticate(db, &credentials)?;
+            usecases::confirm_totp_enrollment(db, &user.email, second_factor(&credentials)?)
+        },
+    )?;
+    Ok(Json(recovery_codes))
+}
+
+#[post(
+    "/users/totp/disable",
+    format = "application/json",
+    data = "<credentials>"
+)]
+pub fn post_totp_disable(
+    db: sqlite::Connections,
+    notify: Notify,
+    cfg: State<Cfg>,
+    client_ip: ClientIp,
+    credentials: Json<json::Credentials>,
+) -> Result<()> {
+    flows::with_login_throttle(
+        &db,
+        &*notify,
+        &cfg.login_throttling,
+        &credentials.email,
+        client_ip.0,
+        |db| {
+            let user = verify_second_factor(db, &credentials)?;
+            usecases::disable_totp(db, &user.email)
+        },
+    )?;
+    Ok(Json(()))
+}
+
+#[post(
+    "/users/totp/recovery-codes",
+    format = "application/json",
+    data = "<credentials>"
+)]
+pub fn post_totp_recovery_codes(
+    db: sqlite::Connections,
+    notify: Notify,
+    cfg: State<Cfg>,
+    client_ip: ClientIp,
+    credentials: Json<json::Credentials>,
+) -> Result<Vec<String>> {
+    let recovery_codes = flows::with_login_throttle(
+        &db,
+        &*notify,
+        &cfg.login_throttling,
+        &credentials.email,
+        client_ip.0,
+        |db| {
+            let user = verify_second_factor(db, &credentials)?;
+            usecases::regenerate_recovery_codes(db, &user.email)
+        },
+    )?;
+    Ok(Json(recovery_codes))
+}
+
 #[get("/users/<email>", format = "application/json", rank = 2)]
 pub fn get_user(db: sqlite::Connections, account: Account, email: String) -> Result<json::User> {
     let user = usecases::get_user(&*db.shared()?, account.email(), &email)?;
@@ -0,0 +1,44 @@
+use super::*;
+use ofdb_gateways::webhook::HttpWebhookGateway;
+
+#[get("/webhooks")]
+pub fn get_webhooks(db: sqlite::Connections, auth: Auth) -> Result<Vec<json::Webhook>> {
+    let org = auth.organization(&db, &ApiTokenScope::all())?;
+    let webhooks = usecases::list_webhooks(&*db.shared()?, &org)?;
+    Ok(Json(webhooks.into_iter().map(Into::into).collect()))
+}
+
+#[post("/webhooks", format = "application/json", data = "<new_webhook>")]
+pub fn post_webhook(
+    db: sqlite::Connections,
+    auth: Auth,
+    gateway: State<HttpWebhookGateway>,
+    new_webhook: Json<json::NewWebhook>,
+) -> Result<json::Webhook> {
+    let org = auth.organization(&db, &ApiTokenScope::all())?;
+    let webhook = usecases::prepare_webhook(&*gateway, &org, new_webhook.into_inner().into())?;
+    let webhook = usecases::register_webhook(&*db.exclusive()?, webhook)?;
+    Ok(Json(webhook.into()))
+}
+
+#[delete("/webhooks/<id>")]
+pub fn delete_webhook(db: sqlite::Connections, auth: Auth, id: String) -> StatusResult {
+    let org = auth.organization(&db, &ApiTokenScope::all())?;
+    usecases::delete_webhook(&*db.exclusive()?, &org, &id.into())?;
+    Ok(Status::NoContent)
+}
+
+#[get("/webhooks/<id>/deliveries?<offset>&<limit>")]
+pub fn get_webhook_deliveries(
+    db: sqlite::Connections,
+    auth: Auth,
+    id: String,
+    offset: Option<u64>,
+    limit: Option<u64>,
+) -> Result<Vec<json::WebhookDelivery>> {
+    let pagination = Pagination { offset, limit };
+    let org = auth.organization(&db, &ApiTokenScope::all())?;
+    let deliveries =
+        usecases::list_webhook_deliveries(&*db.shared()?, &org, &id.into(), &pagination)?;
+    Ok(Json(deliveries.into_iter().map(Into::into).collect()))
+}
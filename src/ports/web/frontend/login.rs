@@ -2,7 +2,8 @@ use super::super::guards::*;
 use super::view;
 use crate::{
     core::{prelude::*, usecases},
-    ports::web::sqlite::Connections,
+    infrastructure::{cfg::Cfg, error::AppError, flows::prelude as flows},
+    ports::web::{notify::Notify, sqlite::Connections},
 };
 use maud::Markup;
 use rocket::{
@@ -10,12 +11,14 @@ use rocket::{
     http::{Cookie, Cookies, SameSite},
     request::{FlashMessage, Form},
     response::{Flash, Redirect},
+    State,
 };
 
 #[derive(FromForm)]
 pub struct LoginCredentials {
     pub email: String,
     password: String,
+    totp_code: Option<String>,
 }
 
 impl<'a> LoginCredentials {
@@ -23,6 +26,7 @@ impl<'a> LoginCredentials {
         let LoginCredentials {
             ref email,
             ref password,
+            ..
         } = self;
         usecases::Credentials { email, password }
     }
@@ -32,50 +36,71 @@ impl<'a> LoginCredentials {
 pub fn get_login(
     account: Option<Account>,
     flash: Option<FlashMessage>,
+    cfg: State<Cfg>,
 ) -> std::result::Result<Markup, Redirect> {
     if account.is_some() {
         Err(Redirect::to(uri!(super::get_index)))
     } else {
-        Ok(view::login(flash, "/reset-password"))
+        Ok(view::login(flash, "/reset-password", &cfg.oidc_providers))
     }
 }
 
 #[post("/login", data = "<credentials>")]
 pub fn post_login(
     db: Connections,
+    notify: Notify,
     credentials: Form<LoginCredentials>,
+    client_ip: ClientIp,
     mut cookies: Cookies,
+    cfg: State<Cfg>,
 ) -> std::result::Result<Redirect, Flash<Redirect>> {
-    match db.shared() {
-        Err(_) => Err(Flash::error(
-            Redirect::to(uri!(get_login)),
-            "We are so sorry! An internal server error has occurred. Please try again later.",
-        )),
-        Ok(db) => {
-            let credentials = credentials.into_inner();
-            match usecases::login_with_email(&*db, &credentials.as_login()) {
-                Err(err) => {
-                    let msg = match err {
-                        Error::Parameter(ParameterError::EmailNotConfirmed) => {
-                            "You have to confirm your email address first."
-                        }
-                        Error::Parameter(ParameterError::Credentials) => {
-                            "Invalid email or password."
-                        }
-                        _ => panic!(),
-                    };
-                    Err(Flash::error(Redirect::to(uri!(get_login)), msg))
+    let credentials = credentials.into_inner();
+    match flows::login_with_email(
+        &db,
+        &*notify,
+        &cfg.login_throttling,
+        &credentials.as_login(),
+        credentials.totp_code.as_deref(),
+        &cfg.totp_required_roles,
+        client_ip.0,
+    ) {
+        Err(err) => {
+            let msg = match err {
+                AppError::Business(Error::Parameter(ParameterError::EmailNotConfirmed)) => {
+                    "You have to confirm your email address first."
                 }
-                Ok(_) => {
-                    cookies.add_private(
-                        Cookie::build(COOKIE_EMAIL_KEY, credentials.email)
-                            .http_only(true)
-                            .same_site(SameSite::Lax)
-                            .finish(),
-                    );
-                    Ok(Redirect::to(uri!(super::get_index)))
+                AppError::Business(Error::Parameter(ParameterError::UserDisabled)) => {
+                    "Your account has been disabled."
                 }
-            }
+                AppError::Business(Error::Parameter(ParameterError::Credentials)) => {
+                    "Invalid email or password."
+                }
+                AppError::Business(Error::Parameter(ParameterError::SecondFactorRequired)) => {
+                    "Please enter the code of your authenticator app."
+                }
+                AppError::Business(Error::Parameter(ParameterError::SecondFactor)) => {
+                    "Invalid authentication or recovery code."
+                }
+                AppError::Business(Error::Parameter(
+                    ParameterError::SecondFactorEnrollmentRequired,
+                )) => "You have to enable two-factor authentication for your account first.",
+                AppError::Business(Error::Parameter(ParameterError::TooManyLoginAttempts(_))) => {
+                    "Too many failed login attempts. Please try again later."
+                }
+                _ => {
+                    "We are so sorry! An internal server error has occurred. Please try again later."
+                }
+            };
+            Err(Flash::error(Redirect::to(uri!(get_login)), msg))
+        }
+        Ok(_) => {
+            cookies.add_private(
+                Cookie::build(COOKIE_EMAIL_KEY, credentials.email)
+                    .http_only(true)
+                    .same_site(SameSite::Lax)
+                    .finish(),
+            );
+            Ok(Redirect::to(uri!(super::get_index)))
         }
     }
 }
@@ -5,10 +5,11 @@ use crate::{
         usecases,
     },
     infrastructure::{db::sqlite, error::*, flows::prelude::*},
-    ports::web::{guards::*, tantivy::SearchEngine},
+    ports::web::{guards::*, notify::Notify, tantivy::SearchEngine},
 };
 use maud::Markup;
 use num_traits::FromPrimitive;
+use ofdb_core::gateways::notify::NotificationGateway;
 use rocket::{
     self,
     http::{ContentType, RawStr},
@@ -25,6 +26,7 @@ mod password;
 mod register;
 #[cfg(test)]
 mod tests;
+mod unsubscribe;
 mod view;
 
 const MAP_JS: &str = include_str!("map.js");
@@ -158,20 +160,29 @@ pub struct Review {
 pub fn post_place_review(
     db: sqlite::Connections,
     search_engine: SearchEngine,
+    notify: Notify,
     id: &RawStr,
     review: Form<Review>,
     account: Account,
 ) -> std::result::Result<Redirect, Flash<Redirect>> {
     let Review { status, comment } = review.into_inner();
     let id = id.as_str();
-    review_place(&db, account.email(), status, comment, id, search_engine)
-        .map(|_| Redirect::to(uri!(get_entry: id)))
-        .map_err(|_| {
-            Flash::error(
-                Redirect::to(uri!(get_place_review: id)),
-                "Failed to archive the place.",
-            )
-        })
+    review_place(
+        &db,
+        account.email(),
+        status,
+        comment,
+        id,
+        search_engine,
+        &*notify,
+    )
+    .map(|_| Redirect::to(uri!(get_entry: id)))
+    .map_err(|_| {
+        Flash::error(
+            Redirect::to(uri!(get_place_review: id)),
+            "Failed to archive the place.",
+        )
+    })
 }
 
 fn review_place(
@@ -181,6 +192,7 @@ fn review_place(
     comment: String,
     id: &str,
     mut search_engine: SearchEngine,
+    notify: &dyn NotificationGateway,
 ) -> Result<()> {
     let reviewer_email = {
         let db = db.shared()?;
@@ -196,7 +208,7 @@ fn review_place(
         status,
         comment: Some(comment),
     };
-    let update_count = review_places(&db, &mut search_engine, &[&id], review)?;
+    let update_count = review_places(&db, &mut search_engine, notify, &[&id], review)?;
     if update_count == 0 {
         return Err(Error::Repo(RepoError::NotFound).into());
     }
@@ -407,5 +419,7 @@ pub fn routes() -> Vec<Route> {
         password::get_reset_password,
         password::post_reset_password_request,
         password::post_reset_password,
+        unsubscribe::get_unsubscribe,
+        unsubscribe::post_unsubscribe,
     ]
 }
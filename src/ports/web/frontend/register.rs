@@ -47,6 +47,8 @@ pub fn post_register(
                 Ok(()) => {
                     if let Ok(user) = db.get_user_by_email(&credentials.email) {
                         debug_assert_eq!(user.email, credentials.email);
+                        // The connection must be released before notifying
+                        drop(db);
                         notify.user_registered_ofdb(&user);
 
                         let msg = "Registered sucessfully. Please confirm your email address.";
@@ -352,7 +352,7 @@ mod entry {
             value: 1.into(),
             entry: e_id.clone().into(),
         };
-        let (r_id, c_id) = flows::prelude::create_rating(db, search, r).unwrap();
+        let (r_id, c_id) = flows::prelude::create_rating(db, search, &gw, r).unwrap();
         (e_id.into(), r_id, c_id)
     }
 
@@ -636,3 +636,67 @@ mod pw_reset {
         assert_eq!(h.value, "/");
     }
 }
+
+mod unsubscribe {
+    use super::*;
+    use crate::ports::web::tests::setup_with_cfg;
+    use ofdb_core::subscription::unsubscribe_token;
+
+    #[test]
+    fn unsubscribe_after_confirmation() {
+        let mut cfg = Cfg::default();
+        cfg.unsubscribe_secret = Some("secret".into());
+        let (client, db, _) = setup_with_cfg(vec![("/", super::super::routes())], cfg);
+        let subscription = usecases::create_bbox_subscription(
+            &*db.exclusive().unwrap(),
+            "user@example.com",
+            usecases::NewBboxSubscription {
+                name: "Home".into(),
+                bbox: MapBbox::new(
+                    MapPoint::from_lat_lng_deg(-10.0, -10.0),
+                    MapPoint::from_lat_lng_deg(10.0, 10.0),
+                ),
+                tags: vec![],
+                categories: vec![],
+                entity_kind: None,
+                change_types: vec![],
+                delivery_mode: None,
+            },
+        )
+        .unwrap();
+        let token = unsubscribe_token("secret", &subscription.id);
+
+        let res = client
+            .get(format!("/unsubscribe?token={}x", token))
+            .dispatch();
+        assert_eq!(res.status(), Status::BadRequest);
+
+        // Visiting the link only shows a confirmation
+        let mut res = client
+            .get(format!("/unsubscribe?token={}", token))
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        let body_str = res.body().and_then(|b| b.into_string()).unwrap();
+        assert!(body_str.contains("<em>Home</em>"));
+        assert!(body_str.contains(&format!("action=\"/unsubscribe?token={}\"", token)));
+        assert_eq!(
+            1,
+            db.shared()
+                .unwrap()
+                .all_bbox_subscriptions_by_email("user@example.com")
+                .unwrap()
+                .len()
+        );
+
+        let res = client
+            .post(format!("/unsubscribe?token={}", token))
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        assert!(db
+            .shared()
+            .unwrap()
+            .all_bbox_subscriptions_by_email("user@example.com")
+            .unwrap()
+            .is_empty());
+    }
+}
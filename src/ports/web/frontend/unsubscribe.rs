@@ -0,0 +1,28 @@
+use super::{view, Result};
+use crate::{
+    core::usecases,
+    infrastructure::cfg::Cfg,
+    ports::web::{sqlite::Connections, unsubscribe_secret},
+};
+use maud::Markup;
+use rocket::{self, http::RawStr, State};
+
+/// Confirmation page for the unsubscribe links in emails.
+///
+/// Visiting the link must not delete the subscription,
+/// because some mail clients and scanners prefetch links.
+#[get("/unsubscribe?<token>")]
+pub fn get_unsubscribe(db: Connections, cfg: State<Cfg>, token: &RawStr) -> Result<Markup> {
+    let subscription = usecases::get_bbox_subscription_by_unsubscribe_token(
+        &*db.shared()?,
+        unsubscribe_secret(&cfg)?,
+        token.as_str(),
+    )?;
+    Ok(view::unsubscribe(&subscription.name, token.as_str()))
+}
+
+#[post("/unsubscribe?<token>")]
+pub fn post_unsubscribe(db: Connections, cfg: State<Cfg>, token: &RawStr) -> Result<Markup> {
+    usecases::unsubscribe_with_token(&*db.exclusive()?, unsubscribe_secret(&cfg)?, token.as_str())?;
+    Ok(view::unsubscribe_ack())
+}
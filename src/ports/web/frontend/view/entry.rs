@@ -1,5 +1,5 @@
-use super::{address_to_html, leaflet_css_link, map_scripts, page};
-use crate::core::prelude::*;
+use super::{address_to_html, json_ld_script, leaflet_css_link, map_scripts, page};
+use crate::{adapters::json_ld, core::prelude::*};
 use maud::{html, Markup};
 use std::collections::HashMap;
 
@@ -44,7 +44,10 @@ pub fn entry(email: Option<&str>, e: EntryPresenter) -> Markup {
         &format!("{} | OpenFairDB", e.place.title),
         email,
         None,
-        Some(leaflet_css_link()),
+        Some(html! {
+            (leaflet_css_link())
+            (json_ld_script(&json_ld::Organization::from(&e.place)))
+        }),
         entry_detail(e),
     )
 }
@@ -12,6 +12,7 @@ pub fn event(user: Option<User>, ev: Event) -> Markup {
                 href=(LEAFLET_CSS_URL)
                 integrity=(LEAFLET_CSS_SHA512)
                 crossorigin="anonymous";
+            (json_ld_script(&json_ld::Event::from(&ev)))
         }),
         html! {
             div class="details event" {
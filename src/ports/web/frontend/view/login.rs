@@ -1,8 +1,13 @@
 use super::page::*;
+use crate::core::usecases::OidcProvider;
 use maud::{html, Markup};
 use rocket::request::FlashMessage;
 
-pub fn login(flash: Option<FlashMessage>, reset_pw_link: &str) -> Markup {
+pub fn login(
+    flash: Option<FlashMessage>,
+    reset_pw_link: &str,
+    oidc_providers: &[OidcProvider],
+) -> Markup {
     page(
         "Login",
         None,
@@ -23,6 +28,12 @@ pub fn login(flash: Option<FlashMessage>, reset_pw_link: &str) -> Markup {
                     input type="password" name="password" placeholder="Password";
                 }
                 br;
+                label{
+                    "Authentication code (if two-factor authentication is enabled):"
+                    br;
+                    input type="text" name="totp_code" autocomplete="one-time-code" placeholder="123456";
+                }
+                br;
                 input type="submit" value="login";
                 p {
                     "Did you forget your password? Don't worry you can "
@@ -31,6 +42,16 @@ pub fn login(flash: Option<FlashMessage>, reset_pw_link: &str) -> Markup {
                 }
               }
           }
+          @if !oidc_providers.is_empty() {
+              p { "Or log in with the account of your organization:" }
+              ul class="oidc-providers" {
+                  @for provider in oidc_providers {
+                      li {
+                          a href=(format!("/api/login/oidc/{}", provider.id)) { (provider.name) }
+                      }
+                  }
+              }
+          }
         },
     )
 }
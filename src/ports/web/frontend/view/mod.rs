@@ -1,5 +1,5 @@
-use crate::core::prelude::*;
-use maud::{html, Markup};
+use crate::{adapters::json_ld, core::prelude::*};
+use maud::{html, Markup, PreEscaped};
 use num_traits::ToPrimitive;
 
 const LEAFLET_CSS_URL: &str = "https://cdnjs.cloudflare.com/ajax/libs/leaflet/1.4.0/leaflet.css";
@@ -16,6 +16,7 @@ mod page;
 mod password;
 mod place;
 mod register;
+mod unsubscribe;
 
 pub use dashboard::*;
 pub use entry::*;
@@ -25,6 +26,7 @@ use page::*;
 pub use password::*;
 pub use place::*;
 pub use register::*;
+pub use unsubscribe::*;
 
 pub fn index(email: Option<&str>) -> Markup {
     page(
@@ -69,6 +71,14 @@ fn leaflet_css_link() -> Markup {
     }
 }
 
+fn json_ld_script<T: serde::Serialize>(obj: &T) -> Markup {
+    html! {
+        script type="application/ld+json" {
+            (PreEscaped(json_ld::to_script_content(obj)))
+        }
+    }
+}
+
 pub fn search_results(email: Option<&str>, search_term: &str, entries: &[IndexedPlace]) -> Markup {
     page(
         "OpenFairDB Search Results",
@@ -0,0 +1,40 @@
+use super::page::*;
+use maud::{html, Markup};
+
+pub fn unsubscribe(subscription_name: &str, token: &str) -> Markup {
+    page(
+        "Unsubscribe",
+        None,
+        None,
+        None,
+        html! {
+          h2 { "Unsubscribe" }
+          p {
+            "Do you no longer want to receive emails about changes in "
+            @if subscription_name.is_empty() {
+              "your subscribed map area"
+            } @else {
+              "the subscribed map area "
+              em { (subscription_name) }
+            }
+            "?"
+          }
+          form class="unsubscribe" action=(format!("/unsubscribe?token={}", token)) method="POST" {
+              input type="submit" value="unsubscribe";
+          }
+        },
+    )
+}
+
+pub fn unsubscribe_ack() -> Markup {
+    page(
+        "Unsubscribe",
+        None,
+        None,
+        None,
+        html! {
+          h2 { "Unsubscribe" }
+          p { "You will no longer receive emails about changes in this map area." }
+        },
+    )
+}
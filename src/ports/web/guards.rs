@@ -1,5 +1,8 @@
 use crate::{
-    core::db::OrganizationRepo, core::prelude::*, core::usecases, infrastructure::error::AppError,
+    adapters::json_ld,
+    core::prelude::*,
+    core::usecases,
+    infrastructure::{cfg::Cfg, db::sqlite, error::AppError},
     ports::web::jwt,
 };
 use chrono::prelude::*;
@@ -9,10 +12,11 @@ use rocket::{
     request::{self, FromRequest, Request},
     Outcome, State,
 };
-use std::time::Duration;
+use std::{net::IpAddr, time::Duration};
 
 pub const COOKIE_EMAIL_KEY: &str = "ofdb-user-email";
 pub const COOKIE_CAPTCHA_KEY: &str = "ofdb-captcha";
+pub const HEADER_ORGANIZATION: &str = "X-Organization";
 pub const MAX_CAPTCHA_TTL: Duration = Duration::from_secs(120);
 
 type Result<T> = std::result::Result<T, AppError>;
@@ -26,10 +30,40 @@ fn get_bearer_token(auth_header_val: &str) -> Option<&str> {
     }
 }
 
+/// The last usage of an API token is only updated after this
+/// period to avoid writing into the database on each request.
+const API_TOKEN_LAST_USED_AT_RESOLUTION: Duration = Duration::from_secs(60);
+
+// Only informational, i.e. failures are not fatal
+fn record_api_token_usage(connections: &sqlite::Connections, api_token: &ApiToken) {
+    let now = TimestampMs::now();
+    let resolution = API_TOKEN_LAST_USED_AT_RESOLUTION.as_millis() as i64;
+    if api_token
+        .last_used_at
+        .map_or(false, |t| now.into_inner() - t.into_inner() < resolution)
+    {
+        return;
+    }
+    let updated = connections
+        .exclusive()
+        .map_err(|err| err.to_string())
+        .and_then(|db| {
+            db.update_api_token_last_used_at(&api_token.id, now)
+                .map_err(|err| err.to_string())
+        });
+    if let Err(err) = updated {
+        warn!(
+            "Failed to update last usage of API token {}: {}",
+            api_token.id, err
+        );
+    }
+}
+
 #[derive(Debug)]
 pub struct Auth {
     bearer_tokens: Vec<String>,
     account_email: Option<String>,
+    org_id: Option<String>,
     has_captcha: bool,
 }
 
@@ -60,11 +94,37 @@ impl Auth {
         }
     }
 
-    pub fn organization<R: OrganizationRepo>(&self, db: &R) -> Result<Organization> {
-        Ok(usecases::authorize_organization_by_possible_api_tokens(
-            db,
+    /// Authorizes an organization either by an API token or
+    /// by the session of a member who selected the organization.
+    ///
+    /// Must not be invoked while holding a database connection,
+    /// because the usage of API tokens is recorded afterwards.
+    pub fn organization(
+        &self,
+        connections: &sqlite::Connections,
+        required_scopes: &[ApiTokenScope],
+    ) -> Result<Organization> {
+        let authorized = usecases::authorize_organization_by_possible_api_tokens(
+            &*connections.shared()?,
             &self.bearer_tokens,
-        )?)
+            required_scopes,
+        );
+        let err = match authorized {
+            Ok((org, api_token)) => {
+                record_api_token_usage(connections, &api_token);
+                return Ok(org);
+            }
+            Err(err) => err,
+        };
+        if let (Some(email), Some(org_id)) = (&self.account_email, &self.org_id) {
+            return Ok(usecases::authorize_organization_member(
+                &*connections.shared()?,
+                email,
+                &org_id.as_str().into(),
+                required_scopes,
+            )?);
+        }
+        Err(err.into())
     }
 
     pub fn user_with_min_role<D: Db>(&self, db: &D, min_required_role: Role) -> Result<User> {
@@ -96,10 +156,36 @@ impl Auth {
         bearer_tokens: &[String],
     ) -> Option<String> {
         let jwt_state = request.guard::<State<jwt::JwtState>>().succeeded()?;
+        let connections = request.guard::<sqlite::Connections>().succeeded()?;
+        let db = connections.shared().ok()?;
         bearer_tokens
             .iter()
-            .filter_map(|token| jwt_state.validate_token_and_get_email(token).ok())
-            .next()
+            .filter_map(|token| jwt_state.decode_token(token).ok())
+            .find(|claims| {
+                // Fail closed if the revocation status is unknown
+                !usecases::is_access_token_revoked(
+                    &*db,
+                    &claims.jti,
+                    &claims.session_id(),
+                    &claims.sub,
+                )
+                .unwrap_or(true)
+            })
+            .map(|claims| claims.sub)
+    }
+
+    fn is_account_disabled(request: &Request, email: &str) -> bool {
+        let connections = match request.guard::<sqlite::Connections>().succeeded() {
+            Some(connections) => connections,
+            None => return true,
+        };
+        // Fail closed if the status of the account is unknown
+        connections
+            .shared()
+            .ok()
+            .and_then(|db| db.try_get_user_by_email(email).ok())
+            .map(|user| user.map(|u| u.disabled).unwrap_or(false))
+            .unwrap_or(true)
     }
 
     fn captcha_from_cookie(request: &Request) -> bool {
@@ -125,12 +211,21 @@ impl<'a, 'r> FromRequest<'a, 'r> for Auth {
         if cfg!(feature = "jwt") && account_email.is_none() {
             account_email = Self::account_email_from_jwt_in_header(request, &bearer_tokens);
         }
+        // Sessions of disabled users are ignored
+        let account_email =
+            account_email.filter(|email| !Self::is_account_disabled(request, email));
+
+        let org_id = request
+            .headers()
+            .get_one(HEADER_ORGANIZATION)
+            .map(ToOwned::to_owned);
 
         let has_captcha = Self::captcha_from_cookie(request);
 
         let auth = Self {
             bearer_tokens,
             account_email,
+            org_id,
             has_captcha,
         };
 
@@ -157,3 +252,86 @@ impl<'a, 'r> FromRequest<'a, 'r> for Account {
         }
     }
 }
+
+/// Matches only requests that explicitly prefer a
+/// linked data representation (JSON-LD) of the
+/// requested resource.
+#[derive(Debug)]
+pub struct AcceptJsonLd;
+
+impl<'a, 'r> FromRequest<'a, 'r> for AcceptJsonLd {
+    type Error = ();
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<AcceptJsonLd, Self::Error> {
+        match request
+            .accept()
+            .map(|accept| accept.preferred().media_type())
+        {
+            Some(media_type)
+                if media_type.top() == json_ld::MEDIA_TYPE_TOP
+                    && media_type.sub() == json_ld::MEDIA_TYPE_SUB =>
+            {
+                Outcome::Success(AcceptJsonLd)
+            }
+            _ => Outcome::Forward(()),
+        }
+    }
+}
+
+/// The `Last-Event-ID` header of a reconnecting
+/// Server-Sent Events client.
+#[derive(Debug)]
+pub struct LastEventId(pub Option<u64>);
+
+impl<'a, 'r> FromRequest<'a, 'r> for LastEventId {
+    type Error = ();
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<LastEventId, Self::Error> {
+        match request.headers().get_one("Last-Event-ID") {
+            None => Outcome::Success(LastEventId(None)),
+            Some(id) => match id.trim().parse() {
+                Ok(seq) => Outcome::Success(LastEventId(Some(seq))),
+                Err(_) => Outcome::Failure((Status::BadRequest, ())),
+            },
+        }
+    }
+}
+
+/// The IP address of the client, if known.
+///
+/// Only requests from trusted reverse proxies may forward
+/// the address in the `X-Real-IP` or `X-Forwarded-For`
+/// header. Otherwise these headers could be forged by
+/// clients.
+#[derive(Debug)]
+pub struct ClientIp(pub Option<IpAddr>);
+
+fn forwarded_client_ip(request: &Request) -> Option<IpAddr> {
+    let headers = request.headers();
+    headers
+        .get_one("X-Real-IP")
+        .and_then(|ip| ip.trim().parse().ok())
+        .or_else(|| {
+            // The last entry has been appended by the proxy itself
+            headers
+                .get("X-Forwarded-For")
+                .last()
+                .and_then(|ips| ips.rsplit(',').next())
+                .and_then(|ip| ip.trim().parse().ok())
+        })
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for ClientIp {
+    type Error = ();
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<ClientIp, Self::Error> {
+        let peer = request.remote().map(|addr| addr.ip());
+        let is_trusted_proxy = match (peer, request.guard::<State<Cfg>>().succeeded()) {
+            (Some(peer), Some(cfg)) => cfg.trusted_proxies.contains(&peer),
+            _ => false,
+        };
+        if is_trusted_proxy {
+            if let Some(ip) = forwarded_client_ip(request) {
+                return Outcome::Success(ClientIp(Some(ip)));
+            }
+        }
+        Outcome::Success(ClientIp(peer))
+    }
+}
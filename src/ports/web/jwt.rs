@@ -1,85 +1,63 @@
-use anyhow::{anyhow, Result};
+use crate::core::entities::{Id, TimestampMs};
+use anyhow::Result;
 use chrono::{Duration, Utc};
 use jwt_service::JwtService;
 use serde::{Deserialize, Serialize};
-use std::{
-    collections::HashSet,
-    convert::TryFrom,
-    sync::{Mutex, MutexGuard},
-};
+use std::{convert::TryFrom, time::Duration as StdDuration};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     /// The email in our case
-    sub: String,
+    pub sub: String,
     /// Expiry time as Unix timestamp
-    exp: usize,
+    pub exp: usize,
+    /// The unique id of the token for revocation
+    pub jti: String,
+    /// The login session that the token belongs to
+    pub sid: String,
+}
+
+impl Claims {
+    pub fn expires_at(&self) -> TimestampMs {
+        TimestampMs::from_inner(self.exp as i64 * 1000)
+    }
+
+    pub fn session_id(&self) -> Id {
+        self.sid.as_str().into()
+    }
 }
 
 pub struct JwtState {
     jwt_service: JwtService,
     time_valid: Duration,
-    blacklist: Mutex<HashSet<String>>,
 }
 
 impl JwtState {
-    pub fn new() -> Self {
+    pub fn new(secret: Option<&str>, access_token_lifetime: StdDuration) -> Self {
         Self {
-            jwt_service: JwtService::new(),
-            time_valid: Duration::days(1),
-            blacklist: Mutex::new(HashSet::new()),
+            jwt_service: JwtService::new(secret),
+            time_valid: Duration::from_std(access_token_lifetime)
+                .unwrap_or_else(|_| Duration::days(1)),
         }
     }
 
-    pub fn generate_token(&self, email: &str) -> Result<String> {
+    pub fn generate_token(&self, email: &str, session_id: &Id) -> Result<String> {
         let exp = usize::try_from((Utc::now() + self.time_valid).timestamp())?;
         let claims = Claims {
             sub: email.to_string(),
             exp,
+            jti: uuid::Uuid::new_v4().to_string(),
+            sid: session_id.to_string(),
         };
         let token = self.jwt_service.encode(&claims)?;
         Ok(token)
     }
 
-    pub fn validate_token_and_get_email(&self, token: &str) -> Result<String> {
-        if self.is_on_blacklist(token) {
-            return Err(anyhow!("Token is no longer valid"));
-        }
-        let claims = self.jwt_service.decode(token)?;
-        let sub = claims.sub;
-        Ok(sub)
-    }
-
-    pub fn blacklist_token(&self, token: String) {
-        self.remove_invalid_tokens(); // do housekeeping
-        self.lock().insert(token);
-    }
-
-    fn is_on_blacklist(&self, token: &str) -> bool {
-        self.lock().get(token).is_some()
-    }
-
-    // TODO: maybe this can be done more efficiently
-    fn remove_invalid_tokens(&self) {
-        let invalid_tokens = self
-            .lock()
-            .iter()
-            .filter(|token| self.jwt_service.decode(token).is_err())
-            .cloned()
-            .collect::<Vec<_>>();
-        for token in invalid_tokens {
-            self.lock().remove(&token);
-        }
-    }
-
-    fn lock(&self) -> MutexGuard<HashSet<String>> {
-        match self.blacklist.lock() {
-            Ok(guard) => guard,
-            Err(poison_err) => {
-                log::error!("A poisoned mutex guard for the JwtState was found.");
-                poison_err.into_inner()
-            }
-        }
+    /// Decodes a valid token that has not expired yet.
+    ///
+    /// Revoked tokens are not detected here.
+    pub fn decode_token(&self, token: &str) -> Result<Claims> {
+        self.jwt_service.decode(token)
     }
 }
 
@@ -120,8 +98,17 @@ mod jwt_service {
     }
 
     impl JwtService {
-        pub fn new() -> Self {
-            Self { key: Key::random() }
+        pub fn new(secret: Option<&str>) -> Self {
+            let key = match secret {
+                Some(secret) => Key::new(secret.to_owned()),
+                None => {
+                    log::warn!(
+                        "No JWT secret configured: Issued tokens will become invalid after a restart"
+                    );
+                    Key::random()
+                }
+            };
+            Self { key }
         }
         pub fn encode(&self, claims: &Claims) -> Result<String> {
             let token = encode(&Header::default(), claims, &self.key.encoding_key)?;
@@ -140,7 +127,7 @@ mod jwt_service {
     use super::{Claims, Result};
     pub struct JwtService;
     impl JwtService {
-        pub fn new() -> Self {
+        pub fn new(_secret: Option<&str>) -> Self {
             Self {}
         }
         pub fn encode(&self, _claims: &Claims) -> Result<String> {
@@ -157,35 +144,49 @@ mod jwt_service {
 mod tests {
     use super::*;
 
+    const LIFETIME: StdDuration = StdDuration::from_secs(60);
+
     #[test]
-    fn blacklisting_works() {
-        let jwt_state = JwtState::new();
-        let token = jwt_state.generate_token("foo@bar.org").unwrap();
-        jwt_state.blacklist_token(token.clone());
-        assert!(jwt_state.is_on_blacklist(&token));
+    fn validation_works() {
+        let jwt_state = JwtState::new(None, LIFETIME);
+        let session_id = Id::new();
+        let token = jwt_state
+            .generate_token("foo@bar.org", &session_id)
+            .unwrap();
+        let claims = jwt_state.decode_token(&token).unwrap();
+        assert_eq!(claims.sub, "foo@bar.org");
+        assert_eq!(claims.session_id(), session_id);
+        assert!(claims.expires_at() > TimestampMs::now());
+        assert!(jwt_state.decode_token("dubidubidu").is_err());
     }
 
     #[test]
-    fn validation_works() {
-        let jwt_state = JwtState::new();
-        let token = jwt_state.generate_token("foo@bar.org").unwrap();
-        let email = jwt_state.validate_token_and_get_email(&token).unwrap();
-        assert_eq!(email, "foo@bar.org");
-        jwt_state.blacklist_token(token.clone());
-        assert!(jwt_state.validate_token_and_get_email(&token).is_err())
+    fn token_ids_are_unique() {
+        let jwt_state = JwtState::new(None, LIFETIME);
+        let session_id = Id::new();
+        let t1 = jwt_state
+            .generate_token("foo@bar.org", &session_id)
+            .unwrap();
+        let t2 = jwt_state
+            .generate_token("foo@bar.org", &session_id)
+            .unwrap();
+        assert_ne!(
+            jwt_state.decode_token(&t1).unwrap().jti,
+            jwt_state.decode_token(&t2).unwrap().jti
+        );
     }
 
     #[test]
-    fn invalid_tokens_are_removed() {
-        let jwt_state = JwtState::new();
-        let token = jwt_state.generate_token("foo@bar.org").unwrap();
-        let invalid_token = "dubidubidu".to_string();
-        jwt_state.blacklist_token(token.clone());
-        jwt_state.blacklist_token(invalid_token.clone());
-        assert!(jwt_state.is_on_blacklist(&token));
-        assert!(jwt_state.is_on_blacklist(&invalid_token));
-        jwt_state.remove_invalid_tokens();
-        assert!(jwt_state.is_on_blacklist(&token));
-        assert!(!jwt_state.is_on_blacklist(&invalid_token));
+    fn configured_secret_survives_restarts() {
+        let token = JwtState::new(Some("secret"), LIFETIME)
+            .generate_token("foo@bar.org", &Id::new())
+            .unwrap();
+        assert!(JwtState::new(Some("secret"), LIFETIME)
+            .decode_token(&token)
+            .is_ok());
+        assert!(JwtState::new(Some("other"), LIFETIME)
+            .decode_token(&token)
+            .is_err());
+        assert!(JwtState::new(None, LIFETIME).decode_token(&token).is_err());
     }
 }
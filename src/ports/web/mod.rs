@@ -1,16 +1,23 @@
 use crate::{
     core::{
         db::{EventIndexer, PlaceIndexer},
+        error::{Error, ParameterError},
         prelude::*,
         usecases,
+        util::rate_limit::RateLimiter,
+    },
+    infrastructure::{
+        cfg::Cfg, email_sender, error::AppError, flows::prelude as flows, GEO_CODING_GW,
     },
-    infrastructure::{cfg::Cfg, error::AppError},
 };
 use ofdb_core::rating::Rated;
+use ofdb_gateways::{
+    geocoder::ThrottledGeoCoder, oidc::HttpOidcGateway, webhook::HttpWebhookGateway,
+};
 use popular_tags_cache::PopularTagsCache;
 use rocket::{config::Config as RocketCfg, Rocket, Route};
 use rocket_contrib::json::Json;
-use std::result;
+use std::{result, thread, time::Duration};
 
 pub mod api;
 #[cfg(feature = "frontend")]
@@ -21,6 +28,7 @@ pub mod jwt;
 mod mockdb;
 pub mod notify;
 mod popular_tags_cache;
+mod rate_limit;
 mod sqlite;
 mod tantivy;
 #[cfg(test)]
@@ -28,6 +36,17 @@ pub mod tests;
 
 type Result<T> = result::Result<Json<T>, AppError>;
 
+/// Requests are answered without geocoding instead of waiting
+/// any longer for the geocoder, that is shared by all requests.
+const MAX_GEOCODING_WAIT: Duration = Duration::from_secs(5);
+
+fn unsubscribe_secret(cfg: &Cfg) -> result::Result<&str, AppError> {
+    // No unsubscribe links are sent without a secret
+    cfg.unsubscribe_secret
+        .as_deref()
+        .ok_or_else(|| Error::Parameter(ParameterError::TokenInvalid).into())
+}
+
 fn index_all_places<D: PlaceRepo + RatingRepository>(
     db: &D,
     indexer: &mut dyn PlaceIndexer,
@@ -83,12 +102,19 @@ pub(crate) fn rocket_instance(
 
     info!("Deleting expired user e-mail tokens...");
     usecases::delete_expired_user_tokens(&*connections.exclusive().unwrap()).unwrap();
+    usecases::delete_expired_email_change_tokens(&*connections.exclusive().unwrap()).unwrap();
+
+    info!("Deleting expired session tokens...");
+    usecases::delete_expired_session_tokens(&*connections.exclusive().unwrap()).unwrap();
+
+    info!("Deleting outdated change log entries...");
+    usecases::delete_outdated_change_log_entries(&*connections.exclusive().unwrap()).unwrap();
 
     info!("Caching most popular tags...");
     let tags_cache = PopularTagsCache::new_from_db(&*connections.shared().unwrap()).unwrap();
 
     let captcha_cache = api::captcha::CaptchaCache::new();
-    let jwt_state = jwt::JwtState::new();
+    let jwt_state = jwt::JwtState::new(cfg.jwt_secret.as_deref(), cfg.jwt_access_token_lifetime);
 
     info!("Initialization finished");
 
@@ -103,7 +129,15 @@ pub(crate) fn rocket_instance(
         .manage(captcha_cache)
         .manage(tags_cache)
         .manage(jwt_state)
-        .manage(cfg);
+        .manage(HttpOidcGateway::new())
+        .manage(HttpWebhookGateway::new())
+        .manage(RateLimiter::new())
+        .manage(
+            ThrottledGeoCoder::new(&*GEO_CODING_GW, cfg.geocoding_delay)
+                .with_max_wait(MAX_GEOCODING_WAIT),
+        )
+        .manage(cfg)
+        .register(catchers![rate_limit::too_many_requests]);
 
     for (m, r) in mounts {
         instance = instance.mount(m, r);
@@ -121,12 +155,90 @@ fn mounts() -> Vec<(&'static str, Vec<Route>)> {
     vec![("/api", api::routes()), ("/", frontend::routes())]
 }
 
+/// The interval between two runs of the webhook delivery.
+const WEBHOOK_DELIVERY_INTERVAL: Duration = Duration::from_secs(30);
+
+fn spawn_webhook_delivery(connections: sqlite::Connections) {
+    thread::spawn(move || {
+        let gateway = HttpWebhookGateway::new();
+        loop {
+            match flows::deliver_due_webhooks(&connections, &gateway) {
+                Ok(0) => {}
+                Ok(count) => debug!("Attempted {} webhook deliveries", count),
+                Err(err) => error!("Failed to deliver webhooks: {}", err),
+            }
+            thread::sleep(WEBHOOK_DELIVERY_INTERVAL);
+        }
+    });
+}
+
+/// The interval between two runs of the email delivery.
+const EMAIL_DELIVERY_INTERVAL: Duration = Duration::from_secs(10);
+
+fn spawn_email_delivery(connections: sqlite::Connections) {
+    let sender = match email_sender() {
+        Some(sender) => sender,
+        None => {
+            warn!("No eMail gateway was configured: emails are not sent");
+            return;
+        }
+    };
+    thread::spawn(move || loop {
+        match flows::send_due_emails(&connections, sender) {
+            Ok(0) => {}
+            Ok(count) => debug!("Attempted to send {} e-mails", count),
+            Err(err) => error!("Failed to send e-mails: {}", err),
+        }
+        thread::sleep(EMAIL_DELIVERY_INTERVAL);
+    });
+}
+
+/// The interval between two checks for due subscription digests.
+const SUBSCRIPTION_DIGEST_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+fn spawn_subscription_digests(connections: sqlite::Connections, cfg: &Cfg) {
+    let notify = notify::Notify::new(connections.clone(), cfg);
+    thread::spawn(move || loop {
+        match flows::send_due_subscription_digests(&connections, &*notify) {
+            Ok(0) => {}
+            Ok(count) => debug!("Sent {} subscription digests", count),
+            Err(err) => error!("Failed to send subscription digests: {}", err),
+        }
+        thread::sleep(SUBSCRIPTION_DIGEST_INTERVAL);
+    });
+}
+
+/// The interval between two checks for due pending clearance digests.
+const PENDING_CLEARANCE_DIGEST_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+fn spawn_pending_clearance_digests(
+    connections: sqlite::Connections,
+    cfg: &Cfg,
+    batch_period: Duration,
+) {
+    let notify = notify::Notify::new(connections.clone(), cfg);
+    thread::spawn(move || loop {
+        match flows::send_due_pending_clearance_digests(&connections, &*notify, batch_period) {
+            Ok(0) => {}
+            Ok(count) => debug!("Sent {} pending clearance digests", count),
+            Err(err) => error!("Failed to send pending clearance digests: {}", err),
+        }
+        thread::sleep(PENDING_CLEARANCE_DIGEST_INTERVAL);
+    });
+}
+
 pub fn run(
     connections: sqlite::Connections,
     search_engine: tantivy::SearchEngine,
     enable_cors: bool,
     cfg: Cfg,
 ) {
+    spawn_webhook_delivery(connections.clone());
+    spawn_email_delivery(connections.clone());
+    spawn_subscription_digests(connections.clone(), &cfg);
+    if let Some(batch_period) = cfg.pending_clearance_batch_period {
+        spawn_pending_clearance_digests(connections.clone(), &cfg, batch_period);
+    }
     if enable_cors {
         let cors = rocket_cors::CorsOptions {
             ..Default::default()
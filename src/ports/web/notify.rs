@@ -1,16 +1,22 @@
 #[cfg(not(test))]
-use crate::infrastructure::{MAILGUN_GW, SENDMAIL_GW};
+use crate::infrastructure::{email_sender, EMAIL_TEMPLATES};
 #[cfg(test)]
 use crate::ports::web::tests::DummyNotifyGW;
+use crate::{
+    core::db::UserGateway,
+    infrastructure::{cfg::Cfg, flows::prelude as flows},
+    ports::web::sqlite::Connections,
+};
 use core::ops::Deref;
 use ofdb_core::gateways::email::EmailGateway;
-use ofdb_entities::email::*;
-#[cfg(not(test))]
-use ofdb_gateways::notify;
+use ofdb_entities::{email::*, user::Language};
+use ofdb_gateways::notify::{self, RecipientLanguages};
 use rocket::{
     request::{self, FromRequest},
-    Outcome, Request,
+    Outcome, Request, State,
 };
+#[cfg(not(test))]
+use std::sync::Arc;
 
 #[cfg(not(test))]
 pub struct Notify(notify::Notify);
@@ -21,11 +27,69 @@ pub struct Notify(DummyNotifyGW);
 struct DummyMailGw;
 
 impl EmailGateway for DummyMailGw {
-    fn compose_and_send(&self, _recipients: &[Email], _subject: &str, _body: &str) {
+    fn compose_and_send(&self, _recipients: &[Email], _content: &EmailContent) {
         debug!("Cannot send emails because no e-mail gateway was configured");
     }
 }
 
+/// Puts all emails into the outbox that is processed
+/// by a background worker.
+///
+/// Takes an exclusive database connection, i.e. must not be
+/// invoked while holding a database connection.
+struct EmailOutbox(Connections);
+
+impl EmailGateway for EmailOutbox {
+    fn compose_and_send(&self, recipients: &[Email], content: &EmailContent) {
+        if let Err(err) = flows::enqueue_emails(&self.0, recipients, content) {
+            error!("Failed to put e-mails into the outbox: {}", err);
+        }
+    }
+}
+
+/// Looks up the preferred language of registered users.
+struct UserLanguages(Connections);
+
+impl RecipientLanguages for UserLanguages {
+    fn preferred_language(&self, email: &str) -> Option<Language> {
+        let db = self.0.shared().ok()?;
+        db.try_get_user_by_email(email).ok().flatten()?.language
+    }
+}
+
+impl Notify {
+    #[cfg(not(test))]
+    pub fn new(connections: Connections, cfg: &Cfg) -> Self {
+        let templates = Arc::clone(&*EMAIL_TEMPLATES);
+        let languages = UserLanguages(connections.clone());
+        // Required for sending emails
+        let unsubscribe_secret = cfg.unsubscribe_secret.clone().unwrap_or_default();
+        let clearance_url = cfg.clearance_app_url.to_string();
+        if email_sender().is_some() {
+            Notify(notify::Notify::new(
+                EmailOutbox(connections),
+                templates,
+                languages,
+                unsubscribe_secret,
+                clearance_url,
+            ))
+        } else {
+            warn!("No eMail gateway was not configured");
+            Notify(notify::Notify::new(
+                DummyMailGw,
+                templates,
+                languages,
+                unsubscribe_secret,
+                clearance_url,
+            ))
+        }
+    }
+    #[cfg(test)]
+    pub fn new(_: Connections, _: &Cfg) -> Self {
+        Notify(DummyNotifyGW)
+    }
+}
+
 impl Deref for Notify {
     type Target = dyn ofdb_core::gateways::notify::NotificationGateway;
     fn deref(&self) -> &Self::Target {
@@ -36,21 +100,88 @@ impl Deref for Notify {
 impl<'a, 'r> FromRequest<'a, 'r> for Notify {
     type Error = ();
 
-    #[cfg(not(test))]
-    fn from_request(_: &'a Request<'r>) -> request::Outcome<Self, ()> {
-        if let Some(gw) = &*MAILGUN_GW {
-            info!("Use Mailgun gateway");
-            Outcome::Success(Notify(notify::Notify::new(gw.clone())))
-        } else if let Some(gw) = &*SENDMAIL_GW {
-            warn!("Mailgun gateway was not configured: use sendmail as fallback");
-            Outcome::Success(Notify(notify::Notify::new(gw.clone())))
-        } else {
-            warn!("No eMail gateway was not configured");
-            Outcome::Success(Notify(notify::Notify::new(DummyMailGw)))
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        let connections = request.guard::<Connections>()?;
+        let cfg = request.guard::<State<Cfg>>()?;
+        Outcome::Success(Notify::new(connections, &cfg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{prelude::*, usecases, util::geo::MapBbox},
+        infrastructure::flows::tests::prelude::{BackendFixture, NewPlace},
+    };
+    use ofdb_core::gateways::email::EmailSender;
+    use ofdb_gateways::email_templates::EmailTemplates;
+    use std::{cell::RefCell, sync::Arc};
+
+    #[derive(Default)]
+    struct RecordingSender(RefCell<Vec<Email>>);
+
+    impl EmailSender for RecordingSender {
+        fn send(&self, recipient: &Email, _: &EmailContent) -> std::result::Result<(), String> {
+            self.0.borrow_mut().push(recipient.clone());
+            Ok(())
         }
     }
-    #[cfg(test)]
-    fn from_request(_: &'a Request<'r>) -> request::Outcome<Self, ()> {
-        Outcome::Success(Notify(DummyNotifyGW))
+
+    // Flows that still hold a database connection while
+    // sending notifications would deadlock here.
+    #[test]
+    fn send_notifications_of_flows_through_the_outbox() {
+        let fixture = BackendFixture::new();
+        let connections = &fixture.db_connections;
+        let notify = notify::Notify::new(
+            EmailOutbox(connections.clone()),
+            Arc::new(EmailTemplates::builtin()),
+            UserLanguages(connections.clone()),
+            "secret".into(),
+            "https://clearance.example.com".into(),
+        );
+        fixture.create_user(
+            usecases::NewUser {
+                email: "a@example.com".into(),
+                password: "secret1".into(),
+                language: None,
+            },
+            None,
+        );
+        usecases::subscribe_to_bbox(
+            &*connections.exclusive().unwrap(),
+            "a@example.com".into(),
+            MapBbox::new(
+                MapPoint::from_lat_lng_deg(-1.0, -1.0),
+                MapPoint::from_lat_lng_deg(1.0, 1.0),
+            ),
+        )
+        .unwrap();
+
+        flows::create_place(
+            connections,
+            &mut *fixture.search_engine.borrow_mut(),
+            &notify,
+            NewPlace::from(0).into(),
+            None,
+            None,
+            &Cfg::default(),
+        )
+        .unwrap();
+        flows::reset_password_request(connections, &notify, "a@example.com").unwrap();
+
+        let sender = RecordingSender::default();
+        assert_eq!(2, flows::send_due_emails(connections, &sender).unwrap());
+        assert_eq!(vec![Email::from("a@example.com"); 2], *sender.0.borrow());
+        assert_eq!(
+            2,
+            connections
+                .shared()
+                .unwrap()
+                .count_outgoing_emails()
+                .unwrap()
+                .sent
+        );
     }
 }
@@ -0,0 +1,209 @@
+//! Rate limiting of public endpoints per client and route group.
+//!
+//! Clients are identified by their IP address or by the
+//! organization of a valid API token. IPv6 clients are
+//! identified by the /64 prefix of their address. Requests
+//! of unknown clients are not limited.
+
+use super::guards::{Auth, ClientIp};
+use crate::{
+    core::{
+        usecases,
+        util::rate_limit::{Quota, RateLimiter},
+    },
+    infrastructure::{
+        cfg::{Cfg, RateLimits},
+        db::sqlite,
+    },
+};
+use rocket::{
+    http::Status,
+    request::{self, FromRequest, Request},
+    response::{self, Responder, Response},
+    Outcome, State,
+};
+use std::{
+    marker::PhantomData,
+    net::{IpAddr, Ipv6Addr},
+    time::{Duration, Instant},
+};
+
+/// A group of endpoints that share the same quota.
+pub trait RouteGroup {
+    const NAME: &'static str;
+    fn quota(limits: &RateLimits) -> Option<Quota>;
+}
+
+pub mod groups {
+    use super::*;
+
+    pub struct Entries;
+
+    impl RouteGroup for Entries {
+        const NAME: &'static str = "entries";
+        fn quota(limits: &RateLimits) -> Option<Quota> {
+            limits.entries
+        }
+    }
+
+    pub struct Ratings;
+
+    impl RouteGroup for Ratings {
+        const NAME: &'static str = "ratings";
+        fn quota(limits: &RateLimits) -> Option<Quota> {
+            limits.ratings
+        }
+    }
+
+    pub struct Users;
+
+    impl RouteGroup for Users {
+        const NAME: &'static str = "users";
+        fn quota(limits: &RateLimits) -> Option<Quota> {
+            limits.users
+        }
+    }
+
+    pub struct Captcha;
+
+    impl RouteGroup for Captcha {
+        const NAME: &'static str = "captcha";
+        fn quota(limits: &RateLimits) -> Option<Quota> {
+            limits.captcha
+        }
+    }
+}
+
+/// The time to wait after a request has been rejected.
+struct RetryAfter(Option<u64>);
+
+/// Rejects the request with `429 Too Many Requests` if the
+/// client has exceeded the quota of the route group.
+#[derive(Debug)]
+pub struct RateLimit<G>(PhantomData<G>);
+
+impl<'a, 'r, G: RouteGroup> FromRequest<'a, 'r> for RateLimit<G> {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        let cfg = request.guard::<State<Cfg>>()?;
+        let limiter = request.guard::<State<RateLimiter>>()?;
+        let quota = match G::quota(&cfg.rate_limits) {
+            Some(quota) => quota,
+            None => return Outcome::Success(RateLimit(PhantomData)),
+        };
+        let org_quota = quota.scaled(cfg.rate_limits.organization_factor);
+        let ClientIp(ip) = request.guard::<ClientIp>()?;
+        let client = ip.map(client_key);
+        let now = Instant::now();
+        let check = |client: &str, quota: Quota| {
+            let key = format!("{} {}", G::NAME, client);
+            limiter.check(&key, quota, now).map_err(|wait| {
+                debug!("Rate limit of {} exceeded", key);
+                wait
+            })
+        };
+        let has_bearer_tokens = request
+            .guard::<Auth>()
+            .succeeded()
+            .map(|auth| !auth.bearer_tokens().is_empty())
+            .unwrap_or(false);
+        let result = if has_bearer_tokens {
+            // Limit the attempts to authorize with API tokens before
+            // hashing and looking them up in the database.
+            let attempt = match &client {
+                Some(client) => check(&format!("token-{}", client), org_quota),
+                None => Ok(()),
+            };
+            attempt.and_then(|()| match organization_of_api_token(request) {
+                Some(org_id) => check(&format!("org:{}", org_id), org_quota),
+                None => client
+                    .as_ref()
+                    .map_or(Ok(()), |client| check(client, quota)),
+            })
+        } else {
+            client
+                .as_ref()
+                .map_or(Ok(()), |client| check(client, quota))
+        };
+        match result {
+            Ok(()) => Outcome::Success(RateLimit(PhantomData)),
+            Err(wait) => {
+                request.local_cache(|| RetryAfter(Some(retry_after_secs(wait))));
+                Outcome::Failure((Status::TooManyRequests, ()))
+            }
+        }
+    }
+}
+
+/// Round up to full seconds
+fn retry_after_secs(wait: Duration) -> u64 {
+    wait.as_secs() + u64::from(wait.subsec_nanos() > 0)
+}
+
+/// IPv6 clients usually control a whole /64 network and
+/// could otherwise bypass the limits by switching addresses.
+fn client_key(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(ip) => format!("ip:{}", ip),
+        IpAddr::V6(ip) => match ip.segments() {
+            // IPv4-mapped addresses
+            [0, 0, 0, 0, 0, 0xffff, hi, lo] => {
+                format!("ip:{}.{}.{}.{}", hi >> 8, hi & 0xff, lo >> 8, lo & 0xff)
+            }
+            [a, b, c, d, ..] => {
+                let prefix = Ipv6Addr::new(a, b, c, d, 0, 0, 0, 0);
+                format!("ip:{}/64", prefix)
+            }
+        },
+    }
+}
+
+fn organization_of_api_token(request: &Request) -> Option<String> {
+    let auth = request.guard::<Auth>().succeeded()?;
+    let connections = request.guard::<sqlite::Connections>().succeeded()?;
+    let db = connections.shared().ok()?;
+    usecases::authorize_organization_by_possible_api_tokens(&*db, auth.bearer_tokens(), &[])
+        .ok()
+        .map(|(org, _)| org.id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identify_ipv6_clients_by_prefix() {
+        assert_eq!("ip:10.0.0.1", client_key("10.0.0.1".parse().unwrap()));
+        assert_eq!(
+            "ip:2001:db8:0:1::/64",
+            client_key("2001:db8:0:1:2:3:4:5".parse().unwrap())
+        );
+        assert_eq!(
+            client_key("2001:db8::1".parse().unwrap()),
+            client_key("2001:db8::ffff:1".parse().unwrap())
+        );
+        assert_eq!(
+            "ip:10.0.0.1",
+            client_key("::ffff:10.0.0.1".parse().unwrap())
+        );
+    }
+}
+
+pub struct TooManyRequests(Option<u64>);
+
+impl<'r> Responder<'r> for TooManyRequests {
+    fn respond_to(self, _: &Request) -> response::Result<'r> {
+        let mut response = Response::build();
+        response.status(Status::TooManyRequests);
+        if let Some(retry_after) = self.0 {
+            response.raw_header("Retry-After", retry_after.to_string());
+        }
+        response.ok()
+    }
+}
+
+#[catch(429)]
+pub fn too_many_requests(request: &Request) -> TooManyRequests {
+    TooManyRequests(request.local_cache(|| RetryAfter(None)).0)
+}
@@ -13,7 +13,7 @@ use rocket::{
 };
 
 pub mod prelude {
-    pub use super::DummyNotifyGW;
+    pub use super::{register_api_token, DummyNotifyGW};
     pub use crate::core::db::*;
     pub use rocket::{
         http::{ContentType, Cookie, Status},
@@ -67,6 +67,7 @@ pub fn register_user(pool: &sqlite::Connections, email: &str, pw: &str, confirme
         usecases::NewUser {
             email: email.to_string(),
             password: pw.to_string(),
+            language: None,
         },
     )
     .unwrap();
@@ -80,15 +81,31 @@ pub fn register_user(pool: &sqlite::Connections, email: &str, pw: &str, confirme
     }
 }
 
+/// Grants all permissions to an API token of an existing
+/// organization.
+pub fn register_api_token(pool: &sqlite::Connections, org_id: &str, secret: &str) {
+    let db = pool.exclusive().unwrap();
+    usecases::create_primary_api_token(&*db, &org_id.into(), secret).unwrap();
+}
+
 pub struct DummyNotifyGW;
 
 impl ofdb_core::gateways::notify::NotificationGateway for DummyNotifyGW {
-    fn place_added(&self, _: &[String], _: &Place, _: Vec<Category>) {}
-    fn place_updated(&self, _: &[String], _: &Place, _: Vec<Category>) {}
-    fn event_created(&self, _: &[String], _: &Event) {}
-    fn event_updated(&self, _: &[String], _: &Event) {}
+    fn place_added(&self, _: &[SubscriptionRecipient], _: &Place, _: Vec<Category>) {}
+    fn place_updated(&self, _: &[SubscriptionRecipient], _: &Place, _: Vec<Category>) {}
+    fn place_reviewed(&self, _: &[SubscriptionRecipient], _: &Place, _: ReviewStatus) {}
+    fn own_place_reviewed(&self, _: &[String], _: &Place, _: ReviewStatus, _: Option<&str>) {}
+    fn own_place_rated(&self, _: &[String], _: &Place, _: &Rating, _: &Comment) {}
+    fn own_place_clearance_pending(&self, _: &[String], _: &Place) {}
+    fn event_created(&self, _: &[SubscriptionRecipient], _: &Event) {}
+    fn event_updated(&self, _: &[SubscriptionRecipient], _: &Event) {}
+    fn subscription_digest(&self, _: &SubscriptionDigest) {}
+    fn pending_clearance_digest(&self, _: &PendingClearanceDigest) {}
     fn user_registered_kvm(&self, _: &User) {}
     fn user_registered_ofdb(&self, _: &User) {}
     fn user_registered(&self, _: &User, _: &str) {}
     fn user_reset_password_requested(&self, _: &EmailNonce) {}
+    fn user_login_locked(&self, _: &User, _: TimestampMs) {}
+    fn user_email_change_requested(&self, _: &EmailNonce) {}
+    fn user_email_changed(&self, _: &str, _: &str) {}
 }